@@ -12,7 +12,16 @@ use crate::{
         fail::Fail,
         limits,
         memory::DemiBuffer,
-        network::transport::NetworkTransport,
+        network::{
+            socket::{
+                operation::Shutdown,
+                option::{
+                    SocketOption,
+                    SocketOptionName,
+                },
+            },
+            transport::NetworkTransport,
+        },
         scheduler::{
             Yielder,
             YielderHandle,
@@ -39,7 +48,7 @@ use ::std::{
     io,
     mem::MaybeUninit,
     net::{
-        Shutdown,
+        self,
         SocketAddr,
     },
     ops::{
@@ -142,8 +151,9 @@ impl ActiveSocketData {
     /// write (i.e., to detect when connect finishes).
     pub fn poll_send(&mut self) {
         if let Some((addr, mut buf, mut yielder_handle)) = self.send_queue.try_pop() {
-            // A dummy request to detect when the socket has connected.
-            if buf.is_empty() {
+            // A dummy request to detect when the socket has connected. Empty buffers with a destination address are
+            // zero-length datagrams and must be sent.
+            if buf.is_empty() && addr.is_none() {
                 yielder_handle.wake_with(Ok(()));
                 return;
             }
@@ -631,13 +641,13 @@ impl NetworkTransport for SharedCatnapTransport {
                 // Set socket options.
                 if let Err(e) = socket.set_reuse_address(true) {
                     let cause: String = format!("cannot set REUSE_ADDRESS option: {:?}", e);
-                    socket.shutdown(Shutdown::Both)?;
+                    socket.shutdown(net::Shutdown::Both)?;
                     error!("new(): {}", cause);
                     return Err(Fail::new(get_libc_err(e), &cause));
                 }
                 if let Err(e) = socket.set_nonblocking(true) {
                     let cause: String = format!("cannot set NONBLOCKING option: {:?}", e);
-                    socket.shutdown(Shutdown::Both)?;
+                    socket.shutdown(net::Shutdown::Both)?;
                     error!("new(): {}", cause);
                     return Err(Fail::new(get_libc_err(e), &cause));
                 }
@@ -646,7 +656,7 @@ impl NetworkTransport for SharedCatnapTransport {
                 if typ == Type::STREAM {
                     if let Err(e) = socket.set_nodelay(true) {
                         let cause: String = format!("cannot set TCP_NODELAY option: {:?}", e);
-                        socket.shutdown(Shutdown::Both)?;
+                        socket.shutdown(net::Shutdown::Both)?;
                         error!("new(): {}", cause);
                         return Err(Fail::new(get_libc_err(e), &cause));
                     }
@@ -713,19 +723,19 @@ impl NetworkTransport for SharedCatnapTransport {
         // Set socket options.
         if let Err(e) = new_socket.set_reuse_address(true) {
             let cause: String = format!("cannot set REUSE_ADDRESS option: {:?}", e);
-            new_socket.shutdown(Shutdown::Both)?;
+            new_socket.shutdown(net::Shutdown::Both)?;
             error!("accept(): {}", cause);
             return Err(Fail::new(get_libc_err(e), &cause));
         }
         if let Err(e) = new_socket.set_nodelay(true) {
             let cause: String = format!("cannot set TCP_NODELAY option: {:?}", e);
-            new_socket.shutdown(Shutdown::Both)?;
+            new_socket.shutdown(net::Shutdown::Both)?;
             error!("accept(): {}", cause);
             return Err(Fail::new(get_libc_err(e), &cause));
         }
         if let Err(e) = new_socket.set_nonblocking(true) {
             let cause: String = format!("cannot set NONBLOCKING option: {:?}", e);
-            self.socket_from_sd(sd).shutdown(Shutdown::Both)?;
+            self.socket_from_sd(sd).shutdown(net::Shutdown::Both)?;
             error!("accept(): {}", cause);
             return Err(Fail::new(get_libc_err(e), &cause));
         }
@@ -770,7 +780,7 @@ impl NetworkTransport for SharedCatnapTransport {
         let data: &mut SharedSocketData = self.data_from_sd(sd);
         loop {
             // Close the socket.
-            match data.get_socket().shutdown(Shutdown::Both) {
+            match data.get_socket().shutdown(net::Shutdown::Both) {
                 Ok(()) => break,
                 Err(e) => {
                     let errno: i32 = get_libc_err(e);
@@ -831,7 +841,7 @@ impl NetworkTransport for SharedCatnapTransport {
     fn hard_close(&mut self, sd: &mut Self::SocketDescriptor) -> Result<(), Fail> {
         let data: &mut SharedSocketData = self.data_from_sd(sd);
         // Close the socket.
-        match data.get_socket().shutdown(Shutdown::Both) {
+        match data.get_socket().shutdown(net::Shutdown::Both) {
             Ok(()) => (),
             Err(e) => {
                 let errno: i32 = get_libc_err(e);
@@ -855,7 +865,74 @@ impl NetworkTransport for SharedCatnapTransport {
         Ok(())
     }
 
+    /// Shuts down the `how` directions of the connection on the underlying socket.
+    fn shutdown(&mut self, sd: &mut Self::SocketDescriptor, how: Shutdown) -> Result<(), Fail> {
+        let how: net::Shutdown = match how {
+            Shutdown::Read => net::Shutdown::Read,
+            Shutdown::Write => net::Shutdown::Write,
+            Shutdown::Both => net::Shutdown::Both,
+        };
+        if let Err(e) = self.socket_from_sd(sd).shutdown(how) {
+            let cause: String = format!("failed to shut down socket (how={:?}, error={:?})", how, e);
+            error!("shutdown(): {}", cause);
+            return Err(Fail::new(get_libc_err(e), &cause));
+        }
+        Ok(())
+    }
+
+    /// Stops polling epoll for events.
+    fn stop(&mut self) {
+        self.background_task
+            .wake_with(Err(Fail::new(libc::ECANCELED, "transport was stopped")));
+    }
+
     fn get_runtime(&self) -> &SharedDemiRuntime {
         &self.runtime
     }
+
+    /// Sets a socket option on the underlying socket. Options that have no equivalent in the kernel fail with
+    /// ENOPROTOOPT.
+    fn set_socket_option(&mut self, sd: &mut Self::SocketDescriptor, option: SocketOption) -> Result<(), Fail> {
+        let socket: &mut Socket = self.socket_from_sd(sd);
+        let result: io::Result<()> = match option {
+            SocketOption::NoDelay(no_delay) => socket.set_nodelay(no_delay),
+            SocketOption::KeepAliveEnabled(keepalive) => socket.set_keepalive(keepalive),
+            SocketOption::SendBufferSize(size) => socket.set_send_buffer_size(size),
+            SocketOption::RecvWindowClamp(clamp) => socket.set_recv_buffer_size(clamp as usize),
+            SocketOption::Dscp(dscp) => socket.set_tos((dscp as u32) << 2),
+            SocketOption::Broadcast(broadcast) => socket.set_broadcast(broadcast),
+            _ => {
+                let cause: String = format!("socket option not supported (option={:?})", option);
+                warn!("set_socket_option(): {}", cause);
+                return Err(Fail::new(libc::ENOPROTOOPT, &cause));
+            },
+        };
+        if let Err(e) = result {
+            let cause: String = format!("failed to set socket option (option={:?}, error={:?})", option, e);
+            error!("set_socket_option(): {}", cause);
+            return Err(Fail::new(get_libc_err(e), &cause));
+        }
+        Ok(())
+    }
+
+    /// Reads a socket option back from the underlying socket.
+    fn get_socket_option(&self, sd: &Self::SocketDescriptor, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        let socket: &Socket = self
+            .socket_table
+            .get(*sd)
+            .expect("should have been allocated")
+            .get_socket();
+        let result: io::Result<SocketOption> = match name {
+            SocketOptionName::NoDelay => socket.nodelay().map(SocketOption::NoDelay),
+            SocketOptionName::RecvWindowClamp => socket
+                .recv_buffer_size()
+                .map(|size| SocketOption::RecvWindowClamp(size.min(u32::MAX as usize) as u32)),
+            SocketOptionName::SendBufferSize => socket.send_buffer_size().map(SocketOption::SendBufferSize),
+        };
+        result.map_err(|e| {
+            let cause: String = format!("failed to get socket option (name={:?}, error={:?})", name, e);
+            error!("get_socket_option(): {}", cause);
+            Fail::new(get_libc_err(e), &cause)
+        })
+    }
 }
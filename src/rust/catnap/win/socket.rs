@@ -7,6 +7,7 @@
 
 use std::{
     fmt::Debug,
+    io,
     marker::PhantomPinned,
     mem::MaybeUninit,
     net::{
@@ -16,11 +17,18 @@ use std::{
         SocketAddrV4,
         SocketAddrV6,
     },
+    os::windows::io::{
+        AsSocket,
+        BorrowedSocket,
+        RawSocket,
+    },
     pin::Pin,
     rc::Rc,
     time::Duration,
 };
 
+use socket2::SockRef;
+
 use windows::{
     core::PSTR,
     Win32::{
@@ -91,6 +99,10 @@ use crate::{
     runtime::{
         fail::Fail,
         memory::DemiBuffer,
+        network::socket::option::{
+            SocketOption,
+            SocketOptionName,
+        },
     },
 };
 
@@ -225,6 +237,38 @@ impl Socket {
         Ok(())
     }
 
+    /// Set a socket option on self. Options that have no equivalent in winsock fail with ENOPROTOOPT.
+    pub fn set_socket_option(&self, option: SocketOption) -> Result<(), Fail> {
+        let socket: SockRef = SockRef::from(self);
+        let result: io::Result<()> = match option {
+            SocketOption::NoDelay(no_delay) => socket.set_nodelay(no_delay),
+            SocketOption::KeepAliveEnabled(keepalive) => socket.set_keepalive(keepalive),
+            SocketOption::SendBufferSize(size) => socket.set_send_buffer_size(size),
+            SocketOption::RecvWindowClamp(clamp) => socket.set_recv_buffer_size(clamp as usize),
+            SocketOption::Dscp(dscp) => socket.set_tos((dscp as u32) << 2),
+            SocketOption::Broadcast(broadcast) => socket.set_broadcast(broadcast),
+            _ => {
+                let cause: String = format!("socket option not supported (option={:?})", option);
+                warn!("set_socket_option(): {}", cause);
+                return Err(Fail::new(libc::ENOPROTOOPT, &cause));
+            },
+        };
+        result.map_err(|_| expect_last_wsa_error())
+    }
+
+    /// Read a socket option back from self.
+    pub fn get_socket_option(&self, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        let socket: SockRef = SockRef::from(self);
+        let result: io::Result<SocketOption> = match name {
+            SocketOptionName::NoDelay => socket.nodelay().map(SocketOption::NoDelay),
+            SocketOptionName::RecvWindowClamp => socket
+                .recv_buffer_size()
+                .map(|size| SocketOption::RecvWindowClamp(size.min(u32::MAX as usize) as u32)),
+            SocketOptionName::SendBufferSize => socket.send_buffer_size().map(SocketOption::SendBufferSize),
+        };
+        result.map_err(|_| expect_last_wsa_error())
+    }
+
     /// Make a new socket like some template socket.
     pub fn new_like(template: &Socket) -> Result<Socket, Fail> {
         // Safety: SO_PROTOCOL_INFOW fills out a WSAPROTOCOL_INFOW structure.
@@ -587,6 +631,13 @@ impl Drop for Socket {
     }
 }
 
+impl AsSocket for Socket {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        // Safety: the socket is not closed before self is dropped, so it outlives the borrow.
+        unsafe { BorrowedSocket::borrow_raw(self.s.0 as RawSocket) }
+    }
+}
+
 impl Debug for Socket {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Socket").field("s", &self.s).finish()
@@ -45,7 +45,13 @@ use crate::{
     runtime::{
         fail::Fail,
         memory::DemiBuffer,
-        network::transport::NetworkTransport,
+        network::{
+            socket::option::{
+                SocketOption,
+                SocketOptionName,
+            },
+            transport::NetworkTransport,
+        },
         scheduler::Yielder,
         DemiRuntime,
         SharedDemiRuntime,
@@ -200,6 +206,16 @@ impl NetworkTransport for SharedCatnapTransport {
         socket.listen(backlog)
     }
 
+    /// Set a socket option on the specified socket.
+    fn set_socket_option(&mut self, socket: &mut Self::SocketDescriptor, option: SocketOption) -> Result<(), Fail> {
+        socket.set_socket_option(option)
+    }
+
+    /// Read a socket option back from the specified socket.
+    fn get_socket_option(&self, socket: &Self::SocketDescriptor, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        socket.get_socket_option(name)
+    }
+
     /// Accept a connection on the specified socket. The coroutine will not finish until a connection is successfully
     /// accepted or `yielder` is cancelled.
     async fn accept(
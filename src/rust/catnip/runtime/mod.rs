@@ -118,6 +118,7 @@ pub struct DPDKRuntime {
     arp_config: ArpConfig,
     tcp_config: TcpConfig,
     udp_config: UdpConfig,
+    mtu: u16,
 }
 
 #[derive(Clone)]
@@ -130,10 +131,11 @@ pub struct SharedDPDKRuntime(SharedObject<DPDKRuntime>);
 /// Associate Functions for DPDK Runtime
 impl SharedDPDKRuntime {
     pub fn new(config: Config) -> Result<Self, Fail> {
+        let mtu: u16 = config.mtu()?;
         let (mm, port_id, link_addr) = Self::initialize_dpdk(
             &config.eal_init_args(),
             config.use_jumbo_frames(),
-            config.mtu()?,
+            mtu,
             config.tcp_checksum_offload(),
             config.udp_checksum_offload(),
         )
@@ -141,6 +143,7 @@ impl SharedDPDKRuntime {
 
         let arp_config = ArpConfig::new(
             Some(Duration::from_secs(15)),
+            None,
             Some(Duration::from_secs(20)),
             Some(5),
             Some(config.arp_table()),
@@ -168,6 +171,7 @@ impl SharedDPDKRuntime {
             arp_config,
             tcp_config,
             udp_config,
+            mtu,
         })))
     }
 
@@ -552,4 +556,9 @@ impl NetworkRuntime for SharedDPDKRuntime {
     fn get_tcp_config(&self) -> TcpConfig {
         self.tcp_config.clone()
     }
+
+    /// Gets the MTU that the port was configured with.
+    fn get_mtu(&self) -> u16 {
+        self.mtu
+    }
 }
@@ -61,6 +61,7 @@ impl LinuxRuntime {
     pub fn new(config: Config) -> Self {
         let arp_config: ArpConfig = ArpConfig::new(
             Some(Duration::from_secs(600)),
+            None,
             Some(Duration::from_secs(1)),
             Some(2),
             Some(HashMap::<Ipv4Addr, MacAddress>::default()),
@@ -93,11 +93,23 @@ impl<T> AsyncQueue<T> {
         }
     }
 
+    /// Wake up all pops that are waiting for something in the queue, failing them with `e`.
+    pub fn fail_waiters(&mut self, e: Fail) {
+        while let Some(mut yielder_handle) = self.waiters.pop() {
+            yielder_handle.wake_with(Err(e.clone()));
+        }
+    }
+
     /// Try to get the head of the queue.
     pub fn try_pop(&mut self) -> Option<T> {
         self.queue.pop_front()
     }
 
+    /// Get a mutable reference to the tail of the queue.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.queue.back_mut()
+    }
+
     /// Get the length of the queue.
     pub fn len(&self) -> usize {
         self.queue.len()
@@ -11,7 +11,10 @@ use ::rand::{
     SeedableRng,
 };
 use ::std::{
-    collections::HashMap,
+    collections::{
+        hash_map::Entry,
+        HashMap,
+    },
     convert::From,
     hash::Hash,
 };
@@ -56,6 +59,8 @@ pub struct IdMap<E: Eq + Hash + From<u64> + Into<u64> + Copy, I: From<u64> + Int
     ids: HashMap<E, I>,
     /// Small random number generator for external ids.
     rng: SmallRng,
+    /// Are ids drawn from the random number generator rather than from a Fibonacci sequence?
+    randomize: bool,
     /// For non-random id generation, we keep the last 2 id numbers for a Fibonacci calculation.
     last_id: u64,
     current_id: u64,
@@ -69,6 +74,16 @@ pub struct IdMap<E: Eq + Hash + From<u64> + Into<u64> + Copy, I: From<u64> + Int
 //======================================================================================================================
 
 impl<E: Eq + Hash + From<u64> + Into<u64> + Copy, I: From<u64> + Into<u64> + Copy> IdMap<E, I> {
+    /// Creates an empty map that draws new ids from `rng`, in release builds too, so that the same generator state
+    /// yields the same sequence of ids.
+    pub fn with_rng(rng: SmallRng) -> Self {
+        Self {
+            rng,
+            randomize: true,
+            ..Default::default()
+        }
+    }
+
     /// Retrieve a mapping for this external id if it exists. If we are using a direct mapping, this operation always
     /// succeeds, so DO NOT use this function to check for the existance of a particular key. We expect the user to use
     /// nother data structure for validity.
@@ -119,7 +134,7 @@ impl<E: Eq + Hash + From<u64> + Into<u64> + Copy, I: From<u64> + Into<u64> + Cop
             return E::from(internal_id.into());
         }
 
-        if RANDOMIZE {
+        if self.randomize {
             // Otherwise, allocate a new external id.
             for _ in 0..MAX_RETRIES_ID_ALLOC {
                 let external_id: E = E::from(self.rng.next_u64());
@@ -130,21 +145,44 @@ impl<E: Eq + Hash + From<u64> + Into<u64> + Copy, I: From<u64> + Into<u64> + Cop
             }
             panic!("Could not find a valid task id");
         } else {
-            // Use a Fibonacci sequence.
-            let id: u64 = self.current_id;
-            // Roll around.
-            self.current_id = if self.current_id < u64::MAX - self.last_id {
-                self.current_id + self.last_id
-            } else {
-                self.last_id - (u64::MAX - self.current_id)
-            };
-            self.last_id = id;
-            let external_id: E = E::from(id);
-            if self.ids.insert(external_id, internal_id).is_some() {
-                panic!("Should not have a previous task with this id");
+            // Use a Fibonacci sequence. Skip ids that are already in use, which happens when the map was created from
+            // exported mappings.
+            for _ in 0..MAX_RETRIES_ID_ALLOC {
+                let id: u64 = self.current_id;
+                // Roll around.
+                self.current_id = if self.current_id < u64::MAX - self.last_id {
+                    self.current_id + self.last_id
+                } else {
+                    self.last_id - (u64::MAX - self.current_id)
+                };
+                self.last_id = id;
+                let external_id: E = E::from(id);
+                if let Entry::Vacant(entry) = self.ids.entry(external_id) {
+                    entry.insert(internal_id);
+                    return external_id;
+                }
             }
-            external_id
+            panic!("Could not find a valid task id");
+        }
+    }
+
+    /// Returns all mappings between external and internal ids, in no particular order. If we are using a direct
+    /// mapping, there is nothing to export.
+    pub fn export(&self) -> Vec<(E, I)> {
+        self.ids
+            .iter()
+            .map(|(external_id, internal_id)| (*external_id, *internal_id))
+            .collect()
+    }
+
+    /// Creates a map that holds the given mappings between external and internal ids, such as the ones returned by
+    /// [Self::export]. If we are using a direct mapping, the mappings are implied and are not stored.
+    pub fn from_mappings(mappings: Vec<(E, I)>) -> Self {
+        let mut id_map: Self = Self::default();
+        for (external_id, internal_id) in mappings {
+            id_map.insert(external_id, internal_id);
         }
+        id_map
     }
 
     #[cfg(test)]
@@ -168,6 +206,7 @@ impl<E: Eq + Hash + From<u64> + Into<u64> + Copy, I: From<u64> + Into<u64> + Cop
             // Don't need to pre-allocate, the overhead is a 6ns on the scheduler insert benchmark.
             ids: HashMap::<E, I>::with_capacity(DEFAULT_SIZE),
             rng: SmallRng::seed_from_u64(SCHEDULER_SEED),
+            randomize: RANDOMIZE,
             last_id: 1,
             current_id: 2,
             #[cfg(test)]
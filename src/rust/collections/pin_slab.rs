@@ -107,6 +107,18 @@ impl<T> PinSlab<T> {
         self.internal_get(key)
     }
 
+    /// Number of allocated entries that do not hold a value. Entries are never freed, so a number of free slots that
+    /// stays low while the slab keeps growing points to values that are never removed.
+    pub fn free_slots(&self) -> usize {
+        let capacity: usize = slot_sizes().take(self.slots.len()).sum();
+        capacity - self.len
+    }
+
+    /// Number of entries that hold a value.
+    pub fn occupied_slots(&self) -> usize {
+        self.len
+    }
+
     /// Insert a value into the pin slab.
     pub fn insert(&mut self, val: T) -> Option<usize> {
         let key: usize = self.next;
@@ -410,6 +422,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn free_and_occupied_slots() -> Result<()> {
+        let mut slab: super::PinSlab<i32> = super::PinSlab::new();
+        crate::ensure_eq!(slab.free_slots(), 0);
+        crate::ensure_eq!(slab.occupied_slots(), 0);
+
+        // The first insertion allocates the first slot.
+        let first: usize = match slab.insert(0) {
+            Some(key) => key,
+            None => anyhow::bail!("insert() failed"),
+        };
+        crate::ensure_eq!(slab.free_slots(), super::FIRST_SLOT_SIZE - 1);
+        crate::ensure_eq!(slab.occupied_slots(), 1);
+
+        // Filling the first slot and spilling into the second one doubles the capacity.
+        let mut keys: Vec<usize> = Vec::new();
+        for i in 1..=super::FIRST_SLOT_SIZE {
+            match slab.insert(i as i32) {
+                Some(key) => keys.push(key),
+                None => anyhow::bail!("insert() failed"),
+            };
+        }
+        crate::ensure_eq!(slab.occupied_slots(), super::FIRST_SLOT_SIZE + 1);
+        crate::ensure_eq!(slab.free_slots(), super::FIRST_SLOT_SIZE - 1);
+
+        // Removed entries become free again, but the capacity does not shrink.
+        crate::ensure_eq!(slab.remove_unpin(first), Some(0));
+        crate::ensure_eq!(slab.remove_unpin(first), None);
+        for key in keys.iter().copied() {
+            crate::ensure_eq!(slab.remove_unpin(key).is_some(), true);
+        }
+        crate::ensure_eq!(slab.occupied_slots(), 0);
+        crate::ensure_eq!(slab.free_slots(), 2 * super::FIRST_SLOT_SIZE);
+
+        // Freed entries are reused before the slab grows.
+        for i in 0..(2 * super::FIRST_SLOT_SIZE) {
+            crate::ensure_eq!(slab.insert(i as i32).is_some(), true);
+        }
+        crate::ensure_eq!(slab.occupied_slots(), 2 * super::FIRST_SLOT_SIZE);
+        crate::ensure_eq!(slab.free_slots(), 0);
+
+        Ok(())
+    }
+
     #[test]
     fn remove_unpin() -> Result<()> {
         let mut slab: super::PinSlab<i32> = super::PinSlab::new();
@@ -5,24 +5,31 @@
 // Imports
 //======================================================================================================================
 
-use crate::runtime::{
-    fail::Fail,
-    types::{
-        demi_qresult_t,
-        demi_sgarray_t,
+use crate::{
+    demikernel::libos::metrics::Metrics,
+    runtime::{
+        fail::Fail,
+        types::{
+            demi_qresult_t,
+            demi_sgarray_t,
+        },
+        wait::{
+            WaitInterrupter,
+            WaitOutcome,
+        },
+        QDesc,
+        QToken,
     },
-    QDesc,
-    QToken,
-};
-use ::std::time::{
-    Duration,
-    Instant,
 };
+use ::std::time::Duration;
 
 #[cfg(feature = "catmem-libos")]
 use crate::{
     catmem::SharedCatmemLibOS,
-    runtime::memory::MemoryRuntime,
+    runtime::memory::{
+        DemiBuffer,
+        MemoryRuntime,
+    },
     runtime::SharedDemiRuntime,
 };
 
@@ -109,31 +116,77 @@ impl MemoryLibOS {
         Ok(qr)
     }
 
-    /// Waits for any of the given pending I/O operations to complete or a timeout to expire.
+    /// Waits for any of the given pending I/O operations to complete, a timeout to expire, or an interrupt.
+    #[allow(unreachable_patterns, unused_variables)]
     pub fn wait_any(&mut self, qts: &[QToken], timeout: Option<Duration>) -> Result<(usize, demi_qresult_t), Fail> {
         trace!("wait_any(): qts={:?}, timeout={:?}", qts, timeout);
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.wait_any(qts, timeout),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
 
-        // Get the wait start time, but only if we have a timeout.  We don't care when we started if we wait forever.
-        let start: Option<Instant> = if timeout.is_none() { None } else { Some(Instant::now()) };
+    /// Waits for any of the given pending I/O operations to complete or its own timeout to expire.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn wait_any_timeout(&mut self, qts: &[QToken], timeouts: &[Duration]) -> Result<(usize, demi_qresult_t), Fail> {
+        trace!("wait_any_timeout(): qts={:?}, timeouts={:?}", qts, timeouts);
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.wait_any_timeout(qts, timeouts),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
+
+    /// Spins on a pending I/O operation for a while before waiting for it.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn wait_spin(&mut self, qt: QToken, spin: Duration, timeout: Option<Duration>) -> Result<demi_qresult_t, Fail> {
+        trace!("wait_spin(): qt={:?}, spin={:?}, timeout={:?}", qt, spin, timeout);
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.wait_spin(qt, spin, timeout),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
 
-        loop {
-            // Poll first, so as to give pending operations a chance to complete.
-            self.poll();
+    /// Waits for a pending I/O operation and reports why the wait returned.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn wait_ex(&mut self, qt: QToken, timeout: Option<Duration>) -> Result<WaitOutcome, Fail> {
+        trace!("wait_ex(): qt={:?}, timeout={:?}", qt, timeout);
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.wait_ex(qt, timeout),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
 
-            // Search for any operation that has completed.
-            for (i, &qt) in qts.iter().enumerate() {
-                if self.has_completed(qt)? {
-                    return Ok((i, self.get_result(qt)?));
-                }
-            }
+    /// Attaches an opaque value to a queue.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn set_user_data(&mut self, qd: QDesc, user_data: u64) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.set_user_data(qd, user_data),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
 
-            // If we have a timeout, check for expiration.
-            if timeout.is_some()
-                && Instant::now().duration_since(start.expect("start should be set if timeout is"))
-                    > timeout.expect("timeout should still be set")
-            {
-                return Err(Fail::new(libc::ETIMEDOUT, "timer expired"));
-            }
+    /// Returns the opaque value attached to a queue, if any.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn get_user_data(&self, qd: QDesc) -> Option<u64> {
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.get_user_data(qd),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
+
+    /// Returns a handle that interrupts waits.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn interrupter(&self) -> WaitInterrupter {
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.get_interrupter(),
+            _ => unreachable!("unknown memory libos"),
         }
     }
 
@@ -157,6 +210,26 @@ impl MemoryLibOS {
         }
     }
 
+    /// Makes poll write completed operations to a ring from which they are harvested.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn enable_completion_ring(&mut self, capacity: usize) {
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.enable_completion_ring(capacity),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
+
+    /// Copies completed operations out of the completion ring.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn harvest(&mut self, out: &mut [demi_qresult_t]) -> usize {
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => runtime.harvest(out),
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
+
     #[allow(unreachable_patterns, unused_variables)]
     pub fn get_result(&mut self, qt: QToken) -> Result<demi_qresult_t, Fail> {
         match self {
@@ -166,6 +239,27 @@ impl MemoryLibOS {
         }
     }
 
+    /// Takes a snapshot of the counters of the scheduler and the small-buffer pool, optionally resetting the resettable
+    /// ones. Memory LibOSes have no network stack, so the network counters are always zero.
+    #[allow(unreachable_patterns, unused_variables)]
+    pub fn metrics_snapshot(&mut self, reset: bool) -> Metrics {
+        match self {
+            #[cfg(feature = "catmem-libos")]
+            MemoryLibOS::Catmem { runtime, libos: _ } => {
+                let metrics: Metrics = Metrics {
+                    scheduler: runtime.slab_stats(),
+                    buffer_pool: DemiBuffer::small_buffer_pool_stats(),
+                    ..Default::default()
+                };
+                if reset {
+                    DemiBuffer::reset_small_buffer_pool_stats();
+                }
+                metrics
+            },
+            _ => unreachable!("unknown memory libos"),
+        }
+    }
+
     /// Waits for any operation in an I/O queue.
     #[allow(unreachable_patterns, unused_variables)]
     pub fn poll(&mut self) {
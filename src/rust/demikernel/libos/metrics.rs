@@ -0,0 +1,39 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::{
+    memory::BufferPoolStats,
+    network::types::DropStats,
+    scheduler::SlabStats,
+};
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Snapshot of the counters of a LibOS, taken without polling in between, so that all values describe the same
+/// instant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// Occupancy of the scheduler slabs. This is a gauge and is never reset.
+    pub scheduler: SlabStats,
+    /// Hit and miss counters of the small-buffer pool of the calling thread. Resettable.
+    pub buffer_pool: BufferPoolStats,
+    /// Incoming packets dropped by the network stack, by reason. Resettable.
+    pub drops: DropStats,
+    /// Aggregates over the queues that are currently open.
+    pub connections: ConnectionMetrics,
+}
+
+/// Aggregates over the open queues of a LibOS. These describe the current connections and are never reset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionMetrics {
+    /// Number of open queues.
+    pub open_queues: usize,
+    /// Number of segments retransmitted on the open queues, summed over all of them.
+    pub retransmits: u64,
+}
@@ -2,6 +2,7 @@
 // Licensed under the MIT license.
 
 pub mod memory;
+pub mod metrics;
 pub mod name;
 pub mod network;
 
@@ -11,8 +12,13 @@ pub mod network;
 
 use self::{
     memory::MemoryLibOS,
+    metrics::Metrics,
     name::LibOSName,
-    network::NetworkLibOSWrapper,
+    network::{
+        accept_stream::AcceptStream,
+        queue_info::QueueInfo,
+        NetworkLibOSWrapper,
+    },
 };
 #[cfg(feature = "catnip-libos")]
 use crate::catnip::runtime::SharedDPDKRuntime;
@@ -20,6 +26,8 @@ use crate::catnip::runtime::SharedDPDKRuntime;
 use crate::catpowder::runtime::LinuxRuntime;
 #[cfg(any(feature = "catpowder-libos", feature = "catnip-libos"))]
 use crate::inetstack::SharedInetStack;
+#[cfg(feature = "state-history")]
+use crate::runtime::network::socket::state::SocketTransition;
 #[cfg(feature = "profiler")]
 use crate::timer;
 
@@ -29,22 +37,60 @@ use crate::{
         libos::network::libos::SharedNetworkLibOS,
     },
     runtime::{
+        cancellation::CancellationToken,
         fail::Fail,
         limits,
         logging,
+        memory::{
+            install_buffer_allocator,
+            BufferAllocator,
+            BufferPoolStats,
+            DemiBuffer,
+            FragmentationReport,
+            PoolId,
+        },
+        network::{
+            socket::{
+                operation::Shutdown,
+                option::{
+                    SocketOption,
+                    SocketOptionName,
+                },
+            },
+            types::{
+                ConnectionSnapshot,
+                DropStats,
+                MacAddress,
+                OpCounts,
+                TcpSeqState,
+                TcpState,
+            },
+        },
         types::{
             demi_qresult_t,
             demi_sgarray_t,
         },
+        wait::{
+            WaitInterrupter,
+            WaitOutcome,
+        },
         QDesc,
         QToken,
         SharedDemiRuntime,
     },
 };
 use ::std::{
+    collections::HashMap,
     env,
-    net::SocketAddr,
-    time::Duration,
+    net::{
+        Ipv4Addr,
+        SocketAddr,
+        SocketAddrV4,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 #[cfg(feature = "catloop-libos")]
@@ -145,6 +191,13 @@ impl LibOS {
         Ok(libos)
     }
 
+    /// Instantiates a new LibOS whose heap-allocated buffers come from `allocator`, e.g. to back them with huge pages.
+    /// The allocator serves the whole process, so this fails with EBUSY if any buffer was allocated before.
+    pub fn new_with_buffer_allocator(libos_name: LibOSName, allocator: Box<dyn BufferAllocator>) -> Result<Self, Fail> {
+        install_buffer_allocator(allocator)?;
+        Self::new(libos_name)
+    }
+
     /// Creates a new memory queue and connect to consumer end.
     pub fn create_pipe(&mut self, name: &str) -> Result<QDesc, Fail> {
         let result: Result<QDesc, Fail> = {
@@ -252,7 +305,72 @@ impl LibOS {
         result
     }
 
-    /// Initiates a connection with a remote TCP socket.
+    /// Accepts up to `max` incoming connections on a TCP socket at once. The operation completes as soon as at least one
+    /// connection is accepted, along with every other connection that is ready by then, which saves the per-accept
+    /// overhead under connection bursts. Wait for it with [Self::wait_accept_batch] to get all the connections; plain
+    /// waits fail on it with `EINVAL`, and it never shows up in the completion ring.
+    pub fn accept_batch(&mut self, sockqd: QDesc, max: usize) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::accept_batch");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.accept_batch(sockqd, max),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "accept_batch() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Starts a loop that keeps accepting connections on a TCP socket. The returned stream yields the queue
+    /// descriptors of the new connections in the order in which they were accepted. Closing the socket stops the loop,
+    /// whose token should then be waited for.
+    pub fn spawn_accept_loop(&mut self, sockqd: QDesc) -> Result<AcceptStream, Fail> {
+        let result: Result<AcceptStream, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::spawn_accept_loop");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.spawn_accept_loop(sockqd),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "spawn_accept_loop() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Accepts an incoming connection on a TCP socket and pushes `initial` to it. The operation completes with the
+    /// new queue descriptor once the push has been enqueued.
+    pub fn accept_and_push(&mut self, sockqd: QDesc, initial: DemiBuffer) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::accept_and_push");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.accept_and_push(sockqd, initial),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "accept_and_push() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Initiates a connection with a remote TCP socket. The returned token completes exactly when the connection is
+    /// established, that is, once the SYN+ACK of the remote was received and acknowledged. Sending the SYN alone never
+    /// completes it, and data may be pushed as soon as it completes.
     pub fn connect(&mut self, sockqd: QDesc, remote: SocketAddr) -> Result<QToken, Fail> {
         let result: Result<QToken, Fail> = {
             #[cfg(feature = "profiler")]
@@ -268,6 +386,87 @@ impl LibOS {
         result
     }
 
+    /// Initiates a connection with a remote TCP socket, like [Self::connect], but completes with `ETIMEDOUT` if the
+    /// connection is not established by `deadline`, as told by the clock of the stack.
+    pub fn connect_until(&mut self, qd: QDesc, remote: SocketAddr, deadline: Instant) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::connect_until");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.connect_until(qd, remote, deadline),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "connect_until() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Initiates a connection with a remote TCP socket and sends `initial` data with it. With TCP Fast Open, the data
+    /// goes in the SYN if a cookie from the remote is cached, and right after the handshake otherwise.
+    pub fn connect_with_data(
+        &mut self,
+        sockqd: QDesc,
+        remote: SocketAddr,
+        initial: DemiBuffer,
+    ) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::connect_with_data");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.connect_with_data(sockqd, remote, initial),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "connect_with_data() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Initiates connections to each of `remotes` in turn, starting one every `stagger` or as soon as all the ones in
+    /// flight have failed. The returned token completes when the first of them is established, and the others are
+    /// aborted. It fails only if all of them fail.
+    pub fn connect_any(&mut self, sockqd: QDesc, remotes: Vec<SocketAddr>, stagger: Duration) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::connect_any");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.connect_any(sockqd, remotes, stagger),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "connect_any() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Shuts down the `how` directions of the connection referred to by `qd`, like POSIX `shutdown()`. After the write
+    /// side was shut down, pushes fail with EPIPE while pops still drain the data that the peer sent. Fails with ENOTCONN
+    /// on sockets that are not connected. The queue must still be closed afterwards.
+    pub fn shutdown(&mut self, qd: QDesc, how: Shutdown) -> Result<(), Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::shutdown");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.shutdown(qd, how),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "shutdown() is not supported on memory liboses",
+            )),
+        }
+    }
+
     /// Closes an I/O queue.
     /// async_close() + wait() achieves the same effect as synchronous close.
     pub fn close(&mut self, qd: QDesc) -> Result<(), Fail> {
@@ -328,6 +527,46 @@ impl LibOS {
         result
     }
 
+    /// Pushes a scatter-gather array to an I/O queue, like [Self::push], but completes with `ETIMEDOUT` if the push has
+    /// not finished by `deadline`, as told by the clock of the stack.
+    pub fn push_until(&mut self, qd: QDesc, sga: &demi_sgarray_t, deadline: Instant) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::push_until");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.push_until(qd, sga, deadline),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "push_until() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Pushes a buffer to several I/O queues and returns a token per queue. The queues share the data of the buffer
+    /// instead of each getting its own copy of it.
+    pub fn push_to_many(&mut self, qds: &[QDesc], buf: DemiBuffer) -> Result<Vec<QToken>, Fail> {
+        let result: Result<Vec<QToken>, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::push_to_many");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.push_to_many(qds, buf),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "push_to_many() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
     /// Pushes a scatter-gather array to a UDP socket.
     pub fn pushto(&mut self, qd: QDesc, sga: &demi_sgarray_t, to: SocketAddr) -> Result<QToken, Fail> {
         let result: Result<QToken, Fail> = {
@@ -371,6 +610,144 @@ impl LibOS {
         result
     }
 
+    /// Pops data from an I/O queue into `buf`, so that applications that manage their own buffers do not pay for an
+    /// allocation on every pop. At most `size` bytes are popped, or as many as `buf` holds if it is smaller. The
+    /// operation completes with `buf`, trimmed to the bytes received, and fails with `EINVAL` if `buf` is empty.
+    pub fn pop_into(&mut self, qd: QDesc, buf: DemiBuffer, size: Option<usize>) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::pop_into");
+
+            // Check if this is a fixed-size pop.
+            if let Some(size) = size {
+                // Check if size is valid.
+                if !((size > 0) && (size <= limits::POP_SIZE_MAX)) {
+                    let cause: String = format!("invalid pop size (size={:?})", size);
+                    error!("pop_into(): {:?}", &cause);
+                    return Err(Fail::new(libc::EINVAL, &cause));
+                }
+            }
+
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.pop_into(qd, buf, size),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "pop_into() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Probes whether another host on the link uses `ipv4_addr`, so that an application can detect conflicts before
+    /// using a self-assigned address (RFC 5227). The probe is issued on the I/O queue `qd`, and closing the queue
+    /// cancels it. The operation completes successfully once the probe window passes with no reply, or fails with
+    /// `EADDRINUSE` if another host claims the address.
+    pub fn probe_address(&mut self, qd: QDesc, ipv4_addr: Ipv4Addr) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::probe_address");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.probe_address(qd, ipv4_addr),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "probe_address() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Pops data from an I/O queue, like [Self::pop], but completes with `ETIMEDOUT` if the pop has not finished by
+    /// `deadline`, as told by the clock of the stack.
+    pub fn pop_until(&mut self, qd: QDesc, size: Option<usize>, deadline: Instant) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::pop_until");
+
+            // Check if this is a fixed-size pop.
+            if let Some(size) = size {
+                // Check if size is valid.
+                if !((size > 0) && (size <= limits::POP_SIZE_MAX)) {
+                    let cause: String = format!("invalid pop size (size={:?})", size);
+                    error!("pop_until(): {:?}", &cause);
+                    return Err(Fail::new(libc::EINVAL, &cause));
+                }
+            }
+
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.pop_until(qd, size, deadline),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "pop_until() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Pops data from an I/O queue, like [Self::pop], but completes with `ECANCELED` if `token` is cancelled before
+    /// the pop finishes. Several operations may share the same token.
+    pub fn pop_cancellable(
+        &mut self,
+        qd: QDesc,
+        size: Option<usize>,
+        token: CancellationToken,
+    ) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::pop_cancellable");
+
+            // Check if this is a fixed-size pop.
+            if let Some(size) = size {
+                // Check if size is valid.
+                if !((size > 0) && (size <= limits::POP_SIZE_MAX)) {
+                    let cause: String = format!("invalid pop size (size={:?})", size);
+                    error!("pop_cancellable(): {:?}", &cause);
+                    return Err(Fail::new(libc::EINVAL, &cause));
+                }
+            }
+
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.pop_cancellable(qd, size, token),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "pop_cancellable() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Pops all data currently buffered on an I/O queue in a single buffer. If no data is buffered, this behaves like a
+    /// regular pop and waits for data to arrive.
+    pub fn pop_all(&mut self, qd: QDesc) -> Result<QToken, Fail> {
+        let result: Result<QToken, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::pop_all");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.pop_all(qd),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(libc::ENOTSUP, "pop_all() is not supported on memory liboses")),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
     /// Waits for a pending I/O operation to complete or a timeout to expire.
     /// This is just a single-token convenience wrapper for wait_any().
     pub fn wait(&mut self, qt: QToken, timeout: Option<Duration>) -> Result<demi_qresult_t, Fail> {
@@ -392,14 +769,753 @@ impl LibOS {
         }
     }
 
-    /// Allocates a scatter-gather array.
-    pub fn sgaalloc(&mut self, size: usize) -> Result<demi_sgarray_t, Fail> {
-        let result: Result<demi_sgarray_t, Fail> = {
-            #[cfg(feature = "profiler")]
-            timer!("demikernel::sgaalloc");
-            match self {
-                LibOS::NetworkLibOS(libos) => libos.sgaalloc(size),
-                LibOS::MemoryLibOS(libos) => libos.sgaalloc(size),
+    /// Waits for any of the given pending I/O operations to complete or its own timeout to expire, whichever happens
+    /// first, and returns the offset in `qts` of that operation. `timeouts` holds a timeout for each operation in `qts`.
+    /// An operation whose timeout expired is reported with a failed result carrying `ETIMEDOUT`, and keeps pending. A
+    /// zero timeout gives the operation a single poll before it expires, like in [Self::wait].
+    pub fn wait_any_timeout(&mut self, qts: &[QToken], timeouts: &[Duration]) -> Result<(usize, demi_qresult_t), Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::wait_any_timeout");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.wait_any_timeout(qts, timeouts),
+            LibOS::MemoryLibOS(libos) => libos.wait_any_timeout(qts, timeouts),
+        }
+    }
+
+    /// Waits for a pending I/O operation like [Self::wait], but first busy-polls the scheduler for up to `spin`. This
+    /// cuts the latency of operations that complete almost immediately. `timeout` counts from the start of the spin, and
+    /// the spin never outlasts it.
+    pub fn wait_spin(&mut self, qt: QToken, spin: Duration, timeout: Option<Duration>) -> Result<demi_qresult_t, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::wait_spin");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.wait_spin(qt, spin, timeout),
+            LibOS::MemoryLibOS(libos) => libos.wait_spin(qt, spin, timeout),
+        }
+    }
+
+    /// Waits for an accept started with [Self::accept_batch] to complete or a timeout to expire, and returns the queue
+    /// descriptors and remote addresses of the connections that it accepted, in the order in which they were accepted.
+    pub fn wait_accept_batch(
+        &mut self,
+        qt: QToken,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(QDesc, SocketAddrV4)>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::wait_accept_batch");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.wait_accept_batch(qt, timeout),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "wait_accept_batch() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Waits for a pending I/O operation like [Self::wait], but reports why the wait returned: the operation
+    /// completed, the timeout expired, the wait was interrupted through [Self::interrupter], or the operation was
+    /// cancelled.
+    pub fn wait_ex(&mut self, qt: QToken, timeout: Option<Duration>) -> Result<WaitOutcome, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::wait_ex");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.wait_ex(qt, timeout),
+            LibOS::MemoryLibOS(libos) => libos.wait_ex(qt, timeout),
+        }
+    }
+
+    /// Attaches an opaque value, such as the index of the application's connection state, to an I/O queue. The value
+    /// replaces any value attached before and is dropped when the queue is closed. When an operation completes, the
+    /// value can be looked up from the `qr_qd` of its result.
+    pub fn set_user_data(&mut self, qd: QDesc, user_data: u64) -> Result<(), Fail> {
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.set_user_data(qd, user_data),
+            LibOS::MemoryLibOS(libos) => libos.set_user_data(qd, user_data),
+        }
+    }
+
+    /// Returns the opaque value attached to an I/O queue, if any.
+    pub fn get_user_data(&self, qd: QDesc) -> Option<u64> {
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.get_user_data(qd),
+            LibOS::MemoryLibOS(libos) => libos.get_user_data(qd),
+        }
+    }
+
+    /// Returns a handle that interrupts an ongoing wait, or the next one if no wait is ongoing. A wait that is
+    /// interrupted fails with `EINTR`. The handle may be used from any thread.
+    pub fn interrupter(&self) -> WaitInterrupter {
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.interrupter(),
+            LibOS::MemoryLibOS(libos) => libos.interrupter(),
+        }
+    }
+
+    /// Makes poll write completed operations to a pre-allocated ring of `capacity` entries. Once enabled, completed
+    /// operations are retrieved with [Self::harvest] instead of wait.
+    pub fn enable_completion_ring(&mut self, capacity: usize) {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::enable_completion_ring");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.enable_completion_ring(capacity),
+            LibOS::MemoryLibOS(libos) => libos.enable_completion_ring(capacity),
+        }
+    }
+
+    /// Copies up to `out.len()` completed operations into `out` and returns how many were copied. This does not poll
+    /// and does not allocate.
+    pub fn harvest(&mut self, out: &mut [demi_qresult_t]) -> usize {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::harvest");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.harvest(out),
+            LibOS::MemoryLibOS(libos) => libos.harvest(out),
+        }
+    }
+
+    /// Sets an option on a socket.
+    pub fn set_socket_option(&mut self, qd: QDesc, option: SocketOption) -> Result<(), Fail> {
+        let result: Result<(), Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::set_socket_option");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.set_socket_option(qd, option),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "set_socket_option() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Reads an option back from a socket.
+    pub fn get_socket_option(&self, qd: QDesc, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::get_socket_option");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.get_socket_option(qd, name),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "get_socket_option() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Sets an option on a socket from its raw `level`, `name` and `value`, like `setsockopt()` does. Only
+    /// `TCP_NODELAY`, `SO_RCVBUF` and `SO_SNDBUF` are supported; other options fail with ENOPROTOOPT.
+    pub fn setsockopt(&mut self, qd: QDesc, level: i32, name: i32, value: &[u8]) -> Result<(), Fail> {
+        let option: SocketOption = SocketOption::from_raw(level, name, value)?;
+        self.set_socket_option(qd, option)
+    }
+
+    /// Reads an option back from a socket by its raw `level` and `name`, like `getsockopt()` does. The value is
+    /// returned as a C `int`.
+    pub fn getsockopt(&self, qd: QDesc, level: i32, name: i32) -> Result<Vec<u8>, Fail> {
+        let name: SocketOptionName = SocketOptionName::from_raw(level, name)?;
+        self.get_socket_option(qd, name)?.to_raw()
+    }
+
+    /// Moves an established connection to a new local address without resetting it.
+    pub fn migrate_local(&mut self, qd: QDesc, new_local: SocketAddr) -> Result<(), Fail> {
+        let result: Result<(), Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::migrate_local");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.migrate_local(qd, new_local),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "migrate_local() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Pauses the data flow of a connection: it advertises a zero window to the peer and refuses new pushes.
+    pub fn pause(&mut self, qd: QDesc) -> Result<(), Fail> {
+        let result: Result<(), Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::pause");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.pause(qd),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(libc::ENOTSUP, "pause() is not supported on memory liboses")),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Resumes the data flow of a connection that was paused with [Self::pause].
+    pub fn resume(&mut self, qd: QDesc) -> Result<(), Fail> {
+        let result: Result<(), Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::resume");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.resume(qd),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(libc::ENOTSUP, "resume() is not supported on memory liboses")),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Pauses a connection, as [Self::pause] does, and returns a snapshot of its endpoints, sequence numbers, and
+    /// buffered data, taken while the connection holds still. The connection is resumed in place with [Self::resume],
+    /// or restored from the snapshot with [Self::resume_from], e.g. by another process that took over its address.
+    pub fn quiesce(&mut self, qd: QDesc) -> Result<ConnectionSnapshot, Fail> {
+        let result: Result<ConnectionSnapshot, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::quiesce");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.quiesce(qd),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(libc::ENOTSUP, "quiesce() is not supported on memory liboses")),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Restores the connection that a snapshot was taken of with [Self::quiesce], and returns the I/O queue descriptor
+    /// of the restored connection. The snapshot may be carried over from another process with
+    /// [ConnectionSnapshot::serialize] and [ConnectionSnapshot::parse]. The libOS that took it must no longer run the
+    /// connection, e.g. because it exited, or the two would both answer the peer.
+    pub fn resume_from(&mut self, snapshot: &ConnectionSnapshot) -> Result<QDesc, Fail> {
+        let result: Result<QDesc, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::resume_from");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.resume_from(snapshot),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "resume_from() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Retries binds that fail with `EADDRINUSE` up to `attempts` times, waiting `delay` before each retry. By default,
+    /// binds are not retried.
+    pub fn set_bind_retry_policy(&mut self, attempts: usize, delay: Duration) -> Result<(), Fail> {
+        match self {
+            LibOS::NetworkLibOS(libos) => {
+                libos.set_bind_retry_policy(attempts, delay);
+                Ok(())
+            },
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "set_bind_retry_policy() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Shuts the libOS down: initiates a graceful close on all connections, drains the scheduler until the closes
+    /// complete or `timeout` expires, releases the sockets that are still open and stops the network transport. Returns
+    /// the connections that could not be drained in time. The libOS cannot carry traffic afterwards.
+    pub fn shutdown_all(&mut self, timeout: Duration) -> Result<Vec<QDesc>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::shutdown_all");
+        match self {
+            LibOS::NetworkLibOS(libos) => Ok(libos.shutdown_all(timeout)),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "shutdown_all() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Takes a consistent snapshot of all counters of the stack: scheduler occupancy, small-buffer pool hits and misses,
+    /// dropped packets and aggregates over the open connections. If `reset` is set, the resettable counters (pool and
+    /// drops) are zeroed as part of the same call.
+    pub fn metrics_snapshot(&mut self, reset: bool) -> Metrics {
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.metrics_snapshot(reset),
+            LibOS::MemoryLibOS(libos) => libos.metrics_snapshot(reset),
+        }
+    }
+
+    /// Returns the number of incoming packets dropped by the network stack, by reason.
+    pub fn drop_stats(&self) -> Result<DropStats, Fail> {
+        match self {
+            LibOS::NetworkLibOS(libos) => Ok(libos.drop_stats()),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "drop_stats() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the MAC address that the network stack uses to frame outgoing packets.
+    pub fn local_mac(&self) -> Result<MacAddress, Fail> {
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.local_link_addr(),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "local_mac() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Overrides the MAC address that the network stack uses to frame outgoing packets and in ARP. This is only valid
+    /// before any socket is created.
+    pub fn set_local_mac(&mut self, mac: MacAddress) -> Result<(), Fail> {
+        let result: Result<(), Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::set_local_mac");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.set_local_link_addr(mac),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "set_local_mac() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Swaps in a new ARP table, e.g. after a configuration reload. All learned entries of the ARP cache are replaced
+    /// with `map` at once, while static entries, such as those of the ARP table of the configuration, are kept.
+    pub fn reload_arp(&mut self, map: HashMap<Ipv4Addr, MacAddress>) -> Result<(), Fail> {
+        let result: Result<(), Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::reload_arp");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.reload_arp(map),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "reload_arp() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Returns the number of segments retransmitted on a socket.
+    pub fn retransmit_count(&self, qd: QDesc) -> Result<u64, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::retransmit_count");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.retransmit_count(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "retransmit_count() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the most recent state transitions of a socket, oldest first. Only built with the `state-history` feature,
+    /// for debugging how a socket reached the state that it is in.
+    #[cfg(feature = "state-history")]
+    pub fn state_history(&self, qd: QDesc) -> Result<Vec<SocketTransition>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::state_history");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.state_history(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "state_history() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the hit and miss counters of the small-buffer pool of a queue, or `None` if the queue draws its buffers
+    /// from the pool of the thread. See [SocketOption::QueueBufferPool].
+    pub fn buffer_pool_stats(&self, qd: QDesc) -> Result<Option<BufferPoolStats>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::buffer_pool_stats");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.buffer_pool_stats(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "buffer_pool_stats() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Creates an empty small-buffer pool, e.g. for the sockets served by one NUMA node, which sockets draw the buffers
+    /// of their pops from once [SocketOption::BufferPool] is set on them. The memory of the pool is placed on NUMA node
+    /// `numa_node`, if any. This fails with EINVAL if the machine has no such node.
+    pub fn create_buffer_pool(&mut self, numa_node: Option<u32>) -> Result<PoolId, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::create_buffer_pool");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.create_buffer_pool(numa_node),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "create_buffer_pool() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the hit and miss counters of a small-buffer pool created with [LibOS::create_buffer_pool], summed over
+    /// all the sockets that draw from it.
+    pub fn pool_stats(&self, pool_id: PoolId) -> Result<BufferPoolStats, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::pool_stats");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.pool_stats(pool_id),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "pool_stats() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the free buffers of the small-buffer pool of the calling thread, by size class, so that operators can see
+    /// how much capacity sits free in size classes that the application no longer uses.
+    pub fn pool_fragmentation(&self) -> FragmentationReport {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::pool_fragmentation");
+        // The small-buffer pool belongs to the thread rather than to the libOS, so every libOS reports on the same one.
+        DemiBuffer::small_buffer_pool_fragmentation()
+    }
+
+    /// Returns the congestion window of a connection, in bytes, or `None` if it has none.
+    pub fn cwnd(&self, qd: QDesc) -> Result<Option<u32>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::cwnd");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.cwnd(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(libc::ENOTSUP, "cwnd() is not supported on memory liboses")),
+        }
+    }
+
+    /// Returns the slow start threshold of a connection, in bytes, or `None` if it has none.
+    pub fn ssthresh(&self, qd: QDesc) -> Result<Option<u32>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::ssthresh");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.ssthresh(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "ssthresh() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the number of complete datagrams that a UDP socket has received and not yet popped.
+    pub fn queued_datagrams(&self, qd: QDesc) -> Result<usize, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::queued_datagrams");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.queued_datagrams(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "queued_datagrams() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Describes every open I/O queue: its type, its local and remote addresses, and the state of its socket.
+    pub fn queue_infos(&self) -> Result<Vec<QueueInfo>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::queue_infos");
+        match self {
+            LibOS::NetworkLibOS(libos) => Ok(libos.queue_infos()),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "queue_infos() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Forcibly closes every open I/O queue that `pred` matches, e.g. all connections to some remote address, and
+    /// returns the descriptors of the closed queues. The queues are hard-closed and released right away, without
+    /// waiting for their connections to shut down gracefully, so their descriptors are invalid afterwards.
+    pub fn close_matching(&mut self, pred: impl Fn(&QueueInfo) -> bool) -> Result<Vec<QDesc>, Fail> {
+        let result: Result<Vec<QDesc>, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::close_matching");
+            match self {
+                LibOS::NetworkLibOS(libos) => Ok(libos.close_matching(pred)),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "close_matching() is not supported on memory liboses",
+                )),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Returns whether a pop on an I/O queue would currently have to wait for data, without popping anything. This is a
+    /// cheap readiness check: it neither schedules an operation nor allocates.
+    pub fn would_block_pop(&self, qd: QDesc) -> Result<bool, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::would_block_pop");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.would_block_pop(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "would_block_pop() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the number of buffers of a socket that were queued in the transport and not transmitted yet. A depth
+    /// that keeps growing means that the send ring of the NIC is backing up.
+    pub fn transport_tx_depth(&self, qd: QDesc) -> Result<usize, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::transport_tx_depth");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.transport_tx_depth(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "transport_tx_depth() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the rate, in bits per second, at which a connection sent payload bytes over the TCP rate window.
+    pub fn send_rate_bps(&self, qd: QDesc) -> Result<u64, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::send_rate_bps");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.send_rate_bps(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "send_rate_bps() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the rate, in bits per second, at which a connection received payload bytes over the TCP rate window.
+    pub fn recv_rate_bps(&self, qd: QDesc) -> Result<u64, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::recv_rate_bps");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.recv_rate_bps(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "recv_rate_bps() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns whether a connection sent data in its SYN, using TCP Fast Open.
+    pub fn used_fast_open(&self, qd: QDesc) -> Result<bool, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::used_fast_open");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.used_fast_open(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "used_fast_open() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns when a connection was established, or `None` if it was never connected. Together with
+    /// [Self::last_activity], this lets applications reap connections by their own policy.
+    pub fn established_at(&self, qd: QDesc) -> Result<Option<Instant>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::established_at");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.established_at(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "established_at() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns when a push or pop on a socket last completed. Until one does, this is when the socket was created or,
+    /// if it is connected, established.
+    pub fn last_activity(&self, qd: QDesc) -> Result<Instant, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::last_activity");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.last_activity(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "last_activity() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the number of connections to a listening socket that are half-open, i.e. whose SYN was received but whose
+    /// handshake has not completed yet.
+    pub fn half_open_count(&self, qd: QDesc) -> Result<usize, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::half_open_count");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.half_open_count(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "half_open_count() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the number of accepts on a queue that have not completed yet. Once the backlog of a listening socket is
+    /// full, the transport turns new connections away and fails an accept with `ECONNABORTED` to report it.
+    pub fn pending_accepts(&self, qd: QDesc) -> Result<usize, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::pending_accepts");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.pending_accepts(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "pending_accepts() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the backlog that a queue listens with, or `None` if it does not listen.
+    pub fn backlog(&self, qd: QDesc) -> Result<Option<usize>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::backlog");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.backlog(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(libc::ENOTSUP, "backlog() is not supported on memory liboses")),
+        }
+    }
+
+    /// Returns the effective send buffer size of a socket, if it is bounded.
+    pub fn send_buffer_size(&self, qd: QDesc) -> Result<Option<usize>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::send_buffer_size");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.send_buffer_size(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "send_buffer_size() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the current effective MTU of the path of a socket, minus the network and transport headers. This is the
+    /// largest write that goes out in a single packet.
+    pub fn path_mtu(&self, qd: QDesc) -> Result<u16, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::path_mtu");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.path_mtu(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "path_mtu() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the number of pushes, pops, accepts, connects and closes that completed successfully on a queue. Once a
+    /// close completes, the final counts stay available through the queue descriptor until it is reused.
+    pub fn op_counts(&self, qd: QDesc) -> Result<OpCounts, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::op_counts");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.op_counts(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "op_counts() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the RFC 793 state of a TCP connection, such as FIN_WAIT_1 or TIME_WAIT, or `None` if the socket is not a
+    /// TCP socket or its transport does not track the state. This follows the close protocol, unlike `SocketState`.
+    pub fn tcp_state(&self, qd: QDesc) -> Result<Option<TcpState>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::tcp_state");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.tcp_state(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "tcp_state() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the initial sequence number that a TCP connection chose for our end, or `None` if the socket never sent
+    /// a SYN.
+    pub fn local_isn(&self, qd: QDesc) -> Result<Option<u32>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::local_isn");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.local_isn(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "local_isn() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the send and receive sequence numbers and windows of a TCP connection, or `None` if the socket is not
+    /// connected.
+    pub fn tcp_sequence_state(&self, qd: QDesc) -> Result<Option<TcpSeqState>, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::tcp_sequence_state");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.tcp_sequence_state(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "tcp_sequence_state() is not supported on memory liboses",
+            )),
+        }
+    }
+
+    /// Returns the DSCP that outgoing packets of a socket are marked with.
+    pub fn dscp(&self, qd: QDesc) -> Result<u8, Fail> {
+        #[cfg(feature = "profiler")]
+        timer!("demikernel::dscp");
+        match self {
+            LibOS::NetworkLibOS(libos) => libos.dscp(qd),
+            LibOS::MemoryLibOS(_) => Err(Fail::new(libc::ENOTSUP, "dscp() is not supported on memory liboses")),
+        }
+    }
+
+    /// Allocates a scatter-gather array.
+    pub fn sgaalloc(&mut self, size: usize) -> Result<demi_sgarray_t, Fail> {
+        let result: Result<demi_sgarray_t, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::sgaalloc");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.sgaalloc(size),
+                LibOS::MemoryLibOS(libos) => libos.sgaalloc(size),
+            }
+        };
+
+        self.poll();
+
+        result
+    }
+
+    /// Allocates a scatter-gather array to push on the socket referred to by `qd`, drawing it from the small-buffer pool
+    /// of the socket if it has one. See [SocketOption::BufferPool].
+    pub fn sgaalloc_for(&mut self, qd: QDesc, size: usize) -> Result<demi_sgarray_t, Fail> {
+        let result: Result<demi_sgarray_t, Fail> = {
+            #[cfg(feature = "profiler")]
+            timer!("demikernel::sgaalloc_for");
+            match self {
+                LibOS::NetworkLibOS(libos) => libos.sgaalloc_for(qd, size),
+                LibOS::MemoryLibOS(_) => Err(Fail::new(
+                    libc::ENOTSUP,
+                    "sgaalloc_for() is not supported on memory liboses",
+                )),
             }
         };
 
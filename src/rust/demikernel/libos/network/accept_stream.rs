@@ -0,0 +1,64 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    collections::async_queue::SharedAsyncQueue,
+    runtime::{
+        fail::Fail,
+        QDesc,
+        QToken,
+    },
+};
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Handle to a background accept loop on a listening socket. The loop keeps accepting connections and queues the
+/// descriptors of the new connections here, in the order in which they were accepted. Accepts that fail because a single
+/// connection went away are skipped. The loop stops after any other failure, including the listening socket being
+/// closed, whose error is the last item of the stream. The loop then completes like any other operation, with that
+/// same error, and is reclaimed by waiting for [AcceptStream::qt].
+pub struct AcceptStream {
+    /// Queue descriptor of the listening socket.
+    listen_qd: QDesc,
+    /// Token of the accept loop.
+    qt: QToken,
+    /// Results of the accepts that completed but were not yet taken from the stream.
+    accepted: SharedAsyncQueue<Result<QDesc, Fail>>,
+}
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl AcceptStream {
+    /// Creates a stream that yields the results pushed to `accepted` by the accept loop `qt` of `listen_qd`.
+    pub fn new(listen_qd: QDesc, qt: QToken, accepted: SharedAsyncQueue<Result<QDesc, Fail>>) -> Self {
+        Self {
+            listen_qd,
+            qt,
+            accepted,
+        }
+    }
+
+    /// Returns the queue descriptor of the listening socket.
+    pub fn listen_qd(&self) -> QDesc {
+        self.listen_qd
+    }
+
+    /// Returns the token of the accept loop, which completes once the loop stops.
+    pub fn qt(&self) -> QToken {
+        self.qt
+    }
+
+    /// Takes the oldest accepted connection from the stream, or returns `None` if no accept completed since the last
+    /// call. The libOS must be polled for new connections to arrive.
+    pub fn try_next(&mut self) -> Option<Result<QDesc, Fail>> {
+        self.accepted.try_pop()
+    }
+}
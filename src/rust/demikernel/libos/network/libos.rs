@@ -5,19 +5,55 @@
 // Imports
 //==============================================================================
 
+#[cfg(feature = "state-history")]
+use crate::runtime::network::socket::state::SocketTransition;
 use crate::{
-    demikernel::libos::network::queue::SharedNetworkQueue,
+    collections::async_queue::SharedAsyncQueue,
+    demikernel::libos::{
+        metrics::{
+            ConnectionMetrics,
+            Metrics,
+        },
+        network::{
+            accept_stream::AcceptStream,
+            queue::SharedNetworkQueue,
+            queue_info::QueueInfo,
+        },
+    },
     pal::constants::SOMAXCONN,
     runtime::{
+        cancellation::{
+            CancellationRegistration,
+            CancellationToken,
+        },
         fail::Fail,
         limits,
         memory::{
+            BufferPoolStats,
             DemiBuffer,
             MemoryRuntime,
+            PoolId,
+            SharedSmallBufferPool,
         },
         network::{
-            socket::SocketId,
+            socket::{
+                operation::Shutdown,
+                option::{
+                    SocketOption,
+                    SocketOptionName,
+                },
+                SocketId,
+            },
             transport::NetworkTransport,
+            types::{
+                ConnectionSnapshot,
+                DropStats,
+                MacAddress,
+                OpCounts,
+                TcpOption,
+                TcpSeqState,
+                TcpState,
+            },
             unwrap_socketaddr,
         },
         queue::{
@@ -27,6 +63,10 @@ use crate::{
             OperationResult,
         },
         scheduler::Yielder,
+        timer::{
+            SharedTimer,
+            TimerExpiry,
+        },
         types::demi_sgarray_t,
         QDesc,
         QToken,
@@ -42,6 +82,8 @@ use ::socket2::{
     Type,
 };
 use ::std::{
+    collections::HashMap,
+    future::Future,
     net::{
         Ipv4Addr,
         SocketAddr,
@@ -52,6 +94,10 @@ use ::std::{
         DerefMut,
     },
     pin::Pin,
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 //======================================================================================================================
@@ -67,6 +113,19 @@ pub struct NetworkLibOS<T: NetworkTransport> {
     runtime: SharedDemiRuntime,
     /// Underlying network transport.
     transport: T,
+    /// Ports below this floor may only be bound by sockets that explicitly allow it. Zero disables the check.
+    privileged_port_floor: u16,
+    /// Number of times a bind that fails with `EADDRINUSE` is retried. Zero disables retries.
+    bind_retry_attempts: usize,
+    /// Time to wait before retrying a failed bind.
+    bind_retry_delay: Duration,
+    /// Small-buffer pools created on request, which sockets draw their buffers from with [SocketOption::BufferPool].
+    buffer_pools: HashMap<PoolId, SharedSmallBufferPool>,
+    /// Identifier of the next small-buffer pool to create.
+    next_pool_id: usize,
+    /// Final operation counts of the queues that closed gracefully, by their former descriptor, until it is released
+    /// again.
+    closed_op_counts: HashMap<QDesc, OpCounts>,
 }
 
 #[derive(Clone)]
@@ -83,9 +142,355 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         Self(SharedObject::new(NetworkLibOS::<T> {
             runtime: runtime.clone(),
             transport,
+            privileged_port_floor: 0,
+            bind_retry_attempts: 0,
+            bind_retry_delay: Duration::ZERO,
+            buffer_pools: HashMap::new(),
+            next_pool_id: 0,
+            closed_op_counts: HashMap::new(),
         }))
     }
 
+    /// Sets the port number below which [bind](Self::bind) fails with `EACCES`, unless the socket was allowed to bind
+    /// to privileged ports. Setting the floor to zero disables this check.
+    pub fn set_privileged_port_floor(&mut self, floor: u16) {
+        self.privileged_port_floor = floor;
+    }
+
+    /// Retries binds that fail with `EADDRINUSE` up to `attempts` times, waiting `delay` before each retry. Some
+    /// transports report this error transiently right after a socket on the same address closes. The retries wait on
+    /// the timer of the runtime, which keeps running other coroutines in the meantime, but bind only returns once they
+    /// are done, so it may take up to `attempts * delay`. Setting `attempts` to zero disables retries.
+    pub fn set_bind_retry_policy(&mut self, attempts: usize, delay: Duration) {
+        self.bind_retry_attempts = attempts;
+        self.bind_retry_delay = delay;
+    }
+
+    /// Allows or disallows the socket referred to by `qd` to bind to ports below the privileged port floor.
+    pub fn set_allow_privileged_bind(&mut self, qd: QDesc, allow: bool) -> Result<(), Fail> {
+        trace!("set_allow_privileged_bind() qd={:?}, allow={:?}", qd, allow);
+        self.get_shared_queue(&qd)?.set_allow_privileged_bind(allow);
+        Ok(())
+    }
+
+    /// Sets an option on the socket referred to by `qd`.
+    pub fn set_socket_option(&mut self, qd: QDesc, option: SocketOption) -> Result<(), Fail> {
+        trace!("set_socket_option() qd={:?}, option={:?}", qd, option);
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        match option {
+            // Coroutines are scheduled by the libOS, so the transport has nothing to do here.
+            SocketOption::Priority(priority) => {
+                self.runtime.set_queue_priority(qd, priority);
+                Ok(())
+            },
+            // No transport raises signals, so there is nothing to turn off.
+            SocketOption::NoSigPipe(_) => Ok(()),
+            // Buffers for pops are allocated by the queue, so the transport has nothing to do here either.
+            SocketOption::QueueBufferPool(enabled) => {
+                queue.set_buffer_pool(enabled);
+                Ok(())
+            },
+            SocketOption::BufferPool(pool_id) => {
+                let pool: SharedSmallBufferPool = self.get_buffer_pool(pool_id)?.clone();
+                queue.set_shared_buffer_pool(pool_id, pool);
+                Ok(())
+            },
+            _ => queue.set_socket_option(option),
+        }
+    }
+
+    /// Reads an option back from the socket referred to by `qd`.
+    pub fn get_socket_option(&self, qd: QDesc, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        trace!("get_socket_option() qd={:?}, name={:?}", qd, name);
+        self.get_shared_queue(&qd)?.get_socket_option(name)
+    }
+
+    /// Returns the number of incoming packets dropped by the transport, by reason.
+    pub fn drop_stats(&self) -> DropStats {
+        self.transport.drop_stats()
+    }
+
+    /// Takes a snapshot of the counters of the scheduler, the small-buffer pool, the transport and the open queues. If
+    /// `reset` is set, the resettable counters are zeroed right after they are read, so that no event is lost or counted
+    /// twice across consecutive snapshots.
+    pub fn metrics_snapshot(&mut self, reset: bool) -> Metrics {
+        let mut connections: ConnectionMetrics = ConnectionMetrics::default();
+        for qd in self.runtime.get_qtable().get_qds() {
+            if let Ok(queue) = self.get_shared_queue(&qd) {
+                connections.open_queues += 1;
+                connections.retransmits += queue.retransmit_count();
+            }
+        }
+        let metrics: Metrics = Metrics {
+            scheduler: self.runtime.slab_stats(),
+            buffer_pool: DemiBuffer::small_buffer_pool_stats(),
+            drops: self.transport.drop_stats(),
+            connections,
+        };
+        if reset {
+            DemiBuffer::reset_small_buffer_pool_stats();
+            self.transport.reset_drop_stats();
+        }
+        metrics
+    }
+
+    /// Returns the link address used to frame outgoing packets.
+    pub fn local_link_addr(&self) -> Result<MacAddress, Fail> {
+        self.transport.get_local_link_addr()
+    }
+
+    /// Overrides the link address used to frame outgoing packets. This is only allowed before any socket is created,
+    /// because existing sockets would keep using the old address.
+    pub fn set_local_link_addr(&mut self, link_addr: MacAddress) -> Result<(), Fail> {
+        trace!("set_local_link_addr() link_addr={:?}", link_addr);
+        if self.runtime.get_qtable().get_values().next().is_some() {
+            let cause: &str = "cannot change local link address while there are open sockets";
+            error!("set_local_link_addr(): {}", cause);
+            return Err(Fail::new(libc::EBUSY, cause));
+        }
+        self.transport.set_local_link_addr(link_addr)
+    }
+
+    /// Replaces the learned entries of the ARP cache with `values` in one go, e.g. after a configuration reload. Static
+    /// entries, which include the ARP table of the configuration, are kept.
+    pub fn reload_arp(&mut self, values: HashMap<Ipv4Addr, MacAddress>) -> Result<(), Fail> {
+        trace!("reload_arp() num_entries={:?}", values.len());
+        self.transport.replace_arp_cache(&values)
+    }
+
+    /// Moves the connection referred to by `qd` to a new local address, e.g. after the address of the NIC changed. The
+    /// connection is not reset, but the peer keeps sending to the old address until it learns about the new one.
+    pub fn migrate_local(&mut self, qd: QDesc, new_local: SocketAddr) -> Result<(), Fail> {
+        trace!("migrate_local() qd={:?}, new_local={:?}", qd, new_local);
+
+        let new_localv4: SocketAddrV4 = unwrap_socketaddr(new_local)?;
+        if new_localv4.ip().is_unspecified() || new_localv4.port() == 0 {
+            let cause: String = format!("cannot migrate to a wildcard address (qd={:?})", qd);
+            error!("migrate_local(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, &cause));
+        }
+        if self.runtime.addr_in_use(new_localv4) {
+            let cause: String = format!("address is already bound to a socket (qd={:?})", qd);
+            error!("migrate_local(): {}", &cause);
+            return Err(Fail::new(libc::EADDRINUSE, &cause));
+        }
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let old_local: Option<SocketAddr> = queue.local();
+        queue.migrate_local(new_local)?;
+
+        // If the queue was bound, move it in the socket id to queue descriptor table.
+        if let Some(old_local) = old_local {
+            // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+            let old_localv4: SocketAddrV4 = unwrap_socketaddr(old_local).expect("we only support IPv4");
+            if self
+                .runtime
+                .remove_socket_id_to_qd(&SocketId::Passive(old_localv4))
+                .is_some()
+            {
+                self.runtime.insert_socket_id_to_qd(SocketId::Passive(new_localv4), qd);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pauses the data flow of the connection referred to by `qd`. The connection advertises a zero window, so that the
+    /// peer stops sending, and refuses new pushes until it is resumed.
+    pub fn pause(&mut self, qd: QDesc) -> Result<(), Fail> {
+        trace!("pause() qd={:?}", qd);
+        self.get_shared_queue(&qd)?.set_paused(true)
+    }
+
+    /// Resumes the data flow of the connection referred to by `qd`.
+    pub fn resume(&mut self, qd: QDesc) -> Result<(), Fail> {
+        trace!("resume() qd={:?}", qd);
+        self.get_shared_queue(&qd)?.set_paused(false)
+    }
+
+    /// Pauses the connection referred to by `qd`, as [Self::pause] does, and returns its state, so that it can be
+    /// inspected while it holds still, or restored elsewhere with [Self::resume_from]. The connection is resumed in
+    /// place with [Self::resume].
+    pub fn quiesce(&mut self, qd: QDesc) -> Result<ConnectionSnapshot, Fail> {
+        trace!("quiesce() qd={:?}", qd);
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        queue.set_paused(true)?;
+        queue.connection_snapshot()
+    }
+
+    /// Restores the connection that `snapshot` was taken of with [Self::quiesce] on a new queue, and returns its queue
+    /// descriptor. The snapshot may come from another libOS, which must no longer run the connection.
+    pub fn resume_from(&mut self, snapshot: &ConnectionSnapshot) -> Result<QDesc, Fail> {
+        trace!("resume_from() local={:?}, remote={:?}", snapshot.local, snapshot.remote);
+        let timer: SharedTimer = self.runtime.get_timer();
+        let queue: SharedNetworkQueue<T> = SharedNetworkQueue::resume_from(snapshot, &mut self.transport, timer)?;
+        Ok(self.runtime.alloc_queue(queue))
+    }
+
+    /// Shuts down the `how` directions of the connection referred to by `qd`, without releasing it. The queue must still
+    /// be closed afterwards.
+    pub fn shutdown(&mut self, qd: QDesc, how: Shutdown) -> Result<(), Fail> {
+        trace!("shutdown() qd={:?}, how={:?}", qd, how);
+        self.get_shared_queue(&qd)?.shutdown(how)
+    }
+
+    /// Returns the number of segments retransmitted on the socket referred to by `qd`.
+    pub fn retransmit_count(&self, qd: QDesc) -> Result<u64, Fail> {
+        Ok(self.get_shared_queue(&qd)?.retransmit_count())
+    }
+
+    /// Returns the most recent state transitions of the socket referred to by `qd`, oldest first.
+    #[cfg(feature = "state-history")]
+    pub fn state_history(&self, qd: QDesc) -> Result<Vec<SocketTransition>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.state_history())
+    }
+
+    /// Returns the hit and miss counters of the small-buffer pool of the queue referred to by `qd`, or `None` if the
+    /// queue draws its buffers from the pool of the thread.
+    pub fn buffer_pool_stats(&self, qd: QDesc) -> Result<Option<BufferPoolStats>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.buffer_pool_stats())
+    }
+
+    /// Creates an empty small-buffer pool, which sockets can draw their buffers from with [SocketOption::BufferPool].
+    /// The memory of the pool is placed on NUMA node `numa_node`, if any. The pool lives as long as the libOS, or as
+    /// long as the last buffer drawn from it if that outlives the libOS.
+    pub fn create_buffer_pool(&mut self, numa_node: Option<u32>) -> Result<PoolId, Fail> {
+        let pool: SharedSmallBufferPool = match numa_node {
+            Some(numa_node) => DemiBuffer::new_small_buffer_pool_on_node(numa_node)?,
+            None => DemiBuffer::new_small_buffer_pool(),
+        };
+        let pool_id: PoolId = PoolId(self.next_pool_id);
+        self.next_pool_id += 1;
+        self.buffer_pools.insert(pool_id, pool);
+        trace!("create_buffer_pool() pool_id={:?}, numa_node={:?}", pool_id, numa_node);
+        Ok(pool_id)
+    }
+
+    /// Returns the hit and miss counters of a small-buffer pool created with
+    /// [create_buffer_pool](Self::create_buffer_pool), summed over all the sockets that draw from it.
+    pub fn pool_stats(&self, pool_id: PoolId) -> Result<BufferPoolStats, Fail> {
+        Ok(self.get_buffer_pool(pool_id)?.stats())
+    }
+
+    /// Allocates a scatter-gather array of `size` bytes to push on the socket referred to by `qd`, from the small-buffer
+    /// pool of the socket, or from the one of the thread if it has none.
+    pub fn sgaalloc_for(&self, qd: QDesc, size: usize) -> Result<demi_sgarray_t, Fail> {
+        if size > u16::MAX as usize {
+            return Err(Fail::new(libc::EINVAL, "size too large for a single demi_sgaseg_t"));
+        }
+        let buf: DemiBuffer = self.get_shared_queue(&qd)?.alloc_buffer(size as u16);
+        self.transport.into_sgarray(buf)
+    }
+
+    /// Returns the congestion window, in bytes, of the connection referred to by `qd`, or `None` if it has none.
+    pub fn cwnd(&self, qd: QDesc) -> Result<Option<u32>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.cwnd())
+    }
+
+    /// Returns the slow start threshold, in bytes, of the connection referred to by `qd`, or `None` if it has none.
+    pub fn ssthresh(&self, qd: QDesc) -> Result<Option<u32>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.ssthresh())
+    }
+
+    /// Returns whether the connection referred to by `qd` sent data in its SYN, using TCP Fast Open.
+    pub fn used_fast_open(&self, qd: QDesc) -> Result<bool, Fail> {
+        Ok(self.get_shared_queue(&qd)?.used_fast_open())
+    }
+
+    /// Returns when the connection referred to by `qd` was established, or `None` if it was never connected.
+    pub fn established_at(&self, qd: QDesc) -> Result<Option<Instant>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.established_at())
+    }
+
+    /// Returns when a push or pop on the queue referred to by `qd` last completed.
+    pub fn last_activity(&self, qd: QDesc) -> Result<Instant, Fail> {
+        Ok(self.get_shared_queue(&qd)?.last_activity())
+    }
+
+    /// Returns the number of half-open connections to the listening socket referred to by `qd`.
+    pub fn half_open_count(&self, qd: QDesc) -> Result<usize, Fail> {
+        self.get_shared_queue(&qd)?.half_open_count()
+    }
+
+    /// Returns the number of accepts on the queue referred to by `qd` that have not completed yet.
+    pub fn pending_accepts(&self, qd: QDesc) -> Result<usize, Fail> {
+        Ok(self.get_shared_queue(&qd)?.pending_accepts())
+    }
+
+    /// Returns the backlog that the queue referred to by `qd` listens with, if it listens.
+    pub fn backlog(&self, qd: QDesc) -> Result<Option<usize>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.backlog())
+    }
+
+    /// Returns the number of datagrams that the socket referred to by `qd` holds for popping.
+    pub fn queued_datagrams(&self, qd: QDesc) -> Result<usize, Fail> {
+        Ok(self.get_shared_queue(&qd)?.queued_datagrams())
+    }
+
+    /// Returns whether a pop on the socket referred to by `qd` would currently block.
+    pub fn would_block_pop(&self, qd: QDesc) -> Result<bool, Fail> {
+        self.get_shared_queue(&qd)?.would_block_pop()
+    }
+
+    /// Returns the number of buffers of the socket referred to by `qd` that the transport has yet to transmit.
+    pub fn transport_tx_depth(&self, qd: QDesc) -> Result<usize, Fail> {
+        Ok(self.get_shared_queue(&qd)?.transport_tx_depth())
+    }
+
+    /// Returns the recent send rate, in bits per second, of the connection referred to by `qd`.
+    pub fn send_rate_bps(&self, qd: QDesc) -> Result<u64, Fail> {
+        Ok(self.get_shared_queue(&qd)?.send_rate_bps())
+    }
+
+    /// Returns the recent receive rate, in bits per second, of the connection referred to by `qd`.
+    pub fn recv_rate_bps(&self, qd: QDesc) -> Result<u64, Fail> {
+        Ok(self.get_shared_queue(&qd)?.recv_rate_bps())
+    }
+
+    /// Returns the TCP options recorded on the connection referred to by `qd`.
+    pub fn observed_options(&self, qd: QDesc) -> Result<Vec<TcpOption>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.observed_options())
+    }
+
+    /// Returns the effective send buffer size of the socket referred to by `qd`, if it is bounded.
+    pub fn send_buffer_size(&self, qd: QDesc) -> Result<Option<usize>, Fail> {
+        self.get_shared_queue(&qd)?.send_buffer_size()
+    }
+
+    /// Returns the largest payload that fits in a single packet on the path of the socket referred to by `qd`.
+    pub fn path_mtu(&self, qd: QDesc) -> Result<u16, Fail> {
+        Ok(self.get_shared_queue(&qd)?.path_mtu())
+    }
+
+    /// Returns the number of operations that completed successfully on the queue referred to by `qd`, by type. The
+    /// final counts of a queue that closed gracefully stay available through its descriptor until the descriptor is
+    /// reused and released again.
+    pub fn op_counts(&self, qd: QDesc) -> Result<OpCounts, Fail> {
+        match self.get_shared_queue(&qd) {
+            Ok(queue) => Ok(queue.op_counts()),
+            Err(e) => self.closed_op_counts.get(&qd).copied().ok_or(e),
+        }
+    }
+
+    /// Returns the RFC 793 state of the connection referred to by `qd`.
+    pub fn tcp_state(&self, qd: QDesc) -> Result<Option<TcpState>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.tcp_state())
+    }
+
+    /// Returns the initial sequence number that the connection referred to by `qd` chose for our end.
+    pub fn local_isn(&self, qd: QDesc) -> Result<Option<u32>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.local_isn())
+    }
+
+    /// Returns the send and receive sequence numbers and windows of the connection referred to by `qd`.
+    pub fn tcp_sequence_state(&self, qd: QDesc) -> Result<Option<TcpSeqState>, Fail> {
+        Ok(self.get_shared_queue(&qd)?.tcp_sequence_state())
+    }
+
+    /// Returns the DSCP that outgoing packets of the socket referred to by `qd` are marked with.
+    pub fn dscp(&self, qd: QDesc) -> Result<u8, Fail> {
+        self.get_shared_queue(&qd)?.dscp()
+    }
+
     /// Creates a socket. This function contains the libOS-level functionality needed to create a SharedNetworkQueue that
     /// wraps the underlying POSIX socket.
     pub fn socket(&mut self, domain: Domain, typ: Type, _protocol: Protocol) -> Result<QDesc, Fail> {
@@ -104,7 +509,8 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         }
 
         // Create underlying queue.
-        let queue: SharedNetworkQueue<T> = SharedNetworkQueue::new(domain, typ, &mut self.transport)?;
+        let timer: SharedTimer = self.runtime.get_timer();
+        let queue: SharedNetworkQueue<T> = SharedNetworkQueue::new(domain, typ, &mut self.transport, timer)?;
         let qd: QDesc = self.runtime.alloc_queue(queue);
         Ok(qd)
     }
@@ -123,6 +529,21 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
             return Err(Fail::new(libc::ENOTSUP, &cause));
         }
 
+        // Check if we are binding to a privileged port.
+        if local.port() != 0
+            && local.port() < self.privileged_port_floor
+            && !self.get_shared_queue(&qd)?.allows_privileged_bind()
+        {
+            let cause: String = format!(
+                "cannot bind to privileged port (qd={:?}, port={:?}, floor={:?})",
+                qd,
+                local.port(),
+                self.privileged_port_floor
+            );
+            error!("bind(): {}", cause);
+            return Err(Fail::new(libc::EACCES, &cause));
+        }
+
         // Check if this is an ephemeral port.
         if SharedDemiRuntime::is_private_ephemeral_port(local.port()) {
             // Allocate ephemeral port from the pool.
@@ -143,15 +564,19 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
             }
         }
 
-        // Check wether the address is in use.
-        if self.runtime.addr_in_use(localv4) {
+        // Check wether the address is in use. Queues that all allow it may share an address.
+        if self.runtime.addr_in_use(localv4) && !self.may_share_addr(qd, localv4)? {
             let cause: String = format!("address is already bound to a socket (qd={:?}", qd);
             error!("bind(): {}", &cause);
             return Err(Fail::new(libc::EADDRINUSE, &cause));
         }
 
-        // Issue bind operation.
-        if let Err(e) = self.get_shared_queue(&qd)?.bind(local) {
+        // Issue bind operation, retrying transient failures if configured to do so.
+        let result: Result<(), Fail> = match self.get_shared_queue(&qd)?.bind(local) {
+            Err(e) if e.errno == libc::EADDRINUSE && self.bind_retry_attempts > 0 => self.retry_bind(qd, local, e),
+            result => result,
+        };
+        if let Err(e) = result {
             // Rollback ephemeral port allocation.
             if SharedDemiRuntime::is_private_ephemeral_port(local.port()) {
                 if self.runtime.free_ephemeral_port(local.port()).is_err() {
@@ -160,13 +585,61 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
             }
             Err(e)
         } else {
-            // Insert into address to queue descriptor table.
-            self.runtime
-                .insert_socket_id_to_qd(SocketId::Passive(localv4.clone()), qd);
+            // Insert into address to queue descriptor table, unless the address is shared and already there.
+            let socket_id: SocketId = SocketId::Passive(localv4);
+            if self.runtime.get_qd_from_socket_id(&socket_id).is_none() {
+                self.runtime.insert_socket_id_to_qd(socket_id, qd);
+            }
             Ok(())
         }
     }
 
+    /// Retries the bind of the SharedNetworkQueue of [qd] to [local], which failed with [error], in a background
+    /// coroutine, and runs the scheduler until that coroutine is done.
+    fn retry_bind(&mut self, qd: QDesc, local: SocketAddr, error: Fail) -> Result<(), Fail> {
+        let outcome: SharedObject<Option<Result<(), Fail>>> = SharedObject::new(None);
+        let task_name: String = format!("NetworkLibOS::bind_retry for qd={:?}", qd);
+        let coroutine = self.clone().bind_retry_coroutine(qd, local, error, outcome.clone());
+        let qt: QToken = self
+            .runtime
+            .insert_background_coroutine(&task_name, Box::pin(coroutine.fuse()))?;
+        while outcome.is_none() {
+            self.runtime.poll_and_advance_clock();
+        }
+        self.runtime.remove_background_coroutine(qt)?;
+        outcome
+            .clone()
+            .take()
+            .expect("bind retry coroutine should have set its outcome")
+    }
+
+    /// Asynchronous code to retry a bind that failed with `EADDRINUSE`, waiting on the timer of the runtime before each
+    /// attempt. Stores the result of the last attempt in [outcome].
+    async fn bind_retry_coroutine(
+        self,
+        qd: QDesc,
+        local: SocketAddr,
+        error: Fail,
+        mut outcome: SharedObject<Option<Result<(), Fail>>>,
+    ) {
+        let yielder: Yielder = Yielder::new();
+        let mut result: Result<(), Fail> = Err(error);
+        for attempt in 1..=self.bind_retry_attempts {
+            match result {
+                Err(ref e) if e.errno == libc::EADDRINUSE => {
+                    warn!("bind(): retrying (qd={:?}, attempt={:?}, error={:?})", qd, attempt, e);
+                    if let Err(e) = self.runtime.get_timer().wait(self.bind_retry_delay, &yielder).await {
+                        result = Err(e);
+                        break;
+                    }
+                    result = self.get_shared_queue(&qd).and_then(|mut queue| queue.bind(local));
+                },
+                _ => break,
+            }
+        }
+        *outcome = Some(result);
+    }
+
     /// Sets a SharedNetworkQueue and its underlying socket as a passive one. This function contains the libOS-level
     /// functionality to move the SharedNetworkQueue and underlying socket into the listen state.
     pub fn listen(&mut self, qd: QDesc, backlog: usize) -> Result<(), Fail> {
@@ -236,6 +709,209 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         }
     }
 
+    /// Synchronous cross-queue code to start accepting up to [max] connections at once. The operation completes as soon
+    /// as at least one connection is accepted, with every connection that can then be accepted without waiting.
+    pub fn accept_batch(&mut self, qd: QDesc, max: usize) -> Result<QToken, Fail> {
+        trace!("accept_batch(): qd={:?}, max={:?}", qd, max);
+        if max == 0 {
+            let cause: &str = "cannot accept an empty batch";
+            warn!("accept_batch(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, cause));
+        }
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::accept_batch for qd={:?}", qd);
+            let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
+                Box::pin(self.clone().accept_batch_coroutine(qd, max, yielder).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        let qt: QToken = queue.accept(coroutine_constructor)?;
+        self.runtime.track_accept_batch(qt);
+        Ok(qt)
+    }
+
+    /// Asynchronous cross-queue code for accepting a batch of connections. Only the first accept may wait; the others
+    /// are only attempted while the transport holds connections that are ready to be accepted.
+    async fn accept_batch_coroutine(mut self, qd: QDesc, max: usize, yielder: Yielder) -> (QDesc, OperationResult) {
+        let mut queue: SharedNetworkQueue<T> = match self.get_shared_queue(&qd) {
+            Ok(queue) => queue.clone(),
+            Err(e) => return (qd, OperationResult::Failed(e)),
+        };
+        let mut accepted: Vec<(QDesc, SocketAddrV4)> = Vec::with_capacity(max);
+        let mut yielder: Option<Yielder> = Some(yielder);
+        while accepted.len() < max {
+            // The first accept waits with the yielder of the operation, so that closing the queue wakes it.
+            let yielder: Yielder = match yielder.take() {
+                Some(yielder) => yielder,
+                None if queue.ready_connections() > 0 => Yielder::new(),
+                None => break,
+            };
+            match queue.accept_coroutine(yielder).await {
+                Ok(new_queue) => {
+                    // It is safe to call except here because the new queue is connected and it should be connected to
+                    // a remote address.
+                    let addr: SocketAddr = new_queue
+                        .remote()
+                        .expect("An accepted socket must have a remote address");
+                    let new_qd: QDesc = self.runtime.alloc_queue(new_queue);
+                    // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+                    accepted.push((new_qd, unwrap_socketaddr(addr).expect("we only support IPv4")));
+                },
+                // A failed handshake only fails the batch if nothing was accepted before it.
+                Err(e) if accepted.is_empty() => {
+                    warn!("accept_batch() listening_qd={:?}: {:?}", qd, &e);
+                    return (qd, OperationResult::Failed(e));
+                },
+                Err(e) => {
+                    warn!("accept_batch() listening_qd={:?}: {:?}", qd, &e);
+                    break;
+                },
+            }
+        }
+        (qd, OperationResult::AcceptBatch(accepted))
+    }
+
+    /// Starts a loop that keeps accepting connections on the listening socket [qd] and returns a stream that yields the
+    /// descriptors of the new connections as they arrive. The loop is tracked like any other operation on [qd], so
+    /// closing [qd] stops it.
+    pub fn spawn_accept_loop(&mut self, qd: QDesc) -> Result<AcceptStream, Fail> {
+        trace!("spawn_accept_loop(): qd={:?}", qd);
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let accepted: SharedAsyncQueue<Result<QDesc, Fail>> = SharedAsyncQueue::default();
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::accept_loop for qd={:?}", qd);
+            let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
+                Box::pin(self.clone().accept_loop_coroutine(qd, accepted.clone(), yielder).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+        let qt: QToken = queue.accept(coroutine_constructor)?;
+
+        Ok(AcceptStream::new(qd, qt, accepted))
+    }
+
+    /// Asynchronous code for the accept loop. This function re-arms the accept after every new connection, skips the
+    /// accepts that fail for a single connection, and stops after any other failure or once [yielder] is woken, which
+    /// happens when [qd] is closed.
+    async fn accept_loop_coroutine(
+        self,
+        qd: QDesc,
+        mut accepted: SharedAsyncQueue<Result<QDesc, Fail>>,
+        yielder: Yielder,
+    ) -> (QDesc, OperationResult) {
+        loop {
+            let accept = self.clone().accept_coroutine(qd, Yielder::new()).fuse();
+            let cancel = yielder.yield_until_wake().fuse();
+            futures::pin_mut!(accept, cancel);
+            let result: (QDesc, OperationResult) = futures::select_biased! {
+                result = accept => result,
+                result = cancel => {
+                    let e: Fail = match result {
+                        Err(e) => e,
+                        Ok(()) => Fail::new(libc::ECANCELED, "accept loop was woken up"),
+                    };
+                    (qd, OperationResult::Failed(e))
+                },
+            };
+            match result {
+                (_, OperationResult::Accept((new_qd, _))) => accepted.push(Ok(new_qd)),
+                (_, OperationResult::Failed(e)) if is_transient_accept_error(e.errno) => {
+                    warn!("accept_loop() listening_qd={:?}: skipping failed accept: {:?}", qd, &e);
+                    // Give the connections that are still coming a chance to arrive before trying again.
+                    if let Err(e) = Yielder::new().yield_once().await {
+                        accepted.push(Err(e.clone()));
+                        return (qd, OperationResult::Failed(e));
+                    }
+                },
+                (_, OperationResult::Failed(e)) => {
+                    accepted.push(Err(e.clone()));
+                    return (qd, OperationResult::Failed(e));
+                },
+                (_, result) => {
+                    let cause: String = format!("unexpected accept result (result={:?})", result);
+                    error!("accept_loop_coroutine(): {}", cause);
+                    let e: Fail = Fail::new(libc::EINVAL, &cause);
+                    accepted.push(Err(e.clone()));
+                    return (qd, OperationResult::Failed(e));
+                },
+            }
+        }
+    }
+
+    /// Synchronous cross-queue code to start accepting a connection and pushing [initial] to it. This function
+    /// schedules the asynchronous coroutine and performs any necessary synchronous, multi-queue operations at the
+    /// libOS-level before beginning the accept.
+    pub fn accept_and_push(&mut self, qd: QDesc, initial: DemiBuffer) -> Result<QToken, Fail> {
+        trace!("accept_and_push(): qd={:?}", qd);
+        if initial.len() == 0 {
+            let cause: &str = "zero-length buffer";
+            warn!("accept_and_push(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, cause));
+        };
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::accept_and_push for qd={:?}", qd);
+            let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
+                Box::pin(self.clone().accept_and_push_coroutine(qd, initial, yielder).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.accept(coroutine_constructor)
+    }
+
+    /// Asynchronous cross-queue code for accepting a connection and pushing [initial] to it. The new queue descriptor
+    /// is only handed out once the push has been enqueued. If the push fails, the accepted connection is closed.
+    async fn accept_and_push_coroutine(
+        mut self,
+        qd: QDesc,
+        mut initial: DemiBuffer,
+        yielder: Yielder,
+    ) -> (QDesc, OperationResult) {
+        let mut queue: SharedNetworkQueue<T> = match self.get_shared_queue(&qd) {
+            Ok(queue) => queue.clone(),
+            Err(e) => return (qd, OperationResult::Failed(e)),
+        };
+        let mut new_queue: SharedNetworkQueue<T> = match queue.accept_coroutine(yielder).await {
+            Ok(new_queue) => new_queue,
+            Err(e) => {
+                warn!("accept_and_push() listening_qd={:?}: {:?}", qd, &e);
+                return (qd, OperationResult::Failed(e));
+            },
+        };
+        // The accepted queue has no descriptor yet, so nothing else can wake this push but the transport itself.
+        let push_yielder: Yielder = Yielder::new();
+        if let Err(e) = new_queue.push_coroutine(&mut initial, None, push_yielder).await {
+            warn!("accept_and_push() listening_qd={:?}: {:?}", qd, &e);
+            if let Err(e) = new_queue.hard_close() {
+                warn!("accept_and_push(): failed to close accepted connection: {:?}", &e);
+            }
+            return (qd, OperationResult::Failed(e));
+        }
+        // It is safe to call except here because the new queue is connected and it should be connected to a remote
+        // address.
+        let addr: SocketAddr = new_queue
+            .remote()
+            .expect("An accepted socket must have a remote address");
+        let new_qd: QDesc = self.runtime.alloc_queue(new_queue);
+        // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+        (
+            qd,
+            OperationResult::Accept((new_qd, unwrap_socketaddr(addr).expect("we only support IPv4"))),
+        )
+    }
+
     /// Synchronous code to establish a connection to a remote endpoint. This function schedules the asynchronous
     /// coroutine and performs any necessary synchronous, multi-queue operations at the libOS-level before beginning
     /// the connect.
@@ -247,7 +923,62 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         let coroutine_constructor = || -> Result<QToken, Fail> {
             let task_name: String = format!("NetworkLibOS::connect for qd={:?}", qd);
             let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
-                Box::pin(self.clone().connect_coroutine(qd, remote, yielder).fuse())
+                Box::pin(self.clone().connect_coroutine(qd, remote, None, yielder).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.connect(coroutine_constructor)
+    }
+
+    /// Synchronous code to establish a connection like [Self::connect], except that the connect fails with `ETIMEDOUT`
+    /// if the connection is not established by `deadline`, as told by the clock of the stack.
+    pub fn connect_until(&mut self, qd: QDesc, remote: SocketAddr, deadline: Instant) -> Result<QToken, Fail> {
+        trace!(
+            "connect_until() qd={:?}, remote={:?}, deadline={:?}",
+            qd,
+            remote,
+            deadline
+        );
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::connect_until for qd={:?}", qd);
+            let coroutine_factory = |yielder: Yielder| -> Pin<Box<Operation>> {
+                let expiry: TimerExpiry = self.runtime.get_timer().expire_at(deadline, yielder.get_handle());
+                let coroutine = self.clone().connect_coroutine(qd, remote, None, yielder);
+                Box::pin(with_expiry(coroutine, Some(expiry)).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.connect(coroutine_constructor)
+    }
+
+    /// Synchronous code to establish a connection to a remote endpoint and send `initial` data with it. Transports that
+    /// support TCP Fast Open send the data in the SYN if they hold a cookie from the remote, and right after the
+    /// handshake otherwise.
+    pub fn connect_with_data(&mut self, qd: QDesc, remote: SocketAddr, initial: DemiBuffer) -> Result<QToken, Fail> {
+        trace!(
+            "connect_with_data() qd={:?}, remote={:?}, len={:?}",
+            qd,
+            remote,
+            initial.len()
+        );
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::connect_with_data for qd={:?}", qd);
+            let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
+                Box::pin(
+                    self.clone()
+                        .connect_coroutine(qd, remote, Some(initial), yielder)
+                        .fuse(),
+                )
             };
             self.runtime
                 .clone()
@@ -257,10 +988,73 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         queue.connect(coroutine_constructor)
     }
 
+    /// Synchronous code to establish a connection to the first of `remotes` that accepts one. Attempts start a
+    /// `stagger` apart, in order, and the first to succeed completes the connect and aborts the others.
+    pub fn connect_any(&mut self, qd: QDesc, remotes: Vec<SocketAddr>, stagger: Duration) -> Result<QToken, Fail> {
+        trace!(
+            "connect_any() qd={:?}, remotes={:?}, stagger={:?}",
+            qd,
+            remotes,
+            stagger
+        );
+
+        if remotes.is_empty() {
+            let cause: String = format!("no remote to connect to (qd={:?})", qd);
+            error!("connect_any(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, &cause));
+        }
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        // Every attempt connects from an ephemeral port of its own, so the queue cannot be bound already.
+        if queue.get_qtype() != QType::TcpSocket || queue.local().is_some() {
+            let cause: String = format!("connect_any() only supports unbound TCP sockets (qd={:?})", qd);
+            error!("connect_any(): {}", cause);
+            return Err(Fail::new(libc::ENOTSUP, &cause));
+        }
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::connect_any for qd={:?}", qd);
+            let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
+                Box::pin(self.clone().connect_any_coroutine(qd, remotes, stagger, yielder).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.connect(coroutine_constructor)
+    }
+
+    /// Asynchronous code to establish a connection to the first of several remote endpoints that accepts one.
+    async fn connect_any_coroutine(
+        self,
+        qd: QDesc,
+        remotes: Vec<SocketAddr>,
+        stagger: Duration,
+        yielder: Yielder,
+    ) -> (QDesc, OperationResult) {
+        let mut queue: SharedNetworkQueue<T> = match self.get_shared_queue(&qd) {
+            Ok(queue) => queue.clone(),
+            Err(e) => return (qd, OperationResult::Failed(e)),
+        };
+        // Wait for one of the connection attempts to complete.
+        match queue.connect_any_coroutine(remotes, stagger, yielder).await {
+            Ok(()) => (qd, OperationResult::Connect),
+            Err(e) => {
+                warn!("connect_any() failed (qd={:?}, error={:?})", qd, e.cause);
+                (qd, OperationResult::Failed(e))
+            },
+        }
+    }
+
     /// Asynchronous code to establish a connection to a remote endpoint. This function returns a coroutine that runs
     /// asynchronously to connect a queue and performs any necessary multi-queue operations at the libOS-level after
     /// the connect succeeds or fails.
-    async fn connect_coroutine(self, qd: QDesc, remote: SocketAddr, yielder: Yielder) -> (QDesc, OperationResult) {
+    async fn connect_coroutine(
+        self,
+        qd: QDesc,
+        remote: SocketAddr,
+        initial: Option<DemiBuffer>,
+        yielder: Yielder,
+    ) -> (QDesc, OperationResult) {
         // Grab the queue, make sure it hasn't been closed in the meantime.
         // This will bump the Rc refcount so the coroutine can have it's own reference to the shared queue data
         // structure and the SharedNetworkQueue will not be freed until this coroutine finishes.
@@ -269,7 +1063,7 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
             Err(e) => return (qd, OperationResult::Failed(e)),
         };
         // Wait for connect operation to complete.
-        match queue.connect_coroutine(remote, yielder).await {
+        match queue.connect_coroutine(remote, initial, yielder).await {
             Ok(()) => {
                 // TODO: Do we need to add this to socket id to queue descriptor table?
                 (qd, OperationResult::Connect)
@@ -313,24 +1107,8 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         // Wait for close operation to complete.
         match queue.close_coroutine(yielder).await {
             Ok(()) => {
-                // If the queue was bound, remove from the socket id to queue descriptor table.
-                if let Some(local) = queue.local() {
-                    // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
-                    self.runtime.remove_socket_id_to_qd(&SocketId::Passive(
-                        unwrap_socketaddr(local).expect("we only support IPv4"),
-                    ));
-
-                    // Check if this is an ephemeral port.
-                    if SharedDemiRuntime::is_private_ephemeral_port(local.port())
-                        && queue.get_qtype() == QType::UdpSocket
-                    {
-                        // Allocate ephemeral port from the pool, to leave  ephemeral port allocator in a consistent state.
-                        if let Err(e) = self.runtime.free_ephemeral_port(local.port()) {
-                            let cause: String = format!("close(): Could not free ephemeral port");
-                            warn!("{}: {:?}", cause, e);
-                        }
-                    }
-                }
+                self.release_local_address(qd, &queue);
+                self.closed_op_counts.insert(qd, queue.op_counts());
                 // Remove the queue from the queue table. Expect is safe here because we looked up the queue to
                 // schedule this coroutine and no other close coroutine should be able to run due to state machine
                 // checks.
@@ -357,11 +1135,63 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
             return Err(Fail::new(libc::EINVAL, &cause));
         };
 
+        self.schedule_push(qd, buf, None)
+    }
+
+    /// Synchronous code to push [buf] like [Self::push], except that the push fails with `ETIMEDOUT` if it has not
+    /// completed by `deadline`, as told by the clock of the stack.
+    pub fn push_until(&mut self, qd: QDesc, sga: &demi_sgarray_t, deadline: Instant) -> Result<QToken, Fail> {
+        let buf: DemiBuffer = self.runtime.clone_sgarray(sga)?;
+        if buf.len() == 0 {
+            let cause: String = format!("zero-length buffer");
+            warn!("push_until(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, &cause));
+        };
+
+        self.schedule_push(qd, buf, Some(deadline))
+    }
+
+    /// Pushes [buf] to every SharedNetworkQueue in [qds] and returns a token per queue, in the same order. The queues
+    /// share the data of [buf] instead of each getting its own copy of it. Nothing is pushed unless every queue
+    /// exists and a push can be scheduled on each of them.
+    pub fn push_to_many(&mut self, qds: &[QDesc], buf: DemiBuffer) -> Result<Vec<QToken>, Fail> {
+        if buf.len() == 0 {
+            let cause: String = format!("zero-length buffer");
+            warn!("push_to_many(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, &cause));
+        };
+        for qd in qds {
+            self.get_shared_queue(qd)?;
+        }
+
+        let mut qts: Vec<QToken> = Vec::with_capacity(qds.len());
+        for qd in qds {
+            // Clones are views into the same data, so this does not copy the payload.
+            match self.schedule_push(*qd, buf.clone(), None) {
+                Ok(qt) => qts.push(qt),
+                Err(e) => {
+                    // The pushes scheduled so far have not run yet, so abort them to push nothing at all.
+                    for qt in qts {
+                        self.runtime.abort_coroutine(qt);
+                    }
+                    return Err(e);
+                },
+            }
+        }
+        Ok(qts)
+    }
+
+    /// Schedules the coroutine that pushes [buf] to the SharedNetworkQueue of [qd].
+    fn schedule_push(&mut self, qd: QDesc, buf: DemiBuffer, deadline: Option<Instant>) -> Result<QToken, Fail> {
         let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
         let coroutine_constructor = || -> Result<QToken, Fail> {
             let task_name: String = format!("NetworkLibOS::push for qd={:?}", qd);
-            let coroutine_factory =
-                |yielder| -> Pin<Box<Operation>> { Box::pin(self.clone().push_coroutine(qd, buf, yielder).fuse()) };
+            let coroutine_factory = |yielder: Yielder| -> Pin<Box<Operation>> {
+                let expiry: Option<TimerExpiry> =
+                    deadline.map(|deadline| self.runtime.get_timer().expire_at(deadline, yielder.get_handle()));
+                let coroutine = self.clone().push_coroutine(qd, buf, yielder);
+                Box::pin(with_expiry(coroutine, expiry).fuse())
+            };
             self.runtime
                 .clone()
                 .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
@@ -398,11 +1228,12 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         trace!("pushto() qd={:?}", qd);
 
         let buf: DemiBuffer = self.runtime.clone_sgarray(sga)?;
-        if buf.len() == 0 {
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        // Zero-length datagrams are meaningful (e.g. as heartbeats), so only reject empty buffers on stream sockets.
+        if buf.len() == 0 && queue.get_qtype() != QType::UdpSocket {
             return Err(Fail::new(libc::EINVAL, "zero-length buffer"));
         }
 
-        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
         let coroutine_constructor = || -> Result<QToken, Fail> {
             let task_name: String = format!("NetworkLibOS::pushto for qd={:?}", qd);
             let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
@@ -465,6 +1296,99 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         queue.pop(coroutine_constructor)
     }
 
+    /// Starts probing whether another host on the link uses [ipv4_addr], before claiming it as our address. The probe
+    /// is issued on the SharedNetworkQueue [qd], so that closing the queue cancels it. The operation completes like a
+    /// connect if no host claims the address, and fails with `EADDRINUSE` otherwise.
+    pub fn probe_address(&mut self, qd: QDesc, ipv4_addr: Ipv4Addr) -> Result<QToken, Fail> {
+        trace!("probe_address() qd={:?}, ipv4_addr={:?}", qd, ipv4_addr);
+
+        // Make sure the queue exists.
+        self.get_shared_queue(&qd)?;
+        let task_name: String = format!("NetworkLibOS::probe_address for ipv4_addr={:?}", ipv4_addr);
+        let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
+            Box::pin(self.clone().probe_address_coroutine(qd, ipv4_addr, yielder).fuse())
+        };
+        self.runtime
+            .clone()
+            .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+    }
+
+    /// Asynchronous code to probe an address.
+    async fn probe_address_coroutine(
+        self,
+        qd: QDesc,
+        ipv4_addr: Ipv4Addr,
+        yielder: Yielder,
+    ) -> (QDesc, OperationResult) {
+        match self.transport.clone().probe_address(ipv4_addr, yielder).await {
+            Ok(()) => (qd, OperationResult::Connect),
+            Err(e) => {
+                warn!("probe_address() ipv4_addr={:?}: {:?}", ipv4_addr, &e);
+                (qd, OperationResult::Failed(e))
+            },
+        }
+    }
+
+    /// Synchronous code to pop data like [Self::pop], except that the pop fails with `ETIMEDOUT` if it has not completed
+    /// by `deadline`, as told by the clock of the stack.
+    pub fn pop_until(&mut self, qd: QDesc, size: Option<usize>, deadline: Instant) -> Result<QToken, Fail> {
+        trace!("pop_until() qd={:?}, size={:?}, deadline={:?}", qd, size, deadline);
+
+        // We just assert 'size' here, because it was previously checked at PDPIX layer.
+        debug_assert!(size.is_none() || ((size.unwrap() > 0) && (size.unwrap() <= limits::POP_SIZE_MAX)));
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::pop_until for qd={:?}", qd);
+            let coroutine_factory = |yielder: Yielder| -> Pin<Box<Operation>> {
+                let expiry: TimerExpiry = self.runtime.get_timer().expire_at(deadline, yielder.get_handle());
+                let coroutine = self.clone().pop_coroutine(qd, size, yielder);
+                Box::pin(with_expiry(coroutine, Some(expiry)).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.pop(coroutine_constructor)
+    }
+
+    /// Synchronous code to pop data like [Self::pop], except that cancelling [token] completes the pop with
+    /// `ECANCELED`.
+    pub fn pop_cancellable(
+        &mut self,
+        qd: QDesc,
+        size: Option<usize>,
+        mut token: CancellationToken,
+    ) -> Result<QToken, Fail> {
+        trace!("pop_cancellable() qd={:?}, size={:?}", qd, size);
+
+        // We just assert 'size' here, because it was previously checked at PDPIX layer.
+        debug_assert!(size.is_none() || ((size.unwrap() > 0) && (size.unwrap() <= limits::POP_SIZE_MAX)));
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::pop_cancellable for qd={:?}", qd);
+            let coroutine_factory = |yielder: Yielder| -> Pin<Box<Operation>> {
+                let registration: CancellationRegistration = token.register(yielder.get_handle());
+                let coroutine = self.clone().pop_coroutine(qd, size, yielder);
+                Box::pin(
+                    async move {
+                        // Keep the pop tied to the token until it completes.
+                        let _registration: CancellationRegistration = registration;
+                        coroutine.await
+                    }
+                    .fuse(),
+                )
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.pop(coroutine_constructor)
+    }
+
     /// Asynchronous code to pop data from a SharedNetworkQueue and its underlying POSIX socket of optional [size]. This
     /// function returns a coroutine that asynchronously runs pop and performs any necessary multi-queue operations at
     /// the libOS-level after the pop succeeds or fails.
@@ -492,12 +1416,286 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
         }
     }
 
+    /// Synchronous code to pop data from a SharedNetworkQueue into [buf], rather than into a buffer allocated for the
+    /// pop. The operation completes with [buf], trimmed to the bytes received.
+    pub fn pop_into(&mut self, qd: QDesc, buf: DemiBuffer, size: Option<usize>) -> Result<QToken, Fail> {
+        trace!("pop_into() qd={:?}, len={:?}, size={:?}", qd, buf.len(), size);
+
+        // We just assert 'size' here, because it was previously checked at PDPIX layer.
+        debug_assert!(size.is_none() || ((size.unwrap() > 0) && (size.unwrap() <= limits::POP_SIZE_MAX)));
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::pop_into for qd={:?}", qd);
+            let coroutine_factory = |yielder| -> Pin<Box<Operation>> {
+                Box::pin(self.clone().pop_into_coroutine(qd, buf, size, yielder).fuse())
+            };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.pop(coroutine_constructor)
+    }
+
+    /// Asynchronous code to pop data from a SharedNetworkQueue into [buf]. This function returns a coroutine that
+    /// asynchronously runs the pop and hands [buf] back when it completes.
+    async fn pop_into_coroutine(
+        self,
+        qd: QDesc,
+        mut buf: DemiBuffer,
+        size: Option<usize>,
+        yielder: Yielder,
+    ) -> (QDesc, OperationResult) {
+        // Grab the queue, make sure it hasn't been closed in the meantime.
+        let mut queue: SharedNetworkQueue<T> = match self.get_shared_queue(&qd) {
+            Ok(queue) => queue,
+            Err(e) => return (qd, OperationResult::Failed(e)),
+        };
+
+        // Wait for pop to complete.
+        match queue.pop_into_coroutine(&mut buf, size, yielder).await {
+            // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+            Ok(Some(addr)) => (
+                qd,
+                OperationResult::Pop(Some(unwrap_socketaddr(addr).expect("we only support IPv4")), buf),
+            ),
+            Ok(None) => (qd, OperationResult::Pop(None, buf)),
+            Err(e) => {
+                warn!("pop_into() qd={:?}: {:?}", qd, &e);
+                (qd, OperationResult::Failed(e))
+            },
+        }
+    }
+
+    /// Pops all data currently buffered on a SharedNetworkQueue in a single buffer, or waits for data if there is none.
+    pub fn pop_all(&mut self, qd: QDesc) -> Result<QToken, Fail> {
+        trace!("pop_all() qd={:?}", qd);
+
+        let mut queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let coroutine_constructor = || -> Result<QToken, Fail> {
+            let task_name: String = format!("NetworkLibOS::pop_all for qd={:?}", qd);
+            let coroutine_factory =
+                |yielder| -> Pin<Box<Operation>> { Box::pin(self.clone().pop_all_coroutine(qd, yielder).fuse()) };
+            self.runtime
+                .clone()
+                .insert_coroutine_with_tracking(&task_name, coroutine_factory, qd)
+        };
+
+        queue.pop(coroutine_constructor)
+    }
+
+    /// Asynchronous code to pop all buffered data from a SharedNetworkQueue.
+    async fn pop_all_coroutine(self, qd: QDesc, yielder: Yielder) -> (QDesc, OperationResult) {
+        let mut queue: SharedNetworkQueue<T> = match self.get_shared_queue(&qd) {
+            Ok(queue) => queue,
+            Err(e) => return (qd, OperationResult::Failed(e)),
+        };
+
+        match queue.pop_all_coroutine(yielder).await {
+            // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+            Ok((Some(addr), buf)) => (
+                qd,
+                OperationResult::Pop(Some(unwrap_socketaddr(addr).expect("we only support IPv4")), buf),
+            ),
+            Ok((None, buf)) => (qd, OperationResult::Pop(None, buf)),
+            Err(e) => {
+                warn!("pop_all() qd={:?}: {:?}", qd, &e);
+                (qd, OperationResult::Failed(e))
+            },
+        }
+    }
+
+    /// Describes every open queue.
+    pub fn queue_infos(&self) -> Vec<QueueInfo> {
+        self.runtime
+            .get_qtable()
+            .get_qds()
+            .into_iter()
+            .filter_map(|qd| self.queue_info(qd).ok())
+            .collect()
+    }
+
+    /// Hard-closes and releases every open queue that `pred` matches, without waiting for the connections to shut down
+    /// gracefully, and returns the descriptors of the queues that were closed.
+    pub fn close_matching<F: Fn(&QueueInfo) -> bool>(&mut self, pred: F) -> Vec<QDesc> {
+        let mut closed: Vec<QDesc> = Vec::new();
+        for info in self.queue_infos() {
+            if !pred(&info) {
+                continue;
+            }
+            let qd: QDesc = info.qd;
+            let mut queue: SharedNetworkQueue<T> = match self.runtime.free_queue::<SharedNetworkQueue<T>>(&qd) {
+                Ok(queue) => queue,
+                Err(e) => {
+                    warn!("close_matching(): cannot release queue (qd={:?}): {:?}", qd, e);
+                    continue;
+                },
+            };
+            self.release_local_address(qd, &queue);
+            // Counts of an earlier queue with this descriptor no longer apply.
+            self.closed_op_counts.remove(&qd);
+            if let Err(e) = queue.hard_close() {
+                warn!("close_matching(): cannot hard close queue (qd={:?}): {:?}", qd, e);
+            }
+            closed.push(qd);
+        }
+        closed
+    }
+
+    /// Gracefully closes every open queue and polls until all of the closes complete or `timeout` expires. Queues that
+    /// are still open afterwards are hard-closed and released, and the transport is stopped. Returns the descriptors of
+    /// the queues that could not be drained before the timeout expired.
+    pub fn shutdown_all(&mut self, timeout: Duration) -> Vec<QDesc> {
+        trace!("shutdown_all() timeout={:?}", timeout);
+        let start: Instant = Instant::now();
+
+        // 1. Initiate a graceful close on every open queue.
+        let mut closing: Vec<(QDesc, QToken)> = Vec::new();
+        for qd in self.runtime.get_qtable().get_qds() {
+            match self.async_close(qd) {
+                Ok(qt) => closing.push((qd, qt)),
+                Err(e) => warn!("shutdown_all(): cannot close queue (qd={:?}): {:?}", qd, e),
+            }
+        }
+
+        // 2. Poll until all closes complete or the timeout expires.
+        loop {
+            let mut pending: Vec<(QDesc, QToken)> = Vec::with_capacity(closing.len());
+            for (qd, qt) in closing {
+                match self.runtime.has_completed(qt) {
+                    Ok(false) => pending.push((qd, qt)),
+                    Ok(true) => {
+                        if let (_, OperationResult::Failed(e)) = self.runtime.remove_coroutine(qt) {
+                            warn!("shutdown_all(): cannot close queue (qd={:?}): {:?}", qd, e);
+                        }
+                    },
+                    // The result was already harvested into the completion ring.
+                    Err(_) => {},
+                }
+            }
+            closing = pending;
+            if closing.is_empty() || start.elapsed() > timeout {
+                break;
+            }
+            self.runtime.poll_and_advance_clock();
+        }
+
+        // 3. Release the queues that did not close gracefully. This cancels the closes that are still pending.
+        for qd in self.runtime.get_qtable().get_qds() {
+            if let Ok(mut queue) = self.runtime.free_queue::<SharedNetworkQueue<T>>(&qd) {
+                self.release_local_address(qd, &queue);
+                self.closed_op_counts.remove(&qd);
+                if let Err(e) = queue.hard_close() {
+                    warn!("shutdown_all(): cannot hard close queue (qd={:?}): {:?}", qd, e);
+                }
+            }
+        }
+        let mut undrained: Vec<QDesc> = Vec::with_capacity(closing.len());
+        if !closing.is_empty() {
+            self.runtime.poll();
+            for (qd, qt) in closing {
+                if let Ok(true) = self.runtime.has_completed(qt) {
+                    self.runtime.remove_coroutine(qt);
+                }
+                undrained.push(qd);
+            }
+            warn!("shutdown_all(): could not drain queues in time (qds={:?})", undrained);
+        }
+
+        // 4. Stop the transport, now that no queue uses it.
+        self.transport.stop();
+        undrained
+    }
+
+    /// Describes the queue `qd`. Accepted queues do not record their local address, so it is taken from the connection.
+    fn queue_info(&self, qd: QDesc) -> Result<QueueInfo, Fail> {
+        let queue: SharedNetworkQueue<T> = self.get_shared_queue(&qd)?;
+        let local: Option<SocketAddr> = match queue.local() {
+            Some(local) => Some(local),
+            None => queue
+                .connection_snapshot()
+                .ok()
+                .map(|snapshot| SocketAddr::V4(snapshot.local)),
+        };
+        Ok(QueueInfo {
+            qd,
+            qtype: queue.get_qtype(),
+            local,
+            remote: queue.remote(),
+            state: queue.state(),
+        })
+    }
+
+    /// Checks whether the queue `qd` may bind to `local` alongside the queue that is bound to it already, that is,
+    /// whether both of them allow sharing their local address.
+    fn may_share_addr(&self, qd: QDesc, local: SocketAddrV4) -> Result<bool, Fail> {
+        if !self.get_shared_queue(&qd)?.reuse_port() {
+            return Ok(false);
+        }
+        match self.runtime.get_qd_from_socket_id(&SocketId::Passive(local)) {
+            Some(bound_qd) => Ok(self.get_shared_queue(&bound_qd)?.reuse_port()),
+            None => Ok(false),
+        }
+    }
+
+    /// Releases the local address of the queue `qd`, which is being closed: removes the queue from the socket id to queue
+    /// descriptor table and, if it is a UDP socket bound to an ephemeral port, returns the port to the pool.
+    fn release_local_address(&mut self, qd: QDesc, queue: &SharedNetworkQueue<T>) {
+        if let Some(local) = queue.local() {
+            // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+            self.remove_passive_socket_id(qd, unwrap_socketaddr(local).expect("we only support IPv4"));
+
+            // Check if this is an ephemeral port.
+            if SharedDemiRuntime::is_private_ephemeral_port(local.port()) && queue.get_qtype() == QType::UdpSocket {
+                // Allocate ephemeral port from the pool, to leave  ephemeral port allocator in a consistent state.
+                if let Err(e) = self.runtime.free_ephemeral_port(local.port()) {
+                    let cause: String = format!("close(): Could not free ephemeral port");
+                    warn!("{}: {:?}", cause, e);
+                }
+            }
+        }
+    }
+
+    /// Removes the queue `qd` from the socket id to queue descriptor table. If other queues share its local address, one
+    /// of them takes its place.
+    fn remove_passive_socket_id(&mut self, qd: QDesc, local: SocketAddrV4) {
+        let socket_id: SocketId = SocketId::Passive(local);
+        if self.runtime.get_qd_from_socket_id(&socket_id) != Some(qd) {
+            return;
+        }
+        self.runtime.remove_socket_id_to_qd(&socket_id);
+        for other_qd in self.runtime.get_qtable().get_qds() {
+            if other_qd == qd {
+                continue;
+            }
+            if let Ok(queue) = self.get_shared_queue(&other_qd) {
+                if queue.reuse_port() && queue.local() == Some(SocketAddr::V4(local)) {
+                    self.runtime.insert_socket_id_to_qd(socket_id, other_qd);
+                    return;
+                }
+            }
+        }
+    }
+
     /// This function gets a shared queue reference out of the I/O queue table. The type if a ref counted pointer to the
     /// queue itself.
     fn get_shared_queue(&self, qd: &QDesc) -> Result<SharedNetworkQueue<T>, Fail> {
         self.runtime.get_shared_queue::<SharedNetworkQueue<T>>(qd)
     }
 
+    /// Looks up a small-buffer pool created with [create_buffer_pool](Self::create_buffer_pool).
+    fn get_buffer_pool(&self, pool_id: PoolId) -> Result<&SharedSmallBufferPool, Fail> {
+        match self.buffer_pools.get(&pool_id) {
+            Some(pool) => Ok(pool),
+            None => {
+                let cause: String = format!("no such buffer pool (pool_id={:?})", pool_id);
+                error!("get_buffer_pool(): {}", cause);
+                Err(Fail::new(libc::EINVAL, &cause))
+            },
+        }
+    }
+
     /// This exposes the transport for testing purposes.
     pub fn get_transport(&self) -> T {
         self.transport.clone()
@@ -509,6 +1707,26 @@ impl<T: NetworkTransport> SharedNetworkLibOS<T> {
     }
 }
 
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Checks whether an accept that failed with `errno` only lost a single connection, so that the listening socket can
+/// keep accepting others.
+fn is_transient_accept_error(errno: i32) -> bool {
+    errno == libc::ECONNABORTED || errno == libc::ECONNRESET || errno == libc::EAGAIN
+}
+
+/// Runs `coroutine` and then drops `expiry`, so that the deadline of the operation does not outlive it in the timer.
+async fn with_expiry<F: Future<Output = (QDesc, OperationResult)>>(
+    coroutine: F,
+    expiry: Option<TimerExpiry>,
+) -> (QDesc, OperationResult) {
+    let result: (QDesc, OperationResult) = coroutine.await;
+    drop(expiry);
+    result
+}
+
 //======================================================================================================================
 // Trait Implementations
 //======================================================================================================================
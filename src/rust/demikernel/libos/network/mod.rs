@@ -5,8 +5,13 @@
 // Imports
 //======================================================================================================================
 
+pub mod accept_stream;
 pub mod libos;
 pub mod queue;
+pub mod queue_info;
+
+#[cfg(test)]
+mod tests;
 
 //======================================================================================================================
 // Imports
@@ -14,22 +19,63 @@ pub mod queue;
 
 #[cfg(all(feature = "catnap-libos"))]
 use crate::demikernel::libos::network::libos::SharedNetworkLibOS;
+#[cfg(feature = "state-history")]
+use crate::runtime::network::socket::state::SocketTransition;
 use crate::{
+    demikernel::libos::{
+        metrics::Metrics,
+        network::{
+            accept_stream::AcceptStream,
+            queue_info::QueueInfo,
+        },
+    },
     pal::constants::SOMAXCONN,
     runtime::{
+        cancellation::CancellationToken,
         fail::Fail,
-        memory::MemoryRuntime,
+        memory::{
+            BufferPoolStats,
+            DemiBuffer,
+            MemoryRuntime,
+            PoolId,
+        },
+        network::{
+            socket::{
+                operation::Shutdown,
+                option::{
+                    SocketOption,
+                    SocketOptionName,
+                },
+            },
+            types::{
+                ConnectionSnapshot,
+                DropStats,
+                MacAddress,
+                OpCounts,
+                TcpSeqState,
+                TcpState,
+            },
+        },
         types::{
             demi_qresult_t,
             demi_sgarray_t,
         },
+        wait::{
+            WaitInterrupter,
+            WaitOutcome,
+        },
         QDesc,
         QToken,
         SharedDemiRuntime,
     },
 };
 use ::std::{
-    net::SocketAddr,
+    collections::HashMap,
+    net::{
+        Ipv4Addr,
+        SocketAddr,
+        SocketAddrV4,
+    },
     time::{
         Duration,
         Instant,
@@ -166,6 +212,48 @@ impl NetworkLibOSWrapper {
         }
     }
 
+    /// Accepts up to `max` incoming connections on a TCP socket at once.
+    pub fn accept_batch(&mut self, sockqd: QDesc, max: usize) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.accept_batch(sockqd, max),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.accept_batch(sockqd, max),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.accept_batch(sockqd, max),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.accept_batch(sockqd, max),
+        }
+    }
+
+    /// Starts a background loop that keeps accepting connections on a TCP socket.
+    pub fn spawn_accept_loop(&mut self, sockqd: QDesc) -> Result<AcceptStream, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.spawn_accept_loop(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.spawn_accept_loop(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.spawn_accept_loop(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.spawn_accept_loop(sockqd),
+        }
+    }
+
+    /// Accepts an incoming connection on a TCP socket and pushes `initial` to it.
+    pub fn accept_and_push(&mut self, sockqd: QDesc, initial: DemiBuffer) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.accept_and_push(sockqd, initial),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.accept_and_push(sockqd, initial),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.accept_and_push(sockqd, initial),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.accept_and_push(sockqd, initial),
+        }
+    }
+
     /// Initiates a connection with a remote TCP peer.
     pub fn connect(&mut self, sockqd: QDesc, remote: SocketAddr) -> Result<QToken, Fail> {
         match self {
@@ -180,6 +268,66 @@ impl NetworkLibOSWrapper {
         }
     }
 
+    /// Initiates a connection with a remote TCP peer, which fails if it is not established by `deadline`.
+    pub fn connect_until(&mut self, sockqd: QDesc, remote: SocketAddr, deadline: Instant) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.connect_until(sockqd, remote, deadline),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.connect_until(sockqd, remote, deadline),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.connect_until(sockqd, remote, deadline),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.connect_until(sockqd, remote, deadline),
+        }
+    }
+
+    pub fn connect_with_data(
+        &mut self,
+        sockqd: QDesc,
+        remote: SocketAddr,
+        initial: DemiBuffer,
+    ) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.connect_with_data(sockqd, remote, initial),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.connect_with_data(sockqd, remote, initial),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.connect_with_data(sockqd, remote, initial),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.connect_with_data(sockqd, remote, initial),
+        }
+    }
+
+    /// Initiates connections to several remote TCP peers, and keeps the first one that is established.
+    pub fn connect_any(&mut self, sockqd: QDesc, remotes: Vec<SocketAddr>, stagger: Duration) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.connect_any(sockqd, remotes, stagger),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.connect_any(sockqd, remotes, stagger),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.connect_any(sockqd, remotes, stagger),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.connect_any(sockqd, remotes, stagger),
+        }
+    }
+
+    /// Shuts down one or both directions of a TCP connection, without releasing the socket.
+    pub fn shutdown(&mut self, sockqd: QDesc, how: Shutdown) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.shutdown(sockqd, how),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.shutdown(sockqd, how),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.shutdown(sockqd, how),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.shutdown(sockqd, how),
+        }
+    }
+
     pub fn async_close(&mut self, sockqd: QDesc) -> Result<QToken, Fail> {
         match self {
             #[cfg(feature = "catpowder-libos")]
@@ -207,6 +355,34 @@ impl NetworkLibOSWrapper {
         }
     }
 
+    /// Pushes a scatter-gather array to a socket, and fails if the push has not completed by `deadline`.
+    pub fn push_until(&mut self, sockqd: QDesc, sga: &demi_sgarray_t, deadline: Instant) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.push_until(sockqd, sga, deadline),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.push_until(sockqd, sga, deadline),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.push_until(sockqd, sga, deadline),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.push_until(sockqd, sga, deadline),
+        }
+    }
+
+    /// Pushes a buffer to several TCP sockets, which share its data.
+    pub fn push_to_many(&mut self, sockqds: &[QDesc], buf: DemiBuffer) -> Result<Vec<QToken>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.push_to_many(sockqds, buf),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.push_to_many(sockqds, buf),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.push_to_many(sockqds, buf),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.push_to_many(sockqds, buf),
+        }
+    }
+
     /// Pushes a scatter-gather array to a UDP socket.
     pub fn pushto(&mut self, sockqd: QDesc, sga: &demi_sgarray_t, to: SocketAddr) -> Result<QToken, Fail> {
         match self {
@@ -237,6 +413,81 @@ impl NetworkLibOSWrapper {
         }
     }
 
+    /// Pops data from a network queue into a buffer of the caller.
+    pub fn pop_into(&mut self, sockqd: QDesc, buf: DemiBuffer, size: Option<usize>) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.pop_into(sockqd, buf, size),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.pop_into(sockqd, buf, size),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.pop_into(sockqd, buf, size),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.pop_into(sockqd, buf, size),
+        }
+    }
+
+    /// Probes whether another host on the link uses an address, on behalf of a socket.
+    pub fn probe_address(&mut self, sockqd: QDesc, ipv4_addr: Ipv4Addr) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.probe_address(sockqd, ipv4_addr),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.probe_address(sockqd, ipv4_addr),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.probe_address(sockqd, ipv4_addr),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.probe_address(sockqd, ipv4_addr),
+        }
+    }
+
+    /// Pops data from a socket, and fails if the pop has not completed by `deadline`.
+    pub fn pop_until(&mut self, sockqd: QDesc, size: Option<usize>, deadline: Instant) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.pop_until(sockqd, size, deadline),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.pop_until(sockqd, size, deadline),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.pop_until(sockqd, size, deadline),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.pop_until(sockqd, size, deadline),
+        }
+    }
+
+    /// Pops data from a socket, unless `token` is cancelled first.
+    pub fn pop_cancellable(
+        &mut self,
+        sockqd: QDesc,
+        size: Option<usize>,
+        token: CancellationToken,
+    ) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.pop_cancellable(sockqd, size, token),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.pop_cancellable(sockqd, size, token),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.pop_cancellable(sockqd, size, token),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.pop_cancellable(sockqd, size, token),
+        }
+    }
+
+    /// Pops all buffered data from a socket in a single buffer.
+    pub fn pop_all(&mut self, sockqd: QDesc) -> Result<QToken, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.pop_all(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.pop_all(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.pop_all(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.pop_all(sockqd),
+        }
+    }
+
     /// Waits for a pending I/O operation to complete or a timeout to expire.
     /// This is just a single-token convenience wrapper for wait_any().
     pub fn wait(&mut self, qt: QToken, timeout: Option<Duration>) -> Result<demi_qresult_t, Fail> {
@@ -251,33 +502,124 @@ impl NetworkLibOSWrapper {
         Ok(qr)
     }
 
-    /// Waits for any of the given pending I/O operations to complete or a timeout to expire.
+    /// Waits for any of the given pending I/O operations to complete, a timeout to expire, or an interrupt.
     pub fn wait_any(&mut self, qts: &[QToken], timeout: Option<Duration>) -> Result<(usize, demi_qresult_t), Fail> {
         trace!("wait_any(): qts={:?}, timeout={:?}", qts, timeout);
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.wait_any(qts, timeout),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.wait_any(qts, timeout),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.wait_any(qts, timeout),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.wait_any(qts, timeout),
+        }
+    }
+
+    /// Waits for any of the given pending I/O operations to complete or its own timeout to expire.
+    pub fn wait_any_timeout(&mut self, qts: &[QToken], timeouts: &[Duration]) -> Result<(usize, demi_qresult_t), Fail> {
+        trace!("wait_any_timeout(): qts={:?}, timeouts={:?}", qts, timeouts);
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.wait_any_timeout(qts, timeouts),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.wait_any_timeout(qts, timeouts),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.wait_any_timeout(qts, timeouts),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.wait_any_timeout(qts, timeouts),
+        }
+    }
+
+    /// Spins on a pending I/O operation for a while before waiting for it.
+    pub fn wait_spin(&mut self, qt: QToken, spin: Duration, timeout: Option<Duration>) -> Result<demi_qresult_t, Fail> {
+        trace!("wait_spin(): qt={:?}, spin={:?}, timeout={:?}", qt, spin, timeout);
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.wait_spin(qt, spin, timeout),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.wait_spin(qt, spin, timeout),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.wait_spin(qt, spin, timeout),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.wait_spin(qt, spin, timeout),
+        }
+    }
+
+    /// Waits for a batch accept to complete and returns the connections that it accepted.
+    pub fn wait_accept_batch(
+        &mut self,
+        qt: QToken,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(QDesc, SocketAddrV4)>, Fail> {
+        trace!("wait_accept_batch(): qt={:?}, timeout={:?}", qt, timeout);
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.wait_accept_batch(qt, timeout),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.wait_accept_batch(qt, timeout),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.wait_accept_batch(qt, timeout),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.wait_accept_batch(qt, timeout),
+        }
+    }
 
-        // Get the wait start time, but only if we have a timeout.  We don't care when we started if we wait forever.
-        let start: Option<Instant> = timeout.filter(|&t| t != Duration::from_secs(0)).map(|_| Instant::now());
+    /// Waits for a pending I/O operation and reports why the wait returned.
+    pub fn wait_ex(&mut self, qt: QToken, timeout: Option<Duration>) -> Result<WaitOutcome, Fail> {
+        trace!("wait_ex(): qt={:?}, timeout={:?}", qt, timeout);
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.wait_ex(qt, timeout),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.wait_ex(qt, timeout),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.wait_ex(qt, timeout),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.wait_ex(qt, timeout),
+        }
+    }
 
-        loop {
-            // Poll first, so as to give pending operations a chance to complete.
-            self.poll();
+    /// Attaches an opaque value to a queue.
+    pub fn set_user_data(&mut self, qd: QDesc, user_data: u64) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.set_user_data(qd, user_data),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.set_user_data(qd, user_data),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.set_user_data(qd, user_data),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.set_user_data(qd, user_data),
+        }
+    }
 
-            // Search for any operation that has completed.
-            for (i, &qt) in qts.iter().enumerate() {
-                if self.has_completed(qt)? {
-                    return Ok((i, self.get_result(qt)?));
-                }
-            }
+    /// Returns the opaque value attached to a queue, if any.
+    pub fn get_user_data(&self, qd: QDesc) -> Option<u64> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.get_user_data(qd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.get_user_data(qd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.get_user_data(qd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.get_user_data(qd),
+        }
+    }
 
-            // If we have a timeout, check for expiration.
-            // For performance reasons we check for immediate expiration first.
-            if let Some(timeout) = timeout {
-                if timeout == Duration::from_secs(0)
-                    || Instant::now().duration_since(start.expect("start should be set if timeout is")) > timeout
-                {
-                    return Err(Fail::new(libc::ETIMEDOUT, "timer expired"));
-                }
-            }
+    /// Returns a handle that interrupts waits.
+    pub fn interrupter(&self) -> WaitInterrupter {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.get_interrupter(),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.get_interrupter(),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.get_interrupter(),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.get_interrupter(),
         }
     }
 
@@ -295,6 +637,34 @@ impl NetworkLibOSWrapper {
         }
     }
 
+    /// Makes poll write completed operations to a ring from which they are harvested.
+    pub fn enable_completion_ring(&mut self, capacity: usize) {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.enable_completion_ring(capacity),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.enable_completion_ring(capacity),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.enable_completion_ring(capacity),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.enable_completion_ring(capacity),
+        }
+    }
+
+    /// Copies completed operations out of the completion ring.
+    pub fn harvest(&mut self, out: &mut [demi_qresult_t]) -> usize {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime, libos: _ } => runtime.harvest(out),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime, libos: _ } => runtime.harvest(out),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime, libos: _ } => runtime.harvest(out),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime, libos: _ } => runtime.harvest(out),
+        }
+    }
+
     pub fn get_result(&mut self, qt: QToken) -> Result<demi_qresult_t, Fail> {
         match self {
             #[cfg(feature = "catpowder-libos")]
@@ -340,6 +710,597 @@ impl NetworkLibOSWrapper {
         }
     }
 
+    /// Sets an option on a socket.
+    pub fn set_socket_option(&mut self, sockqd: QDesc, option: SocketOption) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.set_socket_option(sockqd, option),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.set_socket_option(sockqd, option),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.set_socket_option(sockqd, option),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.set_socket_option(sockqd, option),
+        }
+    }
+
+    /// Reads an option back from a socket.
+    pub fn get_socket_option(&self, sockqd: QDesc, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.get_socket_option(sockqd, name),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.get_socket_option(sockqd, name),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.get_socket_option(sockqd, name),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.get_socket_option(sockqd, name),
+        }
+    }
+
+    /// Moves an established connection to a new local address without resetting it.
+    pub fn migrate_local(&mut self, sockqd: QDesc, new_local: SocketAddr) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.migrate_local(sockqd, new_local),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.migrate_local(sockqd, new_local),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.migrate_local(sockqd, new_local),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.migrate_local(sockqd, new_local),
+        }
+    }
+
+    /// Pauses the data flow of a connection.
+    pub fn pause(&mut self, sockqd: QDesc) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.pause(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.pause(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.pause(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.pause(sockqd),
+        }
+    }
+
+    /// Resumes the data flow of a paused connection.
+    pub fn resume(&mut self, sockqd: QDesc) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.resume(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.resume(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.resume(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.resume(sockqd),
+        }
+    }
+
+    /// Pauses a connection and returns its state. The connection is resumed with [Self::resume].
+    pub fn quiesce(&mut self, sockqd: QDesc) -> Result<ConnectionSnapshot, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.quiesce(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.quiesce(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.quiesce(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.quiesce(sockqd),
+        }
+    }
+
+    /// Restores the connection that a snapshot was taken of on a new queue.
+    pub fn resume_from(&mut self, snapshot: &ConnectionSnapshot) -> Result<QDesc, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.resume_from(snapshot),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.resume_from(snapshot),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.resume_from(snapshot),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.resume_from(snapshot),
+        }
+    }
+
+    /// Sets how many times, and after what delay, a bind that fails with `EADDRINUSE` is retried.
+    pub fn set_bind_retry_policy(&mut self, attempts: usize, delay: Duration) {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.set_bind_retry_policy(attempts, delay),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.set_bind_retry_policy(attempts, delay),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.set_bind_retry_policy(attempts, delay),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.set_bind_retry_policy(attempts, delay),
+        }
+    }
+
+    /// Gracefully closes every open queue, waiting up to `timeout` for the closes to complete, then releases the queues
+    /// that are still open and stops the transport. Returns the queues that could not be drained in time.
+    pub fn shutdown_all(&mut self, timeout: Duration) -> Vec<QDesc> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.shutdown_all(timeout),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.shutdown_all(timeout),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.shutdown_all(timeout),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.shutdown_all(timeout),
+        }
+    }
+
+    /// Takes a snapshot of all counters of the network stack, optionally resetting the resettable ones.
+    pub fn metrics_snapshot(&mut self, reset: bool) -> Metrics {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.metrics_snapshot(reset),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.metrics_snapshot(reset),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.metrics_snapshot(reset),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.metrics_snapshot(reset),
+        }
+    }
+
+    /// Returns the number of incoming packets dropped by the network stack, by reason.
+    pub fn drop_stats(&self) -> DropStats {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.drop_stats(),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.drop_stats(),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.drop_stats(),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.drop_stats(),
+        }
+    }
+
+    /// Returns the link address used to frame outgoing packets.
+    pub fn local_link_addr(&self) -> Result<MacAddress, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.local_link_addr(),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.local_link_addr(),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.local_link_addr(),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.local_link_addr(),
+        }
+    }
+
+    /// Overrides the link address used to frame outgoing packets.
+    pub fn set_local_link_addr(&mut self, link_addr: MacAddress) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.set_local_link_addr(link_addr),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.set_local_link_addr(link_addr),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.set_local_link_addr(link_addr),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.set_local_link_addr(link_addr),
+        }
+    }
+
+    /// Replaces the learned entries of the ARP cache, keeping static ones.
+    pub fn reload_arp(&mut self, values: HashMap<Ipv4Addr, MacAddress>) -> Result<(), Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.reload_arp(values),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.reload_arp(values),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.reload_arp(values),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.reload_arp(values),
+        }
+    }
+
+    /// Returns the number of segments retransmitted on a socket.
+    pub fn retransmit_count(&self, sockqd: QDesc) -> Result<u64, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.retransmit_count(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.retransmit_count(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.retransmit_count(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.retransmit_count(sockqd),
+        }
+    }
+
+    /// Returns the most recent state transitions of a socket, oldest first.
+    #[cfg(feature = "state-history")]
+    pub fn state_history(&self, sockqd: QDesc) -> Result<Vec<SocketTransition>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.state_history(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.state_history(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.state_history(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.state_history(sockqd),
+        }
+    }
+
+    /// Returns the hit and miss counters of the small-buffer pool of a queue, if it has one.
+    pub fn buffer_pool_stats(&self, sockqd: QDesc) -> Result<Option<BufferPoolStats>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.buffer_pool_stats(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.buffer_pool_stats(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.buffer_pool_stats(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.buffer_pool_stats(sockqd),
+        }
+    }
+
+    /// Creates an empty small-buffer pool for sockets to draw their buffers from, whose memory is placed on NUMA node
+    /// `numa_node`, if any.
+    pub fn create_buffer_pool(&mut self, numa_node: Option<u32>) -> Result<PoolId, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.create_buffer_pool(numa_node),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.create_buffer_pool(numa_node),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.create_buffer_pool(numa_node),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.create_buffer_pool(numa_node),
+        }
+    }
+
+    /// Returns the hit and miss counters of a small-buffer pool created on request.
+    pub fn pool_stats(&self, pool_id: PoolId) -> Result<BufferPoolStats, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.pool_stats(pool_id),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.pool_stats(pool_id),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.pool_stats(pool_id),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.pool_stats(pool_id),
+        }
+    }
+
+    /// Allocates a scatter-gather array to push on a socket, from the small-buffer pool of the socket.
+    pub fn sgaalloc_for(&self, sockqd: QDesc, size: usize) -> Result<demi_sgarray_t, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.sgaalloc_for(sockqd, size),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.sgaalloc_for(sockqd, size),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.sgaalloc_for(sockqd, size),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.sgaalloc_for(sockqd, size),
+        }
+    }
+
+    /// Returns the congestion window of a connection, in bytes.
+    pub fn cwnd(&self, sockqd: QDesc) -> Result<Option<u32>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.cwnd(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.cwnd(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.cwnd(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.cwnd(sockqd),
+        }
+    }
+
+    /// Returns the slow start threshold of a connection, in bytes.
+    pub fn ssthresh(&self, sockqd: QDesc) -> Result<Option<u32>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.ssthresh(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.ssthresh(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.ssthresh(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.ssthresh(sockqd),
+        }
+    }
+
+    /// Returns the number of datagrams that a UDP socket holds for popping.
+    pub fn queued_datagrams(&self, sockqd: QDesc) -> Result<usize, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.queued_datagrams(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.queued_datagrams(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.queued_datagrams(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.queued_datagrams(sockqd),
+        }
+    }
+
+    /// Describes every open socket.
+    pub fn queue_infos(&self) -> Vec<QueueInfo> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.queue_infos(),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.queue_infos(),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.queue_infos(),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.queue_infos(),
+        }
+    }
+
+    /// Hard-closes every open socket that `pred` matches and returns their descriptors.
+    pub fn close_matching<F: Fn(&QueueInfo) -> bool>(&mut self, pred: F) -> Vec<QDesc> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.close_matching(pred),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.close_matching(pred),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.close_matching(pred),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.close_matching(pred),
+        }
+    }
+
+    /// Returns whether a pop on a socket would currently block.
+    pub fn would_block_pop(&self, sockqd: QDesc) -> Result<bool, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.would_block_pop(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.would_block_pop(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.would_block_pop(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.would_block_pop(sockqd),
+        }
+    }
+
+    /// Returns the number of buffers of a socket that wait in the transport to be transmitted.
+    pub fn transport_tx_depth(&self, sockqd: QDesc) -> Result<usize, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.transport_tx_depth(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.transport_tx_depth(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.transport_tx_depth(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.transport_tx_depth(sockqd),
+        }
+    }
+
+    /// Returns the recent send rate of a connection, in bits per second.
+    pub fn send_rate_bps(&self, sockqd: QDesc) -> Result<u64, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.send_rate_bps(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.send_rate_bps(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.send_rate_bps(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.send_rate_bps(sockqd),
+        }
+    }
+
+    /// Returns the recent receive rate of a connection, in bits per second.
+    pub fn recv_rate_bps(&self, sockqd: QDesc) -> Result<u64, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.recv_rate_bps(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.recv_rate_bps(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.recv_rate_bps(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.recv_rate_bps(sockqd),
+        }
+    }
+
+    /// Returns whether a connection sent data in its SYN, using TCP Fast Open.
+    pub fn used_fast_open(&self, sockqd: QDesc) -> Result<bool, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.used_fast_open(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.used_fast_open(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.used_fast_open(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.used_fast_open(sockqd),
+        }
+    }
+
+    /// Returns when a connection was established, if it was.
+    pub fn established_at(&self, sockqd: QDesc) -> Result<Option<Instant>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.established_at(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.established_at(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.established_at(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.established_at(sockqd),
+        }
+    }
+
+    /// Returns when a push or pop on a socket last completed.
+    pub fn last_activity(&self, sockqd: QDesc) -> Result<Instant, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.last_activity(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.last_activity(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.last_activity(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.last_activity(sockqd),
+        }
+    }
+
+    /// Returns the number of half-open connections to a listening socket.
+    pub fn half_open_count(&self, sockqd: QDesc) -> Result<usize, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.half_open_count(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.half_open_count(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.half_open_count(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.half_open_count(sockqd),
+        }
+    }
+
+    /// Returns the number of accepts on a queue that have not completed yet.
+    pub fn pending_accepts(&self, sockqd: QDesc) -> Result<usize, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.pending_accepts(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.pending_accepts(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.pending_accepts(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.pending_accepts(sockqd),
+        }
+    }
+
+    /// Returns the backlog that a queue listens with.
+    pub fn backlog(&self, sockqd: QDesc) -> Result<Option<usize>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.backlog(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.backlog(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.backlog(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.backlog(sockqd),
+        }
+    }
+
+    /// Returns the effective send buffer size of a socket, if it is bounded.
+    pub fn send_buffer_size(&self, sockqd: QDesc) -> Result<Option<usize>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.send_buffer_size(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.send_buffer_size(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.send_buffer_size(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.send_buffer_size(sockqd),
+        }
+    }
+
+    /// Returns the largest payload that fits in a single packet on the path of a socket.
+    pub fn path_mtu(&self, sockqd: QDesc) -> Result<u16, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.path_mtu(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.path_mtu(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.path_mtu(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.path_mtu(sockqd),
+        }
+    }
+
+    /// Returns the number of operations that completed successfully on a queue, by type.
+    pub fn op_counts(&self, sockqd: QDesc) -> Result<OpCounts, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.op_counts(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.op_counts(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.op_counts(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.op_counts(sockqd),
+        }
+    }
+
+    /// Returns the RFC 793 state of a TCP connection.
+    pub fn tcp_state(&self, sockqd: QDesc) -> Result<Option<TcpState>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.tcp_state(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.tcp_state(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.tcp_state(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.tcp_state(sockqd),
+        }
+    }
+
+    /// Returns the initial sequence number that a TCP connection chose for our end.
+    pub fn local_isn(&self, sockqd: QDesc) -> Result<Option<u32>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.local_isn(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.local_isn(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.local_isn(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.local_isn(sockqd),
+        }
+    }
+
+    /// Returns the send and receive sequence numbers and windows of a TCP connection.
+    pub fn tcp_sequence_state(&self, sockqd: QDesc) -> Result<Option<TcpSeqState>, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.tcp_sequence_state(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.tcp_sequence_state(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.tcp_sequence_state(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.tcp_sequence_state(sockqd),
+        }
+    }
+
+    /// Returns the DSCP that outgoing packets of a socket are marked with.
+    pub fn dscp(&self, sockqd: QDesc) -> Result<u8, Fail> {
+        match self {
+            #[cfg(feature = "catpowder-libos")]
+            NetworkLibOSWrapper::Catpowder { runtime: _, libos } => libos.dscp(sockqd),
+            #[cfg(all(feature = "catnap-libos"))]
+            NetworkLibOSWrapper::Catnap { runtime: _, libos } => libos.dscp(sockqd),
+            #[cfg(feature = "catnip-libos")]
+            NetworkLibOSWrapper::Catnip { runtime: _, libos } => libos.dscp(sockqd),
+            #[cfg(feature = "catloop-libos")]
+            NetworkLibOSWrapper::Catloop { runtime: _, libos } => libos.dscp(sockqd),
+        }
+    }
+
     pub fn has_completed(&self, qt: QToken) -> Result<bool, Fail> {
         match self {
             #[cfg(feature = "catpowder-libos")]
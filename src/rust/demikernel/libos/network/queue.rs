@@ -5,36 +5,73 @@
 // Imports
 //======================================================================================================================
 
+#[cfg(feature = "state-history")]
+use crate::runtime::network::socket::state::SocketTransition;
 use crate::runtime::{
     fail::Fail,
     limits,
-    memory::DemiBuffer,
+    memory::{
+        BufferPoolStats,
+        DemiBuffer,
+        PoolId,
+        SharedSmallBufferPool,
+    },
     network::{
         socket::{
-            operation::SocketOp,
-            state::SocketStateMachine,
+            operation::{
+                Shutdown,
+                SocketOp,
+            },
+            option::{
+                SocketOption,
+                SocketOptionName,
+            },
+            state::{
+                SocketState,
+                SocketStateMachine,
+            },
         },
         transport::NetworkTransport,
+        types::{
+            ConnectionSnapshot,
+            OpCounts,
+            TcpOption,
+            TcpSeqState,
+            TcpState,
+        },
     },
     queue::{
         IoQueue,
         QType,
     },
     scheduler::Yielder,
+    timer::SharedTimer,
     QToken,
     SharedObject,
 };
+use ::futures::{
+    future::LocalBoxFuture,
+    select_biased,
+    stream::FuturesUnordered,
+    FutureExt,
+    StreamExt,
+};
 use ::socket2::{
     Domain,
     Type,
 };
 use ::std::{
     any::Any,
+    collections::VecDeque,
     net::SocketAddr,
     ops::{
         Deref,
         DerefMut,
     },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 //======================================================================================================================
@@ -56,18 +93,46 @@ pub struct NetworkQueue<T: NetworkTransport> {
     remote: Option<SocketAddr>,
     /// Underlying network transport.
     transport: T,
+    /// May this queue bind to a port below the privileged port floor?
+    allow_privileged_bind: bool,
+    /// May this queue share its local address with other queues that allow it too?
+    reuse_port: bool,
+    /// Clock for the timestamps of the queue.
+    timer: SharedTimer,
+    /// When the underlying connection was established, if it was.
+    established_at: Option<Instant>,
+    /// When a push or pop on this queue last completed, or when the queue was created or established if none did yet.
+    last_activity: Instant,
+    /// Small-buffer pool of this queue, if it does not draw the buffers of its pops from the one of the thread.
+    buffer_pool: Option<SharedSmallBufferPool>,
+    /// Identifier of the small-buffer pool of this queue, if the libOS created it on request rather than for this queue.
+    buffer_pool_id: Option<PoolId>,
+    /// Number of operations that completed successfully on this queue, by type.
+    op_counts: OpCounts,
+    /// Backlog that this queue listens with, if it listens.
+    backlog: Option<usize>,
+    /// Number of accepts on this queue that have not completed yet.
+    pending_accepts: usize,
 }
 
 #[derive(Clone)]
 pub struct SharedNetworkQueue<T: NetworkTransport>(SharedObject<NetworkQueue<T>>);
 
+/// Counts an accept as pending on its queue for as long as it lives, so that accepts that are dropped before they
+/// complete, such as those of a cancelled coroutine, do not stay counted.
+struct PendingAccept<T: NetworkTransport>(SharedNetworkQueue<T>);
+
+/// Queues of the connection attempts of a connect_any(), which are aborted when dropped, such as when the coroutine is
+/// cancelled. The winning attempt is taken out before.
+struct ConnectAttempts<T: NetworkTransport>(Vec<SharedNetworkQueue<T>>);
+
 //======================================================================================================================
 // Associated Functions
 //======================================================================================================================
 
 /// Associate Functions for Catnap LibOS
 impl<T: NetworkTransport> SharedNetworkQueue<T> {
-    pub fn new(domain: Domain, typ: Type, transport: &mut T) -> Result<Self, Fail> {
+    pub fn new(domain: Domain, typ: Type, transport: &mut T, timer: SharedTimer) -> Result<Self, Fail> {
         // This was previously checked in the LibOS layer.
         debug_assert!(typ == Type::STREAM || typ == Type::DGRAM);
 
@@ -79,13 +144,61 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
         };
 
         let socket: T::SocketDescriptor = transport.socket(domain, typ)?;
+        let now: Instant = timer.now();
+        let state_machine: SocketStateMachine = SocketStateMachine::new_unbound(typ);
+        #[cfg(feature = "state-history")]
+        let state_machine: SocketStateMachine = state_machine.with_history(timer.clone());
         Ok(Self(SharedObject::new(NetworkQueue::<T> {
             qtype,
-            state_machine: SocketStateMachine::new_unbound(typ),
+            state_machine,
             socket,
             local: None,
             remote: None,
             transport: transport.clone(),
+            allow_privileged_bind: false,
+            reuse_port: false,
+            timer,
+            established_at: None,
+            last_activity: now,
+            buffer_pool: None,
+            buffer_pool_id: None,
+            op_counts: OpCounts::default(),
+            backlog: None,
+            pending_accepts: 0,
+        })))
+    }
+
+    /// Creates a queue for the TCP connection that `snapshot` was taken of, once the transport restored it on a fresh
+    /// socket.
+    pub fn resume_from(snapshot: &ConnectionSnapshot, transport: &mut T, timer: SharedTimer) -> Result<Self, Fail> {
+        let mut socket: T::SocketDescriptor = transport.socket(Domain::IPV4, Type::STREAM)?;
+        if let Err(e) = transport.resume_from(&mut socket, snapshot) {
+            if let Err(e) = transport.hard_close(&mut socket) {
+                warn!("resume_from(): could not close socket: {:?}", e);
+            }
+            return Err(e);
+        }
+        let now: Instant = timer.now();
+        let state_machine: SocketStateMachine = SocketStateMachine::new_established();
+        #[cfg(feature = "state-history")]
+        let state_machine: SocketStateMachine = state_machine.with_history(timer.clone());
+        Ok(Self(SharedObject::new(NetworkQueue::<T> {
+            qtype: QType::TcpSocket,
+            state_machine,
+            socket,
+            local: Some(SocketAddr::V4(snapshot.local)),
+            remote: Some(SocketAddr::V4(snapshot.remote)),
+            transport: transport.clone(),
+            allow_privileged_bind: false,
+            reuse_port: false,
+            timer,
+            established_at: Some(now),
+            last_activity: now,
+            buffer_pool: None,
+            buffer_pool_id: None,
+            op_counts: OpCounts::default(),
+            backlog: None,
+            pending_accepts: 0,
         })))
     }
 
@@ -114,6 +227,7 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
         match self.transport.clone().listen(&mut self.socket, backlog) {
             Ok(_) => {
                 self.state_machine.commit();
+                self.backlog = Some(backlog);
                 Ok(())
             },
             Err(e) => {
@@ -135,19 +249,44 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
 
     /// Asynchronously accepts a new connection on the queue. This function contains all of the single-queue,
     /// asynchronous code necessary to run an accept and any single-queue functionality after the accept completes.
+    /// Transports that turn connections away once the backlog is full fail the accept with ECONNABORTED when they do.
     pub async fn accept_coroutine(&mut self, yielder: Yielder) -> Result<Self, Fail> {
         self.state_machine.may_accept()?;
-        match self.transport.clone().accept(&mut self.socket, yielder).await {
+        let pending_accept: PendingAccept<T> = PendingAccept::new(self.clone());
+        let result: Result<(T::SocketDescriptor, SocketAddr), Fail> =
+            self.transport.clone().accept(&mut self.socket, yielder).await;
+        drop(pending_accept);
+        match result {
             // Operation completed.
             Ok((new_socket, saddr)) => {
                 trace!("connection accepted ({:?})", new_socket);
+                self.op_counts.accepts += 1;
+                let now: Instant = self.timer.now();
+                let state_machine: SocketStateMachine = SocketStateMachine::new_established();
+                #[cfg(feature = "state-history")]
+                let state_machine: SocketStateMachine = state_machine.with_history(self.timer.clone());
                 Ok(Self(SharedObject::new(NetworkQueue {
                     qtype: self.qtype,
-                    state_machine: SocketStateMachine::new_established(),
+                    state_machine,
                     socket: new_socket,
                     local: None,
                     remote: Some(saddr),
                     transport: self.transport.clone(),
+                    allow_privileged_bind: false,
+                    reuse_port: false,
+                    timer: self.timer.clone(),
+                    established_at: Some(now),
+                    last_activity: now,
+                    // Accepted connections draw from the same pool as their listener if the libOS created it on
+                    // request, and have pools of their own, like their listener, otherwise.
+                    buffer_pool: match self.buffer_pool_id {
+                        Some(_) => self.buffer_pool.clone(),
+                        None => self.buffer_pool.as_ref().map(|_| DemiBuffer::new_small_buffer_pool()),
+                    },
+                    buffer_pool_id: self.buffer_pool_id,
+                    op_counts: OpCounts::default(),
+                    backlog: None,
+                    pending_accepts: 0,
                 })))
             },
             Err(Fail { errno, cause: _ }) if errno == libc::EBADF => {
@@ -171,15 +310,34 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
 
     /// Asynchronously connects the target queue to a remote address. This function contains all of the single-queue,
     /// asynchronous code necessary to run a connect and any single-queue functionality after the connect completes.
-    pub async fn connect_coroutine(&mut self, remote: SocketAddr, yielder: Yielder) -> Result<(), Fail> {
+    /// If there is `initial` data, the transport sends it as part of the connect.
+    pub async fn connect_coroutine(
+        &mut self,
+        remote: SocketAddr,
+        initial: Option<DemiBuffer>,
+        yielder: Yielder,
+    ) -> Result<(), Fail> {
         // Check whether we can connect.
         self.state_machine.may_connect()?;
-        match self.transport.clone().connect(&mut self.socket, remote, yielder).await {
+        let result: Result<(), Fail> = match initial {
+            Some(initial) => {
+                self.transport
+                    .clone()
+                    .connect_with_data(&mut self.socket, remote, initial, yielder)
+                    .await
+            },
+            None => self.transport.clone().connect(&mut self.socket, remote, yielder).await,
+        };
+        match result {
             Ok(()) => {
                 // Successfully connected to remote.
                 self.state_machine.prepare(SocketOp::Established)?;
                 self.state_machine.commit();
                 self.remote = Some(remote);
+                let now: Instant = self.timer.now();
+                self.established_at = Some(now);
+                self.last_activity = now;
+                self.op_counts.connects += 1;
                 Ok(())
             },
             Err(e) => {
@@ -191,6 +349,113 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
         }
     }
 
+    /// Asynchronously connects the target queue to the first of `remotes` that accepts a connection. This function
+    /// starts an attempt to each remote in turn, a `stagger` after the previous one or right away if all attempts in
+    /// flight have failed, and lets them race. The first attempt to succeed becomes the connection of this queue and
+    /// the others are aborted.
+    pub async fn connect_any_coroutine(
+        &mut self,
+        remotes: Vec<SocketAddr>,
+        stagger: Duration,
+        yielder: Yielder,
+    ) -> Result<(), Fail> {
+        // Check whether we can connect.
+        self.state_machine.may_connect()?;
+        let mut remotes: VecDeque<SocketAddr> = remotes.into();
+        let mut candidates: ConnectAttempts<T> = ConnectAttempts(Vec::with_capacity(remotes.len()));
+        let mut attempts: FuturesUnordered<LocalBoxFuture<'static, (usize, Result<(), Fail>)>> =
+            FuturesUnordered::new();
+        let mut next_start: Instant = self.timer.now();
+        let mut last_error: Fail = Fail::new(libc::ECONNREFUSED, "no remote accepted the connection");
+        let result: Result<usize, Fail> = loop {
+            // Start the next attempt when its turn comes, or right away if no other attempt is in flight.
+            if !remotes.is_empty() && (attempts.is_empty() || self.timer.now() >= next_start) {
+                let remote: SocketAddr = remotes
+                    .pop_front()
+                    .expect("should have a remote because we checked that there is one");
+                match self.new_connect_attempt() {
+                    Ok(mut candidate) => {
+                        let index: usize = candidates.0.len();
+                        candidates.0.push(candidate.clone());
+                        attempts.push(
+                            async move { (index, candidate.connect_coroutine(remote, None, Yielder::new()).await) }
+                                .boxed_local(),
+                        );
+                    },
+                    Err(e) => last_error = e,
+                }
+                next_start = self.timer.now() + stagger;
+                continue;
+            }
+            if attempts.is_empty() {
+                break Err(last_error);
+            }
+
+            // Wait for an attempt to finish or for the turn of the next one, whichever comes first. The timer may wake
+            // us up early for the turn of an attempt that already started, so we check the deadline again above.
+            let deadline: Option<Instant> = if remotes.is_empty() { None } else { Some(next_start) };
+            let timer: SharedTimer = self.timer.clone();
+            let turn_future = async {
+                match deadline {
+                    Some(deadline) => timer.wait_until(deadline, &yielder).await,
+                    None => yielder.yield_until_wake().await,
+                }
+            }
+            .fuse();
+            futures::pin_mut!(turn_future);
+            select_biased! {
+                (index, result) = attempts.select_next_some() => match result {
+                    Ok(()) => break Ok(index),
+                    Err(e) => last_error = e,
+                },
+                result = turn_future => if let Err(e) = result {
+                    break Err(e);
+                },
+            }
+        };
+
+        // Abort all attempts that are still in flight, but the winning one.
+        drop(attempts);
+        match result {
+            Ok(index) => {
+                let mut winner: Self = candidates.0.swap_remove(index);
+                drop(candidates);
+                // Take over the connection of the winning attempt, and close the socket that this queue started with.
+                std::mem::swap(&mut self.socket, &mut winner.socket);
+                self.state_machine.prepare(SocketOp::Established)?;
+                self.state_machine.commit();
+                self.remote = winner.remote;
+                let now: Instant = self.timer.now();
+                self.established_at = Some(now);
+                self.last_activity = now;
+                self.op_counts.connects += 1;
+                winner.hard_close()?;
+                Ok(())
+            },
+            Err(e) => {
+                drop(candidates);
+                // If no attempt succeeds, we close the socket.
+                self.state_machine.prepare(SocketOp::Closed)?;
+                self.state_machine.commit();
+                Err(e)
+            },
+        }
+    }
+
+    /// Creates a new queue on the same transport, to attempt one of the connections of a connect_any().
+    fn new_connect_attempt(&self) -> Result<Self, Fail> {
+        // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+        let mut candidate: Self = Self::new(
+            Domain::IPV4,
+            Type::STREAM,
+            &mut self.transport.clone(),
+            self.timer.clone(),
+        )?;
+        candidate.state_machine.prepare(SocketOp::Connect)?;
+        candidate.state_machine.commit();
+        Ok(candidate)
+    }
+
     /// Start an asynchronous coroutine to close this queue.
     pub fn close<F>(&mut self, coroutine_constructor: F) -> Result<QToken, Fail>
     where
@@ -221,6 +486,7 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
             Ok(()) => {
                 self.state_machine.prepare(SocketOp::Closed)?;
                 self.state_machine.commit();
+                self.op_counts.closes += 1;
                 Ok(())
             },
             Err(e) => Err(e),
@@ -249,6 +515,8 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
         match self.transport.clone().push(&mut self.socket, buf, addr, yielder).await {
             Ok(()) => {
                 debug_assert_eq!(buf.len(), 0);
+                self.last_activity = self.timer.now();
+                self.op_counts.pushes += 1;
                 Ok(())
             },
             Err(e) => return Err(e),
@@ -275,7 +543,7 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
     ) -> Result<(Option<SocketAddr>, DemiBuffer), Fail> {
         self.state_machine.may_pop()?;
         let size: usize = size.unwrap_or(limits::RECVBUF_SIZE_MAX);
-        let mut buf: DemiBuffer = DemiBuffer::new(size as u16);
+        let mut buf: DemiBuffer = self.alloc_buffer(size as u16);
 
         // Check that we allocated a DemiBuffer that is big enough.
         debug_assert_eq!(buf.len(), size);
@@ -285,11 +553,48 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
             .pop(&mut self.socket, &mut buf, size, yielder)
             .await
         {
-            Ok(addr) => Ok((addr, buf)),
+            Ok(addr) => {
+                self.last_activity = self.timer.now();
+                self.op_counts.pops += 1;
+                Ok((addr, buf))
+            },
             Err(e) => Err(e),
         }
     }
 
+    /// Asynchronously pops data from the queue into `buf`, rather than into a buffer allocated for the pop. At most
+    /// `size` bytes are popped, or as many as `buf` holds if it is smaller, and `buf` is trimmed to the bytes actually
+    /// received.
+    pub async fn pop_into_coroutine(
+        &mut self,
+        buf: &mut DemiBuffer,
+        size: Option<usize>,
+        yielder: Yielder,
+    ) -> Result<Option<SocketAddr>, Fail> {
+        self.state_machine.may_pop()?;
+        if buf.is_empty() {
+            let cause: &str = "cannot pop into an empty buffer";
+            error!("pop_into_coroutine(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, cause));
+        }
+        let size: usize = size.unwrap_or(limits::RECVBUF_SIZE_MAX).min(buf.len());
+        buf.trim(buf.len() - size)?;
+        let addr: Option<SocketAddr> = self.transport.clone().pop(&mut self.socket, buf, size, yielder).await?;
+        self.last_activity = self.timer.now();
+        self.op_counts.pops += 1;
+        Ok(addr)
+    }
+
+    /// Asynchronously pops all buffered data from the queue in a single buffer.
+    pub async fn pop_all_coroutine(&mut self, yielder: Yielder) -> Result<(Option<SocketAddr>, DemiBuffer), Fail> {
+        self.state_machine.may_pop()?;
+        let result: (Option<SocketAddr>, DemiBuffer) =
+            self.transport.clone().pop_all(&mut self.socket, yielder).await?;
+        self.last_activity = self.timer.now();
+        self.op_counts.pops += 1;
+        Ok(result)
+    }
+
     /// Generic function for spawning a control-path coroutine on [self].
     fn do_generic_sync_control_path_call<F>(&mut self, coroutine_constructor: F) -> Result<QToken, Fail>
     where
@@ -316,15 +621,276 @@ impl<T: NetworkTransport> SharedNetworkQueue<T> {
         self.local
     }
 
+    /// Returns the state of the underlying socket.
+    pub fn state(&self) -> SocketState {
+        self.state_machine.current()
+    }
+
     pub fn remote(&self) -> Option<SocketAddr> {
         self.remote
     }
+
+    /// Returns when the underlying connection was established, or `None` if it was never connected.
+    pub fn established_at(&self) -> Option<Instant> {
+        self.established_at
+    }
+
+    /// Returns when a push or pop on this queue last completed. Until one does, this is when the queue was created or,
+    /// if it is connected, established.
+    pub fn last_activity(&self) -> Instant {
+        self.last_activity
+    }
+
+    /// Returns the number of operations that completed successfully on this queue, by type.
+    pub fn op_counts(&self) -> OpCounts {
+        self.op_counts
+    }
+
+    /// Returns whether this queue may bind to a port below the privileged port floor.
+    pub fn allows_privileged_bind(&self) -> bool {
+        self.allow_privileged_bind
+    }
+
+    /// Sets whether this queue may bind to a port below the privileged port floor.
+    pub fn set_allow_privileged_bind(&mut self, allow: bool) {
+        self.allow_privileged_bind = allow;
+    }
+
+    /// Returns whether this queue may share its local address with other queues that allow it too.
+    pub fn reuse_port(&self) -> bool {
+        self.reuse_port
+    }
+
+    /// Returns the most recent state transitions of this queue, oldest first.
+    #[cfg(feature = "state-history")]
+    pub fn state_history(&self) -> Vec<SocketTransition> {
+        self.state_machine.history()
+    }
+
+    /// Sets whether this queue draws the buffers of its pops from a small-buffer pool of its own. Turning it off drops
+    /// the reference of the queue to its pool, which is freed once the last of its buffers is released.
+    pub fn set_buffer_pool(&mut self, enabled: bool) {
+        match (enabled, self.buffer_pool.is_some()) {
+            (true, false) => self.buffer_pool = Some(DemiBuffer::new_small_buffer_pool()),
+            (false, true) => {
+                self.buffer_pool = None;
+                self.buffer_pool_id = None;
+            },
+            _ => {},
+        }
+    }
+
+    /// Makes this queue draw the buffers of its pops from `pool`, which the libOS created on request and identifies
+    /// with `pool_id`, instead of from the pool that it used so far.
+    pub fn set_shared_buffer_pool(&mut self, pool_id: PoolId, pool: SharedSmallBufferPool) {
+        self.buffer_pool = Some(pool);
+        self.buffer_pool_id = Some(pool_id);
+    }
+
+    /// Returns the identifier of the pool that this queue draws its buffers from, if the libOS created it on request.
+    pub fn buffer_pool_id(&self) -> Option<PoolId> {
+        self.buffer_pool_id
+    }
+
+    /// Allocates a buffer of `size` bytes from the small-buffer pool of this queue, or from the one of the thread if
+    /// it has none.
+    pub fn alloc_buffer(&self, size: u16) -> DemiBuffer {
+        match self.buffer_pool {
+            Some(ref pool) => DemiBuffer::new_pooled_from(size, pool),
+            None => DemiBuffer::new_pooled(size),
+        }
+    }
+
+    /// Returns the hit and miss counters of the small-buffer pool of this queue, or `None` if it has none.
+    pub fn buffer_pool_stats(&self) -> Option<BufferPoolStats> {
+        self.buffer_pool.as_ref().map(|pool| pool.stats())
+    }
+
+    /// Sets a socket option on the underlying socket. The queue also remembers whether it may share its local address,
+    /// which the libOS checks on bind.
+    pub fn set_socket_option(&mut self, option: SocketOption) -> Result<(), Fail> {
+        if let SocketOption::ReusePort(reuse_port) = option {
+            self.transport.clone().set_socket_option(&mut self.socket, option)?;
+            self.reuse_port = reuse_port;
+            return Ok(());
+        }
+        self.transport.clone().set_socket_option(&mut self.socket, option)
+    }
+
+    /// Reads a socket option back from the underlying socket.
+    pub fn get_socket_option(&self, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        self.transport.get_socket_option(&self.socket, name)
+    }
+
+    /// Moves the underlying connection to a new local address.
+    pub fn migrate_local(&mut self, new_local: SocketAddr) -> Result<(), Fail> {
+        self.transport.clone().migrate_local(&mut self.socket, new_local)?;
+        self.local = Some(new_local);
+        Ok(())
+    }
+
+    /// Shuts down the `how` directions of the underlying connection. Pushes fail with EPIPE once the write side was shut
+    /// down, while pops still drain the data that was received.
+    pub fn shutdown(&mut self, how: Shutdown) -> Result<(), Fail> {
+        self.state_machine.prepare(SocketOp::Shutdown)?;
+        match self.transport.clone().shutdown(&mut self.socket, how) {
+            Ok(()) => {
+                self.state_machine.commit_shutdown(how);
+                Ok(())
+            },
+            Err(e) => {
+                self.state_machine.abort();
+                Err(e)
+            },
+        }
+    }
+
+    /// Pauses or resumes the data flow of the underlying connection.
+    pub fn set_paused(&mut self, paused: bool) -> Result<(), Fail> {
+        self.transport.clone().set_paused(&mut self.socket, paused)
+    }
+
+    /// Returns the number of segments the underlying transport has retransmitted on this queue.
+    pub fn retransmit_count(&self) -> u64 {
+        self.transport.retransmit_count(&self.socket)
+    }
+
+    /// Returns the congestion window of the underlying connection, in bytes, or `None` if it has none.
+    pub fn cwnd(&self) -> Option<u32> {
+        self.transport.cwnd(&self.socket)
+    }
+
+    /// Returns the slow start threshold of the underlying connection, in bytes, or `None` if it has none.
+    pub fn ssthresh(&self) -> Option<u32> {
+        self.transport.ssthresh(&self.socket)
+    }
+
+    /// Returns whether the underlying connection sent data in its SYN, using TCP Fast Open.
+    pub fn used_fast_open(&self) -> bool {
+        self.transport.used_fast_open(&self.socket)
+    }
+
+    /// Returns the number of connections to the underlying listening socket whose handshake has not completed yet.
+    pub fn half_open_count(&self) -> Result<usize, Fail> {
+        self.transport.half_open_count(&self.socket)
+    }
+
+    /// Returns the backlog that this queue listens with, or `None` if it does not listen.
+    pub fn backlog(&self) -> Option<usize> {
+        self.backlog
+    }
+
+    /// Returns the number of accepts on this queue that have not completed yet.
+    pub fn pending_accepts(&self) -> usize {
+        self.pending_accepts
+    }
+
+    /// Returns the number of connections that an accept on this queue would return without waiting.
+    pub fn ready_connections(&self) -> usize {
+        self.transport.ready_connections(&self.socket)
+    }
+
+    /// Returns the number of complete datagrams that the underlying socket holds for popping. This counts datagrams, not
+    /// bytes.
+    pub fn queued_datagrams(&self) -> usize {
+        self.transport.queued_datagrams(&self.socket)
+    }
+
+    /// Returns whether popping from the underlying socket would currently have to wait for data.
+    pub fn would_block_pop(&self) -> Result<bool, Fail> {
+        self.transport.would_block_pop(&self.socket)
+    }
+
+    /// Returns the number of buffers of the underlying socket that wait in the transport to be transmitted.
+    pub fn transport_tx_depth(&self) -> usize {
+        self.transport.tx_depth(&self.socket)
+    }
+
+    /// Returns the rate, in bits per second, at which the underlying connection sent payload bytes over the rate window.
+    pub fn send_rate_bps(&self) -> u64 {
+        self.transport.send_rate_bps(&self.socket)
+    }
+
+    /// Returns the rate, in bits per second, at which the underlying connection received payload bytes over the rate
+    /// window.
+    pub fn recv_rate_bps(&self) -> u64 {
+        self.transport.recv_rate_bps(&self.socket)
+    }
+
+    /// Returns the rate, in bits per second, at which the peer of the underlying connection acknowledged payload bytes
+    /// over the rate window.
+    pub fn delivery_rate_bps(&self) -> u64 {
+        self.transport.delivery_rate_bps(&self.socket)
+    }
+
+    /// Returns the TCP options the underlying transport recorded on this queue's connection.
+    pub fn observed_options(&self) -> Vec<TcpOption> {
+        self.transport.observed_options(&self.socket)
+    }
+
+    /// Returns the effective send buffer size of the underlying socket, if it is bounded.
+    pub fn send_buffer_size(&self) -> Result<Option<usize>, Fail> {
+        self.transport.send_buffer_size(&self.socket)
+    }
+
+    /// Returns the current effective MTU of the path of the underlying socket, minus the network and transport headers.
+    pub fn path_mtu(&self) -> u16 {
+        self.transport.path_mtu(&self.socket)
+    }
+
+    /// Returns the send and receive sequence numbers and windows of the underlying TCP connection, if it is connected.
+    pub fn tcp_sequence_state(&self) -> Option<TcpSeqState> {
+        self.transport.tcp_sequence_state(&self.socket)
+    }
+
+    /// Returns the RFC 793 state of the underlying TCP connection, if the queue is a TCP socket.
+    pub fn tcp_state(&self) -> Option<TcpState> {
+        self.transport.tcp_state(&self.socket)
+    }
+
+    /// Returns the initial sequence number that the underlying TCP connection chose for our end, if it sent a SYN.
+    pub fn local_isn(&self) -> Option<u32> {
+        self.transport.local_isn(&self.socket)
+    }
+
+    /// Returns the endpoints, sequence space, and buffered data of the underlying TCP connection.
+    pub fn connection_snapshot(&self) -> Result<ConnectionSnapshot, Fail> {
+        self.transport.connection_snapshot(&self.socket)
+    }
+
+    /// Returns the DSCP that outgoing packets of the underlying socket are marked with.
+    pub fn dscp(&self) -> Result<u8, Fail> {
+        self.transport.dscp(&self.socket)
+    }
+}
+
+impl<T: NetworkTransport> PendingAccept<T> {
+    fn new(mut queue: SharedNetworkQueue<T>) -> Self {
+        queue.pending_accepts += 1;
+        Self(queue)
+    }
 }
 
 //======================================================================================================================
 // Trait implementation
 //======================================================================================================================
 
+impl<T: NetworkTransport> Drop for PendingAccept<T> {
+    fn drop(&mut self) {
+        self.0.pending_accepts -= 1;
+    }
+}
+
+impl<T: NetworkTransport> Drop for ConnectAttempts<T> {
+    fn drop(&mut self) {
+        for mut candidate in self.0.drain(..) {
+            if let Err(e) = candidate.hard_close() {
+                warn!("connect_any(): failed to abort connection attempt (error={:?})", e);
+            }
+        }
+    }
+}
+
 impl<T: NetworkTransport> IoQueue for SharedNetworkQueue<T> {
     fn get_qtype(&self) -> crate::QType {
         self.qtype
@@ -0,0 +1,32 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::{
+    network::socket::state::SocketState,
+    queue::QType,
+    QDesc,
+};
+use ::std::net::SocketAddr;
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Description of an open network queue, as handed to the predicate of `close_matching()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QueueInfo {
+    /// Descriptor of the queue.
+    pub qd: QDesc,
+    /// Type of the queue.
+    pub qtype: QType,
+    /// Local address of the queue, if it is bound or connected.
+    pub local: Option<SocketAddr>,
+    /// Remote address of the queue, if it is connected.
+    pub remote: Option<SocketAddr>,
+    /// State of the socket behind the queue.
+    pub state: SocketState,
+}
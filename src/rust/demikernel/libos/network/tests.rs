@@ -0,0 +1,188 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demikernel::libos::network::libos::SharedNetworkLibOS,
+    runtime::{
+        fail::Fail,
+        memory::DemiBuffer,
+        network::transport::NetworkTransport,
+        scheduler::Yielder,
+        QDesc,
+        SharedDemiRuntime,
+        SharedObject,
+    },
+};
+use ::anyhow::Result;
+use ::socket2::{
+    Domain,
+    Protocol,
+    Type,
+};
+use ::std::{
+    net::{
+        Ipv4Addr,
+        SocketAddr,
+        SocketAddrV4,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Transport whose binds fail with `EADDRINUSE` a configurable number of times, and that reports a configurable
+/// transmit depth. All other operations are unsupported.
+#[derive(Clone)]
+struct MockTransport {
+    runtime: SharedDemiRuntime,
+    /// Number of upcoming binds that fail.
+    bind_failures: SharedObject<usize>,
+    /// Number of binds issued so far.
+    bind_calls: SharedObject<usize>,
+    /// Number of buffers that every socket reports as waiting to be transmitted.
+    tx_depth: SharedObject<usize>,
+}
+
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+impl NetworkTransport for MockTransport {
+    type SocketDescriptor = ();
+
+    fn socket(&mut self, _domain: Domain, _typ: Type) -> Result<Self::SocketDescriptor, Fail> {
+        Ok(())
+    }
+
+    fn bind(&mut self, _sd: &mut Self::SocketDescriptor, _local: SocketAddr) -> Result<(), Fail> {
+        *self.bind_calls += 1;
+        if *self.bind_failures > 0 {
+            *self.bind_failures -= 1;
+            return Err(Fail::new(libc::EADDRINUSE, "address in use"));
+        }
+        Ok(())
+    }
+
+    fn listen(&mut self, _sd: &mut Self::SocketDescriptor, _backlog: usize) -> Result<(), Fail> {
+        Err(Fail::new(libc::ENOTSUP, "not supported"))
+    }
+
+    fn hard_close(&mut self, _sd: &mut Self::SocketDescriptor) -> Result<(), Fail> {
+        Ok(())
+    }
+
+    async fn accept(
+        &mut self,
+        _sd: &mut Self::SocketDescriptor,
+        _yielder: Yielder,
+    ) -> Result<(Self::SocketDescriptor, SocketAddr), Fail> {
+        Err(Fail::new(libc::ENOTSUP, "not supported"))
+    }
+
+    async fn connect(
+        &mut self,
+        _sd: &mut Self::SocketDescriptor,
+        _remote: SocketAddr,
+        _yielder: Yielder,
+    ) -> Result<(), Fail> {
+        Err(Fail::new(libc::ENOTSUP, "not supported"))
+    }
+
+    async fn push(
+        &mut self,
+        _sd: &mut Self::SocketDescriptor,
+        _buf: &mut DemiBuffer,
+        _addr: Option<SocketAddr>,
+        _yielder: Yielder,
+    ) -> Result<(), Fail> {
+        Err(Fail::new(libc::ENOTSUP, "not supported"))
+    }
+
+    async fn pop(
+        &mut self,
+        _sd: &mut Self::SocketDescriptor,
+        _buf: &mut DemiBuffer,
+        _size: usize,
+        _yielder: Yielder,
+    ) -> Result<Option<SocketAddr>, Fail> {
+        Err(Fail::new(libc::ENOTSUP, "not supported"))
+    }
+
+    async fn close(&mut self, _sd: &mut Self::SocketDescriptor, _yielder: Yielder) -> Result<(), Fail> {
+        Ok(())
+    }
+
+    fn tx_depth(&self, _sd: &Self::SocketDescriptor) -> usize {
+        *self.tx_depth
+    }
+
+    fn get_runtime(&self) -> &SharedDemiRuntime {
+        &self.runtime
+    }
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that a bind failing transiently with `EADDRINUSE` succeeds once retries are enabled.
+#[test]
+fn bind_retry_transient_failure() -> Result<()> {
+    let runtime: SharedDemiRuntime = SharedDemiRuntime::new(Instant::now());
+    let transport: MockTransport = MockTransport {
+        runtime: runtime.clone(),
+        bind_failures: SharedObject::new(1),
+        bind_calls: SharedObject::new(0),
+        tx_depth: SharedObject::new(0),
+    };
+    let mut libos: SharedNetworkLibOS<MockTransport> = SharedNetworkLibOS::new(runtime, transport.clone());
+    let local: SocketAddr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 1), 80));
+
+    // Without retries, the transient failure reaches the application.
+    let qd: QDesc = libos.socket(Domain::IPV4, Type::DGRAM, Protocol::UDP)?;
+    match libos.bind(qd, local) {
+        Err(e) if e.errno == libc::EADDRINUSE => {},
+        _ => anyhow::bail!("bind should fail without retries"),
+    };
+    crate::ensure_eq!(*transport.bind_calls, 1);
+
+    // With retries, bind succeeds on the second attempt, after waiting on the clock of the runtime.
+    *transport.clone().bind_failures = 1;
+    let delay: Duration = Duration::from_millis(5);
+    libos.set_bind_retry_policy(3, delay);
+    let start: Instant = transport.runtime.get_now();
+    libos.bind(qd, local)?;
+    crate::ensure_eq!(*transport.bind_calls, 3);
+    crate::ensure_eq!(transport.runtime.get_now() - start >= delay, true);
+
+    Ok(())
+}
+
+/// Checks that the transmit depth of a queue is the one that its transport reports.
+#[test]
+fn transport_tx_depth() -> Result<()> {
+    let runtime: SharedDemiRuntime = SharedDemiRuntime::new(Instant::now());
+    let transport: MockTransport = MockTransport {
+        runtime: runtime.clone(),
+        bind_failures: SharedObject::new(0),
+        bind_calls: SharedObject::new(0),
+        tx_depth: SharedObject::new(0),
+    };
+    let mut libos: SharedNetworkLibOS<MockTransport> = SharedNetworkLibOS::new(runtime, transport.clone());
+    let qd: QDesc = libos.socket(Domain::IPV4, Type::DGRAM, Protocol::UDP)?;
+    crate::ensure_eq!(libos.transport_tx_depth(qd)?, 0);
+
+    *transport.clone().tx_depth = 7;
+    crate::ensure_eq!(libos.transport_tx_depth(qd)?, 7);
+
+    Ok(())
+}
@@ -134,6 +134,11 @@ where
         return self.map.get(key).map(|r| &r.value);
     }
 
+    /// Gets an entry from the cache, unless it has expired by `now`.
+    pub fn get_alive(&self, key: &K, now: Instant) -> Option<&V> {
+        self.map.get(key).filter(|r| !r.has_expired(now)).map(|r| &r.value)
+    }
+
     // Iterator.
     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
         let clock = self.clock;
@@ -146,8 +151,17 @@ where
         })
     }
 
-    /// Collect dead entries in the cache.
-    pub fn cleanup(&mut self) {
+    /// Iterates over the entries that are alive at `now`, along with their remaining time to live. Entries that never
+    /// expire have no time to live.
+    pub fn iter_with_ttl(&self, now: Instant) -> impl Iterator<Item = (&K, &V, Option<Duration>)> {
+        self.map
+            .iter()
+            .filter(move |(_, record)| !record.has_expired(now))
+            .map(move |(key, record)| (key, &record.value, record.expiration.map(|e| e - now)))
+    }
+
+    /// Collect dead entries in the cache. Returns the number of entries collected.
+    pub fn cleanup(&mut self) -> usize {
         let mut dead_entries: Vec<K> = Vec::new();
 
         // Collect dead entries.
@@ -158,9 +172,11 @@ where
         }
 
         // Put dead_entries entries in the graveyard.
+        let num_dead_entries: usize = dead_entries.len();
         for k in dead_entries {
             let (k, v) = self.map.remove_entry(&k).unwrap();
             self.graveyard.insert(k, v.value);
         }
+        num_dead_entries
     }
 }
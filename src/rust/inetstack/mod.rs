@@ -9,27 +9,56 @@ use crate::{
     demi_sgarray_t,
     demikernel::config::Config,
     inetstack::protocols::{
-        arp::SharedArpPeer,
+        arp::{
+            ArpSnapshot,
+            ArpStats,
+            SharedArpPeer,
+        },
         ethernet2::{
             EtherType2,
             Ethernet2Header,
         },
-        tcp::socket::SharedTcpSocket,
-        udp::socket::SharedUdpSocket,
+        ipv4::IPV4_HEADER_MIN_SIZE,
+        tcp::{
+            socket::SharedTcpSocket,
+            ConnTuple,
+        },
+        udp::{
+            socket::SharedUdpSocket,
+            ReorderBuffer,
+            UDP_HEADER_SIZE,
+        },
         Peer,
     },
     runtime::{
         fail::Fail,
+        limits,
         memory::{
             DemiBuffer,
             MemoryRuntime,
         },
         network::{
+            socket::{
+                operation::Shutdown,
+                option::{
+                    SocketOption,
+                    SocketOptionName,
+                },
+            },
             transport::NetworkTransport,
-            types::MacAddress,
+            types::{
+                ConnectionSnapshot,
+                DropReason,
+                DropStats,
+                MacAddress,
+                TcpOption,
+                TcpSeqState,
+                TcpState,
+            },
             unwrap_socketaddr,
             NetworkRuntime,
         },
+        queue::QToken,
         scheduler::Yielder,
         SharedDemiRuntime,
         SharedObject,
@@ -40,14 +69,11 @@ use ::socket2::{
     Type,
 };
 #[cfg(test)]
-use ::std::{
-    collections::HashMap,
-    hash::RandomState,
-    time::Duration,
-};
+use ::std::hash::RandomState;
 
 use ::futures::FutureExt;
 use ::std::{
+    collections::HashMap,
     fmt::Debug,
     net::{
         Ipv4Addr,
@@ -58,6 +84,7 @@ use ::std::{
         Deref,
         DerefMut,
     },
+    time::Duration,
 };
 
 #[cfg(feature = "profiler")]
@@ -98,6 +125,10 @@ pub struct InetStack<N: NetworkRuntime> {
     runtime: SharedDemiRuntime,
     network: N,
     local_link_addr: MacAddress,
+    /// Incoming packets dropped by any layer of the stack.
+    drop_stats: SharedObject<DropStats>,
+    /// Background coroutine that receives packets, until the stack is stopped.
+    poll_task: Option<QToken>,
 }
 
 #[derive(Clone)]
@@ -119,12 +150,14 @@ impl<N: NetworkRuntime> SharedInetStack<N> {
         local_ipv4_addr: Ipv4Addr,
     ) -> Result<Self, Fail> {
         let rng_seed: [u8; 32] = [0; 32];
+        let drop_stats: SharedObject<DropStats> = SharedObject::<DropStats>::new(DropStats::default());
         let arp: SharedArpPeer<N> = SharedArpPeer::new(
             runtime.clone(),
             network.clone(),
             local_link_addr,
             local_ipv4_addr,
             network.get_arp_config(),
+            drop_stats.clone(),
         )?;
         let ipv4: Peer<N> = Peer::new(
             runtime.clone(),
@@ -135,17 +168,22 @@ impl<N: NetworkRuntime> SharedInetStack<N> {
             network.get_tcp_config(),
             arp.clone(),
             rng_seed,
+            drop_stats.clone(),
         )?;
-        let me: Self = Self(SharedObject::<InetStack<N>>::new(InetStack::<N> {
+        let mut me: Self = Self(SharedObject::<InetStack<N>>::new(InetStack::<N> {
             arp,
             ipv4,
             runtime: runtime.clone(),
             network,
             local_link_addr: local_link_addr,
+            drop_stats,
+            poll_task: None,
         }));
         let yielder: Yielder = Yielder::new();
         let background_task: String = format!("inetstack::poll_recv");
-        runtime.insert_background_coroutine(&background_task, Box::pin(me.clone().poll(yielder).fuse()))?;
+        let qt: QToken =
+            runtime.insert_background_coroutine(&background_task, Box::pin(me.clone().poll(yielder).fuse()))?;
+        me.poll_task = Some(qt);
         Ok(me)
     }
 
@@ -186,6 +224,102 @@ impl<N: NetworkRuntime> SharedInetStack<N> {
         }
     }
 
+    /// Enables or disables software verification of checksums on the receive path. Verification is enabled by default
+    /// and should only be disabled on trusted networks or when the NIC verifies checksums.
+    pub fn set_verify_checksums(&mut self, verify_checksums: bool) {
+        self.ipv4.set_verify_checksums(verify_checksums)
+    }
+
+    /// Sets the netmask of the local subnet, so that datagrams sent to its directed broadcast address are handled like
+    /// those sent to the limited broadcast address. Only UDP sockets created afterwards send to it as broadcast.
+    pub fn set_ipv4_netmask(&mut self, netmask: Ipv4Addr) {
+        self.ipv4.set_netmask(netmask)
+    }
+
+    /// Sets the TCP maximum segment lifetime (MSL). Actively closed connections stay in TIME_WAIT, with their local
+    /// address reserved, for `2 * max_segment_lifetime`. Defaults to 60 seconds. None disables TIME_WAIT, so that
+    /// addresses may be reused as soon as their connections close.
+    pub fn set_max_segment_lifetime(&mut self, max_segment_lifetime: Option<Duration>) -> Result<(), Fail> {
+        self.ipv4.tcp.set_max_segment_lifetime(max_segment_lifetime)
+    }
+
+    /// Sets how long TCP receivers wait before sending a standalone ACK when there is no data going the other way to
+    /// piggyback it on. Only affects sockets created afterwards. Defaults to 40 milliseconds.
+    pub fn set_delayed_ack_timeout(&mut self, delayed_ack_timeout: Duration) -> Result<(), Fail> {
+        self.ipv4.tcp.set_delayed_ack_timeout(delayed_ack_timeout)
+    }
+
+    /// Sets whether TCP connections record the options they receive from their peer, for diagnostics. Only affects
+    /// sockets created afterwards. Disabled by default.
+    pub fn set_record_options(&mut self, record_options: bool) {
+        self.ipv4.tcp.set_record_options(record_options)
+    }
+
+    /// Sets whether TCP connections merge adjacent in-order segments into a single buffer before the application pops
+    /// them, which reduces per-segment overhead on bulk transfers. Only affects sockets created afterwards. Disabled
+    /// by default.
+    pub fn set_receive_coalescing(&mut self, receive_coalescing: bool) {
+        self.ipv4.tcp.set_receive_coalescing(receive_coalescing)
+    }
+
+    /// Sets whether TCP listening sockets hand out TCP Fast Open cookies and accept data in the SYNs of clients that
+    /// present one, delivering it with the first pop. Only affects sockets created afterwards. Disabled by default.
+    pub fn set_fast_open(&mut self, fast_open: bool) {
+        self.ipv4.tcp.set_fast_open(fast_open)
+    }
+
+    /// Sets how many half-open connections, i.e. connections in the SYN-RECEIVED state, each TCP listening socket keeps.
+    /// SYNs that arrive while a listening socket is at the limit are dropped, so that their senders retry later. Only
+    /// affects sockets that start listening afterwards. Unlimited by default.
+    pub fn set_max_half_open(&mut self, max_half_open: Option<usize>) {
+        self.ipv4.tcp.set_max_half_open(max_half_open)
+    }
+
+    /// Sets whether TCP connections run the CUBIC congestion control algorithm, which grows and shrinks their
+    /// congestion window as segments are acknowledged and lost. Only affects sockets connected afterwards. Disabled by
+    /// default, in which case the congestion window is unbounded.
+    pub fn set_congestion_control(&mut self, congestion_control: bool) {
+        self.ipv4.tcp.set_congestion_control(congestion_control)
+    }
+
+    /// Sets whether TCP connections pace their segments, spacing them out at slightly more than the rate at which the
+    /// peer recently acknowledged data, rather than sending as much as the windows allow at once. Connections send
+    /// unpaced until they have measured their delivery rate over a full rate window. Only affects sockets connected
+    /// afterwards. Disabled by default.
+    pub fn set_pacing(&mut self, pacing: bool) {
+        self.ipv4.tcp.set_pacing(pacing)
+    }
+
+    /// Makes TCP connections take their initial sequence numbers from `generator`, which is given the addresses of each
+    /// connection, rather than from the default generator, which randomizes them. Meant for tests and debugging.
+    pub fn set_isn_generator(&mut self, generator: Box<dyn Fn(&ConnTuple) -> u32>) {
+        self.ipv4.tcp.set_isn_generator(generator)
+    }
+
+    /// Sets whether TCP connections shrink their segment size when they receive ICMP "fragmentation needed" messages,
+    /// i.e. Path MTU Discovery. Enabled by default.
+    pub fn set_path_mtu_discovery(&mut self, path_mtu_discovery: bool) {
+        self.ipv4.tcp.set_path_mtu_discovery(path_mtu_discovery)
+    }
+
+    /// Sets whether TCP segments that no connection can take are answered with a RST, so that the peer learns about it
+    /// right away. These are segments that reach no connection, such as data that arrives after the connection was
+    /// closed, and data that arrives in LAST_ACK, after the peer sent its FIN. Such segments are dropped silently by
+    /// default, except at ports where a socket listens, which always resets them. Connections in LAST_ACK only reset such
+    /// segments if they were connected afterwards.
+    pub fn set_rst_on_invalid_segment(&mut self, rst_on_invalid_segment: bool) {
+        self.ipv4.tcp.set_rst_on_invalid_segment(rst_on_invalid_segment)
+    }
+
+    /// Enables, replaces, or disables (if `None`) in-order delivery of datagrams on the UDP socket bound to `local`.
+    pub fn set_udp_reorder_buffer(
+        &mut self,
+        local: SocketAddrV4,
+        reorder_buffer: Option<ReorderBuffer>,
+    ) -> Result<(), Fail> {
+        self.ipv4.udp.set_reorder_buffer(local, reorder_buffer)
+    }
+
     /// Generally these functions are for testing.
     #[cfg(test)]
     pub fn get_link_addr(&self) -> MacAddress {
@@ -214,25 +348,55 @@ impl<N: NetworkRuntime> SharedInetStack<N> {
         self.arp.query(addr, &yielder).await
     }
 
+    /// Pins the resolution of `ipv4_addr` to `link_addr` in the ARP cache. Static resolutions never expire, are not
+    /// overwritten by ARP replies, and survive reloads of the ARP table.
+    pub fn insert_static_arp(&mut self, ipv4_addr: Ipv4Addr, link_addr: MacAddress) {
+        self.arp.insert_static(ipv4_addr, link_addr);
+    }
+
+    /// Takes a consistent snapshot of the ARP cache, including how long each resolution has left before it expires.
+    pub fn arp_snapshot(&self) -> ArpSnapshot {
+        self.arp.snapshot_cache()
+    }
+
+    /// Returns how many link address lookups hit and missed the ARP cache, and how many resolutions were cached and
+    /// expired, since the stack was created or since [reset_arp_stats](Self::reset_arp_stats) was last called.
+    pub fn arp_stats(&self) -> ArpStats {
+        self.arp.cache_stats()
+    }
+
+    /// Zeroes the counters of the ARP cache, e.g. to sample them periodically.
+    pub fn reset_arp_stats(&mut self) {
+        self.arp.reset_cache_stats()
+    }
+
     #[cfg(test)]
     pub fn export_arp_cache(&self) -> HashMap<Ipv4Addr, MacAddress, RandomState> {
         self.arp.export_cache()
     }
 
     pub fn receive(&mut self, pkt: DemiBuffer) -> Result<(), Fail> {
-        let (header, payload) = Ethernet2Header::parse(pkt)?;
+        let (header, payload) = match Ethernet2Header::parse(pkt) {
+            Ok(result) => result,
+            Err(e) => {
+                self.drop_stats.record(DropReason::from_parse_error(&e));
+                return Err(e);
+            },
+        };
         debug!("Engine received {:?}", header);
         if self.local_link_addr != header.dst_addr()
             && !header.dst_addr().is_broadcast()
             && !header.dst_addr().is_multicast()
         {
             warn!("dropping packet");
+            self.drop_stats.record(DropReason::NoRoute);
             return Ok(());
         }
         match header.ether_type() {
             EtherType2::Arp => self.arp.receive(payload),
             EtherType2::Ipv4 => self.ipv4.receive(payload),
-            EtherType2::Ipv6 => (), // Ignore for now.
+            // Ignore for now.
+            EtherType2::Ipv6 => self.drop_stats.record(DropReason::UnsupportedProtocol),
         };
         Ok(())
     }
@@ -397,7 +561,24 @@ impl<N: NetworkRuntime> NetworkTransport for SharedInetStack<N> {
         let remote: SocketAddrV4 = unwrap_socketaddr(remote)?;
 
         match sd {
-            Socket::Tcp(socket) => self.ipv4.tcp.connect(socket, remote, yielder).await,
+            Socket::Tcp(socket) => self.ipv4.tcp.connect(socket, remote, None, yielder).await,
+            _ => Err(Fail::new(libc::EINVAL, "invalid queue type")),
+        }
+    }
+
+    async fn connect_with_data(
+        &mut self,
+        sd: &mut Self::SocketDescriptor,
+        remote: SocketAddr,
+        initial: DemiBuffer,
+        yielder: Yielder,
+    ) -> Result<(), Fail> {
+        trace!("connect_with_data(): remote={:?}, len={:?}", remote, initial.len());
+
+        let remote: SocketAddrV4 = unwrap_socketaddr(remote)?;
+
+        match sd {
+            Socket::Tcp(socket) => self.ipv4.tcp.connect(socket, remote, Some(initial), yielder).await,
             _ => Err(Fail::new(libc::EINVAL, "invalid queue type")),
         }
     }
@@ -456,9 +637,291 @@ impl<N: NetworkRuntime> NetworkTransport for SharedInetStack<N> {
         }
     }
 
+    /// Pops all buffered data from a TCP connection. Datagram boundaries are preserved on UDP sockets, so these fall
+    /// back to popping a single datagram.
+    async fn pop_all(
+        &mut self,
+        sd: &mut Self::SocketDescriptor,
+        yielder: Yielder,
+    ) -> Result<(Option<SocketAddr>, DemiBuffer), Fail> {
+        match sd {
+            Socket::Tcp(socket) => self.ipv4.tcp.pop_all(socket, yielder).await,
+            Socket::Udp(socket) => {
+                let size: usize = limits::RECVBUF_SIZE_MAX;
+                let mut buf: DemiBuffer = DemiBuffer::new(size as u16);
+                let addr: Option<SocketAddr> = self.ipv4.udp.pop(socket, &mut buf, size, yielder).await?;
+                Ok((addr, buf))
+            },
+        }
+    }
+
+    /// Probes whether another host on the link uses an address, with ARP.
+    async fn probe_address(&mut self, ipv4_addr: Ipv4Addr, yielder: Yielder) -> Result<(), Fail> {
+        self.arp.probe(ipv4_addr, &yielder).await
+    }
+
+    /// Stops receiving packets.
+    fn stop(&mut self) {
+        if let Some(qt) = self.poll_task.take() {
+            if let Err(e) = self.runtime.remove_background_coroutine(qt) {
+                warn!("stop(): cannot remove background coroutine: {:?}", e);
+            }
+        }
+    }
+
     fn get_runtime(&self) -> &SharedDemiRuntime {
         &self.runtime
     }
+
+    fn set_socket_option(&mut self, sd: &mut Self::SocketDescriptor, option: SocketOption) -> Result<(), Fail> {
+        match sd {
+            Socket::Tcp(socket) => socket.set_socket_option(option),
+            Socket::Udp(socket) => match option {
+                SocketOption::Broadcast(broadcast) => {
+                    socket.set_broadcast(broadcast);
+                    Ok(())
+                },
+                SocketOption::MaxDatagramQueue(max_datagram_queue) => {
+                    if max_datagram_queue == 0 {
+                        let cause: &str = "datagram queue must hold at least one datagram";
+                        warn!("set_socket_option(): {}", cause);
+                        return Err(Fail::new(libc::EINVAL, cause));
+                    }
+                    socket.set_max_datagram_queue(max_datagram_queue);
+                    Ok(())
+                },
+                _ => {
+                    let cause: String = format!("socket option not supported on udp sockets (option={:?})", option);
+                    warn!("set_socket_option(): {}", cause);
+                    Err(Fail::new(libc::ENOPROTOOPT, &cause))
+                },
+            },
+        }
+    }
+
+    fn get_socket_option(&self, sd: &Self::SocketDescriptor, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        match sd {
+            Socket::Tcp(socket) => socket.get_socket_option(name),
+            Socket::Udp(_) => {
+                let cause: String = format!("socket option not supported on udp sockets (name={:?})", name);
+                warn!("get_socket_option(): {}", cause);
+                Err(Fail::new(libc::ENOPROTOOPT, &cause))
+            },
+        }
+    }
+
+    fn migrate_local(&mut self, sd: &mut Self::SocketDescriptor, new_local: SocketAddr) -> Result<(), Fail> {
+        // FIXME: add IPv6 support; https://github.com/microsoft/demikernel/issues/935
+        let new_local: SocketAddrV4 = unwrap_socketaddr(new_local)?;
+        // We only receive traffic on a single address.
+        if *new_local.ip() != self.ipv4.get_local_addr() {
+            let cause: String = format!("address is not local (new_local={:?})", new_local);
+            error!("migrate_local(): {}", cause);
+            return Err(Fail::new(libc::EADDRNOTAVAIL, &cause));
+        }
+
+        match sd {
+            Socket::Tcp(socket) => self.ipv4.tcp.migrate_local(socket, new_local),
+            Socket::Udp(_) => Err(Fail::new(libc::ENOTSUP, "cannot migrate udp sockets")),
+        }
+    }
+
+    fn set_paused(&mut self, sd: &mut Self::SocketDescriptor, paused: bool) -> Result<(), Fail> {
+        match sd {
+            Socket::Tcp(socket) => socket.set_paused(paused),
+            Socket::Udp(_) => Err(Fail::new(libc::ENOTSUP, "cannot pause udp sockets")),
+        }
+    }
+
+    fn shutdown(&mut self, sd: &mut Self::SocketDescriptor, how: Shutdown) -> Result<(), Fail> {
+        match sd {
+            Socket::Tcp(socket) => socket.shutdown(how),
+            Socket::Udp(_) => Err(Fail::new(libc::ENOTSUP, "cannot shut down udp sockets")),
+        }
+    }
+
+    fn retransmit_count(&self, sd: &Self::SocketDescriptor) -> u64 {
+        match sd {
+            Socket::Tcp(socket) => socket.retransmit_count(),
+            Socket::Udp(_) => 0,
+        }
+    }
+
+    fn cwnd(&self, sd: &Self::SocketDescriptor) -> Option<u32> {
+        match sd {
+            Socket::Tcp(socket) => socket.cwnd(),
+            Socket::Udp(_) => None,
+        }
+    }
+
+    fn ssthresh(&self, sd: &Self::SocketDescriptor) -> Option<u32> {
+        match sd {
+            Socket::Tcp(socket) => socket.ssthresh(),
+            Socket::Udp(_) => None,
+        }
+    }
+
+    fn used_fast_open(&self, sd: &Self::SocketDescriptor) -> bool {
+        match sd {
+            Socket::Tcp(socket) => socket.used_fast_open(),
+            Socket::Udp(_) => false,
+        }
+    }
+
+    fn half_open_count(&self, sd: &Self::SocketDescriptor) -> Result<usize, Fail> {
+        match sd {
+            Socket::Tcp(socket) => socket.half_open_count(),
+            Socket::Udp(_) => Err(Fail::new(
+                libc::ENOTSUP,
+                "half-open connections are only tracked on TCP sockets",
+            )),
+        }
+    }
+
+    fn ready_connections(&self, sd: &Self::SocketDescriptor) -> usize {
+        match sd {
+            Socket::Tcp(socket) => socket.ready_connections(),
+            Socket::Udp(_) => 0,
+        }
+    }
+
+    fn send_rate_bps(&self, sd: &Self::SocketDescriptor) -> u64 {
+        match sd {
+            Socket::Tcp(socket) => socket.send_rate_bps(),
+            Socket::Udp(_) => 0,
+        }
+    }
+
+    fn recv_rate_bps(&self, sd: &Self::SocketDescriptor) -> u64 {
+        match sd {
+            Socket::Tcp(socket) => socket.recv_rate_bps(),
+            Socket::Udp(_) => 0,
+        }
+    }
+
+    fn delivery_rate_bps(&self, sd: &Self::SocketDescriptor) -> u64 {
+        match sd {
+            Socket::Tcp(socket) => socket.delivery_rate_bps(),
+            Socket::Udp(_) => 0,
+        }
+    }
+
+    fn queued_datagrams(&self, sd: &Self::SocketDescriptor) -> usize {
+        match sd {
+            Socket::Tcp(_) => 0,
+            Socket::Udp(socket) => socket.queued_datagrams(),
+        }
+    }
+
+    fn would_block_pop(&self, sd: &Self::SocketDescriptor) -> Result<bool, Fail> {
+        match sd {
+            Socket::Tcp(socket) => socket.would_block_pop(),
+            Socket::Udp(socket) => Ok(socket.queued_datagrams() == 0),
+        }
+    }
+
+    fn observed_options(&self, sd: &Self::SocketDescriptor) -> Vec<TcpOption> {
+        match sd {
+            Socket::Tcp(socket) => socket.observed_options(),
+            Socket::Udp(_) => Vec::new(),
+        }
+    }
+
+    fn connection_snapshot(&self, sd: &Self::SocketDescriptor) -> Result<ConnectionSnapshot, Fail> {
+        match sd {
+            Socket::Tcp(socket) => socket.connection_snapshot(),
+            Socket::Udp(_) => Err(Fail::new(libc::ENOTSUP, "cannot snapshot udp sockets")),
+        }
+    }
+
+    fn resume_from(&mut self, sd: &mut Self::SocketDescriptor, snapshot: &ConnectionSnapshot) -> Result<(), Fail> {
+        match sd {
+            Socket::Tcp(socket) => self.ipv4.tcp.resume_from(socket, snapshot),
+            Socket::Udp(_) => Err(Fail::new(libc::ENOTSUP, "cannot restore udp sockets")),
+        }
+    }
+
+    fn tcp_sequence_state(&self, sd: &Self::SocketDescriptor) -> Option<TcpSeqState> {
+        match sd {
+            Socket::Tcp(socket) => socket.tcp_sequence_state(),
+            Socket::Udp(_) => None,
+        }
+    }
+
+    fn tcp_state(&self, sd: &Self::SocketDescriptor) -> Option<TcpState> {
+        match sd {
+            Socket::Tcp(socket) => Some(socket.tcp_state()),
+            Socket::Udp(_) => None,
+        }
+    }
+
+    fn local_isn(&self, sd: &Self::SocketDescriptor) -> Option<u32> {
+        match sd {
+            Socket::Tcp(socket) => socket.local_isn().map(u32::from),
+            Socket::Udp(_) => None,
+        }
+    }
+
+    fn send_buffer_size(&self, sd: &Self::SocketDescriptor) -> Result<Option<usize>, Fail> {
+        match sd {
+            Socket::Tcp(socket) => Ok(socket.send_buffer_size()),
+            Socket::Udp(_) => {
+                let cause: &str = "send buffer size is not available on udp sockets";
+                warn!("send_buffer_size(): {}", cause);
+                Err(Fail::new(libc::ENOPROTOOPT, cause))
+            },
+        }
+    }
+
+    fn path_mtu(&self, sd: &Self::SocketDescriptor) -> u16 {
+        match sd {
+            Socket::Tcp(socket) => socket.path_mtu(),
+            Socket::Udp(_) => self.network.get_mtu() - IPV4_HEADER_MIN_SIZE - UDP_HEADER_SIZE as u16,
+        }
+    }
+
+    fn dscp(&self, sd: &Self::SocketDescriptor) -> Result<u8, Fail> {
+        match sd {
+            Socket::Tcp(socket) => Ok(socket.dscp()),
+            Socket::Udp(_) => {
+                let cause: &str = "dscp is not available on udp sockets";
+                warn!("dscp(): {}", cause);
+                Err(Fail::new(libc::ENOPROTOOPT, cause))
+            },
+        }
+    }
+
+    fn drop_stats(&self) -> DropStats {
+        *self.drop_stats
+    }
+
+    fn reset_drop_stats(&mut self) {
+        *self.drop_stats = DropStats::default();
+    }
+
+    fn get_local_link_addr(&self) -> Result<MacAddress, Fail> {
+        Ok(self.local_link_addr)
+    }
+
+    /// Overrides the link address of this stack and announces it with a gratuitous ARP. Sockets that already exist keep
+    /// using the old address, so callers should only do this before creating any socket.
+    fn set_local_link_addr(&mut self, link_addr: MacAddress) -> Result<(), Fail> {
+        if !link_addr.is_unicast() {
+            let cause: String = format!("local link address must be unicast (link_addr={:?})", link_addr);
+            error!("set_local_link_addr(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, &cause));
+        }
+        self.local_link_addr = link_addr;
+        self.arp.set_local_link_addr(link_addr);
+        self.ipv4.set_local_link_addr(link_addr);
+        self.arp.announce();
+        Ok(())
+    }
+
+    fn replace_arp_cache(&mut self, values: &HashMap<Ipv4Addr, MacAddress>) -> Result<(), Fail> {
+        self.arp.replace_cache(values);
+        Ok(())
+    }
 }
 
 /// This implements the memory runtime trait for the inetstack. Other libOSes without a network runtime can directly
@@ -12,9 +12,13 @@ use crate::{
     },
 };
 use ::std::{
+    cell::Cell,
     collections::HashMap,
     net::Ipv4Addr,
-    time::Duration,
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 //==============================================================================
@@ -29,7 +33,57 @@ const DUMMY_MAC_ADDRESS: MacAddress = MacAddress::new([0; 6]);
 
 #[derive(Debug)]
 struct Record {
-    link_addr: MacAddress,
+    /// Resolved link address, or `None` if the address recently failed to resolve.
+    link_addr: Option<MacAddress>,
+    /// Static resolutions never expire, and are neither overwritten by learned resolutions nor dropped by
+    /// [ArpCache::replace_all].
+    is_static: bool,
+}
+
+/// Outcome of a lookup in the ARP cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArpLookup {
+    /// The address resolves to this link address.
+    Hit(MacAddress),
+    /// The address recently failed to resolve, and should not be queried again yet.
+    NegativeHit,
+    /// Nothing is known about the address.
+    Miss,
+}
+
+/// Address resolution in a snapshot of the ARP cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArpSnapshotEntry {
+    /// Resolved link address.
+    pub link_addr: MacAddress,
+    /// Time left before the resolution expires, or `None` if it never does.
+    pub remaining_ttl: Option<Duration>,
+}
+
+/// Counters of the activity of the ARP cache, since it was created or since they were last reset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArpStats {
+    /// Lookups of a link address.
+    pub lookups: u64,
+    /// Lookups that found a resolution.
+    pub hits: u64,
+    /// Lookups that found none.
+    pub misses: u64,
+    /// Lookups that found that the address recently failed to resolve.
+    pub negative_hits: u64,
+    /// Resolutions cached, including failed ones.
+    pub insertions: u64,
+    /// Resolutions dropped because they expired, including failed ones.
+    pub evictions: u64,
+}
+
+/// Consistent view of the ARP cache, with all expiry times computed at the same instant.
+#[derive(Clone, Debug)]
+pub struct ArpSnapshot {
+    /// Instant at which the snapshot was taken.
+    pub taken_at: Instant,
+    /// Resolutions that had not expired when the snapshot was taken. Failed resolutions are left out.
+    pub entries: HashMap<Ipv4Addr, ArpSnapshotEntry>,
 }
 
 ///
@@ -42,64 +96,221 @@ pub struct ArpCache {
     /// Cache for IPv4 Addresses
     cache: HashTtlCache<Ipv4Addr, Record>,
 
+    /// Clock that insertion and expiry times are taken from.
+    clock: SharedTimer,
+
+    /// Time to live of the entries for addresses that failed to resolve. None disables negative caching.
+    negative_ttl: Option<Duration>,
+
     /// Disable ARP?
     disable: bool,
+
+    /// Counters of lookups, insertions and evictions. Lookups do not borrow the cache mutably, so they live in a cell.
+    stats: Cell<ArpStats>,
 }
 
 //==============================================================================
 // Associate Functions
 //==============================================================================
 
+impl ArpLookup {
+    /// Returns the resolved link address, if any.
+    pub fn link_addr(self) -> Option<MacAddress> {
+        match self {
+            ArpLookup::Hit(link_addr) => Some(link_addr),
+            ArpLookup::NegativeHit | ArpLookup::Miss => None,
+        }
+    }
+}
+
 impl ArpCache {
-    /// Creates an ARP Cache.
+    /// Creates an ARP Cache. The resolutions in `values` are the static ARP table of the configuration, so they are
+    /// cached as static resolutions.
     pub fn new(
         clock: SharedTimer,
         default_ttl: Option<Duration>,
+        negative_ttl: Option<Duration>,
         values: Option<&HashMap<Ipv4Addr, MacAddress>>,
         disable: bool,
     ) -> ArpCache {
         let mut peer = ArpCache {
             cache: HashTtlCache::new(clock.now(), default_ttl),
+            clock,
+            // The underlying cache does not take zero times to live.
+            negative_ttl: negative_ttl.filter(|ttl| !ttl.is_zero()),
             disable,
+            stats: Cell::new(ArpStats::default()),
         };
 
         // Populate cache.
         if let Some(values) = values {
             for (&k, &v) in values {
-                peer.insert(k, v);
+                peer.insert_static(k, v);
             }
         }
 
         peer
     }
 
-    /// Caches an address resolution.
+    /// Caches an address resolution. Static resolutions of the same address are left in place.
     pub fn insert(&mut self, ipv4_addr: Ipv4Addr, link_addr: MacAddress) -> Option<MacAddress> {
-        let record = Record { link_addr };
-        self.cache.insert(ipv4_addr, record).map(|r| r.link_addr)
+        if let Some(record) = self.cache.get(&ipv4_addr) {
+            if record.is_static {
+                return record.link_addr;
+            }
+        }
+        let record = Record {
+            link_addr: Some(link_addr),
+            is_static: false,
+        };
+        // Entries expire relative to the time at which they are inserted.
+        self.advance_clock();
+        self.update_stats(|stats| stats.insertions += 1);
+        self.cache.insert(ipv4_addr, record).and_then(|r| r.link_addr)
     }
 
-    /// Gets the MAC address of given IPv4 address.
-    pub fn get(&self, ipv4_addr: Ipv4Addr) -> Option<&MacAddress> {
+    /// Records that an address failed to resolve, for the negative time to live of the cache, so that lookups of it
+    /// report a [ArpLookup::NegativeHit] meanwhile. Static resolutions of the same address are left in place. Does nothing
+    /// if negative caching is disabled.
+    pub fn insert_negative(&mut self, ipv4_addr: Ipv4Addr) {
+        let negative_ttl: Duration = match self.negative_ttl {
+            Some(negative_ttl) => negative_ttl,
+            None => return,
+        };
+        if let Some(record) = self.cache.get(&ipv4_addr) {
+            if record.is_static {
+                return;
+            }
+        }
+        let record = Record {
+            link_addr: None,
+            is_static: false,
+        };
+        self.advance_clock();
+        self.update_stats(|stats| stats.insertions += 1);
+        self.cache.insert_with_ttl(ipv4_addr, record, Some(negative_ttl));
+    }
+
+    /// Caches an address resolution that never expires, replacing any other resolution of the same address.
+    pub fn insert_static(&mut self, ipv4_addr: Ipv4Addr, link_addr: MacAddress) -> Option<MacAddress> {
+        let record = Record {
+            link_addr: Some(link_addr),
+            is_static: true,
+        };
+        self.advance_clock();
+        self.update_stats(|stats| stats.insertions += 1);
+        self.cache
+            .insert_with_ttl(ipv4_addr, record, None)
+            .and_then(|r| r.link_addr)
+    }
+
+    /// Replaces all learned address resolutions, failed ones included, with the ones in `values`, in one go, so that
+    /// lookups see either the old resolutions or the new ones. Static resolutions are kept, and win over resolutions of
+    /// the same address in `values`.
+    pub fn replace_all(&mut self, values: &HashMap<Ipv4Addr, MacAddress>) {
+        // Expired resolutions are collected first, so that none of them outlives the replacement.
+        self.advance_clock();
+        let dynamic_addrs: Vec<Ipv4Addr> = self
+            .cache
+            .iter()
+            .filter(|(_, record)| !record.is_static)
+            .map(|(ipv4_addr, _)| *ipv4_addr)
+            .collect();
+        for ipv4_addr in dynamic_addrs {
+            self.cache.remove(&ipv4_addr);
+        }
+        for (&ipv4_addr, &link_addr) in values {
+            self.insert(ipv4_addr, link_addr);
+        }
+    }
+
+    /// Looks up the MAC address of given IPv4 address. Failed resolutions are reported only until they expire, so that
+    /// the address gets queried again afterwards.
+    pub fn get(&self, ipv4_addr: Ipv4Addr) -> ArpLookup {
         if self.disable {
-            Some(&DUMMY_MAC_ADDRESS)
+            ArpLookup::Hit(DUMMY_MAC_ADDRESS)
         } else {
-            self.cache.get(&ipv4_addr).map(|r| &r.link_addr)
+            let lookup: ArpLookup = match self.cache.get(&ipv4_addr) {
+                Some(Record {
+                    link_addr: Some(link_addr),
+                    ..
+                }) => ArpLookup::Hit(*link_addr),
+                Some(Record { link_addr: None, .. }) => match self.cache.get_alive(&ipv4_addr, self.clock.now()) {
+                    Some(_) => ArpLookup::NegativeHit,
+                    None => ArpLookup::Miss,
+                },
+                None => ArpLookup::Miss,
+            };
+            self.update_stats(|stats| {
+                stats.lookups += 1;
+                match lookup {
+                    ArpLookup::Hit(_) => stats.hits += 1,
+                    ArpLookup::NegativeHit => stats.negative_hits += 1,
+                    ArpLookup::Miss => stats.misses += 1,
+                }
+            });
+            lookup
         }
     }
 
-    /// Clears the ARP cache.
+    /// Clears the ARP cache. Resolutions that expired by now count as evictions.
     #[allow(unused)]
     pub fn clear(&mut self) {
+        self.advance_clock();
         self.cache.clear();
     }
 
+    /// Returns the counters of lookups, insertions and evictions.
+    pub fn stats(&self) -> ArpStats {
+        self.stats.get()
+    }
+
+    /// Zeroes the counters of lookups, insertions and evictions.
+    pub fn reset_stats(&mut self) {
+        self.stats.set(ArpStats::default());
+    }
+
+    /// Brings the clock of the underlying cache up to date and drops the resolutions that expired, counting them as
+    /// evictions.
+    fn advance_clock(&mut self) {
+        self.cache.advance_clock(self.clock.now());
+        let evictions: u64 = self.cache.cleanup() as u64;
+        self.update_stats(|stats| stats.evictions += evictions);
+    }
+
+    /// Applies `f` to the counters.
+    fn update_stats(&self, f: impl FnOnce(&mut ArpStats)) {
+        let mut stats: ArpStats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+
+    /// Takes a snapshot of the address resolutions that are stored in the ARP cache and of their remaining time to
+    /// live, all at the current time.
+    pub fn snapshot(&self) -> ArpSnapshot {
+        let now: Instant = self.clock.now();
+        let entries: HashMap<Ipv4Addr, ArpSnapshotEntry> = self
+            .cache
+            .iter_with_ttl(now)
+            .filter_map(|(ipv4_addr, record, remaining_ttl)| {
+                let entry: ArpSnapshotEntry = ArpSnapshotEntry {
+                    link_addr: record.link_addr?,
+                    remaining_ttl,
+                };
+                Some((*ipv4_addr, entry))
+            })
+            .collect();
+        ArpSnapshot { taken_at: now, entries }
+    }
+
     // Exports address resolutions that are stored in the ARP cache.
     #[cfg(test)]
     pub fn export(&self) -> HashMap<Ipv4Addr, MacAddress> {
         let mut map: HashMap<Ipv4Addr, MacAddress> = HashMap::default();
         for (k, v) in self.cache.iter() {
-            map.insert(*k, v.link_addr);
+            if let Some(link_addr) = v.link_addr {
+                map.insert(*k, link_addr);
+            }
         }
         map
     }
@@ -18,16 +18,55 @@ fn evict_with_default_ttl() -> Result<()> {
     let mut clock = SharedTimer::new(now);
 
     // Insert an IPv4 address in the ARP Cache.
-    let mut cache = ArpCache::new(clock.clone(), Some(ttl), None, false);
+    let mut cache = ArpCache::new(clock.clone(), Some(ttl), Some(ttl), None, false);
     cache.insert(test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC);
-    crate::ensure_eq!(cache.get(test_helpers::ALICE_IPV4), Some(&test_helpers::ALICE_MAC));
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
 
     // Advance the internal clock of the cache and clear it.
     clock.advance_clock(later);
     cache.clear();
 
     // The IPv4 address must be gone.
-    crate::ensure_eq!(cache.get(test_helpers::ALICE_IPV4), None);
+    crate::ensure_eq!(cache.get(test_helpers::ALICE_IPV4), ArpLookup::Miss);
+
+    Ok(())
+}
+
+/// Tests that the ARP Cache counts hits, misses, insertions and evictions.
+#[test]
+fn stats() -> Result<()> {
+    let now = Instant::now();
+    let ttl = Duration::from_secs(1);
+    let later = now + ttl;
+    let mut clock = SharedTimer::new(now);
+
+    // Insert an IPv4 address in the ARP Cache, then look it up along with one that is not there.
+    let mut cache = ArpCache::new(clock.clone(), Some(ttl), Some(ttl), None, false);
+    crate::ensure_eq!(cache.stats(), ArpStats::default());
+    cache.insert(test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC);
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
+    crate::ensure_eq!(cache.get(test_helpers::BOB_IPV4), ArpLookup::Miss);
+
+    // Let the entry expire and clear the cache.
+    clock.advance_clock(later);
+    cache.clear();
+
+    let stats: ArpStats = cache.stats();
+    crate::ensure_eq!(stats.lookups, 2);
+    crate::ensure_eq!(stats.hits, 1);
+    crate::ensure_eq!(stats.misses, 1);
+    crate::ensure_eq!(stats.insertions, 1);
+    crate::ensure_eq!(stats.evictions, 1);
+
+    // Resetting the counters zeroes them.
+    cache.reset_stats();
+    crate::ensure_eq!(cache.stats(), ArpStats::default());
 
     Ok(())
 }
@@ -44,10 +83,20 @@ fn import() -> Result<()> {
     map.insert(test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC);
 
     // Create an ARP Cache and import address resolution map.
-    let cache = ArpCache::new(clock, Some(ttl), Some(&map), false);
+    let mut cache = ArpCache::new(clock, Some(ttl), Some(ttl), Some(&map), false);
 
     // Check if address resolutions are in the ARP Cache.
-    crate::ensure_eq!(cache.get(test_helpers::ALICE_IPV4), Some(&test_helpers::ALICE_MAC));
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
+
+    // Imported address resolutions are static, so they survive replacing the contents of the ARP Cache.
+    cache.replace_all(&HashMap::new());
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
 
     Ok(())
 }
@@ -60,9 +109,12 @@ fn export() -> Result<()> {
     let clock = SharedTimer::new(now);
 
     // Insert an IPv4 address in the ARP Cache.
-    let mut cache = ArpCache::new(clock, Some(ttl), None, false);
+    let mut cache = ArpCache::new(clock, Some(ttl), Some(ttl), None, false);
     cache.insert(test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC);
-    crate::ensure_eq!(cache.get(test_helpers::ALICE_IPV4), Some(&test_helpers::ALICE_MAC));
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
 
     // Export address resolution map.
     let map: HashMap<Ipv4Addr, MacAddress> = cache.export();
@@ -75,3 +127,156 @@ fn export() -> Result<()> {
 
     Ok(())
 }
+
+/// Tests that a snapshot of the ARP Cache reports how long each entry has left, as of the time of the snapshot.
+#[test]
+fn snapshot() -> Result<()> {
+    let now = Instant::now();
+    let ttl = Duration::from_secs(10);
+    let mut clock = SharedTimer::new(now);
+
+    // Insert IPv4 addresses in the ARP Cache at different times.
+    let mut cache = ArpCache::new(clock.clone(), Some(ttl), Some(ttl), None, false);
+    cache.insert(test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC);
+    clock.advance_clock(now + Duration::from_secs(3));
+    cache.insert(test_helpers::BOB_IPV4, test_helpers::BOB_MAC);
+    clock.advance_clock(now + Duration::from_secs(4));
+    cache.insert(test_helpers::CARRIE_IPV4, test_helpers::CARRIE_MAC);
+
+    // Take a snapshot later on.
+    let later = now + Duration::from_secs(7);
+    clock.advance_clock(later);
+    let snapshot: ArpSnapshot = cache.snapshot();
+    crate::ensure_eq!(snapshot.taken_at, later);
+    crate::ensure_eq!(snapshot.entries.len(), 3);
+    for (ipv4_addr, link_addr, remaining_ttl) in [
+        (test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC, 3),
+        (test_helpers::BOB_IPV4, test_helpers::BOB_MAC, 6),
+        (test_helpers::CARRIE_IPV4, test_helpers::CARRIE_MAC, 7),
+    ] {
+        let expected: ArpSnapshotEntry = ArpSnapshotEntry {
+            link_addr,
+            remaining_ttl: Some(Duration::from_secs(remaining_ttl)),
+        };
+        crate::ensure_eq!(snapshot.entries.get(&ipv4_addr), Some(&expected));
+    }
+
+    // Expired entries are left out.
+    clock.advance_clock(now + Duration::from_secs(12));
+    let snapshot: ArpSnapshot = cache.snapshot();
+    crate::ensure_eq!(snapshot.entries.contains_key(&test_helpers::ALICE_IPV4), false);
+    crate::ensure_eq!(snapshot.entries.len(), 2);
+
+    Ok(())
+}
+
+/// Tests that replacing the contents of the ARP Cache drops the learned entries and keeps the static ones.
+#[test]
+fn replace_all() -> Result<()> {
+    let now = Instant::now();
+    let ttl = Duration::from_secs(10);
+    let clock = SharedTimer::new(now);
+
+    // Populate the ARP Cache with a static entry and a learned one.
+    let mut cache = ArpCache::new(clock, Some(ttl), Some(ttl), None, false);
+    cache.insert_static(test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC);
+    cache.insert(test_helpers::BOB_IPV4, test_helpers::BOB_MAC);
+
+    // A learned resolution does not overwrite a static one.
+    cache.insert(test_helpers::ALICE_IPV4, test_helpers::BOB_MAC);
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
+
+    // Replace the contents of the ARP Cache, including a resolution for the address of the static entry.
+    let mut map: HashMap<Ipv4Addr, MacAddress> = HashMap::new();
+    map.insert(test_helpers::CARRIE_IPV4, test_helpers::CARRIE_MAC);
+    map.insert(test_helpers::ALICE_IPV4, test_helpers::CARRIE_MAC);
+    cache.replace_all(&map);
+
+    // The old learned entry is gone, the static one remains, and the new one is present.
+    crate::ensure_eq!(cache.get(test_helpers::BOB_IPV4), ArpLookup::Miss);
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
+    crate::ensure_eq!(
+        cache.get(test_helpers::CARRIE_IPV4),
+        ArpLookup::Hit(test_helpers::CARRIE_MAC)
+    );
+    crate::ensure_eq!(cache.export().len(), 2);
+
+    // Static entries never expire.
+    let snapshot: ArpSnapshot = cache.snapshot();
+    crate::ensure_eq!(
+        snapshot
+            .entries
+            .get(&test_helpers::ALICE_IPV4)
+            .map(|entry| entry.remaining_ttl),
+        Some(None)
+    );
+
+    Ok(())
+}
+
+/// Tests that failed resolutions are reported until their own time to live runs out, and give way to learned ones.
+#[test]
+fn negative() -> Result<()> {
+    let now = Instant::now();
+    let ttl = Duration::from_secs(10);
+    let negative_ttl = Duration::from_secs(2);
+    let mut clock = SharedTimer::new(now);
+
+    // Record failed resolutions for an address that has a static entry and for two that have none.
+    let mut cache = ArpCache::new(clock.clone(), Some(ttl), Some(negative_ttl), None, false);
+    cache.insert_static(test_helpers::ALICE_IPV4, test_helpers::ALICE_MAC);
+    cache.insert_negative(test_helpers::ALICE_IPV4);
+    cache.insert_negative(test_helpers::BOB_IPV4);
+    cache.insert_negative(test_helpers::CARRIE_IPV4);
+    crate::ensure_eq!(
+        cache.get(test_helpers::ALICE_IPV4),
+        ArpLookup::Hit(test_helpers::ALICE_MAC)
+    );
+    crate::ensure_eq!(cache.get(test_helpers::BOB_IPV4), ArpLookup::NegativeHit);
+    crate::ensure_eq!(cache.stats().negative_hits, 1);
+
+    // Failed resolutions are left out of snapshots and exports.
+    crate::ensure_eq!(cache.snapshot().entries.len(), 1);
+    crate::ensure_eq!(cache.export().len(), 1);
+
+    // A learned resolution replaces a failed one.
+    cache.insert(test_helpers::CARRIE_IPV4, test_helpers::CARRIE_MAC);
+    crate::ensure_eq!(
+        cache.get(test_helpers::CARRIE_IPV4),
+        ArpLookup::Hit(test_helpers::CARRIE_MAC)
+    );
+
+    // Failed resolutions expire after the negative time to live, well before learned ones do.
+    clock.advance_clock(now + negative_ttl);
+    crate::ensure_eq!(cache.get(test_helpers::BOB_IPV4), ArpLookup::Miss);
+    crate::ensure_eq!(
+        cache.get(test_helpers::CARRIE_IPV4),
+        ArpLookup::Hit(test_helpers::CARRIE_MAC)
+    );
+
+    Ok(())
+}
+
+/// Tests that failed resolutions are not recorded when negative caching is disabled, be it with no time to live or a
+/// zero one.
+#[test]
+fn negative_disabled() -> Result<()> {
+    let now = Instant::now();
+    let ttl = Duration::from_secs(10);
+    let clock = SharedTimer::new(now);
+
+    for negative_ttl in [None, Some(Duration::ZERO)] {
+        let mut cache = ArpCache::new(clock.clone(), Some(ttl), negative_ttl, None, false);
+        cache.insert_negative(test_helpers::BOB_IPV4);
+        crate::ensure_eq!(cache.get(test_helpers::BOB_IPV4), ArpLookup::Miss);
+        crate::ensure_eq!(cache.stats().insertions, 0);
+    }
+
+    Ok(())
+}
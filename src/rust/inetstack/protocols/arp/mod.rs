@@ -8,4 +8,9 @@ mod peer;
 #[cfg(test)]
 mod tests;
 
+pub use cache::{
+    ArpSnapshot,
+    ArpSnapshotEntry,
+    ArpStats,
+};
 pub use peer::SharedArpPeer;
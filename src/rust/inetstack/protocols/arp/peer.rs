@@ -2,7 +2,12 @@
 // Licensed under the MIT license.
 
 use super::{
-    cache::ArpCache,
+    cache::{
+        ArpCache,
+        ArpLookup,
+        ArpSnapshot,
+        ArpStats,
+    },
     packet::{
         ArpHeader,
         ArpMessage,
@@ -20,7 +25,11 @@ use crate::{
         memory::DemiBuffer,
         network::{
             config::ArpConfig,
-            types::MacAddress,
+            types::{
+                DropReason,
+                DropStats,
+                MacAddress,
+            },
             NetworkRuntime,
         },
         scheduler::Yielder,
@@ -38,7 +47,10 @@ use ::futures::{
     select_biased,
     FutureExt,
 };
-use ::libc::ETIMEDOUT;
+use ::libc::{
+    EHOSTUNREACH,
+    ETIMEDOUT,
+};
 use ::std::{
     collections::{
         HashMap,
@@ -70,13 +82,23 @@ pub struct ArpPeer<N: NetworkRuntime> {
     local_ipv4_addr: Ipv4Addr,
     cache: ArpCache,
     waiters: HashMap<Ipv4Addr, LinkedList<Sender<MacAddress>>>,
+    /// Pending address probes, which are told the link address of any other host that claims the probed address.
+    probes: HashMap<Ipv4Addr, Sender<MacAddress>>,
     arp_config: ArpConfig,
     recv_queue: AsyncQueue<DemiBuffer>,
+    drop_stats: SharedObject<DropStats>,
 }
 
 #[derive(Clone)]
 pub struct SharedArpPeer<N: NetworkRuntime>(SharedObject<ArpPeer<N>>);
 
+/// Address probe that is told about hosts that claim the probed address, until it is dropped.
+struct PendingProbe<N: NetworkRuntime> {
+    peer: SharedArpPeer<N>,
+    ipv4_addr: Ipv4Addr,
+    conflict: Receiver<MacAddress>,
+}
+
 //==============================================================================
 // Associate Functions
 //==============================================================================
@@ -91,10 +113,12 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
         local_link_addr: MacAddress,
         local_ipv4_addr: Ipv4Addr,
         arp_config: ArpConfig,
+        drop_stats: SharedObject<DropStats>,
     ) -> Result<Self, Fail> {
         let cache: ArpCache = ArpCache::new(
             runtime.get_timer(),
             Some(arp_config.get_cache_ttl()),
+            arp_config.get_negative_cache_ttl(),
             Some(arp_config.get_initial_values()),
             arp_config.get_disable_arp(),
         );
@@ -106,8 +130,10 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
             local_ipv4_addr,
             cache,
             waiters: HashMap::default(),
+            probes: HashMap::default(),
             arp_config,
             recv_queue: AsyncQueue::<DemiBuffer>::default(),
+            drop_stats,
         }));
         // This is a future returned by the async function.
         runtime.insert_background_coroutine("Inetstack::arp::background", Box::pin(peer.clone().poll().fuse()))?;
@@ -135,7 +161,7 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
 
     async fn do_wait_link_addr(&mut self, ipv4_addr: Ipv4Addr) -> MacAddress {
         let (tx, rx): (Sender<MacAddress>, Receiver<MacAddress>) = channel();
-        if let Some(&link_addr) = self.cache.get(ipv4_addr) {
+        if let ArpLookup::Hit(link_addr) = self.cache.get(ipv4_addr) {
             let _ = tx.send(link_addr);
         } else {
             if let Some(wait_queue) = self.waiters.get_mut(&ipv4_addr) {
@@ -185,19 +211,41 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
                 Err(e) => {
                     let cause: String = format!("could not parse ARP header:");
                     warn!("arp_cache::poll(): {} {:?}", &cause, e);
+                    self.drop_stats.record(DropReason::from_parse_error(&e));
                     continue;
                 },
             };
             debug!("Received {:?}", header);
 
+            // from RFC 5227:
+            // > If during this period, from the beginning of the probing process until ANNOUNCE_WAIT seconds after the
+            // > last probe packet is sent, the host receives any ARP packet (Request *or* Reply) on the interface where
+            // > the probe is being performed, where the packet's 'sender IP address' is the address being probed for,
+            // > then the host MUST treat this address as being in use by some other host. In addition, if during this
+            // > period the host receives any ARP Probe where the packet's 'target IP address' is the address being
+            // > probed for, and the packet's 'sender hardware address' is not the hardware address of any of the host's
+            // > interfaces, then the host SHOULD similarly treat this as an address conflict.
+            let sender_protocol_addr: Ipv4Addr = header.get_sender_protocol_addr();
+            let claimed_addr: Ipv4Addr = if sender_protocol_addr.is_unspecified() {
+                header.get_destination_protocol_addr()
+            } else {
+                sender_protocol_addr
+            };
+            if header.get_sender_hardware_addr() != self.local_link_addr {
+                if let Some(probe) = self.probes.remove(&claimed_addr) {
+                    let _ = probe.send(header.get_sender_hardware_addr());
+                }
+            }
+
             // from RFC 826:
             // > Merge_flag := false
             // > If the pair <protocol type, sender protocol address> is
             // > already in my translation table, update the sender
             // > hardware address field of the entry with the new
             // > information in the packet and set Merge_flag to true.
+            // An address that recently failed to resolve is also in the table, and this shows that it resolves now.
             let merge_flag: bool = {
-                if self.cache.get(header.get_sender_protocol_addr()).is_some() {
+                if self.cache.get(header.get_sender_protocol_addr()) != ArpLookup::Miss {
                     self.do_insert(header.get_sender_protocol_addr(), header.get_sender_hardware_addr());
                     true
                 } else {
@@ -217,7 +265,8 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
             // > If Merge_flag is false, add the triplet <protocol type,
             // > sender protocol address, sender hardware address> to
             // > the translation table.
-            if !merge_flag {
+            // Address probes (RFC 5227) come from hosts that do not have an address yet, so there is nothing to cache.
+            if !merge_flag && !sender_protocol_addr.is_unspecified() {
                 self.do_insert(header.get_sender_protocol_addr(), header.get_sender_hardware_addr());
             }
 
@@ -252,13 +301,42 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
         }
     }
 
+    /// Changes the local link address that is advertised in ARP messages.
+    pub fn set_local_link_addr(&mut self, local_link_addr: MacAddress) {
+        self.local_link_addr = local_link_addr;
+    }
+
+    /// Broadcasts a gratuitous ARP request, so that neighbors update the entry for our IPv4 address.
+    pub fn announce(&mut self) {
+        let msg: ArpMessage = ArpMessage::new(
+            Ethernet2Header::new(MacAddress::broadcast(), self.local_link_addr, EtherType2::Arp),
+            ArpHeader::new(
+                ArpOperation::Request,
+                self.local_link_addr,
+                self.local_ipv4_addr,
+                MacAddress::broadcast(),
+                self.local_ipv4_addr,
+            ),
+        );
+        debug!("Announcing {:?}", msg);
+        self.network.transmit(Box::new(msg));
+    }
+
     pub fn try_query(&self, ipv4_addr: Ipv4Addr) -> Option<MacAddress> {
-        self.cache.get(ipv4_addr).cloned()
+        self.cache.get(ipv4_addr).link_addr()
     }
 
+    /// Resolves the link address of `ipv4_addr`. If no host answers, the failure is cached for a while, and queries of
+    /// the same address fail right away with `EHOSTUNREACH` meanwhile.
     pub async fn query(&mut self, ipv4_addr: Ipv4Addr, yielder: &Yielder) -> Result<MacAddress, Fail> {
-        if let Some(&link_addr) = self.cache.get(ipv4_addr) {
-            return Ok(link_addr);
+        match self.cache.get(ipv4_addr) {
+            ArpLookup::Hit(link_addr) => return Ok(link_addr),
+            ArpLookup::NegativeHit => {
+                let cause: String = format!("address recently failed to resolve (ipv4_addr={:?})", ipv4_addr);
+                warn!("query(): {}", cause);
+                return Err(Fail::new(EHOSTUNREACH, &cause));
+            },
+            ArpLookup::Miss => {},
         }
         let msg = ArpMessage::new(
             Ethernet2Header::new(MacAddress::broadcast(), self.local_link_addr, EtherType2::Arp),
@@ -294,6 +372,7 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
                     },
                 }
             }
+            self.cache.insert_negative(ipv4_addr);
             Err(Fail::new(ETIMEDOUT, "ARP query timeout"))
         };
 
@@ -302,6 +381,105 @@ impl<N: NetworkRuntime> SharedArpPeer<N> {
         result
     }
 
+    /// Probes whether another host on the link uses `ipv4_addr`, before we claim it (RFC 5227). The probe is an ARP
+    /// request with an all-zero sender IPv4 address, so that it does not pollute the ARP caches of other hosts. It is
+    /// sent as many times as ARP requests are, and fails with `EADDRINUSE` as soon as another host claims the address.
+    pub async fn probe(&mut self, ipv4_addr: Ipv4Addr, yielder: &Yielder) -> Result<(), Fail> {
+        if self.probes.contains_key(&ipv4_addr) {
+            let cause: String = format!("address is already being probed (ipv4_addr={:?})", ipv4_addr);
+            warn!("probe(): {}", cause);
+            return Err(Fail::new(libc::EALREADY, &cause));
+        }
+        let msg = ArpMessage::new(
+            Ethernet2Header::new(MacAddress::broadcast(), self.local_link_addr, EtherType2::Arp),
+            ArpHeader::new(
+                ArpOperation::Request,
+                self.local_link_addr,
+                Ipv4Addr::UNSPECIFIED,
+                MacAddress::nil(),
+                ipv4_addr,
+            ),
+        );
+        let (tx, rx): (Sender<MacAddress>, Receiver<MacAddress>) = channel();
+        self.probes.insert(ipv4_addr, tx);
+        let mut probe: PendingProbe<N> = PendingProbe {
+            peer: self.clone(),
+            ipv4_addr,
+            conflict: rx,
+        };
+        let mut conflict = (&mut probe.conflict).fuse();
+
+        for i in 0..self.arp_config.get_retry_count() + 1 {
+            self.network.transmit(Box::new(msg.clone()));
+            let timer = self
+                .runtime
+                .get_timer()
+                .wait(self.arp_config.get_request_timeout(), yielder);
+
+            match conflict.with_timeout(timer).await {
+                Ok(Ok(link_addr)) => {
+                    let cause: String = format!(
+                        "address is in use by another host (ipv4_addr={:?}, link_addr={:?})",
+                        ipv4_addr, link_addr
+                    );
+                    warn!("probe(): {}", cause);
+                    return Err(Fail::new(libc::EADDRINUSE, &cause));
+                },
+                Ok(Err(_)) => {
+                    let cause: String = format!("address probe was abandoned (ipv4_addr={:?})", ipv4_addr);
+                    warn!("probe(): {}", cause);
+                    return Err(Fail::new(libc::ECANCELED, &cause));
+                },
+                Err(e) if e.errno == ETIMEDOUT => debug!("no conflict for {:?}; probe {}.", ipv4_addr, i + 1),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Caches an address resolution that never expires, and hands it to the queries that were waiting for it.
+    pub fn insert_static(&mut self, ipv4_addr: Ipv4Addr, link_addr: MacAddress) {
+        if let Some(wait_queue) = self.waiters.remove(&ipv4_addr) {
+            for sender in wait_queue {
+                let _ = sender.send(link_addr);
+            }
+        }
+        self.cache.insert_static(ipv4_addr, link_addr);
+    }
+
+    /// Replaces the learned address resolutions in the ARP cache with the ones in `values`, keeping static ones, and
+    /// hands the new resolutions to the queries that were waiting for them.
+    pub fn replace_cache(&mut self, values: &HashMap<Ipv4Addr, MacAddress>) {
+        self.cache.replace_all(values);
+        for ipv4_addr in values.keys() {
+            if let Some(wait_queue) = self.waiters.remove(ipv4_addr) {
+                let link_addr: MacAddress = self
+                    .cache
+                    .get(*ipv4_addr)
+                    .link_addr()
+                    .expect("address should have just been cached");
+                for sender in wait_queue {
+                    let _ = sender.send(link_addr);
+                }
+            }
+        }
+    }
+
+    /// Returns the counters of lookups, insertions and evictions of the ARP cache.
+    pub fn cache_stats(&self) -> ArpStats {
+        self.cache.stats()
+    }
+
+    /// Zeroes the counters of the ARP cache.
+    pub fn reset_cache_stats(&mut self) {
+        self.cache.reset_stats()
+    }
+
+    /// Takes a consistent snapshot of the ARP cache.
+    pub fn snapshot_cache(&self) -> ArpSnapshot {
+        self.cache.snapshot()
+    }
+
     #[cfg(test)]
     pub fn export_cache(&self) -> HashMap<Ipv4Addr, MacAddress> {
         self.cache.export()
@@ -325,3 +503,15 @@ impl<N: NetworkRuntime> DerefMut for SharedArpPeer<N> {
         self.0.deref_mut()
     }
 }
+
+impl<N: NetworkRuntime> Drop for PendingProbe<N> {
+    fn drop(&mut self) {
+        // Once a conflict was reported, another probe of the same address may have started.
+        let ipv4_addr: Ipv4Addr = self.ipv4_addr;
+        if let Some(tx) = self.peer.probes.get(&ipv4_addr) {
+            if tx.is_connected_to(&self.conflict) {
+                self.peer.probes.remove(&ipv4_addr);
+            }
+        }
+    }
+}
@@ -13,9 +13,14 @@ use crate::{
             SharedEngine,
         },
     },
-    runtime::network::{
-        types::MacAddress,
-        NetworkRuntime,
+    runtime::{
+        network::{
+            types::MacAddress,
+            NetworkRuntime,
+        },
+        OperationResult,
+        QDesc,
+        QToken,
     },
 };
 use ::anyhow::Result;
@@ -23,9 +28,13 @@ use ::futures::task::{
     noop_waker_ref,
     Context,
 };
-use ::libc::ETIMEDOUT;
+use ::libc::{
+    EHOSTUNREACH,
+    ETIMEDOUT,
+};
 use ::std::{
     future::Future,
+    net::Ipv4Addr,
     task::Poll,
     time::{
         Duration,
@@ -222,3 +231,216 @@ fn no_reply() -> Result<()> {
         _ => anyhow::bail!("poll should have succeeded"),
     }
 }
+
+/// Tests that once a query goes unanswered, queries of the same address fail right away until the failure expires.
+#[test]
+fn no_reply_is_cached_negatively() -> Result<()> {
+    let mut now = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice(now);
+    let mut alice_transport = alice.get_transport();
+    let request_timeout: Duration = alice_transport.get_network().get_arp_config().get_request_timeout();
+    let negative_cache_ttl: Duration = alice_transport
+        .get_network()
+        .get_arp_config()
+        .get_negative_cache_ttl()
+        .expect("negative caching should be enabled by default");
+    let mut ctx = Context::from_waker(noop_waker_ref());
+
+    // Let a query time out.
+    {
+        let fut = alice_transport.arp_query(test_helpers::CARRIE_IPV4);
+        pin_mut!(fut);
+        let result = loop {
+            if let Poll::Ready(result) = Future::poll(fut.as_mut(), &mut ctx) {
+                break result;
+            }
+            now += request_timeout;
+            alice.advance_clock(now);
+        };
+        crate::ensure_eq!(result.map_err(|e| e.errno), Err(ETIMEDOUT));
+    }
+    alice.pop_all_frames();
+
+    // The next query fails without sending any request.
+    {
+        let fut = alice_transport.arp_query(test_helpers::CARRIE_IPV4);
+        pin_mut!(fut);
+        match Future::poll(fut.as_mut(), &mut ctx) {
+            Poll::Ready(Err(error)) if error.errno == EHOSTUNREACH => {},
+            _ => anyhow::bail!("query should have failed right away"),
+        }
+        crate::ensure_eq!(alice.pop_all_frames().len(), 0);
+    }
+
+    // Once the failure expires, queries send requests again.
+    now += negative_cache_ttl;
+    alice.advance_clock(now);
+    let fut = alice_transport.arp_query(test_helpers::CARRIE_IPV4);
+    pin_mut!(fut);
+    crate::ensure_eq!(Future::poll(fut.as_mut(), &mut ctx).is_pending(), true);
+    crate::ensure_eq!(alice.pop_all_frames().len(), 1);
+
+    Ok(())
+}
+
+/// Tests that overriding the local link address announces it with a gratuitous ARP.
+#[test]
+fn set_local_link_addr_announces() -> Result<()> {
+    let now = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice(now);
+    let mac: MacAddress = MacAddress::new([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    alice.set_local_link_addr(mac)?;
+    crate::ensure_eq!(alice.local_link_addr()?, mac);
+
+    let bytes = alice.pop_frame();
+    let (eth2_header, payload) = match Ethernet2Header::parse(bytes) {
+        Ok(result) => result,
+        Err(e) => anyhow::bail!("Could not parse ethernet header: {:?}", e),
+    };
+    crate::ensure_eq!(eth2_header.src_addr(), mac);
+    crate::ensure_eq!(eth2_header.dst_addr(), MacAddress::broadcast());
+    let arp = match ArpHeader::parse(payload) {
+        Ok(arp) => arp,
+        Err(e) => anyhow::bail!("Could not parse arp header: {:?}", e),
+    };
+    crate::ensure_eq!(arp.get_operation(), ArpOperation::Request);
+    crate::ensure_eq!(arp.get_sender_hardware_addr(), mac);
+    crate::ensure_eq!(arp.get_sender_protocol_addr(), test_helpers::ALICE_IPV4);
+
+    // The link address cannot be changed once a socket exists.
+    alice.tcp_socket()?;
+    match alice.set_local_link_addr(test_helpers::ALICE_MAC) {
+        Err(e) if e.errno == libc::EBUSY => Ok(()),
+        _ => anyhow::bail!("set_local_link_addr should fail with open sockets"),
+    }
+}
+
+/// Tests that probing an address that another host uses fails with EADDRINUSE.
+#[test]
+fn probe_address_in_use() -> Result<()> {
+    let now = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice(now);
+    let mut carrie: SharedEngine = test_helpers::new_carrie(now);
+
+    // Alice probes Carrie's address, without giving away her own.
+    let alice_qd: QDesc = alice.udp_socket()?;
+    let probe_qt: QToken = alice.probe_address(alice_qd, test_helpers::CARRIE_IPV4)?;
+    alice.poll();
+    let probe = alice.pop_frame();
+    let payload = match Ethernet2Header::parse(probe.clone()) {
+        Ok((_, payload)) => payload,
+        Err(e) => anyhow::bail!("Could not parse ethernet header: {:?}", e),
+    };
+    let arp = match ArpHeader::parse(payload) {
+        Ok(arp) => arp,
+        Err(e) => anyhow::bail!("Could not parse arp header: {:?}", e),
+    };
+    crate::ensure_eq!(arp.get_operation(), ArpOperation::Request);
+    crate::ensure_eq!(arp.get_sender_protocol_addr(), Ipv4Addr::UNSPECIFIED);
+    crate::ensure_eq!(arp.get_destination_protocol_addr(), test_helpers::CARRIE_IPV4);
+
+    // Carrie replies, which reveals the conflict.
+    carrie.receive(probe)?;
+    crate::ensure_eq!(carrie.export_arp_cache().is_empty(), true);
+    alice.receive(carrie.pop_frame())?;
+    match alice.wait(probe_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::EADDRINUSE => Ok(()),
+        _ => anyhow::bail!("probe should fail with EADDRINUSE"),
+    }
+}
+
+/// Tests that probing an address that no host uses succeeds once all probes went unanswered.
+#[test]
+fn probe_address_unused() -> Result<()> {
+    let mut now = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice(now);
+    let retry_count = alice.get_transport().get_network().get_arp_config().get_retry_count();
+    let request_timeout = alice
+        .get_transport()
+        .get_network()
+        .get_arp_config()
+        .get_request_timeout();
+
+    let alice_qd: QDesc = alice.udp_socket()?;
+    let probe_qt: QToken = alice.probe_address(alice_qd, Ipv4Addr::new(192, 168, 1, 100))?;
+    for _ in 0..retry_count + 1 {
+        alice.poll();
+        crate::ensure_eq!(alice.get_runtime().has_completed(probe_qt)?, false);
+        crate::ensure_eq!(alice.pop_all_frames().len(), 1);
+        now += request_timeout;
+        alice.advance_clock(now);
+    }
+    match alice.wait(probe_qt)? {
+        (_, OperationResult::Connect) => Ok(()),
+        _ => anyhow::bail!("probe should succeed"),
+    }
+}
+
+/// Tests that probing an address that another host is probing at the same time fails with EADDRINUSE.
+#[test]
+fn probe_address_simultaneous() -> Result<()> {
+    let now = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice(now);
+    let mut carrie: SharedEngine = test_helpers::new_carrie(now);
+    let ipv4_addr: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 100);
+
+    // Alice and Carrie both probe an address that no host uses.
+    let alice_qd: QDesc = alice.udp_socket()?;
+    let alice_qt: QToken = alice.probe_address(alice_qd, ipv4_addr)?;
+    alice.poll();
+    alice.pop_frame();
+    let carrie_qd: QDesc = carrie.udp_socket()?;
+    carrie.probe_address(carrie_qd, ipv4_addr)?;
+    carrie.poll();
+
+    // Alice sees the probe of Carrie, which reveals the conflict.
+    alice.receive(carrie.pop_frame())?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::EADDRINUSE => Ok(()),
+        _ => anyhow::bail!("probe should fail with EADDRINUSE"),
+    }
+}
+
+/// Tests that closing the queue on which an address is probed cancels the probe, and that the address may be probed
+/// again afterwards.
+#[test]
+fn probe_address_close() -> Result<()> {
+    let now = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice(now);
+    let ipv4_addr: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 100);
+
+    let alice_qd: QDesc = alice.udp_socket()?;
+    let probe_qt: QToken = alice.probe_address(alice_qd, ipv4_addr)?;
+    alice.poll();
+    alice.udp_close(alice_qd)?;
+    match alice.wait(probe_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ECANCELED => {},
+        _ => anyhow::bail!("probe should fail with ECANCELED"),
+    };
+
+    let alice_qd: QDesc = alice.udp_socket()?;
+    let probe_qt: QToken = alice.probe_address(alice_qd, ipv4_addr)?;
+    alice.poll();
+    crate::ensure_eq!(alice.get_runtime().has_completed(probe_qt)?, false);
+
+    Ok(())
+}
+
+/// Tests that an address may be probed again once a probe of it was aborted before it completed.
+#[test]
+fn probe_address_abort() -> Result<()> {
+    let now = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice(now);
+    let ipv4_addr: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 100);
+
+    let alice_qd: QDesc = alice.udp_socket()?;
+    let probe_qt: QToken = alice.probe_address(alice_qd, ipv4_addr)?;
+    alice.poll();
+    alice.get_runtime().abort_coroutine(probe_qt);
+
+    let probe_qt: QToken = alice.probe_address(alice_qd, ipv4_addr)?;
+    alice.poll();
+    crate::ensure_eq!(alice.get_runtime().has_completed(probe_qt)?, false);
+
+    Ok(())
+}
@@ -3,16 +3,19 @@
 
 use super::protocol::Icmpv4Type2;
 use crate::{
+    inetstack::protocols::{
+        compute_generic_checksum,
+        fold16,
+    },
     runtime::{
         fail::Fail,
         memory::DemiBuffer,
     },
-    inetstack::protocols::{
-        fold16,
-        compute_generic_checksum,
-    }
 };
-use ::libc::EBADMSG;
+use ::libc::{
+    EBADMSG,
+    EILSEQ,
+};
 use ::std::convert::TryInto;
 
 /// Size of ICMPv4 Headers (in bytes)
@@ -49,7 +52,7 @@ impl Icmpv4Header {
         let type_byte: u8 = hdr_buf[0];
         let code: u8 = hdr_buf[1];
         if Self::compute_checksum(hdr_buf, &buf[ICMPV4_HEADER_SIZE..]) != 0 {
-            return Err(Fail::new(EBADMSG, "ICMPv4 checksum mismatch"));
+            return Err(Fail::new(EILSEQ, "ICMPv4 checksum mismatch"));
         }
         let rest_of_header: &[u8; 4] = hdr_buf[4..8].try_into().unwrap();
         let icmpv4_type: Icmpv4Type2 = Icmpv4Type2::parse(type_byte, rest_of_header)?;
@@ -88,4 +91,8 @@ impl Icmpv4Header {
     pub fn get_protocol(&self) -> Icmpv4Type2 {
         self.protocol
     }
+
+    pub fn get_code(&self) -> u8 {
+        self.code
+    }
 }
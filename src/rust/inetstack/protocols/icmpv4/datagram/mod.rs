@@ -4,8 +4,10 @@
 mod header;
 mod message;
 mod protocol;
+mod unreachable;
 
 pub use self::protocol::ICMPV4_ECHO_REQUEST_MESSAGE_SIZE;
 pub use header::Icmpv4Header;
 pub use message::Icmpv4Message;
 pub use protocol::Icmpv4Type2;
+pub use unreachable::FragmentationNeeded;
@@ -17,11 +17,20 @@ pub const ICMPV4_ECHO_REQUEST_MESSAGE_SIZE: u16 = 56;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Icmpv4Type2 {
-    EchoReply { id: u16, seq_num: u16 },
-    DestinationUnreachable,
+    EchoReply {
+        id: u16,
+        seq_num: u16,
+    },
+    /// The MTU of the next hop is only meaningful for "fragmentation needed" messages (RFC 1191).
+    DestinationUnreachable {
+        next_hop_mtu: u16,
+    },
     SourceQuench,
     RedirectMessage,
-    EchoRequest { id: u16, seq_num: u16 },
+    EchoRequest {
+        id: u16,
+        seq_num: u16,
+    },
     RouterAdvertisement,
     RouterSolicitation,
     TimeExceeded,
@@ -39,7 +48,10 @@ impl Icmpv4Type2 {
                 let seq_num: u16 = u16::from_be_bytes([rest_of_header[2], rest_of_header[3]]);
                 Ok(EchoReply { id, seq_num })
             },
-            3 => Ok(DestinationUnreachable),
+            3 => {
+                let next_hop_mtu: u16 = u16::from_be_bytes([rest_of_header[2], rest_of_header[3]]);
+                Ok(DestinationUnreachable { next_hop_mtu })
+            },
             4 => Ok(SourceQuench),
             5 => Ok(RedirectMessage),
             8 => {
@@ -66,7 +78,10 @@ impl Icmpv4Type2 {
                 let [seq1, seq2] = seq_num.to_be_bytes();
                 (0, [id1, id2, seq1, seq2])
             },
-            DestinationUnreachable => (3, zero),
+            DestinationUnreachable { next_hop_mtu } => {
+                let [mtu1, mtu2] = next_hop_mtu.to_be_bytes();
+                (3, [0, 0, mtu1, mtu2])
+            },
             SourceQuench => (4, zero),
             RedirectMessage => (5, zero),
             EchoRequest { id, seq_num } => {
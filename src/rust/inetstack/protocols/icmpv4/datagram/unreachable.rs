@@ -0,0 +1,106 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use super::{
+    Icmpv4Header,
+    Icmpv4Type2,
+};
+use crate::{
+    inetstack::protocols::{
+        ip::IpProtocol,
+        ipv4::IPV4_HEADER_MIN_SIZE,
+    },
+    runtime::memory::DemiBuffer,
+};
+use ::std::{
+    convert::TryFrom,
+    net::{
+        Ipv4Addr,
+        SocketAddrV4,
+    },
+};
+
+//==============================================================================
+// Constants
+//==============================================================================
+
+/// Code of "fragmentation needed and DF set" Destination Unreachable messages.
+const ICMPV4_CODE_FRAGMENTATION_NEEDED: u8 = 4;
+
+/// Smallest MTU that every IPv4 link must support (RFC 791).
+const IPV4_MIN_MTU: u16 = 68;
+
+/// Number of bytes of the original datagram's payload that a Destination Unreachable message carries.
+const ORIGINAL_PAYLOAD_SIZE: usize = 8;
+
+//==============================================================================
+// Structures
+//==============================================================================
+
+/// ICMP "fragmentation needed" message, which a router sends back when a datagram with the DF flag set does not fit
+/// in the MTU of the next hop (RFC 1191).
+#[derive(Copy, Clone, Debug)]
+pub struct FragmentationNeeded {
+    /// Protocol of the datagram that did not fit.
+    pub protocol: IpProtocol,
+    /// Source address and port of the datagram that did not fit, i.e. our end of the flow.
+    pub src: SocketAddrV4,
+    /// Destination address and port of the datagram that did not fit, i.e. the remote end of the flow.
+    pub dst: SocketAddrV4,
+    /// MTU of the next hop.
+    pub next_hop_mtu: u16,
+    /// Sequence number of the datagram that did not fit, if it was a TCP segment.
+    pub seq_num: Option<u32>,
+}
+
+//==============================================================================
+// Associated Functions
+//==============================================================================
+
+impl FragmentationNeeded {
+    /// Parses the payload of an ICMPv4 datagram, returning `None` if it is not a well-formed "fragmentation needed"
+    /// message for a TCP or UDP datagram.
+    pub fn parse(buf: DemiBuffer) -> Option<Self> {
+        let (icmpv4_hdr, data): (Icmpv4Header, DemiBuffer) = Icmpv4Header::parse(buf).ok()?;
+        let next_hop_mtu: u16 = match icmpv4_hdr.get_protocol() {
+            Icmpv4Type2::DestinationUnreachable { next_hop_mtu }
+                if icmpv4_hdr.get_code() == ICMPV4_CODE_FRAGMENTATION_NEEDED =>
+            {
+                next_hop_mtu
+            },
+            _ => return None,
+        };
+        if next_hop_mtu < IPV4_MIN_MTU {
+            return None;
+        }
+
+        // The message carries the IPv4 header of the original datagram, followed by the start of its payload, which
+        // holds the ports of both TCP and UDP.
+        let ihl: usize = ((*data.first()? & 0xf) as usize) * 4;
+        if ihl < IPV4_HEADER_MIN_SIZE as usize || data.len() < ihl + ORIGINAL_PAYLOAD_SIZE {
+            return None;
+        }
+        let protocol: IpProtocol = IpProtocol::try_from(data[9]).ok()?;
+        let src_addr: Ipv4Addr = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+        let dst_addr: Ipv4Addr = Ipv4Addr::new(data[16], data[17], data[18], data[19]);
+        let src_port: u16 = u16::from_be_bytes([data[ihl], data[ihl + 1]]);
+        let dst_port: u16 = u16::from_be_bytes([data[ihl + 2], data[ihl + 3]]);
+        let seq_num: Option<u32> = match protocol {
+            IpProtocol::TCP => Some(u32::from_be_bytes([
+                data[ihl + 4],
+                data[ihl + 5],
+                data[ihl + 6],
+                data[ihl + 7],
+            ])),
+            _ => None,
+        };
+
+        Some(Self {
+            protocol,
+            src: SocketAddrV4::new(src_addr, src_port),
+            dst: SocketAddrV4::new(dst_addr, dst_port),
+            next_hop_mtu,
+            seq_num,
+        })
+    }
+}
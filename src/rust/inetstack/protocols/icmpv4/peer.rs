@@ -25,7 +25,11 @@ use crate::{
         fail::Fail,
         memory::DemiBuffer,
         network::{
-            types::MacAddress,
+            types::{
+                DropReason,
+                DropStats,
+                MacAddress,
+            },
             NetworkRuntime,
         },
         scheduler::{
@@ -100,6 +104,9 @@ pub struct Icmpv4Peer<N: NetworkRuntime> {
 
     /// Inflight ping requests.
     inflight: HashMap<(u16, u16), AsyncValue<Result<(), Fail>>>,
+
+    /// Incoming packets dropped by the stack.
+    drop_stats: SharedObject<DropStats>,
 }
 
 #[derive(Clone)]
@@ -113,6 +120,7 @@ impl<N: NetworkRuntime> SharedIcmpv4Peer<N> {
         local_ipv4_addr: Ipv4Addr,
         arp: SharedArpPeer<N>,
         rng_seed: [u8; 32],
+        drop_stats: SharedObject<DropStats>,
     ) -> Result<Self, Fail> {
         let rng: SmallRng = SmallRng::from_seed(rng_seed);
         let yielder: Yielder = Yielder::new();
@@ -127,6 +135,7 @@ impl<N: NetworkRuntime> SharedIcmpv4Peer<N> {
             rng,
             yielder_handle: yielder.get_handle(),
             inflight: HashMap::<(u16, u16), AsyncValue<Result<(), Fail>>>::new(),
+            drop_stats,
         }));
         runtime.insert_background_coroutine(
             "Inetstack::ICMP::background",
@@ -147,6 +156,7 @@ impl<N: NetworkRuntime> SharedIcmpv4Peer<N> {
                 Err(e) => {
                     let cause = "Cannot parse ICMP header";
                     warn!("{}: {:?}", cause, e);
+                    self.drop_stats.record(DropReason::from_parse_error(&e));
                     continue;
                 },
             };
@@ -187,6 +197,11 @@ impl<N: NetworkRuntime> SharedIcmpv4Peer<N> {
         }
     }
 
+    /// Changes the local link address used to frame outgoing messages.
+    pub fn set_local_link_addr(&mut self, local_link_addr: MacAddress) {
+        self.local_link_addr = local_link_addr;
+    }
+
     /// Parses and handles a ICMP message.
     pub fn receive(&mut self, ipv4_hdr: Ipv4Header, buf: DemiBuffer) {
         self.recv_queue.push((ipv4_hdr, buf));
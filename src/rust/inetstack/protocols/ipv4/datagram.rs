@@ -10,10 +10,12 @@ use crate::{
     runtime::{
         fail::Fail,
         memory::DemiBuffer,
+        network::consts::MAX_DSCP,
     },
 };
 use ::libc::{
     EBADMSG,
+    EILSEQ,
     ENOTSUP,
 };
 use ::std::{
@@ -123,7 +125,16 @@ impl Ipv4Header {
     }
 
     /// Parses a buffer into an IPv4 header and payload.
-    pub fn parse(mut buf: DemiBuffer) -> Result<(Self, DemiBuffer), Fail> {
+    pub fn parse(buf: DemiBuffer) -> Result<(Self, DemiBuffer), Fail> {
+        Self::parse_with_checksum_offload(buf, false)
+    }
+
+    /// Parses a buffer into an IPv4 header and payload. The header checksum is not verified if `checksum_offload` is
+    /// set.
+    pub fn parse_with_checksum_offload(
+        mut buf: DemiBuffer,
+        checksum_offload: bool,
+    ) -> Result<(Self, DemiBuffer), Fail> {
         // The datagram should be as big as the header.
         if buf.len() < (IPV4_DATAGRAM_MIN_SIZE as usize) {
             return Err(Fail::new(EBADMSG, "ipv4 datagram too small"));
@@ -213,11 +224,13 @@ impl Ipv4Header {
 
         // Header checksum.
         let header_checksum: u16 = u16::from_be_bytes([hdr_buf[10], hdr_buf[11]]);
-        if header_checksum == 0xffff {
-            return Err(Fail::new(EBADMSG, "ipv4 checksum invalid"));
-        }
-        if header_checksum != Self::compute_checksum(hdr_buf) {
-            return Err(Fail::new(EBADMSG, "ipv4 checksum mismatch"));
+        if !checksum_offload {
+            if header_checksum == 0xffff {
+                return Err(Fail::new(EILSEQ, "ipv4 checksum invalid"));
+            }
+            if header_checksum != Self::compute_checksum(hdr_buf) {
+                return Err(Fail::new(EILSEQ, "ipv4 checksum mismatch"));
+            }
         }
 
         // Source address.
@@ -305,6 +318,21 @@ impl Ipv4Header {
         self.protocol
     }
 
+    /// Returns the DSCP field stored in the target IPv4 header.
+    pub fn get_dscp(&self) -> u8 {
+        self.dscp
+    }
+
+    /// Sets the DSCP field of the target IPv4 header. The ECN field is left untouched.
+    pub fn set_dscp(&mut self, dscp: u8) {
+        self.dscp = dscp & MAX_DSCP;
+    }
+
+    /// Returns the ECN field stored in the target IPv4 header.
+    pub fn get_ecn(&self) -> u8 {
+        self.ecn
+    }
+
     /// Computes the checksum of the target IPv4 header.
     pub fn compute_checksum(buf: &[u8]) -> u16 {
         let mut state: u32 = 0xffff;
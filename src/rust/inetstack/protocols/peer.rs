@@ -4,10 +4,16 @@
 use crate::{
     inetstack::protocols::{
         arp::SharedArpPeer,
-        icmpv4::SharedIcmpv4Peer,
+        icmpv4::{
+            FragmentationNeeded,
+            SharedIcmpv4Peer,
+        },
         ip::IpProtocol,
         ipv4::Ipv4Header,
-        tcp::SharedTcpPeer,
+        tcp::{
+            LocalAddrs,
+            SharedTcpPeer,
+        },
         udp::SharedUdpPeer,
     },
     runtime::{
@@ -18,10 +24,15 @@ use crate::{
                 TcpConfig,
                 UdpConfig,
             },
-            types::MacAddress,
+            types::{
+                DropReason,
+                DropStats,
+                MacAddress,
+            },
             NetworkRuntime,
         },
         SharedDemiRuntime,
+        SharedObject,
     },
 };
 use ::std::{
@@ -34,9 +45,14 @@ use crate::inetstack::protocols::tcp::socket::SharedTcpSocket;
 
 pub struct Peer<N: NetworkRuntime> {
     local_ipv4_addr: Ipv4Addr,
+    /// Directed broadcast address of the local subnet, if its netmask is known.
+    directed_broadcast: Option<Ipv4Addr>,
+    /// Verify checksums of incoming packets in software?
+    verify_checksums: bool,
     icmpv4: SharedIcmpv4Peer<N>,
     pub tcp: SharedTcpPeer<N>,
     pub udp: SharedUdpPeer<N>,
+    drop_stats: SharedObject<DropStats>,
 }
 
 impl<N: NetworkRuntime> Peer<N> {
@@ -49,6 +65,7 @@ impl<N: NetworkRuntime> Peer<N> {
         tcp_config: TcpConfig,
         arp: SharedArpPeer<N>,
         rng_seed: [u8; 32],
+        drop_stats: SharedObject<DropStats>,
     ) -> Result<Self, Fail> {
         let udp_offload_checksum: bool = udp_config.get_tx_checksum_offload();
         let udp: SharedUdpPeer<N> = SharedUdpPeer::<N>::new(
@@ -58,6 +75,7 @@ impl<N: NetworkRuntime> Peer<N> {
             local_ipv4_addr,
             udp_offload_checksum,
             arp.clone(),
+            drop_stats.clone(),
         )?;
         let icmpv4: SharedIcmpv4Peer<N> = SharedIcmpv4Peer::<N>::new(
             runtime.clone(),
@@ -66,42 +84,61 @@ impl<N: NetworkRuntime> Peer<N> {
             local_ipv4_addr,
             arp.clone(),
             rng_seed,
+            drop_stats.clone(),
         )?;
         let tcp: SharedTcpPeer<N> = SharedTcpPeer::<N>::new(
             runtime.clone(),
             transport.clone(),
-            local_link_addr,
-            local_ipv4_addr,
+            LocalAddrs {
+                link_addr: local_link_addr,
+                ipv4_addr: local_ipv4_addr,
+            },
             tcp_config,
             arp,
             rng_seed,
+            drop_stats.clone(),
         )?;
 
         Ok(Peer {
             local_ipv4_addr,
+            directed_broadcast: None,
+            verify_checksums: true,
             icmpv4,
             tcp,
             udp,
+            drop_stats,
         })
     }
 
     pub fn receive(&mut self, buf: DemiBuffer) {
-        let (header, payload) = match Ipv4Header::parse(buf) {
+        let (header, payload) = match Ipv4Header::parse_with_checksum_offload(buf, !self.verify_checksums) {
             Ok(result) => result,
             Err(e) => {
                 let cause: String = format!("Invalid destination address: {:?}", e);
                 warn!("dropping packet: {}", cause);
+                self.drop_stats.record(DropReason::from_parse_error(&e));
                 return;
             },
         };
         debug!("Ipv4 received {:?}", header);
-        if header.get_dest_addr() != self.local_ipv4_addr && !header.get_dest_addr().is_broadcast() {
+        let dest_addr: Ipv4Addr = header.get_dest_addr();
+        if dest_addr != self.local_ipv4_addr && !dest_addr.is_broadcast() && Some(dest_addr) != self.directed_broadcast
+        {
             let cause: String = format!("Invalid destination address");
             warn!("dropping packet: {}", cause);
+            self.drop_stats.record(DropReason::NoRoute);
             return;
         }
         match header.get_protocol() {
-            IpProtocol::ICMPv4 => self.icmpv4.receive(header, payload),
+            IpProtocol::ICMPv4 => {
+                if let Some(msg) = FragmentationNeeded::parse(payload.clone()) {
+                    if let Some(seq_num) = msg.seq_num {
+                        self.tcp
+                            .receive_fragmentation_needed(msg.src, msg.dst, msg.next_hop_mtu, seq_num.into());
+                    }
+                }
+                self.icmpv4.receive(header, payload)
+            },
             IpProtocol::TCP => self.tcp.receive(header, payload),
             IpProtocol::UDP => self.udp.receive(header, payload),
         }
@@ -111,6 +148,28 @@ impl<N: NetworkRuntime> Peer<N> {
         self.icmpv4.ping(dest_ipv4_addr, timeout).await
     }
 
+    /// Enables or disables software verification of checksums on the IPv4, TCP, and UDP receive paths.
+    pub fn set_verify_checksums(&mut self, verify_checksums: bool) {
+        self.verify_checksums = verify_checksums;
+        self.tcp.set_verify_checksums(verify_checksums);
+        self.udp.set_verify_checksums(verify_checksums);
+    }
+
+    /// Sets the netmask of the local subnet, so that packets sent to its directed broadcast address are received like
+    /// those sent to the limited broadcast address. UDP sockets that already exist do not send to it as broadcast.
+    pub fn set_netmask(&mut self, netmask: Ipv4Addr) {
+        let directed_broadcast: Ipv4Addr = Ipv4Addr::from(u32::from(self.local_ipv4_addr) | !u32::from(netmask));
+        self.directed_broadcast = Some(directed_broadcast);
+        self.udp.set_directed_broadcast(directed_broadcast);
+    }
+
+    /// Changes the local link address used by ICMPv4, TCP, and UDP. Sockets that already exist are not updated.
+    pub fn set_local_link_addr(&mut self, local_link_addr: MacAddress) {
+        self.icmpv4.set_local_link_addr(local_link_addr);
+        self.tcp.set_local_link_addr(local_link_addr);
+        self.udp.set_local_link_addr(local_link_addr);
+    }
+
     /// This function is only used for testing for now.
     /// TODO: Remove this function once our legacy tests have been disabled.
     pub fn get_local_addr(&self) -> Ipv4Addr {
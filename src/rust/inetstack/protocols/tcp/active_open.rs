@@ -24,7 +24,9 @@ use crate::{
                 },
                 EstablishedSocket,
             },
+            fast_open::SharedFastOpenState,
             segment::{
+                FastOpenCookie,
                 TcpHeader,
                 TcpOptions2,
                 TcpSegment,
@@ -76,6 +78,11 @@ pub struct ActiveOpenSocket<N: NetworkRuntime> {
     tcp_config: TcpConfig,
     arp: SharedArpPeer<N>,
     dead_socket_tx: mpsc::UnboundedSender<QDesc>,
+    /// Data to send as soon as the connection is established, in the SYN if the server gave us a TCP Fast Open cookie.
+    initial: Option<DemiBuffer>,
+    /// Number of bytes of [initial](Self::initial) that the SYN carries.
+    syn_data_len: usize,
+    fast_open: SharedFastOpenState,
 }
 
 #[derive(Clone)]
@@ -98,6 +105,8 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
         local_link_addr: MacAddress,
         arp: SharedArpPeer<N>,
         dead_socket_tx: mpsc::UnboundedSender<QDesc>,
+        initial: Option<DemiBuffer>,
+        fast_open: SharedFastOpenState,
     ) -> Result<Self, Fail> {
         // TODO: Add fast path here when remote is already in the ARP cache (and subtract one retry).
 
@@ -113,14 +122,19 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
             tcp_config,
             arp,
             dead_socket_tx,
+            initial,
+            syn_data_len: 0,
+            fast_open,
         })))
     }
 
     fn process_ack(&mut self, header: TcpHeader) -> Result<EstablishedSocket<N>, Fail> {
         let expected_seq: SeqNumber = self.local_isn + SeqNumber::from(1);
+        let syn_data_seq: SeqNumber = expected_seq + SeqNumber::from(self.syn_data_len as u32);
 
-        // Bail if we didn't receive a ACK packet with the right sequence number.
-        if !(header.ack && header.ack_num == expected_seq) {
+        // Bail if we didn't receive a ACK packet with the right sequence number. The server may or may not have accepted
+        // the data in our SYN.
+        if !(header.ack && (header.ack_num == expected_seq || header.ack_num == syn_data_seq)) {
             let cause: String = format!(
                 "expected ack_num: {}, received ack_num: {}",
                 expected_seq, header.ack_num
@@ -156,7 +170,7 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
         tcp_hdr.ack = true;
         tcp_hdr.ack_num = remote_seq_num;
         tcp_hdr.window_size = self.tcp_config.get_receive_window_size();
-        tcp_hdr.seq_num = self.local_isn + SeqNumber::from(1);
+        tcp_hdr.seq_num = header.ack_num;
         debug!("Sending ACK: {:?}", tcp_hdr);
 
         let segment = TcpSegment {
@@ -168,6 +182,7 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
         };
         self.transport.transmit(Box::new(segment));
 
+        let remote: SocketAddrV4 = self.remote;
         let mut remote_window_scale = None;
         let mut mss = FALLBACK_MSS;
         for option in header.iter_options() {
@@ -180,6 +195,10 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
                     info!("Received advertised MSS: {}", m);
                     mss = *m as usize;
                 },
+                TcpOptions2::FastOpen(cookie) if !cookie.is_request() => {
+                    info!("Received fast open cookie");
+                    self.fast_open.cache_cookie(*remote.ip(), *cookie);
+                },
                 _ => continue,
             }
         }
@@ -209,7 +228,13 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
             "Window scale: local {}, remote {}",
             local_window_scale, remote_window_scale
         );
-        Ok(EstablishedSocket::new(
+        let cc_constructor: congestion_control::CongestionControlConstructor =
+            if self.tcp_config.get_congestion_control() {
+                congestion_control::Cubic::new
+            } else {
+                congestion_control::None::new
+            };
+        let mut socket: EstablishedSocket<N> = EstablishedSocket::new(
             self.local,
             self.remote,
             self.runtime.clone(),
@@ -223,20 +248,44 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
             self.tcp_config.get_ack_delay_timeout(),
             rx_window_size,
             local_window_scale,
-            expected_seq,
+            header.ack_num,
             tx_window_size,
             remote_window_scale,
             mss,
-            congestion_control::None::new,
+            cc_constructor,
             None,
             self.dead_socket_tx.clone(),
-        )?)
+        )?;
+        socket.record_options(&header);
+        socket.set_local_isn(self.local_isn);
+
+        // Send the initial data now, unless the server already accepted it with the SYN.
+        if self.syn_data_len > 0 && header.ack_num == syn_data_seq {
+            socket.set_used_fast_open();
+        } else if let Some(data) = self.initial.take() {
+            socket.send(data)?;
+        }
+        Ok(socket)
     }
 
     pub async fn connect(mut self, yielder: Yielder) -> Result<EstablishedSocket<N>, Fail> {
         // Start connection handshake.
         let handshake_retries: usize = self.tcp_config.get_handshake_retries();
         let handshake_timeout = self.tcp_config.get_handshake_timeout();
+
+        // With initial data, send it along with the SYN if the server gave us a cookie for it, or ask for a cookie
+        // otherwise. Data that does not fit in a segment of minimal size goes after the handshake regardless.
+        let cookie: Option<FastOpenCookie> = self.initial.as_ref().map(|_| {
+            self.fast_open
+                .cached_cookie(self.remote.ip())
+                .unwrap_or_else(FastOpenCookie::request)
+        });
+        let syn_data: Option<DemiBuffer> = match (&self.initial, cookie) {
+            (Some(data), Some(cookie)) if !cookie.is_request() && data.len() <= FALLBACK_MSS => Some(data.clone()),
+            _ => None,
+        };
+        self.syn_data_len = syn_data.as_ref().map_or(0, |data| data.len());
+
         for _ in 0..handshake_retries {
             // Look up remote MAC address.
             // TODO: Do we need to do this every iteration?
@@ -261,12 +310,16 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
             tcp_hdr.push_option(TcpOptions2::WindowScale(self.tcp_config.get_window_scale()));
             info!("Advertising window scale: {}", self.tcp_config.get_window_scale());
 
+            if let Some(cookie) = cookie {
+                tcp_hdr.push_option(TcpOptions2::FastOpen(cookie));
+            }
+
             debug!("Sending SYN {:?}", tcp_hdr);
             let segment = TcpSegment {
                 ethernet2_hdr: Ethernet2Header::new(remote_link_addr, self.local_link_addr, EtherType2::Ipv4),
                 ipv4_hdr: Ipv4Header::new(self.local.ip().clone(), self.remote.ip().clone(), IpProtocol::TCP),
                 tcp_hdr,
-                data: None,
+                data: syn_data.clone(),
                 tx_checksum_offload: self.tcp_config.get_rx_checksum_offload(),
             };
             // Send SYN.
@@ -303,6 +356,11 @@ impl<N: NetworkRuntime> SharedActiveOpenSocket<N> {
         Err(Fail::new(libc::ETIMEDOUT, &cause))
     }
 
+    /// Returns the initial sequence number that we chose for this connection.
+    pub fn local_isn(&self) -> SeqNumber {
+        self.local_isn
+    }
+
     /// Returns the addresses of the two ends of this connection.
     pub fn endpoints(&self) -> (SocketAddrV4, SocketAddrV4) {
         (self.local, self.remote)
@@ -0,0 +1,49 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use super::SharedControlBlock;
+use crate::runtime::{
+    fail::Fail,
+    network::NetworkRuntime,
+    scheduler::Yielder,
+    timer::SharedTimer,
+    watched::SharedWatchedValue,
+};
+use ::futures::{
+    future::{
+        self,
+        Either,
+        FutureExt,
+    },
+    never::Never,
+};
+use ::std::time::Instant;
+
+pub async fn keepalive<N: NetworkRuntime>(mut cb: SharedControlBlock<N>, yielder: Yielder) -> Result<Never, Fail> {
+    loop {
+        let mut keepalive_deadline: SharedWatchedValue<Option<Instant>> = cb.get_keepalive_deadline();
+        let deadline: Option<Instant> = keepalive_deadline.get();
+        let keepalive_yielder: Yielder = Yielder::new();
+        let keepalive_deadline_changed = keepalive_deadline.watch(keepalive_yielder).fuse();
+        futures::pin_mut!(keepalive_deadline_changed);
+
+        let clock_ref: SharedTimer = cb.get_timer();
+        let keepalive_future = match deadline {
+            Some(t) => Either::Left(clock_ref.wait_until(t, &yielder).fuse()),
+            None => Either::Right(future::pending()),
+        };
+        futures::pin_mut!(keepalive_future);
+
+        futures::select_biased! {
+            _ = keepalive_deadline_changed => continue,
+            _ = keepalive_future => {
+                match cb.get_keepalive_deadline().get() {
+                    Some(timeout) if timeout > cb.get_now() => continue,
+                    None => continue,
+                    _ => {},
+                }
+                cb.send_keepalive_probe()?;
+            },
+        }
+    }
+}
@@ -2,11 +2,13 @@
 // Licensed under the MIT license.
 
 mod acknowledger;
+mod keepalive;
 mod retransmitter;
 mod sender;
 
 use self::{
     acknowledger::acknowledger,
+    keepalive::keepalive,
     retransmitter::retransmitter,
     sender::sender,
 };
@@ -37,6 +39,10 @@ pub async fn background<N: NetworkRuntime>(cb: SharedControlBlock<N>, _dead_sock
     let sender = sender(cb.clone(), yielder_sender).fuse();
     futures::pin_mut!(sender);
 
+    let yielder_keepalive: Yielder = Yielder::new();
+    let keepalive = keepalive(cb.clone(), yielder_keepalive).fuse();
+    futures::pin_mut!(keepalive);
+
     let yielder_receiver: Yielder = Yielder::new();
     let mut cb2: SharedControlBlock<N> = cb.clone();
     let receiver = cb2.poll(yielder_receiver).fuse();
@@ -47,6 +53,7 @@ pub async fn background<N: NetworkRuntime>(cb: SharedControlBlock<N>, _dead_sock
         r = acknowledger => r,
         r = retransmitter => r,
         r = sender => r,
+        r = keepalive => r,
     };
     error!("Connection terminated: {:?}", r);
 }
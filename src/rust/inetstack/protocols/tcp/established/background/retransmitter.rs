@@ -30,10 +30,16 @@ pub async fn retransmitter<N: NetworkRuntime>(mut cb: SharedControlBlock<N>, yie
         let mut rtx_deadline_watched: SharedWatchedValue<Option<Instant>> = cb.watch_retransmit_deadline();
         let rtx_yielder: Yielder = Yielder::new();
         let rtx_deadline: Option<Instant> = rtx_deadline_watched.get();
+        // Wake up early if the user timeout expires before the retransmission timer does.
+        let user_deadline: Option<Instant> = cb.get_user_timeout_deadline();
+        let wake_deadline: Option<Instant> = match (rtx_deadline, user_deadline) {
+            (Some(r), Some(u)) => Some(r.min(u)),
+            (r, u) => r.or(u),
+        };
         let rtx_deadline_changed = rtx_deadline_watched.watch(rtx_yielder).fuse();
         futures::pin_mut!(rtx_deadline_changed);
         let clock_ref: SharedTimer = cb.get_timer();
-        let rtx_future = match rtx_deadline {
+        let rtx_future = match wake_deadline {
             Some(t) => Either::Left(clock_ref.wait_until(t, &yielder).fuse()),
             None => Either::Right(future::pending()),
         };
@@ -60,6 +66,12 @@ pub async fn retransmitter<N: NetworkRuntime>(mut cb: SharedControlBlock<N>, yie
             _ = rtx_deadline_changed => continue,
             _ = rtx_fast_retransmit_changed => continue,
             _ = rtx_future => {
+                // Give up on the connection once sent data went unacknowledged for longer than the user timeout.
+                match cb.get_user_timeout_deadline() {
+                    Some(deadline) if deadline <= cb.get_now() => return Err(cb.user_time_out()),
+                    _ => {},
+                }
+
                 match cb.get_retransmit_deadline() {
                     Some(timeout) if timeout > cb.get_now() => continue,
                     None => continue,
@@ -74,6 +86,8 @@ pub async fn retransmitter<N: NetworkRuntime>(mut cb: SharedControlBlock<N>, yie
 
                 // RFC 6298 Section 5.4: Retransmit earliest unacknowledged segment.
                 cb.retransmit();
+                let now: Instant = cb.get_now();
+                cb.rto_record_retransmission(now);
 
                 // RFC 6298 Section 5.5: Back off the retransmission timer.
                 cb.clone().rto_back_off();
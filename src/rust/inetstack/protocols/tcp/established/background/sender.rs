@@ -53,6 +53,22 @@ pub async fn sender<N: NetworkRuntime>(mut cb: SharedControlBlock<N>, yielder: Y
             }
         }
 
+        // A corked connection holds data back until it fills a segment, or until the cork is lifted.
+        let mut corked_watched: SharedWatchedValue<bool> = cb.get_corked();
+        let corked: bool = corked_watched.get();
+        let corked_yielder: Yielder = Yielder::new();
+        let corked_changed = corked_watched.watch(corked_yielder).fuse();
+        futures::pin_mut!(corked_changed);
+
+        let unsent_bytes: u32 = (unsent_seq - send_next).into();
+        if corked && unsent_bytes < cb.get_mss() as u32 {
+            futures::select_biased! {
+                _ = corked_changed => continue 'top,
+                _ = unsent_seq_changed => continue 'top,
+                _ = send_next_changed => continue 'top,
+            }
+        }
+
         // Okay, we know we have some unsent data past this point. Next, check to see that the
         // remote side has available window.
         let mut win_sz_watched: SharedWatchedValue<u32> = cb.get_send_window();
@@ -145,7 +161,11 @@ pub async fn sender<N: NetworkRuntime>(mut cb: SharedControlBlock<N>, yielder: Y
             }
         }
 
-        // Past this point we have data to send and it's valid to send it!
+        // Past this point we have data to send and it's valid to send it, once the pacer lets it go.
+        if let Some(delay) = cb.pacing_delay() {
+            cb.get_timer().wait(delay, &yielder).await?;
+            continue 'top;
+        }
 
         // TODO: Nagle's algorithm - We need to coalese small buffers together to send MSS sized packets.
         // TODO: Silly window syndrome - See RFC 1122's discussion of the SWS avoidance algorithm.
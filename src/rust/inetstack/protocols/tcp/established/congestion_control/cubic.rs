@@ -48,6 +48,7 @@ pub struct Cubic {
     pub rtt_at_last_send: Cell<Duration>,           // The RTT at the moment we last sent data.
     pub ssthresh: Cell<u32>, // The size of cwnd at which we will change from using slow start to congestion avoidance.
     pub w_max: Cell<u32>,    // The size of cwnd before the previous congestion event.
+    pub pre_rto_state: Cell<Option<(u32, u32, u32)>>, // cwnd, ssthresh and w_max before the RTOs since the last new ACK.
 
     // Fast Recovery / Fast Retransmit State
     pub duplicate_ack_count: Cell<u32>, // The number of consecutive duplicate ACKs we've received.
@@ -84,6 +85,7 @@ impl CongestionControl for Cubic {
             rtt_at_last_send: Cell::new(Duration::new(1, 0)), // The default RTT is 1 sec.
             ssthresh: Cell::new(u32::MAX), // According to RFC5681 ssthresh should be initialised 'arbitrarily high'.
             w_max: Cell::new(0), // Because ssthresh is u32::MAX, this will be set appropriately during the 1st congestion event.
+            pre_rto_state: Cell::new(None),
             last_congestion_was_rto: Cell::new(false),
 
             in_fast_recovery: Cell::new(false),
@@ -241,6 +243,12 @@ impl Cubic {
     fn on_rto_ss_ca(&mut self) {
         let cwnd: u32 = self.cwnd.get();
 
+        // Remember the state from before the first of consecutive RTOs, in case they turn out to be spurious.
+        if self.pre_rto_state.get().is_none() {
+            self.pre_rto_state
+                .set(Some((cwnd, self.ssthresh.get(), self.w_max.get())));
+        }
+
         if self.fast_convergence {
             self.fast_convergence();
         } else {
@@ -276,6 +284,10 @@ impl SlowStartCongestionAvoidance for Cubic {
         self.cwnd.clone()
     }
 
+    fn get_ssthresh(&self) -> u32 {
+        self.ssthresh.get()
+    }
+
     fn on_cwnd_check_before_send(&mut self) {
         let long_time_since_send: bool =
             Instant::now().duration_since(self.last_send_time.get()) > self.rtt_at_last_send.get();
@@ -304,6 +316,8 @@ impl SlowStartCongestionAvoidance for Cubic {
                 .set(self.retransmitted_packets_in_flight.get().saturating_sub(1));
         } else {
             self.duplicate_ack_count.set(0);
+            // New data got through, so any RTO before this is settled.
+            self.pre_rto_state.set(None);
 
             if self.in_fast_recovery.get() {
                 // Fast Recovery response to new data.
@@ -321,6 +335,16 @@ impl SlowStartCongestionAvoidance for Cubic {
         self.on_rto_ss_ca();
         self.on_rto_fast_recovery(send_unacked);
     }
+
+    fn on_spurious_rto(&mut self) {
+        if let Some((cwnd, ssthresh, w_max)) = self.pre_rto_state.take() {
+            self.cwnd.set(cwnd);
+            self.ssthresh.set(ssthresh);
+            self.w_max.set(w_max);
+            self.retransmitted_packets_in_flight.set(0);
+            self.last_congestion_was_rto.set(false);
+        }
+    }
 }
 
 impl FastRetransmitRecovery for Cubic {
@@ -26,6 +26,11 @@ pub use self::{
 pub trait SlowStartCongestionAvoidance {
     fn get_cwnd(&self) -> SharedWatchedValue<u32>;
 
+    // Size of cwnd at which slow start gives way to congestion avoidance. Unbounded unless overridden.
+    fn get_ssthresh(&self) -> u32 {
+        u32::MAX
+    }
+
     // Called immediately before the cwnd check is performed before data is sent.
     fn on_cwnd_check_before_send(&mut self) {}
 
@@ -41,6 +46,10 @@ pub trait SlowStartCongestionAvoidance {
     // Called immediately before retransmit after RTO.
     fn on_rto(&mut self, _send_unacked: SeqNumber) {}
 
+    // Called when an ACK shows that the original transmission of the segment retransmitted after the last RTO was not
+    // lost, immediately before the ACK itself is handled. Undoes the response to the RTO.
+    fn on_spurious_rto(&mut self) {}
+
     // Called immediately before a segment is sent for the 1st time.
     fn on_send(&mut self, _rto: Duration, _num_sent_bytes: u32) {}
 }
@@ -6,6 +6,7 @@ use super::{
         self,
         CongestionControlConstructor,
     },
+    rate::RateEstimator,
     rto::RtoCalculator,
     sender::{
         Sender,
@@ -24,11 +25,15 @@ use crate::{
             Ethernet2Header,
         },
         ip::IpProtocol,
-        ipv4::Ipv4Header,
+        ipv4::{
+            Ipv4Header,
+            IPV4_HEADER_MIN_SIZE,
+        },
         tcp::{
             segment::{
                 TcpHeader,
                 TcpSegment,
+                MIN_TCP_HEADER_SIZE,
             },
             SeqNumber,
         },
@@ -38,7 +43,20 @@ use crate::{
         memory::DemiBuffer,
         network::{
             config::TcpConfig,
-            types::MacAddress,
+            consts::{
+                DEFAULT_KEEPALIVE_IDLE,
+                DEFAULT_KEEPALIVE_INTERVAL,
+                DEFAULT_KEEPALIVE_PROBES,
+                MIN_MSS,
+            },
+            socket::option::HalfClosedReadPolicy,
+            types::{
+                ConnectionSnapshot,
+                MacAddress,
+                TcpOption,
+                TcpSeqState,
+                TcpState,
+            },
             NetworkRuntime,
         },
         scheduler::Yielder,
@@ -50,8 +68,10 @@ use crate::{
 };
 use ::futures::never::Never;
 use ::std::{
+    cmp,
     collections::VecDeque,
     convert::TryInto,
+    mem,
     net::SocketAddrV4,
     ops::{
         Deref,
@@ -74,6 +94,14 @@ const RECV_QUEUE_SZ: usize = 2048;
 // Ideally, we'd limit out-of-order data to that which (along with the unread data) will fit in the receive window.
 const MAX_OUT_OF_ORDER: usize = 16;
 
+// When pacing, segments go out this much faster than the measured delivery rate, so that the connection keeps probing
+// for more bandwidth instead of locking in at the rate it measured first.
+const PACING_GAIN: f64 = 1.25;
+
+// Reports of next-hop MTUs below this one are ignored, as a segment of the minimum MSS would not fit in them.  This
+// keeps forged ICMP messages from shrinking our segments to a few bytes each.
+const MIN_PATH_MTU: u16 = MIN_MSS as u16 + IPV4_HEADER_MIN_SIZE + MIN_TCP_HEADER_SIZE as u16;
+
 // TCP Connection State.
 // Note: This ControlBlock structure is only used after we've reached the ESTABLISHED state, so states LISTEN,
 // SYN_RCVD, and SYN_SENT aren't included here.
@@ -113,14 +141,18 @@ struct Receiver {
 
     // Receive queue.  Contains in-order received (and acknowledged) data ready for the application to read.
     recv_queue: AsyncQueue<DemiBuffer>,
+
+    // Merge new data into the buffer at the tail of the receive queue, rather than queuing one buffer per segment?
+    coalesce: bool,
 }
 
 impl Receiver {
-    pub fn new(reader_next: SeqNumber, receive_next: SeqNumber) -> Self {
+    pub fn new(reader_next: SeqNumber, receive_next: SeqNumber, coalesce: bool) -> Self {
         Self {
             reader_next,
             receive_next,
             recv_queue: AsyncQueue::with_capacity(RECV_QUEUE_SZ),
+            coalesce,
         }
     }
 
@@ -142,11 +174,86 @@ impl Receiver {
         Ok(buf)
     }
 
+    /// Pops all buffered data in a single buffer, waiting if there is none. A zero-length buffer marking the end of the
+    /// stream is never merged with data, so that it is returned on its own by a later pop.
+    pub async fn pop_all(&mut self, yielder: Yielder) -> Result<DemiBuffer, Fail> {
+        let first: DemiBuffer = self.recv_queue.pop(&yielder).await?;
+        let buf: DemiBuffer = if first.len() == 0 || self.recv_queue.is_empty() {
+            first
+        } else {
+            let mut data: Vec<u8> = first[..].to_vec();
+            while let Some(next) = self.recv_queue.try_pop() {
+                if next.len() == 0 || data.len() + next.len() > u16::MAX as usize {
+                    self.recv_queue.push_front(next);
+                    break;
+                }
+                data.extend_from_slice(&next[..]);
+            }
+            DemiBuffer::from_slice(&data)?
+        };
+
+        self.reader_next = self.reader_next + SeqNumber::from(buf.len() as u32);
+
+        Ok(buf)
+    }
+
+    /// Fails all pops that are waiting for data with `e`.
+    pub fn fail_pops(&mut self, e: Fail) {
+        self.recv_queue.fail_waiters(e);
+    }
+
     pub fn push(&mut self, buf: DemiBuffer) {
         let buf_len: u32 = buf.len() as u32;
+        if self.coalesce && buf_len > 0 {
+            // Zero-length buffers mark the end of the stream, so they are never merged with data.
+            if let Some(last) = self.recv_queue.back_mut() {
+                if last.len() > 0
+                    && last.len() + buf.len() <= u16::MAX as usize
+                    && Self::coalesce_into(last, &buf).is_ok()
+                {
+                    self.receive_next = self.receive_next + SeqNumber::from(buf_len);
+                    return;
+                }
+            }
+        }
         self.recv_queue.push(buf);
         self.receive_next = self.receive_next + SeqNumber::from(buf_len as u32);
     }
+
+    /// Appends `buf` to `last`. When `last` runs out of room, it moves to a buffer of twice the size it needs, so that
+    /// merging a run of segments copies each byte a bounded number of times.
+    fn coalesce_into(last: &mut DemiBuffer, buf: &DemiBuffer) -> Result<(), Fail> {
+        let len: usize = last.len() + buf.len();
+        if last.capacity() < len {
+            // The caller ensures that `len` fits into a u16, so this cast is safe.
+            let capacity: u16 = cmp::min(2 * len, u16::MAX as usize) as u16;
+            let mut grown: DemiBuffer = DemiBuffer::new(capacity);
+            grown.trim(capacity as usize)?;
+            grown.append(&last[..])?;
+            *last = grown;
+        }
+        last.append(&buf[..])
+    }
+
+    /// Returns whether in-order data, or the end of the stream, is waiting to be popped.
+    pub fn has_data_to_pop(&self) -> bool {
+        !self.recv_queue.is_empty()
+    }
+
+    /// Returns the in-order data that the application has not popped yet.
+    pub fn get_buffered_data(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        for buf in self.recv_queue.get_values() {
+            data.extend_from_slice(&buf[..]);
+        }
+        data
+    }
+
+    /// Consumes `len` bytes of in-order data without queuing them for the application.
+    pub fn discard(&mut self, len: u32) {
+        self.receive_next = self.receive_next + SeqNumber::from(len);
+        self.reader_next = self.reader_next + SeqNumber::from(len);
+    }
 }
 
 /// Transmission control block for representing our TCP connection.
@@ -175,10 +282,45 @@ pub struct ControlBlock<N: NetworkRuntime> {
 
     ack_deadline: SharedWatchedValue<Option<Instant>>,
 
+    // Acknowledge every incoming segment immediately, rather than delaying the ACK?
+    quick_ack: bool,
+
+    // Probe the peer when the connection goes idle?
+    keepalive_enabled: bool,
+
+    // When the next keepalive probe is due, if keepalive is enabled.
+    keepalive_deadline: SharedWatchedValue<Option<Instant>>,
+
+    // Number of keepalive probes sent since we last heard from the peer.
+    keepalive_probes_sent: usize,
+
     // This is our receive buffer size, which is also the maximum size of our receive window.
     // Note: The maximum possible advertised window is 1 GiB with window scaling and 64 KiB without.
     receive_buffer_size: u32,
 
+    // Upper bound on the receive window that we advertise, independent of the space left in the receive buffer.
+    receive_window_clamp: Option<u32>,
+
+    // Differentiated Services Code Point that we mark outgoing segments with.
+    dscp: u8,
+
+    // MTU of the path to the peer, if a router reported one below the MTU of our interface.
+    path_mtu: Option<u16>,
+
+    // Whether the application shut down the read side of the connection, and what to do with data that still arrives.
+    read_shutdown: bool,
+    half_closed_read_policy: HalfClosedReadPolicy,
+
+    // Whether the application shut down the write side of the connection. Segments keep being processed in the
+    // FIN-WAIT states until the application closes the connection.
+    write_shutdown: bool,
+
+    // Whether the application paused the data flow. A paused connection advertises a zero window and refuses pushes.
+    paused: bool,
+
+    // Whether either end reset the connection. Pushes on a reset connection fail with ECONNRESET.
+    reset: bool,
+
     // TODO: Review how this is used.  We could have separate window scale factors, so there should be one for the
     // receiver and one for the sender.
     // This is the receive-side window scale factor.
@@ -192,6 +334,9 @@ pub struct ControlBlock<N: NetworkRuntime> {
     //
     out_of_order: VecDeque<(SeqNumber, DemiBuffer)>,
 
+    // Upper bound on the number of bytes held in the out-of-order queue, if any.  Segments past it are dropped.
+    max_reassembly_bytes: Option<usize>,
+
     // The sequence number of the FIN, if we received it out-of-order.
     // Note: This could just be a boolean to remember if we got a FIN; the sequence number is for checking correctness.
     pub out_of_order_fin: Option<SeqNumber>,
@@ -210,10 +355,55 @@ pub struct ControlBlock<N: NetworkRuntime> {
     // Retransmission Timeout (RTO) calculator.
     rto_calculator: RtoCalculator,
 
+    // When the earliest unacknowledged segment was last retransmitted after an RTO, until new data is acknowledged.
+    rto_retransmitted_at: Option<Instant>,
+
+    // How long sent data may go unacknowledged before we give up on the connection, if there is a limit.
+    user_timeout: Option<Duration>,
+
+    // Why we gave up on the connection because our peer stopped answering, if we did.
+    timeout_error: Option<Fail>,
+
     // Incoming packets for this connection.
     recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)>,
 
     ack_queue: SharedAsyncQueue<usize>,
+
+    // TCP options received from the peer, if the configuration asks for them to be recorded. Holds the latest value of
+    // each kind of option.
+    observed_options: Vec<TcpOption>,
+
+    // Was data exchanged in the SYN of this connection, using TCP Fast Open?
+    used_fast_open: bool,
+
+    // Initial sequence number that we chose for this connection, once recorded.
+    local_isn: Option<SeqNumber>,
+
+    // Rate at which this connection transmits payload bytes, including retransmissions.
+    send_rate: RateEstimator,
+
+    // Rate at which this connection receives in-order payload bytes.
+    recv_rate: RateEstimator,
+
+    // Rate at which our peer acknowledges the bytes that we send.
+    delivery_rate: RateEstimator,
+
+    // When our peer first acknowledged data, if it did.
+    first_delivery_at: Option<Instant>,
+
+    // Earliest time at which the next data segment may be sent, if the configuration asks for pacing.
+    next_send_at: Option<Instant>,
+
+    // Whether the application corked the connection, to coalesce small pushes into full segments.
+    cork: bool,
+
+    // Whether a completed pop lifts the cork, whether it is currently lifted, and whether data was pushed since.
+    auto_flush_after_pop: bool,
+    cork_lifted: bool,
+    pushed_since_lift: bool,
+
+    // Whether segments that are not full are held back, i.e. the connection is corked and the cork is not lifted.
+    corked: SharedWatchedValue<bool>,
 }
 
 #[derive(Clone)]
@@ -243,6 +433,8 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         ack_queue: SharedAsyncQueue<usize>,
     ) -> Self {
         let sender: Sender = Sender::new(sender_seq_no, sender_window_size, sender_window_scale, sender_mss);
+        let receiver: Receiver = Receiver::new(receiver_seq_no, receiver_seq_no, tcp_config.get_receive_coalescing());
+        let rate_window: Duration = tcp_config.get_rate_window();
         Self(SharedObject::<ControlBlock<N>>::new(ControlBlock {
             local,
             remote,
@@ -255,16 +447,45 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
             state: State::Established,
             ack_delay_timeout,
             ack_deadline: SharedWatchedValue::new(None),
+            quick_ack: false,
+            keepalive_enabled: false,
+            keepalive_deadline: SharedWatchedValue::new(None),
+            keepalive_probes_sent: 0,
             receive_buffer_size: receiver_window_size,
+            receive_window_clamp: None,
+            dscp: 0,
+            paused: false,
+            reset: false,
+            path_mtu: None,
+            read_shutdown: false,
+            half_closed_read_policy: HalfClosedReadPolicy::default(),
+            write_shutdown: false,
             window_scale: receiver_window_scale,
             out_of_order: VecDeque::new(),
+            max_reassembly_bytes: None,
             out_of_order_fin: Option::None,
-            receiver: Receiver::new(receiver_seq_no, receiver_seq_no),
+            receiver,
             cc: cc_constructor(sender_mss, sender_seq_no, congestion_control_options),
             retransmit_deadline: SharedWatchedValue::new(None),
             rto_calculator: RtoCalculator::new(),
+            rto_retransmitted_at: None,
+            user_timeout: None,
+            timeout_error: None,
             recv_queue,
             ack_queue,
+            observed_options: Vec::new(),
+            used_fast_open: false,
+            local_isn: None,
+            send_rate: RateEstimator::new(rate_window),
+            recv_rate: RateEstimator::new(rate_window),
+            delivery_rate: RateEstimator::new(rate_window),
+            first_delivery_at: None,
+            next_send_at: None,
+            cork: false,
+            auto_flush_after_pop: false,
+            cork_lifted: false,
+            pushed_since_lift: false,
+            corked: SharedWatchedValue::new(false),
         }))
     }
 
@@ -276,14 +497,49 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.remote
     }
 
+    /// Moves this connection to new endpoints. Segments sent from now on carry the new addresses.
+    pub fn set_endpoints(&mut self, local: SocketAddrV4, remote: SocketAddrV4) {
+        self.local = local;
+        self.remote = remote;
+    }
+
     // TODO: Remove this.  ARP doesn't belong at this layer.
     pub fn arp(&self) -> SharedArpPeer<N> {
         self.arp.clone()
     }
 
     pub fn send(&mut self, buf: DemiBuffer) -> Result<(), Fail> {
+        if let Some(e) = self.timeout_error.clone() {
+            return Err(e);
+        }
+        if self.reset {
+            let cause: String = format!(
+                "connection was reset (local={:?}, remote={:?})",
+                self.local, self.remote
+            );
+            warn!("send(): {}", cause);
+            return Err(Fail::new(libc::ECONNRESET, &cause));
+        }
+        // Segments from the peer are no longer processed once it closed the connection, so nothing that we send would
+        // ever be acknowledged.
+        if self.state == State::CloseWait {
+            let cause: String = format!(
+                "connection was closed by the peer (local={:?}, remote={:?})",
+                self.local, self.remote
+            );
+            warn!("send(): {}", cause);
+            return Err(Fail::new(libc::EPIPE, &cause));
+        }
+        if self.paused {
+            let cause: &str = "connection is paused";
+            warn!("send(): {}", cause);
+            return Err(Fail::new(libc::EWOULDBLOCK, cause));
+        }
+        self.recork_after_flush();
         let self_: Self = self.clone();
-        self.sender.send(buf, self_)
+        self.sender.send(buf, self_)?;
+        self.pushed_since_lift = self.cork_lifted;
+        Ok(())
     }
 
     pub fn retransmit(&self) {
@@ -314,6 +570,10 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.cc.get_cwnd()
     }
 
+    pub fn congestion_control_get_ssthresh(&self) -> u32 {
+        self.cc.get_ssthresh()
+    }
+
     pub fn congestion_control_get_limited_transmit_cwnd_increase(&self) -> SharedWatchedValue<u32> {
         self.cc.get_limited_transmit_cwnd_increase()
     }
@@ -322,6 +582,43 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.sender.get_mss()
     }
 
+    pub fn get_retransmit_count(&self) -> u64 {
+        self.sender.get_retransmit_count()
+    }
+
+    /// Returns the rate, in bits per second, at which this connection transmitted payload bytes over the rate window.
+    pub fn send_rate_bps(&self) -> u64 {
+        self.send_rate.rate_bps(self.get_now())
+    }
+
+    /// Returns the rate, in bits per second, at which this connection received in-order payload bytes over the rate
+    /// window.
+    pub fn recv_rate_bps(&self) -> u64 {
+        self.recv_rate.rate_bps(self.get_now())
+    }
+
+    /// Returns the rate, in bits per second, at which our peer acknowledged the bytes that we sent over the rate window.
+    pub fn delivery_rate_bps(&self) -> u64 {
+        self.delivery_rate.rate_bps(self.get_now())
+    }
+
+    /// Returns how long to hold the next data segment back for, if this connection paces its segments and the last one
+    /// went out too recently.
+    pub fn pacing_delay(&self) -> Option<Duration> {
+        let next_send_at: Instant = self.next_send_at?;
+        let now: Instant = self.get_now();
+        if now < next_send_at {
+            Some(next_send_at - now)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether a pop on this connection would complete without waiting for more segments.
+    pub fn has_data_to_pop(&self) -> bool {
+        self.receiver.has_data_to_pop()
+    }
+
     pub fn get_send_window(&self) -> SharedWatchedValue<u32> {
         self.sender.get_send_window()
     }
@@ -330,6 +627,44 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.sender.get_send_unacked()
     }
 
+    /// Returns a snapshot of the send and receive sequence numbers and windows of this connection.
+    pub fn get_sequence_state(&self) -> TcpSeqState {
+        TcpSeqState {
+            snd_una: self.get_send_unacked().get().into(),
+            snd_nxt: self.get_send_next().get().into(),
+            rcv_nxt: self.receiver.receive_next.into(),
+            snd_wnd: self.get_send_window().get(),
+            rcv_wnd: self.get_receive_window_size(),
+        }
+    }
+
+    /// Returns the endpoints, sequence space, and buffered data of this connection. The state only holds still while
+    /// the connection is paused.
+    pub fn get_snapshot(&self) -> ConnectionSnapshot {
+        ConnectionSnapshot {
+            local: self.local,
+            remote: self.remote,
+            seq_state: self.get_sequence_state(),
+            send_window_scale: self.sender.get_window_scale(),
+            receive_window_scale: self.window_scale as u8,
+            mss: self.sender.get_mss() as u16,
+            send_buffered: self.sender.get_buffered_data(),
+            recv_buffered: self.receiver.get_buffered_data(),
+        }
+    }
+
+    /// Takes over the data that a connection buffered when `snapshot` was taken of it. Data from the peer is queued for
+    /// the application to pop, and data for the peer is sent again from SND.UNA, as the peer may not have received it.
+    pub fn restore_buffered_data(&mut self, snapshot: &ConnectionSnapshot) -> Result<(), Fail> {
+        for chunk in snapshot.recv_buffered.chunks(u16::MAX as usize) {
+            self.receiver.push(DemiBuffer::from_slice(chunk)?);
+        }
+        for chunk in snapshot.send_buffered.chunks(self.sender.get_mss()) {
+            self.send(DemiBuffer::from_slice(chunk)?)?;
+        }
+        Ok(())
+    }
+
     pub fn get_unsent_seq_no(&self) -> SharedWatchedValue<SeqNumber> {
         self.sender.get_unsent_seq_no()
     }
@@ -370,6 +705,105 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.rto_calculator.back_off()
     }
 
+    pub fn set_max_rto(&mut self, max_rto: Option<Duration>) {
+        self.rto_calculator.set_max_rto(max_rto)
+    }
+
+    pub fn set_max_reassembly_bytes(&mut self, max_reassembly_bytes: Option<usize>) {
+        self.max_reassembly_bytes = max_reassembly_bytes;
+    }
+
+    /// Corks or uncorks the connection. Uncorking sends the data that was held back right away.
+    pub fn set_cork(&mut self, cork: bool) {
+        self.cork = cork;
+        self.update_corked();
+    }
+
+    pub fn set_auto_flush_after_pop(&mut self, auto_flush_after_pop: bool) {
+        self.auto_flush_after_pop = auto_flush_after_pop;
+        self.cork_lifted = false;
+        self.pushed_since_lift = false;
+        self.update_corked();
+    }
+
+    /// Returns whether segments that are not full are held back. The sender watches this to send them once they are no
+    /// longer.
+    pub fn get_corked(&self) -> SharedWatchedValue<bool> {
+        self.corked.clone()
+    }
+
+    pub fn is_corked(&self) -> bool {
+        self.corked.get()
+    }
+
+    /// Lifts the cork once a pop completes, if the application asked for it, which sends the data that was held back.
+    fn lift_cork(&mut self) {
+        if self.auto_flush_after_pop {
+            self.cork_lifted = true;
+            self.pushed_since_lift = false;
+            self.update_corked();
+        }
+    }
+
+    /// Puts the cork back before a push, once the data pushed since the cork was lifted has all been sent. The push
+    /// that follows a pop thus goes out right away, whenever the next pop starts, and later pushes are held back again.
+    fn recork_after_flush(&mut self) {
+        if self.cork_lifted
+            && self.pushed_since_lift
+            && self.sender.get_unsent_seq_no().get() == self.sender.get_send_next().get()
+        {
+            self.cork_lifted = false;
+            self.pushed_since_lift = false;
+            self.update_corked();
+        }
+    }
+
+    fn update_corked(&mut self) {
+        let corked: bool = self.cork && !self.cork_lifted;
+        if self.corked.get() != corked {
+            self.corked.set(corked);
+        }
+    }
+
+    pub fn set_user_timeout(&mut self, user_timeout: Option<Duration>) {
+        self.user_timeout = user_timeout;
+        // Wake up the retransmitter, so that it waits for the new deadline.
+        let retransmit_deadline: Option<Instant> = self.retransmit_deadline.get();
+        self.retransmit_deadline.set(retransmit_deadline);
+    }
+
+    /// When we give up on the connection if our peer keeps acknowledging nothing, if there is a user timeout and
+    /// unacknowledged data.
+    pub fn get_user_timeout_deadline(&self) -> Option<Instant> {
+        Some(self.sender.get_unacked_since()? + self.user_timeout?)
+    }
+
+    /// Gives up on the connection because sent data went unacknowledged for longer than the user timeout. Pending and
+    /// later pops and pushes fail with ETIMEDOUT.
+    pub fn user_time_out(&mut self) -> Fail {
+        let cause: String = format!(
+            "sent data went unacknowledged for longer than the user timeout (local={:?}, remote={:?})",
+            self.local, self.remote
+        );
+        error!("user_time_out(): {}", cause);
+        self.time_out(Fail::new(libc::ETIMEDOUT, &cause))
+    }
+
+    /// Gives up on the connection because our peer stopped answering. Pending and later pops and pushes fail with `e`.
+    fn time_out(&mut self, e: Fail) -> Fail {
+        self.timeout_error = Some(e.clone());
+        self.retransmit_deadline.set(None);
+        self.keepalive_deadline.set(None);
+        self.receiver.fail_pops(e.clone());
+        e
+    }
+
+    /// Records that the earliest unacknowledged segment was just retransmitted after an RTO, so that an ACK arriving
+    /// too soon afterwards can tell that the RTO was spurious.
+    pub fn rto_record_retransmission(&mut self, when: Instant) {
+        self.rto_retransmitted_at = Some(when);
+    }
+
     pub fn unsent_top_size(&self) -> Option<usize> {
         self.sender.top_size_unsent()
     }
@@ -399,7 +833,7 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         // Normal data processing in the Established state.
         loop {
             let (header, data): (TcpHeader, DemiBuffer) = match self.recv_queue.pop(&yielder).await {
-                Ok((_, header, data)) if self.state == State::Established => (header, data),
+                Ok((_, header, data)) if self.is_receiving() => (header, data),
                 Ok(result) => {
                     self.recv_queue.push_front(result);
                     let cause: String = format!(
@@ -426,8 +860,26 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
                 header
             );
 
+            // Any segment from our peer shows that the connection is alive.
+            self.restart_keepalive_timer();
+            self.record_options(&header);
+
             match self.process_packet(header, data) {
+                Ok(()) if self.state == State::FinWait1 && self.fin_acknowledged() => self.state = State::FinWait2,
                 Ok(()) => (),
+                Err(e) if e.errno == libc::ECONNRESET && self.write_shutdown => {
+                    self.state = match self.state {
+                        State::FinWait1 if self.fin_acknowledged() => State::TimeWait,
+                        State::FinWait1 => State::Closing,
+                        _ => State::TimeWait,
+                    };
+                    let cause: String = format!(
+                        "remote closed half-closed connection, stopping processing (local={:?}, remote={:?})",
+                        self.local, self.remote
+                    );
+                    error!("poll(): {}", cause);
+                    return Err(Fail::new(libc::ECANCELED, &cause));
+                },
                 Err(e) if e.errno == libc::ECONNRESET => {
                     self.state = State::CloseWait;
                     let cause: String = format!(
@@ -442,6 +894,22 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         }
     }
 
+    /// Returns whether the receive polling loop processes segments in the current state. Besides the Established state,
+    /// these are the FIN-WAIT states of a connection whose write side was shut down.
+    fn is_receiving(&self) -> bool {
+        match self.state {
+            State::Established => true,
+            State::FinWait1 | State::FinWait2 => self.write_shutdown,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the peer acknowledged everything that we sent, including our FIN.
+    fn fin_acknowledged(&self) -> bool {
+        self.sender.top_size_unsent().is_none()
+            && self.sender.get_send_unacked().get() == self.sender.get_send_next().get()
+    }
+
     /// This is the main function for processing an incoming packet during the Established state when the connection is
     /// active. Each step in this function return Ok if there is further processing to be done and EBADMSG if the
     /// packet should be dropped after the step.
@@ -468,7 +936,12 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.process_remote_close(&header)?;
         // We should ACK this segment, preferably via piggybacking on a response.
         // TODO: Consider replacing the delayed ACK timer with a simple flag.
-        if self.ack_deadline.get().is_none() {
+        if self.quick_ack {
+            // Delayed ACKs are disabled, so ACK now.
+            self.ack_deadline.set(None);
+            trace!("process_packet(): sending quick ack");
+            self.send_ack();
+        } else if self.ack_deadline.get().is_none() {
             // Start the delayed ACK timer to ensure an ACK gets sent soon even if no piggyback opportunity occurs.
             let timeout: Duration = self.ack_delay_timeout;
             // Getting the current time is extremely cheap as it is just a variable lookup.
@@ -615,6 +1088,7 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
 
             // Our peer has given up.  Shut the connection down hard.
             info!("Received RST");
+            self.reset = true;
             // TODO: Schedule a close coroutine.
             let cause: String = format!("remote reset connection");
             info!("check_rst(): {}", cause);
@@ -663,6 +1137,24 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         let send_unacknowledged: SeqNumber = self.sender.get_send_unacked().get();
         let send_next: SeqNumber = self.sender.get_send_next().get();
 
+        // An ACK of new data that arrives sooner after a retransmission than half the smallest RTT we have seen cannot
+        // be for the retransmission, so the original transmission got through and the RTO was spurious (see Allman and
+        // Paxson, "On Estimating End-to-End Network Path Properties"). Undo the congestion response to it.
+        if send_unacknowledged < header.ack_num && header.ack_num <= send_next {
+            if let Some(retransmitted_at) = self.rto_retransmitted_at.take() {
+                let now: Instant = self.get_timer().now();
+                if let Some(min_rtt) = self.rto_calculator.min_rtt() {
+                    if now - retransmitted_at < min_rtt / 2 {
+                        debug!(
+                            "process_ack(): spurious rto (local={:?}, remote={:?})",
+                            self.local, self.remote
+                        );
+                        self.cc.on_spurious_rto();
+                    }
+                }
+            }
+        }
+
         // TODO: Restructure this call into congestion control to either integrate it directly or make it more fine-
         // grained.  It currently duplicates the new/duplicate ack check itself internally, which is inefficient.
         // We should either make separate calls for each case or integrate those cases directly.
@@ -703,6 +1195,8 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
                 }
 
                 let nbytes: usize = Into::<u32>::into(header.ack_num - send_unacknowledged) as usize;
+                self.delivery_rate.record(now, nbytes);
+                self.first_delivery_at.get_or_insert(now);
                 self.ack_queue.push(nbytes);
             } else {
                 // This segment acknowledges data we have yet to send!?  Send an ACK and drop the segment.
@@ -716,6 +1210,11 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
             // Duplicate ACK (doesn't acknowledge anything new).  We can mostly ignore this, except for fast-retransmit.
             // TODO: Implement fast-retransmit.  In which case, we'd increment our dup-ack counter here.
             warn!("process_ack(): received duplicate ack ({:?})", header.ack_num);
+
+            // It may still be a window update, e.g. when the peer reopens a closed window.
+            if header.ack_num == send_unacknowledged {
+                self.sender.update_send_window(header);
+            }
         }
         Ok(())
     }
@@ -728,6 +1227,10 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         mut seg_end: SeqNumber,
         mut seg_len: u32,
     ) -> Result<(), Fail> {
+        if self.read_shutdown {
+            return self.process_data_after_read_shutdown(header, data, seg_start);
+        }
+
         // We can only process in-order data (or FIN).  Check for out-of-order segment.
         if seg_start != self.receiver.receive_next {
             debug!("Received out-of-order segment");
@@ -763,6 +1266,38 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         Ok(())
     }
 
+    /// Handles data that arrived after the application shut down the read side of the connection.
+    fn process_data_after_read_shutdown(
+        &mut self,
+        header: &mut TcpHeader,
+        data: DemiBuffer,
+        seg_start: SeqNumber,
+    ) -> Result<(), Fail> {
+        match self.half_closed_read_policy {
+            HalfClosedReadPolicy::Discard => {
+                // Only in-order data moves RCV.NXT forward. Out-of-order data is dropped without being stored, and the
+                // peer retransmits it once the hole is filled.
+                if seg_start == self.receiver.receive_next {
+                    trace!("process_data(): discarding {:?} bytes after read shutdown", data.len());
+                    self.receiver.discard(data.len() as u32);
+                } else {
+                    header.fin = false;
+                }
+                Ok(())
+            },
+            HalfClosedReadPolicy::Reset => {
+                self.send_rst();
+                self.reset = true;
+                let cause: String = format!(
+                    "reset connection on data received after read shutdown (local={:?}, remote={:?})",
+                    self.local, self.remote
+                );
+                info!("process_data(): {}", cause);
+                Err(Fail::new(libc::ECONNRESET, &cause))
+            },
+        }
+    }
+
     /// Fetch a TCP header filling out various values based on our current state.
     /// TODO: Fix the "filling out various values based on our current state" part to actually do that correctly.
     pub fn tcp_header(&self) -> TcpHeader {
@@ -792,6 +1327,16 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         }
     }
 
+    /// Send a RST to our peer, which aborts the connection.
+    fn send_rst(&mut self) {
+        let mut header: TcpHeader = self.tcp_header();
+        header.seq_num = self.get_send_next().get();
+        header.rst = true;
+        if let Some(remote_link_addr) = self.arp().try_query(*self.remote.ip()) {
+            self.emit(header, None, remote_link_addr);
+        }
+    }
+
     /// Transmit this message to our connected peer.
     ///
     pub fn emit(&mut self, header: TcpHeader, body: Option<DemiBuffer>, remote_link_addr: MacAddress) {
@@ -810,16 +1355,35 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
 
         // Prepare description of TCP segment to send.
         // TODO: Change this to call lower levels to fill in their header information, handle routing, ARPing, etc.
+        let mut ipv4_hdr: Ipv4Header =
+            Ipv4Header::new(self.local.ip().clone(), self.remote.ip().clone(), IpProtocol::TCP);
+        ipv4_hdr.set_dscp(self.dscp);
         let segment = TcpSegment {
             ethernet2_hdr: Ethernet2Header::new(remote_link_addr, self.local_link_addr, EtherType2::Ipv4),
-            ipv4_hdr: Ipv4Header::new(self.local.ip().clone(), self.remote.ip().clone(), IpProtocol::TCP),
+            ipv4_hdr,
             tcp_hdr: header,
             data: body,
             tx_checksum_offload: self.tcp_config.get_tx_checksum_offload(),
         };
 
         // Call the runtime to send the segment.
+        let sent_bytes: usize = segment.data.as_ref().map_or(0, |data| data.len());
         self.transport.transmit(Box::new(segment));
+        let now: Instant = self.get_now();
+        self.send_rate.record(now, sent_bytes);
+
+        // When pacing, the next data segment waits for as long as this one takes to go out at the pacing rate. The
+        // delivery rate is underestimated until it was measured over a full rate window, so segments go out unpaced
+        // until then.
+        let measured_delivery_rate: bool = self
+            .first_delivery_at
+            .is_some_and(|first_delivery_at| now - first_delivery_at >= self.tcp_config.get_rate_window());
+        if self.tcp_config.get_pacing() && measured_delivery_rate && sent_bytes > 0 {
+            let pacing_rate_bps: f64 = self.delivery_rate.rate_bps(now) as f64 * PACING_GAIN;
+            if pacing_rate_bps > 0.0 {
+                self.next_send_at = Some(now + Duration::from_secs_f64((sent_bytes * 8) as f64 / pacing_rate_bps));
+            }
+        }
 
         // Post-send operations follow.
         // Review: We perform these after the send, in order to keep send latency as low as possible.
@@ -850,6 +1414,105 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.ack_deadline.clone()
     }
 
+    pub fn set_quick_ack(&mut self, quick_ack: bool) {
+        self.quick_ack = quick_ack;
+    }
+
+    pub fn get_send_buffer_size(&self) -> Option<usize> {
+        self.sender.get_send_buffer_size()
+    }
+
+    pub fn set_send_buffer_size(&mut self, size: usize) {
+        self.sender.set_send_buffer_size(size)
+    }
+
+    /// Records the options carried by a segment from the peer, replacing earlier values of the same kind. Does nothing
+    /// unless the configuration asks for options to be recorded.
+    pub fn record_options(&mut self, header: &TcpHeader) {
+        if !self.tcp_config.get_record_options() {
+            return;
+        }
+        for option in header.iter_options().filter_map(|option| option.to_observed()) {
+            match self
+                .observed_options
+                .iter_mut()
+                .find(|observed| mem::discriminant(*observed) == mem::discriminant(&option))
+            {
+                Some(observed) => *observed = option,
+                None => self.observed_options.push(option),
+            }
+        }
+    }
+
+    pub fn get_observed_options(&self) -> Vec<TcpOption> {
+        self.observed_options.clone()
+    }
+
+    pub fn get_used_fast_open(&self) -> bool {
+        self.used_fast_open
+    }
+
+    /// Marks this connection as one that exchanged data in its SYN.
+    pub fn set_used_fast_open(&mut self) {
+        self.used_fast_open = true;
+    }
+
+    pub fn get_local_isn(&self) -> Option<SeqNumber> {
+        self.local_isn
+    }
+
+    pub fn set_local_isn(&mut self, local_isn: SeqNumber) {
+        self.local_isn = Some(local_isn);
+    }
+
+    pub fn set_keepalive(&mut self, enabled: bool) {
+        self.keepalive_enabled = enabled;
+        self.restart_keepalive_timer();
+    }
+
+    pub fn get_keepalive_deadline(&self) -> SharedWatchedValue<Option<Instant>> {
+        self.keepalive_deadline.clone()
+    }
+
+    /// Sends a keepalive probe and schedules the next one. Gives up on the connection once the peer has left the maximum
+    /// number of probes unanswered, so that pending and later pops and pushes fail with ETIMEDOUT.
+    pub fn send_keepalive_probe(&mut self) -> Result<(), Fail> {
+        // Data in flight already probes the peer through retransmissions.
+        if self.get_send_unacked().get() != self.get_send_next().get() {
+            self.restart_keepalive_timer();
+            return Ok(());
+        }
+
+        if self.keepalive_probes_sent >= DEFAULT_KEEPALIVE_PROBES {
+            let cause: String = format!("keepalive timed out (local={:?}, remote={:?})", self.local, self.remote);
+            error!("send_keepalive_probe(): {}", cause);
+            return Err(self.time_out(Fail::new(libc::ETIMEDOUT, &cause)));
+        }
+
+        // A keepalive probe carries a sequence number that our peer has already acknowledged, which forces it to reply
+        // with an ACK (RFC 1122 Section 4.2.3.6).
+        let mut header: TcpHeader = self.tcp_header();
+        header.seq_num = self.get_send_next().get() - SeqNumber::from(1);
+        if let Some(remote_link_addr) = self.arp().try_query(*self.remote.ip()) {
+            self.emit(header, None, remote_link_addr);
+        }
+        self.keepalive_probes_sent += 1;
+        let now: Instant = self.get_now();
+        self.keepalive_deadline.set(Some(now + DEFAULT_KEEPALIVE_INTERVAL));
+        Ok(())
+    }
+
+    // Reset the keepalive timer to fire after the connection has been idle for the keepalive idle time.
+    fn restart_keepalive_timer(&mut self) {
+        self.keepalive_probes_sent = 0;
+        if self.keepalive_enabled {
+            let now: Instant = self.get_now();
+            self.keepalive_deadline.set(Some(now + DEFAULT_KEEPALIVE_IDLE));
+        } else if self.keepalive_deadline.get().is_some() {
+            self.keepalive_deadline.set(None);
+        }
+    }
+
     pub fn set_ack_deadline(&mut self, when: Option<Instant>) {
         self.ack_deadline.set(when);
     }
@@ -859,8 +1522,117 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.receive_buffer_size - bytes_unread
     }
 
+    pub fn set_receive_window_clamp(&mut self, clamp: Option<u32>) {
+        self.receive_window_clamp = clamp;
+    }
+
+    /// Largest receive window that we may advertise, that is, the receive buffer size bounded by the clamp.
+    pub fn get_receive_window_limit(&self) -> u32 {
+        match self.receive_window_clamp {
+            Some(clamp) => self.receive_buffer_size.min(clamp),
+            None => self.receive_buffer_size,
+        }
+    }
+
+    /// Pauses or resumes the data flow, and immediately tells the peer about the resulting window.
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused != paused {
+            self.paused = paused;
+            self.send_ack();
+        }
+    }
+
+    /// Returns the largest TCP payload that fits in a single packet on the path to the peer.
+    pub fn get_path_mtu(&self) -> u16 {
+        let mtu: u16 = match self.path_mtu {
+            Some(path_mtu) => path_mtu.min(self.transport.get_mtu()),
+            None => self.transport.get_mtu(),
+        };
+        mtu.saturating_sub(IPV4_HEADER_MIN_SIZE + MIN_TCP_HEADER_SIZE as u16)
+    }
+
+    /// Lowers the path MTU to `next_hop_mtu`, as reported by a router that could not forward our segment starting at
+    /// `seq_num`, and sends all unacknowledged data again in segments that fit. Reports that would raise the path MTU,
+    /// that go below [MIN_PATH_MTU], or that quote a segment which is not in flight (RFC 5927, section 4.1) are
+    /// ignored.
+    pub fn update_path_mtu(&mut self, next_hop_mtu: u16, seq_num: SeqNumber) {
+        let current_mtu: u16 = self.get_path_mtu() + IPV4_HEADER_MIN_SIZE + MIN_TCP_HEADER_SIZE as u16;
+        if next_hop_mtu >= current_mtu {
+            return;
+        }
+        if next_hop_mtu < MIN_PATH_MTU {
+            warn!(
+                "update_path_mtu(): ignoring next-hop mtu below the minimum (next_hop_mtu={:?}, min={:?})",
+                next_hop_mtu, MIN_PATH_MTU
+            );
+            return;
+        }
+        let send_unacked: SeqNumber = self.sender.get_send_unacked().get();
+        let send_next: SeqNumber = self.sender.get_send_next().get();
+        if seq_num < send_unacked || seq_num >= send_next {
+            warn!(
+                "update_path_mtu(): ignoring report for a segment that is not in flight (seq_num={:?}, snd_una={:?}, \
+                 snd_nxt={:?})",
+                seq_num, send_unacked, send_next
+            );
+            return;
+        }
+        self.path_mtu = Some(next_hop_mtu);
+        let mss: usize = (self.get_path_mtu() as usize).max(MIN_MSS);
+        if mss < self.sender.get_mss() {
+            debug!(
+                "update_path_mtu(): lowering mss (mss={:?}, next_hop_mtu={:?})",
+                mss, next_hop_mtu
+            );
+            self.sender.set_mss(mss);
+        }
+
+        // The segment that did not fit was dropped, and so were any that followed it.
+        if self.sender.requeue_unacknowledged() {
+            self.retransmit_deadline.set(None);
+        }
+    }
+
+    pub fn get_dscp(&self) -> u8 {
+        self.dscp
+    }
+
+    pub fn set_half_closed_read_policy(&mut self, policy: HalfClosedReadPolicy) {
+        self.half_closed_read_policy = policy;
+    }
+
+    /// Shuts down the read side of the connection. Data that arrives afterwards is handled according to the
+    /// half-closed read policy.
+    pub fn shutdown_read(&mut self) {
+        self.read_shutdown = true;
+    }
+
+    /// Shuts down the write side of the connection by sending a FIN. Segments from the peer keep being processed, so
+    /// that data can still be received until the application closes the connection.
+    pub fn shutdown_write(&mut self) {
+        let next_state: State = match self.state {
+            State::Established => State::FinWait1,
+            State::CloseWait => State::LastAck,
+            // We already sent a FIN.
+            _ => return,
+        };
+        self.write_shutdown = true;
+        self.state = next_state;
+        self.send_fin();
+    }
+
+    pub fn set_dscp(&mut self, dscp: u8) {
+        self.dscp = dscp;
+    }
+
     fn hdr_window_size(&self) -> u16 {
-        let window_size: u32 = self.get_receive_window_size();
+        if self.paused {
+            return 0;
+        }
+        let window_size: u32 = match self.receive_window_clamp {
+            Some(clamp) => self.get_receive_window_size().min(clamp),
+            None => self.get_receive_window_size(),
+        };
         let hdr_window_size: u16 = (window_size >> self.window_scale)
             .try_into()
             .expect("Window size overflow");
@@ -898,7 +1670,40 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         //  if self.receiver.reader_next.get() == self.receiver.receive_next.get() {
         // But that will think data is available to be read once we've received a FIN, because FINs consume sequence
         // number space.  Now we call is_empty() on the receive queue instead.
-        self.receiver.pop(size, yielder).await
+        if let Some(e) = self.timeout_error.clone() {
+            return Err(e);
+        }
+        let buf: DemiBuffer = self.receiver.pop(size, yielder).await?;
+        self.lift_cork();
+        Ok(buf)
+    }
+
+    pub async fn pop_all(&mut self, yielder: Yielder) -> Result<DemiBuffer, Fail> {
+        if let Some(e) = self.timeout_error.clone() {
+            return Err(e);
+        }
+        let buf: DemiBuffer = self.receiver.pop_all(yielder).await?;
+        self.lift_cork();
+        Ok(buf)
+    }
+
+    /// Returns the RFC 793 state of the connection.
+    pub fn get_tcp_state(&self) -> TcpState {
+        match self.state {
+            State::Established => TcpState::Established,
+            State::FinWait1 => TcpState::FinWait1,
+            State::FinWait2 => TcpState::FinWait2,
+            State::Closing => TcpState::Closing,
+            State::TimeWait => TcpState::TimeWait,
+            State::CloseWait => TcpState::CloseWait,
+            State::LastAck => TcpState::LastAck,
+            State::Closed => TcpState::Closed,
+        }
+    }
+
+    /// Did we close the connection first and move into the TIME_WAIT state?
+    pub fn in_time_wait(&self) -> bool {
+        self.state == State::TimeWait
     }
 
     // This routine remembers that we have received an out-of-order FIN.
@@ -1004,6 +1809,23 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         while self.out_of_order.len() > MAX_OUT_OF_ORDER {
             self.out_of_order.pop_back();
         }
+
+        // If the out-of-order store now holds more data than the connection allows, also delete the later entries.
+        // The peer retransmits them once the hole before them is filled.
+        if let Some(max_reassembly_bytes) = self.max_reassembly_bytes {
+            let mut stored_bytes: usize = self.out_of_order.iter().map(|(_, buf)| buf.len()).sum();
+            while stored_bytes > max_reassembly_bytes {
+                if let Some((_, dropped)) = self.out_of_order.pop_back() {
+                    debug!(
+                        "Dropping out-of-order segment past the reassembly limit ({} bytes)",
+                        dropped.len()
+                    );
+                    stored_bytes -= dropped.len();
+                    // A FIN that we remembered came after the data we dropped, so the peer has to resend it too.
+                    self.out_of_order_fin = None;
+                }
+            }
+        }
     }
 
     // This routine takes an incoming in-order TCP segment and adds the data to the user's receive queue.  If the new
@@ -1047,6 +1869,9 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
             }
         }
 
+        let now: Instant = self.get_now();
+        self.recv_rate.record(now, u32::from(recv_next - seg_start) as usize);
+
         // TODO: Review recent change to update control block copy of recv_next upon each push to the receiver.
         // When receiving a retransmitted segment that fills a "hole" in the receive space, thus allowing a number
         // (potentially large number) of out-of-order segments to be added, we'll be modifying the TCB copy of
@@ -1094,6 +1919,8 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
 
     /// Send a fin by pushing a zero-length DemiBuffer to the sender function.
     fn send_fin(&mut self) {
+        // Data held back by the cork goes out ahead of the FIN.
+        self.set_cork(false);
         // Construct FIN.
         let fin_buf: DemiBuffer = DemiBuffer::new(0);
         // Send.
@@ -1108,6 +1935,16 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         match self.state {
             State::Established => self.local_close(yielder).await,
             State::CloseWait => self.remote_already_closed(yielder).await,
+            // The write side was shut down, so the FIN was already sent. Take the remaining segments over from the
+            // receive polling loop and finish the close protocol.
+            State::FinWait1 | State::FinWait2 | State::Closing | State::TimeWait if self.write_shutdown => {
+                self.write_shutdown = false;
+                self.wait_local_close(yielder).await
+            },
+            State::LastAck if self.write_shutdown => {
+                self.write_shutdown = false;
+                self.wait_last_ack(yielder).await
+            },
             _ => {
                 let cause: String = format!("socket is already closing");
                 error!("close(): {}", cause);
@@ -1121,7 +1958,12 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.state = State::FinWait1;
         // 1. Send FIN.
         self.send_fin();
+        // 2. Wait for the peer to acknowledge it and to close its side.
+        self.wait_local_close(yielder).await
+    }
 
+    /// Runs the rest of an active close, once our FIN was sent.
+    async fn wait_local_close(&mut self, yielder: Yielder) -> Result<(), Fail> {
         while self.state != State::TimeWait {
             // Wait for next packet.
             let (_, header, _) = self.recv_queue.pop(&yielder).await?;
@@ -1156,15 +1998,9 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
             }
         }
 
-        // TODO: Get 2MSL value or linger option if set.
-        // TODO: Turn this on when our tests support it.
-        // let timeout_yielder: Yielder = Yielder::new();
-        // self.runtime
-        //     .get_timer()
-        //     .wait(Duration::from_secs(1), &timeout_yielder)
-        //     .await?;
-
-        self.state = State::Closed;
+        // We stay in TIME_WAIT without waiting for 2MSL to elapse here. Instead, the TCP peer keeps the local address
+        // reserved until then, if it is configured with a maximum segment lifetime.
+        // TODO: Get linger option if set.
         Ok(())
     }
 
@@ -1173,10 +2009,30 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
         self.state = State::LastAck;
         // 1. Send FIN.
         self.send_fin();
+        // 2. Wait for the peer to acknowledge it.
+        self.wait_last_ack(yielder).await
+    }
+
+    /// Runs the rest of a passive close, once our FIN was sent.
+    async fn wait_last_ack(&mut self, yielder: Yielder) -> Result<(), Fail> {
         // Wait for ACK of FIN.
         loop {
             // Wait for next packet.
-            let (_, header, _) = self.recv_queue.pop(&yielder).await?;
+            let (_, header, data) = self.recv_queue.pop(&yielder).await?;
+
+            // Our peer sent its FIN, so any data past it is invalid. Reset the connection if asked to, rather than
+            // leave our peer waiting for an acknowledgement that never comes.
+            let seg_end: SeqNumber = header.seq_num + SeqNumber::from(data.len() as u32);
+            let invalid: bool = !data.is_empty() && seg_end > self.receiver.receive_next;
+            if invalid && !header.rst && self.tcp_config.get_rst_on_invalid_segment() {
+                let cause: String = format!(
+                    "reset connection on data received after the remote closed (local={:?}, remote={:?})",
+                    self.local, self.remote
+                );
+                info!("wait_last_ack(): {}", cause);
+                self.send_rst();
+                break;
+            }
 
             // Check ACK.
             match self.process_ack(&header) {
@@ -1184,6 +2040,7 @@ impl<N: NetworkRuntime> SharedControlBlock<N> {
                 Err(_) => (),
             }
         }
+        self.state = State::Closed;
         Ok(())
     }
 }
@@ -4,6 +4,7 @@
 mod background;
 pub mod congestion_control;
 mod ctrlblk;
+mod rate;
 mod rto;
 mod sender;
 
@@ -27,6 +28,13 @@ use crate::{
         memory::DemiBuffer,
         network::{
             config::TcpConfig,
+            socket::option::HalfClosedReadPolicy,
+            types::{
+                ConnectionSnapshot,
+                TcpOption,
+                TcpSeqState,
+                TcpState,
+            },
             NetworkRuntime,
         },
         scheduler::Yielder,
@@ -130,6 +138,10 @@ impl<N: NetworkRuntime> EstablishedSocket<N> {
         self.cb.pop(size, yielder).await
     }
 
+    pub async fn pop_all(&mut self, yielder: Yielder) -> Result<DemiBuffer, Fail> {
+        self.cb.pop_all(yielder).await
+    }
+
     pub async fn close(&mut self, yielder: Yielder) -> Result<(), Fail> {
         self.cb.close(yielder).await
     }
@@ -142,9 +154,161 @@ impl<N: NetworkRuntime> EstablishedSocket<N> {
         self.cb.rto()
     }
 
+    pub fn retransmit_count(&self) -> u64 {
+        self.cb.get_retransmit_count()
+    }
+
+    pub fn cwnd(&self) -> u32 {
+        self.cb.congestion_control_get_cwnd().get()
+    }
+
+    pub fn ssthresh(&self) -> u32 {
+        self.cb.congestion_control_get_ssthresh()
+    }
+
+    pub fn send_rate_bps(&self) -> u64 {
+        self.cb.send_rate_bps()
+    }
+
+    pub fn recv_rate_bps(&self) -> u64 {
+        self.cb.recv_rate_bps()
+    }
+
+    pub fn delivery_rate_bps(&self) -> u64 {
+        self.cb.delivery_rate_bps()
+    }
+
+    pub fn has_data_to_pop(&self) -> bool {
+        self.cb.has_data_to_pop()
+    }
+
+    pub fn record_options(&mut self, header: &TcpHeader) {
+        self.cb.record_options(header)
+    }
+
+    pub fn observed_options(&self) -> Vec<TcpOption> {
+        self.cb.get_observed_options()
+    }
+
+    pub fn used_fast_open(&self) -> bool {
+        self.cb.get_used_fast_open()
+    }
+
+    pub fn set_used_fast_open(&mut self) {
+        self.cb.set_used_fast_open()
+    }
+
+    pub fn local_isn(&self) -> Option<SeqNumber> {
+        self.cb.get_local_isn()
+    }
+
+    pub fn set_local_isn(&mut self, local_isn: SeqNumber) {
+        self.cb.set_local_isn(local_isn)
+    }
+
+    pub fn sequence_state(&self) -> TcpSeqState {
+        self.cb.get_sequence_state()
+    }
+
+    pub fn snapshot(&self) -> ConnectionSnapshot {
+        self.cb.get_snapshot()
+    }
+
+    pub fn restore_buffered_data(&mut self, snapshot: &ConnectionSnapshot) -> Result<(), Fail> {
+        self.cb.restore_buffered_data(snapshot)
+    }
+
+    pub fn set_quick_ack(&mut self, quick_ack: bool) {
+        self.cb.set_quick_ack(quick_ack)
+    }
+
+    pub fn set_keepalive(&mut self, enabled: bool) {
+        self.cb.set_keepalive(enabled)
+    }
+
+    pub fn send_buffer_size(&self) -> Option<usize> {
+        self.cb.get_send_buffer_size()
+    }
+
+    pub fn set_send_buffer_size(&mut self, size: usize) {
+        self.cb.set_send_buffer_size(size)
+    }
+
+    pub fn set_receive_window_clamp(&mut self, clamp: Option<u32>) {
+        self.cb.set_receive_window_clamp(clamp)
+    }
+
+    pub fn receive_window_limit(&self) -> u32 {
+        self.cb.get_receive_window_limit()
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.cb.set_paused(paused)
+    }
+
+    pub fn path_mtu(&self) -> u16 {
+        self.cb.get_path_mtu()
+    }
+
+    pub fn update_path_mtu(&mut self, next_hop_mtu: u16, seq_num: SeqNumber) {
+        self.cb.update_path_mtu(next_hop_mtu, seq_num)
+    }
+
+    pub fn dscp(&self) -> u8 {
+        self.cb.get_dscp()
+    }
+
+    pub fn set_dscp(&mut self, dscp: u8) {
+        self.cb.set_dscp(dscp)
+    }
+
+    pub fn set_max_rto(&mut self, max_rto: Option<Duration>) {
+        self.cb.set_max_rto(max_rto)
+    }
+
+    pub fn set_max_reassembly_bytes(&mut self, max_reassembly_bytes: Option<usize>) {
+        self.cb.set_max_reassembly_bytes(max_reassembly_bytes)
+    }
+
+    pub fn set_cork(&mut self, cork: bool) {
+        self.cb.set_cork(cork)
+    }
+
+    pub fn set_auto_flush_after_pop(&mut self, auto_flush_after_pop: bool) {
+        self.cb.set_auto_flush_after_pop(auto_flush_after_pop)
+    }
+
+    pub fn set_user_timeout(&mut self, user_timeout: Option<Duration>) {
+        self.cb.set_user_timeout(user_timeout)
+    }
+
+    pub fn set_half_closed_read_policy(&mut self, policy: HalfClosedReadPolicy) {
+        self.cb.set_half_closed_read_policy(policy)
+    }
+
+    pub fn shutdown_read(&mut self) {
+        self.cb.shutdown_read()
+    }
+
+    pub fn shutdown_write(&mut self) {
+        self.cb.shutdown_write()
+    }
+
+    pub fn tcp_state(&self) -> TcpState {
+        self.cb.get_tcp_state()
+    }
+
+    pub fn in_time_wait(&self) -> bool {
+        self.cb.in_time_wait()
+    }
+
     pub fn endpoints(&self) -> (SocketAddrV4, SocketAddrV4) {
         (self.cb.get_local(), self.cb.get_remote())
     }
+
+    pub fn set_endpoints(&mut self, local: SocketAddrV4, remote: SocketAddrV4) {
+        self.cb.set_endpoints(local, remote)
+    }
 }
 
 //======================================================================================================================
@@ -0,0 +1,108 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use ::std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+// Sliding-window rate estimator.
+
+#[derive(Debug)]
+pub struct RateEstimator {
+    // Length of the window over which the rate is computed.
+    window: Duration,
+
+    // Number of bytes transferred at each instant within the window, oldest first.
+    samples: VecDeque<(Instant, usize)>,
+}
+
+impl RateEstimator {
+    /// Initializes an estimator that averages over `window`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Records that `bytes` bytes were transferred at `now`, and drops the samples that fell out of the window, so that
+    /// the estimator only ever holds the samples of one window.
+    pub fn record(&mut self, now: Instant, bytes: usize) {
+        self.expire(now);
+        if bytes == 0 {
+            return;
+        }
+        match self.samples.back_mut() {
+            // Transfers at the same instant share a sample, so that bursts do not grow the number of samples.
+            Some((last, count)) if *last == now => *count += bytes,
+            _ => self.samples.push_back((now, bytes)),
+        }
+    }
+
+    /// Returns the rate, in bits per second, of the transfers within the window that ends at `now`.
+    pub fn rate_bps(&self, now: Instant) -> u64 {
+        let bytes: usize = self
+            .samples
+            .iter()
+            .filter(|(instant, _)| self.is_within_window(now, *instant))
+            .map(|(_, bytes)| bytes)
+            .sum();
+        ((bytes as f64 * 8.0) / self.window.as_secs_f64()) as u64
+    }
+
+    /// Drops the samples that fell out of the window that ends at `now`.
+    fn expire(&mut self, now: Instant) {
+        while let Some(&(instant, _)) = self.samples.front() {
+            if self.is_within_window(now, instant) {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// Checks whether a sample taken at `instant` falls within the window that ends at `now`.
+    fn is_within_window(&self, now: Instant, instant: Instant) -> bool {
+        now.saturating_duration_since(instant) < self.window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateEstimator;
+    use ::anyhow::Result;
+    use ::std::time::{
+        Duration,
+        Instant,
+    };
+
+    // Records transfers over many windows and checks that only the samples of the last window are kept.
+    #[test]
+    fn record_evicts_old_samples() -> Result<()> {
+        const WINDOW: Duration = Duration::from_secs(1);
+        const STEP: Duration = Duration::from_millis(100);
+        let mut now: Instant = Instant::now();
+        let mut estimator: RateEstimator = RateEstimator::new(WINDOW);
+
+        for _ in 0..100 {
+            now += STEP;
+            estimator.record(now, 1000);
+        }
+        crate::ensure_eq!(
+            estimator.samples.len() as u32,
+            WINDOW.as_millis() as u32 / STEP.as_millis() as u32
+        );
+        crate::ensure_eq!(estimator.rate_bps(now), 10 * 1000 * 8);
+
+        // Recording nothing still drops the samples that fell out of the window.
+        now += WINDOW;
+        estimator.record(now, 0);
+        crate::ensure_eq!(estimator.samples.is_empty(), true);
+        crate::ensure_eq!(estimator.rate_bps(now), 0);
+
+        Ok(())
+    }
+}
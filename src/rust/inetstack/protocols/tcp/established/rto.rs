@@ -21,6 +21,12 @@ pub struct RtoCalculator {
 
     // Whether a RTT (round-trip-time) sample has been received yet.
     received_sample: bool,
+
+    // Smallest round-trip time sampled so far.
+    min_rtt: f64,
+
+    // Upper bound on the retransmission timeout set by the application, if any.
+    max_rto: Option<f64>,
 }
 
 impl RtoCalculator {
@@ -33,6 +39,8 @@ impl RtoCalculator {
             rttvar: 0.0,
             rto: 1.0,
             received_sample: false,
+            min_rtt: f64::MAX,
+            max_rto: None,
         }
     }
 
@@ -46,6 +54,7 @@ impl RtoCalculator {
         const GRANULARITY: f64 = 0.001f64;
 
         let rtt: f64 = rtt.as_secs_f64();
+        self.min_rtt = self.min_rtt.min(rtt);
 
         if !self.received_sample {
             // Initial sample formula from RFC 6298 Section 2.2:
@@ -72,10 +81,23 @@ impl RtoCalculator {
         // RFC 6298's suggested value for the upper bound is >= 60 seconds.
         const UPPER_BOUND_SEC: f64 = 60.0f64;
 
+        // The application may lower the upper bound, but not below the lower one.
+        let upper_bound: f64 = match self.max_rto {
+            Some(max_rto) => max_rto.clamp(LOWER_BOUND_SEC, UPPER_BOUND_SEC),
+            None => UPPER_BOUND_SEC,
+        };
+
         // Note: We use clamp() below as it is clearer in intent than a min/max combination.  However, if we were
         // concerned that new_rto could be NaN here (we're not) we wouldn't want to use clamp() as it would pass NaN
         // through.  We'd use "self.rto = f64::min(new_rto.max(LOWER_BOUND_SEC), UPPER_BOUND_SEC);" below instead.
-        self.rto = new_rto.clamp(LOWER_BOUND_SEC, UPPER_BOUND_SEC);
+        self.rto = new_rto.clamp(LOWER_BOUND_SEC, upper_bound);
+    }
+
+    /// Caps the retransmission timeout, however many times it has been backed off. Takes effect on the current value
+    /// right away.
+    pub fn set_max_rto(&mut self, max_rto: Option<Duration>) {
+        self.max_rto = max_rto.map(|max_rto| max_rto.as_secs_f64());
+        self.update_rto(self.rto);
     }
 
     /// Performs an exponential "back off" of the RTO (doubles the current timeout).
@@ -87,4 +109,13 @@ impl RtoCalculator {
     pub fn rto(&self) -> Duration {
         Duration::from_secs_f64(self.rto)
     }
+
+    /// Gets the smallest RTT sampled so far, if any sample has been received.
+    pub fn min_rtt(&self) -> Option<Duration> {
+        if self.received_sample {
+            Some(Duration::from_secs_f64(self.min_rtt))
+        } else {
+            None
+        }
+    }
 }
@@ -17,15 +17,18 @@ use crate::{
 use ::libc::{
     EBUSY,
     EINVAL,
+    EWOULDBLOCK,
 };
 use ::std::{
     cell::{
         Cell,
         RefCell,
     },
+    cmp,
     collections::VecDeque,
     convert::TryInto,
     fmt,
+    mem,
     time::{
         Duration,
         Instant,
@@ -43,8 +46,8 @@ pub struct UnackedSegment {
 }
 
 /// Hard limit for unsent queue.
-/// TODO: Remove this.  We should limit the unsent queue by either having a (configurable) send buffer size (in bytes,
-/// not segments) and rejecting send requests that exceed that, or by limiting the user's send buffer allocations.
+/// TODO: Remove this.  A send buffer size (SO_SNDBUF) bounds the unsent queue in bytes, but only when it is set, since
+/// the send buffer is unbounded by default.
 const UNSENT_QUEUE_CUTOFF: usize = 1024;
 
 // TODO: Consider moving retransmit timer and congestion control fields out of this structure.
@@ -69,6 +72,9 @@ pub struct Sender {
     // Queue of unacknowledged sent data.  RFC 793 calls this the "retransmission queue".
     unacked_queue: RefCell<VecDeque<UnackedSegment>>,
 
+    // Since when the oldest data on the unacknowledged queue has gone without our peer acknowledging anything.
+    unacked_since: Cell<Option<Instant>>,
+
     // Sequence Number of the next data to be sent.  In RFC 793 terms, this is SND.NXT.
     send_next: SharedWatchedValue<SeqNumber>,
 
@@ -89,6 +95,12 @@ pub struct Sender {
     // Maximum Segment Size currently in use for this connection.
     // TODO: Revisit this once we support path MTU discovery.
     mss: usize,
+
+    // Number of segments retransmitted on this connection.
+    retransmit_count: Cell<u64>,
+
+    // Maximum number of unacknowledged and unsent bytes we are willing to buffer, if bounded.
+    send_buffer_size: Option<usize>,
 }
 
 impl fmt::Debug for Sender {
@@ -100,6 +112,7 @@ impl fmt::Debug for Sender {
             .field("send_window", &self.send_window)
             .field("window_scale", &self.window_scale)
             .field("mss", &self.mss)
+            .field("send_buffer_size", &self.send_buffer_size)
             .finish()
     }
 }
@@ -109,6 +122,7 @@ impl Sender {
         Self {
             send_unacked: SharedWatchedValue::new(seq_no),
             unacked_queue: RefCell::new(VecDeque::new()),
+            unacked_since: Cell::new(None),
             send_next: SharedWatchedValue::new(seq_no),
             unsent_queue: RefCell::new(VecDeque::new()),
             unsent_seq_no: SharedWatchedValue::new(seq_no),
@@ -119,6 +133,8 @@ impl Sender {
 
             window_scale,
             mss,
+            retransmit_count: Cell::new(0),
+            send_buffer_size: None,
         }
     }
 
@@ -126,6 +142,39 @@ impl Sender {
         self.mss
     }
 
+    pub fn get_window_scale(&self) -> u8 {
+        self.window_scale
+    }
+
+    pub fn set_mss(&mut self, mss: usize) {
+        self.mss = mss;
+    }
+
+    pub fn get_retransmit_count(&self) -> u64 {
+        self.retransmit_count.get()
+    }
+
+    /// Returns the data that the peer has not acknowledged yet, followed by the data that was not sent yet.
+    pub fn get_buffered_data(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        for segment in self.unacked_queue.borrow().iter() {
+            data.extend_from_slice(&segment.bytes[..]);
+        }
+        for buf in self.unsent_queue.borrow().iter() {
+            data.extend_from_slice(&buf[..]);
+        }
+        data
+    }
+
+    pub fn get_send_buffer_size(&self) -> Option<usize> {
+        self.send_buffer_size
+    }
+
+    /// Bounds the send buffer. It never drops below one MSS, so that a full segment can always be buffered.
+    pub fn set_send_buffer_size(&mut self, size: usize) {
+        self.send_buffer_size = Some(size.max(self.mss));
+    }
+
     pub fn get_send_window(&self) -> SharedWatchedValue<u32> {
         self.send_window.clone()
     }
@@ -147,7 +196,16 @@ impl Sender {
     }
 
     pub fn push_unacked_segment(&self, segment: UnackedSegment) {
-        self.unacked_queue.borrow_mut().push_back(segment)
+        let mut unacked_queue = self.unacked_queue.borrow_mut();
+        if unacked_queue.is_empty() {
+            self.unacked_since.set(segment.initial_tx);
+        }
+        unacked_queue.push_back(segment)
+    }
+
+    /// Since when sent data has gone unacknowledged, if there is any.
+    pub fn get_unacked_since(&self) -> Option<Instant> {
+        self.unacked_since.get()
     }
 
     // This is the main TCP send routine.
@@ -167,6 +225,20 @@ impl Sender {
             .try_into()
             .map_err(|_| Fail::new(EINVAL, "buffer too large"))?;
 
+        // Apply backpressure once a bounded send buffer is full. A push into an empty buffer always goes through, so
+        // that buffers larger than the send buffer can still be sent.
+        if let Some(send_buffer_size) = self.send_buffer_size {
+            let buffered: usize = u32::from(self.unsent_seq_no.get() - self.send_unacked.get()) as usize;
+            if buf_len > 0 && buffered > 0 && buffered + buf_len as usize > send_buffer_size {
+                let cause: String = format!(
+                    "send buffer is full (buffered={:?}, send_buffer_size={:?})",
+                    buffered, send_buffer_size
+                );
+                warn!("send(): {}", cause);
+                return Err(Fail::new(EWOULDBLOCK, &cause));
+            }
+        }
+
         // TODO: What we should do here:
         //
         // Conceptually, we should take the provided buffer and add it to the unsent queue.  Then calculate the amount
@@ -192,7 +264,7 @@ impl Sender {
             let send_next: SeqNumber = self.send_next.get();
             let sent_data: u32 = (send_next - send_unacknowledged).into();
 
-            // TODO: What limits buffer len to MSS?
+            // Buffers larger than the MSS are left to the background sender, which segments them.
             let in_flight_after_send: u32 = sent_data + buf_len;
 
             // Before we get cwnd for the check, we prompt it to shrink it if the connection has been idle.
@@ -204,7 +276,15 @@ impl Sender {
 
             let win_sz: u32 = self.send_window.get();
 
-            if win_sz > 0 && win_sz >= in_flight_after_send && effective_cwnd >= in_flight_after_send {
+            // A segment that the pacer or the cork holds back is left to the background sender, which waits for its
+            // turn.
+            if win_sz > 0
+                && cb.pacing_delay().is_none()
+                && !cb.is_corked()
+                && win_sz >= in_flight_after_send
+                && effective_cwnd >= in_flight_after_send
+                && buf_len as usize <= self.mss
+            {
                 if let Some(remote_link_addr) = cb.arp().try_query(cb.get_remote().ip().clone()) {
                     // This hook is primarily intended to record the last time we sent data, so we can later tell if
                     // the connection has been idle.
@@ -236,7 +316,7 @@ impl Sender {
                         bytes: buf,
                         initial_tx: Some(cb.get_timer().now()),
                     };
-                    self.unacked_queue.borrow_mut().push_back(unacked_segment);
+                    self.push_unacked_segment(unacked_segment);
 
                     // Start the retransmission timer if it isn't already running.
                     if cb.get_retransmit_deadline().is_none() {
@@ -259,12 +339,38 @@ impl Sender {
 
         // Slow path: Delegating sending the data to background processing.
         trace!("Queueing Send for background processing");
-        self.unsent_queue.borrow_mut().push_back(buf);
+        if cb.is_corked() {
+            self.coalesce_unsent(buf)?;
+        } else {
+            self.unsent_queue.borrow_mut().push_back(buf);
+        }
         self.unsent_seq_no.modify(|s| s + SeqNumber::from(buf_len));
 
         Ok(())
     }
 
+    /// Queues `buf` for sending, topping up the last queued buffer with its front up to the MSS, so that small pushes
+    /// go out as full segments. Zero-length buffers mark the end of the stream, so they are never merged.
+    fn coalesce_unsent(&self, mut buf: DemiBuffer) -> Result<(), Fail> {
+        let mut unsent_queue = self.unsent_queue.borrow_mut();
+        if let Some(last) = unsent_queue.back_mut() {
+            if last.len() > 0 && last.len() < self.mss && buf.len() > 0 {
+                let nbytes: usize = cmp::min(self.mss - last.len(), buf.len());
+                let front: DemiBuffer = if nbytes < buf.len() {
+                    buf.split_front(nbytes)?
+                } else {
+                    mem::replace(&mut buf, DemiBuffer::new(0))
+                };
+                *last = DemiBuffer::concat(vec![last.clone(), front])?;
+                if buf.len() == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        unsent_queue.push_back(buf);
+        Ok(())
+    }
+
     /// Retransmits the earliest segment that has not (yet) been acknowledged by our peer.
     pub fn retransmit<N: NetworkRuntime>(&self, mut cb: SharedControlBlock<N>) {
         // Check that we have an unacknowledged segment.
@@ -289,6 +395,7 @@ impl Sender {
                     header.psh = true;
                 }
                 cb.emit(header, Some(data), first_hop_link_addr);
+                self.retransmit_count.set(self.retransmit_count.get() + 1);
             }
         } else {
             // We shouldn't enter the retransmit routine with an empty unacknowledged queue.  So maybe we should assert
@@ -298,6 +405,24 @@ impl Sender {
         }
     }
 
+    // Move all unacknowledged data back to the front of the unsent queue, so that the background sender sends it again
+    // in segments of the current MSS. Returns whether there was any unacknowledged data.
+    //
+    pub fn requeue_unacknowledged(&mut self) -> bool {
+        let mut unacked_queue = self.unacked_queue.borrow_mut();
+        if unacked_queue.is_empty() {
+            return false;
+        }
+        let mut unsent_queue = self.unsent_queue.borrow_mut();
+        while let Some(segment) = unacked_queue.pop_back() {
+            unsent_queue.push_front(segment.bytes);
+        }
+        self.unacked_since.set(None);
+        self.retransmit_count.set(self.retransmit_count.get() + 1);
+        self.send_next.set(self.send_unacked.get());
+        true
+    }
+
     // Remove acknowledged data from the unacknowledged (a.k.a. retransmission) queue.
     //
     pub fn remove_acknowledged_data<N: NetworkRuntime>(
@@ -345,6 +470,15 @@ impl Sender {
             // TODO: Mark the send operation associated with this buffer as complete, so the user can reuse the buffer.
             self.unacked_queue.borrow_mut().pop_front();
         }
+
+        // Our peer made progress, so whatever data is left has only gone unacknowledged since now.
+        if bytes_acknowledged != 0 {
+            let unacked_since: Option<Instant> = match self.unacked_queue.borrow().is_empty() {
+                true => None,
+                false => Some(now),
+            };
+            self.unacked_since.set(unacked_since);
+        }
     }
 
     pub fn pop_one_unsent_byte(&self) -> Option<DemiBuffer> {
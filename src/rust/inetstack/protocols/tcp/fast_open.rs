@@ -0,0 +1,174 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::protocols::tcp::segment::FastOpenCookie,
+    runtime::SharedObject,
+};
+use ::std::{
+    collections::HashMap,
+    net::Ipv4Addr,
+    ops::{
+        Deref,
+        DerefMut,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Size of the key that cookies are derived from.
+pub const FAST_OPEN_KEY_SIZE: usize = 16;
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// TCP Fast Open (RFC 7413) state shared by all sockets of a peer: the secret from which we derive the cookies that we
+/// hand out as a server, and the cookies that servers handed out to us as a client.
+pub struct FastOpenState {
+    /// Key that cookies handed out to clients are derived from.
+    key: [u8; FAST_OPEN_KEY_SIZE],
+    /// Cookies received from servers, by server address.
+    cookies: HashMap<Ipv4Addr, FastOpenCookie>,
+}
+
+#[derive(Clone)]
+pub struct SharedFastOpenState(SharedObject<FastOpenState>);
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl SharedFastOpenState {
+    pub fn new(key: [u8; FAST_OPEN_KEY_SIZE]) -> Self {
+        Self(SharedObject::<FastOpenState>::new(FastOpenState {
+            key,
+            cookies: HashMap::<Ipv4Addr, FastOpenCookie>::new(),
+        }))
+    }
+
+    /// Generates the cookie for `client`. A client always gets the same cookie, so that servers do not need to keep
+    /// track of the cookies they handed out. Cookies are a keyed MAC of the client address, so that they cannot be
+    /// forged without the key (RFC 7413, section 4.1.2).
+    pub fn generate_cookie(&self, client: &Ipv4Addr) -> FastOpenCookie {
+        FastOpenCookie::new(&siphash24(&self.key, &client.octets()).to_be_bytes())
+    }
+
+    /// Checks whether `cookie` is the one that we handed out to `client`.
+    pub fn is_valid_cookie(&self, client: &Ipv4Addr, cookie: &FastOpenCookie) -> bool {
+        !cookie.is_request() && *cookie == self.generate_cookie(client)
+    }
+
+    /// Returns the cookie that `server` handed out to us, if any.
+    pub fn cached_cookie(&self, server: &Ipv4Addr) -> Option<FastOpenCookie> {
+        self.cookies.get(server).copied()
+    }
+
+    /// Remembers the cookie that `server` handed out to us, for later connections to it.
+    pub fn cache_cookie(&mut self, server: Ipv4Addr, cookie: FastOpenCookie) {
+        self.cookies.insert(server, cookie);
+    }
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Computes the SipHash-2-4 MAC of `data` under `key`.
+fn siphash24(key: &[u8; FAST_OPEN_KEY_SIZE], data: &[u8]) -> u64 {
+    let k0: u64 = u64::from_le_bytes(key[..8].try_into().expect("key should have 16 bytes"));
+    let k1: u64 = u64::from_le_bytes(key[8..].try_into().expect("key should have 16 bytes"));
+    let mut v: [u64; 4] = [
+        k0 ^ 0x736f6d6570736575,
+        k1 ^ 0x646f72616e646f6d,
+        k0 ^ 0x6c7967656e657261,
+        k1 ^ 0x7465646279746573,
+    ];
+
+    // The last block holds the remaining bytes and, in its most significant byte, the length of the data.
+    let mut chunks = data.chunks_exact(8);
+    let mut last: [u8; 8] = [0; 8];
+    last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    last[7] = data.len() as u8;
+    for m in chunks
+        .by_ref()
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("chunk should have 8 bytes")))
+    {
+        v[3] ^= m;
+        sipround(&mut v);
+        sipround(&mut v);
+        v[0] ^= m;
+    }
+    let m: u64 = u64::from_le_bytes(last);
+    v[3] ^= m;
+    sipround(&mut v);
+    sipround(&mut v);
+    v[0] ^= m;
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        sipround(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// Runs one SipRound over the state `v`.
+fn sipround(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+impl Deref for SharedFastOpenState {
+    type Target = FastOpenState;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.deref()
+    }
+}
+
+impl DerefMut for SharedFastOpenState {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.deref_mut()
+    }
+}
+
+//======================================================================================================================
+// Unit Tests
+//======================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::siphash24;
+    use ::anyhow::Result;
+
+    /// Checks the MAC against the test vectors of the SipHash reference implementation, whose key is 00..0f and whose
+    /// messages are 00, 00 01, 00 01 02, and so on.
+    #[test]
+    fn siphash24_test_vectors() -> Result<()> {
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let data: Vec<u8> = (0..16).collect();
+        crate::ensure_eq!(siphash24(&key, &data[..0]), 0x726fdb47dd0e0e31);
+        crate::ensure_eq!(siphash24(&key, &data[..1]), 0x74f839c593dc67fd);
+        crate::ensure_eq!(siphash24(&key, &data[..8]), 0x93f5f5799a932462);
+        crate::ensure_eq!(siphash24(&key, &data[..15]), 0xa129ca6149be45e5);
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT license.
 
-use crate::inetstack::protocols::tcp::SeqNumber;
+use crate::{
+    inetstack::protocols::tcp::SeqNumber,
+    runtime::SharedObject,
+};
 #[allow(unused_imports)]
 use std::{
     hash::Hasher,
@@ -9,27 +12,50 @@ use std::{
     num::Wrapping,
 };
 
+/// Local and remote addresses of a TCP connection, from which its initial sequence number is generated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConnTuple {
+    pub local: SocketAddrV4,
+    pub remote: SocketAddrV4,
+}
+
+/// Function that generates initial sequence numbers in place of the default generator.
+pub type IsnFn = Box<dyn Fn(&ConnTuple) -> u32>;
+
 #[allow(dead_code)]
 pub struct IsnGenerator {
     nonce: u32,
     counter: Wrapping<u16>,
+    /// Generator installed on the stack, which all of its sockets share, if any.
+    custom: SharedObject<Option<IsnFn>>,
 }
 
 impl IsnGenerator {
-    pub fn new(nonce: u32) -> Self {
+    pub fn new(nonce: u32, custom: SharedObject<Option<IsnFn>>) -> Self {
         Self {
             nonce,
             counter: Wrapping(0),
+            custom,
+        }
+    }
+
+    pub fn generate(&mut self, local: &SocketAddrV4, remote: &SocketAddrV4) -> SeqNumber {
+        match self.custom.as_ref() {
+            Some(custom) => SeqNumber::from(custom(&ConnTuple {
+                local: *local,
+                remote: *remote,
+            })),
+            None => self.generate_default(local, remote),
         }
     }
 
     #[cfg(test)]
-    pub fn generate(&mut self, _local: &SocketAddrV4, _remote: &SocketAddrV4) -> SeqNumber {
+    fn generate_default(&mut self, _local: &SocketAddrV4, _remote: &SocketAddrV4) -> SeqNumber {
         SeqNumber::from(0)
     }
 
     #[cfg(not(test))]
-    pub fn generate(&mut self, local: &SocketAddrV4, remote: &SocketAddrV4) -> SeqNumber {
+    fn generate_default(&mut self, local: &SocketAddrV4, remote: &SocketAddrV4) -> SeqNumber {
         let crc: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_CKSUM);
         let mut digest = crc.digest();
         digest.update(&remote.ip().octets());
@@ -4,6 +4,7 @@
 mod active_open;
 pub mod constants;
 mod established;
+mod fast_open;
 mod isn_generator;
 mod passive_open;
 pub mod peer;
@@ -16,7 +17,11 @@ mod tests;
 
 pub use self::{
     established::congestion_control,
-    peer::SharedTcpPeer,
+    isn_generator::ConnTuple,
+    peer::{
+        LocalAddrs,
+        SharedTcpPeer,
+    },
     segment::{
         MAX_TCP_HEADER_SIZE,
         MIN_TCP_HEADER_SIZE,
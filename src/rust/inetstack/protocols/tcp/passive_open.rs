@@ -25,8 +25,10 @@ use crate::{
                 congestion_control::CongestionControl,
                 EstablishedSocket,
             },
+            fast_open::SharedFastOpenState,
             isn_generator::IsnGenerator,
             segment::{
+                FastOpenCookie,
                 TcpHeader,
                 TcpOptions2,
                 TcpSegment,
@@ -39,7 +41,11 @@ use crate::{
         memory::DemiBuffer,
         network::{
             config::TcpConfig,
-            types::MacAddress,
+            types::{
+                DropReason,
+                DropStats,
+                MacAddress,
+            },
             NetworkRuntime,
         },
         scheduler::{
@@ -60,6 +66,7 @@ use ::futures::{
 };
 use ::libc::{
     EBADMSG,
+    ECONNABORTED,
     ETIMEDOUT,
 };
 use ::std::{
@@ -82,6 +89,10 @@ pub struct PassiveSocket<N: NetworkRuntime> {
     recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)>,
     ready: AsyncQueue<Result<EstablishedSocket<N>, Fail>>,
     max_backlog: usize,
+    /// Whether an overflow of the backlog is queued for an accept to report, so that a flood of SYNs queues at most one.
+    overflow_pending: bool,
+    /// Number of connections whose SYN was received but whose handshake has not completed yet.
+    half_open: usize,
     isn_generator: IsnGenerator,
     local: SocketAddrV4,
     runtime: SharedDemiRuntime,
@@ -90,6 +101,8 @@ pub struct PassiveSocket<N: NetworkRuntime> {
     local_link_addr: MacAddress,
     arp: SharedArpPeer<N>,
     dead_socket_tx: mpsc::UnboundedSender<QDesc>,
+    drop_stats: SharedObject<DropStats>,
+    fast_open: SharedFastOpenState,
     yielder_handle: YielderHandle,
     background_task_qt: Option<QToken>,
 }
@@ -112,7 +125,9 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
         local_link_addr: MacAddress,
         arp: SharedArpPeer<N>,
         dead_socket_tx: mpsc::UnboundedSender<QDesc>,
-        nonce: u32,
+        drop_stats: SharedObject<DropStats>,
+        fast_open: SharedFastOpenState,
+        isn_generator: IsnGenerator,
     ) -> Result<Self, Fail> {
         let yielder: Yielder = Yielder::new();
         let mut me: Self = Self(SharedObject::<PassiveSocket<N>>::new(PassiveSocket {
@@ -120,7 +135,9 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
             recv_queue,
             ready: AsyncQueue::<Result<EstablishedSocket<N>, Fail>>::default(),
             max_backlog,
-            isn_generator: IsnGenerator::new(nonce),
+            overflow_pending: false,
+            half_open: 0,
+            isn_generator,
             local,
             local_link_addr,
             runtime: runtime.clone(),
@@ -128,6 +145,8 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
             tcp_config,
             arp,
             dead_socket_tx,
+            drop_stats,
+            fast_open,
             yielder_handle: yielder.get_handle(),
             background_task_qt: None,
         }));
@@ -142,9 +161,32 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
         self.local
     }
 
-    /// Accept a new connection by fetching one from the queue of requests, blocking if there are no new requests.
+    /// Returns the number of connections that are in the SYN-RECEIVED state.
+    pub fn half_open_count(&self) -> usize {
+        self.half_open
+    }
+
+    /// Returns the number of connections whose handshake completed and that wait to be accepted. Handshakes that failed
+    /// count too, as accepting them reports the failure without waiting.
+    pub fn ready_connections(&self) -> usize {
+        self.ready.len()
+    }
+
+    /// Stops demultiplexing incoming segments from `remote`. Returns whether there was a connection from it.
+    pub fn forget_connection(&mut self, remote: &SocketAddrV4) -> bool {
+        self.connections.remove(remote).is_some()
+    }
+
+    /// Accept a new connection by fetching one from the queue of requests, blocking if there are no new requests. If the
+    /// backlog overflowed since the last accept, this fails with ECONNABORTED instead.
     pub async fn do_accept(&mut self, yielder: Yielder) -> Result<EstablishedSocket<N>, Fail> {
-        self.ready.pop(&yielder).await?
+        let result: Result<EstablishedSocket<N>, Fail> = self.ready.pop(&yielder).await?;
+        if let Err(ref e) = result {
+            if e.errno == ECONNABORTED {
+                self.overflow_pending = false;
+            }
+        }
+        result
     }
 
     async fn poll(mut self, yielder: Yielder) {
@@ -171,22 +213,12 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
                 continue;
             }
 
-            // Check if this SYN segment carries any data.
-            if !buf.is_empty() {
-                // RFC 793 allows connections to be established with data-carrying segments, but we do not support this.
-                // We simply drop the data and and proceed with the three-way handshake protocol, on the hope that the
-                // remote will retransmit the data after the connection is established.
-                // See: https://datatracker.ietf.org/doc/html/rfc793#section-3.4 fo more details.
-                warn!("Received SYN with data (len={})", buf.len());
-                // TODO: https://github.com/microsoft/demikernel/issues/1115
-            }
-
             // Start a new connection.
-            self.handle_new_syn(remote, tcp_hdr);
+            self.handle_new_syn(remote, tcp_hdr, buf);
         }
     }
 
-    fn handle_new_syn(&mut self, remote: SocketAddrV4, tcp_hdr: TcpHeader) {
+    fn handle_new_syn(&mut self, remote: SocketAddrV4, tcp_hdr: TcpHeader, buf: DemiBuffer) {
         debug!("Received SYN: {:?}", tcp_hdr);
         let inflight_len: usize = self.connections.len();
         if inflight_len + self.ready.len() >= self.max_backlog {
@@ -197,10 +229,65 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
                 self.max_backlog
             );
             warn!("handle_new_syn(): {}", cause);
-            self.send_rst(&remote, tcp_hdr);
+            self.drop_stats.record(DropReason::BufferFull);
+            // Acknowledge the SYN, so that the remote takes the RST as a refusal rather than ignore it.
+            let ack_num: SeqNumber = tcp_hdr.seq_num + SeqNumber::from(1 + buf.len() as u32);
+            self.transmit_rst(&remote, SeqNumber::from(0), Some(ack_num));
+            // Let the application know that it is falling behind, rather than have it wait for connections that we
+            // turn away.
+            if !self.overflow_pending {
+                self.overflow_pending = true;
+                self.ready.push(Err(Fail::new(ECONNABORTED, &cause)));
+            }
             return;
         }
 
+        // Drop the SYN without a reset if there are too many half-open connections, so that legitimate clients retry
+        // once some of them complete or time out.
+        if let Some(max_half_open) = self.tcp_config.get_max_half_open() {
+            if self.half_open >= max_half_open {
+                let cause: String = format!(
+                    "too many half-open connections (half_open={}, max={})",
+                    self.half_open, max_half_open
+                );
+                warn!("handle_new_syn(): {}", cause);
+                self.drop_stats.record(DropReason::BufferFull);
+                return;
+            }
+        }
+
+        // Look for a TCP Fast Open cookie, or a request for one, if we take part in TCP Fast Open.
+        let cookie: Option<FastOpenCookie> = match self.tcp_config.get_fast_open() {
+            true => tcp_hdr.iter_options().find_map(|option| match option {
+                TcpOptions2::FastOpen(cookie) => Some(*cookie),
+                _ => None,
+            }),
+            false => None,
+        };
+        let valid_cookie: bool = cookie.map_or(false, |cookie| self.fast_open.is_valid_cookie(remote.ip(), &cookie));
+
+        // Check if this SYN segment carries any data.
+        let syn_data: Option<DemiBuffer> = if buf.is_empty() {
+            None
+        } else if valid_cookie {
+            debug!("Accepting SYN with data (len={})", buf.len());
+            Some(buf)
+        } else {
+            // RFC 793 allows connections to be established with data-carrying segments, but we only accept them from
+            // clients that present a valid TCP Fast Open cookie. Otherwise, we simply drop the data and and proceed with
+            // the three-way handshake protocol, on the hope that the remote will retransmit the data after the
+            // connection is established.
+            // See: https://datatracker.ietf.org/doc/html/rfc793#section-3.4 fo more details.
+            warn!("Received SYN with data (len={})", buf.len());
+            None
+        };
+
+        // Hand out a cookie to clients that asked for one or presented a wrong one.
+        let reply_cookie: Option<FastOpenCookie> = match cookie {
+            Some(_) if !valid_cookie => Some(self.fast_open.generate_cookie(remote.ip())),
+            _ => None,
+        };
+
         // Send SYN+ACK.
         let local: SocketAddrV4 = self.local.clone();
         let local_isn = self.isn_generator.generate(&local, &remote);
@@ -211,18 +298,26 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
         let recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)> =
             SharedAsyncQueue::<(Ipv4Header, TcpHeader, DemiBuffer)>::default();
         let ack_queue: SharedAsyncQueue<usize> = SharedAsyncQueue::<usize>::default();
-        let future = self
-            .clone()
-            .send_syn_ack_and_wait_for_ack(
-                remote,
-                remote_isn,
-                local_isn,
-                tcp_hdr,
-                recv_queue.clone(),
-                ack_queue,
-                yielder,
-            )
-            .fuse();
+        let mut me: Self = self.clone();
+        let recv_queue_clone: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)> = recv_queue.clone();
+        let future = async move {
+            me.clone()
+                .send_syn_ack_and_wait_for_ack(
+                    remote,
+                    remote_isn,
+                    local_isn,
+                    tcp_hdr,
+                    syn_data,
+                    reply_cookie,
+                    recv_queue_clone,
+                    ack_queue,
+                    yielder,
+                )
+                .await;
+            // The handshake either completed or failed, so the connection is no longer half-open.
+            me.half_open -= 1;
+        }
+        .fuse();
         match self
             .runtime
             .insert_background_coroutine("Inetstack::TCP::passiveopen::background", Box::pin(future))
@@ -234,6 +329,7 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
                 return;
             },
         };
+        self.half_open += 1;
         // TODO: Clean up the connections table once we have merged all of the routing tables into one.
         self.connections.insert(remote, recv_queue);
     }
@@ -257,7 +353,11 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
                 Some(tcp_hdr.seq_num + SeqNumber::from(tcp_hdr.compute_size() as u32)),
             )
         };
+        self.transmit_rst(remote, seq_num, ack_num);
+    }
 
+    /// Sends a RST segment to `remote` with the given sequence and acknowledgement numbers.
+    fn transmit_rst(&mut self, remote: &SocketAddrV4, seq_num: SeqNumber, ack_num: Option<SeqNumber>) {
         // Query link address for destination.
         let dst_link_addr: MacAddress = match self.arp.try_query(remote.ip().clone()) {
             Some(link_addr) => link_addr,
@@ -265,7 +365,7 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
                 // ARP query is unlikely to fail, but if it does, don't send the RST segment,
                 // and return an error to server side.
                 let cause: String = format!("missing ARP entry (remote={})", remote.ip());
-                error!("send_rst(): {}", &cause);
+                error!("transmit_rst(): {}", &cause);
                 return;
             },
         };
@@ -299,6 +399,8 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
         remote_isn: SeqNumber,
         local_isn: SeqNumber,
         tcp_hdr: TcpHeader,
+        syn_data: Option<DemiBuffer>,
+        reply_cookie: Option<FastOpenCookie>,
         recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)>,
         ack_queue: SharedAsyncQueue<usize>,
         yielder: Yielder,
@@ -322,10 +424,14 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
 
         let mut handshake_retries: usize = self.tcp_config.get_handshake_retries();
         let handshake_timeout: Duration = self.tcp_config.get_handshake_timeout();
+        let syn_data_len: usize = syn_data.as_ref().map_or(0, |data| data.len());
 
         loop {
             // Send the SYN + ACK.
-            if let Err(e) = self.send_syn_ack(local_isn, remote_isn, remote).await {
+            if let Err(e) = self
+                .send_syn_ack(local_isn, remote_isn, remote, syn_data_len, reply_cookie)
+                .await
+            {
                 self.ready.push(Err(e));
                 return;
             }
@@ -346,6 +452,7 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
                     remote,
                     local_isn,
                     remote_isn,
+                    syn_data.clone(),
                     tcp_hdr.window_size,
                     remote_window_scale,
                     mss,
@@ -360,7 +467,8 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
             select_biased! {
                 r = ack => match r {
                     // Got an ack
-                    Ok(socket) => {
+                    Ok(mut socket) => {
+                        socket.record_options(&tcp_hdr);
                         self.ready.push(Ok(socket));
                         return;
                     },
@@ -392,13 +500,15 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
         local_isn: SeqNumber,
         remote_isn: SeqNumber,
         remote: SocketAddrV4,
+        syn_data_len: usize,
+        cookie: Option<FastOpenCookie>,
     ) -> Result<(), Fail> {
         let remote_link_addr = self.arp.query(remote.ip().clone(), &Yielder::new()).await?;
         let mut tcp_hdr = TcpHeader::new(self.local.port(), remote.port());
         tcp_hdr.syn = true;
         tcp_hdr.seq_num = local_isn;
         tcp_hdr.ack = true;
-        tcp_hdr.ack_num = remote_isn + SeqNumber::from(1);
+        tcp_hdr.ack_num = remote_isn + SeqNumber::from(1 + syn_data_len as u32);
         tcp_hdr.window_size = self.tcp_config.get_receive_window_size();
 
         let mss = self.tcp_config.get_advertised_mss() as u16;
@@ -408,6 +518,11 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
         tcp_hdr.push_option(TcpOptions2::WindowScale(self.tcp_config.get_window_scale()));
         info!("Advertising window scale: {}", self.tcp_config.get_window_scale());
 
+        if let Some(cookie) = cookie {
+            tcp_hdr.push_option(TcpOptions2::FastOpen(cookie));
+            info!("Handing out fast open cookie");
+        }
+
         debug!("Sending SYN+ACK: {:?}", tcp_hdr);
         let segment = TcpSegment {
             ethernet2_hdr: Ethernet2Header::new(remote_link_addr, self.local_link_addr, EtherType2::Ipv4),
@@ -421,12 +536,13 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
     }
 
     async fn wait_for_ack(
-        self,
+        mut self,
         mut recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)>,
         ack_queue: SharedAsyncQueue<usize>,
         remote: SocketAddrV4,
         local_isn: SeqNumber,
         remote_isn: SeqNumber,
+        syn_data: Option<DemiBuffer>,
         header_window_size: u16,
         remote_window_scale: Option<u8>,
         mss: usize,
@@ -461,12 +577,34 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
             local_window_scale, remote_window_scale
         );
 
+        // If there was data with the SYN, deliver it first, as a segment that immediately follows the SYN.
+        let used_fast_open: bool = syn_data.is_some();
+        if let Some(data) = syn_data {
+            let mut syn_data_hdr: TcpHeader = TcpHeader::new(remote.port(), self.local.port());
+            syn_data_hdr.seq_num = remote_isn + SeqNumber::from(1);
+            syn_data_hdr.ack = true;
+            syn_data_hdr.ack_num = local_isn + SeqNumber::from(1);
+            syn_data_hdr.window_size = tcp_hdr.window_size;
+            recv_queue.push((ipv4_hdr, syn_data_hdr, data));
+        }
+
+        // Keep what it takes to reset the connection, in case it cannot be established.
+        let mut ack_hdr: TcpHeader = TcpHeader::new(remote.port(), self.local.port());
+        ack_hdr.ack = true;
+        ack_hdr.ack_num = tcp_hdr.ack_num;
+
         // If there is data with the SYN+ACK, deliver it.
         if !buf.is_empty() {
             recv_queue.push((ipv4_hdr, tcp_hdr, buf));
         }
 
-        let new_socket: EstablishedSocket<N> = EstablishedSocket::<N>::new(
+        let cc_constructor: congestion_control::CongestionControlConstructor =
+            if self.tcp_config.get_congestion_control() {
+                congestion_control::Cubic::new
+            } else {
+                congestion_control::None::new
+            };
+        let mut new_socket: EstablishedSocket<N> = match EstablishedSocket::<N>::new(
             self.local,
             remote,
             self.runtime.clone(),
@@ -484,15 +622,47 @@ impl<N: NetworkRuntime> SharedPassiveSocket<N> {
             remote_window_size,
             remote_window_scale,
             mss,
-            congestion_control::None::new,
+            cc_constructor,
             None,
             self.dead_socket_tx.clone(),
-        )?;
+        ) {
+            Ok(socket) => socket,
+            // The scheduler could not take the background coroutine of the connection, e.g. because it is at its task
+            // limit. The connection cannot run without it, so reset it rather than leave the remote thinking that it
+            // is established, and stop demultiplexing its segments so that the remote may connect again later.
+            Err(e) => {
+                warn!(
+                    "wait_for_ack(): cannot establish connection (remote={:?}): {:?}",
+                    remote, e
+                );
+                self.send_rst(&remote, ack_hdr);
+                self.forget_connection(&remote);
+                return Err(e);
+            },
+        };
+        new_socket.set_local_isn(local_isn);
+        if used_fast_open {
+            new_socket.set_used_fast_open();
+        }
 
         Ok(new_socket)
     }
 }
 
+impl<N: NetworkRuntime> PassiveSocket<N> {
+    /// Stops the coroutine that demultiplexes incoming segments. The coroutine holds a reference to the socket, so it
+    /// must be stopped for the socket to ever be dropped.
+    pub fn cancel_background_task(&mut self) {
+        if let Some(qt) = self.background_task_qt.take() {
+            self.yielder_handle
+                .wake_with(Err(Fail::new(libc::ECANCELED, "Socket is closing")));
+            if let Err(e) = self.runtime.remove_background_coroutine(qt) {
+                warn!("Could not remove background coroutine: {:?}", e);
+            }
+        }
+    }
+}
+
 //======================================================================================================================
 // Trait Implementations
 //======================================================================================================================
@@ -513,12 +683,6 @@ impl<N: NetworkRuntime> DerefMut for SharedPassiveSocket<N> {
 
 impl<N: NetworkRuntime> Drop for PassiveSocket<N> {
     fn drop(&mut self) {
-        if let Some(qt) = self.background_task_qt.take() {
-            self.yielder_handle
-                .wake_with(Err(Fail::new(libc::ECANCELED, "Socket is closing")));
-            if let Err(e) = self.runtime.remove_background_coroutine(qt) {
-                warn!("Could not remove background coroutine: {:?}", e);
-            }
-        }
+        self.cancel_background_task();
     }
 }
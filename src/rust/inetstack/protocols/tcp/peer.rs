@@ -8,10 +8,26 @@
 use crate::{
     inetstack::protocols::{
         arp::SharedArpPeer,
+        ethernet2::{
+            EtherType2,
+            Ethernet2Header,
+        },
+        ip::IpProtocol,
         ipv4::Ipv4Header,
         tcp::{
-            isn_generator::IsnGenerator,
-            segment::TcpHeader,
+            fast_open::{
+                SharedFastOpenState,
+                FAST_OPEN_KEY_SIZE,
+            },
+            isn_generator::{
+                ConnTuple,
+                IsnFn,
+                IsnGenerator,
+            },
+            segment::{
+                TcpHeader,
+                TcpSegment,
+            },
             socket::SharedTcpSocket,
             SeqNumber,
         },
@@ -21,25 +37,43 @@ use crate::{
         memory::DemiBuffer,
         network::{
             config::TcpConfig,
+            consts::MAX_DELAYED_ACK_TIMEOUT,
             socket::SocketId,
-            types::MacAddress,
+            types::{
+                ConnectionSnapshot,
+                DropReason,
+                DropStats,
+                MacAddress,
+            },
             NetworkRuntime,
         },
         scheduler::Yielder,
         QDesc,
+        QToken,
         SharedDemiRuntime,
         SharedObject,
     },
 };
-use ::futures::channel::mpsc;
+use ::futures::{
+    channel::mpsc,
+    FutureExt,
+};
 use ::rand::{
     prelude::SmallRng,
+    rngs::OsRng,
     Rng,
     SeedableRng,
 };
 
 use ::std::{
-    collections::HashMap,
+    collections::{
+        hash_map::DefaultHasher,
+        HashMap,
+    },
+    hash::{
+        Hash,
+        Hasher,
+    },
     net::{
         Ipv4Addr,
         SocketAddr,
@@ -49,23 +83,62 @@ use ::std::{
         Deref,
         DerefMut,
     },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Default maximum segment lifetime, as suggested by RFC 793.
+const DEFAULT_MAX_SEGMENT_LIFETIME: Duration = Duration::from_secs(60);
+
 //======================================================================================================================
 // Structures
 //======================================================================================================================
 
+/// Link and IPv4 addresses of the local host.
+#[derive(Clone, Copy, Debug)]
+pub struct LocalAddrs {
+    pub link_addr: MacAddress,
+    pub ipv4_addr: Ipv4Addr,
+}
+
 pub struct TcpPeer<N: NetworkRuntime> {
     runtime: SharedDemiRuntime,
     isn_generator: IsnGenerator,
+    /// Generator of initial sequence numbers that replaces the default one, if any.
+    custom_isn_generator: SharedObject<Option<IsnFn>>,
     transport: N,
     local_link_addr: MacAddress,
     local_ipv4_addr: Ipv4Addr,
     tcp_config: TcpConfig,
+    /// Verify checksums of incoming segments in software?
+    verify_checksums: bool,
+    /// Shrink the segment size of connections when routers report that their segments do not fit the path?
+    path_mtu_discovery: bool,
     arp: SharedArpPeer<N>,
     rng: SmallRng,
     dead_socket_tx: mpsc::UnboundedSender<QDesc>,
     addresses: HashMap<SocketId, SharedTcpSocket<N>>,
+    /// Listening sockets that share each local address through SO_REUSEPORT, in the order in which they started to
+    /// listen. The first of them also stands for the address in `addresses`, unless a socket that is merely bound does.
+    reuse_port: HashMap<SocketAddrV4, Vec<SharedTcpSocket<N>>>,
+    /// Maximum segment lifetime. Actively closed connections stay in TIME_WAIT for twice this long. Defaults to
+    /// [DEFAULT_MAX_SEGMENT_LIFETIME]. None disables TIME_WAIT, so that addresses may be reused as soon as their
+    /// connections close.
+    max_segment_lifetime: Option<Duration>,
+    /// Local addresses of connections in TIME_WAIT, with the time at which they may be reused.
+    time_wait: HashMap<SocketAddrV4, Instant>,
+    /// Background coroutine that removes the addresses that left TIME_WAIT, while TIME_WAIT is enabled.
+    time_wait_pruner: Option<QToken>,
+    /// Incoming packets dropped by the stack.
+    drop_stats: SharedObject<DropStats>,
+    /// Cookies handed out to and received from other peers for TCP Fast Open.
+    fast_open: SharedFastOpenState,
 }
 
 #[derive(Clone)]
@@ -79,27 +152,42 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
     pub fn new(
         runtime: SharedDemiRuntime,
         transport: N,
-        local_link_addr: MacAddress,
-        local_ipv4_addr: Ipv4Addr,
+        local: LocalAddrs,
         tcp_config: TcpConfig,
         arp: SharedArpPeer<N>,
         rng_seed: [u8; 32],
+        drop_stats: SharedObject<DropStats>,
     ) -> Result<Self, Fail> {
         let mut rng: SmallRng = SmallRng::from_seed(rng_seed);
         let nonce: u32 = rng.gen();
+        // The key of Fast Open cookies must not be predictable from the seed, which is often fixed.
+        let fast_open_key: [u8; FAST_OPEN_KEY_SIZE] = OsRng.gen();
         let (tx, _) = mpsc::unbounded();
-        Ok(Self(SharedObject::<TcpPeer<N>>::new(TcpPeer {
-            isn_generator: IsnGenerator::new(nonce),
+        let custom_isn_generator: SharedObject<Option<IsnFn>> = SharedObject::new(None);
+        let mut peer: Self = Self(SharedObject::<TcpPeer<N>>::new(TcpPeer {
+            isn_generator: IsnGenerator::new(nonce, custom_isn_generator.clone()),
+            custom_isn_generator,
             runtime,
             transport,
-            local_link_addr,
-            local_ipv4_addr,
+            local_link_addr: local.link_addr,
+            local_ipv4_addr: local.ipv4_addr,
             tcp_config,
+            verify_checksums: true,
+            path_mtu_discovery: true,
             arp,
             rng,
             dead_socket_tx: tx,
             addresses: HashMap::<SocketId, SharedTcpSocket<N>>::new(),
-        })))
+            reuse_port: HashMap::<SocketAddrV4, Vec<SharedTcpSocket<N>>>::new(),
+            max_segment_lifetime: None,
+            time_wait: HashMap::<SocketAddrV4, Instant>::new(),
+            time_wait_pruner: None,
+            drop_stats,
+            fast_open: SharedFastOpenState::new(fast_open_key),
+        }));
+        // Start pruning TIME_WAIT in the background.
+        peer.set_max_segment_lifetime(Some(DEFAULT_MAX_SEGMENT_LIFETIME))?;
+        Ok(peer)
     }
 
     /// Creates a TCP socket.
@@ -111,6 +199,8 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
             self.tcp_config.clone(),
             self.arp.clone(),
             self.dead_socket_tx.clone(),
+            self.drop_stats.clone(),
+            self.fast_open.clone(),
         ))
     }
 
@@ -119,11 +209,28 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         // All other checks should have been done already.
         debug_assert!(!Ipv4Addr::is_unspecified(local.ip()));
         debug_assert!(local.port() != 0);
-        debug_assert!(self.addresses.get(&SocketId::Passive(local)).is_none());
+
+        // Check if another socket is bound to this address. Sockets that all allow it may share the address.
+        if let Some(bound) = self.addresses.get(&SocketId::Passive(local)) {
+            if !(bound.reuse_port() && socket.reuse_port()) {
+                let cause: String = format!("address is already bound to a socket (local={:?})", local);
+                error!("bind(): {}", &cause);
+                return Err(Fail::new(libc::EADDRINUSE, &cause));
+            }
+        }
+
+        // Check if a connection on this address is still in TIME_WAIT.
+        if self.in_time_wait(&local) {
+            let cause: String = format!("address is in TIME_WAIT (local={:?})", local);
+            error!("bind(): {}", &cause);
+            return Err(Fail::new(libc::EADDRINUSE, &cause));
+        }
 
         // Issue operation.
         socket.bind(local)?;
-        self.addresses.insert(SocketId::Passive(local), socket.clone());
+        self.addresses
+            .entry(SocketId::Passive(local))
+            .or_insert_with(|| socket.clone());
         Ok(())
     }
 
@@ -132,7 +239,19 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         // Most checks should have been performed already
         debug_assert!(socket.local().is_some());
         let nonce: u32 = self.rng.gen();
-        socket.listen(backlog, nonce)
+        socket.listen(backlog, IsnGenerator::new(nonce, self.custom_isn_generator.clone()))?;
+
+        // Sockets that share their address only take connections once they listen, so that none are sent to a socket
+        // that is merely bound.
+        if socket.reuse_port() {
+            if let Some(local) = socket.local() {
+                self.reuse_port.entry(local).or_default().push(socket.clone());
+                self.addresses
+                    .entry(SocketId::Passive(local))
+                    .or_insert_with(|| socket.clone());
+            }
+        }
+        Ok(())
     }
 
     /// Runs until a new connection is accepted.
@@ -141,18 +260,21 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         Ok(socket.accept(yielder).await?)
     }
 
-    /// Runs until the connect to remote is made or times out.
+    /// Runs until the connect to remote is made or times out. The connect is made when the connection reaches
+    /// ESTABLISHED, after we acknowledged the SYN+ACK of the remote. If there is `initial` data, it is sent in the SYN
+    /// when we hold a TCP Fast Open cookie from the remote, and right after the handshake otherwise.
     pub async fn connect(
         &mut self,
         socket: &mut SharedTcpSocket<N>,
         remote: SocketAddrV4,
+        initial: Option<DemiBuffer>,
         yielder: Yielder,
     ) -> Result<(), Fail> {
         // Check whether we need to allocate an ephemeral port.
         let local: SocketAddrV4 = match socket.local() {
             Some(addr) => addr,
             None => {
-                let local_port: u16 = self.runtime.alloc_ephemeral_port()?;
+                let local_port: u16 = self.alloc_ephemeral_port()?;
                 SocketAddrV4::new(self.local_ipv4_addr, local_port)
             },
         };
@@ -172,7 +294,7 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         }
         let local_isn: SeqNumber = self.isn_generator.generate(&local, &remote);
         // Wait for connect to complete.
-        if let Err(e) = socket.connect(local, remote, local_isn, yielder).await {
+        if let Err(e) = socket.connect(local, remote, local_isn, initial, yielder).await {
             self.addresses.remove(&SocketId::Active(local, remote.clone()));
             Err(e)
         } else {
@@ -180,6 +302,34 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         }
     }
 
+    /// Restores the connection that `snapshot` was taken of on `socket` and demultiplexes its incoming segments to it.
+    /// The local address of the connection must be ours.
+    pub fn resume_from(&mut self, socket: &mut SharedTcpSocket<N>, snapshot: &ConnectionSnapshot) -> Result<(), Fail> {
+        let (local, remote): (SocketAddrV4, SocketAddrV4) = (snapshot.local, snapshot.remote);
+        if *local.ip() != self.local_ipv4_addr {
+            let cause: String = format!("local address of snapshot is not ours (local={:?})", local);
+            error!("resume_from(): {}", &cause);
+            return Err(Fail::new(libc::EADDRNOTAVAIL, &cause));
+        }
+        let socket_id: SocketId = SocketId::Active(local, remote);
+        if self.addresses.contains_key(&socket_id) {
+            let cause: String = format!("connection already exists (local={:?}, remote={:?})", local, remote);
+            error!("resume_from(): {}", &cause);
+            return Err(Fail::new(libc::EADDRINUSE, &cause));
+        }
+
+        // Reserve the local port, if it is an ephemeral one, as it is freed when the connection closes.
+        if SharedDemiRuntime::is_private_ephemeral_port(local.port()) {
+            self.runtime.reserve_ephemeral_port(local.port())?;
+        }
+        if let Err(e) = socket.resume_from(snapshot) {
+            self.free_ephemeral_port(&socket_id);
+            return Err(e);
+        }
+        self.addresses.insert(socket_id, socket.clone());
+        Ok(())
+    }
+
     /// Pushes immediately to the socket and returns the result asynchronously.
     pub async fn push(
         &self,
@@ -211,6 +361,42 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         Ok(None)
     }
 
+    /// Sets up a coroutine for popping all buffered data from the socket.
+    pub async fn pop_all(
+        &self,
+        socket: &mut SharedTcpSocket<N>,
+        yielder: Yielder,
+    ) -> Result<(Option<SocketAddr>, DemiBuffer), Fail> {
+        let incoming: DemiBuffer = socket.pop_all(yielder).await?;
+        Ok((None, incoming))
+    }
+
+    /// Allocates an ephemeral port whose address is not held by a connection in TIME_WAIT.
+    fn alloc_ephemeral_port(&mut self) -> Result<u16, Fail> {
+        let mut held: Vec<u16> = Vec::new();
+        let result: Result<u16, Fail> = loop {
+            match self.runtime.alloc_ephemeral_port() {
+                Ok(port) if self.in_time_wait(&SocketAddrV4::new(self.local_ipv4_addr, port)) => held.push(port),
+                result => break result,
+            }
+        };
+        // Give back the ports in TIME_WAIT, so that they can be allocated once they leave it.
+        for port in held {
+            if self.runtime.free_ephemeral_port(port).is_err() {
+                warn!("connect(): leaking ephemeral port (port={})", port);
+            }
+        }
+        result
+    }
+
+    /// Checks whether a connection on the local address [local] is still in TIME_WAIT.
+    fn in_time_wait(&self, local: &SocketAddrV4) -> bool {
+        match self.time_wait.get(local) {
+            Some(expiry) => *expiry > self.runtime.get_now(),
+            None => false,
+        }
+    }
+
     /// Frees an ephemeral port (if any) allocated to a given socket.
     fn free_ephemeral_port(&mut self, socket_id: &SocketId) {
         let local: &SocketAddrV4 = match socket_id {
@@ -233,7 +419,13 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         // Wait for close to complete.
         // Handle result: If unsuccessful, free the new queue descriptor.
         if let Some(socket_id) = socket.close(yielder).await? {
-            self.addresses.remove(&socket_id);
+            if let (SocketId::Active(local, _), Some(max_segment_lifetime)) = (&socket_id, self.max_segment_lifetime) {
+                if socket.in_time_wait() {
+                    let expiry: Instant = self.runtime.get_now() + 2 * max_segment_lifetime;
+                    self.time_wait.insert(*local, expiry);
+                }
+            }
+            self.remove_address(&socket_id, socket);
             self.free_ephemeral_port(&socket_id);
         }
         Ok(())
@@ -241,23 +433,249 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
 
     pub fn hard_close(&mut self, socket: &mut SharedTcpSocket<N>) -> Result<(), Fail> {
         if let Some(socket_id) = socket.hard_close()? {
-            self.addresses.remove(&socket_id);
+            self.remove_address(&socket_id, socket);
             self.free_ephemeral_port(&socket_id);
         }
         Ok(())
     }
 
+    /// Changes the local link address given to new sockets.
+    pub fn set_local_link_addr(&mut self, local_link_addr: MacAddress) {
+        self.local_link_addr = local_link_addr;
+    }
+
+    /// Enables or disables software verification of checksums of incoming segments.
+    pub fn set_verify_checksums(&mut self, verify_checksums: bool) {
+        self.verify_checksums = verify_checksums;
+    }
+
+    /// Sets the maximum segment lifetime, which determines how long actively closed connections stay in TIME_WAIT.
+    /// None disables TIME_WAIT and releases the addresses that are in it.
+    pub fn set_max_segment_lifetime(&mut self, max_segment_lifetime: Option<Duration>) -> Result<(), Fail> {
+        self.max_segment_lifetime = max_segment_lifetime;
+        match (max_segment_lifetime, self.time_wait_pruner) {
+            (Some(_), None) => {
+                let coroutine = self.clone().prune_time_wait();
+                let qt: QToken = self
+                    .runtime
+                    .insert_background_coroutine("Inetstack::TCP::time_wait", Box::pin(coroutine.fuse()))?;
+                self.time_wait_pruner = Some(qt);
+            },
+            (None, Some(qt)) => {
+                self.runtime.remove_background_coroutine(qt)?;
+                self.time_wait_pruner = None;
+                self.time_wait.clear();
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    /// Removes the addresses that left TIME_WAIT, once every maximum segment lifetime.
+    async fn prune_time_wait(mut self) {
+        while let Some(max_segment_lifetime) = self.max_segment_lifetime {
+            let yielder: Yielder = Yielder::new();
+            if let Err(e) = self.runtime.get_timer().wait(max_segment_lifetime, &yielder).await {
+                warn!("prune_time_wait(): stopping (error={:?})", e);
+                break;
+            }
+            let now: Instant = self.runtime.get_now();
+            self.time_wait.retain(|_, expiry| *expiry > now);
+        }
+    }
+
+    /// Moves an established connection to a new local address, without resetting it. The peer keeps sending to the old
+    /// address, so it must learn about the new one by other means.
+    pub fn migrate_local(&mut self, socket: &mut SharedTcpSocket<N>, new_local: SocketAddrV4) -> Result<(), Fail> {
+        let (_, remote): (SocketAddrV4, SocketAddrV4) = socket.endpoints()?;
+        self.migrate(socket, new_local, remote)
+    }
+
+    /// Stops demultiplexing incoming segments for `socket_id` to `socket`. If other sockets share the address of `socket`
+    /// through SO_REUSEPORT, they keep it.
+    fn remove_address(&mut self, socket_id: &SocketId, socket: &SharedTcpSocket<N>) {
+        if let SocketId::Active(local, remote) = socket_id {
+            if self.addresses.remove(socket_id).is_none() {
+                self.forget_accepted_connection(local, remote);
+            }
+            return;
+        }
+        if let SocketId::Passive(local) = socket_id {
+            if let Some(group) = self.reuse_port.get_mut(local) {
+                group.retain(|member| member != socket);
+                match group.first().cloned() {
+                    Some(next) => {
+                        if self.addresses.get(socket_id) == Some(socket) {
+                            self.addresses.insert(SocketId::Passive(*local), next);
+                        }
+                        return;
+                    },
+                    None => {
+                        self.reuse_port.remove(local);
+                    },
+                }
+            }
+        }
+        // Another socket that shares the address may stand for it.
+        if self.addresses.get(socket_id) == Some(socket) {
+            self.addresses.remove(socket_id);
+        }
+    }
+
+    /// Moves an established connection to new endpoints and demultiplexes incoming segments accordingly.
+    fn migrate(
+        &mut self,
+        socket: &mut SharedTcpSocket<N>,
+        local: SocketAddrV4,
+        remote: SocketAddrV4,
+    ) -> Result<(), Fail> {
+        let (old_local, old_remote): (SocketAddrV4, SocketAddrV4) = socket.endpoints()?;
+        if (old_local, old_remote) == (local, remote) {
+            return Ok(());
+        }
+        if self.addresses.contains_key(&SocketId::Active(local, remote)) {
+            let cause: String = format!("connection already exists (local={:?}, remote={:?})", local, remote);
+            error!("migrate(): {}", &cause);
+            return Err(Fail::new(libc::EADDRINUSE, &cause));
+        }
+
+        socket.migrate(local, remote)?;
+
+        // Reserve the new local port, if it is an ephemeral one.
+        let port_changed: bool = local.port() != old_local.port();
+        if port_changed && SharedDemiRuntime::is_private_ephemeral_port(local.port()) {
+            if let Err(e) = self.runtime.reserve_ephemeral_port(local.port()) {
+                socket.migrate(old_local, old_remote)?;
+                return Err(e);
+            }
+        }
+
+        let old_socket_id: SocketId = SocketId::Active(old_local, old_remote);
+        if self.addresses.remove(&old_socket_id).is_some() {
+            if port_changed {
+                self.free_ephemeral_port(&old_socket_id);
+            }
+        } else {
+            self.forget_accepted_connection(&old_local, &old_remote);
+        }
+        self.addresses.insert(SocketId::Active(local, remote), socket.clone());
+        Ok(())
+    }
+
+    /// Stops demultiplexing incoming segments from `remote` to the connection that a socket listening on `local`
+    /// accepted from it. Actively opened connections are demultiplexed by the peer, but accepted ones by their listening
+    /// socket, which would otherwise keep taking segments for the connection after it went away.
+    fn forget_accepted_connection(&mut self, local: &SocketAddrV4, remote: &SocketAddrV4) {
+        if let Some(group) = self.reuse_port.get_mut(local) {
+            for listener in group.iter_mut() {
+                if listener.forget_connection(remote) {
+                    break;
+                }
+            }
+        } else if let Some(listener) = self.addresses.get_mut(&SocketId::Passive(*local)) {
+            listener.forget_connection(remote);
+        }
+    }
+
+    /// Sets how long receivers wait before sending a standalone ACK. Only affects sockets created afterwards.
+    pub fn set_delayed_ack_timeout(&mut self, delayed_ack_timeout: Duration) -> Result<(), Fail> {
+        if delayed_ack_timeout > MAX_DELAYED_ACK_TIMEOUT {
+            let cause: String = format!("delayed ack timeout is too long (timeout={:?})", delayed_ack_timeout);
+            error!("set_delayed_ack_timeout(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, &cause));
+        }
+        self.tcp_config = self.tcp_config.clone().set_ack_delay_timeout(delayed_ack_timeout);
+        Ok(())
+    }
+
+    /// Sets whether connections record the TCP options they receive. Only affects sockets created afterwards.
+    pub fn set_record_options(&mut self, record_options: bool) {
+        self.tcp_config = self.tcp_config.clone().set_record_options(record_options);
+    }
+
+    /// Sets whether connections merge adjacent in-order segments before delivering them. Only affects sockets created
+    /// afterwards.
+    pub fn set_receive_coalescing(&mut self, receive_coalescing: bool) {
+        self.tcp_config = self.tcp_config.clone().set_receive_coalescing(receive_coalescing);
+    }
+
+    /// Sets whether listening sockets hand out TCP Fast Open cookies and accept data in the SYNs of clients that present
+    /// one. Only affects sockets created afterwards.
+    pub fn set_fast_open(&mut self, fast_open: bool) {
+        self.tcp_config = self.tcp_config.clone().set_fast_open(fast_open);
+    }
+
+    /// Sets how many connections each listening socket keeps in the SYN-RECEIVED state, or removes the limit (if
+    /// `None`). Only affects sockets that start listening afterwards.
+    pub fn set_max_half_open(&mut self, max_half_open: Option<usize>) {
+        self.tcp_config = self.tcp_config.clone().set_max_half_open(max_half_open);
+    }
+
+    /// Sets whether connections run the CUBIC congestion control algorithm. Only affects sockets connected afterwards.
+    pub fn set_congestion_control(&mut self, congestion_control: bool) {
+        self.tcp_config = self.tcp_config.clone().set_congestion_control(congestion_control);
+    }
+
+    /// Sets whether connections pace their segments at their measured delivery rate. Only affects sockets connected
+    /// afterwards.
+    pub fn set_pacing(&mut self, pacing: bool) {
+        self.tcp_config = self.tcp_config.clone().set_pacing(pacing);
+    }
+
+    /// Makes connections take their initial sequence numbers from `generator` rather than from the default generator,
+    /// which randomizes them. Affects connections opened afterwards, including those on sockets already listening.
+    pub fn set_isn_generator(&mut self, generator: Box<dyn Fn(&ConnTuple) -> u32>) {
+        *self.custom_isn_generator = Some(generator);
+    }
+
+    /// Sets whether connections shrink their segments when routers report that they do not fit the path.
+    pub fn set_path_mtu_discovery(&mut self, path_mtu_discovery: bool) {
+        self.path_mtu_discovery = path_mtu_discovery;
+    }
+
+    /// Processes an ICMP "fragmentation needed" message that a router sent for the segment starting at `seq_num` from
+    /// `local` to `remote`.
+    pub fn receive_fragmentation_needed(
+        &mut self,
+        local: SocketAddrV4,
+        remote: SocketAddrV4,
+        next_hop_mtu: u16,
+        seq_num: SeqNumber,
+    ) {
+        if !self.path_mtu_discovery {
+            return;
+        }
+        match self.addresses.get_mut(&SocketId::Active(local, remote)) {
+            Some(socket) => socket.update_path_mtu(next_hop_mtu, seq_num),
+            None => warn!(
+                "receive_fragmentation_needed(): no connection for flow (local={:?}, remote={:?})",
+                local, remote
+            ),
+        }
+    }
+
+    /// Sets whether segments that no connection can take, such as data that arrives after the connection was closed,
+    /// are answered with a RST rather than dropped silently. Connections in LAST_ACK only reset such segments if they
+    /// were connected afterwards.
+    pub fn set_rst_on_invalid_segment(&mut self, rst_on_invalid_segment: bool) {
+        self.tcp_config = self
+            .tcp_config
+            .clone()
+            .set_rst_on_invalid_segment(rst_on_invalid_segment);
+    }
+
     /// Processes an incoming TCP segment.
     pub fn receive(&mut self, ip_hdr: Ipv4Header, buf: DemiBuffer) {
-        let (tcp_hdr, data): (TcpHeader, DemiBuffer) =
-            match TcpHeader::parse(&ip_hdr, buf, self.tcp_config.get_rx_checksum_offload()) {
-                Ok(result) => result,
-                Err(e) => {
-                    let cause: String = format!("invalid tcp header: {:?}", e);
-                    error!("receive(): {}", &cause);
-                    return;
-                },
-            };
+        let rx_checksum_offload: bool = self.tcp_config.get_rx_checksum_offload() || !self.verify_checksums;
+        let (tcp_hdr, data): (TcpHeader, DemiBuffer) = match TcpHeader::parse(&ip_hdr, buf, rx_checksum_offload) {
+            Ok(result) => result,
+            Err(e) => {
+                let cause: String = format!("invalid tcp header: {:?}", e);
+                error!("receive(): {}", &cause);
+                self.drop_stats.record(DropReason::from_parse_error(&e));
+                return;
+            },
+        };
         debug!("TCP received {:?}", tcp_hdr);
         let local: SocketAddrV4 = SocketAddrV4::new(ip_hdr.get_dest_addr(), tcp_hdr.dst_port);
         let remote: SocketAddrV4 = SocketAddrV4::new(ip_hdr.get_src_addr(), tcp_hdr.src_port);
@@ -265,18 +683,34 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         if remote.ip().is_broadcast() || remote.ip().is_multicast() || remote.ip().is_unspecified() {
             let cause: String = format!("invalid remote address (remote={})", remote.ip());
             error!("receive(): {}", &cause);
+            self.drop_stats.record(DropReason::MalformedHeader);
             return;
         }
 
-        // Retrieve the queue descriptor based on the incoming segment.
+        // Retrieve the queue descriptor based on the incoming segment. Listening sockets that share their address split
+        // the connections by a hash of the four-tuple, so all segments of a connection reach the same listener.
         let socket: &mut SharedTcpSocket<N> = match self.addresses.get_mut(&SocketId::Active(local, remote)) {
             Some(socket) => socket,
-            None => match self.addresses.get_mut(&SocketId::Passive(local)) {
-                Some(socket) => socket,
-                None => {
-                    let cause: String = format!("no queue descriptor for remote address (remote={})", remote.ip());
-                    error!("receive(): {}", &cause);
-                    return;
+            None => match self.reuse_port.get_mut(&local) {
+                Some(group) => {
+                    let index: usize = reuse_port_index(&local, &remote, group.len());
+                    &mut group[index]
+                },
+                None => match self.addresses.get_mut(&SocketId::Passive(local)) {
+                    Some(socket) => socket,
+                    None => {
+                        let cause: String = format!("no queue descriptor for remote address (remote={})", remote.ip());
+                        error!("receive(): {}", &cause);
+                        self.drop_stats.record(DropReason::NoSocket);
+                        // Refuse connection requests right away, so that the remote does not wait for its handshake to
+                        // time out.
+                        if tcp_hdr.syn && !tcp_hdr.ack && !tcp_hdr.rst {
+                            self.refuse_connection(&local, &remote, &tcp_hdr, data.len());
+                        } else if self.tcp_config.get_rst_on_invalid_segment() && !tcp_hdr.rst {
+                            self.reset_segment(&local, &remote, &tcp_hdr, data.len());
+                        }
+                        return;
+                    },
                 },
             },
         };
@@ -284,6 +718,67 @@ impl<N: NetworkRuntime> SharedTcpPeer<N> {
         // Dispatch to further processing depending on the socket state.
         socket.receive(ip_hdr, tcp_hdr, data)
     }
+
+    /// Sends a RST in reply to a SYN from `remote` to `local`, where nobody listens. The RST acknowledges the SYN, as
+    /// described for the CLOSED state in https://datatracker.ietf.org/doc/html/rfc793#section-3.4.
+    fn refuse_connection(&mut self, local: &SocketAddrV4, remote: &SocketAddrV4, syn_hdr: &TcpHeader, data_len: usize) {
+        debug!("refuse_connection(): sending RST to {:?}", remote);
+        // The SYN takes up one sequence number, in addition to any data that it carries.
+        let ack_num: SeqNumber = syn_hdr.seq_num + SeqNumber::from(1 + data_len as u32);
+        self.transmit_rst(local, remote, SeqNumber::from(0), Some(ack_num));
+    }
+
+    /// Sends a RST in reply to a segment from `remote` to `local` that reaches no connection, as described for the CLOSED
+    /// state in https://datatracker.ietf.org/doc/html/rfc793#section-3.4.
+    fn reset_segment(&mut self, local: &SocketAddrV4, remote: &SocketAddrV4, hdr: &TcpHeader, data_len: usize) {
+        debug!("reset_segment(): sending RST to {:?}", remote);
+        if hdr.ack {
+            self.transmit_rst(local, remote, hdr.ack_num, None);
+        } else {
+            // SYN and FIN take up one sequence number each.
+            let seg_len: u32 = data_len as u32 + hdr.syn as u32 + hdr.fin as u32;
+            self.transmit_rst(
+                local,
+                remote,
+                SeqNumber::from(0),
+                Some(hdr.seq_num + SeqNumber::from(seg_len)),
+            );
+        }
+    }
+
+    /// Sends a RST with sequence number `seq_num` from `local` to `remote`, which acknowledges `ack_num` if any.
+    fn transmit_rst(
+        &mut self,
+        local: &SocketAddrV4,
+        remote: &SocketAddrV4,
+        seq_num: SeqNumber,
+        ack_num: Option<SeqNumber>,
+    ) {
+        let dst_link_addr: MacAddress = match self.arp.try_query(*remote.ip()) {
+            Some(link_addr) => link_addr,
+            None => {
+                let cause: String = format!("missing ARP entry (remote={})", remote.ip());
+                warn!("transmit_rst(): {}", &cause);
+                return;
+            },
+        };
+
+        let mut tcp_hdr: TcpHeader = TcpHeader::new(local.port(), remote.port());
+        tcp_hdr.rst = true;
+        tcp_hdr.seq_num = seq_num;
+        if let Some(ack_num) = ack_num {
+            tcp_hdr.ack = true;
+            tcp_hdr.ack_num = ack_num;
+        }
+        let segment: TcpSegment = TcpSegment {
+            ethernet2_hdr: Ethernet2Header::new(dst_link_addr, self.local_link_addr, EtherType2::Ipv4),
+            ipv4_hdr: Ipv4Header::new(*local.ip(), *remote.ip(), IpProtocol::TCP),
+            tcp_hdr,
+            data: None,
+            tx_checksum_offload: self.tcp_config.get_tx_checksum_offload(),
+        };
+        self.transport.transmit(Box::new(segment));
+    }
 }
 
 //======================================================================================================================
@@ -303,3 +798,14 @@ impl<N: NetworkRuntime> DerefMut for SharedTcpPeer<N> {
         self.0.deref_mut()
     }
 }
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Picks which of `num_listeners` listening sockets that share `local` gets the connection from `remote`.
+fn reuse_port_index(local: &SocketAddrV4, remote: &SocketAddrV4, num_listeners: usize) -> usize {
+    let mut hasher: DefaultHasher = DefaultHasher::new();
+    (local, remote).hash(&mut hasher);
+    (hasher.finish() % num_listeners as u64) as usize
+}
@@ -11,10 +11,16 @@ use crate::{
     runtime::{
         fail::Fail,
         memory::DemiBuffer,
-        network::PacketBuf,
+        network::{
+            types::TcpOption,
+            PacketBuf,
+        },
     },
 };
-use ::libc::EBADMSG;
+use ::libc::{
+    EBADMSG,
+    EILSEQ,
+};
 use ::std::{
     convert::TryInto,
     io::{
@@ -28,6 +34,12 @@ pub const MIN_TCP_HEADER_SIZE: usize = 20;
 pub const MAX_TCP_HEADER_SIZE: usize = 60;
 pub const MAX_TCP_OPTIONS: usize = 5;
 
+/// Largest TCP Fast Open cookie (RFC 7413).
+pub const MAX_FAST_OPEN_COOKIE_SIZE: usize = 16;
+
+/// Smallest TCP Fast Open cookie (RFC 7413). Shorter cookies are only allowed in the empty form, which requests one.
+pub const MIN_FAST_OPEN_COOKIE_SIZE: usize = 4;
+
 pub struct TcpSegment {
     pub ethernet2_hdr: Ethernet2Header,
     pub ipv4_hdr: Ipv4Header,
@@ -89,6 +101,42 @@ pub struct SelectiveAcknowlegement {
     pub end: SeqNumber,
 }
 
+/// TCP Fast Open cookie (RFC 7413). An empty cookie requests one from the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FastOpenCookie {
+    len: usize,
+    bytes: [u8; MAX_FAST_OPEN_COOKIE_SIZE],
+}
+
+impl FastOpenCookie {
+    /// Creates a cookie with the contents of `bytes`, which must not be longer than [MAX_FAST_OPEN_COOKIE_SIZE].
+    pub fn new(bytes: &[u8]) -> Self {
+        assert!(bytes.len() <= MAX_FAST_OPEN_COOKIE_SIZE);
+        let mut cookie: Self = Self::request();
+        cookie.bytes[..bytes.len()].copy_from_slice(bytes);
+        cookie.len = bytes.len();
+        cookie
+    }
+
+    /// Creates an empty cookie, which asks the server for one.
+    pub fn request() -> Self {
+        Self {
+            len: 0,
+            bytes: [0; MAX_FAST_OPEN_COOKIE_SIZE],
+        }
+    }
+
+    /// Is this a request for a cookie, rather than a cookie?
+    pub fn is_request(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bytes of the cookie.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TcpOptions2 {
     EndOfOptionsList,
@@ -104,9 +152,31 @@ pub enum TcpOptions2 {
         sender_timestamp: u32,
         echo_timestamp: u32,
     },
+    FastOpen(FastOpenCookie),
 }
 
 impl TcpOptions2 {
+    /// Converts this option into one that connections record, if it describes the remote end. Padding and
+    /// per-segment acknowledgement blocks are not recorded.
+    pub fn to_observed(&self) -> Option<TcpOption> {
+        match *self {
+            TcpOptions2::MaximumSegmentSize(mss) => Some(TcpOption::MaximumSegmentSize(mss)),
+            TcpOptions2::WindowScale(shift) => Some(TcpOption::WindowScale(shift)),
+            TcpOptions2::SelectiveAcknowlegementPermitted => Some(TcpOption::SelectiveAcknowledgementPermitted),
+            TcpOptions2::Timestamp {
+                sender_timestamp,
+                echo_timestamp,
+            } => Some(TcpOption::Timestamp {
+                sender_timestamp,
+                echo_timestamp,
+            }),
+            TcpOptions2::EndOfOptionsList
+            | TcpOptions2::NoOperation
+            | TcpOptions2::SelectiveAcknowlegement { .. }
+            | TcpOptions2::FastOpen(_) => None,
+        }
+    }
+
     fn compute_size(&self) -> usize {
         use TcpOptions2::*;
         match self {
@@ -117,6 +187,7 @@ impl TcpOptions2 {
             SelectiveAcknowlegementPermitted => 2,
             SelectiveAcknowlegement { num_sacks, .. } => 2 + 8 * num_sacks,
             Timestamp { .. } => 10,
+            FastOpen(cookie) => 2 + cookie.len,
         }
     }
 
@@ -164,6 +235,12 @@ impl TcpOptions2 {
                 buf[6..10].copy_from_slice(&echo_timestamp.to_be_bytes());
                 10
             },
+            FastOpen(cookie) => {
+                buf[0] = 34;
+                buf[1] = 2 + cookie.len as u8;
+                buf[2..(2 + cookie.len)].copy_from_slice(cookie.as_slice());
+                2 + cookie.len
+            },
         }
     }
 }
@@ -268,7 +345,7 @@ impl TcpHeader {
         if !rx_checksum_offload {
             let checksum: u16 = u16::from_be_bytes([hdr_buf[16], hdr_buf[17]]);
             if checksum != tcp_checksum(ipv4_header, hdr_buf, data_buf) {
-                return Err(Fail::new(EBADMSG, "TCP checksum mismatch"));
+                return Err(Fail::new(EILSEQ, "TCP checksum mismatch"));
             }
         }
 
@@ -356,6 +433,19 @@ impl TcpHeader {
                             echo_timestamp,
                         }
                     },
+                    34 => {
+                        let mut temp: [u8; 1] = [0; 1];
+                        option_rdr.read_exact(&mut temp)?;
+                        let cookie_len: usize = (temp[0] as usize).wrapping_sub(2);
+                        if cookie_len != 0
+                            && !(MIN_FAST_OPEN_COOKIE_SIZE..=MAX_FAST_OPEN_COOKIE_SIZE).contains(&cookie_len)
+                        {
+                            return Err(Fail::new(EBADMSG, "invalid TCP fast open cookie size"));
+                        }
+                        let mut temp: [u8; MAX_FAST_OPEN_COOKIE_SIZE] = [0; MAX_FAST_OPEN_COOKIE_SIZE];
+                        option_rdr.read_exact(&mut temp[..cookie_len])?;
+                        TcpOptions2::FastOpen(FastOpenCookie::new(&temp[..cookie_len]))
+                    },
                     _ => return Err(Fail::new(EBADMSG, "invalid TCP option")),
                 };
                 if num_options >= option_list.len() {
@@ -9,12 +9,27 @@ use crate::{
     collections::async_queue::SharedAsyncQueue,
     inetstack::{
         protocols::{
-            ipv4::Ipv4Header,
+            ipv4::{
+                Ipv4Header,
+                IPV4_HEADER_MIN_SIZE,
+            },
             tcp::{
                 active_open::SharedActiveOpenSocket,
-                established::EstablishedSocket,
+                established::{
+                    congestion_control::{
+                        self,
+                        CongestionControl,
+                        CongestionControlConstructor,
+                    },
+                    EstablishedSocket,
+                },
+                fast_open::SharedFastOpenState,
+                isn_generator::IsnGenerator,
                 passive_open::SharedPassiveSocket,
-                segment::TcpHeader,
+                segment::{
+                    TcpHeader,
+                    MIN_TCP_HEADER_SIZE,
+                },
                 SeqNumber,
             },
         },
@@ -26,7 +41,23 @@ use crate::{
         memory::DemiBuffer,
         network::{
             config::TcpConfig,
-            socket::SocketId,
+            consts::MAX_DSCP,
+            socket::{
+                operation::Shutdown,
+                option::{
+                    HalfClosedReadPolicy,
+                    SocketOption,
+                    SocketOptionName,
+                },
+                SocketId,
+            },
+            types::{
+                ConnectionSnapshot,
+                DropStats,
+                TcpOption,
+                TcpSeqState,
+                TcpState,
+            },
             NetworkRuntime,
         },
         scheduler::Yielder,
@@ -73,6 +104,32 @@ pub struct TcpSocket<N: NetworkRuntime> {
     tcp_config: TcpConfig,
     arp: SharedArpPeer<N>,
     dead_socket_tx: mpsc::UnboundedSender<QDesc>,
+    drop_stats: SharedObject<DropStats>,
+    fast_open: SharedFastOpenState,
+    // Disable delayed ACKs once the connection is established?
+    quick_ack: bool,
+    // Probe the peer once the connection is established and goes idle?
+    keepalive: bool,
+    // Send buffer size requested for the connection, if any.
+    send_buffer_size: Option<usize>,
+    // Upper bound on the receive window advertised by the connection, if any.
+    recv_window_clamp: Option<u32>,
+    // DSCP to mark the connection's segments with.
+    dscp: u8,
+    // Handling of data that arrives after the read side of the connection is shut down.
+    half_closed_read_policy: HalfClosedReadPolicy,
+    // Share the local address with other sockets that set this too?
+    reuse_port: bool,
+    // Upper bound on the retransmission timeout of the connection, if any.
+    max_rto: Option<Duration>,
+    // How long sent data may go unacknowledged before the connection is closed, if there is a limit.
+    user_timeout: Option<Duration>,
+    // Upper bound on the out-of-order data that the connection holds for reassembly, if any.
+    max_reassembly_bytes: Option<usize>,
+    // Hold back segments that are not full?
+    cork: bool,
+    // Lift the cork whenever a pop completes?
+    auto_flush_after_pop: bool,
 }
 
 pub struct SharedTcpSocket<N: NetworkRuntime>(SharedObject<TcpSocket<N>>);
@@ -90,6 +147,8 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
         tcp_config: TcpConfig,
         arp: SharedArpPeer<N>,
         dead_socket_tx: mpsc::UnboundedSender<QDesc>,
+        drop_stats: SharedObject<DropStats>,
+        fast_open: SharedFastOpenState,
     ) -> Self {
         Self(SharedObject::<TcpSocket<N>>::new(TcpSocket::<N> {
             state: SocketState::Unbound,
@@ -100,6 +159,20 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
             tcp_config,
             arp,
             dead_socket_tx,
+            drop_stats,
+            fast_open,
+            quick_ack: false,
+            keepalive: false,
+            send_buffer_size: None,
+            recv_window_clamp: None,
+            dscp: 0,
+            half_closed_read_policy: HalfClosedReadPolicy::default(),
+            reuse_port: false,
+            max_rto: None,
+            user_timeout: None,
+            max_reassembly_bytes: None,
+            cork: false,
+            auto_flush_after_pop: false,
         }))
     }
 
@@ -111,6 +184,8 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
         tcp_config: TcpConfig,
         arp: SharedArpPeer<N>,
         dead_socket_tx: mpsc::UnboundedSender<QDesc>,
+        drop_stats: SharedObject<DropStats>,
+        fast_open: SharedFastOpenState,
     ) -> Self {
         let recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)> = socket.get_recv_queue();
         Self(SharedObject::<TcpSocket<N>>::new(TcpSocket::<N> {
@@ -122,6 +197,20 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
             tcp_config,
             arp,
             dead_socket_tx,
+            drop_stats,
+            fast_open,
+            quick_ack: false,
+            keepalive: false,
+            send_buffer_size: None,
+            recv_window_clamp: None,
+            dscp: 0,
+            half_closed_read_policy: HalfClosedReadPolicy::default(),
+            reuse_port: false,
+            max_rto: None,
+            user_timeout: None,
+            max_reassembly_bytes: None,
+            cork: false,
+            auto_flush_after_pop: false,
         }))
     }
 
@@ -132,7 +221,7 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
     }
 
     /// Sets the target queue to listen for incoming connections.
-    pub fn listen(&mut self, backlog: usize, nonce: u32) -> Result<(), Fail> {
+    pub fn listen(&mut self, backlog: usize, isn_generator: IsnGenerator) -> Result<(), Fail> {
         let recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)> =
             SharedAsyncQueue::<(Ipv4Header, TcpHeader, DemiBuffer)>::default();
         self.state = SocketState::Listening(SharedPassiveSocket::new(
@@ -146,7 +235,9 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
             self.local_link_addr,
             self.arp.clone(),
             self.dead_socket_tx.clone(),
-            nonce,
+            self.drop_stats.clone(),
+            self.fast_open.clone(),
+            isn_generator,
         )?);
         self.recv_queue = Some(recv_queue);
         Ok(())
@@ -158,9 +249,15 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
             SocketState::Listening(ref listening_socket) => listening_socket.clone(),
             _ => unreachable!("State machine check should ensure that this socket is listening"),
         };
-        let new_socket: EstablishedSocket<N> = listening_socket.do_accept(yielder).await?;
+        let mut new_socket: EstablishedSocket<N> = listening_socket.do_accept(yielder).await?;
+        // The connection inherits the options that were set on the listening socket.
+        new_socket.set_max_rto(self.max_rto);
+        new_socket.set_user_timeout(self.user_timeout);
+        new_socket.set_max_reassembly_bytes(self.max_reassembly_bytes);
+        new_socket.set_cork(self.cork);
+        new_socket.set_auto_flush_after_pop(self.auto_flush_after_pop);
         // Insert queue into queue table and get new queue descriptor.
-        let new_queue = Self::new_established(
+        let mut new_queue: Self = Self::new_established(
             new_socket,
             self.runtime.clone(),
             self.network.clone(),
@@ -168,7 +265,14 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
             self.tcp_config.clone(),
             self.arp.clone(),
             self.dead_socket_tx.clone(),
+            self.drop_stats.clone(),
+            self.fast_open.clone(),
         );
+        new_queue.max_rto = self.max_rto;
+        new_queue.user_timeout = self.user_timeout;
+        new_queue.max_reassembly_bytes = self.max_reassembly_bytes;
+        new_queue.cork = self.cork;
+        new_queue.auto_flush_after_pop = self.auto_flush_after_pop;
         Ok(new_queue)
     }
 
@@ -177,6 +281,7 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
         local: SocketAddrV4,
         remote: SocketAddrV4,
         local_isn: SeqNumber,
+        initial: Option<DemiBuffer>,
         yielder: Yielder,
     ) -> Result<(), Fail> {
         let recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)> =
@@ -195,14 +300,80 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
             self.local_link_addr,
             self.arp.clone(),
             self.dead_socket_tx.clone(),
+            initial,
+            self.fast_open.clone(),
         )?;
         self.state = SocketState::Connecting(socket.clone());
         self.recv_queue = Some(recv_queue);
-        let new_socket = socket.connect(yielder).await?;
+        let mut new_socket: EstablishedSocket<N> = socket.connect(yielder).await?;
+        self.apply_options(&mut new_socket);
+        self.state = SocketState::Established(new_socket);
+        Ok(())
+    }
+
+    /// Restores the connection that `snapshot` was taken of on this socket, which takes over its endpoints, sequence
+    /// space, and buffered data. The peer does not take part, so the connection is established right away.
+    pub fn resume_from(&mut self, snapshot: &ConnectionSnapshot) -> Result<(), Fail> {
+        let recv_queue: SharedAsyncQueue<(Ipv4Header, TcpHeader, DemiBuffer)> =
+            SharedAsyncQueue::<(Ipv4Header, TcpHeader, DemiBuffer)>::default();
+        let ack_queue: SharedAsyncQueue<usize> = SharedAsyncQueue::<usize>::default();
+        let receive_window_size: u32 = (self.tcp_config.get_receive_window_size() as u32)
+            .checked_shl(snapshot.receive_window_scale as u32)
+            .ok_or_else(|| Fail::new(libc::EINVAL, "window scale of snapshot is too large"))?;
+        let cc_constructor: CongestionControlConstructor = if self.tcp_config.get_congestion_control() {
+            congestion_control::Cubic::new
+        } else {
+            congestion_control::None::new
+        };
+        // The data that the application has not popped yet precedes RCV.NXT, and is queued again below.
+        let reader_next: SeqNumber =
+            SeqNumber::from(snapshot.seq_state.rcv_nxt) - SeqNumber::from(snapshot.recv_buffered.len() as u32);
+        let mut new_socket: EstablishedSocket<N> = EstablishedSocket::new(
+            snapshot.local,
+            snapshot.remote,
+            self.runtime.clone(),
+            self.network.clone(),
+            recv_queue.clone(),
+            ack_queue,
+            self.local_link_addr,
+            self.tcp_config.clone(),
+            self.arp.clone(),
+            reader_next,
+            self.tcp_config.get_ack_delay_timeout(),
+            receive_window_size,
+            snapshot.receive_window_scale as u32,
+            SeqNumber::from(snapshot.seq_state.snd_una),
+            snapshot.seq_state.snd_wnd,
+            snapshot.send_window_scale,
+            snapshot.mss as usize,
+            cc_constructor,
+            None,
+            self.dead_socket_tx.clone(),
+        )?;
+        self.apply_options(&mut new_socket);
+        new_socket.restore_buffered_data(snapshot)?;
         self.state = SocketState::Established(new_socket);
+        self.recv_queue = Some(recv_queue);
         Ok(())
     }
 
+    /// Applies the options that were set on this socket to the connection that it establishes.
+    fn apply_options(&self, new_socket: &mut EstablishedSocket<N>) {
+        new_socket.set_quick_ack(self.quick_ack);
+        new_socket.set_keepalive(self.keepalive);
+        if let Some(size) = self.send_buffer_size {
+            new_socket.set_send_buffer_size(size);
+        }
+        new_socket.set_receive_window_clamp(self.recv_window_clamp);
+        new_socket.set_dscp(self.dscp);
+        new_socket.set_half_closed_read_policy(self.half_closed_read_policy);
+        new_socket.set_max_rto(self.max_rto);
+        new_socket.set_user_timeout(self.user_timeout);
+        new_socket.set_max_reassembly_bytes(self.max_reassembly_bytes);
+        new_socket.set_cork(self.cork);
+        new_socket.set_auto_flush_after_pop(self.auto_flush_after_pop);
+    }
+
     pub async fn push(&mut self, buf: DemiBuffer, _yielder: Yielder) -> Result<(), Fail> {
         // Send synchronously.
         match self.state {
@@ -218,6 +389,13 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
         }
     }
 
+    pub async fn pop_all(&mut self, yielder: Yielder) -> Result<DemiBuffer, Fail> {
+        match self.state {
+            SocketState::Established(ref mut socket) => socket.pop_all(yielder).await,
+            _ => unreachable!("State machine check should ensure that this socket is connected"),
+        }
+    }
+
     pub async fn close(&mut self, yielder: Yielder) -> Result<Option<SocketId>, Fail> {
         match self.state {
             // Closing an active socket.
@@ -226,10 +404,9 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
                 Ok(Some(SocketId::Active(socket.endpoints().0, socket.endpoints().1)))
             },
             // Closing a listening socket.
-            SocketState::Listening(_) => {
-                let cause: String = format!("cannot close a listening socket");
-                error!("do_close(): {}", &cause);
-                Err(Fail::new(libc::ENOTSUP, &cause))
+            SocketState::Listening(ref mut socket) => {
+                socket.cancel_background_task();
+                Ok(Some(SocketId::Passive(socket.endpoint())))
             },
             // Closing a connecting socket.
             SocketState::Connecting(_) => {
@@ -256,16 +433,13 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
                 Ok(Some(SocketId::Active(socket.endpoints().0, socket.endpoints().1)))
             },
             // Closing a listening socket.
-            SocketState::Listening(_) => {
-                let cause: String = format!("cannot close a listening socket");
-                error!("do_close(): {}", &cause);
-                Err(Fail::new(libc::ENOTSUP, &cause))
+            SocketState::Listening(ref mut socket) => {
+                socket.cancel_background_task();
+                Ok(Some(SocketId::Passive(socket.endpoint())))
             },
-            // Closing a connecting socket.
-            SocketState::Connecting(_) => {
-                let cause: String = format!("cannot close a connecting socket");
-                error!("do_close(): {}", &cause);
-                Err(Fail::new(libc::ENOTSUP, &cause))
+            // Aborting a connecting socket. Whoever drove the connect must have stopped polling it.
+            SocketState::Connecting(ref socket) => {
+                Ok(Some(SocketId::Active(socket.endpoints().0, socket.endpoints().1)))
             },
             // Closing a closing socket.
             SocketState::Closing(_) => {
@@ -292,6 +466,388 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
         }
     }
 
+    /// Sets a socket option. Options set before the connection is established take effect once it is.
+    pub fn set_socket_option(&mut self, option: SocketOption) -> Result<(), Fail> {
+        match option {
+            SocketOption::QuickAck(quick_ack) => {
+                self.quick_ack = quick_ack;
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_quick_ack(quick_ack);
+                }
+            },
+            SocketOption::KeepAliveEnabled(keepalive) => {
+                self.keepalive = keepalive;
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_keepalive(keepalive);
+                }
+            },
+            SocketOption::SendBufferSize(size) => {
+                if size == 0 {
+                    let cause: &str = "send buffer size must be positive";
+                    error!("set_socket_option(): {}", cause);
+                    return Err(Fail::new(libc::EINVAL, cause));
+                }
+                self.send_buffer_size = Some(size);
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_send_buffer_size(size);
+                }
+            },
+            SocketOption::RecvWindowClamp(clamp) => {
+                if clamp == 0 {
+                    let cause: &str = "receive window clamp must be positive";
+                    error!("set_socket_option(): {}", cause);
+                    return Err(Fail::new(libc::EINVAL, cause));
+                }
+                self.recv_window_clamp = Some(clamp);
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_receive_window_clamp(Some(clamp));
+                }
+            },
+            SocketOption::Dscp(dscp) => {
+                if dscp > MAX_DSCP {
+                    let cause: String = format!("dscp does not fit in 6 bits (dscp={:?})", dscp);
+                    error!("set_socket_option(): {}", cause);
+                    return Err(Fail::new(libc::EINVAL, &cause));
+                }
+                self.dscp = dscp;
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_dscp(dscp);
+                }
+            },
+            SocketOption::HalfClosedReadPolicy(policy) => {
+                self.half_closed_read_policy = policy;
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_half_closed_read_policy(policy);
+                }
+            },
+            SocketOption::MaxRto(max_rto) => {
+                if max_rto.is_zero() {
+                    let cause: &str = "retransmission timeout bound must be positive";
+                    error!("set_socket_option(): {}", cause);
+                    return Err(Fail::new(libc::EINVAL, cause));
+                }
+                self.max_rto = Some(max_rto);
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_max_rto(Some(max_rto));
+                }
+            },
+            SocketOption::UserTimeout(user_timeout) => {
+                if user_timeout.is_zero() {
+                    let cause: &str = "user timeout must be positive";
+                    error!("set_socket_option(): {}", cause);
+                    return Err(Fail::new(libc::EINVAL, cause));
+                }
+                self.user_timeout = Some(user_timeout);
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_user_timeout(Some(user_timeout));
+                }
+            },
+            SocketOption::MaxReassemblyBytes(max_reassembly_bytes) => {
+                if max_reassembly_bytes == 0 {
+                    let cause: &str = "reassembly limit must be positive";
+                    error!("set_socket_option(): {}", cause);
+                    return Err(Fail::new(libc::EINVAL, cause));
+                }
+                self.max_reassembly_bytes = Some(max_reassembly_bytes);
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_max_reassembly_bytes(Some(max_reassembly_bytes));
+                }
+            },
+            SocketOption::Broadcast(_) | SocketOption::MaxDatagramQueue(_) => {
+                let cause: String = format!("socket option not supported on tcp sockets (option={:?})", option);
+                warn!("set_socket_option(): {}", cause);
+                return Err(Fail::new(libc::ENOPROTOOPT, &cause));
+            },
+            SocketOption::Priority(_) => {
+                let cause: &str = "scheduler priorities are set by the libOS, not by the transport";
+                warn!("set_socket_option(): {}", cause);
+                return Err(Fail::new(libc::ENOPROTOOPT, cause));
+            },
+            SocketOption::NoSigPipe(_) => {
+                let cause: &str = "signal suppression is handled by the libOS, not by the transport";
+                warn!("set_socket_option(): {}", cause);
+                return Err(Fail::new(libc::ENOPROTOOPT, cause));
+            },
+            SocketOption::QueueBufferPool(_) | SocketOption::BufferPool(_) => {
+                let cause: &str = "buffer pools of queues are managed by the libOS, not by the transport";
+                warn!("set_socket_option(): {}", cause);
+                return Err(Fail::new(libc::ENOPROTOOPT, cause));
+            },
+            SocketOption::NoDelay(no_delay) => {
+                if !no_delay {
+                    let cause: &str = "nagle's algorithm is not implemented";
+                    warn!("set_socket_option(): {}", cause);
+                    return Err(Fail::new(libc::ENOTSUP, cause));
+                }
+            },
+            SocketOption::ReusePort(reuse_port) => self.reuse_port = reuse_port,
+            SocketOption::Cork(cork) => {
+                self.cork = cork;
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_cork(cork);
+                }
+            },
+            SocketOption::AutoFlushAfterPop(auto_flush_after_pop) => {
+                self.auto_flush_after_pop = auto_flush_after_pop;
+                if let Some(socket) = self.get_established_socket_mut() {
+                    socket.set_auto_flush_after_pop(auto_flush_after_pop);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Reads a socket option back. Before the connection is established, this is the requested value, if any, or the
+    /// default.
+    pub fn get_socket_option(&self, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        match name {
+            // Segments are sent as soon as they are pushed, unless the socket is corked.
+            SocketOptionName::NoDelay => Ok(SocketOption::NoDelay(!self.cork)),
+            // An unbounded send buffer reads back as the largest possible size.
+            SocketOptionName::SendBufferSize => Ok(SocketOption::SendBufferSize(
+                self.send_buffer_size().unwrap_or(usize::MAX),
+            )),
+            SocketOptionName::RecvWindowClamp => {
+                let limit: u32 = match self.state {
+                    SocketState::Established(ref socket) | SocketState::Closing(ref socket) => {
+                        socket.receive_window_limit()
+                    },
+                    _ => self.recv_window_clamp.unwrap_or(
+                        (self.tcp_config.get_receive_window_size() as u32) << self.tcp_config.get_window_scale(),
+                    ),
+                };
+                Ok(SocketOption::RecvWindowClamp(limit))
+            },
+        }
+    }
+
+    /// Whether this socket may share its local address with other sockets that allow it too.
+    pub fn reuse_port(&self) -> bool {
+        self.reuse_port
+    }
+
+    /// Whether data was exchanged in the SYN of this connection, using TCP Fast Open. Sockets that were never connected
+    /// have not exchanged any.
+    pub fn used_fast_open(&self) -> bool {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.used_fast_open(),
+            _ => false,
+        }
+    }
+
+    /// Returns the number of connections to this listening socket that an accept would return without waiting. Sockets
+    /// that are not listening have none.
+    pub fn ready_connections(&self) -> usize {
+        match self.state {
+            SocketState::Listening(ref socket) => socket.ready_connections(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of connections to this listening socket that are in the SYN-RECEIVED state.
+    pub fn half_open_count(&self) -> Result<usize, Fail> {
+        match self.state {
+            SocketState::Listening(ref socket) => Ok(socket.half_open_count()),
+            _ => Err(Fail::new(libc::EINVAL, "socket is not listening")),
+        }
+    }
+
+    /// Returns the rate, in bits per second, at which this connection recently sent payload bytes. Sockets that were
+    /// never connected have not sent anything.
+    pub fn send_rate_bps(&self) -> u64 {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.send_rate_bps(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the rate, in bits per second, at which this connection recently received payload bytes. Sockets that
+    /// were never connected have not received anything.
+    pub fn recv_rate_bps(&self) -> u64 {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.recv_rate_bps(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the rate, in bits per second, at which the peer of this connection recently acknowledged payload bytes.
+    /// Sockets that were never connected have not had anything acknowledged.
+    pub fn delivery_rate_bps(&self) -> u64 {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.delivery_rate_bps(),
+            _ => 0,
+        }
+    }
+
+    /// Returns whether a pop on this connection would have to wait for data. Only connected sockets can be popped.
+    pub fn would_block_pop(&self) -> Result<bool, Fail> {
+        match self.state {
+            SocketState::Established(ref socket) => Ok(!socket.has_data_to_pop()),
+            _ => Err(Fail::new(libc::ENOTCONN, "socket is not connected")),
+        }
+    }
+
+    /// Returns the TCP options received on this connection, if they are being recorded. Sockets that were never
+    /// connected have not received any.
+    pub fn observed_options(&self) -> Vec<TcpOption> {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.observed_options(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the sequence numbers and windows of the connection. Sockets that were never connected have none.
+    pub fn tcp_sequence_state(&self) -> Option<TcpSeqState> {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => Some(socket.sequence_state()),
+            _ => None,
+        }
+    }
+
+    /// Returns the RFC 793 state of the connection. Sockets that neither listen nor ever sent a SYN are CLOSED.
+    pub fn tcp_state(&self) -> TcpState {
+        match self.state {
+            SocketState::Unbound | SocketState::Bound(_) => TcpState::Closed,
+            SocketState::Listening(_) => TcpState::Listen,
+            SocketState::Connecting(_) => TcpState::SynSent,
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.tcp_state(),
+        }
+    }
+
+    /// Returns the initial sequence number that we chose for the connection. Sockets that never sent a SYN have none.
+    pub fn local_isn(&self) -> Option<SeqNumber> {
+        match self.state {
+            SocketState::Connecting(ref socket) => Some(socket.local_isn()),
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.local_isn(),
+            _ => None,
+        }
+    }
+
+    /// Returns the effective send buffer size of the connection, if it is bounded. Before the connection is
+    /// established, this is the requested size, if any. The send buffer is unbounded unless a size was requested.
+    pub fn send_buffer_size(&self) -> Option<usize> {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.send_buffer_size(),
+            _ => self.send_buffer_size,
+        }
+    }
+
+    /// Shuts down the `how` directions of the connection. Shutting down the write side sends a FIN to the peer, while
+    /// data keeps being received. Data that arrives after the read side was shut down is handled according to the
+    /// half-closed read policy.
+    pub fn shutdown(&mut self, how: Shutdown) -> Result<(), Fail> {
+        match self.get_established_socket_mut() {
+            Some(socket) => {
+                if how != Shutdown::Write {
+                    socket.shutdown_read();
+                }
+                if how != Shutdown::Read {
+                    socket.shutdown_write();
+                }
+                Ok(())
+            },
+            None => {
+                let cause: String = format!("connection not established (how={:?})", how);
+                error!("shutdown(): {}", cause);
+                Err(Fail::new(libc::ENOTCONN, &cause))
+            },
+        }
+    }
+
+    /// Pauses or resumes the data flow of the connection.
+    pub fn set_paused(&mut self, paused: bool) -> Result<(), Fail> {
+        match self.get_established_socket_mut() {
+            Some(socket) => {
+                socket.set_paused(paused);
+                Ok(())
+            },
+            None => {
+                let cause: &str = "connection not established";
+                error!("set_paused(): {}", cause);
+                Err(Fail::new(libc::ENOTCONN, cause))
+            },
+        }
+    }
+
+    /// Returns the endpoints, sequence space, and buffered data of the connection.
+    pub fn connection_snapshot(&self) -> Result<ConnectionSnapshot, Fail> {
+        match self.state {
+            SocketState::Established(ref socket) => Ok(socket.snapshot()),
+            _ => {
+                let cause: &str = "connection not established";
+                error!("connection_snapshot(): {}", cause);
+                Err(Fail::new(libc::ENOTCONN, cause))
+            },
+        }
+    }
+
+    /// Returns the largest TCP payload that fits in a single packet on the path of the connection. Before the connection
+    /// is established, this is derived from the MTU of the network interface.
+    pub fn path_mtu(&self) -> u16 {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.path_mtu(),
+            _ => self.network.get_mtu() - IPV4_HEADER_MIN_SIZE - MIN_TCP_HEADER_SIZE as u16,
+        }
+    }
+
+    /// Lowers the path MTU of the connection to `next_hop_mtu`, as reported by a router for the segment starting at
+    /// `seq_num`. Sockets that are not connected have no path to update.
+    pub fn update_path_mtu(&mut self, next_hop_mtu: u16, seq_num: SeqNumber) {
+        if let Some(socket) = self.get_established_socket_mut() {
+            socket.update_path_mtu(next_hop_mtu, seq_num);
+        }
+    }
+
+    /// Returns the DSCP that the connection's segments are marked with.
+    pub fn dscp(&self) -> u8 {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => socket.dscp(),
+            _ => self.dscp,
+        }
+    }
+
+    /// Returns the underlying connection, if it has been established.
+    fn get_established_socket_mut(&mut self) -> Option<&mut EstablishedSocket<N>> {
+        match self.state {
+            SocketState::Established(ref mut socket) | SocketState::Closing(ref mut socket) => Some(socket),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of segments retransmitted on this connection. Sockets that were never connected have not
+    /// retransmitted anything.
+    pub fn retransmit_count(&self) -> u64 {
+        match self.state {
+            SocketState::Established(ref socket) => socket.retransmit_count(),
+            SocketState::Closing(ref socket) => socket.retransmit_count(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the congestion window of this connection, in bytes, or `None` if it is not connected.
+    pub fn cwnd(&self) -> Option<u32> {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => Some(socket.cwnd()),
+            _ => None,
+        }
+    }
+
+    /// Returns the slow start threshold of this connection, in bytes, or `None` if it is not connected.
+    pub fn ssthresh(&self) -> Option<u32> {
+        match self.state {
+            SocketState::Established(ref socket) | SocketState::Closing(ref socket) => Some(socket.ssthresh()),
+            _ => None,
+        }
+    }
+
+    /// Whether this socket was closed actively and its connection is in the TIME_WAIT state.
+    pub fn in_time_wait(&self) -> bool {
+        match self.state {
+            SocketState::Established(ref socket) => socket.in_time_wait(),
+            _ => false,
+        }
+    }
+
     pub fn endpoints(&self) -> Result<(SocketAddrV4, SocketAddrV4), Fail> {
         match self.state {
             SocketState::Established(ref socket) => Ok(socket.endpoints()),
@@ -300,6 +856,26 @@ impl<N: NetworkRuntime> SharedTcpSocket<N> {
         }
     }
 
+    /// Moves an established connection to new endpoints, without resetting it.
+    pub fn migrate(&mut self, local: SocketAddrV4, remote: SocketAddrV4) -> Result<(), Fail> {
+        match self.state {
+            SocketState::Established(ref mut socket) => {
+                socket.set_endpoints(local, remote);
+                Ok(())
+            },
+            _ => Err(Fail::new(libc::ENOTCONN, "connection not established")),
+        }
+    }
+
+    /// Stops demultiplexing incoming segments from `remote` to a connection accepted by this listening socket. Returns
+    /// whether there was such a connection.
+    pub fn forget_connection(&mut self, remote: &SocketAddrV4) -> bool {
+        match self.state {
+            SocketState::Listening(ref mut socket) => socket.forget_connection(remote),
+            _ => false,
+        }
+    }
+
     pub fn receive(&mut self, ip_hdr: Ipv4Header, tcp_hdr: TcpHeader, buf: DemiBuffer) {
         // If this queue has an allocated receive queue, then direct the packet there.
         if let Some(recv_queue) = self.recv_queue.as_mut() {
@@ -357,6 +933,13 @@ impl<N: NetworkRuntime> Clone for SharedTcpSocket<N> {
     }
 }
 
+/// Two handles are equal if they refer to the same socket.
+impl<N: NetworkRuntime> PartialEq for SharedTcpSocket<N> {
+    fn eq(&self, other: &Self) -> bool {
+        SharedObject::ptr_eq(&self.0, &other.0)
+    }
+}
+
 impl<N: NetworkRuntime> Debug for SharedTcpSocket<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "TCP socket local={:?} remote={:?}", self.local(), self.remote())
@@ -0,0 +1,171 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Queues three connections on a listening socket and checks that a single batch accept returns all of them.
+#[test]
+fn tcp_accept_batch_drains_ready_connections() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = listen(&mut bob, bob_addr)?;
+
+    let mut clients: Vec<QDesc> = Vec::new();
+    for _ in 0..3 {
+        clients.push(connect(&mut alice, &mut bob, bob_addr)?);
+    }
+
+    let accept_qt: QToken = bob.accept_batch(listen_qd, 4)?;
+    let accepted: Vec<(QDesc, SocketAddrV4)> = match bob.wait(accept_qt)? {
+        (qd, OperationResult::AcceptBatch(accepted)) if qd == listen_qd => accepted,
+        _ => anyhow::bail!("accept_batch should succeed"),
+    };
+    crate::ensure_eq!(accepted.len(), clients.len());
+
+    // Every connection got its own descriptor, and each one comes from a different client.
+    let mut qds: Vec<QDesc> = accepted.iter().map(|(qd, _)| *qd).collect();
+    qds.sort_by_key(|qd| u32::from(*qd));
+    qds.dedup();
+    crate::ensure_eq!(qds.len(), clients.len());
+    let mut ports: Vec<u16> = accepted.iter().map(|(_, addr)| addr.port()).collect();
+    ports.sort();
+    ports.dedup();
+    crate::ensure_eq!(ports.len(), clients.len());
+    for (_, addr) in accepted.iter() {
+        crate::ensure_eq!(*addr.ip(), test_helpers::ALICE_IPV4);
+    }
+
+    Ok(())
+}
+
+/// Checks that a batch accept returns no more than the requested number of connections, and leaves the others for the
+/// next accept.
+#[test]
+fn tcp_accept_batch_respects_max() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = listen(&mut bob, bob_addr)?;
+
+    // Empty batches are rejected.
+    match bob.accept_batch(listen_qd, 0) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("empty batch should be rejected"),
+    }
+
+    for _ in 0..3 {
+        connect(&mut alice, &mut bob, bob_addr)?;
+    }
+
+    let accept_qt: QToken = bob.accept_batch(listen_qd, 2)?;
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::AcceptBatch(accepted)) => crate::ensure_eq!(accepted.len(), 2),
+        _ => anyhow::bail!("accept_batch should succeed"),
+    };
+    let accept_qt: QToken = bob.accept_batch(listen_qd, 2)?;
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::AcceptBatch(accepted)) => crate::ensure_eq!(accepted.len(), 1),
+        _ => anyhow::bail!("accept_batch should succeed"),
+    };
+
+    Ok(())
+}
+
+/// Checks that batch accepts can only be waited for with `wait_accept_batch()`, as plain waits and the completion ring
+/// would only deliver the first connection.
+#[test]
+fn tcp_accept_batch_refuses_plain_waits() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = listen(&mut bob, bob_addr)?;
+    let mut runtime: SharedDemiRuntime = bob.get_runtime();
+    runtime.enable_completion_ring(4);
+
+    for _ in 0..2 {
+        connect(&mut alice, &mut bob, bob_addr)?;
+    }
+
+    let accept_qt: QToken = bob.accept_batch(listen_qd, 4)?;
+    match runtime.wait_any(&[accept_qt], Some(Duration::from_secs(0))) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("plain waits on a batch accept should fail"),
+    }
+    match runtime.wait_any_timeout(&[accept_qt], &[Duration::from_secs(0)]) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("plain waits on a batch accept should fail"),
+    }
+
+    // The batch completes, but stays out of the completion ring.
+    bob.poll();
+    crate::ensure_eq!(runtime.completion_ring_len(), 0);
+    let accepted: Vec<(QDesc, SocketAddrV4)> = runtime.wait_accept_batch(accept_qt, None)?;
+    crate::ensure_eq!(accepted.len(), 2);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Makes Bob listen for incoming connections on `bob_addr`.
+fn listen(bob: &mut SharedEngine, bob_addr: SocketAddrV4) -> Result<QDesc> {
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 8)?;
+    Ok(listen_qd)
+}
+
+/// Connects a new socket of Alice to Bob, without Bob accepting the connection.
+fn connect(alice: &mut SharedEngine, bob: &mut SharedEngine, bob_addr: SocketAddrV4) -> Result<QDesc> {
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+    // SYN, SYN+ACK, and ACK.
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => Ok(alice_qd),
+        _ => anyhow::bail!("connect should succeed"),
+    }
+}
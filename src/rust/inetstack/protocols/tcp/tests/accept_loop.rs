@@ -0,0 +1,119 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demikernel::libos::network::accept_stream::AcceptStream,
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::queue::{
+        OperationResult,
+        QDesc,
+        QToken,
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Connects several clients to a listening socket with an accept loop and checks that the loop yields one descriptor
+/// per connection, in order, without being re-armed.
+#[test]
+fn tcp_accept_loop() -> Result<()> {
+    const NUM_CLIENTS: usize = 3;
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Bob listens for incoming connections and accepts them in the background.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, NUM_CLIENTS)?;
+    let mut stream: AcceptStream = bob.spawn_accept_loop(listen_qd)?;
+    crate::ensure_eq!(stream.listen_qd(), listen_qd);
+    bob.poll();
+    crate::ensure_eq!(stream.try_next().is_none(), true);
+
+    let mut accepted: Vec<QDesc> = Vec::with_capacity(NUM_CLIENTS);
+    for _ in 0..NUM_CLIENTS {
+        // Alice connects to Bob.
+        let alice_qd: QDesc = alice.tcp_socket()?;
+        let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+        // SYN, SYN+ACK, and ACK.
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+        alice.receive(bob.pop_frame())?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+
+        match alice.wait(connect_qt)? {
+            (_, OperationResult::Connect) => {},
+            _ => anyhow::bail!("connect should succeed"),
+        };
+        // The loop hands out the new connection without another accept being issued.
+        let bob_qd: QDesc = loop {
+            match stream.try_next() {
+                Some(result) => break result?,
+                None => bob.poll(),
+            }
+        };
+        accepted.push(bob_qd);
+        crate::ensure_eq!(stream.try_next().is_none(), true);
+    }
+
+    // Every connection got its own descriptor, handed out in order.
+    let mut sorted: Vec<QDesc> = accepted.clone();
+    sorted.sort_by_key(|qd| u32::from(*qd));
+    sorted.dedup();
+    crate::ensure_eq!(sorted, accepted);
+
+    Ok(())
+}
+
+/// Closes a listening socket with an accept loop and checks that the loop stops, reporting the cancellation both
+/// through the stream and through its own token.
+#[test]
+fn tcp_accept_loop_stops_on_close() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let mut stream: AcceptStream = bob.spawn_accept_loop(listen_qd)?;
+    bob.poll();
+    crate::ensure_eq!(stream.try_next().is_none(), true);
+
+    let close_qt: QToken = bob.tcp_async_close(listen_qd)?;
+    match bob.wait(close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    match bob.wait(stream.qt())? {
+        (qd, OperationResult::Failed(e)) if qd == listen_qd && e.errno == libc::ECANCELED => {},
+        _ => anyhow::bail!("accept loop should be cancelled"),
+    };
+    match stream.try_next() {
+        Some(Err(e)) if e.errno == libc::ECANCELED => {},
+        _ => anyhow::bail!("stream should report the cancellation"),
+    };
+    crate::ensure_eq!(stream.try_next().is_none(), true);
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Accepts a connection while pushing a greeting to it and checks that the client receives the greeting.
+#[test]
+fn tcp_accept_and_push() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let greeting: DemiBuffer = DemiBuffer::from_slice(b"220 hello\r\n").expect("slice should fit in DemiBuffer");
+
+    // Bob listens for incoming connections and greets them.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.accept_and_push(listen_qd, greeting.clone())?;
+
+    // Alice connects to Bob.
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+    // SYN, SYN+ACK, and ACK.
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept(_)) => {},
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    // Deliver the greeting to Alice.
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    let pop_qt: QToken = alice.tcp_pop(alice_qd)?;
+    match alice.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], greeting[..]),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    pal::data_structures::SockAddr,
+    runtime::{
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        types::{
+            demi_opcode_t,
+            demi_qresult_t,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+#[cfg(target_os = "windows")]
+use crate::pal::functions::socketaddrv4_to_sockaddr;
+
+#[cfg(target_os = "linux")]
+use crate::pal::linux::socketaddrv4_to_sockaddr;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Accepts a connection from a known address and checks that the result of the accept carries that address, so that
+/// the application does not need to look it up.
+#[test]
+fn tcp_accept_result_has_peer_address() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 12345);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+
+    // Alice connects from a fixed address.
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    alice.tcp_bind(alice_qd, alice_addr)?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+    // SYN, SYN+ACK, and ACK.
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let (qd, result): (QDesc, OperationResult) = bob.wait(accept_qt)?;
+    match result {
+        OperationResult::Accept((_, addr)) => crate::ensure_eq!(addr, alice_addr),
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    // The address reaches the application in the accept result.
+    let qr: demi_qresult_t = bob.get_runtime().create_result(result, qd, accept_qt);
+    crate::ensure_eq!(qr.qr_opcode, demi_opcode_t::DEMI_OPC_ACCEPT);
+    let addr: SockAddr = unsafe { qr.qr_value.ares.addr };
+    let expected: SockAddr = socketaddrv4_to_sockaddr(&alice_addr);
+    crate::ensure_eq!(addr.sa_family, expected.sa_family);
+    crate::ensure_eq!(addr.sa_data, expected.sa_data);
+
+    Ok(())
+}
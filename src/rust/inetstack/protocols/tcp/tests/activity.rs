@@ -0,0 +1,81 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Establishes a connection, pushes and pops on it at known times, and checks that both ends report when the
+/// connection was established and when it was last active.
+#[test]
+fn tcp_connection_timestamps() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let mut bob: SharedEngine = test_helpers::new_bob2(t0);
+
+    // A socket that is not connected is only as old as its creation.
+    let qd: QDesc = alice.tcp_socket()?;
+    crate::ensure_eq!(alice.established_at(qd)?, None);
+    crate::ensure_eq!(alice.last_activity(qd)?, t0);
+
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    crate::ensure_eq!(alice.established_at(alice_qd)?, Some(t0));
+    crate::ensure_eq!(alice.last_activity(alice_qd)?, t0);
+    crate::ensure_eq!(bob.established_at(bob_qd)?, Some(t0));
+    crate::ensure_eq!(bob.last_activity(bob_qd)?, t0);
+
+    // Alice pushes a second later.
+    let t1: Instant = t0 + Duration::from_secs(1);
+    alice.advance_clock(t1);
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    crate::ensure_eq!(alice.last_activity(alice_qd)?, t1);
+
+    // Bob pops a second after that.
+    let t2: Instant = t1 + Duration::from_secs(1);
+    bob.advance_clock(t2);
+    bob.receive(alice.pop_frame())?;
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, _)) => {},
+        _ => anyhow::bail!("pop should succeed"),
+    };
+    crate::ensure_eq!(bob.last_activity(bob_qd)?, t2);
+
+    // Activity does not change when the connections were established.
+    crate::ensure_eq!(alice.established_at(alice_qd)?, Some(t0));
+    crate::ensure_eq!(bob.established_at(bob_qd)?, Some(t0));
+
+    Ok(())
+}
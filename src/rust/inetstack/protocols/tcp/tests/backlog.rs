@@ -0,0 +1,162 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demikernel::libos::network::accept_stream::AcceptStream,
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Overflows the backlog of a listening socket and checks that the pending accept fails with ECONNABORTED, that the
+/// connection that overflowed is refused, and that the connection that fit in the backlog is still accepted.
+#[test]
+fn tcp_backlog_overflow() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    crate::ensure_eq!(bob.backlog(listen_qd)?, None);
+    bob.tcp_listen(listen_qd, 1)?;
+    crate::ensure_eq!(bob.backlog(listen_qd)?, Some(1));
+
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    bob.poll();
+    crate::ensure_eq!(bob.pending_accepts(listen_qd)?, 1);
+
+    // Alice connects twice, but there is only room for one connection in the backlog.
+    let (first_qd, first_qt, first_syn): (QDesc, QToken, DemiBuffer) = start_connect(&mut alice, bob_addr)?;
+    let (second_qd, second_qt, second_syn): (QDesc, QToken, DemiBuffer) = start_connect(&mut alice, bob_addr)?;
+    bob.receive(first_syn)?;
+    bob.poll();
+    let syn_ack: DemiBuffer = bob.pop_frame();
+    bob.receive(second_syn)?;
+    bob.poll();
+    let rst: DemiBuffer = bob.pop_frame();
+
+    // The accept reports the overflow rather than wait for a connection that will never come.
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ECONNABORTED => {},
+        _ => anyhow::bail!("accept should fail with ECONNABORTED"),
+    };
+    crate::ensure_eq!(bob.pending_accepts(listen_qd)?, 0);
+    alice.receive(rst)?;
+    match alice.wait(second_qt)? {
+        (qd, OperationResult::Failed(e)) if qd == second_qd && e.errno == libc::ECONNREFUSED => {},
+        _ => anyhow::bail!("connect that overflowed the backlog should be refused"),
+    };
+
+    // The connection that fit in the backlog completes its handshake and is accepted.
+    alice.receive(syn_ack)?;
+    alice.poll();
+    match alice.wait(first_qt)? {
+        (qd, OperationResult::Connect) if qd == first_qd => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept(_)) => {},
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    Ok(())
+}
+
+/// Drops a pending accept before it completes, as happens when its coroutine is removed from the scheduler, and checks
+/// that it no longer counts as pending.
+#[test]
+fn tcp_dropped_accept_is_not_pending() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    bob.poll();
+    crate::ensure_eq!(bob.pending_accepts(listen_qd)?, 1);
+    bob.get_runtime().remove_background_coroutine(accept_qt)?;
+    crate::ensure_eq!(bob.pending_accepts(listen_qd)?, 0);
+
+    Ok(())
+}
+
+/// Overflows the backlog of a listening socket with an accept loop and checks that the loop skips the aborted accept and
+/// keeps going, so that it still hands out the connection that fit in the backlog.
+#[test]
+fn tcp_accept_loop_survives_backlog_overflow() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let mut stream: AcceptStream = bob.spawn_accept_loop(listen_qd)?;
+    bob.poll();
+
+    let (first_qd, first_qt, first_syn): (QDesc, QToken, DemiBuffer) = start_connect(&mut alice, bob_addr)?;
+    let (_, _, second_syn): (QDesc, QToken, DemiBuffer) = start_connect(&mut alice, bob_addr)?;
+    bob.receive(first_syn)?;
+    bob.poll();
+    let syn_ack: DemiBuffer = bob.pop_frame();
+    bob.receive(second_syn)?;
+    bob.poll();
+    bob.poll();
+    crate::ensure_eq!(stream.try_next().is_none(), true);
+
+    alice.receive(syn_ack)?;
+    alice.poll();
+    match alice.wait(first_qt)? {
+        (qd, OperationResult::Connect) if qd == first_qd => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    let accepted: QDesc = loop {
+        match stream.try_next() {
+            Some(result) => break result?,
+            None => bob.poll(),
+        }
+    };
+    crate::ensure_eq!(accepted != listen_qd, true);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Starts a connection from Alice to `remote` and returns its queue, its token and its SYN.
+fn start_connect(alice: &mut SharedEngine, remote: SocketAddrV4) -> Result<(QDesc, QToken, DemiBuffer)> {
+    let qd: QDesc = alice.tcp_socket()?;
+    let qt: QToken = alice.tcp_connect(qd, remote)?;
+    alice.poll();
+    Ok((qd, qt, alice.pop_frame()))
+}
@@ -0,0 +1,162 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::{
+            BufferPoolStats,
+            DemiBuffer,
+            MemoryRuntime,
+            PoolId,
+        },
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        types::demi_sgarray_t,
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of small messages that Alice sends to Bob.
+const NUM_MESSAGES: usize = 64;
+
+/// Size of each message, and of each pop.
+const MESSAGE_SIZE: usize = 16;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Assigns a connection to a buffer pool created by the libOS, and checks that its pops, and the scatter-gather arrays
+/// allocated for it, draw from that pool rather than from the pool of the thread or from another pool.
+#[test]
+fn tcp_buffer_pool() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Pools that do not exist are rejected.
+    match bob.set_socket_option(bob_qd, SocketOption::BufferPool(PoolId(42))) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("unknown buffer pool should be rejected"),
+    }
+
+    let other: PoolId = bob.create_buffer_pool(None)?;
+    let pool: PoolId = bob.create_buffer_pool(None)?;
+    crate::ensure_neq!(pool, other);
+    bob.set_socket_option(bob_qd, SocketOption::BufferPool(pool))?;
+    crate::ensure_eq!(bob.pool_stats(pool)?, BufferPoolStats::default());
+
+    let thread_before: BufferPoolStats = DemiBuffer::small_buffer_pool_stats();
+    let mut last: Option<DemiBuffer> = None;
+    for i in 0..NUM_MESSAGES {
+        let buf: DemiBuffer =
+            DemiBuffer::from_slice(&[i as u8; MESSAGE_SIZE][..]).expect("slice should fit in DemiBuffer");
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        bob.receive(alice.pop_frame())?;
+
+        let pop_qt: QToken = bob.pop(bob_qd, Some(MESSAGE_SIZE))?;
+        match bob.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, received)) => {
+                crate::ensure_eq!(received[..], buf[..]);
+                last = Some(received);
+            },
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+    let thread_after: BufferPoolStats = DemiBuffer::small_buffer_pool_stats();
+
+    // Every pop was served by the designated pool, and none by the pool of the thread or by the other pool.
+    let stats: BufferPoolStats = bob.pool_stats(pool)?;
+    crate::ensure_eq!(stats.hits + stats.misses, NUM_MESSAGES);
+    crate::ensure_eq!(stats.misses <= 2, true);
+    crate::ensure_eq!(bob.buffer_pool_stats(bob_qd)?, Some(stats));
+    crate::ensure_eq!(bob.pool_stats(other)?, BufferPoolStats::default());
+    crate::ensure_eq!(
+        thread_after.hits + thread_after.misses,
+        thread_before.hits + thread_before.misses
+    );
+
+    // Scatter-gather arrays allocated for pushes on the connection come from the same pool.
+    let sga: demi_sgarray_t = bob.sgaalloc_for(bob_qd, MESSAGE_SIZE)?;
+    crate::ensure_eq!(sga.sga_segs[0].sgaseg_len as usize, MESSAGE_SIZE);
+    crate::ensure_eq!(
+        bob.pool_stats(pool)?.hits + bob.pool_stats(pool)?.misses,
+        NUM_MESSAGES + 1
+    );
+    bob.sgafree(sga)?;
+
+    // Going back to the pool of the thread leaves the buffers that are still out bound to the designated pool.
+    bob.set_socket_option(bob_qd, SocketOption::QueueBufferPool(false))?;
+    crate::ensure_eq!(bob.buffer_pool_stats(bob_qd)?, None);
+    drop(last);
+
+    Ok(())
+}
+
+/// Places a buffer pool on a NUMA node, and checks that pops of the default size draw from it.
+#[test]
+fn tcp_buffer_pool_on_numa_node() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Nodes that the machine does not have are rejected.
+    match bob.create_buffer_pool(Some(u32::MAX)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("unknown NUMA node should be rejected"),
+    }
+
+    let pool: PoolId = bob.create_buffer_pool(Some(0))?;
+    bob.set_socket_option(bob_qd, SocketOption::BufferPool(pool))?;
+
+    for i in 0..NUM_MESSAGES {
+        let buf: DemiBuffer =
+            DemiBuffer::from_slice(&[i as u8; MESSAGE_SIZE][..]).expect("slice should fit in DemiBuffer");
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        bob.receive(alice.pop_frame())?;
+
+        let pop_qt: QToken = bob.pop(bob_qd, None)?;
+        match bob.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+
+    // Every pop was served by the pool, which recycled the buffers of the earlier pops.
+    let stats: BufferPoolStats = bob.pool_stats(pool)?;
+    crate::ensure_eq!(stats.hits + stats.misses, NUM_MESSAGES);
+    crate::ensure_eq!(stats.misses <= 2, true);
+    crate::ensure_eq!(stats.allocation_failures, 0);
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demikernel::libos::network::queue_info::QueueInfo,
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::state::SocketState,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::{
+        IpAddr,
+        SocketAddrV4,
+    },
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Connects Alice to Bob twice and Carrie to Bob once, then has Bob close every connection to Alice. Only those are
+/// closed: the listening socket and the connection to Carrie stay open and usable.
+#[test]
+fn tcp_close_matching_remote_ip() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let mut carrie: SharedEngine = test_helpers::new_carrie2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 4)?;
+    let mut to_alice: Vec<QDesc> = Vec::new();
+    for _ in 0..2 {
+        let (_, bob_qd): (QDesc, QDesc) = connect(&mut alice, &mut bob, listen_qd, bob_addr)?;
+        to_alice.push(bob_qd);
+    }
+    let (carrie_qd, to_carrie): (QDesc, QDesc) = connect(&mut carrie, &mut bob, listen_qd, bob_addr)?;
+
+    let infos: Vec<QueueInfo> = bob.queue_infos();
+    crate::ensure_eq!(infos.len(), 4);
+    for info in &infos {
+        if info.qd == listen_qd {
+            crate::ensure_eq!(info.state, SocketState::PassiveListening);
+            crate::ensure_eq!(info.remote, None);
+        } else {
+            crate::ensure_eq!(info.state, SocketState::ActiveEstablished);
+            crate::ensure_eq!(info.local, Some(bob_addr.into()));
+        }
+    }
+
+    let alice_ip: IpAddr = test_helpers::ALICE_IPV4.into();
+    let mut closed: Vec<QDesc> = bob.close_matching(|info| info.remote.map(|remote| remote.ip()) == Some(alice_ip));
+    closed.sort_by_key(|qd| u32::from(*qd));
+    crate::ensure_eq!(closed, to_alice);
+
+    // The closed queues are gone.
+    let mut open: Vec<QDesc> = bob.queue_infos().iter().map(|info| info.qd).collect();
+    open.sort_by_key(|qd| u32::from(*qd));
+    crate::ensure_eq!(open, vec![listen_qd, to_carrie]);
+    for qd in to_alice {
+        match bob.tcp_pop(qd) {
+            Err(e) if e.errno == libc::EBADF => {},
+            _ => anyhow::bail!("pop on a closed queue should fail with EBADF"),
+        };
+    }
+
+    // The connection to Carrie still carries data.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = carrie.tcp_push(carrie_qd, buf.clone())?;
+    match carrie.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(carrie.pop_frame())?;
+    let pop_qt: QToken = bob.tcp_pop(to_carrie)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    // A predicate that matches nothing closes nothing.
+    crate::ensure_eq!(bob.close_matching(|_| false), vec![]);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Connects `client` to the listening socket `listen_qd` of `server` at `server_addr`. Returns the queue descriptors of
+/// both ends of the connection.
+fn connect(
+    client: &mut SharedEngine,
+    server: &mut SharedEngine,
+    listen_qd: QDesc,
+    server_addr: SocketAddrV4,
+) -> Result<(QDesc, QDesc)> {
+    let accept_qt: QToken = server.tcp_accept(listen_qd)?;
+    let client_qd: QDesc = client.tcp_socket()?;
+    let connect_qt: QToken = client.tcp_connect(client_qd, server_addr)?;
+    client.poll();
+    server.receive(client.pop_frame())?;
+    server.poll();
+    client.receive(server.pop_frame())?;
+    client.poll();
+    server.receive(client.pop_frame())?;
+    match client.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let server_qd: QDesc = match server.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+    Ok((client_qd, server_qd))
+}
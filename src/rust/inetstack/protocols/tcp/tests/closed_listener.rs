@@ -0,0 +1,153 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                SeqNumber,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Closes a listener and then connects to its former port. The SYN is answered with a RST that acknowledges it, and
+/// the connect fails right away instead of timing out.
+#[test]
+fn tcp_connect_to_closed_listener() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Bob listens and then closes the listener.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let close_qt: QToken = bob.tcp_async_close(listen_qd)?;
+    match bob.wait(close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+
+    // Alice connects to the former port of the listener.
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    let syn: DemiBuffer = alice.pop_frame();
+    let syn_hdr: TcpHeader = parse_tcp_header(syn.clone())?;
+    crate::ensure_eq!(syn_hdr.syn, true);
+
+    // Bob answers with a RST that acknowledges the SYN.
+    bob.receive(syn)?;
+    let rst: DemiBuffer = bob.pop_frame();
+    let rst_hdr: TcpHeader = parse_tcp_header(rst.clone())?;
+    crate::ensure_eq!(rst_hdr.rst, true);
+    crate::ensure_eq!(rst_hdr.ack, true);
+    crate::ensure_eq!(rst_hdr.ack_num, syn_hdr.seq_num + SeqNumber::from(1));
+    crate::ensure_eq!(rst_hdr.src_port, bob_addr.port());
+    crate::ensure_eq!(rst_hdr.dst_port, syn_hdr.src_port);
+
+    // Alice gives up on the connection.
+    alice.receive(rst)?;
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ECONNREFUSED => {},
+        _ => anyhow::bail!("connect should fail with ECONNREFUSED"),
+    };
+
+    Ok(())
+}
+
+/// Closes a listener and checks that the coroutine that served it goes away with it.
+#[test]
+fn tcp_close_listener_stops_background_task() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let occupied_slots: usize = bob.get_runtime().slab_stats().occupied_slots;
+
+    // Bob listens, which starts a coroutine for the listener.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    crate::ensure_eq!(bob.get_runtime().slab_stats().occupied_slots, occupied_slots + 1);
+
+    // Bob closes the listener, which stops that coroutine.
+    let close_qt: QToken = bob.tcp_async_close(listen_qd)?;
+    match bob.wait(close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    crate::ensure_eq!(bob.get_runtime().slab_stats().occupied_slots, occupied_slots);
+
+    Ok(())
+}
+
+/// Closes listeners whose coroutines are still waiting to be polled in the sweep that runs the close, including one
+/// whose coroutine comes after the close in that sweep, and one with a pending accept.
+#[test]
+fn tcp_close_listener_with_ready_background_task() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+
+    // The slots of the first listener are reused by the second one, in the opposite order, so that the coroutine of
+    // the second listener is polled after its close.
+    for port in [80, 81] {
+        let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, port);
+        let listen_qd: QDesc = bob.tcp_socket()?;
+        bob.tcp_bind(listen_qd, bob_addr)?;
+        bob.tcp_listen(listen_qd, 1)?;
+        let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+        let close_qt: QToken = bob.tcp_async_close(listen_qd)?;
+        match bob.wait(close_qt)? {
+            (_, OperationResult::Close) => {},
+            _ => anyhow::bail!("close should succeed"),
+        };
+        match bob.wait(accept_qt)? {
+            (_, OperationResult::Failed(e)) if e.errno == libc::EBADF => {},
+            (_, result) => anyhow::bail!("accept should fail once the listener closes (result={:?})", result),
+        };
+    }
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Extracts the TCP header of an Ethernet frame.
+fn parse_tcp_header(frame: DemiBuffer) -> Result<TcpHeader> {
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    Ok(tcp_hdr)
+}
@@ -0,0 +1,136 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::consts::DEFAULT_DELAYED_ACK_TIMEOUT,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Size of the segments that the tests send.
+const SEGMENT_SIZE: usize = 100;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that connections without congestion control do not report a slow start threshold, and that sockets that
+/// are not connected report no congestion state at all.
+#[test]
+fn tcp_congestion_state_without_congestion_control() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+
+    let qd: QDesc = alice.tcp_socket()?;
+    crate::ensure_eq!(alice.cwnd(qd)?, None);
+    crate::ensure_eq!(alice.ssthresh(qd)?, None);
+
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    crate::ensure_eq!(alice.cwnd(alice_qd)?, Some(u32::MAX));
+    crate::ensure_eq!(alice.ssthresh(alice_qd)?, Some(u32::MAX));
+
+    Ok(())
+}
+
+/// Grows the congestion window of a connection in slow start, then loses a segment on the wire. The duplicate ACKs
+/// that the receiver sends for the segments that follow it make the sender shrink its congestion window and lower its
+/// slow start threshold to match.
+#[test]
+fn tcp_congestion_window_slow_start_and_loss() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    alice.get_transport().set_congestion_control(true);
+    bob.get_transport().set_congestion_control(true);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let initial_cwnd: u32 = match alice.cwnd(alice_qd)? {
+        Some(cwnd) => cwnd,
+        None => anyhow::bail!("connected socket should have a congestion window"),
+    };
+    crate::ensure_eq!(alice.ssthresh(alice_qd)?, Some(u32::MAX));
+
+    // In slow start, every acknowledged segment grows the congestion window by its size.
+    const NUM_ROUNDS: u32 = 4;
+    for i in 1..=NUM_ROUNDS {
+        push(&mut alice, alice_qd)?;
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+        now += DEFAULT_DELAYED_ACK_TIMEOUT;
+        alice.advance_clock(now);
+        bob.advance_clock(now);
+        bob.poll();
+        alice.receive(bob.pop_frame())?;
+        crate::ensure_eq!(alice.cwnd(alice_qd)?, Some(initial_cwnd + i * SEGMENT_SIZE as u32));
+    }
+    let grown_cwnd: u32 = initial_cwnd + NUM_ROUNDS * SEGMENT_SIZE as u32;
+
+    // Drop one segment and let the three that follow it through. Bob acknowledges each of them right away, with the
+    // sequence number of the dropped segment.
+    for _ in 0..4 {
+        push(&mut alice, alice_qd)?;
+    }
+    let mut segments: VecDeque<DemiBuffer> = alice.pop_all_frames();
+    crate::ensure_eq!(segments.len(), 4);
+    segments.pop_front();
+    for segment in segments {
+        bob.receive(segment)?;
+    }
+    let dup_acks: VecDeque<DemiBuffer> = bob.pop_all_frames();
+    crate::ensure_eq!(dup_acks.len(), 3);
+
+    // The third duplicate ACK shrinks the congestion window, and the slow start threshold follows it.
+    for dup_ack in dup_acks {
+        alice.receive(dup_ack)?;
+    }
+    let reduced_cwnd: u32 = match alice.cwnd(alice_qd)? {
+        Some(cwnd) => cwnd,
+        None => anyhow::bail!("connected socket should have a congestion window"),
+    };
+    crate::ensure_eq!(reduced_cwnd < grown_cwnd, true);
+    crate::ensure_eq!(reduced_cwnd >= grown_cwnd / 2, true);
+    crate::ensure_eq!(alice.ssthresh(alice_qd)?, Some(reduced_cwnd));
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Pushes one segment's worth of data on a connection.
+fn push(engine: &mut SharedEngine, qd: QDesc) -> Result<()> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; SEGMENT_SIZE][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = engine.tcp_push(qd, buf)?;
+    match engine.wait(push_qt)? {
+        (_, OperationResult::Push) => Ok(()),
+        _ => anyhow::bail!("push should succeed"),
+    }
+}
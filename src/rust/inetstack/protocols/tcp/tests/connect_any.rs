@@ -0,0 +1,185 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::{
+                EtherType2,
+                Ethernet2Header,
+            },
+            ipv4::Ipv4Header,
+            tcp::segment::TcpHeader,
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    net::SocketAddrV4,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Time between the starts of two connection attempts.
+const STAGGER: Duration = Duration::from_secs(1);
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Connects to a black hole and to a listening socket. The attempt to the black hole gets no answer, so the attempt to
+/// the listening socket starts a stagger later and wins, and the attempt to the black hole is aborted.
+#[test]
+fn tcp_connect_any_skips_black_hole() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let mut bob: SharedEngine = test_helpers::new_bob2(t0);
+    let black_hole: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 8080);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+
+    // The first attempt goes to the black hole, which drops its SYN.
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.connect_any(alice_qd, vec![black_hole.into(), bob_addr.into()], STAGGER)?;
+    alice.poll();
+    let syns: Vec<TcpHeader> = tcp_headers(alice.pop_all_frames())?;
+    crate::ensure_eq!(syns.len(), 1);
+    crate::ensure_eq!(syns[0].syn, true);
+    crate::ensure_eq!(syns[0].dst_port, black_hole.port());
+
+    // The second attempt starts a stagger later, and Bob accepts it before the turn of a third would have come.
+    let t1: Instant = t0 + STAGGER;
+    alice.advance_clock(t1);
+    bob.advance_clock(t1);
+    alice.poll();
+    let syn: DemiBuffer = alice.pop_frame();
+    let syn_hdr: TcpHeader = tcp_headers(VecDeque::from([syn.clone()]))?.remove(0);
+    crate::ensure_eq!(syn_hdr.syn, true);
+    crate::ensure_eq!(syn_hdr.dst_port, bob_addr.port());
+    bob.receive(syn)?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect_any should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    // The queue of Alice carries the connection of the attempt that won.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    let pop_qt: QToken = bob.pop(bob_qd, None)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    // The attempt to the black hole was aborted, so it never retransmits its SYN.
+    let t2: Instant = t1 + Duration::from_secs(60);
+    alice.advance_clock(t2);
+    alice.poll();
+    for hdr in tcp_headers(alice.pop_all_frames())? {
+        crate::ensure_eq!(hdr.syn, false);
+    }
+
+    Ok(())
+}
+
+/// Drops a connect_any() while its attempt is in flight and checks that the attempt is aborted along with it.
+#[test]
+fn tcp_dropped_connect_any_aborts_attempts() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let black_hole: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 8080);
+
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.connect_any(alice_qd, vec![black_hole.into()], STAGGER)?;
+    alice.poll();
+    let syns: Vec<TcpHeader> = tcp_headers(alice.pop_all_frames())?;
+    crate::ensure_eq!(syns.len(), 1);
+    crate::ensure_eq!(syns[0].syn, true);
+
+    // Once the connect_any() is gone, nothing retransmits the SYN and the port of the attempt is free again.
+    alice.get_runtime().remove_background_coroutine(connect_qt)?;
+    let t1: Instant = t0 + Duration::from_secs(60);
+    alice.advance_clock(t1);
+    alice.poll();
+    for hdr in tcp_headers(alice.pop_all_frames())? {
+        crate::ensure_eq!(hdr.syn, false);
+    }
+    let qd: QDesc = alice.tcp_socket()?;
+    alice.tcp_bind(qd, SocketAddrV4::new(test_helpers::ALICE_IPV4, syns[0].src_port))?;
+
+    Ok(())
+}
+
+/// Checks that connect_any() needs somewhere to connect to.
+#[test]
+fn tcp_connect_any_without_remotes() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    match alice.connect_any(alice_qd, vec![], STAGGER) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("connect_any without remotes should fail with EINVAL"),
+    };
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Extracts the TCP headers of the IPv4 frames among `frames`.
+fn tcp_headers(frames: VecDeque<DemiBuffer>) -> Result<Vec<TcpHeader>> {
+    let mut headers: Vec<TcpHeader> = Vec::new();
+    for frame in frames {
+        let (eth_hdr, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+        if eth_hdr.ether_type() != EtherType2::Ipv4 {
+            continue;
+        }
+        let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+        let (tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+        headers.push(tcp_hdr);
+    }
+    Ok(headers)
+}
@@ -0,0 +1,113 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::segment::TcpHeader,
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Steps through the three-way handshake and checks that the connect token completes exactly when the connection is
+/// established: after the SYN+ACK was received and acknowledged, and not any earlier.
+#[test]
+fn tcp_connect_completes_at_established() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+    // Sending the SYN does not complete the connect.
+    alice.poll();
+    let syn: DemiBuffer = alice.pop_frame();
+    crate::ensure_eq!(parse_tcp_header(syn.clone())?.syn, true);
+    crate::ensure_eq!(alice.get_runtime().has_completed(connect_qt)?, false);
+
+    // Neither does Bob answering, as long as Alice has not received the SYN+ACK.
+    bob.receive(syn)?;
+    bob.poll();
+    let syn_ack: DemiBuffer = bob.pop_frame();
+    alice.poll();
+    crate::ensure_eq!(alice.get_runtime().has_completed(connect_qt)?, false);
+
+    // Receiving the SYN+ACK establishes the connection, and the connect completes along with the ACK going out.
+    alice.receive(syn_ack)?;
+    alice.poll();
+    crate::ensure_eq!(alice.get_runtime().has_completed(connect_qt)?, true);
+    let ack: DemiBuffer = alice.pop_frame();
+    let ack_hdr: TcpHeader = parse_tcp_header(ack.clone())?;
+    crate::ensure_eq!(ack_hdr.syn, false);
+    crate::ensure_eq!(ack_hdr.ack, true);
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+
+    // The connection is usable right away.
+    bob.receive(ack)?;
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received.len(), 32),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Extracts the TCP header of a frame.
+fn parse_tcp_header(frame: DemiBuffer) -> Result<TcpHeader> {
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    Ok(tcp_hdr)
+}
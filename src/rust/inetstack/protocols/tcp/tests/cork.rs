@@ -0,0 +1,172 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::{
+            SocketOption,
+            SocketOptionName,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Size of requests and of the pieces of responses.
+const MESSAGE_SIZE: usize = 100;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that a corked connection holds small pushes back and sends them coalesced in one segment once uncorked.
+#[test]
+fn tcp_cork_coalesces_pushes() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    alice.set_socket_option(alice_qd, SocketOption::Cork(true))?;
+
+    for _ in 0..3 {
+        push(&mut alice, alice_qd)?;
+    }
+    crate::ensure_eq!(payload_lengths(&mut alice)?, vec![]);
+
+    alice.set_socket_option(alice_qd, SocketOption::Cork(false))?;
+    alice.poll();
+    crate::ensure_eq!(payload_lengths(&mut alice)?, vec![3 * MESSAGE_SIZE]);
+
+    Ok(())
+}
+
+/// Checks that with auto-flush enabled, a response pushed after the pop of its request goes out right away, although
+/// the connection is corked and the next pop already started, and that later pushes are held back again.
+#[test]
+fn tcp_auto_flush_after_pop() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    bob.set_socket_option(bob_qd, SocketOption::Cork(true))?;
+    bob.set_socket_option(bob_qd, SocketOption::AutoFlushAfterPop(true))?;
+
+    // Send a request to Bob, who pops it.
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    push(&mut alice, alice_qd)?;
+    bob.receive(alice.pop_frame())?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, buf)) => crate::ensure_eq!(buf.len(), MESSAGE_SIZE),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    // Bob waits for the next request before he responds, and the response goes out without Bob uncorking the
+    // connection.
+    let _: QToken = bob.tcp_pop(bob_qd)?;
+    bob.poll();
+    push(&mut bob, bob_qd)?;
+    crate::ensure_eq!(payload_lengths(&mut bob)?, vec![MESSAGE_SIZE]);
+
+    // Once the response went out, the connection is corked again.
+    push(&mut bob, bob_qd)?;
+    crate::ensure_eq!(payload_lengths(&mut bob)?, vec![]);
+
+    Ok(())
+}
+
+/// Checks that a connection accepted on a corked listening socket is corked too.
+#[test]
+fn tcp_accept_inherits_cork() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Bob corks the listening socket, and then accepts a connection from Alice.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.set_socket_option(listen_qd, SocketOption::Cork(true))?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    // The accepted connection holds small pushes back.
+    crate::ensure_eq!(
+        bob.get_socket_option(bob_qd, SocketOptionName::NoDelay)?,
+        SocketOption::NoDelay(false)
+    );
+    push(&mut bob, bob_qd)?;
+    crate::ensure_eq!(payload_lengths(&mut bob)?, vec![]);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Pushes a message on `qd` and lets the engine run, without waiting for the push to complete.
+fn push(engine: &mut SharedEngine, qd: QDesc) -> Result<()> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; MESSAGE_SIZE][..]).expect("slice should fit in DemiBuffer");
+    let _: QToken = engine.tcp_push(qd, buf)?;
+    engine.poll();
+    Ok(())
+}
+
+/// Takes the frames that `engine` sent, and returns the lengths of their TCP payloads.
+fn payload_lengths(engine: &mut SharedEngine) -> Result<Vec<usize>> {
+    let mut lengths: Vec<usize> = Vec::new();
+    for frame in engine.pop_all_frames() {
+        let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+        let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+        let (_, payload): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+        lengths.push(payload.len());
+    }
+    Ok(lengths)
+}
@@ -0,0 +1,125 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demi_sgarray_t,
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::{
+            DemiBuffer,
+            MemoryRuntime,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Time from the start of the test to the deadline of the operations.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Issues a pop that nothing arrives for and checks that it keeps waiting until the clock reaches its deadline, and
+/// fails with ETIMEDOUT right then.
+#[test]
+fn tcp_pop_until_times_out_at_deadline() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let mut bob: SharedEngine = test_helpers::new_bob2(t0);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let deadline: Instant = t0 + TIMEOUT;
+    let pop_qt: QToken = alice.pop_until(alice_qd, None, deadline)?;
+    alice.advance_clock(deadline - Duration::from_nanos(1));
+    alice.poll();
+    crate::ensure_eq!(alice.get_runtime().has_completed(pop_qt)?, false);
+
+    alice.advance_clock(deadline);
+    match alice.wait(pop_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ETIMEDOUT => {},
+        _ => anyhow::bail!("pop should fail with ETIMEDOUT"),
+    };
+
+    Ok(())
+}
+
+/// Connects to a black hole and checks that the connect fails with ETIMEDOUT exactly at its deadline.
+#[test]
+fn tcp_connect_until_times_out_at_deadline() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let black_hole: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 8080);
+
+    let deadline: Instant = t0 + TIMEOUT;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.connect_until(alice_qd, black_hole.into(), deadline)?;
+    alice.poll();
+    alice.advance_clock(deadline - Duration::from_nanos(1));
+    alice.poll();
+    crate::ensure_eq!(alice.get_runtime().has_completed(connect_qt)?, false);
+
+    alice.advance_clock(deadline);
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ETIMEDOUT => {},
+        _ => anyhow::bail!("connect should fail with ETIMEDOUT"),
+    };
+
+    Ok(())
+}
+
+/// Checks that a push that completes before its deadline succeeds, and that the deadline passing afterwards does not
+/// disturb the connection.
+#[test]
+fn tcp_push_until_before_deadline() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let mut bob: SharedEngine = test_helpers::new_bob2(t0);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let deadline: Instant = t0 + TIMEOUT;
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let sga: demi_sgarray_t = alice.get_transport().into_sgarray(buf.clone())?;
+    let push_qt: QToken = alice.push_until(alice_qd, &sga, deadline)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+
+    alice.advance_clock(deadline);
+    bob.advance_clock(deadline);
+    bob.receive(alice.pop_frame())?;
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                tests::connection_setup,
+                SeqNumber,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that a receiver with a custom delayed ACK timeout sends a standalone ACK once that timeout elapses.
+#[test]
+fn tcp_delayed_ack_timeout() -> Result<()> {
+    const DELAYED_ACK_TIMEOUT: Duration = Duration::from_millis(100);
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    bob.get_transport().set_delayed_ack_timeout(DELAYED_ACK_TIMEOUT)?;
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Send one segment to Bob, who has no data to send back.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let segment: DemiBuffer = alice.pop_frame();
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(segment.clone())?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (data_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    bob.receive(segment)?;
+
+    // Bob should hold the ACK until the delayed ACK timeout elapses.
+    bob.poll();
+    now += DELAYED_ACK_TIMEOUT - Duration::from_millis(1);
+    bob.advance_clock(now);
+    bob.poll();
+    crate::ensure_eq!(bob.pop_all_frames().len(), 0);
+
+    now += Duration::from_millis(1);
+    bob.advance_clock(now);
+    bob.poll();
+    let mut frames: VecDeque<DemiBuffer> = bob.pop_all_frames();
+    crate::ensure_eq!(frames.len(), 1);
+    let ack: DemiBuffer = frames.pop_front().expect("should be one frame");
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(ack)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (ack_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    crate::ensure_eq!(ack_hdr.ack, true);
+    crate::ensure_eq!(ack_hdr.ack_num, data_hdr.seq_num + SeqNumber::from(buf.len() as u32));
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demikernel::libos::network::queue::SharedNetworkQueue,
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+            SharedTestRuntime,
+        },
+        SharedInetStack,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Types
+//======================================================================================================================
+
+type TestQueue = SharedNetworkQueue<SharedInetStack<SharedTestRuntime>>;
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Payload bytes sent at every step.
+const BYTES_PER_STEP: usize = 1000;
+
+/// Time between steps.
+const STEP: Duration = Duration::from_millis(100);
+
+/// Rate, in bits per second, at which Bob acknowledges the steps, given the default rate window of one second.
+const ACK_RATE_BPS: u64 = (BYTES_PER_STEP as u64 * 8 * 1000) / STEP.as_millis() as u64;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Sends a segment at every step to a peer that acknowledges each of them a step later, and checks that the delivery
+/// rate estimate climbs to the rate of the acknowledgements as the window fills, and then stays there.
+#[test]
+fn tcp_delivery_rate_converges_to_ack_rate() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let alice_queue: TestQueue = alice.get_runtime().get_shared_queue(&alice_qd)?;
+
+    // Nothing was acknowledged yet.
+    crate::ensure_eq!(alice_queue.delivery_rate_bps(), 0);
+
+    let mut last_rate: u64 = 0;
+    for step in 0..30 {
+        send_step(&mut alice, &mut bob, alice_qd)?;
+        now += STEP;
+        advance_both(&mut alice, &mut bob, now)?;
+
+        let rate: u64 = alice_queue.delivery_rate_bps();
+        if step < 9 {
+            // The estimate grows with every acknowledged step until the window holds ten of them.
+            crate::ensure_eq!(rate > last_rate, true);
+            crate::ensure_eq!(rate < ACK_RATE_BPS, true);
+        } else {
+            ensure_close(rate, ACK_RATE_BPS)?;
+        }
+        last_rate = rate;
+    }
+
+    Ok(())
+}
+
+/// Measures a delivery rate with pacing enabled, then pushes two segments at once, and checks that the sender spaces
+/// them out at the pacing rate rather than sending them back to back.
+#[test]
+fn tcp_pacing_spaces_out_segments() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    alice.get_transport().set_pacing(true);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let alice_queue: TestQueue = alice.get_runtime().get_shared_queue(&alice_qd)?;
+
+    // Segments go out unpaced until the delivery rate was measured over a full window, which the first acknowledgement
+    // starts a step in.
+    for _ in 0..11 {
+        send_step(&mut alice, &mut bob, alice_qd)?;
+        now += STEP;
+        advance_both(&mut alice, &mut bob, now)?;
+    }
+    ensure_close(alice_queue.delivery_rate_bps(), ACK_RATE_BPS)?;
+
+    // Of two segments pushed back to back, the second goes out as long after the first as the first takes at 5/4 of
+    // the delivery rate. This is well within the retransmission timeout, so nothing else goes out meanwhile.
+    let gap: Duration = Duration::from_secs_f64((BYTES_PER_STEP * 8) as f64 / (ACK_RATE_BPS as f64 * 1.25));
+    for _ in 0..2 {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[0; BYTES_PER_STEP][..]).expect("slice should fit");
+        alice.tcp_push(alice_qd, buf)?;
+    }
+    alice.poll();
+    crate::ensure_eq!(alice.pop_all_frames().len(), 1);
+    now += gap / 2;
+    alice.advance_clock(now);
+    alice.poll();
+    crate::ensure_eq!(alice.pop_all_frames().len(), 0);
+    now += gap - gap / 2;
+    alice.advance_clock(now);
+    alice.poll();
+    crate::ensure_eq!(alice.pop_all_frames().len(), 1);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Pushes one step of data from Alice and has Bob take it in, so that Bob acknowledges it once his delayed ACK timer
+/// expires.
+fn send_step(alice: &mut SharedEngine, bob: &mut SharedEngine, alice_qd: QDesc) -> Result<()> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0; BYTES_PER_STEP][..]).expect("slice should fit");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    Ok(())
+}
+
+/// Moves the clocks of both ends to `now` and delivers the acknowledgements that Bob sends meanwhile.
+fn advance_both(alice: &mut SharedEngine, bob: &mut SharedEngine, now: Instant) -> Result<()> {
+    alice.advance_clock(now);
+    bob.advance_clock(now);
+    bob.poll();
+    for frame in bob.pop_all_frames() {
+        alice.receive(frame)?;
+    }
+    alice.poll();
+    Ok(())
+}
+
+/// Checks that `rate` is within one percent of `expected`.
+fn ensure_close(rate: u64, expected: u64) -> Result<()> {
+    if rate.abs_diff(expected) * 100 > expected {
+        anyhow::bail!("rate should be close to {} bps (rate={} bps)", expected, rate);
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::tests::connection_setup,
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that outgoing segments carry the DSCP set on the socket, without touching the ECN bits.
+#[test]
+fn tcp_dscp() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    crate::ensure_eq!(alice.dscp(alice_qd)?, 0);
+
+    // Expedited Forwarding.
+    const DSCP: u8 = 46;
+    alice.set_socket_option(alice_qd, SocketOption::Dscp(DSCP))?;
+    crate::ensure_eq!(alice.dscp(alice_qd)?, DSCP);
+
+    // Values that do not fit in the DSCP bits are rejected.
+    match alice.set_socket_option(alice_qd, SocketOption::Dscp(DSCP << 2)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("setting an oversized dscp should fail with EINVAL"),
+    };
+    crate::ensure_eq!(alice.dscp(alice_qd)?, DSCP);
+
+    // Alice's data segment is marked.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 64][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let frame: DemiBuffer = alice.pop_frame();
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame.clone())?;
+    let (ipv4_hdr, _): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    crate::ensure_eq!(ipv4_hdr.get_dscp(), DSCP);
+    crate::ensure_eq!(ipv4_hdr.get_ecn(), 0);
+
+    // Bob's ACK is not.
+    bob.set_socket_option(bob_qd, SocketOption::QuickAck(true))?;
+    bob.receive(frame)?;
+    bob.poll();
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(bob.pop_frame())?;
+    let (ipv4_hdr, _): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    crate::ensure_eq!(ipv4_hdr.get_dscp(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::{
+        SocketAddr,
+        SocketAddrV4,
+    },
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Establishes a connection with a regular three-way handshake and checks that neither end reports using TCP Fast Open.
+#[test]
+fn tcp_used_fast_open_normal_connection() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    crate::ensure_eq!(alice.used_fast_open(alice_qd)?, false);
+    crate::ensure_eq!(bob.used_fast_open(bob_qd)?, false);
+
+    Ok(())
+}
+
+/// Connects with data twice to a server that takes part in TCP Fast Open. The first connection has no cookie and falls
+/// back to a regular handshake, but obtains one. The second one uses the cached cookie to send its data in the SYN,
+/// which the server delivers on the first pop.
+#[test]
+fn tcp_fast_open_with_cached_cookie() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    bob.get_transport().set_fast_open(true);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 2)?;
+
+    // Without a cookie, the data follows a regular handshake.
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connect_with_data(&mut alice, &mut bob, listen_qd, b"first")?;
+    crate::ensure_eq!(alice.used_fast_open(alice_qd)?, false);
+    crate::ensure_eq!(bob.used_fast_open(bob_qd)?, false);
+    crate::ensure_eq!(pop(&mut bob, bob_qd)?[..], b"first"[..]);
+
+    // With the cookie from the first connection, the data goes in the SYN.
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connect_with_data(&mut alice, &mut bob, listen_qd, b"second")?;
+    crate::ensure_eq!(alice.used_fast_open(alice_qd)?, true);
+    crate::ensure_eq!(bob.used_fast_open(bob_qd)?, true);
+    crate::ensure_eq!(pop(&mut bob, bob_qd)?[..], b"second"[..]);
+
+    Ok(())
+}
+
+/// Connects with data without any cookie from a server that does not take part in TCP Fast Open, and checks that the
+/// data is still delivered after a regular handshake.
+#[test]
+fn tcp_fast_open_fallback() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 2)?;
+
+    for _ in 0..2 {
+        let (alice_qd, bob_qd): (QDesc, QDesc) = connect_with_data(&mut alice, &mut bob, listen_qd, b"data")?;
+        crate::ensure_eq!(alice.used_fast_open(alice_qd)?, false);
+        crate::ensure_eq!(bob.used_fast_open(bob_qd)?, false);
+        crate::ensure_eq!(pop(&mut bob, bob_qd)?[..], b"data"[..]);
+    }
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Connects Alice to Bob, who listens on `listen_qd`, with `data`, and hand-delivers every segment until both ends
+/// are connected.
+fn connect_with_data(
+    alice: &mut SharedEngine,
+    bob: &mut SharedEngine,
+    listen_qd: QDesc,
+    data: &[u8],
+) -> Result<(QDesc, QDesc)> {
+    let bob_addr: SocketAddr = SocketAddr::V4(SocketAddrV4::new(test_helpers::BOB_IPV4, 80));
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let initial: DemiBuffer = DemiBuffer::from_slice(data).expect("slice should fit in DemiBuffer");
+    let connect_qt: QToken = alice.connect_with_data(alice_qd, bob_addr, initial)?;
+
+    // SYN, SYN+ACK, and ACK, followed by the data if it did not fit in the SYN.
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    let alice_qd: QDesc = match alice.wait(connect_qt)? {
+        (qd, OperationResult::Connect) => qd,
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    alice.poll();
+    for frame in alice.pop_all_frames() {
+        bob.receive(frame)?;
+    }
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    Ok((alice_qd, bob_qd))
+}
+
+/// Pops the next message from `qd`.
+fn pop(engine: &mut SharedEngine, qd: QDesc) -> Result<DemiBuffer> {
+    let pop_qt: QToken = engine.tcp_pop(qd)?;
+    match engine.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, buf)) => Ok(buf),
+        _ => anyhow::bail!("pop should succeed"),
+    }
+}
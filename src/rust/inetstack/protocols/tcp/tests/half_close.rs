@@ -0,0 +1,147 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::{
+            socket::operation::Shutdown,
+            types::TcpState,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Pushes `bytes` on `qd` and waits for the push to complete.
+fn push(engine: &mut SharedEngine, qd: QDesc, bytes: &[u8]) -> Result<()> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(bytes).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = engine.tcp_push(qd, buf)?;
+    match engine.wait(push_qt)? {
+        (_, OperationResult::Push) => Ok(()),
+        _ => anyhow::bail!("push should succeed"),
+    }
+}
+
+/// Pops from `qd` and returns the bytes that were popped.
+fn pop(engine: &mut SharedEngine, qd: QDesc) -> Result<Vec<u8>> {
+    let pop_qt: QToken = engine.tcp_pop(qd)?;
+    match engine.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, buf)) => Ok(buf[..].to_vec()),
+        _ => anyhow::bail!("pop should succeed"),
+    }
+}
+
+/// Starts closing `qd` and sends the resulting FIN, if any.
+fn close(engine: &mut SharedEngine, qd: QDesc) -> Result<QToken> {
+    let close_qt: QToken = engine.tcp_async_close(qd)?;
+    engine.poll();
+    Ok(close_qt)
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Has Bob write a response and shut down the write side of the connection, and checks that he can no longer push
+/// but still receives what Alice sends until both close.
+#[test]
+fn tcp_shutdown_write_keeps_reading() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Bob writes the response and signals the end of it.
+    push(&mut bob, bob_qd, b"response")?;
+    bob.shutdown(bob_qd, Shutdown::Write)?;
+    bob.poll();
+    crate::ensure_eq!(bob.tcp_state(bob_qd)?, Some(TcpState::FinWait1));
+    let buf: DemiBuffer = DemiBuffer::from_slice(b"more").expect("slice should fit in DemiBuffer");
+    match bob.tcp_push(bob_qd, buf) {
+        Err(e) if e.errno == libc::EPIPE => {},
+        _ => anyhow::bail!("push after a write shutdown should fail with EPIPE"),
+    };
+
+    // Alice sends a request before the response reaches her.
+    push(&mut alice, alice_qd, b"request")?;
+
+    // Alice reads the response up to its end.
+    for frame in bob.pop_all_frames() {
+        alice.receive(frame)?;
+    }
+    alice.poll();
+    crate::ensure_eq!(pop(&mut alice, alice_qd)?, b"response".to_vec());
+    crate::ensure_eq!(pop(&mut alice, alice_qd)?, Vec::<u8>::new());
+
+    // Bob still reads the request.
+    for frame in alice.pop_all_frames() {
+        bob.receive(frame)?;
+    }
+    bob.poll();
+    crate::ensure_eq!(pop(&mut bob, bob_qd)?, b"request".to_vec());
+    crate::ensure_eq!(bob.tcp_state(bob_qd)?, Some(TcpState::FinWait2));
+
+    // Closing both ends still succeeds.
+    let alice_close_qt: QToken = close(&mut alice, alice_qd)?;
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    crate::ensure_eq!(bob.tcp_state(bob_qd)?, Some(TcpState::TimeWait));
+    let bob_close_qt: QToken = close(&mut bob, bob_qd)?;
+    match bob.wait(bob_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    alice.receive(bob.pop_frame())?;
+    match alice.wait(alice_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+
+    Ok(())
+}
+
+/// Checks that sockets that are not connected cannot be shut down.
+#[test]
+fn tcp_shutdown_not_connected() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let qd: QDesc = bob.tcp_socket()?;
+    match bob.shutdown(qd, Shutdown::Both) {
+        Err(e) if e.errno == libc::ENOTCONN => {},
+        _ => anyhow::bail!("shutting down an unbound socket should fail with ENOTCONN"),
+    };
+    bob.tcp_bind(qd, bob_addr)?;
+    bob.tcp_listen(qd, 8)?;
+    match bob.shutdown(qd, Shutdown::Write) {
+        Err(e) if e.errno == libc::ENOTCONN => {},
+        _ => anyhow::bail!("shutting down a listening socket should fail with ENOTCONN"),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::{
+            socket::{
+                operation::Shutdown,
+                option::{
+                    HalfClosedReadPolicy,
+                    SocketOption,
+                },
+            },
+            types::TcpSeqState,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of bytes that Bob sends after Alice shut down the read side of the connection.
+const NUM_BYTES: usize = 32;
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Connects Alice to Bob, shuts down the read side of Alice's end with `policy`, and has Bob send data to Alice.
+/// Returns the frames that Alice sends in response.
+fn send_after_read_shutdown(
+    policy: HalfClosedReadPolicy,
+) -> Result<(SharedEngine, SharedEngine, QDesc, QDesc, VecDeque<DemiBuffer>)> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    alice.set_socket_option(alice_qd, SocketOption::HalfClosedReadPolicy(policy))?;
+    alice.shutdown(alice_qd, Shutdown::Read)?;
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; NUM_BYTES][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = bob.tcp_push(bob_qd, buf)?;
+    match bob.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+
+    // Let the delayed ACK timer fire, if there is one.
+    now += Duration::from_secs(1);
+    alice.advance_clock(now);
+    alice.poll();
+    let frames: VecDeque<DemiBuffer> = alice.pop_all_frames();
+    Ok((alice, bob, alice_qd, bob_qd, frames))
+}
+
+/// Parses the TCP header of `frame`.
+fn tcp_header(frame: DemiBuffer) -> Result<TcpHeader> {
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    Ok(tcp_hdr)
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that data received after a read shutdown is acknowledged and dropped by default.
+#[test]
+fn tcp_half_closed_read_discard() -> Result<()> {
+    let (mut alice, mut bob, alice_qd, bob_qd, mut frames): (
+        SharedEngine,
+        SharedEngine,
+        QDesc,
+        QDesc,
+        VecDeque<DemiBuffer>,
+    ) = send_after_read_shutdown(HalfClosedReadPolicy::Discard)?;
+
+    // Alice acknowledges the data without resetting the connection.
+    crate::ensure_eq!(frames.len(), 1);
+    let ack: DemiBuffer = frames.pop_front().expect("should be one frame");
+    crate::ensure_eq!(tcp_header(ack.clone())?.rst, false);
+    bob.receive(ack)?;
+    let alice_state: Option<TcpSeqState> = alice.tcp_sequence_state(alice_qd)?;
+    let bob_state: Option<TcpSeqState> = bob.tcp_sequence_state(bob_qd)?;
+    let (Some(alice_state), Some(bob_state)) = (alice_state, bob_state) else {
+        anyhow::bail!("connected sockets should have a sequence state")
+    };
+    crate::ensure_eq!(bob_state.snd_una, bob_state.snd_nxt);
+    crate::ensure_eq!(alice_state.rcv_nxt, bob_state.snd_nxt);
+
+    // The data was dropped, so there is nothing for the application to pop.
+    let pop_qt: QToken = alice.tcp_pop(alice_qd)?;
+    alice.poll();
+    crate::ensure_eq!(alice.get_runtime().has_completed(pop_qt)?, false);
+
+    Ok(())
+}
+
+/// Checks that data received after a read shutdown resets the connection when asked to.
+#[test]
+fn tcp_half_closed_read_reset() -> Result<()> {
+    let (_, _, _, _, mut frames): (SharedEngine, SharedEngine, QDesc, QDesc, VecDeque<DemiBuffer>) =
+        send_after_read_shutdown(HalfClosedReadPolicy::Reset)?;
+
+    crate::ensure_eq!(frames.len(), 1);
+    let rst: TcpHeader = tcp_header(frames.pop_front().expect("should be one frame"))?;
+    crate::ensure_eq!(rst.rst, true);
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::QDesc,
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Starts several handshakes without completing them and checks that the listener counts each of them as half-open.
+#[test]
+fn tcp_half_open_count() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let listen_qd: QDesc = listen(&mut bob)?;
+    crate::ensure_eq!(bob.half_open_count(listen_qd)?, 0);
+
+    let syns: VecDeque<DemiBuffer> = send_syns(&mut alice, 3)?;
+    for syn in syns {
+        bob.receive(syn)?;
+    }
+    bob.poll();
+    crate::ensure_eq!(bob.half_open_count(listen_qd)?, 3);
+    crate::ensure_eq!(bob.pop_all_frames().len(), 3);
+
+    // Only listening sockets have half-open connections.
+    let qd: QDesc = bob.tcp_socket()?;
+    match bob.half_open_count(qd) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("half_open_count() should fail on sockets that are not listening"),
+    };
+
+    Ok(())
+}
+
+/// Sends more SYNs than the half-open limit of a listener and checks that the extra ones are dropped without reply.
+#[test]
+fn tcp_half_open_limit() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    bob.get_transport().set_max_half_open(Some(2));
+    let listen_qd: QDesc = listen(&mut bob)?;
+
+    let syns: VecDeque<DemiBuffer> = send_syns(&mut alice, 3)?;
+    for syn in syns {
+        bob.receive(syn)?;
+    }
+    bob.poll();
+    crate::ensure_eq!(bob.half_open_count(listen_qd)?, 2);
+    crate::ensure_eq!(bob.pop_all_frames().len(), 2);
+    crate::ensure_eq!(bob.drop_stats().buffer_full, 1);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Makes `bob` listen on port 80 with room for plenty of connections.
+fn listen(bob: &mut SharedEngine) -> Result<QDesc> {
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 16)?;
+    Ok(listen_qd)
+}
+
+/// Starts `count` connections from `alice` to Bob and returns the SYNs that she sent.
+fn send_syns(alice: &mut SharedEngine, count: usize) -> Result<VecDeque<DemiBuffer>> {
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    for _ in 0..count {
+        let qd: QDesc = alice.tcp_socket()?;
+        alice.tcp_connect(qd, bob_addr)?;
+    }
+    alice.poll();
+    let syns: VecDeque<DemiBuffer> = alice.pop_all_frames();
+    crate::ensure_eq!(syns.len(), count);
+    Ok(syns)
+}
@@ -0,0 +1,114 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                ConnTuple,
+                SeqNumber,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Initial sequence number that Alice picks for her connections.
+const ALICE_ISN: u32 = 0x1234_5678;
+
+/// Initial sequence number that Bob picks for his connections, near the end of the sequence space.
+const BOB_ISN: u32 = u32::MAX - 10;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Pins the initial sequence numbers of both ends of a connection, and checks that the SYN and the SYN+ACK carry them.
+#[test]
+fn tcp_isn_generator() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    alice
+        .get_transport()
+        .set_isn_generator(Box::new(move |tuple: &ConnTuple| {
+            assert_eq!(tuple.remote, bob_addr);
+            ALICE_ISN
+        }));
+
+    // Bob installs his generator after he starts listening.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    bob.get_transport().set_isn_generator(Box::new(|_: &ConnTuple| BOB_ISN));
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    let syn: DemiBuffer = alice.pop_frame();
+    crate::ensure_eq!(tcp_header(syn.clone())?.seq_num, SeqNumber::from(ALICE_ISN));
+    crate::ensure_eq!(alice.local_isn(alice_qd)?, Some(ALICE_ISN));
+
+    bob.receive(syn)?;
+    bob.poll();
+    let syn_ack: DemiBuffer = bob.pop_frame();
+    crate::ensure_eq!(tcp_header(syn_ack.clone())?.seq_num, SeqNumber::from(BOB_ISN));
+    alice.receive(syn_ack)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+    crate::ensure_eq!(alice.local_isn(alice_qd)?, Some(ALICE_ISN));
+    crate::ensure_eq!(bob.local_isn(bob_qd)?, Some(BOB_ISN));
+    crate::ensure_eq!(bob.local_isn(listen_qd)?, None);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Parses the TCP header of `frame`.
+fn tcp_header(frame: DemiBuffer) -> Result<TcpHeader> {
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    Ok(tcp_hdr)
+}
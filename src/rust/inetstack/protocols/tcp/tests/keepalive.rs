@@ -0,0 +1,133 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::{
+            consts::{
+                DEFAULT_KEEPALIVE_IDLE,
+                DEFAULT_KEEPALIVE_INTERVAL,
+                DEFAULT_KEEPALIVE_PROBES,
+            },
+            socket::option::SocketOption,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Enables keepalive on an established connection and checks that a probe goes out once it has been idle for the
+/// default idle time.
+#[test]
+fn tcp_keepalive_enabled() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    alice.set_socket_option(alice_qd, SocketOption::KeepAliveEnabled(true))?;
+
+    // Nothing should be sent before the connection has been idle long enough.
+    now += DEFAULT_KEEPALIVE_IDLE - Duration::from_secs(1);
+    alice.advance_clock(now);
+    alice.poll();
+    crate::ensure_eq!(alice.pop_all_frames().len(), 0);
+
+    // Alice should probe Bob once the idle time elapses.
+    now += Duration::from_secs(1);
+    alice.advance_clock(now);
+    alice.poll();
+    let mut frames: VecDeque<DemiBuffer> = alice.pop_all_frames();
+    crate::ensure_eq!(frames.len(), 1);
+    let probe: DemiBuffer = frames.pop_front().expect("should be one frame");
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(probe.clone())?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (probe_hdr, probe_data): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    crate::ensure_eq!(probe_hdr.ack, true);
+    crate::ensure_eq!(probe_data.len(), 0);
+
+    // Bob should answer the probe with an ACK for data that Alice has already sent.
+    bob.receive(probe)?;
+    bob.poll();
+    let mut frames: VecDeque<DemiBuffer> = bob.pop_all_frames();
+    crate::ensure_eq!(frames.len(), 1);
+    let reply: DemiBuffer = frames.pop_front().expect("should be one frame");
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(reply)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (reply_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    crate::ensure_eq!(reply_hdr.ack, true);
+    crate::ensure_eq!(reply_hdr.ack_num, probe_hdr.seq_num + 1.into());
+
+    Ok(())
+}
+
+/// Has Bob leave every keepalive probe of Alice unanswered, and checks that her pending pop fails with ETIMEDOUT once
+/// the last probe goes unanswered, and that her pushes fail with ETIMEDOUT after.
+#[test]
+fn tcp_keepalive_times_out() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let mut bob: SharedEngine = test_helpers::new_bob2(t0);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    alice.set_socket_option(alice_qd, SocketOption::KeepAliveEnabled(true))?;
+    let pop_qt: QToken = alice.tcp_pop(alice_qd)?;
+    alice.poll();
+
+    // Every probe that Alice sends is lost.
+    let mut now: Instant = t0 + DEFAULT_KEEPALIVE_IDLE;
+    for _ in 0..DEFAULT_KEEPALIVE_PROBES {
+        alice.advance_clock(now);
+        alice.poll();
+        crate::ensure_eq!(alice.pop_all_frames().len(), 1);
+        crate::ensure_eq!(alice.get_runtime().has_completed(pop_qt)?, false);
+        now += DEFAULT_KEEPALIVE_INTERVAL;
+    }
+
+    // The connection is closed once the last probe goes unanswered.
+    alice.advance_clock(now);
+    match alice.wait(pop_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ETIMEDOUT => {},
+        _ => anyhow::bail!("pop should fail with ETIMEDOUT"),
+    };
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ETIMEDOUT => {},
+        _ => anyhow::bail!("push should fail with ETIMEDOUT"),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Fills the scheduler of Bob while a connection is in the middle of its handshake, so that the connection cannot get
+/// its background coroutine. The accept fails with EAGAIN and Alice is reset, and once the scheduler has room again,
+/// Bob accepts connections as usual.
+#[test]
+fn tcp_accept_with_full_scheduler() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 4)?;
+
+    // Bob answers the SYN, and only then runs out of room for coroutines.
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    let mut runtime: SharedDemiRuntime = bob.get_runtime();
+    runtime.set_max_tasks(runtime.slab_stats().occupied_slots);
+    match bob.tcp_accept(listen_qd) {
+        Err(e) if e.errno == libc::EAGAIN => {},
+        _ => anyhow::bail!("accept should fail with EAGAIN while the scheduler is full"),
+    };
+
+    // The handshake completes at Alice, but Bob cannot establish the connection.
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::EAGAIN => {},
+        _ => anyhow::bail!("accept should fail with EAGAIN"),
+    };
+
+    // Bob resets the connection, so that Alice does not take it for established.
+    alice.receive(bob.pop_frame())?;
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ECONNRESET => {},
+        _ => anyhow::bail!("push on a reset connection should fail with ECONNRESET"),
+    };
+
+    // Once there is room again, Alice connects anew.
+    runtime.set_max_tasks(usize::MAX);
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept(_)) => {},
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    Ok(())
+}
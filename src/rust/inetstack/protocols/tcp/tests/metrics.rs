@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demikernel::libos::metrics::Metrics,
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::{
+            BufferPoolStats,
+            DemiBuffer,
+        },
+        network::types::DropStats,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of small messages that Alice sends to Bob.
+const NUM_MESSAGES: usize = 8;
+
+/// Size of each message, and of each pop.
+const MESSAGE_SIZE: usize = 16;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Generates pool and drop activity, then checks that a resetting snapshot reports it and that the next snapshot
+/// starts over from zero.
+#[test]
+fn tcp_metrics_snapshot_reset() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Start from a clean slate, as connection setup already moved some counters.
+    alice.metrics_snapshot(true);
+
+    for i in 0..NUM_MESSAGES {
+        let buf: DemiBuffer =
+            DemiBuffer::from_slice(&[i as u8; MESSAGE_SIZE][..]).expect("slice should fit in DemiBuffer");
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        let frame: DemiBuffer = alice.pop_frame();
+
+        // Alice drops her own segment, as it is addressed to Bob.
+        alice.receive(frame.clone())?;
+        bob.receive(frame)?;
+
+        let pop_qt: QToken = bob.pop(bob_qd, Some(MESSAGE_SIZE))?;
+        match bob.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, _)) => {},
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+
+    let metrics: Metrics = alice.metrics_snapshot(true);
+    crate::ensure_eq!(metrics.drops.no_route, NUM_MESSAGES as u64);
+    crate::ensure_eq!(metrics.drops.total(), NUM_MESSAGES as u64);
+    crate::ensure_eq!(metrics.buffer_pool.hits + metrics.buffer_pool.misses, NUM_MESSAGES);
+    crate::ensure_eq!(metrics.connections.open_queues, 1);
+    crate::ensure_eq!(metrics.connections.retransmits, 0);
+    crate::ensure_eq!(metrics.scheduler.occupied_slots > 0, true);
+
+    // Resettable counters start over, while gauges and connection aggregates are unaffected.
+    let again: Metrics = alice.metrics_snapshot(false);
+    crate::ensure_eq!(again.drops, DropStats::default());
+    crate::ensure_eq!(again.buffer_pool, BufferPoolStats::default());
+    crate::ensure_eq!(again.connections, metrics.connections);
+    crate::ensure_eq!(again.scheduler, metrics.scheduler);
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::{
+                    TcpHeader,
+                    TcpSegment,
+                },
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::PacketBuf,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::{
+        SocketAddr,
+        SocketAddrV4,
+    },
+    time::Instant,
+};
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Replaces the endpoint `from` with `to` in the TCP header of `frame`, like a NAT on the path would.
+fn translate(frame: DemiBuffer, from: SocketAddrV4, to: SocketAddrV4) -> Result<DemiBuffer> {
+    let (ethernet2_hdr, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (mut tcp_hdr, data): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    // Both endpoints share the same IPv4 address, so only ports need to be translated.
+    crate::ensure_eq!(from.ip(), to.ip());
+    if SocketAddrV4::new(ipv4_hdr.get_src_addr(), tcp_hdr.src_port) == from {
+        tcp_hdr.src_port = to.port();
+    }
+    if SocketAddrV4::new(ipv4_hdr.get_dest_addr(), tcp_hdr.dst_port) == from {
+        tcp_hdr.dst_port = to.port();
+    }
+    let segment: TcpSegment = TcpSegment {
+        ethernet2_hdr,
+        ipv4_hdr,
+        tcp_hdr,
+        data: if data.len() > 0 { Some(data) } else { None },
+        tx_checksum_offload: false,
+    };
+
+    let header_size: usize = segment.header_size();
+    let body_size: usize = segment.body_size();
+    let mut buf: DemiBuffer = DemiBuffer::new((header_size + body_size) as u16);
+    segment.write_header(&mut buf[..header_size]);
+    if let Some(body) = segment.take_body() {
+        buf[header_size..].copy_from_slice(&body[..]);
+    }
+    Ok(buf)
+}
+
+/// Sends `data` from one end of a connection to the other and returns the source address of the segment carrying it.
+/// If `nat` is set, the segment is translated with it on its way.
+fn send(
+    sender: &mut SharedEngine,
+    sender_qd: QDesc,
+    receiver: &mut SharedEngine,
+    receiver_qd: QDesc,
+    data: &[u8],
+    nat: Option<(SocketAddrV4, SocketAddrV4)>,
+) -> Result<SocketAddrV4> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(data).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = sender.tcp_push(sender_qd, buf)?;
+    match sender.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+
+    let frame: DemiBuffer = sender.pop_frame();
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame.clone())?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    match nat {
+        Some((from, to)) => receiver.receive(translate(frame, from, to)?)?,
+        None => receiver.receive(frame)?,
+    };
+
+    let pop_qt: QToken = receiver.tcp_pop(receiver_qd)?;
+    match receiver.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(&received[..], data),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    Ok(SocketAddrV4::new(ipv4_hdr.get_src_addr(), tcp_hdr.src_port))
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Migrates an established connection to a new local address and checks that data keeps flowing both ways.
+#[test]
+fn tcp_migrate_local() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let old_local: SocketAddrV4 = send(&mut alice, alice_qd, &mut bob, bob_qd, b"before", None)?;
+
+    // Alice moves to a new address. A NAT on the path hides it from Bob, who keeps using the old one.
+    let new_local: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 8080);
+    crate::ensure_neq!(old_local, new_local);
+    alice.migrate_local(alice_qd, SocketAddr::V4(new_local))?;
+
+    // Data flows on the migrated connection.
+    let after: SocketAddrV4 = send(
+        &mut alice,
+        alice_qd,
+        &mut bob,
+        bob_qd,
+        b"after",
+        Some((new_local, old_local)),
+    )?;
+    crate::ensure_eq!(after, new_local);
+    send(
+        &mut bob,
+        bob_qd,
+        &mut alice,
+        alice_qd,
+        b"reply",
+        Some((old_local, new_local)),
+    )?;
+
+    // Migrating to an address that is not local fails.
+    let foreign: SocketAddrV4 = SocketAddrV4::new(test_helpers::CARRIE_IPV4, 8080);
+    match alice.migrate_local(alice_qd, SocketAddr::V4(foreign)) {
+        Err(e) if e.errno == libc::EADDRNOTAVAIL => {},
+        _ => anyhow::bail!("migrate_local should fail for a foreign address"),
+    };
+
+    Ok(())
+}
@@ -5,5 +5,141 @@
 // Exports
 //======================================================================================================================
 
+mod accept_batch;
+mod accept_loop;
+mod accept_push;
+mod accept_result;
+mod activity;
+mod backlog;
+mod buffer_pool;
+mod close_matching;
+mod closed_listener;
+mod congestion;
+mod connect_any;
+mod connect_established;
+mod cork;
+mod deadline;
+mod delayed_ack;
+mod delivery_rate;
+mod dscp;
+mod fast_open;
+mod half_close;
+mod half_closed_read;
+mod half_open;
+mod isn_generator;
+mod keepalive;
+mod max_tasks;
+mod metrics;
+mod migrate;
+mod observed_options;
+mod op_counts;
+mod path_mtu;
+mod path_mtu_discovery;
+mod pause;
+mod pop_all;
+mod pop_cancellable;
+mod pop_into;
+mod priority;
+mod push_after_close;
+mod push_after_reset;
+mod push_to_many;
+mod queue_buffer_pool;
+mod quick_ack;
+mod quiesce;
+mod rate;
+mod reassembly_limit;
+mod receive_coalescing;
+mod retransmit;
+mod reuse_port;
+mod rst_on_invalid_segment;
+mod send_buffer;
+mod sequence_state;
+mod shutdown;
 #[cfg(debug_assertions)]
 mod simulator;
+mod small_pops;
+mod sockopt;
+mod spurious_rto;
+#[cfg(feature = "state-history")]
+mod state_history;
+mod tcp_state;
+mod time_wait;
+mod user_data;
+mod user_timeout;
+mod wait_any_timeout;
+mod wait_ex;
+mod wait_spin;
+mod window_clamp;
+mod would_block_pop;
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::net::SocketAddrV4;
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Establishes a TCP connection from Alice to Bob by hand-delivering the three-way handshake.
+pub fn connection_setup(alice: &mut SharedEngine, bob: &mut SharedEngine) -> Result<(QDesc, QDesc)> {
+    connection_setup_with_options(alice, bob, &[])
+}
+
+/// Establishes a TCP connection like [connection_setup], except that Bob sets `options` on the listening socket, for
+/// the connection to inherit them.
+pub fn connection_setup_with_options(
+    alice: &mut SharedEngine,
+    bob: &mut SharedEngine,
+    options: &[SocketOption],
+) -> Result<(QDesc, QDesc)> {
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Bob listens for incoming connections.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    for option in options {
+        bob.set_socket_option(listen_qd, *option)?;
+    }
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+
+    // Alice connects to Bob.
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+    // SYN, SYN+ACK, and ACK.
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    Ok((alice_qd, bob_qd))
+}
@@ -0,0 +1,162 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::segment::{
+                TcpHeader,
+                TcpOptions2,
+                TcpSegment,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::{
+            consts::DEFAULT_MSS,
+            types::TcpOption,
+            PacketBuf,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Appends `options` to the TCP header of `frame`.
+fn add_options(frame: DemiBuffer, options: &[TcpOptions2]) -> Result<DemiBuffer> {
+    let (ethernet2_hdr, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (mut tcp_hdr, data): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    for option in options {
+        tcp_hdr.push_option(*option);
+    }
+    let segment: TcpSegment = TcpSegment {
+        ethernet2_hdr,
+        ipv4_hdr,
+        tcp_hdr,
+        data: if data.len() > 0 { Some(data) } else { None },
+        tx_checksum_offload: false,
+    };
+
+    let header_size: usize = segment.header_size();
+    let body_size: usize = segment.body_size();
+    let mut buf: DemiBuffer = DemiBuffer::new((header_size + body_size) as u16);
+    segment.write_header(&mut buf[..header_size]);
+    if let Some(body) = segment.take_body() {
+        buf[header_size..].copy_from_slice(&body[..]);
+    }
+    Ok(buf)
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Establishes a connection whose SYN carries several options and checks that the listener records all of them.
+#[test]
+fn tcp_observed_options() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    bob.get_transport().set_record_options(true);
+
+    // Bob listens for incoming connections.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+
+    // Alice connects to Bob and her SYN also carries SACK-permitted and timestamp options.
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    let syn: DemiBuffer = add_options(
+        alice.pop_frame(),
+        &[
+            TcpOptions2::SelectiveAcknowlegementPermitted,
+            TcpOptions2::Timestamp {
+                sender_timestamp: 1,
+                echo_timestamp: 0,
+            },
+        ],
+    )?;
+    bob.receive(syn)?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+    let expected: Vec<TcpOption> = vec![
+        TcpOption::MaximumSegmentSize(DEFAULT_MSS as u16),
+        TcpOption::WindowScale(0),
+        TcpOption::SelectiveAcknowledgementPermitted,
+        TcpOption::Timestamp {
+            sender_timestamp: 1,
+            echo_timestamp: 0,
+        },
+    ];
+    crate::ensure_eq!(bob.observed_options(bob_qd)?, expected);
+
+    // A later timestamp replaces the one from the SYN.
+    let buf: DemiBuffer = DemiBuffer::from_slice(b"ping").expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let data: DemiBuffer = add_options(
+        alice.pop_frame(),
+        &[TcpOptions2::Timestamp {
+            sender_timestamp: 2,
+            echo_timestamp: 1,
+        }],
+    )?;
+    bob.receive(data)?;
+    bob.poll();
+    let observed: Vec<TcpOption> = bob.observed_options(bob_qd)?;
+    crate::ensure_eq!(observed.len(), expected.len());
+    crate::ensure_eq!(
+        observed[3],
+        TcpOption::Timestamp {
+            sender_timestamp: 2,
+            echo_timestamp: 1,
+        }
+    );
+
+    // Alice did not ask for options to be recorded.
+    crate::ensure_eq!(alice.observed_options(alice_qd)?.is_empty(), true);
+
+    Ok(())
+}
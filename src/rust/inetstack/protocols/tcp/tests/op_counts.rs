@@ -0,0 +1,131 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::types::OpCounts,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Runs a mix of operations over a connection and checks that each queue counts the ones that completed on it.
+#[test]
+fn tcp_op_counts() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Bob accepts a connection from Alice.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 8)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    // Alice sends three segments, which Bob pops one at a time, and Bob answers with one.
+    for _ in 0..3 {
+        send(&mut alice, alice_qd, &mut bob, bob_qd)?;
+    }
+    send(&mut bob, bob_qd, &mut alice, alice_qd)?;
+
+    crate::ensure_eq!(
+        alice.op_counts(alice_qd)?,
+        OpCounts {
+            pushes: 3,
+            pops: 1,
+            connects: 1,
+            ..Default::default()
+        }
+    );
+    crate::ensure_eq!(
+        bob.op_counts(bob_qd)?,
+        OpCounts {
+            pushes: 1,
+            pops: 3,
+            ..Default::default()
+        }
+    );
+    crate::ensure_eq!(
+        bob.op_counts(listen_qd)?,
+        OpCounts {
+            accepts: 1,
+            ..Default::default()
+        }
+    );
+
+    // The final counts of a closed queue, including its close, stay available through its descriptor.
+    let close_qt: QToken = bob.tcp_async_close(listen_qd)?;
+    match bob.wait(close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    crate::ensure_eq!(
+        bob.op_counts(listen_qd)?,
+        OpCounts {
+            accepts: 1,
+            closes: 1,
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Pushes a segment from `sender` and pops it on `receiver`.
+fn send(sender: &mut SharedEngine, sender_qd: QDesc, receiver: &mut SharedEngine, receiver_qd: QDesc) -> Result<()> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = sender.tcp_push(sender_qd, buf)?;
+    match sender.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    receiver.receive(sender.pop_frame())?;
+    let pop_qt: QToken = receiver.tcp_pop(receiver_qd)?;
+    match receiver.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, buf)) => crate::ensure_eq!(buf.len(), 32),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ipv4::IPV4_HEADER_MIN_SIZE,
+            tcp::{
+                segment::MIN_TCP_HEADER_SIZE,
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        network::consts::DEFAULT_MTU,
+        queue::QDesc,
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that both ends of a connection report the interface MTU minus the IPv4 and TCP headers.
+#[test]
+fn tcp_path_mtu() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let expected: u16 = DEFAULT_MTU - IPV4_HEADER_MIN_SIZE - MIN_TCP_HEADER_SIZE as u16;
+
+    // Before the connection is established, the MTU is derived from the interface.
+    let qd: QDesc = alice.tcp_socket()?;
+    crate::ensure_eq!(alice.path_mtu(qd)?, expected);
+
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    crate::ensure_eq!(alice.path_mtu(alice_qd)?, expected);
+    crate::ensure_eq!(bob.path_mtu(bob_qd)?, expected);
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::{
+                EtherType2,
+                Ethernet2Header,
+            },
+            icmpv4::{
+                Icmpv4Header,
+                Icmpv4Message,
+                Icmpv4Type2,
+            },
+            ip::IpProtocol,
+            ipv4::{
+                Ipv4Header,
+                IPV4_HEADER_MIN_SIZE,
+            },
+            tcp::{
+                segment::{
+                    TcpHeader,
+                    MIN_TCP_HEADER_SIZE,
+                },
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::PacketBuf,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::Ipv4Addr,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Address of the router that reports the smaller MTU.
+const ROUTER_IPV4: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 254);
+
+/// MTU of the next hop, as reported by the router.
+const NEXT_HOP_MTU: u16 = 1000;
+
+/// Code of "fragmentation needed and DF set" Destination Unreachable messages.
+const ICMPV4_CODE_FRAGMENTATION_NEEDED: u8 = 4;
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Builds the ICMP "fragmentation needed" message that a router sends back to Alice when `frame` does not fit in
+/// `next_hop_mtu`.
+fn fragmentation_needed(frame: DemiBuffer, next_hop_mtu: u16) -> Result<DemiBuffer> {
+    // The message quotes the IPv4 header of the dropped datagram and the first 8 bytes of its payload.
+    let (_, ipv4_packet): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let quoted: &[u8] = &ipv4_packet[..(IPV4_HEADER_MIN_SIZE as usize + 8)];
+
+    let msg: Icmpv4Message = Icmpv4Message::new(
+        Ethernet2Header::new(test_helpers::ALICE_MAC, test_helpers::BOB_MAC, EtherType2::Ipv4),
+        Ipv4Header::new(ROUTER_IPV4, test_helpers::ALICE_IPV4, IpProtocol::ICMPv4),
+        Icmpv4Header::new(
+            Icmpv4Type2::DestinationUnreachable { next_hop_mtu },
+            ICMPV4_CODE_FRAGMENTATION_NEEDED,
+        ),
+        DemiBuffer::from_slice(quoted).expect("slice should fit in DemiBuffer"),
+    );
+    let header_size: usize = msg.header_size();
+    let body_size: usize = msg.body_size();
+    let mut buf: DemiBuffer = DemiBuffer::new((header_size + body_size) as u16);
+    msg.write_header(&mut buf[..header_size]);
+    if let Some(body) = msg.take_body() {
+        buf[header_size..].copy_from_slice(&body[..]);
+    }
+    Ok(buf)
+}
+
+/// Returns the size of the TCP payload carried in `frame`.
+fn payload_size(frame: DemiBuffer) -> Result<usize> {
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (_, data): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    Ok(data.len())
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Drops a segment that does not fit the path, reports it with an ICMP "fragmentation needed" message, and checks that
+/// the data is sent again in segments that fit the next hop.
+#[test]
+fn tcp_path_mtu_discovery() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let expected_mtu: u16 = NEXT_HOP_MTU - IPV4_HEADER_MIN_SIZE - MIN_TCP_HEADER_SIZE as u16;
+
+    // Send a segment that is too large for the next hop and have the router drop it.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 1400][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let dropped: DemiBuffer = alice.pop_frame();
+    crate::ensure_eq!(payload_size(dropped.clone())?, buf.len());
+
+    // Report the drop and check that the data is sent again in segments that fit.
+    alice.receive(fragmentation_needed(dropped, NEXT_HOP_MTU)?)?;
+    alice.poll();
+    crate::ensure_eq!(alice.path_mtu(alice_qd)?, expected_mtu);
+    let mut sent: usize = 0;
+    for frame in alice.pop_all_frames() {
+        let size: usize = payload_size(frame.clone())?;
+        crate::ensure_eq!(size <= expected_mtu as usize, true);
+        sent += size;
+        bob.receive(frame)?;
+    }
+    crate::ensure_eq!(sent, buf.len());
+
+    // Bob receives all of the data.
+    let mut received: Vec<u8> = Vec::with_capacity(buf.len());
+    while received.len() < buf.len() {
+        let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+        match bob.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, data)) => received.extend_from_slice(&data[..]),
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+    crate::ensure_eq!(received[..], buf[..]);
+
+    Ok(())
+}
+
+/// Checks that ICMP "fragmentation needed" messages are ignored when Path MTU Discovery is disabled.
+#[test]
+fn tcp_path_mtu_discovery_disabled() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let mtu: u16 = alice.path_mtu(alice_qd)?;
+    alice.get_transport().set_path_mtu_discovery(false);
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 1400][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let dropped: DemiBuffer = alice.pop_frame();
+    alice.receive(fragmentation_needed(dropped, NEXT_HOP_MTU)?)?;
+    alice.poll();
+    crate::ensure_eq!(alice.path_mtu(alice_qd)?, mtu);
+    crate::ensure_eq!(alice.pop_all_frames().len(), 0);
+
+    Ok(())
+}
+
+/// Checks that reports quoting a segment that is no longer in flight, or a next-hop MTU that is too small to carry a
+/// segment of the minimum MSS, are ignored.
+#[test]
+fn tcp_path_mtu_discovery_ignores_invalid_reports() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let mtu: u16 = alice.path_mtu(alice_qd)?;
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 1400][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let sent: DemiBuffer = alice.pop_frame();
+
+    // The segment is in flight, but the reported MTU is too small.
+    alice.receive(fragmentation_needed(sent.clone(), 100)?)?;
+    alice.poll();
+    crate::ensure_eq!(alice.path_mtu(alice_qd)?, mtu);
+    crate::ensure_eq!(alice.pop_all_frames().len(), 0);
+
+    // Once Bob acknowledges the segment, reports about it are stale.
+    bob.receive(sent.clone())?;
+    bob.poll();
+    bob.advance_clock(now + Duration::from_secs(1));
+    bob.poll();
+    for frame in bob.pop_all_frames() {
+        alice.receive(frame)?;
+    }
+    alice.poll();
+    alice.receive(fragmentation_needed(sent, NEXT_HOP_MTU)?)?;
+    alice.poll();
+    crate::ensure_eq!(alice.path_mtu(alice_qd)?, mtu);
+    crate::ensure_eq!(alice.pop_all_frames().len(), 0);
+
+    Ok(())
+}
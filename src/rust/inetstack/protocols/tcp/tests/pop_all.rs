@@ -0,0 +1,76 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Buffers several segments on the receiver and checks that they are all returned by a single pop.
+#[test]
+fn tcp_pop_all() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Deliver several segments to Bob before he pops anything.
+    let mut expected: Vec<u8> = Vec::new();
+    for i in 0..3u8 {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[i; 16][..]).expect("slice should fit in DemiBuffer");
+        expected.extend_from_slice(&buf[..]);
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        bob.receive(alice.pop_frame())?;
+    }
+
+    let pop_qt: QToken = bob.pop_all(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], expected[..]),
+        _ => anyhow::bail!("pop_all should succeed"),
+    };
+
+    // With nothing buffered, pop_all waits for the next segment.
+    let pop_qt: QToken = bob.pop_all(bob_qd)?;
+    bob.poll();
+    crate::ensure_eq!(bob.get_runtime().has_completed(pop_qt)?, false);
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0xff; 8][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+        _ => anyhow::bail!("pop_all should succeed"),
+    };
+
+    Ok(())
+}
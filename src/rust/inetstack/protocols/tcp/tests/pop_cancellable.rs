@@ -0,0 +1,104 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        cancellation::CancellationToken,
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Issues two pops that share a cancellation token and checks that cancelling the token completes both of them with
+/// ECANCELED.
+#[test]
+fn tcp_pop_cancellable() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Nothing arrives, so both pops keep waiting.
+    let token: CancellationToken = CancellationToken::new();
+    let pop_qts: [QToken; 2] = [
+        alice.pop_cancellable(alice_qd, None, token.clone())?,
+        alice.pop_cancellable(alice_qd, None, token.clone())?,
+    ];
+    alice.poll();
+    for qt in pop_qts {
+        crate::ensure_eq!(alice.get_runtime().has_completed(qt)?, false);
+    }
+
+    // Cancelling the token through any of its clones completes both pops.
+    let mut clone: CancellationToken = token.clone();
+    clone.cancel();
+    crate::ensure_eq!(token.is_cancelled(), true);
+    for qt in pop_qts {
+        match alice.wait(qt)? {
+            (_, OperationResult::Failed(e)) if e.errno == libc::ECANCELED => {},
+            _ => anyhow::bail!("cancelled pop should fail with ECANCELED"),
+        };
+    }
+
+    // Pops issued with a cancelled token complete right away.
+    let pop_qt: QToken = alice.pop_cancellable(alice_qd, None, token)?;
+    match alice.wait(pop_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ECANCELED => {},
+        _ => anyhow::bail!("pop with a cancelled token should fail with ECANCELED"),
+    };
+
+    Ok(())
+}
+
+/// Issues pops with a token that is never cancelled and checks that the token lets go of them once they complete.
+#[test]
+fn tcp_pop_cancellable_completed() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let token: CancellationToken = CancellationToken::new();
+    for _ in 0..3 {
+        let pop_qt: QToken = alice.pop_cancellable(alice_qd, None, token.clone())?;
+        alice.poll();
+        crate::ensure_eq!(token.num_pending(), 1);
+
+        // Bob sends data, which completes the pop.
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+        let push_qt: QToken = bob.tcp_push(bob_qd, buf)?;
+        match bob.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        alice.receive(bob.pop_frame())?;
+        match alice.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, _)) => {},
+            _ => anyhow::bail!("pop should succeed"),
+        };
+        crate::ensure_eq!(token.num_pending(), 0);
+    }
+
+    Ok(())
+}
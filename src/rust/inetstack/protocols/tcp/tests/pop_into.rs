@@ -0,0 +1,112 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Pops into a buffer of the caller that is larger than the data, and checks that the pop fills the buffer that it
+/// was given and trims it to the bytes received.
+#[test]
+fn tcp_pop_into() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let data: Vec<u8> = send(&mut alice, alice_qd, &mut bob, 40)?;
+    let buf: Vec<u8> = pop_into(&mut bob, bob_qd, DemiBuffer::new(256), None)?;
+    crate::ensure_eq!(buf, data);
+
+    Ok(())
+}
+
+/// Checks that a pop into a buffer of the caller stops at the end of the buffer, or at the requested size if it is
+/// smaller.
+#[test]
+fn tcp_pop_into_capped() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let data: Vec<u8> = send(&mut alice, alice_qd, &mut bob, 100)?;
+    let buf: Vec<u8> = pop_into(&mut bob, bob_qd, DemiBuffer::new(64), None)?;
+    crate::ensure_eq!(&buf[..], &data[..64]);
+
+    let data: Vec<u8> = send(&mut alice, alice_qd, &mut bob, 100)?;
+    let buf: Vec<u8> = pop_into(&mut bob, bob_qd, DemiBuffer::new(256), Some(16))?;
+    crate::ensure_eq!(&buf[..], &data[..16]);
+
+    Ok(())
+}
+
+/// Checks that popping into an empty buffer fails with EINVAL.
+#[test]
+fn tcp_pop_into_empty_buffer() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (_, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let pop_qt: QToken = bob.pop_into(bob_qd, DemiBuffer::new(0), None)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("pop into an empty buffer should fail with EINVAL"),
+    };
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Pops into `buf` and checks that the operation hands back the same memory.
+fn pop_into(engine: &mut SharedEngine, qd: QDesc, buf: DemiBuffer, size: Option<usize>) -> Result<Vec<u8>> {
+    let ptr: *const u8 = buf.as_ptr();
+    let pop_qt: QToken = engine.pop_into(qd, buf, size)?;
+    match engine.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, buf)) => {
+            crate::ensure_eq!(buf.as_ptr(), ptr);
+            Ok(buf.to_vec())
+        },
+        _ => anyhow::bail!("pop should succeed"),
+    }
+}
+
+/// Pushes `len` bytes from Alice to Bob and returns them.
+fn send(alice: &mut SharedEngine, alice_qd: QDesc, bob: &mut SharedEngine, len: u8) -> Result<Vec<u8>> {
+    let data: Vec<u8> = (0..len).collect();
+    let push_qt: QToken = alice.tcp_push(alice_qd, DemiBuffer::from_slice(&data)?)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    Ok(data)
+}
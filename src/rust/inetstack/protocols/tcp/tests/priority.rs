@@ -0,0 +1,108 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        scheduler::Priority,
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Floods a bulk connection and a high-priority connection with data and checks that the high-priority pushes are
+/// sent first, even though they were issued last.
+#[test]
+fn tcp_priority() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bulk_qd: QDesc = connect(&mut alice, &mut bob, 80)?;
+    let urgent_qd: QDesc = connect(&mut alice, &mut bob, 81)?;
+    alice.set_socket_option(urgent_qd, SocketOption::Priority(Priority::High))?;
+
+    // Flood the bulk connection first, and the high-priority connection afterwards.
+    const NUM_PUSHES: usize = 4;
+    const BULK: u8 = 0x0b;
+    const URGENT: u8 = 0x0a;
+    let mut push_qts: Vec<QToken> = Vec::with_capacity(2 * NUM_PUSHES);
+    for (qd, fill) in [(bulk_qd, BULK), (urgent_qd, URGENT)] {
+        for _ in 0..NUM_PUSHES {
+            let buf: DemiBuffer = DemiBuffer::from_slice(&[fill; 64][..]).expect("slice should fit in DemiBuffer");
+            push_qts.push(alice.tcp_push(qd, buf)?);
+        }
+    }
+
+    // A single sweep sends everything, starting with the high-priority connection.
+    alice.poll();
+    for qt in push_qts {
+        match alice.wait(qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+    }
+    let fills: Vec<u8> = alice
+        .pop_all_frames()
+        .into_iter()
+        .map(|frame| frame[frame.len() - 1])
+        .collect();
+    crate::ensure_eq!(fills, [[URGENT; NUM_PUSHES], [BULK; NUM_PUSHES]].concat());
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Connects Alice to a new listening socket of Bob on `port` and returns Alice's end of the connection.
+fn connect(alice: &mut SharedEngine, bob: &mut SharedEngine, port: u16) -> Result<QDesc> {
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, port);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+    // SYN, SYN+ACK, and ACK.
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept(_)) => {},
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    Ok(alice_qd)
+}
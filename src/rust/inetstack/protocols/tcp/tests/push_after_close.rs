@@ -0,0 +1,63 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Has Alice close the connection and checks that Bob's pushes fail with EPIPE instead of hanging or being dropped.
+#[test]
+fn tcp_push_after_close() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Signals are never raised, so the compatibility option is accepted but changes nothing.
+    bob.set_socket_option(bob_qd, SocketOption::NoSigPipe(true))?;
+
+    // Alice closes and Bob receives her FIN.
+    let _close_qt: QToken = alice.tcp_async_close(alice_qd)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+
+    // Every push fails, and nothing goes out on the wire.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    for _ in 0..2 {
+        let push_qt: QToken = bob.tcp_push(bob_qd, buf.clone())?;
+        match bob.wait(push_qt)? {
+            (_, OperationResult::Failed(e)) if e.errno == libc::EPIPE => {},
+            _ => anyhow::bail!("push on a connection closed by the peer should fail with EPIPE"),
+        };
+    }
+    crate::ensure_eq!(bob.pop_all_frames().is_empty(), true);
+
+    Ok(())
+}
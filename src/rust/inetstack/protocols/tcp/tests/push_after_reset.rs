@@ -0,0 +1,75 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::{
+            operation::Shutdown,
+            option::{
+                HalfClosedReadPolicy,
+                SocketOption,
+            },
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Has Alice abort the connection and checks that Bob's next push fails with ECONNRESET instead of being dropped.
+#[test]
+fn tcp_push_after_reset() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Alice resets the connection as soon as Bob sends data after she stopped reading.
+    alice.set_socket_option(
+        alice_qd,
+        SocketOption::HalfClosedReadPolicy(HalfClosedReadPolicy::Reset),
+    )?;
+    alice.shutdown(alice_qd, Shutdown::Read)?;
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = bob.tcp_push(bob_qd, buf.clone())?;
+    match bob.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+
+    // Neither end may push anymore.
+    for (engine, qd) in [(&mut bob, bob_qd), (&mut alice, alice_qd)] {
+        let push_qt: QToken = engine.tcp_push(qd, buf.clone())?;
+        match engine.wait(push_qt)? {
+            (_, OperationResult::Failed(e)) if e.errno == libc::ECONNRESET => {},
+            _ => anyhow::bail!("push on a reset connection should fail with ECONNRESET"),
+        };
+    }
+    crate::ensure_eq!(bob.pop_all_frames().is_empty(), true);
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of connections that Bob pushes to.
+const NUM_CONNECTIONS: usize = 3;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Pushes one buffer to several connections at once and checks that every peer receives the same bytes.
+#[test]
+fn tcp_push_to_many() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let qds: Vec<(QDesc, QDesc)> = connect(&mut alice, &mut bob)?;
+
+    let data: Vec<u8> = (0..64).collect();
+    let buf: DemiBuffer = DemiBuffer::from_slice(&data).expect("slice should fit in DemiBuffer");
+    let bob_qds: Vec<QDesc> = qds.iter().map(|(_, bob_qd)| *bob_qd).collect();
+    let push_qts: Vec<QToken> = bob.push_to_many(&bob_qds, buf)?;
+    crate::ensure_eq!(push_qts.len(), NUM_CONNECTIONS);
+
+    // Every push completes, and the segments that carry them reach Alice.
+    for push_qt in push_qts {
+        match bob.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+    }
+    for _ in 0..NUM_CONNECTIONS {
+        alice.receive(bob.pop_frame())?;
+    }
+
+    for (alice_qd, _) in qds {
+        let pop_qt: QToken = alice.tcp_pop(alice_qd)?;
+        match alice.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(&received[..], &data[..]),
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+
+    Ok(())
+}
+
+/// Checks that nothing is pushed when one of the descriptors does not exist.
+#[test]
+fn tcp_push_to_many_bad_qd() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let qds: Vec<(QDesc, QDesc)> = connect(&mut alice, &mut bob)?;
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let bob_qds: Vec<QDesc> = vec![qds[0].1, QDesc::from(u32::MAX)];
+    match bob.push_to_many(&bob_qds, buf) {
+        Err(e) if e.errno == libc::EBADF => {},
+        _ => anyhow::bail!("push to a bad descriptor should fail with EBADF"),
+    };
+    crate::ensure_eq!(bob.get_runtime().has_pending_ops(), false);
+
+    Ok(())
+}
+
+/// Checks that the pushes scheduled so far are aborted when one of the later pushes cannot be scheduled.
+#[test]
+fn tcp_push_to_many_out_of_tasks() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let qds: Vec<(QDesc, QDesc)> = connect(&mut alice, &mut bob)?;
+
+    // Leave room for a single push only.
+    let mut runtime: SharedDemiRuntime = bob.get_runtime();
+    let occupied_slots: usize = runtime.slab_stats().occupied_slots;
+    runtime.set_max_tasks(occupied_slots + 1);
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let bob_qds: Vec<QDesc> = qds.iter().map(|(_, bob_qd)| *bob_qd).collect();
+    match bob.push_to_many(&bob_qds, buf) {
+        Err(e) if e.errno == libc::EAGAIN => {},
+        _ => anyhow::bail!("push to many should fail with EAGAIN while the scheduler is full"),
+    };
+    crate::ensure_eq!(runtime.has_pending_ops(), false);
+    crate::ensure_eq!(runtime.slab_stats().occupied_slots, occupied_slots);
+
+    // Not even the first connection sends anything.
+    bob.poll();
+    crate::ensure_eq!(bob.pop_all_frames().len(), 0);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Opens [NUM_CONNECTIONS] connections from Alice to a listening socket of Bob and returns both ends of them.
+fn connect(alice: &mut SharedEngine, bob: &mut SharedEngine) -> Result<Vec<(QDesc, QDesc)>> {
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, NUM_CONNECTIONS)?;
+
+    let mut qds: Vec<(QDesc, QDesc)> = Vec::with_capacity(NUM_CONNECTIONS);
+    for _ in 0..NUM_CONNECTIONS {
+        let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+        let alice_qd: QDesc = alice.tcp_socket()?;
+        let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+        // SYN, SYN+ACK, and ACK.
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+        alice.receive(bob.pop_frame())?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+
+        match alice.wait(connect_qt)? {
+            (_, OperationResult::Connect) => {},
+            _ => anyhow::bail!("connect should succeed"),
+        };
+        match bob.wait(accept_qt)? {
+            (_, OperationResult::Accept((bob_qd, _))) => qds.push((alice_qd, bob_qd)),
+            _ => anyhow::bail!("accept should succeed"),
+        };
+    }
+
+    Ok(qds)
+}
@@ -0,0 +1,102 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::{
+            BufferPoolStats,
+            DemiBuffer,
+        },
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of small messages that Alice sends to Bob.
+const NUM_MESSAGES: usize = 128;
+
+/// Size of each message, and of each pop.
+const MESSAGE_SIZE: usize = 16;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Pops many small messages on a connection with a buffer pool of its own, and checks that their buffers are recycled
+/// through that pool rather than through the pool of the thread.
+#[test]
+fn tcp_queue_buffer_pool() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    crate::ensure_eq!(bob.buffer_pool_stats(bob_qd)?, None);
+
+    bob.set_socket_option(bob_qd, SocketOption::QueueBufferPool(true))?;
+    crate::ensure_eq!(bob.buffer_pool_stats(bob_qd)?, Some(BufferPoolStats::default()));
+
+    let thread_before: BufferPoolStats = DemiBuffer::small_buffer_pool_stats();
+    let mut last: Option<DemiBuffer> = None;
+    for i in 0..NUM_MESSAGES {
+        let buf: DemiBuffer =
+            DemiBuffer::from_slice(&[i as u8; MESSAGE_SIZE][..]).expect("slice should fit in DemiBuffer");
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        bob.receive(alice.pop_frame())?;
+
+        // The popped buffer of the previous iteration is dropped here, which returns it to the pool of the queue.
+        let pop_qt: QToken = bob.pop(bob_qd, Some(MESSAGE_SIZE))?;
+        match bob.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, received)) => {
+                crate::ensure_eq!(received[..], buf[..]);
+                last = Some(received);
+            },
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+    let thread_after: BufferPoolStats = DemiBuffer::small_buffer_pool_stats();
+
+    // Every pop was served by the pool of the queue, which only went to the general allocator while it warmed up.
+    let stats: BufferPoolStats = match bob.buffer_pool_stats(bob_qd)? {
+        Some(stats) => stats,
+        None => anyhow::bail!("queue should have a buffer pool"),
+    };
+    crate::ensure_eq!(stats.hits + stats.misses, NUM_MESSAGES);
+    crate::ensure_eq!(stats.misses <= 2, true);
+    crate::ensure_eq!(stats.allocation_failures, 0);
+    crate::ensure_eq!(
+        thread_after.hits + thread_after.misses,
+        thread_before.hits + thread_before.misses
+    );
+
+    // A buffer may outlive the pool of its queue.
+    bob.set_socket_option(bob_qd, SocketOption::QueueBufferPool(false))?;
+    crate::ensure_eq!(bob.buffer_pool_stats(bob_qd)?, None);
+    drop(last);
+
+    Ok(())
+}
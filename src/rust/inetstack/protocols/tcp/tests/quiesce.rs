@@ -0,0 +1,168 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::types::{
+            ConnectionSnapshot,
+            TcpSeqState,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Quiesces a connection that buffers data in both directions, checks that the snapshot holds that data and the
+/// sequence state of the connection, then resumes it in place and checks that data flows again.
+#[test]
+fn tcp_quiesce_and_resume() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Bob holds data from Alice that he has not popped yet.
+    let from_alice: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    push(&mut alice, alice_qd, from_alice.clone())?;
+    bob.receive(alice.pop_frame())?;
+
+    // And data for Alice that she has not acknowledged yet.
+    let from_bob: DemiBuffer = DemiBuffer::from_slice(&[0xa5; 16][..]).expect("slice should fit in DemiBuffer");
+    push(&mut bob, bob_qd, from_bob.clone())?;
+
+    let snapshot: ConnectionSnapshot = bob.quiesce(bob_qd)?;
+    crate::ensure_eq!(*snapshot.local.ip(), test_helpers::BOB_IPV4);
+    crate::ensure_eq!(*snapshot.remote.ip(), test_helpers::ALICE_IPV4);
+    crate::ensure_eq!(snapshot.recv_buffered[..], from_alice[..]);
+    crate::ensure_eq!(snapshot.send_buffered[..], from_bob[..]);
+    crate::ensure_eq!(
+        snapshot.seq_state.snd_nxt.wrapping_sub(snapshot.seq_state.snd_una),
+        from_bob.len() as u32
+    );
+    crate::ensure_eq!(Some(snapshot.seq_state), bob.tcp_sequence_state(bob_qd)?);
+
+    // A quiesced connection refuses pushes.
+    let push_qt: QToken = bob.tcp_push(bob_qd, from_bob.clone())?;
+    match bob.wait(push_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::EWOULDBLOCK => {},
+        _ => anyhow::bail!("push should fail while the connection is quiesced"),
+    };
+
+    // Resuming lets the data flow again.
+    bob.resume(bob_qd)?;
+    for frame in bob.pop_all_frames() {
+        alice.receive(frame)?;
+    }
+    crate::ensure_eq!(pop(&mut alice, alice_qd)?[..], from_bob[..]);
+    crate::ensure_eq!(pop(&mut bob, bob_qd)?[..], from_alice[..]);
+
+    // New data flows as well.
+    let more: DemiBuffer = DemiBuffer::from_slice(&[0x3c; 8][..]).expect("slice should fit in DemiBuffer");
+    push(&mut alice, alice_qd, more.clone())?;
+    bob.receive(alice.pop_frame())?;
+    crate::ensure_eq!(pop(&mut bob, bob_qd)?[..], more[..]);
+
+    Ok(())
+}
+
+/// Quiesces a connection that buffers data in both directions and restores it from its serialized snapshot on another
+/// stack with the same address, as when a new process takes over. The connection carries on from there: the data that
+/// it buffered reaches both ends, and new data flows in both directions.
+#[test]
+fn tcp_quiesce_and_resume_from() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Bob holds data from Alice that he has not popped yet, and data for Alice that never reaches her.
+    let from_alice: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    push(&mut alice, alice_qd, from_alice.clone())?;
+    bob.receive(alice.pop_frame())?;
+    let from_bob: DemiBuffer = DemiBuffer::from_slice(&[0xa5; 16][..]).expect("slice should fit in DemiBuffer");
+    push(&mut bob, bob_qd, from_bob.clone())?;
+    let snapshot: ConnectionSnapshot = bob.quiesce(bob_qd)?;
+    let serialized: Vec<u8> = snapshot.serialize();
+    drop(bob);
+
+    // The snapshot survives serialization, and a truncated one is refused.
+    crate::ensure_eq!(ConnectionSnapshot::parse(&serialized)?, snapshot);
+    match ConnectionSnapshot::parse(&serialized[..serialized.len() - 1]) {
+        Err(e) if e.errno == libc::EBADMSG => {},
+        _ => anyhow::bail!("parsing a truncated snapshot should fail with EBADMSG"),
+    };
+
+    // Another stack takes over the connection, with the sequence state of the snapshot.
+    let mut new_bob: SharedEngine = test_helpers::new_bob2(now);
+    let new_bob_qd: QDesc = new_bob.resume_from(&ConnectionSnapshot::parse(&serialized)?)?;
+    let seq_state: TcpSeqState = new_bob
+        .tcp_sequence_state(new_bob_qd)?
+        .expect("restored connection should be established");
+    crate::ensure_eq!(seq_state.snd_una, snapshot.seq_state.snd_una);
+    crate::ensure_eq!(seq_state.rcv_nxt, snapshot.seq_state.rcv_nxt);
+
+    // The data for Alice is sent again, and the data from her is still there to pop.
+    for frame in new_bob.pop_all_frames() {
+        alice.receive(frame)?;
+    }
+    crate::ensure_eq!(pop(&mut alice, alice_qd)?[..], from_bob[..]);
+    crate::ensure_eq!(pop(&mut new_bob, new_bob_qd)?[..], from_alice[..]);
+
+    // New data flows in both directions.
+    let more: DemiBuffer = DemiBuffer::from_slice(&[0x3c; 8][..]).expect("slice should fit in DemiBuffer");
+    push(&mut alice, alice_qd, more.clone())?;
+    for frame in alice.pop_all_frames() {
+        new_bob.receive(frame)?;
+    }
+    crate::ensure_eq!(pop(&mut new_bob, new_bob_qd)?[..], more[..]);
+    push(&mut new_bob, new_bob_qd, more.clone())?;
+    for frame in new_bob.pop_all_frames() {
+        alice.receive(frame)?;
+    }
+    crate::ensure_eq!(pop(&mut alice, alice_qd)?[..], more[..]);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Pushes `buf` on `qd` and waits for the push to complete.
+fn push(engine: &mut SharedEngine, qd: QDesc, buf: DemiBuffer) -> Result<()> {
+    let push_qt: QToken = engine.tcp_push(qd, buf)?;
+    match engine.wait(push_qt)? {
+        (_, OperationResult::Push) => Ok(()),
+        _ => anyhow::bail!("push should succeed"),
+    }
+}
+
+/// Pops from `qd` and returns the data.
+fn pop(engine: &mut SharedEngine, qd: QDesc) -> Result<DemiBuffer> {
+    let pop_qt: QToken = engine.tcp_pop(qd)?;
+    match engine.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, buf)) => Ok(buf),
+        _ => anyhow::bail!("pop should succeed"),
+    }
+}
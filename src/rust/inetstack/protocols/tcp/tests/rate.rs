@@ -0,0 +1,116 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Payload bytes sent at every step.
+const BYTES_PER_STEP: usize = 1000;
+
+/// Time between steps.
+const STEP: Duration = Duration::from_millis(100);
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Sends a known number of bytes at known intervals and checks that both ends report the expected rate over the
+/// default window of one second.
+#[test]
+fn tcp_send_and_recv_rate() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Nothing was sent yet.
+    crate::ensure_eq!(alice.send_rate_bps(alice_qd)?, 0);
+    crate::ensure_eq!(bob.recv_rate_bps(bob_qd)?, 0);
+
+    // Ten steps fill the window.
+    for step in 0..10 {
+        if step > 0 {
+            now += STEP;
+            advance_both(&mut alice, &mut bob, now)?;
+        }
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[0; BYTES_PER_STEP][..]).expect("slice should fit");
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        bob.receive(alice.pop_frame())?;
+    }
+    let expected: u64 = (10 * BYTES_PER_STEP * 8) as u64;
+    ensure_close(alice.send_rate_bps(alice_qd)?, expected)?;
+    ensure_close(bob.recv_rate_bps(bob_qd)?, expected)?;
+
+    // Older steps fall out of the window as time goes by.
+    now += Duration::from_millis(600);
+    advance_both(&mut alice, &mut bob, now)?;
+    let expected: u64 = (4 * BYTES_PER_STEP * 8) as u64;
+    ensure_close(alice.send_rate_bps(alice_qd)?, expected)?;
+    ensure_close(bob.recv_rate_bps(bob_qd)?, expected)?;
+
+    // Once the window is past the last step, the connection is idle.
+    now += Duration::from_secs(1);
+    advance_both(&mut alice, &mut bob, now)?;
+    crate::ensure_eq!(alice.send_rate_bps(alice_qd)?, 0);
+    crate::ensure_eq!(bob.recv_rate_bps(bob_qd)?, 0);
+    crate::ensure_eq!(bob.send_rate_bps(bob_qd)?, 0);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Moves the clocks of both ends to `now` and delivers the acknowledgements that Bob sends meanwhile, so that Alice
+/// does not retransmit.
+fn advance_both(alice: &mut SharedEngine, bob: &mut SharedEngine, now: Instant) -> Result<()> {
+    alice.advance_clock(now);
+    bob.advance_clock(now);
+    bob.poll();
+    for frame in bob.pop_all_frames() {
+        alice.receive(frame)?;
+    }
+    alice.poll();
+    Ok(())
+}
+
+/// Checks that `rate` is within one percent of `expected`.
+fn ensure_close(rate: u64, expected: u64) -> Result<()> {
+    if rate.abs_diff(expected) * 100 > expected {
+        anyhow::bail!("rate should be close to {} bps (rate={} bps)", expected, rate);
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::{
+            connection_setup,
+            connection_setup_with_options,
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Size of the segments that the test sends.
+const SEGMENT_SIZE: usize = 100;
+
+/// Number of segments that the test sends.
+const NUM_SEGMENTS: usize = 8;
+
+/// Number of out-of-order segments that fit in the reassembly limit of Bob.
+const NUM_HELD: usize = 3;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Loses the first of a burst of segments, and checks that Bob holds only as many of the segments after the hole as fit
+/// in its reassembly limit. Once the hole is filled, Bob gets the held segments at once, and the dropped ones are not
+/// there to be popped.
+#[test]
+fn tcp_max_reassembly_bytes() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    match bob.set_socket_option(bob_qd, SocketOption::MaxReassemblyBytes(0)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("zero reassembly limit should fail with EINVAL"),
+    };
+    bob.set_socket_option(bob_qd, SocketOption::MaxReassemblyBytes(NUM_HELD * SEGMENT_SIZE))?;
+
+    check_reassembly_limit(&mut alice, &mut bob, alice_qd, bob_qd)
+}
+
+/// Checks that a connection accepted on a listening socket that has a reassembly limit holds no more out-of-order data
+/// than the limit.
+#[test]
+fn tcp_max_reassembly_bytes_inherited_on_accept() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup_with_options(
+        &mut alice,
+        &mut bob,
+        &[SocketOption::MaxReassemblyBytes(NUM_HELD * SEGMENT_SIZE)],
+    )?;
+
+    check_reassembly_limit(&mut alice, &mut bob, alice_qd, bob_qd)
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Loses the first of a burst of segments from Alice, and checks that Bob holds only [NUM_HELD] of the segments after
+/// the hole.
+fn check_reassembly_limit(
+    alice: &mut SharedEngine,
+    bob: &mut SharedEngine,
+    alice_qd: QDesc,
+    bob_qd: QDesc,
+) -> Result<()> {
+    let mut frames: VecDeque<DemiBuffer> = VecDeque::new();
+    for i in 0..NUM_SEGMENTS {
+        let buf: DemiBuffer =
+            DemiBuffer::from_slice(&[i as u8; SEGMENT_SIZE][..]).expect("slice should fit in DemiBuffer");
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        frames.append(&mut alice.pop_all_frames());
+    }
+    crate::ensure_eq!(frames.len(), NUM_SEGMENTS);
+
+    // The first segment is held up, and all the others arrive out of order.
+    let first: DemiBuffer = frames.pop_front().expect("first segment should have been sent");
+    for frame in frames {
+        bob.receive(frame)?;
+    }
+    bob.poll();
+    bob.pop_all_frames();
+
+    // The first segment fills the hole, and brings the held segments with it.
+    bob.receive(first)?;
+    for i in 0..(NUM_HELD + 1) {
+        let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+        match bob.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(&received[..], &[i as u8; SEGMENT_SIZE][..]),
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+
+    // The segments past the limit were dropped.
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    bob.poll();
+    crate::ensure_eq!(bob.get_runtime().has_completed(pop_qt)?, false);
+
+    Ok(())
+}
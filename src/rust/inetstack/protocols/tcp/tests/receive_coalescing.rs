@@ -0,0 +1,68 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Delivers many back-to-back in-order segments to a receiver that coalesces them, and checks that a single pop
+/// returns all of them in one buffer.
+#[test]
+fn tcp_receive_coalescing() -> Result<()> {
+    const NUM_SEGMENTS: u8 = 64;
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    bob.get_transport().set_receive_coalescing(true);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let mut expected: Vec<u8> = Vec::new();
+    for i in 0..NUM_SEGMENTS {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[i; 32][..]).expect("slice should fit in DemiBuffer");
+        expected.extend_from_slice(&buf[..]);
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        bob.receive(alice.pop_frame())?;
+    }
+
+    // A regular pop returns every segment at once.
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], expected[..]),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    // Nothing is left behind for a second pop.
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    bob.poll();
+    crate::ensure_eq!(bob.get_runtime().has_completed(pop_qt)?, false);
+
+    Ok(())
+}
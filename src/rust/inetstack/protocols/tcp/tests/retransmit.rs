@@ -0,0 +1,81 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Drops data segments on the wire and checks that every retransmission is counted.
+#[test]
+fn tcp_retransmit_count() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    crate::ensure_eq!(alice.retransmit_count(alice_qd)?, 0);
+
+    // Send data to Bob and drop the original transmission.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    alice.pop_frame();
+    // Let the retransmitter arm its timer.
+    alice.poll();
+
+    // Drop every retransmission until the retransmission timer has fired a few times.
+    const NUM_DROPS: u64 = 3;
+    for i in 1..=NUM_DROPS {
+        now += Duration::from_secs(60);
+        alice.advance_clock(now);
+        alice.poll();
+        alice.pop_frame();
+        crate::ensure_eq!(alice.retransmit_count(alice_qd)?, i);
+    }
+
+    // Let the next retransmission through and check that Bob receives the data.
+    now += Duration::from_secs(60);
+    alice.advance_clock(now);
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    crate::ensure_eq!(alice.retransmit_count(alice_qd)?, NUM_DROPS + 1);
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    // Bob has not retransmitted anything.
+    crate::ensure_eq!(bob.retransmit_count(bob_qd)?, 0);
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of connections that Alice opens to the listeners of Bob.
+const NUM_CONNECTIONS: usize = 16;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Binds two listening sockets that allow it to the same address and opens several connections to it. Each connection
+/// is accepted by exactly one of the listeners, and both of them get some.
+#[test]
+fn tcp_reuse_port_spreads_connections() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let mut listeners: Vec<QDesc> = Vec::new();
+    for _ in 0..2 {
+        let listen_qd: QDesc = bob.tcp_socket()?;
+        bob.set_socket_option(listen_qd, SocketOption::ReusePort(true))?;
+        bob.tcp_bind(listen_qd, bob_addr)?;
+        bob.tcp_listen(listen_qd, NUM_CONNECTIONS)?;
+        listeners.push(listen_qd);
+    }
+
+    // A socket that does not allow it cannot join them.
+    let other_qd: QDesc = bob.tcp_socket()?;
+    match bob.tcp_bind(other_qd, bob_addr) {
+        Err(e) if e.errno == libc::EADDRINUSE => {},
+        _ => anyhow::bail!("bind without reuse port should fail with EADDRINUSE"),
+    };
+
+    let mut accept_qts: Vec<QToken> = Vec::new();
+    for listen_qd in &listeners {
+        accept_qts.push(bob.tcp_accept(*listen_qd)?);
+    }
+    let mut accepted: [usize; 2] = [0, 0];
+    for _ in 0..NUM_CONNECTIONS {
+        let alice_qd: QDesc = alice.tcp_socket()?;
+        let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+        alice.receive(bob.pop_frame())?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        match alice.wait(connect_qt)? {
+            (_, OperationResult::Connect) => {},
+            _ => anyhow::bail!("connect should succeed"),
+        };
+
+        // Exactly one of the listeners accepted the connection, which takes a few sweeps of the scheduler. It gets to
+        // accept the next one.
+        for _ in 0..4 {
+            bob.poll();
+        }
+        let mut completed: Vec<usize> = Vec::new();
+        for (i, accept_qt) in accept_qts.iter().enumerate() {
+            if bob.get_runtime().has_completed(*accept_qt)? {
+                completed.push(i);
+            }
+        }
+        crate::ensure_eq!(completed.len(), 1);
+        let i: usize = completed[0];
+        match bob.wait(accept_qts[i])? {
+            (_, OperationResult::Accept(_)) => {},
+            _ => anyhow::bail!("accept should succeed"),
+        };
+        accepted[i] += 1;
+        accept_qts[i] = bob.tcp_accept(listeners[i])?;
+    }
+    crate::ensure_eq!(accepted[0] + accepted[1], NUM_CONNECTIONS);
+    crate::ensure_eq!(accepted[0] > 0, true);
+    crate::ensure_eq!(accepted[1] > 0, true);
+
+    // Once one listener closes, the other one accepts all connections.
+    let close_qt: QToken = bob.tcp_async_close(listeners[0])?;
+    match bob.wait(close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    for _ in 0..4 {
+        let alice_qd: QDesc = alice.tcp_socket()?;
+        let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+        alice.receive(bob.pop_frame())?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        match alice.wait(connect_qt)? {
+            (_, OperationResult::Connect) => {},
+            _ => anyhow::bail!("connect should succeed"),
+        };
+        match bob.wait(accept_qts[1])? {
+            (_, OperationResult::Accept(_)) => {},
+            _ => anyhow::bail!("accept should succeed"),
+        };
+        accept_qts[1] = bob.tcp_accept(listeners[1])?;
+    }
+
+    Ok(())
+}
+
+/// Binds two sockets that allow it to the same address, but only the second of them listens. All connections go to the
+/// listening socket.
+#[test]
+fn tcp_reuse_port_skips_bound_sockets() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    let bound_qd: QDesc = bob.tcp_socket()?;
+    bob.set_socket_option(bound_qd, SocketOption::ReusePort(true))?;
+    bob.tcp_bind(bound_qd, bob_addr)?;
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.set_socket_option(listen_qd, SocketOption::ReusePort(true))?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, NUM_CONNECTIONS)?;
+
+    for _ in 0..NUM_CONNECTIONS {
+        let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+        let alice_qd: QDesc = alice.tcp_socket()?;
+        let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+        alice.receive(bob.pop_frame())?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        match alice.wait(connect_qt)? {
+            (_, OperationResult::Connect) => {},
+            _ => anyhow::bail!("connect should succeed"),
+        };
+        match bob.wait(accept_qt)? {
+            (_, OperationResult::Accept(_)) => {},
+            _ => anyhow::bail!("accept should succeed"),
+        };
+    }
+
+    Ok(())
+}
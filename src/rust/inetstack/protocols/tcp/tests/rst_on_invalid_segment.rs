@@ -0,0 +1,252 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::{
+                    TcpHeader,
+                    TcpSegment,
+                },
+                sequence_number::SeqNumber,
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::PacketBuf,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    collections::VecDeque,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Has Bob send data to Alice and close the connection, and then replays the data to Alice. Returns the replayed frame
+/// and the frames that Alice sends in response.
+fn replay_after_close(rst_on_invalid_segment: bool) -> Result<(DemiBuffer, VecDeque<DemiBuffer>)> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    alice.get_transport().set_rst_on_invalid_segment(rst_on_invalid_segment);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(b"data").expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = bob.tcp_push(bob_qd, buf)?;
+    match bob.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let data: DemiBuffer = bob.pop_frame();
+    alice.receive(data.clone())?;
+
+    // Bob closes first, so Alice's end of the connection goes away once she closes too.
+    let bob_close_qt: QToken = bob.tcp_async_close(bob_qd)?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    let alice_close_qt: QToken = alice.tcp_async_close(alice_qd)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match bob.wait(bob_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    alice.receive(bob.pop_frame())?;
+    match alice.wait(alice_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    crate::ensure_eq!(alice.pop_all_frames().is_empty(), true);
+
+    alice.receive(data.clone())?;
+    alice.poll();
+    Ok((data, alice.pop_all_frames()))
+}
+
+/// Has Alice close the connection, and Bob close it too, so that Bob waits in LAST_ACK for Alice to acknowledge his FIN.
+/// Then has Alice send data past her FIN. Returns the frames that Bob sends in response, and the sequence number that
+/// follows Bob's FIN.
+fn data_in_last_ack(rst_on_invalid_segment: bool) -> Result<(VecDeque<DemiBuffer>, SeqNumber)> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    bob.get_transport().set_rst_on_invalid_segment(rst_on_invalid_segment);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    // Alice closes, and Bob acknowledges her FIN.
+    let _alice_close_qt: QToken = alice.tcp_async_close(alice_qd)?;
+    alice.poll();
+    let fin: DemiBuffer = alice.pop_frame();
+    bob.receive(fin.clone())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+
+    // Bob closes too, and his FIN is lost.
+    let _bob_close_qt: QToken = bob.tcp_async_close(bob_qd)?;
+    bob.poll();
+    let bob_fin_hdr: TcpHeader = tcp_header(bob.pop_frame())?;
+
+    bob.receive(data_past_fin(fin, b"data")?)?;
+    bob.poll();
+    Ok((bob.pop_all_frames(), bob_fin_hdr.seq_num + SeqNumber::from(1)))
+}
+
+/// Turns the FIN segment in `fin` into a segment that carries `payload` right after the FIN.
+fn data_past_fin(fin: DemiBuffer, payload: &[u8]) -> Result<DemiBuffer> {
+    let (ethernet2_hdr, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(fin)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (mut tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    tcp_hdr.fin = false;
+    tcp_hdr.seq_num = tcp_hdr.seq_num + SeqNumber::from(1);
+    let segment: TcpSegment = TcpSegment {
+        ethernet2_hdr,
+        ipv4_hdr,
+        tcp_hdr,
+        data: Some(DemiBuffer::from_slice(payload)?),
+        tx_checksum_offload: false,
+    };
+
+    let header_size: usize = segment.header_size();
+    let body_size: usize = segment.body_size();
+    let mut buf: DemiBuffer = DemiBuffer::new((header_size + body_size) as u16);
+    segment.write_header(&mut buf[..header_size]);
+    if let Some(body) = segment.take_body() {
+        buf[header_size..].copy_from_slice(&body[..]);
+    }
+    Ok(buf)
+}
+
+/// Parses the TCP header of `frame`.
+fn tcp_header(frame: DemiBuffer) -> Result<TcpHeader> {
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(frame)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (tcp_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    Ok(tcp_hdr)
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that data that arrives after the connection was closed is answered with a RST when asked to.
+#[test]
+fn tcp_rst_on_invalid_segment() -> Result<()> {
+    let (data, mut frames): (DemiBuffer, VecDeque<DemiBuffer>) = replay_after_close(true)?;
+
+    crate::ensure_eq!(frames.len(), 1);
+    let data_hdr: TcpHeader = tcp_header(data)?;
+    let rst_hdr: TcpHeader = tcp_header(frames.pop_front().expect("should be one frame"))?;
+    crate::ensure_eq!(rst_hdr.rst, true);
+    crate::ensure_eq!(rst_hdr.ack, false);
+    crate::ensure_eq!(rst_hdr.seq_num, data_hdr.ack_num);
+    crate::ensure_eq!(rst_hdr.src_port, data_hdr.dst_port);
+    crate::ensure_eq!(rst_hdr.dst_port, data_hdr.src_port);
+
+    Ok(())
+}
+
+/// Checks that data that arrives after the connection was closed is dropped silently by default.
+#[test]
+fn tcp_drop_invalid_segment() -> Result<()> {
+    let (_, frames): (DemiBuffer, VecDeque<DemiBuffer>) = replay_after_close(false)?;
+    crate::ensure_eq!(frames.is_empty(), true);
+
+    Ok(())
+}
+
+/// Checks that data that arrives after a connection accepted on a listening socket was closed is answered with a RST by
+/// the listening socket, rather than taken by the connection that went away.
+#[test]
+fn tcp_rst_on_segment_for_closed_accepted_connection() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(b"data").expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let data: DemiBuffer = alice.pop_frame();
+    bob.receive(data.clone())?;
+
+    // Alice closes first, so Bob's end of the connection goes away once he closes too.
+    let alice_close_qt: QToken = alice.tcp_async_close(alice_qd)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    let bob_close_qt: QToken = bob.tcp_async_close(bob_qd)?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    match alice.wait(alice_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    match bob.wait(bob_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    bob.pop_all_frames();
+
+    bob.receive(data.clone())?;
+    bob.poll();
+    let mut frames: VecDeque<DemiBuffer> = bob.pop_all_frames();
+    crate::ensure_eq!(frames.len(), 1);
+    let data_hdr: TcpHeader = tcp_header(data)?;
+    let rst_hdr: TcpHeader = tcp_header(frames.pop_front().expect("should be one frame"))?;
+    crate::ensure_eq!(rst_hdr.rst, true);
+    crate::ensure_eq!(rst_hdr.seq_num, data_hdr.ack_num);
+
+    Ok(())
+}
+
+/// Checks that data that arrives past the FIN of the remote while we wait in LAST_ACK is answered with a RST when asked
+/// to.
+#[test]
+fn tcp_rst_on_data_in_last_ack() -> Result<()> {
+    let (mut frames, send_next): (VecDeque<DemiBuffer>, SeqNumber) = data_in_last_ack(true)?;
+
+    crate::ensure_eq!(frames.len(), 1);
+    let rst_hdr: TcpHeader = tcp_header(frames.pop_front().expect("should be one frame"))?;
+    crate::ensure_eq!(rst_hdr.rst, true);
+    crate::ensure_eq!(rst_hdr.seq_num, send_next);
+
+    Ok(())
+}
+
+/// Checks that data that arrives past the FIN of the remote while we wait in LAST_ACK is dropped silently by default.
+#[test]
+fn tcp_drop_data_in_last_ack() -> Result<()> {
+    let (frames, _): (VecDeque<DemiBuffer>, SeqNumber) = data_in_last_ack(false)?;
+    crate::ensure_eq!(frames.is_empty(), true);
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Floods a peer that never acknowledges anything and checks that pushes fail with EWOULDBLOCK once the send buffer is
+/// full.
+#[test]
+fn tcp_send_buffer_size_backpressure() -> Result<()> {
+    const SEND_BUFFER_SIZE: usize = 8192;
+    const PUSH_SIZE: usize = 1024;
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    crate::ensure_eq!(alice.send_buffer_size(alice_qd)?, None);
+
+    alice.set_socket_option(alice_qd, SocketOption::SendBufferSize(SEND_BUFFER_SIZE))?;
+    crate::ensure_eq!(alice.send_buffer_size(alice_qd)?, Some(SEND_BUFFER_SIZE));
+
+    // Bob is stalled: nothing Alice sends reaches him, so nothing is ever acknowledged.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; PUSH_SIZE][..]).expect("slice should fit in DemiBuffer");
+    for _ in 0..(SEND_BUFFER_SIZE / PUSH_SIZE) {
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed while the send buffer has room"),
+        };
+        alice.pop_all_frames();
+    }
+
+    // The send buffer is now full.
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::EWOULDBLOCK => {},
+        _ => anyhow::bail!("push should fail once the send buffer is full"),
+    };
+
+    // Sizes below one MSS are rounded up.
+    alice.set_socket_option(alice_qd, SocketOption::SendBufferSize(1))?;
+    crate::ensure_eq!(alice.send_buffer_size(alice_qd)? > Some(1), true);
+
+    Ok(())
+}
+
+/// Floods a peer that never acknowledges anything with several megabytes of data, and checks that a send buffer that was
+/// never bounded takes all of it.
+#[test]
+fn tcp_send_buffer_unbounded_by_default() -> Result<()> {
+    const PUSH_SIZE: usize = 64000;
+    const NUM_PUSHES: usize = 80;
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; PUSH_SIZE][..]).expect("slice should fit in DemiBuffer");
+    for _ in 0..NUM_PUSHES {
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed while the send buffer is unbounded"),
+        };
+        alice.pop_all_frames();
+    }
+    crate::ensure_eq!(alice.send_buffer_size(alice_qd)?, None);
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::types::TcpSeqState,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Returns the sequence state of the connection referred to by `qd`, failing if there is none.
+fn sequence_state(engine: &SharedEngine, qd: QDesc) -> Result<TcpSeqState> {
+    match engine.tcp_sequence_state(qd)? {
+        Some(state) => Ok(state),
+        None => anyhow::bail!("connected socket should have a sequence state"),
+    }
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Pushes data over a connection and checks that SND.NXT advances right away, while SND.UNA only catches up once the
+/// peer acknowledges the data.
+#[test]
+fn tcp_sequence_state() -> Result<()> {
+    const NUM_BYTES: usize = 32;
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+
+    // Sockets that are not connected have no sequence space.
+    let qd: QDesc = alice.tcp_socket()?;
+    crate::ensure_eq!(alice.tcp_sequence_state(qd)?, None);
+
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let before: TcpSeqState = sequence_state(&alice, alice_qd)?;
+    crate::ensure_eq!(before.snd_una, before.snd_nxt);
+    crate::ensure_eq!(before.rcv_nxt, sequence_state(&bob, bob_qd)?.snd_nxt);
+
+    // Send data to Bob.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; NUM_BYTES][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let sent: TcpSeqState = sequence_state(&alice, alice_qd)?;
+    crate::ensure_eq!(sent.snd_nxt, before.snd_nxt.wrapping_add(NUM_BYTES as u32));
+    crate::ensure_eq!(sent.snd_una, before.snd_una);
+
+    // Bob receives the data and acknowledges it once the delayed ACK timer fires.
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    crate::ensure_eq!(
+        sequence_state(&bob, bob_qd)?.rcv_nxt,
+        before.snd_nxt.wrapping_add(NUM_BYTES as u32)
+    );
+    now += Duration::from_secs(1);
+    bob.advance_clock(now);
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    let acked: TcpSeqState = sequence_state(&alice, alice_qd)?;
+    crate::ensure_eq!(acked.snd_una, sent.snd_nxt);
+    crate::ensure_eq!(acked.snd_nxt, sent.snd_nxt);
+
+    Ok(())
+}
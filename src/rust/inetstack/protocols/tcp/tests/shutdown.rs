@@ -0,0 +1,160 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+            SharedTestRuntime,
+        },
+        SharedInetStack,
+    },
+    runtime::{
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::futures::{
+    pin_mut,
+    task::noop_waker_ref,
+};
+use ::std::{
+    future::Future,
+    net::SocketAddrV4,
+    task::Context,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of connections that Alice opens to Bob.
+const NUM_CONNECTIONS: usize = 3;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Shuts Alice down while Bob answers her FINs, and checks that every connection is drained gracefully.
+#[test]
+fn tcp_shutdown_drains_connections() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_qds: Vec<QDesc> = connect(&mut alice, &mut bob)?;
+
+    // Bob closes his ends of the connections, so that Alice's closes can complete.
+    for bob_qd in bob_qds {
+        bob.async_close(bob_qd)?;
+    }
+
+    // Shutdown blocks Alice, so exchange frames with Bob at the start of each of her poll sweeps.
+    let mut alice_transport: SharedInetStack<SharedTestRuntime> = alice.get_transport();
+    let mut bob_transport: SharedInetStack<SharedTestRuntime> = bob.get_transport();
+    let mut bob_runtime: SharedDemiRuntime = bob.get_runtime();
+    alice.get_runtime().set_per_sweep_hook(Box::new(move || {
+        for frame in alice_transport.get_network().pop_all_frames() {
+            bob_transport.receive(frame).expect("bob should accept the frame");
+        }
+        bob_runtime.poll();
+        for frame in bob_transport.get_network().pop_all_frames() {
+            alice_transport.receive(frame).expect("alice should accept the frame");
+        }
+    }));
+
+    let undrained: Vec<QDesc> = alice.shutdown_all(Duration::from_secs(10));
+    alice.get_runtime().set_per_sweep_hook(Box::new(|| {}));
+    crate::ensure_eq!(undrained.is_empty(), true);
+
+    // All of Alice's queues are closed and no operation is left in her scheduler.
+    crate::ensure_eq!(alice.get_runtime().get_qtable().get_qds().is_empty(), true);
+    crate::ensure_eq!(alice.get_runtime().has_pending_ops(), false);
+
+    // Bob pings Alice, but her stack stopped receiving, so she does not answer.
+    let mut bob_transport: SharedInetStack<SharedTestRuntime> = bob.get_transport();
+    let ping = bob_transport.ping(test_helpers::ALICE_IPV4, None);
+    pin_mut!(ping);
+    let mut ctx: Context = Context::from_waker(noop_waker_ref());
+    crate::ensure_eq!(Future::poll(ping.as_mut(), &mut ctx).is_pending(), true);
+    alice.get_transport().get_network().push_frame(bob.pop_frame());
+    // A running stack would send the reply in the sweep after the one that receives the request.
+    alice.poll();
+    alice.poll();
+    crate::ensure_eq!(alice.pop_all_frames().is_empty(), true);
+
+    Ok(())
+}
+
+/// Shuts Alice down while Bob does not answer, and checks that the connections are released anyway.
+#[test]
+fn tcp_shutdown_times_out() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    connect(&mut alice, &mut bob)?;
+    let alice_qds: Vec<QDesc> = alice.get_runtime().get_qtable().get_qds();
+
+    // Every connection could not be drained.
+    let undrained: Vec<QDesc> = alice.shutdown_all(Duration::ZERO);
+    crate::ensure_eq!(undrained.len(), alice_qds.len());
+    for qd in alice_qds {
+        crate::ensure_eq!(undrained.contains(&qd), true);
+    }
+
+    crate::ensure_eq!(alice.get_runtime().get_qtable().get_qds().is_empty(), true);
+    crate::ensure_eq!(alice.get_runtime().has_pending_ops(), false);
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Opens [NUM_CONNECTIONS] connections from Alice to a listening socket of Bob and returns Bob's ends of them.
+fn connect(alice: &mut SharedEngine, bob: &mut SharedEngine) -> Result<Vec<QDesc>> {
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, NUM_CONNECTIONS)?;
+
+    let mut bob_qds: Vec<QDesc> = Vec::with_capacity(NUM_CONNECTIONS);
+    for _ in 0..NUM_CONNECTIONS {
+        let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+        let alice_qd: QDesc = alice.tcp_socket()?;
+        let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+
+        // SYN, SYN+ACK, and ACK.
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+        bob.poll();
+        alice.receive(bob.pop_frame())?;
+        alice.poll();
+        bob.receive(alice.pop_frame())?;
+
+        match alice.wait(connect_qt)? {
+            (_, OperationResult::Connect) => {},
+            _ => anyhow::bail!("connect should succeed"),
+        };
+        match bob.wait(accept_qt)? {
+            (_, OperationResult::Accept((qd, _))) => bob_qds.push(qd),
+            _ => anyhow::bail!("accept should succeed"),
+        };
+    }
+
+    Ok(bob_qds)
+}
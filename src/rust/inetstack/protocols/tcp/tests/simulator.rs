@@ -277,6 +277,7 @@ impl Simulation {
 
         ArpConfig::new(
             cache_ttl,
+            None,
             request_timeout,
             retry_count,
             Some(initial_values),
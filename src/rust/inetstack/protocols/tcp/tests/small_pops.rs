@@ -0,0 +1,80 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::{
+            BufferPoolStats,
+            DemiBuffer,
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of small messages that Alice sends to Bob.
+const NUM_MESSAGES: usize = 128;
+
+/// Size of each message, and of each pop.
+const MESSAGE_SIZE: usize = 16;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Pops many small messages and checks that their buffers are recycled through the small-buffer pool.
+#[test]
+fn tcp_small_pops() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    let before: BufferPoolStats = DemiBuffer::small_buffer_pool_stats();
+    for i in 0..NUM_MESSAGES {
+        let buf: DemiBuffer =
+            DemiBuffer::from_slice(&[i as u8; MESSAGE_SIZE][..]).expect("slice should fit in DemiBuffer");
+        let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+        match alice.wait(push_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("push should succeed"),
+        };
+        bob.receive(alice.pop_frame())?;
+
+        // The popped buffer is dropped at the end of each iteration, which returns it to the pool.
+        let pop_qt: QToken = bob.pop(bob_qd, Some(MESSAGE_SIZE))?;
+        match bob.wait(pop_qt)? {
+            (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+            _ => anyhow::bail!("pop should succeed"),
+        };
+    }
+    let after: BufferPoolStats = DemiBuffer::small_buffer_pool_stats();
+
+    let hits: usize = after.hits - before.hits;
+    let misses: usize = after.misses - before.misses;
+    crate::ensure_eq!(hits + misses, NUM_MESSAGES);
+    crate::ensure_eq!(misses <= 1, true);
+    crate::ensure_eq!(after.allocation_failures, 0);
+
+    Ok(())
+}
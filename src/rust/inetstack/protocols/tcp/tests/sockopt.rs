@@ -0,0 +1,86 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    pal::constants::{
+        IPPROTO_TCP,
+        SOL_SOCKET,
+        SO_RCVBUF,
+        SO_SNDBUF,
+        TCP_NODELAY,
+    },
+    runtime::{
+        network::socket::option::{
+            SocketOption,
+            SocketOptionName,
+        },
+        queue::QDesc,
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Sets options from their raw encoding on a connected socket, and checks that they read back the same way.
+#[test]
+fn tcp_sockopt_round_trip() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    for (level, name, value) in [
+        (IPPROTO_TCP, TCP_NODELAY, 1),
+        (SOL_SOCKET, SO_RCVBUF, 4096),
+        (SOL_SOCKET, SO_SNDBUF, 8192),
+    ] {
+        let raw: [u8; 4] = i32::to_ne_bytes(value);
+        alice.set_socket_option(alice_qd, SocketOption::from_raw(level, name, &raw)?)?;
+        let option: SocketOption = alice.get_socket_option(alice_qd, SocketOptionName::from_raw(level, name)?)?;
+        crate::ensure_eq!(option.to_raw()?, raw.to_vec());
+    }
+
+    Ok(())
+}
+
+/// Checks that options we do not know about, and turning Nagle's algorithm on, are refused rather than ignored.
+#[test]
+fn tcp_sockopt_unsupported() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    match SocketOption::from_raw(IPPROTO_TCP, -1, &i32::to_ne_bytes(1)) {
+        Err(e) if e.errno == libc::ENOPROTOOPT => {},
+        _ => anyhow::bail!("unknown socket options should fail with ENOPROTOOPT"),
+    }
+    match SocketOption::from_raw(SOL_SOCKET, SO_SNDBUF, &[0; 2]) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("truncated socket option values should fail with EINVAL"),
+    }
+    match alice.set_socket_option(alice_qd, SocketOption::NoDelay(false)) {
+        Err(e) if e.errno == libc::ENOTSUP => {},
+        _ => anyhow::bail!("disabling TCP_NODELAY should fail with ENOTSUP"),
+    }
+    crate::ensure_eq!(
+        alice.get_socket_option(alice_qd, SocketOptionName::NoDelay)?,
+        SocketOption::NoDelay(true)
+    );
+
+    Ok(())
+}
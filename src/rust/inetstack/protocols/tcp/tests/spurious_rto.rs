@@ -0,0 +1,185 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Size of the segments that the tests send.
+const SEGMENT_SIZE: usize = 100;
+
+/// Round-trip time of the path between Alice and Bob.
+const RTT: Duration = Duration::from_millis(100);
+
+/// Retransmission timeout after a single RTT sample, as computed by RFC 6298: the sample plus four times half of it.
+const RTO: Duration = Duration::from_millis(300);
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Delays the ACK of a segment past the retransmission timeout, so that the timeout fires although nothing was lost.
+/// The ACK arrives right after the retransmission, too soon to answer it, so the stack tells that the timeout was
+/// spurious and restores the congestion window that it collapsed.
+#[test]
+fn tcp_spurious_rto_restores_cwnd() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd, cwnd): (QDesc, QDesc, u32) = setup(&mut alice, &mut bob, &mut now)?;
+
+    // Bob acknowledges the segment right away, but the ACK is held up on the way.
+    push(&mut alice, alice_qd)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    let delayed_ack: DemiBuffer = bob.pop_frame();
+
+    // The retransmission timer fires and collapses the congestion window.
+    now += RTO;
+    alice.advance_clock(now);
+    alice.poll();
+    alice.pop_frame();
+    crate::ensure_eq!(alice.retransmit_count(alice_qd)?, 1);
+    crate::ensure_eq!(alice.cwnd(alice_qd)?.unwrap_or(0) < cwnd, true);
+
+    // The delayed ACK restores it, and grows it as if the timeout had never fired.
+    alice.receive(delayed_ack)?;
+    crate::ensure_eq!(alice.cwnd(alice_qd)?, Some(cwnd + SEGMENT_SIZE as u32));
+
+    // Bob got the data only once.
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received.len(), SEGMENT_SIZE),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+
+    Ok(())
+}
+
+/// Loses a segment, so that the timeout that retransmits it is genuine. The ACK of the retransmission takes a full RTT
+/// to arrive, and the congestion window stays collapsed.
+#[test]
+fn tcp_genuine_rto_keeps_cwnd_collapsed() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _, cwnd): (QDesc, QDesc, u32) = setup(&mut alice, &mut bob, &mut now)?;
+
+    push(&mut alice, alice_qd)?;
+    alice.poll();
+    alice.pop_frame();
+
+    now += RTO;
+    alice.advance_clock(now);
+    bob.advance_clock(now);
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    crate::ensure_eq!(alice.retransmit_count(alice_qd)?, 1);
+
+    now += RTT;
+    alice.advance_clock(now);
+    alice.receive(bob.pop_frame())?;
+    crate::ensure_eq!(alice.cwnd(alice_qd)?.unwrap_or(u32::MAX) < cwnd, true);
+
+    Ok(())
+}
+
+/// Drops every transmission of a segment and checks that the retransmission timeout does not back off past its bound.
+#[test]
+fn tcp_max_rto() -> Result<()> {
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    match alice.set_socket_option(alice_qd, SocketOption::MaxRto(Duration::ZERO)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("zero retransmission timeout bound should fail with EINVAL"),
+    };
+    const MAX_RTO: Duration = Duration::from_millis(200);
+    alice.set_socket_option(alice_qd, SocketOption::MaxRto(MAX_RTO))?;
+
+    push(&mut alice, alice_qd)?;
+    alice.poll();
+    alice.pop_frame();
+
+    // Without the bound, the timeout would double after every retransmission.
+    const NUM_DROPS: u64 = 4;
+    for i in 1..=NUM_DROPS {
+        now += MAX_RTO;
+        alice.advance_clock(now);
+        alice.poll();
+        alice.pop_frame();
+        crate::ensure_eq!(alice.retransmit_count(alice_qd)?, i);
+    }
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Connects Alice to Bob with congestion control, and has Alice take a sample of the RTT of the path. Returns the
+/// queues of the connection and the congestion window of Alice after the sample.
+fn setup(alice: &mut SharedEngine, bob: &mut SharedEngine, now: &mut Instant) -> Result<(QDesc, QDesc, u32)> {
+    alice.get_transport().set_congestion_control(true);
+    bob.get_transport().set_congestion_control(true);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(alice, bob)?;
+    bob.set_socket_option(bob_qd, SocketOption::QuickAck(true))?;
+
+    push(alice, alice_qd)?;
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    *now += RTT;
+    alice.advance_clock(*now);
+    bob.advance_clock(*now);
+    alice.receive(bob.pop_frame())?;
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    bob.wait(pop_qt)?;
+
+    match alice.cwnd(alice_qd)? {
+        Some(cwnd) => Ok((alice_qd, bob_qd, cwnd)),
+        None => anyhow::bail!("connected socket should have a congestion window"),
+    }
+}
+
+/// Pushes one segment's worth of data on a connection.
+fn push(engine: &mut SharedEngine, qd: QDesc) -> Result<()> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; SEGMENT_SIZE][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = engine.tcp_push(qd, buf)?;
+    match engine.wait(push_qt)? {
+        (_, OperationResult::Push) => Ok(()),
+        _ => anyhow::bail!("push should succeed"),
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::test_helpers::{
+        self,
+        engine::SharedEngine,
+    },
+    runtime::{
+        network::socket::{
+            operation::SocketOp,
+            state::{
+                SocketState,
+                SocketTransition,
+            },
+        },
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Drives a listening socket and a connecting socket through their lifecycles and checks that their histories hold
+/// every committed transition, in order, and none of the operations that failed.
+#[test]
+fn tcp_state_history() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let mut bob: SharedEngine = test_helpers::new_bob2(t0);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Bob listens, and fails to bind a second time, to another address.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    crate::ensure_eq!(bob.state_history(listen_qd)?, vec![]);
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 1)?;
+    match bob.tcp_bind(listen_qd, SocketAddrV4::new(test_helpers::BOB_IPV4, 81)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("second bind should fail with EINVAL"),
+    };
+    crate::ensure_eq!(
+        bob.state_history(listen_qd)?,
+        vec![
+            transition(SocketOp::Bind, SocketState::Unbound, SocketState::Bound, t0),
+            transition(SocketOp::Listen, SocketState::Bound, SocketState::PassiveListening, t0),
+        ]
+    );
+
+    // Alice connects to Bob a second later.
+    let t1: Instant = t0 + Duration::from_secs(1);
+    alice.advance_clock(t1);
+    bob.advance_clock(t1);
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+
+    // Accepted connections start out established.
+    crate::ensure_eq!(bob.state_history(bob_qd)?, vec![]);
+
+    // Alice starts closing the connection a second after that.
+    let t2: Instant = t1 + Duration::from_secs(1);
+    alice.advance_clock(t2);
+    alice.tcp_async_close(alice_qd)?;
+    crate::ensure_eq!(
+        alice.state_history(alice_qd)?,
+        vec![
+            transition(
+                SocketOp::Connect,
+                SocketState::Unbound,
+                SocketState::ActiveConnecting,
+                t1
+            ),
+            transition(
+                SocketOp::Established,
+                SocketState::ActiveConnecting,
+                SocketState::ActiveEstablished,
+                t1
+            ),
+            transition(
+                SocketOp::Close,
+                SocketState::ActiveEstablished,
+                SocketState::Closing,
+                t2
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Builds the expected record of a transition.
+fn transition(op: SocketOp, from: SocketState, to: SocketState, timestamp: Instant) -> SocketTransition {
+    SocketTransition {
+        op,
+        from,
+        to,
+        timestamp,
+    }
+}
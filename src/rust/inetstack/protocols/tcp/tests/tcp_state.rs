@@ -0,0 +1,115 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    demikernel::libos::network::queue::SharedNetworkQueue,
+    inetstack::{
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+            SharedTestRuntime,
+        },
+        SharedInetStack,
+    },
+    runtime::{
+        network::types::TcpState,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Types
+//======================================================================================================================
+
+type TestQueue = SharedNetworkQueue<SharedInetStack<SharedTestRuntime>>;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Drives a connection through the three-way handshake and the close protocol, and checks the RFC 793 state of both
+/// ends at every step. Alice closes first and ends up in TIME_WAIT, while Bob goes through CLOSE_WAIT and LAST_ACK.
+#[test]
+fn tcp_state_transitions() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Handshake.
+    let listen_qd: QDesc = bob.tcp_socket()?;
+    crate::ensure_eq!(bob.tcp_state(listen_qd)?, Some(TcpState::Closed));
+    bob.tcp_bind(listen_qd, bob_addr)?;
+    bob.tcp_listen(listen_qd, 8)?;
+    crate::ensure_eq!(bob.tcp_state(listen_qd)?, Some(TcpState::Listen));
+    let accept_qt: QToken = bob.tcp_accept(listen_qd)?;
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let connect_qt: QToken = alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    crate::ensure_eq!(alice.tcp_state(alice_qd)?, Some(TcpState::SynSent));
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match alice.wait(connect_qt)? {
+        (_, OperationResult::Connect) => {},
+        _ => anyhow::bail!("connect should succeed"),
+    };
+    let bob_qd: QDesc = match bob.wait(accept_qt)? {
+        (_, OperationResult::Accept((qd, _))) => qd,
+        _ => anyhow::bail!("accept should succeed"),
+    };
+    crate::ensure_eq!(alice.tcp_state(alice_qd)?, Some(TcpState::Established));
+    crate::ensure_eq!(bob.tcp_state(bob_qd)?, Some(TcpState::Established));
+
+    // The descriptors are released once the closes complete, so keep the queues to look at their final states.
+    let alice_queue: TestQueue = alice.get_runtime().get_shared_queue(&alice_qd)?;
+    let bob_queue: TestQueue = bob.get_runtime().get_shared_queue(&bob_qd)?;
+
+    // Alice sends her FIN, which Bob acknowledges.
+    let alice_close_qt: QToken = alice.tcp_async_close(alice_qd)?;
+    alice.poll();
+    crate::ensure_eq!(alice_queue.tcp_state(), Some(TcpState::FinWait1));
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    crate::ensure_eq!(bob_queue.tcp_state(), Some(TcpState::CloseWait));
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    crate::ensure_eq!(alice_queue.tcp_state(), Some(TcpState::FinWait2));
+
+    // Bob sends his FIN, which Alice acknowledges.
+    let bob_close_qt: QToken = bob.tcp_async_close(bob_qd)?;
+    bob.poll();
+    crate::ensure_eq!(bob_queue.tcp_state(), Some(TcpState::LastAck));
+    alice.receive(bob.pop_frame())?;
+    match alice.wait(alice_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    crate::ensure_eq!(alice_queue.tcp_state(), Some(TcpState::TimeWait));
+    bob.receive(alice.pop_frame())?;
+    match bob.wait(bob_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    crate::ensure_eq!(bob_queue.tcp_state(), Some(TcpState::Closed));
+
+    crate::ensure_eq!(TcpState::FinWait2.to_string(), "FIN_WAIT_2");
+    crate::ensure_eq!(TcpState::TimeWait.to_string(), "TIME_WAIT");
+
+    Ok(())
+}
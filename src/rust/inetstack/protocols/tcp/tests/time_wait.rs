@@ -0,0 +1,174 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::{
+    net::SocketAddrV4,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Actively closes a connection and checks that its local address can only be bound again after 2*MSL.
+#[test]
+fn tcp_time_wait_max_segment_lifetime() -> Result<()> {
+    const MSL: Duration = Duration::from_millis(10);
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    alice.get_transport().set_max_segment_lifetime(Some(MSL))?;
+    let alice_addr: SocketAddrV4 = connect_and_close(&mut alice, &mut bob)?;
+
+    // Alice's connection is in TIME_WAIT, so its address cannot be bound yet.
+    let qd: QDesc = alice.tcp_socket()?;
+    match alice.tcp_bind(qd, alice_addr) {
+        Err(e) if e.errno == libc::EADDRINUSE => {},
+        _ => anyhow::bail!("bind should fail while the address is in TIME_WAIT"),
+    };
+
+    // Once 2*MSL has elapsed, the address is free again.
+    now += 2 * MSL;
+    alice.advance_clock(now);
+    alice.tcp_bind(qd, alice_addr)?;
+
+    Ok(())
+}
+
+/// Checks that, by default, the local address of an actively closed connection stays in TIME_WAIT for 2 * 60 seconds.
+#[test]
+fn tcp_time_wait_default_max_segment_lifetime() -> Result<()> {
+    const DEFAULT_MSL: Duration = Duration::from_secs(60);
+    let mut now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let alice_addr: SocketAddrV4 = connect_and_close(&mut alice, &mut bob)?;
+
+    // Alice's connection is still in TIME_WAIT just before 2*MSL elapses.
+    let qd: QDesc = alice.tcp_socket()?;
+    now += 2 * DEFAULT_MSL - Duration::from_millis(1);
+    alice.advance_clock(now);
+    match alice.tcp_bind(qd, alice_addr) {
+        Err(e) if e.errno == libc::EADDRINUSE => {},
+        _ => anyhow::bail!("bind should fail while the address is in TIME_WAIT"),
+    };
+
+    // Once 2*MSL has elapsed, the address is free again.
+    now += Duration::from_millis(1);
+    alice.advance_clock(now);
+    alice.tcp_bind(qd, alice_addr)?;
+
+    Ok(())
+}
+
+/// Checks that the local address of an actively closed connection can be bound again right away when TIME_WAIT is
+/// disabled.
+#[test]
+fn tcp_time_wait_disabled() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    alice.get_transport().set_max_segment_lifetime(None)?;
+    let alice_addr: SocketAddrV4 = connect_and_close(&mut alice, &mut bob)?;
+
+    let qd: QDesc = alice.tcp_socket()?;
+    alice.tcp_bind(qd, alice_addr)?;
+
+    Ok(())
+}
+
+/// Checks that connections do not get an ephemeral port whose address is in TIME_WAIT.
+#[test]
+fn tcp_time_wait_ephemeral_port() -> Result<()> {
+    const MSL: Duration = Duration::from_millis(10);
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    alice.get_transport().set_max_segment_lifetime(Some(MSL))?;
+    let alice_addr: SocketAddrV4 = connect_and_close(&mut alice, &mut bob)?;
+
+    // The port of the closed connection was freed last, so it would be allocated first if it were not in TIME_WAIT.
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    alice.tcp_connect(alice_qd, bob_addr)?;
+    alice.poll();
+    let syn: DemiBuffer = alice.pop_frame();
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(syn)?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (syn_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    crate::ensure_eq!(syn_hdr.syn, true);
+    crate::ensure_neq!(syn_hdr.src_port, alice_addr.port());
+
+    Ok(())
+}
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Opens a connection from Alice to Bob, which Alice closes first, and returns the local address of Alice.
+fn connect_and_close(alice: &mut SharedEngine, bob: &mut SharedEngine) -> Result<SocketAddrV4> {
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(alice, bob)?;
+
+    // Alice closes first and learns her local address from the FIN.
+    let alice_close_qt: QToken = alice.tcp_async_close(alice_qd)?;
+    alice.poll();
+    let fin: DemiBuffer = alice.pop_frame();
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(fin.clone())?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (fin_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    crate::ensure_eq!(fin_hdr.fin, true);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, fin_hdr.src_port);
+
+    // Bob acknowledges the FIN and closes his side.
+    bob.receive(fin)?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    let bob_close_qt: QToken = bob.tcp_async_close(bob_qd)?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    match alice.wait(alice_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    match bob.wait(bob_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+
+    Ok(alice_addr)
+}
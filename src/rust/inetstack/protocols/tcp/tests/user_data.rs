@@ -0,0 +1,77 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Attaches user data to a connection and checks that it can be looked up from the results of its operations until
+/// the connection is closed.
+#[test]
+fn tcp_user_data() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let mut runtime: SharedDemiRuntime = alice.get_runtime();
+
+    // Nothing is attached until the application attaches something, and the latest value wins.
+    crate::ensure_eq!(runtime.get_user_data(alice_qd), None);
+    runtime.set_user_data(alice_qd, 7)?;
+    runtime.set_user_data(alice_qd, 42)?;
+
+    // The value is found from the queue of a completed operation.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    let qd: QDesc = match alice.wait(push_qt)? {
+        (qd, OperationResult::Push) => qd,
+        _ => anyhow::bail!("push should succeed"),
+    };
+    crate::ensure_eq!(runtime.get_user_data(qd), Some(42));
+
+    // Values are dropped along with their queue, and cannot be attached to queues that do not exist.
+    let alice_close_qt: QToken = alice.tcp_async_close(alice_qd)?;
+    alice.poll();
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    let _bob_close_qt: QToken = bob.tcp_async_close(bob_qd)?;
+    bob.poll();
+    alice.receive(bob.pop_frame())?;
+    match alice.wait(alice_close_qt)? {
+        (_, OperationResult::Close) => {},
+        _ => anyhow::bail!("close should succeed"),
+    };
+    crate::ensure_eq!(runtime.get_user_data(alice_qd), None);
+    match runtime.set_user_data(alice_qd, 42) {
+        Err(e) if e.errno == libc::EBADF => {},
+        _ => anyhow::bail!("attaching user data to a closed queue should fail with EBADF"),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// How long the data of Alice may go unacknowledged.
+const USER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Time between two polls of Alice while she waits for an acknowledgement.
+const TICK: Duration = Duration::from_millis(100);
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Has Alice push data that Bob never acknowledges, and checks that she keeps the connection until the user timeout
+/// expires, even though she retransmitted only once by then, and that her pops and pushes fail with ETIMEDOUT after.
+#[test]
+fn tcp_user_timeout_closes_connection() -> Result<()> {
+    let t0: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(t0);
+    let mut bob: SharedEngine = test_helpers::new_bob2(t0);
+    let (alice_qd, _): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    alice.set_socket_option(alice_qd, SocketOption::UserTimeout(USER_TIMEOUT))?;
+
+    let pop_qt: QToken = alice.tcp_pop(alice_qd)?;
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+
+    // Everything that Alice sends is lost.
+    let mut sent: usize = alice.pop_all_frames().len();
+    let mut now: Instant = t0;
+    while now + TICK < t0 + USER_TIMEOUT {
+        now += TICK;
+        alice.advance_clock(now);
+        alice.poll();
+        sent += alice.pop_all_frames().len();
+        crate::ensure_eq!(alice.get_runtime().has_completed(pop_qt)?, false);
+    }
+    crate::ensure_eq!(sent, 2);
+
+    // The connection is closed as soon as the user timeout expires.
+    alice.advance_clock(t0 + USER_TIMEOUT);
+    match alice.wait(pop_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ETIMEDOUT => {},
+        _ => anyhow::bail!("pop should fail with ETIMEDOUT"),
+    };
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == libc::ETIMEDOUT => {},
+        _ => anyhow::bail!("push should fail with ETIMEDOUT"),
+    };
+
+    Ok(())
+}
+
+/// Checks that the user timeout must be positive.
+#[test]
+fn tcp_user_timeout_zero() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    match alice.set_socket_option(alice_qd, SocketOption::UserTimeout(Duration::ZERO)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("zero user timeout should fail with EINVAL"),
+    };
+
+    Ok(())
+}
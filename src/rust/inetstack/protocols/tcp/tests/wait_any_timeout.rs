@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        types::{
+            demi_opcode_t,
+            demi_qresult_t,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Waits on two pops with timeouts of their own, and checks that the wait reports the pop whose timeout expires first
+/// while it leaves the other one pending, and that a pop that completes is reported before its timeout.
+#[test]
+fn tcp_wait_any_timeout() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let mut runtime: SharedDemiRuntime = bob.get_runtime();
+
+    let first_qt: QToken = bob.tcp_pop(bob_qd)?;
+    let second_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match runtime.wait_any_timeout(&[first_qt, second_qt], &[Duration::from_secs(0)]) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("wait without a timeout for every operation should fail with EINVAL"),
+    };
+    match runtime.wait_any_timeout(&[], &[]) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("wait on no operation should fail with EINVAL"),
+    };
+
+    // Nothing arrives, so the pop with the shorter timeout expires first.
+    let timeouts: [Duration; 2] = [Duration::from_secs(60), Duration::from_millis(10)];
+    let (i, qr): (usize, demi_qresult_t) = runtime.wait_any_timeout(&[first_qt, second_qt], &timeouts)?;
+    crate::ensure_eq!(i, 1);
+    crate::ensure_eq!(qr.qr_opcode, demi_opcode_t::DEMI_OPC_FAILED);
+    crate::ensure_eq!(qr.qr_ret, libc::ETIMEDOUT as i64);
+    crate::ensure_eq!(qr.qr_qd, u32::from(bob_qd));
+    crate::ensure_eq!(runtime.has_completed(second_qt)?, false);
+
+    // Data that already arrived completes a pop in the single poll of a zero timeout.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[1; 16][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    let qts: [QToken; 2] = [first_qt, second_qt];
+    let timeouts: [Duration; 2] = [Duration::from_secs(0), Duration::from_secs(0)];
+    let (i, qr): (usize, demi_qresult_t) = runtime.wait_any_timeout(&qts, &timeouts)?;
+    crate::ensure_eq!(qr.qr_opcode, demi_opcode_t::DEMI_OPC_POP);
+
+    // The other pop is still there to be waited for.
+    let other_qt: QToken = qts[1 - i];
+    let (i, qr): (usize, demi_qresult_t) = runtime.wait_any_timeout(&[other_qt], &[Duration::from_secs(0)])?;
+    crate::ensure_eq!(i, 0);
+    crate::ensure_eq!(qr.qr_ret, libc::ETIMEDOUT as i64);
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        cancellation::CancellationToken,
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        types::demi_opcode_t,
+        wait::{
+            WaitInterrupter,
+            WaitOutcome,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Waits on a pop in every way that a wait can return and checks the reported outcome.
+#[test]
+fn tcp_wait_ex() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let mut runtime: SharedDemiRuntime = bob.get_runtime();
+
+    // Nothing arrives, so the wait times out and the pop keeps waiting.
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match runtime.wait_ex(pop_qt, Some(Duration::from_secs(0)))? {
+        WaitOutcome::TimedOut => {},
+        _ => anyhow::bail!("wait should time out"),
+    };
+
+    // An interrupt stops a single wait.
+    let interrupter: WaitInterrupter = runtime.get_interrupter();
+    interrupter.interrupt();
+    match runtime.wait_ex(pop_qt, None)? {
+        WaitOutcome::Interrupted => {},
+        _ => anyhow::bail!("wait should be interrupted"),
+    };
+    match runtime.wait_ex(pop_qt, Some(Duration::from_secs(0)))? {
+        WaitOutcome::TimedOut => {},
+        _ => anyhow::bail!("interrupt should have been consumed"),
+    };
+
+    // Once data arrives, the pop completes.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[1; 16][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    match runtime.wait_ex(pop_qt, None)? {
+        WaitOutcome::Completed(qr) => crate::ensure_eq!(qr.qr_opcode, demi_opcode_t::DEMI_OPC_POP),
+        _ => anyhow::bail!("pop should complete"),
+    };
+
+    // A cancelled pop is told apart from a completed one.
+    let mut token: CancellationToken = CancellationToken::new();
+    let pop_qt: QToken = bob.pop_cancellable(bob_qd, None, token.clone())?;
+    token.cancel();
+    match runtime.wait_ex(pop_qt, None)? {
+        WaitOutcome::Cancelled => {},
+        _ => anyhow::bail!("pop should be cancelled"),
+    };
+
+    Ok(())
+}
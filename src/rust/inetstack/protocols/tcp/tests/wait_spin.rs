@@ -0,0 +1,85 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+        types::{
+            demi_opcode_t,
+            demi_qresult_t,
+        },
+        SharedDemiRuntime,
+    },
+};
+use ::anyhow::Result;
+use ::std::time::{
+    Duration,
+    Instant,
+};
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Spins on a pop whose data already arrived, and checks that the spin returns it without falling back to the regular
+/// wait, which would advance the clock.
+#[test]
+fn tcp_wait_spin() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let mut runtime: SharedDemiRuntime = bob.get_runtime();
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[1; 16][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    bob.receive(alice.pop_frame())?;
+
+    let before: Instant = runtime.get_now();
+    let qr: demi_qresult_t = runtime.wait_spin(pop_qt, Duration::from_secs(1), None)?;
+    crate::ensure_eq!(qr.qr_opcode, demi_opcode_t::DEMI_OPC_POP);
+    crate::ensure_eq!(runtime.get_now(), before);
+
+    Ok(())
+}
+
+/// Checks that the spin never outlasts the timeout of the wait.
+#[test]
+fn tcp_wait_spin_timeout() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (_, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    let mut runtime: SharedDemiRuntime = bob.get_runtime();
+
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    let start: Instant = Instant::now();
+    match runtime.wait_spin(pop_qt, Duration::from_secs(60), Some(Duration::from_millis(10))) {
+        Err(e) if e.errno == libc::ETIMEDOUT => {},
+        _ => anyhow::bail!("wait should time out"),
+    };
+    crate::ensure_eq!(start.elapsed() < Duration::from_secs(60), true);
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::{
+            ethernet2::Ethernet2Header,
+            ipv4::Ipv4Header,
+            tcp::{
+                segment::TcpHeader,
+                tests::connection_setup,
+            },
+        },
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        network::socket::option::SocketOption,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Helper Functions
+//======================================================================================================================
+
+/// Sends a segment from Alice to Bob and returns the window advertised in the ACK that Bob sends back.
+fn send_and_get_window(alice: &mut SharedEngine, alice_qd: QDesc, bob: &mut SharedEngine) -> Result<u16> {
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 64][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf)?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+
+    let ack: DemiBuffer = bob.pop_frame();
+    let (_, ipv4_payload): (Ethernet2Header, DemiBuffer) = Ethernet2Header::parse(ack.clone())?;
+    let (ipv4_hdr, tcp_payload): (Ipv4Header, DemiBuffer) = Ipv4Header::parse(ipv4_payload)?;
+    let (ack_hdr, _): (TcpHeader, DemiBuffer) = TcpHeader::parse(&ipv4_hdr, tcp_payload, false)?;
+    crate::ensure_eq!(ack_hdr.ack, true);
+    alice.receive(ack)?;
+
+    Ok(ack_hdr.window_size)
+}
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that the receive window clamp caps the window advertised in outgoing ACKs.
+#[test]
+fn tcp_recv_window_clamp() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+    bob.set_socket_option(bob_qd, SocketOption::QuickAck(true))?;
+
+    // Without a clamp, Bob advertises the free space of his receive buffer.
+    const CLAMP: u32 = 512;
+    let window: u16 = send_and_get_window(&mut alice, alice_qd, &mut bob)?;
+    crate::ensure_eq!(window as u32 > CLAMP, true);
+
+    // With a clamp, the advertised window never exceeds it.
+    bob.set_socket_option(bob_qd, SocketOption::RecvWindowClamp(CLAMP))?;
+    for _ in 0..4 {
+        let window: u16 = send_and_get_window(&mut alice, alice_qd, &mut bob)?;
+        crate::ensure_eq!(window as u32 <= CLAMP, true);
+    }
+
+    // A clamp of zero would close the window for good.
+    match bob.set_socket_option(bob_qd, SocketOption::RecvWindowClamp(0)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("setting a zero receive window clamp should fail"),
+    };
+
+    Ok(())
+}
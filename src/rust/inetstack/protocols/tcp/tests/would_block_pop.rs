@@ -0,0 +1,78 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    inetstack::{
+        protocols::tcp::tests::connection_setup,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
+    },
+    runtime::{
+        memory::DemiBuffer,
+        queue::{
+            OperationResult,
+            QDesc,
+            QToken,
+        },
+    },
+};
+use ::anyhow::Result;
+use ::std::time::Instant;
+
+//======================================================================================================================
+// Tests
+//======================================================================================================================
+
+/// Checks that a pop would block on a connection that holds no data, and would not once data is buffered, and that
+/// asking does not consume the data.
+#[test]
+fn tcp_would_block_pop() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let (alice_qd, bob_qd): (QDesc, QDesc) = connection_setup(&mut alice, &mut bob)?;
+
+    crate::ensure_eq!(bob.would_block_pop(bob_qd)?, true);
+
+    let buf: DemiBuffer = DemiBuffer::from_slice(&[0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let push_qt: QToken = alice.tcp_push(alice_qd, buf.clone())?;
+    match alice.wait(push_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("push should succeed"),
+    };
+    // The segment is processed in the background, which takes another sweep of the scheduler.
+    bob.receive(alice.pop_frame())?;
+    bob.poll();
+    crate::ensure_eq!(bob.would_block_pop(bob_qd)?, false);
+    crate::ensure_eq!(bob.would_block_pop(bob_qd)?, false);
+
+    let pop_qt: QToken = bob.tcp_pop(bob_qd)?;
+    match bob.wait(pop_qt)? {
+        (_, OperationResult::Pop(_, received)) => crate::ensure_eq!(received[..], buf[..]),
+        _ => anyhow::bail!("pop should succeed"),
+    };
+    crate::ensure_eq!(bob.would_block_pop(bob_qd)?, true);
+
+    Ok(())
+}
+
+/// Checks that asking about a socket that is not connected fails, since popping from it would not wait either.
+#[test]
+fn tcp_would_block_pop_not_connected() -> Result<()> {
+    let now: Instant = Instant::now();
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+
+    let alice_qd: QDesc = alice.tcp_socket()?;
+    match alice.would_block_pop(alice_qd) {
+        Err(e) if e.errno == libc::ENOTCONN => {},
+        _ => anyhow::bail!("would_block_pop() on an unconnected socket should fail with ENOTCONN"),
+    };
+
+    Ok(())
+}
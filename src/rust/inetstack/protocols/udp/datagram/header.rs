@@ -15,7 +15,10 @@ use crate::{
         memory::DemiBuffer,
     },
 };
-use ::libc::EBADMSG;
+use ::libc::{
+    EBADMSG,
+    EILSEQ,
+};
 use ::std::convert::TryInto;
 use std::slice::ChunksExact;
 
@@ -93,7 +96,7 @@ impl UdpHeader {
             if checksum != 0 {
                 // No, so check if checksum value matches what we expect.
                 if checksum != Self::checksum(&ipv4_hdr, hdr_buf, payload_buf) {
-                    return Err(Fail::new(EBADMSG, "UDP checksum mismatch"));
+                    return Err(Fail::new(EILSEQ, "UDP checksum mismatch"));
                 }
             }
         }
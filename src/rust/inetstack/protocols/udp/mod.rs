@@ -9,6 +9,7 @@
 
 mod datagram;
 pub mod peer;
+mod reorder;
 pub mod socket;
 
 #[cfg(test)]
@@ -19,6 +20,13 @@ mod tests;
 //==============================================================================
 
 pub use self::{
-    datagram::UdpHeader,
+    datagram::{
+        UdpHeader,
+        UDP_HEADER_SIZE,
+    },
     peer::SharedUdpPeer,
+    reorder::{
+        ReorderBuffer,
+        SequenceExtractor,
+    },
 };
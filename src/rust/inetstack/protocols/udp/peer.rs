@@ -8,6 +8,7 @@
 use super::{
     datagram::UdpHeader,
     socket::SharedUdpSocket,
+    ReorderBuffer,
 };
 use crate::{
     inetstack::protocols::{
@@ -18,14 +19,20 @@ use crate::{
         fail::Fail,
         memory::DemiBuffer,
         network::{
-            types::MacAddress,
+            types::{
+                DropReason,
+                DropStats,
+                MacAddress,
+            },
             NetworkRuntime,
         },
         scheduler::Yielder,
+        QToken,
         SharedDemiRuntime,
         SharedObject,
     },
 };
+use ::futures::FutureExt;
 use ::std::{
     collections::HashMap,
     net::{
@@ -37,6 +44,10 @@ use ::std::{
         Deref,
         DerefMut,
     },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 #[cfg(feature = "profiler")]
@@ -49,6 +60,8 @@ use crate::timer;
 
 /// UDP Peer
 pub struct UdpPeer<N: NetworkRuntime> {
+    /// Shared DemiRuntime.
+    runtime: SharedDemiRuntime,
     /// Underlying transport.
     transport: N,
     /// Underlying ARP peer.
@@ -59,8 +72,16 @@ pub struct UdpPeer<N: NetworkRuntime> {
     local_ipv4_addr: Ipv4Addr,
     /// Offload checksum to hardware?
     checksum_offload: bool,
+    /// Verify checksums of incoming datagrams in software?
+    verify_checksums: bool,
     /// Incoming routing table.
     addresses: HashMap<SocketAddrV4, SharedUdpSocket<N>>,
+    /// Background coroutines that release datagrams held for too long by reorder buffers, by local address.
+    reorder_expirers: HashMap<SocketAddrV4, QToken>,
+    /// Incoming packets dropped by the stack.
+    drop_stats: SharedObject<DropStats>,
+    /// Directed broadcast address of the local subnet, if its netmask is known.
+    directed_broadcast: Option<Ipv4Addr>,
 }
 
 #[derive(Clone)]
@@ -74,20 +95,26 @@ pub struct SharedUdpPeer<N: NetworkRuntime>(SharedObject<UdpPeer<N>>);
 
 impl<N: NetworkRuntime> SharedUdpPeer<N> {
     pub fn new(
-        _runtime: SharedDemiRuntime,
+        runtime: SharedDemiRuntime,
         transport: N,
         local_link_addr: MacAddress,
         local_ipv4_addr: Ipv4Addr,
         offload_checksum: bool,
         arp: SharedArpPeer<N>,
+        drop_stats: SharedObject<DropStats>,
     ) -> Result<Self, Fail> {
         Ok(Self(SharedObject::<UdpPeer<N>>::new(UdpPeer {
+            runtime,
             transport,
             arp,
             local_link_addr,
             local_ipv4_addr,
             checksum_offload: offload_checksum,
+            verify_checksums: true,
             addresses: HashMap::<SocketAddrV4, SharedUdpSocket<N>>::new(),
+            reorder_expirers: HashMap::<SocketAddrV4, QToken>::new(),
+            drop_stats,
+            directed_broadcast: None,
         })))
     }
 
@@ -99,6 +126,7 @@ impl<N: NetworkRuntime> SharedUdpPeer<N> {
             self.transport.clone(),
             self.arp.clone(),
             self.checksum_offload,
+            self.directed_broadcast,
         )
     }
 
@@ -119,6 +147,7 @@ impl<N: NetworkRuntime> SharedUdpPeer<N> {
     pub fn hard_close(&mut self, socket: &mut SharedUdpSocket<N>) -> Result<(), Fail> {
         if let Some(addr) = socket.local() {
             self.addresses.remove(&addr);
+            self.stop_reorder_expirer(&addr)?;
         }
         Ok(())
     }
@@ -164,16 +193,90 @@ impl<N: NetworkRuntime> SharedUdpPeer<N> {
         Ok(Some(addr.into()))
     }
 
+    /// Changes the local link address given to new sockets.
+    pub fn set_local_link_addr(&mut self, local_link_addr: MacAddress) {
+        self.local_link_addr = local_link_addr;
+    }
+
+    /// Sets the directed broadcast address of the local subnet. Sockets that already exist are not updated.
+    pub fn set_directed_broadcast(&mut self, directed_broadcast: Ipv4Addr) {
+        self.directed_broadcast = Some(directed_broadcast);
+    }
+
+    /// Enables or disables software verification of checksums of incoming datagrams.
+    pub fn set_verify_checksums(&mut self, verify_checksums: bool) {
+        self.verify_checksums = verify_checksums;
+    }
+
+    /// Enables, replaces, or disables (if `None`) reordering of incoming datagrams on the socket bound to `local`. If
+    /// the reorder buffer has a hold timeout, a background coroutine releases the datagrams that it holds for longer.
+    pub fn set_reorder_buffer(
+        &mut self,
+        local: SocketAddrV4,
+        reorder_buffer: Option<ReorderBuffer>,
+    ) -> Result<(), Fail> {
+        let hold_timeout: Option<Duration> = reorder_buffer.as_ref().and_then(|buffer| buffer.hold_timeout());
+        match self.get_socket_from_addr(&local) {
+            Some(socket) => {
+                socket.set_reorder_buffer(reorder_buffer);
+                let socket: SharedUdpSocket<N> = socket.clone();
+                self.stop_reorder_expirer(&local)?;
+                if let Some(hold_timeout) = hold_timeout {
+                    let coroutine = self.clone().expire_reorder_buffer(socket, hold_timeout);
+                    let qt: QToken = self
+                        .runtime
+                        .insert_background_coroutine("Inetstack::UDP::reorder", Box::pin(coroutine.fuse()))?;
+                    self.reorder_expirers.insert(local, qt);
+                }
+                Ok(())
+            },
+            None => {
+                let cause: String = format!("no socket bound to address (local={:?})", local);
+                error!("set_reorder_buffer(): {}", &cause);
+                Err(Fail::new(libc::EINVAL, &cause))
+            },
+        }
+    }
+
+    /// Stops releasing datagrams held for too long by the reorder buffer of the socket bound to `local`, if we were.
+    fn stop_reorder_expirer(&mut self, local: &SocketAddrV4) -> Result<(), Fail> {
+        match self.reorder_expirers.remove(local) {
+            Some(qt) => self.runtime.remove_background_coroutine(qt),
+            None => Ok(()),
+        }
+    }
+
+    /// Releases the datagrams that the reorder buffer of `socket` holds for longer than `hold_timeout`, as soon as they
+    /// reach it.
+    async fn expire_reorder_buffer(mut self, mut socket: SharedUdpSocket<N>, hold_timeout: Duration) {
+        loop {
+            let now: Instant = self.runtime.get_now();
+            let expiry: Instant = socket.next_reorder_expiry().unwrap_or(now + hold_timeout);
+            let yielder: Yielder = Yielder::new();
+            if let Err(e) = self.runtime.get_timer().wait_until(expiry, &yielder).await {
+                warn!("expire_reorder_buffer(): stopping (error={:?})", e);
+                break;
+            }
+            let dropped: usize = socket.expire_reorder_buffer(self.runtime.get_now());
+            for _ in 0..dropped {
+                self.drop_stats.record(DropReason::BufferFull);
+            }
+        }
+    }
+
     /// Consumes the payload from a buffer.
     pub fn receive(&mut self, ipv4_hdr: Ipv4Header, buf: DemiBuffer) {
         #[cfg(feature = "profiler")]
         timer!("udp::receive");
+        let now: Instant = self.runtime.get_now();
         // Parse datagram.
-        let (hdr, data): (UdpHeader, DemiBuffer) = match UdpHeader::parse(&ipv4_hdr, buf, self.checksum_offload) {
+        let checksum_offload: bool = self.checksum_offload || !self.verify_checksums;
+        let (hdr, data): (UdpHeader, DemiBuffer) = match UdpHeader::parse(&ipv4_hdr, buf, checksum_offload) {
             Ok(result) => result,
             Err(e) => {
                 let cause: String = format!("dropping packet: unable to parse UDP header");
                 warn!("{}: {:?}", cause, e);
+                self.drop_stats.record(DropReason::from_parse_error(&e));
                 return;
             },
         };
@@ -182,8 +285,20 @@ impl<N: NetworkRuntime> SharedUdpPeer<N> {
         let local: SocketAddrV4 = SocketAddrV4::new(ipv4_hdr.get_dest_addr(), hdr.dest_port());
         let remote: SocketAddrV4 = SocketAddrV4::new(ipv4_hdr.get_src_addr(), hdr.src_port());
 
-        let socket: &mut SharedUdpSocket<N> = match self.get_socket_from_addr(&local) {
+        let is_broadcast: bool = self.is_broadcast(local.ip());
+        let socket: Option<&mut SharedUdpSocket<N>> = if is_broadcast {
+            self.get_socket_for_broadcast(hdr.dest_port())
+        } else {
+            self.get_socket_from_addr(&local)
+        };
+        let socket: &mut SharedUdpSocket<N> = match socket {
             Some(queue) => queue,
+            None if is_broadcast => {
+                let cause: &str = "dropping packet: no socket accepts broadcast datagrams";
+                warn!("{}: {:?}", cause, local);
+                self.drop_stats.record(DropReason::NoSocket);
+                return;
+            },
             None => {
                 // Handle wildcard address.
                 let local: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, hdr.dest_port());
@@ -196,18 +311,42 @@ impl<N: NetworkRuntime> SharedUdpPeer<N> {
                         // details.
                         let cause: String = format!("dropping packet: port not bound");
                         warn!("{}: {:?}", cause, local);
+                        self.drop_stats.record(DropReason::NoSocket);
                         return;
                     },
                 }
             },
         };
         // TODO: Drop this packet if local address/port pair is not bound.
-        socket.receive(remote, data)
+        let dropped: usize = socket.receive(remote, data, now);
+        for _ in 0..dropped {
+            self.drop_stats.record(DropReason::BufferFull);
+        }
+    }
+
+    /// Checks whether `addr` is the limited broadcast address or the directed broadcast address of the local subnet.
+    fn is_broadcast(&self, addr: &Ipv4Addr) -> bool {
+        addr.is_broadcast() || Some(*addr) == self.directed_broadcast
     }
 
     fn get_socket_from_addr(&mut self, local: &SocketAddrV4) -> Option<&mut SharedUdpSocket<N>> {
         self.addresses.get_mut(local)
     }
+
+    /// Finds the socket that receives broadcast datagrams sent to `port`: one bound to the broadcast address, or one
+    /// bound to our address or the wildcard address that opted in to broadcast.
+    fn get_socket_for_broadcast(&mut self, port: u16) -> Option<&mut SharedUdpSocket<N>> {
+        let candidates: [SocketAddrV4; 3] = [
+            SocketAddrV4::new(Ipv4Addr::BROADCAST, port),
+            SocketAddrV4::new(self.local_ipv4_addr, port),
+            SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port),
+        ];
+        let local: SocketAddrV4 = candidates.into_iter().find(|addr| match self.addresses.get(addr) {
+            Some(socket) => addr.ip().is_broadcast() || socket.broadcast(),
+            None => false,
+        })?;
+        self.addresses.get_mut(&local)
+    }
 }
 
 //======================================================================================================================
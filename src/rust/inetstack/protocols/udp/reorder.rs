@@ -0,0 +1,152 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::memory::DemiBuffer;
+use ::std::{
+    collections::BTreeMap,
+    net::SocketAddrV4,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Application-provided function that extracts the sequence number from the payload of a datagram. Datagrams for which
+/// it returns `None` bypass reordering.
+pub type SequenceExtractor = Box<dyn Fn(&[u8]) -> Option<u64>>;
+
+/// Reorders incoming datagrams that arrive out of sequence within a small window. Sequence numbers wrap around, so a
+/// datagram is taken to be late if it is more than half the sequence space behind the next expected one.
+pub struct ReorderBuffer {
+    /// Maximum distance ahead of the next expected sequence number that a datagram may be buffered at.
+    window_size: u64,
+    /// Longest time that a datagram is held waiting for a gap to be filled. None holds it until the gap is filled or
+    /// given up on because a datagram arrived too far ahead.
+    hold_timeout: Option<Duration>,
+    /// Extracts the sequence number of a datagram.
+    extractor: SequenceExtractor,
+    /// Sequence number of the next datagram to deliver. Set by the first sequenced datagram.
+    next_seq: Option<u64>,
+    /// Datagrams waiting for a gap to be filled, keyed by sequence number, along with the time at which they arrived.
+    pending: BTreeMap<u64, (SocketAddrV4, DemiBuffer, Instant)>,
+}
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl ReorderBuffer {
+    /// Creates a reorder buffer that holds datagrams up to `window_size` sequence numbers ahead of the next one.
+    pub fn new(window_size: usize, extractor: SequenceExtractor) -> Self {
+        Self {
+            window_size: window_size as u64,
+            hold_timeout: None,
+            extractor,
+            next_seq: None,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Gives up on a gap once the datagrams after it have been held for `hold_timeout`, so that they are not held
+    /// forever when the missing datagrams were lost.
+    pub fn with_hold_timeout(mut self, hold_timeout: Duration) -> Self {
+        self.hold_timeout = Some(hold_timeout);
+        self
+    }
+
+    /// Returns the longest time that a datagram is held waiting for a gap to be filled, if bounded.
+    pub fn hold_timeout(&self) -> Option<Duration> {
+        self.hold_timeout
+    }
+
+    /// Accepts an incoming datagram that arrived at `now` and returns the datagrams that are ready for delivery, in
+    /// order.
+    pub fn insert(&mut self, remote: SocketAddrV4, buf: DemiBuffer, now: Instant) -> Vec<(SocketAddrV4, DemiBuffer)> {
+        let seq: u64 = match (self.extractor)(&buf[..]) {
+            Some(seq) => seq,
+            None => return vec![(remote, buf)],
+        };
+        let next_seq: u64 = *self.next_seq.get_or_insert(seq);
+        let distance: u64 = seq.wrapping_sub(next_seq);
+
+        // Late or duplicate datagram: the gap it belonged to has already been given up on.
+        if distance > u64::MAX / 2 {
+            return vec![(remote, buf)];
+        }
+
+        // Too far ahead: give up on the gap and deliver everything we are holding.
+        if distance > self.window_size {
+            let mut ready: Vec<(SocketAddrV4, DemiBuffer)> = self.flush();
+            ready.push((remote, buf));
+            self.next_seq = Some(seq.wrapping_add(1));
+            return ready;
+        }
+
+        self.pending.insert(seq, (remote, buf, now));
+        self.drain_in_order()
+    }
+
+    /// Returns the time at which the datagram that has been held the longest reaches the hold timeout, if any.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        let hold_timeout: Duration = self.hold_timeout?;
+        self.pending
+            .values()
+            .map(|(_, _, arrival)| *arrival + hold_timeout)
+            .min()
+    }
+
+    /// Gives up on the gaps before datagrams that have been held for longer than the hold timeout by `now`, and returns
+    /// the datagrams that are ready for delivery as a result, in order.
+    pub fn expire(&mut self, now: Instant) -> Vec<(SocketAddrV4, DemiBuffer)> {
+        let mut ready: Vec<(SocketAddrV4, DemiBuffer)> = Vec::new();
+        while self.next_expiry().is_some_and(|expiry| expiry <= now) {
+            // Skip to the first datagram that we are holding.
+            self.next_seq = self.pending_in_order().first().copied();
+            ready.append(&mut self.drain_in_order());
+        }
+        ready
+    }
+
+    /// Removes all buffered datagrams in sequence order, regardless of gaps.
+    pub fn flush(&mut self) -> Vec<(SocketAddrV4, DemiBuffer)> {
+        let seqs: Vec<u64> = self.pending_in_order();
+        if let Some(&last) = seqs.last() {
+            self.next_seq = Some(last.wrapping_add(1));
+        }
+        seqs.into_iter()
+            .filter_map(|seq| self.pending.remove(&seq))
+            .map(|(remote, buf, _)| (remote, buf))
+            .collect()
+    }
+
+    /// Returns the sequence numbers of the buffered datagrams, in the order in which they follow the next expected one.
+    fn pending_in_order(&self) -> Vec<u64> {
+        let next_seq: u64 = self.next_seq.unwrap_or_default();
+        let mut seqs: Vec<u64> = self.pending.keys().copied().collect();
+        seqs.sort_by_key(|seq| seq.wrapping_sub(next_seq));
+        seqs
+    }
+
+    /// Removes buffered datagrams that directly follow the last delivered one.
+    fn drain_in_order(&mut self) -> Vec<(SocketAddrV4, DemiBuffer)> {
+        let mut ready: Vec<(SocketAddrV4, DemiBuffer)> = Vec::new();
+        while let Some(next_seq) = self.next_seq {
+            match self.pending.remove(&next_seq) {
+                Some((remote, buf, _)) => {
+                    ready.push((remote, buf));
+                    self.next_seq = Some(next_seq.wrapping_add(1));
+                },
+                None => break,
+            }
+        }
+        ready
+    }
+}
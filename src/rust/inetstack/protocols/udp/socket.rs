@@ -17,6 +17,7 @@ use crate::{
         ipv4::Ipv4Header,
         udp::{
             datagram::UdpDatagram,
+            ReorderBuffer,
             UdpHeader,
         },
     },
@@ -43,6 +44,7 @@ use ::std::{
         Deref,
         DerefMut,
     },
+    time::Instant,
 };
 
 //======================================================================================================================
@@ -73,6 +75,14 @@ pub struct UdpSocket<N: NetworkRuntime> {
     recv_queue: AsyncQueue<(SocketAddrV4, DemiBuffer)>,
     arp: SharedArpPeer<N>,
     checksum_offload: bool,
+    // Restores the sequence of incoming datagrams, if enabled.
+    reorder_buffer: Option<ReorderBuffer>,
+    // Send and receive broadcast datagrams?
+    broadcast: bool,
+    // Directed broadcast address of the local subnet, if its netmask is known.
+    directed_broadcast: Option<Ipv4Addr>,
+    // Maximum number of datagrams in the receive queue, if bounded.
+    max_datagram_queue: Option<usize>,
 }
 #[derive(Clone)]
 pub struct SharedUdpSocket<N: NetworkRuntime>(SharedObject<UdpSocket<N>>);
@@ -88,6 +98,7 @@ impl<N: NetworkRuntime> SharedUdpSocket<N> {
         network: N,
         arp: SharedArpPeer<N>,
         checksum_offload: bool,
+        directed_broadcast: Option<Ipv4Addr>,
     ) -> Result<Self, Fail> {
         Ok(Self(SharedObject::new(UdpSocket::<N> {
             local_ipv4_addr,
@@ -97,6 +108,10 @@ impl<N: NetworkRuntime> SharedUdpSocket<N> {
             recv_queue: AsyncQueue::<(SocketAddrV4, DemiBuffer)>::default(),
             arp,
             checksum_offload,
+            reorder_buffer: None,
+            broadcast: false,
+            directed_broadcast,
+            max_datagram_queue: None,
         })))
     }
 
@@ -121,12 +136,22 @@ impl<N: NetworkRuntime> SharedUdpSocket<N> {
             error!("pushto(): {}", &cause);
             return Err(Fail::new(libc::ENOTSUP, &cause));
         };
-        let remote_link_addr: MacAddress = self.arp.query(remote.ip().clone(), &yielder).await?;
+        let remote_link_addr: MacAddress =
+            if remote.ip().is_broadcast() || Some(*remote.ip()) == self.directed_broadcast {
+                if !self.broadcast {
+                    let cause: &str = "socket is not allowed to send broadcast datagrams";
+                    error!("pushto(): {}", cause);
+                    return Err(Fail::new(libc::EACCES, cause));
+                }
+                MacAddress::broadcast()
+            } else {
+                self.arp.query(*remote.ip(), &yielder).await?
+            };
         let udp_header: UdpHeader = UdpHeader::new(port, remote.port());
         debug!("UDP send {:?}", udp_header);
         let datagram = UdpDatagram::new(
             Ethernet2Header::new(remote_link_addr, self.local_link_addr, EtherType2::Ipv4),
-            Ipv4Header::new(self.local_ipv4_addr, remote.ip().clone(), IpProtocol::UDP),
+            Ipv4Header::new(self.local_ipv4_addr, *remote.ip(), IpProtocol::UDP),
             udp_header,
             buf,
             self.checksum_offload,
@@ -152,10 +177,79 @@ impl<N: NetworkRuntime> SharedUdpSocket<N> {
         }
     }
 
-    pub fn receive(&mut self, remote: SocketAddrV4, buf: DemiBuffer) {
+    /// Queues an incoming datagram that arrived at `now`. Returns the number of queued datagrams that were dropped to
+    /// make room for it.
+    pub fn receive(&mut self, remote: SocketAddrV4, buf: DemiBuffer, now: Instant) -> usize {
+        let msgs: Vec<(SocketAddrV4, DemiBuffer)> = match self.reorder_buffer {
+            Some(ref mut reorder_buffer) => reorder_buffer.insert(remote, buf, now),
+            None => vec![(remote, buf)],
+        };
+        self.deliver(msgs)
+    }
+
+    /// Queues the datagrams that the reorder buffer has held for longer than its hold timeout by `now`. Returns the
+    /// number of queued datagrams that were dropped to make room for them.
+    pub fn expire_reorder_buffer(&mut self, now: Instant) -> usize {
+        let msgs: Vec<(SocketAddrV4, DemiBuffer)> = match self.reorder_buffer {
+            Some(ref mut reorder_buffer) => reorder_buffer.expire(now),
+            None => return 0,
+        };
+        self.deliver(msgs)
+    }
+
+    /// Returns the time at which the reorder buffer next gives up on a gap, if it is holding any datagrams and has a
+    /// hold timeout.
+    pub fn next_reorder_expiry(&self) -> Option<Instant> {
+        self.reorder_buffer.as_ref()?.next_expiry()
+    }
+
+    /// Pushes datagrams to the receive queue. Returns the number of queued datagrams that were dropped to make room.
+    fn deliver(&mut self, msgs: Vec<(SocketAddrV4, DemiBuffer)>) -> usize {
         // Push data to the receiver-side shared queue. This will cause the
         // associated pool operation to be ready.
-        self.recv_queue.push((remote, buf));
+        let mut dropped: usize = 0;
+        for msg in msgs {
+            if let Some(max_datagram_queue) = self.max_datagram_queue {
+                while self.recv_queue.len() >= max_datagram_queue {
+                    self.recv_queue.try_pop();
+                    dropped += 1;
+                }
+            }
+            self.recv_queue.push(msg);
+        }
+        dropped
+    }
+
+    /// Enables, replaces, or disables (if `None`) reordering of incoming datagrams. Datagrams held by the previous
+    /// reorder buffer are delivered right away.
+    pub fn set_reorder_buffer(&mut self, reorder_buffer: Option<ReorderBuffer>) {
+        if let Some(mut old) = std::mem::replace(&mut self.reorder_buffer, reorder_buffer) {
+            for msg in old.flush() {
+                self.recv_queue.push(msg);
+            }
+        }
+    }
+
+    /// Returns the number of datagrams that are ready to be popped. Datagrams held back by the reorder buffer are not
+    /// counted.
+    pub fn queued_datagrams(&self) -> usize {
+        self.recv_queue.len()
+    }
+
+    /// Bounds the number of datagrams held for popping. Datagrams already queued beyond the bound are dropped when the
+    /// next one arrives.
+    pub fn set_max_datagram_queue(&mut self, max_datagram_queue: usize) {
+        self.max_datagram_queue = Some(max_datagram_queue);
+    }
+
+    /// Allows or forbids sending and receiving broadcast datagrams.
+    pub fn set_broadcast(&mut self, broadcast: bool) {
+        self.broadcast = broadcast;
+    }
+
+    /// Returns whether the target queue sends and receives broadcast datagrams.
+    pub fn broadcast(&self) -> bool {
+        self.broadcast
     }
 
     pub fn is_bound(&self) -> bool {
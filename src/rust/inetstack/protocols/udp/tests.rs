@@ -2,12 +2,16 @@
 // // Licensed under the MIT license.
 
 use crate::{
-    inetstack::test_helpers::{
-        self,
-        engine::SharedEngine,
+    inetstack::{
+        protocols::udp::ReorderBuffer,
+        test_helpers::{
+            self,
+            engine::SharedEngine,
+        },
     },
     runtime::{
         memory::DemiBuffer,
+        network::socket::option::SocketOption,
         queue::{
             OperationResult,
             QDesc,
@@ -17,6 +21,7 @@ use crate::{
 };
 use ::anyhow::Result;
 use ::libc::{
+    EACCES,
     EADDRINUSE,
     EBADF,
 };
@@ -24,6 +29,7 @@ use ::std::{
     convert::TryFrom,
     net::{
         Ipv4Addr,
+        SocketAddr,
         SocketAddrV4,
     },
     time::{
@@ -167,6 +173,606 @@ fn udp_push_pop_wildcard_address() -> Result<()> {
     Ok(())
 }
 
+/// Tests that a zero-length datagram is sent and popped as a datagram of its own.
+#[test]
+fn udp_push_pop_zero_length() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+
+    // Send an empty datagram, followed by a non-empty one, to Bob.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    for payload in [DemiBuffer::new(0), buf.clone()] {
+        let qt: QToken = alice.udp_pushto(alice_fd, payload, bob_addr)?;
+        match alice.wait(qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("Push failed"),
+        };
+        bob.receive(alice.pop_frame())?;
+    }
+
+    // Bob pops the empty datagram on its own and then the non-empty one.
+    for expected in [&[][..], &buf[..]] {
+        let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+        let (remote_addr, received_buf): (Option<SocketAddrV4>, DemiBuffer) = match bob.wait(bob_qt)? {
+            (_, OperationResult::Pop(addr, buf)) => (addr, buf),
+            _ => anyhow::bail!("Pop failed"),
+        };
+        crate::ensure_eq!(remote_addr, Some(alice_addr));
+        crate::ensure_eq!(&received_buf[..], expected);
+    }
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+//==============================================================================
+// Checksum Verification
+//==============================================================================
+
+#[test]
+fn udp_verify_checksums() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_port: u16 = 80;
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, alice_port);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_port: u16 = 80;
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, bob_port);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+
+    // Offset of the UDP checksum in an Ethernet frame without IPv4 options.
+    const UDP_CHECKSUM_OFFSET: usize = 14 + 20 + 6;
+
+    // Send data with a corrupted checksum to Bob, who should drop it.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf.clone(), bob_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    let mut frame: DemiBuffer = alice.pop_frame();
+    frame[UDP_CHECKSUM_OFFSET + 1] ^= 0x01;
+    bob.receive(frame)?;
+    let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+    bob.poll();
+    crate::ensure_eq!(bob.get_runtime().has_completed(bob_qt)?, false);
+
+    // Send data with a corrupted checksum to Bob again, who should accept it after disabling verification.
+    bob.get_transport().set_verify_checksums(false);
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf.clone(), bob_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    let mut frame: DemiBuffer = alice.pop_frame();
+    frame[UDP_CHECKSUM_OFFSET + 1] ^= 0x01;
+    bob.receive(frame)?;
+    let received_buf: DemiBuffer = match bob.wait(bob_qt)? {
+        (_, OperationResult::Pop(_, buf)) => buf,
+        _ => anyhow::bail!("Pop failed"),
+    };
+    assert_eq!(received_buf[..], buf[..]);
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+//==============================================================================
+// Reordering
+//==============================================================================
+
+#[test]
+fn udp_reorder_buffer() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_port: u16 = 80;
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, alice_port);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob, who reads the sequence number from the first byte of each datagram.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_port: u16 = 80;
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, bob_port);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+    let reorder_buffer: ReorderBuffer = ReorderBuffer::new(4, Box::new(|data: &[u8]| data.first().map(|b| *b as u64)));
+    bob.get_transport()
+        .set_udp_reorder_buffer(bob_addr, Some(reorder_buffer))?;
+
+    // Send datagrams 1, 2, and 3 to Bob.
+    let mut frames: Vec<DemiBuffer> = Vec::new();
+    for seq in 1..=3 {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[seq; 32][..]).expect("slice should fit in DemiBuffer");
+        let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+        match alice.wait(alice_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("Push failed"),
+        };
+        frames.push(alice.pop_frame());
+    }
+
+    // Deliver them out of order.
+    bob.receive(frames[0].clone())?;
+    bob.receive(frames[2].clone())?;
+    bob.receive(frames[1].clone())?;
+
+    // Bob should pop them in order.
+    for seq in 1..=3 {
+        let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+        let received_buf: DemiBuffer = match bob.wait(bob_qt)? {
+            (_, OperationResult::Pop(_, buf)) => buf,
+            _ => anyhow::bail!("Pop failed"),
+        };
+        crate::ensure_eq!(received_buf[0], seq);
+    }
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+/// Tests that datagrams held back by the reorder buffer are delivered once the hold timeout gives up on the gap.
+#[test]
+fn udp_reorder_buffer_hold_timeout() -> Result<()> {
+    let now: Instant = Instant::now();
+    let hold_timeout: Duration = Duration::from_millis(10);
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_port: u16 = 80;
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, alice_port);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob, who reads the sequence number from the first byte of each datagram.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_port: u16 = 80;
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, bob_port);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+    let reorder_buffer: ReorderBuffer =
+        ReorderBuffer::new(4, Box::new(|data: &[u8]| data.first().map(|b| *b as u64))).with_hold_timeout(hold_timeout);
+    bob.get_transport()
+        .set_udp_reorder_buffer(bob_addr, Some(reorder_buffer))?;
+
+    // Send datagrams 1 and 3 to Bob, as if 2 was lost.
+    for seq in [1, 3] {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[seq; 32][..]).expect("slice should fit in DemiBuffer");
+        let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+        match alice.wait(alice_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("Push failed"),
+        };
+        bob.receive(alice.pop_frame())?;
+    }
+
+    // Bob should pop 1 right away.
+    let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+    match bob.wait(bob_qt)? {
+        (_, OperationResult::Pop(_, buf)) => crate::ensure_eq!(buf[0], 1),
+        _ => anyhow::bail!("Pop failed"),
+    };
+
+    // Bob should hold 3 until the hold timeout.
+    let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+    bob.poll();
+    crate::ensure_eq!(bob.get_runtime().has_completed(bob_qt)?, false);
+    bob.advance_clock(now + hold_timeout);
+    match bob.wait(bob_qt)? {
+        (_, OperationResult::Pop(_, buf)) => crate::ensure_eq!(buf[0], 3),
+        _ => anyhow::bail!("Pop failed"),
+    };
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+/// Tests that the reorder buffer restores the sequence of datagrams whose sequence numbers wrap around.
+#[test]
+fn udp_reorder_buffer_wraparound() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_port: u16 = 80;
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, alice_port);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob, whose sequence numbers for datagrams 1, 2, and 3 are the last one, zero, and one.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_port: u16 = 80;
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, bob_port);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+    let reorder_buffer: ReorderBuffer = ReorderBuffer::new(
+        4,
+        Box::new(|data: &[u8]| data.first().map(|b| (*b as u64).wrapping_add(u64::MAX - 1))),
+    );
+    bob.get_transport()
+        .set_udp_reorder_buffer(bob_addr, Some(reorder_buffer))?;
+
+    // Send datagrams 1, 2, and 3 to Bob.
+    let mut frames: Vec<DemiBuffer> = Vec::new();
+    for seq in 1..=3 {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[seq; 32][..]).expect("slice should fit in DemiBuffer");
+        let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+        match alice.wait(alice_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("Push failed"),
+        };
+        frames.push(alice.pop_frame());
+    }
+
+    // Deliver them out of order.
+    bob.receive(frames[0].clone())?;
+    bob.receive(frames[2].clone())?;
+    bob.receive(frames[1].clone())?;
+
+    // Bob should pop them in order.
+    for seq in 1..=3 {
+        let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+        let received_buf: DemiBuffer = match bob.wait(bob_qt)? {
+            (_, OperationResult::Pop(_, buf)) => buf,
+            _ => anyhow::bail!("Pop failed"),
+        };
+        crate::ensure_eq!(received_buf[0], seq);
+    }
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+//==============================================================================
+// Queued Datagrams
+//==============================================================================
+
+/// Tests that the number of queued datagrams counts datagrams awaiting pop, whatever their size.
+#[test]
+fn udp_queued_datagrams() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+    crate::ensure_eq!(bob.queued_datagrams(bob_fd)?, 0);
+
+    // Deliver three datagrams of different sizes to Bob.
+    for size in [1, 32, 64] {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; size][..]).expect("slice should fit in DemiBuffer");
+        let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+        match alice.wait(alice_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("Push failed"),
+        };
+        bob.receive(alice.pop_frame())?;
+    }
+    crate::ensure_eq!(bob.queued_datagrams(bob_fd)?, 3);
+
+    // Popping one leaves the other two.
+    let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+    match bob.wait(bob_qt)? {
+        (_, OperationResult::Pop(_, buf)) => crate::ensure_eq!(buf.len(), 1),
+        _ => anyhow::bail!("Pop failed"),
+    };
+    crate::ensure_eq!(bob.queued_datagrams(bob_fd)?, 2);
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+/// Tests that a socket with a bounded datagram queue drops its oldest datagram to make room for a new one.
+#[test]
+fn udp_max_datagram_queue() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob, who holds at most two datagrams.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+    match bob.set_socket_option(bob_fd, SocketOption::MaxDatagramQueue(0)) {
+        Err(e) if e.errno == libc::EINVAL => {},
+        _ => anyhow::bail!("empty datagram queue should be rejected"),
+    };
+    bob.set_socket_option(bob_fd, SocketOption::MaxDatagramQueue(2))?;
+
+    // Deliver datagrams 1, 2, and 3 to Bob.
+    for seq in 1..=3 {
+        let buf: DemiBuffer = DemiBuffer::from_slice(&[seq; 32][..]).expect("slice should fit in DemiBuffer");
+        let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+        match alice.wait(alice_qt)? {
+            (_, OperationResult::Push) => {},
+            _ => anyhow::bail!("Push failed"),
+        };
+        bob.receive(alice.pop_frame())?;
+    }
+    crate::ensure_eq!(bob.queued_datagrams(bob_fd)?, 2);
+    crate::ensure_eq!(bob.drop_stats().buffer_full, 1);
+
+    // The first datagram was dropped.
+    for seq in 2..=3 {
+        let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+        let received_buf: DemiBuffer = match bob.wait(bob_qt)? {
+            (_, OperationResult::Pop(_, buf)) => buf,
+            _ => anyhow::bail!("Pop failed"),
+        };
+        crate::ensure_eq!(received_buf[0], seq);
+    }
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+//==============================================================================
+// Drop Statistics
+//==============================================================================
+
+/// Tests that datagrams for unbound ports are counted as dropped for lack of a socket.
+#[test]
+fn udp_drop_stats_no_socket() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob, without binding any socket.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    crate::ensure_eq!(bob.drop_stats().total(), 0);
+
+    // Send data to Bob, who should drop it.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    crate::ensure_eq!(bob.drop_stats().no_socket, 1);
+    crate::ensure_eq!(bob.drop_stats().total(), 1);
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+
+    Ok(())
+}
+
+/// Tests that datagrams whose checksum does not match are counted as dropped for a bad checksum.
+#[test]
+fn udp_drop_stats_bad_checksum() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, bob_addr)?;
+
+    // Send data to Bob, corrupting it on the way.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    let mut frame: DemiBuffer = alice.pop_frame();
+    let last: usize = frame.len() - 1;
+    frame[last] ^= 0xff;
+    bob.receive(frame)?;
+    crate::ensure_eq!(bob.drop_stats().bad_checksum, 1);
+    crate::ensure_eq!(bob.drop_stats().total(), 1);
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+/// Tests that IPv6 packets are counted as dropped for an unsupported protocol.
+#[test]
+fn udp_drop_stats_unsupported_protocol() -> Result<()> {
+    let now: Instant = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::BOB_IPV4, 80);
+
+    // Send data to Bob, relabeling the frame as IPv6.
+    let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf, bob_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    let mut frame: DemiBuffer = alice.pop_frame();
+    frame[12..14].copy_from_slice(&[0x86, 0xdd]);
+    bob.receive(frame)?;
+    crate::ensure_eq!(bob.drop_stats().unsupported_protocol, 1);
+    crate::ensure_eq!(bob.drop_stats().total(), 1);
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+
+    Ok(())
+}
+
+//==============================================================================
+// Broadcast
+//==============================================================================
+
+#[test]
+fn udp_broadcast() -> Result<()> {
+    let now: Instant = Instant::now();
+    let broadcast_addr: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::BROADCAST, 80);
+    let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+
+    // Setup Bob.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 80))?;
+
+    // Sockets cannot send broadcast datagrams unless they opt in.
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf.clone(), broadcast_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Failed(e)) if e.errno == EACCES => {},
+        _ => anyhow::bail!("Push to broadcast address should fail"),
+    };
+
+    // Nor do they receive them.
+    alice.set_socket_option(alice_fd, SocketOption::Broadcast(true))?;
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf.clone(), broadcast_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    crate::ensure_eq!(bob.drop_stats().no_socket, 1);
+
+    // Once Bob opts in, the broadcast datagram is delivered.
+    bob.set_socket_option(bob_fd, SocketOption::Broadcast(true))?;
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf.clone(), broadcast_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+    let (remote_addr, received_buf): (Option<SocketAddrV4>, DemiBuffer) = match bob.wait(bob_qt)? {
+        (_, OperationResult::Pop(addr, buf)) => (addr, buf),
+        _ => anyhow::bail!("Pop failed"),
+    };
+    crate::ensure_eq!(remote_addr, Some(alice_addr));
+    crate::ensure_eq!(received_buf[..], buf[..]);
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
+/// Sends a datagram to the directed broadcast address of the subnet of Alice and Bob, and checks that Bob receives it.
+#[test]
+fn udp_directed_broadcast() -> Result<()> {
+    let now: Instant = Instant::now();
+    let netmask: Ipv4Addr = Ipv4Addr::new(255, 255, 255, 0);
+    let broadcast_addr: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 255), 80);
+    let buf: DemiBuffer = DemiBuffer::from_slice(&vec![0x5a; 32][..]).expect("slice should fit in DemiBuffer");
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    alice.get_transport().set_ipv4_netmask(netmask);
+    let alice_addr: SocketAddrV4 = SocketAddrV4::new(test_helpers::ALICE_IPV4, 80);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+    alice.set_socket_option(alice_fd, SocketOption::Broadcast(true))?;
+
+    // Setup Bob.
+    let mut bob: SharedEngine = test_helpers::new_bob2(now);
+    bob.get_transport().set_ipv4_netmask(netmask);
+    let bob_fd: QDesc = bob.udp_socket()?;
+    bob.udp_bind(bob_fd, SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 80))?;
+    bob.set_socket_option(bob_fd, SocketOption::Broadcast(true))?;
+
+    let alice_qt: QToken = alice.udp_pushto(alice_fd, buf.clone(), broadcast_addr)?;
+    match alice.wait(alice_qt)? {
+        (_, OperationResult::Push) => {},
+        _ => anyhow::bail!("Push failed"),
+    };
+    bob.receive(alice.pop_frame())?;
+    let bob_qt: QToken = bob.udp_pop(bob_fd)?;
+    let (remote_addr, received_buf): (Option<SocketAddrV4>, DemiBuffer) = match bob.wait(bob_qt)? {
+        (_, OperationResult::Pop(addr, buf)) => (addr, buf),
+        _ => anyhow::bail!("Pop failed"),
+    };
+    crate::ensure_eq!(remote_addr, Some(alice_addr));
+    crate::ensure_eq!(received_buf[..], buf[..]);
+
+    // Close peers.
+    alice.udp_close(alice_fd)?;
+    bob.udp_close(bob_fd)?;
+
+    Ok(())
+}
+
 //==============================================================================
 // Ping Pong
 //==============================================================================
@@ -514,6 +1120,33 @@ fn udp_udp_close_bad_file_descriptor() -> Result<()> {
     Ok(())
 }
 
+/// Closes a socket bound to an ephemeral port with `close_matching` and checks that the port can be bound again.
+#[test]
+fn udp_close_matching_frees_ephemeral_port() -> Result<()> {
+    let now = Instant::now();
+
+    // Setup Alice.
+    let mut alice: SharedEngine = test_helpers::new_alice2(now);
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, SocketAddrV4::new(test_helpers::ALICE_IPV4, 0))?;
+    let alice_addr: SocketAddrV4 = match alice.queue_infos().iter().find(|info| info.qd == alice_fd) {
+        Some(info) => match info.local {
+            Some(SocketAddr::V4(addr)) => addr,
+            _ => anyhow::bail!("socket should be bound to an IPv4 address"),
+        },
+        None => anyhow::bail!("socket should be open"),
+    };
+
+    crate::ensure_eq!(alice.close_matching(|info| info.qd == alice_fd), vec![alice_fd]);
+
+    // The port went back to the pool.
+    let alice_fd: QDesc = alice.udp_socket()?;
+    alice.udp_bind(alice_fd, alice_addr)?;
+    alice.udp_close(alice_fd)?;
+
+    Ok(())
+}
+
 //==============================================================================
 // Bad Pop
 //==============================================================================
@@ -41,6 +41,7 @@ pub const CARRIE_IPV4: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 3);
 pub fn new_alice(now: Instant) -> SharedEngine {
     let arp_config = ArpConfig::new(
         Some(Duration::from_secs(600)),
+        None,
         Some(Duration::from_secs(1)),
         Some(2),
         Some(HashMap::new()),
@@ -56,6 +57,7 @@ pub fn new_alice(now: Instant) -> SharedEngine {
 pub fn new_bob(now: Instant) -> SharedEngine {
     let arp_config = ArpConfig::new(
         Some(Duration::from_secs(600)),
+        None,
         Some(Duration::from_secs(1)),
         Some(2),
         Some(HashMap::new()),
@@ -73,6 +75,7 @@ pub fn new_alice2(now: Instant) -> SharedEngine {
     arp.insert(BOB_IPV4, BOB_MAC);
     let arp_config = ArpConfig::new(
         Some(Duration::from_secs(600)),
+        None,
         Some(Duration::from_secs(1)),
         Some(2),
         Some(arp),
@@ -88,8 +91,10 @@ pub fn new_bob2(now: Instant) -> SharedEngine {
     let mut arp: HashMap<Ipv4Addr, MacAddress> = HashMap::<Ipv4Addr, MacAddress>::new();
     arp.insert(BOB_IPV4, BOB_MAC);
     arp.insert(ALICE_IPV4, ALICE_MAC);
+    arp.insert(CARRIE_IPV4, CARRIE_MAC);
     let arp_config = ArpConfig::new(
         Some(Duration::from_secs(600)),
+        None,
         Some(Duration::from_secs(1)),
         Some(2),
         Some(arp),
@@ -104,6 +109,7 @@ pub fn new_bob2(now: Instant) -> SharedEngine {
 pub fn new_carrie(now: Instant) -> SharedEngine {
     let arp_config = ArpConfig::new(
         Some(Duration::from_secs(600)),
+        None,
         Some(Duration::from_secs(1)),
         Some(2),
         Some(HashMap::new()),
@@ -115,3 +121,21 @@ pub fn new_carrie(now: Instant) -> SharedEngine {
     let network = SharedTestRuntime::new(now, arp_config, udp_config, tcp_config, CARRIE_MAC, CARRIE_IPV4);
     SharedEngine::new(network, now).unwrap()
 }
+
+pub fn new_carrie2(now: Instant) -> SharedEngine {
+    let mut arp: HashMap<Ipv4Addr, MacAddress> = HashMap::<Ipv4Addr, MacAddress>::new();
+    arp.insert(CARRIE_IPV4, CARRIE_MAC);
+    arp.insert(BOB_IPV4, BOB_MAC);
+    let arp_config = ArpConfig::new(
+        Some(Duration::from_secs(600)),
+        None,
+        Some(Duration::from_secs(1)),
+        Some(2),
+        Some(arp),
+        Some(false),
+    );
+    let udp_config = UdpConfig::default();
+    let tcp_config = TcpConfig::default();
+    let network = SharedTestRuntime::new(now, arp_config, udp_config, tcp_config, CARRIE_MAC, CARRIE_IPV4);
+    SharedEngine::new(network, now).unwrap()
+}
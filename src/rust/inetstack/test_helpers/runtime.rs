@@ -95,6 +95,11 @@ impl SharedTestRuntime {
         self.pop_frames(1).pop_front().expect("should be at least one frame")
     }
 
+    /// Add a frame to the runtime's incoming queue, from which the stack receives it when it is polled.
+    pub fn push_frame(&mut self, buf: DemiBuffer) {
+        self.incoming.push_back(buf);
+    }
+
     /// Get the link address assigned to the runtime.
     pub fn get_link_addr(&self) -> MacAddress {
         self.link_addr
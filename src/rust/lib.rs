@@ -42,18 +42,34 @@ mod catmem;
 mod catloop;
 
 pub use self::demikernel::libos::{
+    metrics::{
+        ConnectionMetrics,
+        Metrics,
+    },
     name::LibOSName,
+    network::{
+        accept_stream::AcceptStream,
+        queue_info::QueueInfo,
+    },
     LibOS,
 };
 pub use crate::runtime::{
+    cancellation::CancellationToken,
     network::types::{
+        ConnectionSnapshot,
+        DropStats,
         MacAddress,
         Port16,
+        TcpSeqState,
     },
     types::{
         demi_sgarray_t,
         demi_sgaseg_t,
     },
+    wait::{
+        WaitInterrupter,
+        WaitOutcome,
+    },
     OperationResult,
     QDesc,
     QToken,
@@ -64,9 +80,78 @@ pub mod demikernel;
 
 use mimalloc::MiMalloc;
 
+#[cfg(not(test))]
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+/// Unit tests count the allocations that a thread makes within an [AllocationCounter] scope, so that they can check
+/// that a code path does not allocate. Allocations made outside such a scope, including those of every other test, are
+/// passed through untouched.
+#[cfg(test)]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[cfg(test)]
+thread_local! {
+    /// Number of allocations made by the thread within the current counting scope, if any.
+    static ALLOCATIONS: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+}
+
+#[cfg(test)]
+struct CountingAllocator;
+
+#[cfg(test)]
+impl CountingAllocator {
+    fn count() {
+        let _ = ALLOCATIONS.try_with(|count| {
+            if let Some(n) = count.get() {
+                count.set(Some(n + 1));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        Self::count();
+        MiMalloc.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        MiMalloc.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        Self::count();
+        MiMalloc.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Counts the heap allocations made by the calling thread from its creation until it is dropped.
+#[cfg(test)]
+pub(crate) struct AllocationCounter(());
+
+#[cfg(test)]
+impl AllocationCounter {
+    pub(crate) fn new() -> Self {
+        ALLOCATIONS.with(|count| count.set(Some(0)));
+        Self(())
+    }
+
+    /// Returns the number of allocations made so far within the scope.
+    pub(crate) fn allocations(&self) -> usize {
+        ALLOCATIONS.with(|count| count.get().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+impl Drop for AllocationCounter {
+    fn drop(&mut self) {
+        let _ = ALLOCATIONS.try_with(|count| count.set(None));
+    }
+}
+
 //======================================================================================================================
 // Macros
 //======================================================================================================================
@@ -17,6 +17,21 @@ pub const AF_INET6: WinSock::ADDRESS_FAMILY = WinSock::AF_INET6;
 #[cfg(target_os = "windows")]
 pub const SOMAXCONN: i32 = WinSock::SOMAXCONN as i32;
 
+#[cfg(target_os = "windows")]
+pub const SOL_SOCKET: i32 = WinSock::SOL_SOCKET as i32;
+
+#[cfg(target_os = "windows")]
+pub const SO_RCVBUF: i32 = WinSock::SO_RCVBUF as i32;
+
+#[cfg(target_os = "windows")]
+pub const SO_SNDBUF: i32 = WinSock::SO_SNDBUF as i32;
+
+#[cfg(target_os = "windows")]
+pub const IPPROTO_TCP: i32 = WinSock::IPPROTO_TCP.0;
+
+#[cfg(target_os = "windows")]
+pub const TCP_NODELAY: i32 = WinSock::TCP_NODELAY as i32;
+
 //==============================================================================
 // Linux constants
 //==============================================================================
@@ -29,3 +44,18 @@ pub const AF_INET6: libc::sa_family_t = libc::AF_INET6 as u16;
 
 #[cfg(target_os = "linux")]
 pub const SOMAXCONN: i32 = libc::SOMAXCONN;
+
+#[cfg(target_os = "linux")]
+pub const SOL_SOCKET: i32 = libc::SOL_SOCKET;
+
+#[cfg(target_os = "linux")]
+pub const SO_RCVBUF: i32 = libc::SO_RCVBUF;
+
+#[cfg(target_os = "linux")]
+pub const SO_SNDBUF: i32 = libc::SO_SNDBUF;
+
+#[cfg(target_os = "linux")]
+pub const IPPROTO_TCP: i32 = libc::IPPROTO_TCP;
+
+#[cfg(target_os = "linux")]
+pub const TCP_NODELAY: i32 = libc::TCP_NODELAY;
@@ -5,6 +5,7 @@
 // Exports
 //======================================================================================================================
 
+pub mod numa;
 #[cfg(feature = "catmem-libos")]
 pub mod shm;
 
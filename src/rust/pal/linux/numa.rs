@@ -0,0 +1,98 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::fail::Fail;
+use ::std::{
+    io,
+    path::Path,
+    ptr::{
+        self,
+        NonNull,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Policy of `mbind()` that takes the pages of a memory range from the given nodes only.
+const MPOL_BIND: libc::c_int = 2;
+
+/// Directory where the kernel lists the NUMA nodes of the machine.
+const NUMA_NODES_DIR: &str = "/sys/devices/system/node";
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Checks whether the machine has NUMA node `node`.
+pub fn is_numa_node(node: u32) -> bool {
+    Path::new(&format!("{}/node{}", NUMA_NODES_DIR, node)).exists()
+}
+
+/// Maps `len` bytes of anonymous memory, whose pages are taken from NUMA node `node` as they are first touched.
+/// Fails with ENOMEM if the memory cannot be mapped.
+pub fn map_on_node(len: usize, node: u32) -> Result<NonNull<u8>, Fail> {
+    // Safety: This is safe, as we ask for a fresh anonymous mapping and check for failure.
+    let addr: *mut libc::c_void = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    if addr == libc::MAP_FAILED {
+        let cause: String = format!("failed to map memory (len={:?})", len);
+        error!("map_on_node(): {}", cause);
+        return Err(Fail::new(libc::ENOMEM, &cause));
+    }
+
+    // Build a mask with the bit of the node set.
+    let bits: usize = libc::c_ulong::BITS as usize;
+    let mut nodemask: Vec<libc::c_ulong> = vec![0; node as usize / bits + 1];
+    nodemask[node as usize / bits] |= 1 << (node as usize % bits);
+
+    // Safety: This is safe, as the range was just mapped and the mask holds as many bits as we say.
+    let ret: libc::c_long = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            addr,
+            len,
+            MPOL_BIND,
+            nodemask.as_ptr(),
+            // The kernel reads one bit less than it is told.
+            nodemask.len() * bits + 1,
+            0,
+        )
+    };
+    // The memory is still usable if it cannot be bound, e.g. because the process may not set memory policies, so it
+    // is then left wherever the kernel places it.
+    if ret != 0 {
+        warn!(
+            "map_on_node(): failed to bind memory to NUMA node (node={:?}, error={:?})",
+            node,
+            io::Error::last_os_error()
+        );
+    }
+
+    // Safety: This is safe, as mmap() never returns a null address on success.
+    Ok(unsafe { NonNull::new_unchecked(addr.cast::<u8>()) })
+}
+
+/// Unmaps memory mapped with [map_on_node].
+///
+/// # Safety
+///
+/// The address and length must be those of a mapping made with [map_on_node], and nothing may reference the memory.
+pub unsafe fn unmap(addr: NonNull<u8>, len: usize) {
+    if libc::munmap(addr.as_ptr().cast::<libc::c_void>(), len) != 0 {
+        warn!("unmap(): failed to unmap memory (len={:?})", len);
+    }
+}
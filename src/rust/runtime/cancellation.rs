@@ -0,0 +1,121 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::{
+    fail::Fail,
+    scheduler::YielderHandle,
+    SharedObject,
+};
+use ::std::{
+    collections::HashMap,
+    ops::{
+        Deref,
+        DerefMut,
+    },
+};
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// State shared by the clones of a cancellation token.
+#[derive(Default)]
+pub struct CancellationTokenState {
+    /// Has the token been cancelled?
+    cancelled: bool,
+    /// Yielders of the pending operations that were issued with the token, by registration.
+    yielder_handles: HashMap<u64, YielderHandle>,
+    /// Identifier of the next registration.
+    next_registration: u64,
+}
+
+/// Token that cancels a group of related operations at once. Every operation issued with a clone of the token
+/// completes with `ECANCELED` when the token is cancelled, unless it has already completed.
+#[derive(Clone)]
+pub struct CancellationToken(SharedObject<CancellationTokenState>);
+
+/// Ties an operation to a cancellation token for as long as it lives. Dropping it once the operation completes stops
+/// the token from holding on to the yielder of the operation.
+pub struct CancellationRegistration {
+    token: CancellationToken,
+    id: u64,
+}
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl CancellationToken {
+    /// Creates a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self(SharedObject::new(CancellationTokenState::default()))
+    }
+
+    /// Cancels every operation issued with this token, including the ones that are issued after this call.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+        for (_, mut yielder_handle) in self.yielder_handles.drain() {
+            yielder_handle.wake_with(Err(Fail::new(libc::ECANCELED, "operation was cancelled")));
+        }
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Returns the number of operations issued with this token that are still pending.
+    pub fn num_pending(&self) -> usize {
+        self.yielder_handles.len()
+    }
+
+    /// Ties the operation that waits on `yielder_handle` to this token, until the returned registration is dropped. If
+    /// the token has already been cancelled, the operation is cancelled right away.
+    pub fn register(&mut self, mut yielder_handle: YielderHandle) -> CancellationRegistration {
+        let id: u64 = self.next_registration;
+        self.next_registration += 1;
+        if self.cancelled {
+            yielder_handle.wake_with(Err(Fail::new(libc::ECANCELED, "operation was cancelled")));
+        } else {
+            self.yielder_handles.insert(id, yielder_handle);
+        }
+        CancellationRegistration {
+            token: self.clone(),
+            id,
+        }
+    }
+}
+
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+impl Drop for CancellationRegistration {
+    fn drop(&mut self) {
+        self.token.yielder_handles.remove(&self.id);
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for CancellationToken {
+    type Target = CancellationTokenState;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.deref()
+    }
+}
+
+impl DerefMut for CancellationToken {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.deref_mut()
+    }
+}
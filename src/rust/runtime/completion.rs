@@ -0,0 +1,181 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::types::demi_qresult_t;
+use ::std::collections::VecDeque;
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Fixed-capacity ring of completed operations. All storage is allocated up front, so completions can be written
+/// during poll and harvested afterwards without touching the heap.
+pub struct CompletionRing {
+    /// Completed operations, oldest first. Never grows beyond its initial capacity.
+    completions: VecDeque<demi_qresult_t>,
+    /// Maximum number of completions that the ring holds.
+    capacity: usize,
+}
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl CompletionRing {
+    /// Creates a ring that holds up to `capacity` completions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            completions: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the number of completions that can still be written to the ring.
+    pub fn free_space(&self) -> usize {
+        self.capacity - self.completions.len()
+    }
+
+    /// Returns the number of completions waiting to be harvested.
+    pub fn len(&self) -> usize {
+        self.completions.len()
+    }
+
+    /// Returns whether there are no completions waiting to be harvested.
+    pub fn is_empty(&self) -> bool {
+        self.completions.is_empty()
+    }
+
+    /// Writes a completion to the ring. The caller must ensure that there is free space.
+    pub fn push(&mut self, qr: demi_qresult_t) {
+        debug_assert!(self.free_space() > 0, "completion ring overflow");
+        self.completions.push_back(qr);
+    }
+
+    /// Moves up to `out.len()` completions from the ring into `out`, oldest first, and returns how many were moved.
+    pub fn harvest(&mut self, out: &mut [demi_qresult_t]) -> usize {
+        let mut n: usize = 0;
+        while n < out.len() {
+            match self.completions.pop_front() {
+                Some(qr) => {
+                    out[n] = qr;
+                    n += 1;
+                },
+                None => break,
+            }
+        }
+        n
+    }
+}
+
+//======================================================================================================================
+// Unit Tests
+//======================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        runtime::{
+            scheduler::Yielder,
+            types::{
+                demi_opcode_t,
+                demi_qresult_t,
+            },
+            Operation,
+            OperationResult,
+            QDesc,
+            QToken,
+            SharedDemiRuntime,
+        },
+        AllocationCounter,
+    };
+    use ::anyhow::Result;
+    use ::futures::FutureExt;
+    use ::std::{
+        mem,
+        pin::Pin,
+        time::Instant,
+    };
+
+    #[test]
+    fn harvest_completions_in_chunks_without_allocating() -> Result<()> {
+        const NUM_COMPLETIONS: usize = 5;
+        let mut runtime: SharedDemiRuntime = SharedDemiRuntime::new(Instant::now());
+        runtime.enable_completion_ring(NUM_COMPLETIONS);
+
+        let mut qts: Vec<QToken> = Vec::with_capacity(NUM_COMPLETIONS);
+        for i in 0..NUM_COMPLETIONS {
+            let qd: QDesc = QDesc::from(i as u32);
+            let coroutine_factory = move |_: Yielder| -> Pin<Box<Operation>> {
+                Box::pin(async move { (qd, OperationResult::Push) }.fuse())
+            };
+            let qt: QToken = runtime.insert_coroutine_with_tracking("push", coroutine_factory, qd)?;
+            qts.push(qt);
+        }
+        runtime.poll();
+        crate::ensure_eq!(runtime.completion_ring_len(), NUM_COMPLETIONS);
+
+        let mut out: [demi_qresult_t; 2] = unsafe { mem::zeroed() };
+        let mut harvested: Vec<u64> = Vec::with_capacity(NUM_COMPLETIONS);
+        let mut all_pushes: bool = true;
+        let counter: AllocationCounter = AllocationCounter::new();
+        let mut counts: [usize; 3] = [0; 3];
+        for count in counts.iter_mut() {
+            *count = runtime.harvest(&mut out);
+            for qr in &out[..*count] {
+                harvested.push(qr.qr_qt);
+                all_pushes &= qr.qr_opcode == demi_opcode_t::DEMI_OPC_PUSH;
+            }
+        }
+        crate::ensure_eq!(counter.allocations(), 0);
+        drop(counter);
+
+        crate::ensure_eq!(counts, [2, 2, 1]);
+        crate::ensure_eq!(runtime.harvest(&mut out), 0);
+        crate::ensure_eq!(all_pushes, true);
+        harvested.sort();
+        let mut expected: Vec<u64> = qts.iter().map(|qt| u64::from(*qt)).collect();
+        expected.sort();
+        crate::ensure_eq!(harvested, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn completions_wait_for_room_in_ring() -> Result<()> {
+        const CAPACITY: usize = 2;
+        const NUM_COMPLETIONS: usize = 5;
+        let mut runtime: SharedDemiRuntime = SharedDemiRuntime::new(Instant::now());
+        runtime.enable_completion_ring(CAPACITY);
+
+        let mut qts: Vec<QToken> = Vec::with_capacity(NUM_COMPLETIONS);
+        for i in 0..NUM_COMPLETIONS {
+            let qd: QDesc = QDesc::from(i as u32);
+            let coroutine_factory = move |_: Yielder| -> Pin<Box<Operation>> {
+                Box::pin(async move { (qd, OperationResult::Push) }.fuse())
+            };
+            let qt: QToken = runtime.insert_coroutine_with_tracking("push", coroutine_factory, qd)?;
+            qts.push(qt);
+        }
+
+        // Operations that do not fit in the ring are delivered in completion order as room is made.
+        let mut out: [demi_qresult_t; CAPACITY] = unsafe { mem::zeroed() };
+        let mut harvested: Vec<u64> = Vec::with_capacity(NUM_COMPLETIONS);
+        for expected in [2, 2, 1, 0] {
+            runtime.poll();
+            crate::ensure_eq!(runtime.completion_ring_len(), expected);
+            let count: usize = runtime.harvest(&mut out);
+            harvested.extend(out[..count].iter().map(|qr| qr.qr_qt));
+        }
+
+        let mut expected: Vec<u64> = qts.iter().map(|qt| u64::from(*qt)).collect();
+        harvested.sort();
+        expected.sort();
+        crate::ensure_eq!(harvested, expected);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::fail::Fail;
+use ::std::{
+    alloc::{
+        alloc,
+        dealloc,
+        Layout,
+    },
+    sync::OnceLock,
+};
+
+//======================================================================================================================
+// Global Variables
+//======================================================================================================================
+
+/// Allocator that backs every heap-allocated DemiBuffer of the process. It is fixed at the first allocation, so that
+/// every buffer is released to the allocator that it came from.
+static BUFFER_ALLOCATOR: OnceLock<Box<dyn BufferAllocator>> = OnceLock::new();
+
+//======================================================================================================================
+// Traits
+//======================================================================================================================
+
+/// Source of the memory that backs heap-allocated DemiBuffers, e.g. a pool of huge pages or of NUMA-local memory. The
+/// allocator receives the layout of the whole allocation, which holds the buffer's metadata followed by its data.
+pub trait BufferAllocator: Send + Sync {
+    /// Allocates memory with the given layout. Returns a null pointer if the allocation fails.
+    ///
+    /// # Safety
+    ///
+    /// The layout must have a non-zero size.
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8;
+
+    /// Releases memory previously returned by [BufferAllocator::allocate].
+    ///
+    /// # Safety
+    ///
+    /// The pointer must have been returned by [BufferAllocator::allocate] of this allocator with the same layout, and
+    /// must not have been released already.
+    unsafe fn deallocate(&self, allocation: *mut u8, layout: Layout);
+}
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Default [BufferAllocator], which takes memory from the global heap.
+pub struct HeapBufferAllocator;
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Installs the allocator that backs heap-allocated DemiBuffers. This must be done before the process allocates its
+/// first DemiBuffer, and can only be done once. Otherwise, this fails with EBUSY.
+pub fn install_buffer_allocator(allocator: Box<dyn BufferAllocator>) -> Result<(), Fail> {
+    BUFFER_ALLOCATOR.set(allocator).map_err(|_| {
+        let cause: &str = "buffer allocator is already in use";
+        error!("install_buffer_allocator(): {}", cause);
+        Fail::new(libc::EBUSY, cause)
+    })
+}
+
+/// Allocates memory for a DemiBuffer from the installed allocator, installing the default one if there is none yet.
+/// Returns a null pointer if the allocation fails.
+///
+/// # Safety
+///
+/// The layout must have a non-zero size.
+pub(super) unsafe fn allocate_buffer(layout: Layout) -> *mut u8 {
+    BUFFER_ALLOCATOR
+        .get_or_init(|| Box::new(HeapBufferAllocator))
+        .allocate(layout)
+}
+
+/// Releases memory that was allocated with [allocate_buffer].
+///
+/// # Safety
+///
+/// The pointer must have been returned by [allocate_buffer] with the same layout, and must not have been released
+/// already.
+pub(super) unsafe fn deallocate_buffer(allocation: *mut u8, layout: Layout) {
+    // The allocator is fixed at the first allocation, so it is always there by the time anything is released.
+    if let Some(allocator) = BUFFER_ALLOCATOR.get() {
+        allocator.deallocate(allocation, layout)
+    }
+}
+
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+impl BufferAllocator for HeapBufferAllocator {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        alloc(layout)
+    }
+
+    unsafe fn deallocate(&self, allocation: *mut u8, layout: Layout) {
+        dealloc(allocation, layout)
+    }
+}
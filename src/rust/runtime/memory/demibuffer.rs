@@ -24,13 +24,31 @@
 // TODO: Expose calls to get/set a linking field.
 
 // Note on the allocation functions:
-// This code currently uses std::alloc() and std::dealloc() to allocate/free things from the heap.  Note that the Rust
-// documentation says that these functions are expected to be deprecated in favor of their respective methods of the
-// "Global" type when it and the "Allocator" trait become stable.
+// This code allocates/frees things from the heap through the installed BufferAllocator, which defaults to
+// std::alloc() and std::dealloc().  Note that the Rust documentation says that these functions are expected to be
+// deprecated in favor of their respective methods of the "Global" type when it and the "Allocator" trait become stable.
 
+#[cfg(target_os = "linux")]
+use crate::pal::linux::numa;
 use crate::{
     pal::arch,
-    runtime::fail::Fail,
+    runtime::{
+        fail::Fail,
+        limits,
+        memory::{
+            allocator::{
+                allocate_buffer,
+                deallocate_buffer,
+            },
+            pool::{
+                BufferPoolStats,
+                FragmentationReport,
+                SharedSmallBufferPool,
+                SizeClassFragmentation,
+                SmallBufferPool,
+            },
+        },
+    },
 };
 #[cfg(feature = "libdpdk")]
 use ::dpdk_rs::{
@@ -43,11 +61,10 @@ use ::dpdk_rs::{
 };
 use ::std::{
     alloc::{
-        alloc,
-        dealloc,
         handle_alloc_error,
         Layout,
     },
+    cell::RefCell,
     marker::PhantomData,
     mem::{
         self,
@@ -115,7 +132,8 @@ struct MetaData {
     // Allocated length of the buffer that buf_addr points to.
     buf_len: u16,
 
-    // Pointer to memory pool (rte_mempool) from which mbuf was allocated.
+    // Pointer to memory pool (rte_mempool) from which mbuf was allocated. For heap-allocated buffers, this is the
+    // address of the SharedSmallBufferPool that the buffer was drawn from, or zero if there is none.
     _pool: u64,
 
     // Second cache line (64 bytes) begins here.
@@ -149,6 +167,34 @@ const _: () = assert!(std::mem::size_of::<MetaData>() == 2 * arch::CPU_DATA_CACH
 // points to another MetaData's directly attached data.
 const METADATA_F_INDIRECT: u64 = 1 << 62;
 
+/// Capacity of the buffers in the largest size class of the small-buffer pool. [DemiBuffer::new_pooled] draws buffers
+/// of up to this size from the pool rather than from the general allocator.
+pub const SMALL_BUFFER_SIZE: u16 = 256;
+
+/// Capacities of the size classes of the small-buffer pool, from the smallest to the largest. [DemiBuffer::new_pooled]
+/// draws each buffer from the smallest size class that fits it.
+pub const SMALL_BUFFER_SIZE_CLASSES: [u16; 3] = [64, 128, SMALL_BUFFER_SIZE];
+
+/// Capacities of the size classes of the pools that queues draw their buffers from, from the smallest to the largest.
+/// They go up to the size of the buffers that pops allocate when the application does not ask for a size.
+pub const POOL_SIZE_CLASSES: [u16; 8] = [
+    64,
+    128,
+    SMALL_BUFFER_SIZE,
+    512,
+    1024,
+    2048,
+    4096,
+    limits::RECVBUF_SIZE_MAX as u16,
+];
+
+// Pools of recycled allocations for heap-allocated DemiBuffers, one for each of the SMALL_BUFFER_SIZE_CLASSES of
+// direct data.  Like the reference counts, the pools are not shared between threads.
+thread_local! {
+    static SMALL_BUFFER_POOLS: RefCell<[SmallBufferPool; SMALL_BUFFER_SIZE_CLASSES.len()]> =
+        RefCell::new(SMALL_BUFFER_SIZE_CLASSES.map(|size| SmallBufferPool::new(metadata_data_layout(size))));
+}
+
 impl MetaData {
     // Note on Reference Counts:
     // Since we are currently single-threaded, there is no need to use atomic operations for refcnt manipulations.
@@ -262,15 +308,120 @@ impl DemiBuffer {
     // status quo, and assume this allocation never fails.
     pub fn new(capacity: u16) -> Self {
         // Allocate some memory off the heap.
-        let mut temp: NonNull<MetaData> = allocate_metadata_data(capacity);
+        let temp: NonNull<MetaData> = allocate_metadata_data(capacity);
+        Self::new_with_allocation(temp, capacity, capacity)
+    }
+
+    /// Creates a new (Heap-allocated) `DemiBuffer`, drawing it from the smallest size class of the small-buffer pool
+    /// that fits it if `capacity` does not exceed [SMALL_BUFFER_SIZE]. This avoids the general allocator for the many
+    /// small buffers of a busy receive path.
+    pub fn new_pooled(capacity: u16) -> Self {
+        if capacity == 0 {
+            return Self::new(capacity);
+        }
+        let size_class: usize = match SMALL_BUFFER_SIZE_CLASSES.iter().position(|size| *size >= capacity) {
+            Some(size_class) => size_class,
+            None => return Self::new(capacity),
+        };
+        let temp: NonNull<MetaData> = allocate_pooled_metadata_data(size_class);
+        Self::new_with_allocation(temp, SMALL_BUFFER_SIZE_CLASSES[size_class], capacity)
+    }
+
+    /// Creates a `DemiBuffer` from the smallest of the [POOL_SIZE_CLASSES] of `pool` that fits it, if any, like
+    /// [DemiBuffer::new_pooled] does from the small-buffer pool of the calling thread. The buffer goes back to `pool`
+    /// when released.
+    pub fn new_pooled_from(capacity: u16, pool: &SharedSmallBufferPool) -> Self {
+        if capacity == 0 {
+            return Self::new(capacity);
+        }
+        let size_class: usize = match POOL_SIZE_CLASSES.iter().position(|size| *size >= capacity) {
+            Some(size_class) => size_class,
+            None => return Self::new(capacity),
+        };
+        let buf_len: u16 = POOL_SIZE_CLASSES[size_class];
+        let mut temp: NonNull<MetaData> =
+            initialize_metadata_data(pool.allocate(size_class), metadata_data_layout(buf_len));
+        // Safety: This is safe, as temp is aligned, dereferenceable, and metadata isn't aliased in this block.
+        unsafe { temp.as_mut() }._pool = pool.clone().into_raw();
+        Self::new_with_allocation(temp, buf_len, capacity)
+    }
+
+    /// Creates an empty small-buffer pool, for a queue to draw its buffers from with [DemiBuffer::new_pooled_from].
+    pub fn new_small_buffer_pool() -> SharedSmallBufferPool {
+        SharedSmallBufferPool::new(POOL_SIZE_CLASSES.iter().map(|size| metadata_data_layout(*size)), None)
+    }
+
+    /// Creates an empty small-buffer pool like [DemiBuffer::new_small_buffer_pool], whose memory is placed on NUMA node
+    /// `numa_node`. This fails with EINVAL if the machine has no such node, and with ENOTSUP on platforms where memory
+    /// cannot be placed.
+    pub fn new_small_buffer_pool_on_node(numa_node: u32) -> Result<SharedSmallBufferPool, Fail> {
+        #[cfg(target_os = "linux")]
+        {
+            if !numa::is_numa_node(numa_node) {
+                let cause: String = format!("no such NUMA node (numa_node={:?})", numa_node);
+                error!("new_small_buffer_pool_on_node(): {}", cause);
+                return Err(Fail::new(libc::EINVAL, &cause));
+            }
+            Ok(SharedSmallBufferPool::new(
+                POOL_SIZE_CLASSES.iter().map(|size| metadata_data_layout(*size)),
+                Some(numa_node),
+            ))
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let cause: String = format!("cannot place memory on NUMA nodes (numa_node={:?})", numa_node);
+            error!("new_small_buffer_pool_on_node(): {}", cause);
+            Err(Fail::new(libc::ENOTSUP, &cause))
+        }
+    }
+
+    /// Returns the hit and miss counters of the small-buffer pool of the calling thread, summed over its size classes.
+    pub fn small_buffer_pool_stats() -> BufferPoolStats {
+        SMALL_BUFFER_POOLS.with(|pools| {
+            let mut stats: BufferPoolStats = BufferPoolStats::default();
+            for pool in pools.borrow().iter() {
+                let pool_stats: BufferPoolStats = pool.stats();
+                stats.hits += pool_stats.hits;
+                stats.misses += pool_stats.misses;
+                stats.allocation_failures += pool_stats.allocation_failures;
+            }
+            stats
+        })
+    }
+
+    /// Zeroes the hit and miss counters of the small-buffer pool of the calling thread.
+    pub fn reset_small_buffer_pool_stats() {
+        SMALL_BUFFER_POOLS.with(|pools| {
+            for pool in pools.borrow_mut().iter_mut() {
+                pool.reset_stats();
+            }
+        })
+    }
+
+    /// Returns the free buffers of the small-buffer pool of the calling thread, by size class.
+    pub fn small_buffer_pool_fragmentation() -> FragmentationReport {
+        SMALL_BUFFER_POOLS.with(|pools| FragmentationReport {
+            size_classes: SMALL_BUFFER_SIZE_CLASSES
+                .iter()
+                .zip(pools.borrow().iter())
+                .map(|(size, pool)| SizeClassFragmentation {
+                    buffer_size: *size,
+                    free_buffers: pool.free_allocations(),
+                })
+                .collect(),
+        })
+    }
 
+    // Initializes a freshly allocated `DemiBuffer` with `buf_len` bytes of direct data, of which the first `capacity`
+    // bytes are in use.
+    fn new_with_allocation(mut temp: NonNull<MetaData>, buf_len: u16, capacity: u16) -> Self {
         // Initialize the MetaData.
         {
             // Safety: This is safe, as temp is aligned, dereferenceable, and metadata isn't aliased in this block.
             let metadata: &mut MetaData = unsafe { temp.as_mut() };
 
             // Point buf_addr at the newly allocated data space (if any).
-            if capacity == 0 {
+            if buf_len == 0 {
                 // No direct data, so don't point buf_addr at anything.
                 metadata.buf_addr = null_mut();
             } else {
@@ -287,7 +438,7 @@ impl DemiBuffer {
             metadata.ol_flags = 0;
             metadata.pkt_len = capacity as u32;
             metadata.data_len = capacity;
-            metadata.buf_len = capacity;
+            metadata.buf_len = buf_len;
             metadata.next = None;
         }
 
@@ -308,6 +459,31 @@ impl DemiBuffer {
         slice.try_into()
     }
 
+    /// Create a new Heap-allocated `DemiBuffer` holding the contents of `bufs`, in order. The data is copied into a
+    /// single allocation, so it fails with EINVAL if the total length exceeds what a `DemiBuffer` can hold.
+    pub fn concat(bufs: Vec<DemiBuffer>) -> Result<Self, Fail> {
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let capacity: u16 = match u16::try_from(total_len) {
+            Ok(capacity) => capacity,
+            Err(_) => {
+                let cause: String = format!(
+                    "buffers are larger than a DemiBuffer can hold (total_len={})",
+                    total_len
+                );
+                error!("concat(): {}", cause);
+                return Err(Fail::new(libc::EINVAL, &cause));
+            },
+        };
+
+        let mut result: DemiBuffer = DemiBuffer::new(capacity);
+        let mut offset: usize = 0;
+        for buf in &bufs {
+            result[offset..offset + buf.len()].copy_from_slice(&buf[..]);
+            offset += buf.len();
+        }
+        Ok(result)
+    }
+
     /// Creates a `DemiBuffer` from a raw pointer.
     pub unsafe fn from_raw(token: NonNull<u8>) -> Self {
         DemiBuffer {
@@ -353,6 +529,77 @@ impl DemiBuffer {
         self.as_metadata().data_len as usize
     }
 
+    /// Returns the number of bytes that the `DemiBuffer` can hold without reallocating, counting from the start of its
+    /// data. Buffers that share their data with clones, span multiple segments, or were allocated by DPDK cannot grow
+    /// in place, so their capacity is their length.
+    pub fn capacity(&self) -> usize {
+        if !self.can_grow_in_place() {
+            return self.len();
+        }
+        let metadata: &MetaData = self.as_metadata();
+        (metadata.buf_len - metadata.data_off) as usize
+    }
+
+    /// Ensures that at least `additional` bytes can be appended to the `DemiBuffer` without reallocating. If the
+    /// current capacity does not suffice, the data is moved once to a new allocation that has exactly the requested
+    /// capacity. This fails with EINVAL if the length plus `additional` exceeds what a `DemiBuffer` can hold.
+    pub fn reserve(&mut self, additional: usize) -> Result<(), Fail> {
+        let len: usize = self.len();
+        if self.capacity() - len >= additional {
+            return Ok(());
+        }
+
+        #[cfg(feature = "libdpdk")]
+        if self.get_tag() == Tag::Dpdk {
+            let cause: &str = "cannot reserve capacity in a DPDK-allocated buffer";
+            error!("reserve(): {}", cause);
+            return Err(Fail::new(libc::ENOTSUP, cause));
+        }
+
+        if self.is_multi_segment() {
+            let cause: &str = "cannot reserve capacity in a multi-segment buffer";
+            error!("reserve(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, cause));
+        }
+
+        let capacity: u16 = match u16::try_from(len + additional) {
+            Ok(capacity) => capacity,
+            Err(_) => {
+                let cause: String = format!(
+                    "requested capacity is larger than a DemiBuffer can hold (len={}, additional={})",
+                    len, additional
+                );
+                error!("reserve(): {}", cause);
+                return Err(Fail::new(libc::EINVAL, &cause));
+            },
+        };
+
+        let mut buf: DemiBuffer = DemiBuffer::new(capacity);
+        buf[..len].copy_from_slice(&self[..]);
+        // This unwrap won't panic, as the new buffer holds more than `capacity - len` bytes.
+        buf.trim(capacity as usize - len).unwrap();
+        *self = buf;
+        Ok(())
+    }
+
+    /// Appends a copy of `data` to the end of the `DemiBuffer`, reallocating as in [Self::reserve] if its capacity does
+    /// not suffice.
+    pub fn append(&mut self, data: &[u8]) -> Result<(), Fail> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.reserve(data.len())?;
+
+        // Reserving succeeded, so this is a single-segment heap-allocated buffer with enough room after its data.
+        let len: usize = self.len();
+        let metadata: &mut MetaData = self.as_metadata();
+        // The reserve above ensures that the new length fits into a u16.  So these casts are safe.
+        metadata.data_len += data.len() as u16;
+        metadata.pkt_len += data.len() as u32;
+        self[len..].copy_from_slice(data);
+        Ok(())
+    }
+
     /// Removes `nbytes` bytes from the beginning of the `DemiBuffer` chain.
     // Note: If `nbytes` is greater than the length of the first segment in the chain, then this function will fail and
     // return an error, rather than remove the remaining bytes from subsequent segments in the chain.  This is to match
@@ -644,36 +891,67 @@ impl DemiBuffer {
             },
         }
     }
+
+    // Checks whether the room after the data of this `DemiBuffer` may be written to, that is, whether this is a
+    // single-segment heap-allocated buffer that holds the only reference to its data.
+    fn can_grow_in_place(&self) -> bool {
+        match self.get_tag() {
+            Tag::Heap => {
+                let metadata: &MetaData = self.as_metadata();
+                metadata.nb_segs == 1 && metadata.refcnt == 1 && metadata.ol_flags & METADATA_F_INDIRECT == 0
+            },
+            #[cfg(feature = "libdpdk")]
+            Tag::Dpdk => false,
+        }
+    }
 }
 
 // ----------------
 // Helper Functions
 // ----------------
 
-// Allocates the MetaData (plus the space for any directly attached data) for a new heap-allocated DemiBuffer.
-fn allocate_metadata_data(direct_data_size: u16) -> NonNull<MetaData> {
+// Computes the layout of the MetaData (plus the space for any directly attached data) of a heap-allocated DemiBuffer.
+fn metadata_data_layout(direct_data_size: u16) -> Layout {
     // We need space for the MetaData struct, plus any extra memory for directly attached data.
     let amount: usize = size_of::<MetaData>() + direct_data_size as usize;
 
     // Given our limited allocation amount (u16::MAX) and fixed alignment size, this unwrap cannot panic.
-    let layout: Layout = Layout::from_size_align(amount, arch::CPU_DATA_CACHE_LINE_SIZE).unwrap();
+    Layout::from_size_align(amount, arch::CPU_DATA_CACHE_LINE_SIZE).unwrap()
+}
+
+// Allocates the MetaData (plus the space for any directly attached data) for a new heap-allocated DemiBuffer.
+fn allocate_metadata_data(direct_data_size: u16) -> NonNull<MetaData> {
+    let layout: Layout = metadata_data_layout(direct_data_size);
 
     // Safety: This is safe, as we check for a null return value before dereferencing "allocation".
-    let allocation: *mut u8 = unsafe { alloc(layout) };
+    let allocation: *mut u8 = unsafe { allocate_buffer(layout) };
+    initialize_metadata_data(allocation, layout)
+}
+
+// Allocates the MetaData (plus the directly attached data of the given size class) for a new heap-allocated DemiBuffer
+// from the small-buffer pool.
+fn allocate_pooled_metadata_data(size_class: usize) -> NonNull<MetaData> {
+    let allocation: *mut u8 = SMALL_BUFFER_POOLS.with(|pools| pools.borrow_mut()[size_class].allocate());
+    initialize_metadata_data(allocation, metadata_data_layout(SMALL_BUFFER_SIZE_CLASSES[size_class]))
+}
+
+// Checks that an allocation of the given layout succeeded and prepares it to hold the MetaData of a DemiBuffer.
+fn initialize_metadata_data(allocation: *mut u8, layout: Layout) -> NonNull<MetaData> {
     if allocation.is_null() {
         handle_alloc_error(layout);
     }
 
     let metadata: *mut MetaData = allocation.cast::<MetaData>();
 
+    // The buffer is not drawn from the pool of any queue, unless the caller says otherwise.
+    // Safety: The `metadata` dereference is safe, as it is known to be aligned and dereferenceable.
+    unsafe { (*metadata)._pool = 0 };
+
     // Initialize select MetaData fields in debug builds for sanity checking.
     // We check in debug builds that they aren't accidentally messed with.
     // Safety: The `metadata` dereferences in this block are safe, as it is known to be aligned and dereferenceable.
     #[cfg(debug_assertions)]
     unsafe {
-        // This field should only be non-null for DPDK-allocated DemiBuffers.
-        (*metadata)._pool = 0;
-
         // We don't currently use a "private data" feature akin to DPDK's.
         (*metadata)._priv_size = 0;
     }
@@ -688,21 +966,41 @@ fn free_metadata_data(buffer: NonNull<MetaData>) {
     // Safety: This is safe, as `buffer` is aligned, dereferenceable, and we don't let `metadata` escape this function.
     let metadata: &MetaData = unsafe { buffer.as_ref() };
 
-    // Check in debug builds that we weren't accidentally passed a DPDK-allocated MBuf to free.
-    debug_assert_eq!(metadata._pool, 0);
-
     // Determine the size of the original allocation.
     // Note that this code currently assumes we're not using a "private data" feature akin to DPDK's.
     debug_assert_eq!(metadata._priv_size, 0);
-    let amount: usize = size_of::<MetaData>() + metadata.buf_len as usize;
-    // This unwrap will never panic, as we pass a known allocation amount and a fixed alignment to from_size_align().
-    let layout: Layout = Layout::from_size_align(amount, arch::CPU_DATA_CACHE_LINE_SIZE).unwrap();
+    let buf_len: u16 = metadata.buf_len;
+
+    // Allocations drawn from the pool of a queue go back to it, along with their reference to it.
+    if metadata._pool != 0 {
+        let size_class: usize = POOL_SIZE_CLASSES
+            .iter()
+            .position(|size| *size == buf_len)
+            .expect("buffers drawn from the pool of a queue should have the size of one of its classes");
+        // Safety: This is safe, as the address was stored by new_pooled_from(), and is only taken back here, once.
+        let pool: SharedSmallBufferPool = unsafe { SharedSmallBufferPool::from_raw(metadata._pool) };
+        pool.release(size_class, buffer.cast::<u8>());
+        return;
+    }
+
+    // Allocations with the layout of a size class of the small-buffer pool go back to it, unless the pool is already
+    // gone because the thread is exiting.
+    if let Some(size_class) = SMALL_BUFFER_SIZE_CLASSES.iter().position(|size| *size == buf_len) {
+        if SMALL_BUFFER_POOLS
+            .try_with(|pools| pools.borrow_mut()[size_class].release(buffer.cast::<u8>()))
+            .is_ok()
+        {
+            return;
+        }
+    }
+
+    let layout: Layout = metadata_data_layout(buf_len);
 
     // Convert buffer pointer into a raw allocation pointer.
     let allocation: *mut u8 = buffer.cast::<u8>().as_ptr();
 
     // Safety: this is safe because we're using the same (de)allocator and Layout used for allocation.
-    unsafe { dealloc(allocation, layout) };
+    unsafe { deallocate_buffer(allocation, layout) };
 }
 
 // ---------------------
@@ -997,8 +1295,17 @@ impl TryFrom<&[u8]> for DemiBuffer {
 // Note that due to DPDK being a configurable option, all of these unit tests are only for heap-allocated `DemiBuffer`s.
 #[cfg(test)]
 mod tests {
-    use super::DemiBuffer;
+    use super::{
+        DemiBuffer,
+        SMALL_BUFFER_SIZE_CLASSES,
+    };
+    use crate::runtime::memory::pool::{
+        BufferPoolStats,
+        FragmentationReport,
+        SharedSmallBufferPool,
+    };
     use ::anyhow::Result;
+    use ::std::thread;
     use std::ptr::NonNull;
 
     // Test basic allocation, len, adjust, and trim.
@@ -1167,4 +1474,139 @@ mod tests {
 
         Ok(())
     }
+
+    // Tests concat.
+    #[test]
+    fn concat() -> Result<()> {
+        let bufs: Vec<DemiBuffer> = vec![
+            DemiBuffer::from_slice(b"word one ")?,
+            DemiBuffer::from_slice(b"")?,
+            DemiBuffer::from_slice(b"two three")?,
+        ];
+        let buf: DemiBuffer = DemiBuffer::concat(bufs)?;
+        crate::ensure_eq!(buf.len(), 18);
+        crate::ensure_eq!(&buf[..], b"word one two three");
+
+        // Concatenating nothing yields an empty buffer.
+        let empty: DemiBuffer = DemiBuffer::concat(vec![])?;
+        crate::ensure_eq!(empty.len(), 0);
+
+        // The result must fit in a single buffer.
+        let bufs: Vec<DemiBuffer> = vec![DemiBuffer::new(u16::MAX), DemiBuffer::new(1)];
+        match DemiBuffer::concat(bufs) {
+            Err(e) if e.errno == libc::EINVAL => {},
+            _ => anyhow::bail!("DemiBuffer::concat should fail for oversized buffers"),
+        };
+
+        Ok(())
+    }
+
+    // Tests reserve and append.
+    #[test]
+    fn reserve_and_append() -> Result<()> {
+        let mut buf: DemiBuffer = DemiBuffer::from_slice(b"word")?;
+        crate::ensure_eq!(buf.capacity(), 4);
+
+        // Reserving grows the capacity but not the length.
+        buf.reserve(20)?;
+        crate::ensure_eq!(buf.len(), 4);
+        crate::ensure_eq!(buf.capacity(), 24);
+        crate::ensure_eq!(&buf[..], b"word");
+
+        // Appends within the reserved capacity do not move the data.
+        let data: *const u8 = buf.as_ptr();
+        for word in [&b" one"[..], b" two", b" three"] {
+            buf.append(word)?;
+            crate::ensure_eq!(buf.as_ptr(), data);
+            crate::ensure_eq!(buf.capacity(), 24);
+        }
+        crate::ensure_eq!(&buf[..], b"word one two three");
+
+        // Appending beyond the capacity reallocates.
+        buf.append(b" four five six")?;
+        crate::ensure_eq!(&buf[..], b"word one two three four five six");
+
+        // Buffers that share their data with a clone reallocate rather than overwrite the clone's data.
+        let mut front: DemiBuffer = DemiBuffer::from_slice(b"word one")?;
+        let back: DemiBuffer = front.split_back(4)?;
+        front.append(b" two")?;
+        crate::ensure_eq!(&front[..], b"word two");
+        crate::ensure_eq!(&back[..], b" one");
+
+        // The total capacity is capped.
+        match buf.reserve(u16::MAX as usize) {
+            Err(e) if e.errno == libc::EINVAL => {},
+            _ => anyhow::bail!("DemiBuffer::reserve should fail for oversized capacities"),
+        };
+
+        Ok(())
+    }
+
+    // Tests that the fragmentation report of the small-buffer pool breaks its free buffers down by size class.
+    #[test]
+    fn small_buffer_pool_fragmentation() -> Result<()> {
+        let before: FragmentationReport = DemiBuffer::small_buffer_pool_fragmentation();
+        crate::ensure_eq!(before.size_classes.len(), SMALL_BUFFER_SIZE_CLASSES.len());
+
+        // Allocate buffers of mixed sizes, some of which are too large for the pool, and free them all.
+        let sizes: [(u16, usize); 4] = [(50, 8), (100, 4), (200, 2), (1000, 1)];
+        let mut bufs: Vec<DemiBuffer> = Vec::new();
+        for (size, count) in sizes {
+            for _ in 0..count {
+                bufs.push(DemiBuffer::new_pooled(size));
+            }
+        }
+        drop(bufs);
+
+        // Each buffer went back to the smallest size class that fits it.
+        let after: FragmentationReport = DemiBuffer::small_buffer_pool_fragmentation();
+        let freed: Vec<usize> = after
+            .size_classes
+            .iter()
+            .zip(before.size_classes.iter())
+            .map(|(after, before)| after.free_buffers - before.free_buffers)
+            .collect();
+        crate::ensure_eq!(freed, vec![8, 4, 2]);
+        crate::ensure_eq!(after.free_buffers() - before.free_buffers(), 14);
+        crate::ensure_eq!(after.free_bytes() - before.free_bytes(), 8 * 64 + 4 * 128 + 2 * 256);
+
+        // Reallocating small buffers only drains the smallest size class.
+        let bufs: Vec<DemiBuffer> = (0..after.size_classes[0].free_buffers)
+            .map(|_| DemiBuffer::new_pooled(30))
+            .collect();
+        let drained: FragmentationReport = DemiBuffer::small_buffer_pool_fragmentation();
+        crate::ensure_eq!(drained.size_classes[0].free_buffers, 0);
+        crate::ensure_eq!(drained.size_classes[1..], after.size_classes[1..]);
+        drop(bufs);
+
+        Ok(())
+    }
+
+    // Tests that a buffer drawn from the pool of a queue can be released on another thread, and that it goes back to
+    // the pool there.
+    #[test]
+    fn pooled_from_released_on_other_thread() -> Result<()> {
+        let pool: SharedSmallBufferPool = DemiBuffer::new_small_buffer_pool();
+        let bufs: Vec<DemiBuffer> = (0..4).map(|_| DemiBuffer::new_pooled_from(100, &pool)).collect();
+
+        // Drop the buffers on other threads, while this thread keeps drawing from the pool.
+        let handles: Vec<thread::JoinHandle<()>> =
+            bufs.into_iter().map(|buf| thread::spawn(move || drop(buf))).collect();
+        let local: Vec<DemiBuffer> = (0..4).map(|_| DemiBuffer::new_pooled_from(100, &pool)).collect();
+        for handle in handles {
+            if handle.join().is_err() {
+                anyhow::bail!("dropping a pooled buffer on another thread should not panic");
+            }
+        }
+        drop(local);
+
+        // Every buffer went back to the pool, and the pool is only referenced from here.
+        let stats: BufferPoolStats = pool.stats();
+        crate::ensure_eq!(stats.hits + stats.misses, 8);
+        let reused: Vec<DemiBuffer> = (0..8).map(|_| DemiBuffer::new_pooled_from(100, &pool)).collect();
+        crate::ensure_eq!(pool.stats().hits - stats.hits, stats.misses);
+        drop(reused);
+
+        Ok(())
+    }
 }
@@ -1,7 +1,9 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT license.
 
+mod allocator;
 mod demibuffer;
+mod pool;
 
 //==============================================================================
 // Imports
@@ -26,7 +28,21 @@ use ::std::{
 // Exports
 //==============================================================================
 
-pub use self::demibuffer::*;
+pub use self::{
+    allocator::{
+        install_buffer_allocator,
+        BufferAllocator,
+        HeapBufferAllocator,
+    },
+    demibuffer::*,
+    pool::{
+        BufferPoolStats,
+        FragmentationReport,
+        PoolId,
+        SharedSmallBufferPool,
+        SizeClassFragmentation,
+    },
+};
 
 //==============================================================================
 // Traits
@@ -0,0 +1,308 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+#[cfg(target_os = "linux")]
+use crate::pal::linux::numa;
+use crate::runtime::memory::allocator::{
+    allocate_buffer,
+    deallocate_buffer,
+};
+#[cfg(target_os = "linux")]
+use ::std::cmp;
+use ::std::{
+    alloc::Layout,
+    ptr::{
+        self,
+        NonNull,
+    },
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+    },
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Maximum number of free allocations that a pool keeps for reuse. Allocations released beyond this are returned to
+/// the general allocator.
+const MAX_FREE_ALLOCATIONS: usize = 1024;
+
+/// Number of bytes of memory that a pool placed on a NUMA node maps at a time, to split into allocations.
+#[cfg(target_os = "linux")]
+const NUMA_CHUNK_SIZE: usize = 64 * 1024;
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Hit and miss counters of a [SmallBufferPool].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferPoolStats {
+    /// Number of allocations served with a recycled allocation.
+    pub hits: usize,
+    /// Number of allocations that had to go to the general allocator because the pool was empty.
+    pub misses: usize,
+    /// Number of allocations that the general allocator failed.
+    pub allocation_failures: usize,
+}
+
+/// Identifier of a small-buffer pool that the libOS created on request, so that sockets can draw their buffers from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PoolId(pub usize);
+
+/// Free buffers of one size class of the small-buffer pools of a thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeClassFragmentation {
+    /// Capacity of the buffers in the size class.
+    pub buffer_size: u16,
+    /// Number of released buffers that the size class keeps for reuse.
+    pub free_buffers: usize,
+}
+
+/// Free buffers of the small-buffer pools of a thread, by size class from the smallest to the largest. Buffers that
+/// sit free in one size class cannot serve allocations of another, so a large share of free bytes in classes that
+/// the application no longer uses is wasted capacity.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FragmentationReport {
+    /// Free buffers of each size class.
+    pub size_classes: Vec<SizeClassFragmentation>,
+}
+
+/// Pool of recycled allocations of a single layout. Allocations are taken from the pool whenever possible and are
+/// returned to it when released, so that a steady stream of allocations of the same size does not reach the general
+/// allocator.
+pub struct SmallBufferPool {
+    /// Layout of every allocation in the pool.
+    layout: Layout,
+    /// Released allocations, ready for reuse.
+    free: Vec<NonNull<u8>>,
+    /// Hit and miss counters.
+    stats: BufferPoolStats,
+    /// NUMA node that the memory of the pool is placed on, if any. That memory is mapped for the pool in chunks, and
+    /// only goes back to the system when the pool is dropped.
+    numa_node: Option<u32>,
+    /// Chunks of memory mapped on the NUMA node of the pool, with their lengths.
+    #[cfg(target_os = "linux")]
+    chunks: Vec<(NonNull<u8>, usize)>,
+}
+
+/// [SmallBufferPool]s of several size classes, owned by one or more queues. Every buffer drawn from them holds a
+/// reference to them and goes back to them when released, even if it outlives the queues, so that the buffers they
+/// recycle stay warm for their later pops. Buffers may be sent to and released on other threads, so the reference
+/// count is atomic and the pools are locked.
+#[derive(Clone)]
+pub struct SharedSmallBufferPool(Arc<Mutex<Vec<SmallBufferPool>>>);
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl SmallBufferPool {
+    /// Creates an empty pool of allocations with the given layout.
+    pub fn new(layout: Layout) -> Self {
+        Self::new_on_node(layout, None)
+    }
+
+    /// Creates an empty pool of allocations with the given layout, whose memory is placed on `numa_node`, if any. The
+    /// caller checks that the machine has the node.
+    pub fn new_on_node(layout: Layout, numa_node: Option<u32>) -> Self {
+        Self {
+            layout,
+            free: Vec::with_capacity(MAX_FREE_ALLOCATIONS),
+            stats: BufferPoolStats::default(),
+            numa_node,
+            #[cfg(target_os = "linux")]
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Takes an allocation from the pool, or from the general allocator if the pool is empty. Returns a null pointer
+    /// if the general allocator fails. Pools placed on a NUMA node take new allocations from a new chunk instead.
+    pub fn allocate(&mut self) -> *mut u8 {
+        if let Some(allocation) = self.free.pop() {
+            self.stats.hits += 1;
+            return allocation.as_ptr();
+        }
+        self.stats.misses += 1;
+        let allocation: *mut u8 = match self.numa_node {
+            Some(numa_node) => self.allocate_chunk(numa_node),
+            // Safety: This is safe, as the layout has a non-zero size and the caller checks for a null return value.
+            None => unsafe { allocate_buffer(self.layout) },
+        };
+        if allocation.is_null() {
+            self.stats.allocation_failures += 1;
+        }
+        allocation
+    }
+
+    /// Maps a chunk of memory on `numa_node` and splits it into allocations. Returns the first one and keeps the
+    /// others for reuse, or returns a null pointer if the chunk cannot be mapped.
+    #[cfg(target_os = "linux")]
+    fn allocate_chunk(&mut self, numa_node: u32) -> *mut u8 {
+        let stride: usize = self.layout.pad_to_align().size();
+        let len: usize = cmp::max(NUMA_CHUNK_SIZE, stride);
+        let chunk: NonNull<u8> = match numa::map_on_node(len, numa_node) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                warn!("allocate_chunk(): {:?}", e);
+                return ptr::null_mut();
+            },
+        };
+        self.chunks.push((chunk, len));
+        for i in (1..len / stride).rev() {
+            // Safety: This is safe, as the offset is within the chunk, and the chunk is aligned to a page, so that
+            // every allocation has the alignment of the layout.
+            self.free
+                .push(unsafe { NonNull::new_unchecked(chunk.as_ptr().add(i * stride)) });
+        }
+        chunk.as_ptr()
+    }
+
+    /// Maps a chunk of memory on `numa_node`, which is not supported on this platform, so this returns a null pointer.
+    #[cfg(not(target_os = "linux"))]
+    fn allocate_chunk(&mut self, _numa_node: u32) -> *mut u8 {
+        ptr::null_mut()
+    }
+
+    /// Returns an allocation to the pool. The allocation must have the layout of the pool.
+    pub fn release(&mut self, allocation: NonNull<u8>) {
+        // Allocations from chunks cannot be released one by one, so they are all kept.
+        if self.numa_node.is_some() || self.free.len() < MAX_FREE_ALLOCATIONS {
+            self.free.push(allocation);
+        } else {
+            // Safety: This is safe, as the allocation was made with the layout of the pool.
+            unsafe { deallocate_buffer(allocation.as_ptr(), self.layout) };
+        }
+    }
+
+    /// Returns the hit and miss counters of the pool.
+    pub fn stats(&self) -> BufferPoolStats {
+        self.stats
+    }
+
+    /// Zeroes the hit and miss counters of the pool.
+    pub fn reset_stats(&mut self) {
+        self.stats = BufferPoolStats::default();
+    }
+
+    /// Returns the number of released allocations that the pool keeps for reuse.
+    pub fn free_allocations(&self) -> usize {
+        self.free.len()
+    }
+}
+
+impl SizeClassFragmentation {
+    /// Returns the number of bytes of buffer capacity that sit free in the size class.
+    pub fn free_bytes(&self) -> usize {
+        self.buffer_size as usize * self.free_buffers
+    }
+}
+
+impl FragmentationReport {
+    /// Returns the number of free buffers across all size classes.
+    pub fn free_buffers(&self) -> usize {
+        self.size_classes.iter().map(|size_class| size_class.free_buffers).sum()
+    }
+
+    /// Returns the number of bytes of buffer capacity that sit free across all size classes.
+    pub fn free_bytes(&self) -> usize {
+        self.size_classes.iter().map(|size_class| size_class.free_bytes()).sum()
+    }
+}
+
+impl SharedSmallBufferPool {
+    /// Creates empty pools of allocations, one for each of the given layouts, whose memory is placed on `numa_node`, if
+    /// any. The caller checks that the machine has the node.
+    pub(super) fn new(layouts: impl Iterator<Item = Layout>, numa_node: Option<u32>) -> Self {
+        let pools: Vec<SmallBufferPool> = layouts
+            .map(|layout| SmallBufferPool::new_on_node(layout, numa_node))
+            .collect();
+        Self(Arc::new(Mutex::new(pools)))
+    }
+
+    /// Takes an allocation from the pool of the given size class, or from the general allocator if that pool is empty.
+    /// Returns a null pointer if the general allocator fails.
+    pub(super) fn allocate(&self, size_class: usize) -> *mut u8 {
+        self.lock()[size_class].allocate()
+    }
+
+    /// Returns an allocation to the pool of the given size class. The allocation must have the layout of that pool.
+    pub(super) fn release(&self, size_class: usize, allocation: NonNull<u8>) {
+        self.lock()[size_class].release(allocation)
+    }
+
+    /// Returns the hit and miss counters of the pools, summed over their size classes.
+    pub fn stats(&self) -> BufferPoolStats {
+        let mut stats: BufferPoolStats = BufferPoolStats::default();
+        for pool in self.lock().iter() {
+            let pool_stats: BufferPoolStats = pool.stats();
+            stats.hits += pool_stats.hits;
+            stats.misses += pool_stats.misses;
+            stats.allocation_failures += pool_stats.allocation_failures;
+        }
+        stats
+    }
+
+    /// Zeroes the hit and miss counters of the pools.
+    pub fn reset_stats(&self) {
+        for pool in self.lock().iter_mut() {
+            pool.reset_stats();
+        }
+    }
+
+    /// Turns this reference to the pool into an address, so that it can be stored in the metadata of a buffer.
+    pub(super) fn into_raw(self) -> u64 {
+        Arc::into_raw(self.0) as u64
+    }
+
+    /// Takes back a reference to a pool that was turned into an address with [SharedSmallBufferPool::into_raw].
+    ///
+    /// # Safety
+    ///
+    /// The address must have been returned by [SharedSmallBufferPool::into_raw], and must not have been taken back
+    /// already.
+    pub(super) unsafe fn from_raw(address: u64) -> Self {
+        Self(Arc::from_raw(address as *const Mutex<Vec<SmallBufferPool>>))
+    }
+
+    /// Locks the pools. A thread that panicked while holding the lock cannot have left the pools inconsistent, as none
+    /// of their operations panic half-way, so a poisoned lock is taken over.
+    fn lock(&self) -> MutexGuard<Vec<SmallBufferPool>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+// Safety: The allocations that the pool holds are plain memory that no one else references, so the pool may move to
+// another thread.
+unsafe impl Send for SmallBufferPool {}
+
+impl Drop for SmallBufferPool {
+    fn drop(&mut self) {
+        // Allocations from chunks go back to the system with their chunks. Every buffer drawn from the pool holds a
+        // reference to it, so none is in use anymore.
+        if self.numa_node.is_some() {
+            self.free.clear();
+            #[cfg(target_os = "linux")]
+            for (chunk, len) in self.chunks.drain(..) {
+                // Safety: This is safe, as the chunk was mapped by allocate_chunk(), and nothing references it.
+                unsafe { numa::unmap(chunk, len) };
+            }
+            return;
+        }
+        for allocation in self.free.drain(..) {
+            // Safety: This is safe, as every allocation in the pool was made with the layout of the pool.
+            unsafe { deallocate_buffer(allocation.as_ptr(), self.layout) };
+        }
+    }
+}
@@ -5,6 +5,8 @@
 // Exports
 //======================================================================================================================
 
+pub mod cancellation;
+pub mod completion;
 pub mod fail;
 pub mod limits;
 pub mod logging;
@@ -14,6 +16,7 @@ pub mod queue;
 pub mod scheduler;
 pub mod timer;
 pub mod types;
+pub mod wait;
 pub mod watched;
 pub use queue::{
     BackgroundTask,
@@ -35,6 +38,7 @@ pub use dpdk_rs as libdpdk;
 use crate::{
     pal::data_structures::SockAddr,
     runtime::{
+        completion::CompletionRing,
         fail::Fail,
         memory::MemoryRuntime,
         network::{
@@ -47,17 +51,27 @@ use crate::{
             IoQueueTable,
         },
         scheduler::{
+            Priority,
             Scheduler,
+            SlabStats,
             Task,
+            TaskId,
         },
         timer::SharedTimer,
         types::demi_opcode_t,
+        wait::{
+            WaitInterrupter,
+            WaitOutcome,
+        },
     },
 };
 use ::futures::future::FusedFuture;
 use ::std::{
     boxed::Box,
-    collections::HashMap,
+    collections::{
+        HashMap,
+        HashSet,
+    },
     convert::{
         AsMut,
         AsRef,
@@ -70,7 +84,10 @@ use ::std::{
     },
     pin::Pin,
     rc::Rc,
-    time::Instant,
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 #[cfg(target_os = "windows")]
@@ -116,7 +133,22 @@ pub struct DemiRuntime {
     network_table: NetworkQueueTable,
     /// Currently running coroutines.
     pending_ops: HashMap<QDesc, HashMap<QToken, YielderHandle>>,
+    /// Scheduler priority of the coroutines of each queue, for queues that do not run at normal priority.
+    queue_priorities: HashMap<QDesc, Priority>,
+    /// Opaque values that the application attached to its queues.
+    user_data: HashMap<QDesc, u64>,
     ts_iters: usize,
+    /// Ring where completed operations are written during poll, if the application opted into harvesting them.
+    completion_ring: Option<CompletionRing>,
+    /// Completed operations waiting for room in the completion ring, in the order in which they completed.
+    completed_qts: Vec<QToken>,
+    /// Scratch space for the tasks that completed since the last poll, if the completion ring is enabled.
+    completed_tasks: Vec<TaskId>,
+    /// Batch accepts, which can only be waited for with wait_accept_batch(), as their results do not fit in a
+    /// `demi_qresult_t`.
+    accept_batches: HashSet<QToken>,
+    /// Interrupts ongoing waits.
+    interrupter: WaitInterrupter,
 }
 
 #[derive(Clone)]
@@ -151,15 +183,27 @@ impl SharedDemiRuntime {
             timer: SharedTimer::new(now),
             network_table: NetworkQueueTable::default(),
             pending_ops: HashMap::<QDesc, HashMap<QToken, YielderHandle>>::new(),
+            queue_priorities: HashMap::<QDesc, Priority>::new(),
+            user_data: HashMap::<QDesc, u64>::new(),
             ts_iters: 0,
+            completion_ring: None,
+            completed_qts: Vec::new(),
+            completed_tasks: Vec::new(),
+            accept_batches: HashSet::new(),
+            interrupter: WaitInterrupter::default(),
         }))
     }
 
-    /// Inserts the `coroutine` named `task_name` into the scheduler.
-    fn insert_coroutine(&mut self, task_name: &str, coroutine: Pin<Box<Operation>>) -> Result<QToken, Fail> {
+    /// Inserts the `coroutine` named `task_name` into the scheduler, to run at `priority`.
+    fn insert_coroutine(
+        &mut self,
+        task_name: &str,
+        coroutine: Pin<Box<Operation>>,
+        priority: Priority,
+    ) -> Result<QToken, Fail> {
         trace!("Inserting coroutine: {:?}", task_name);
         let task: OperationTask = OperationTask::new(task_name.to_string(), coroutine);
-        match self.scheduler.insert_task(task) {
+        match self.scheduler.insert_task_with_priority(task, priority) {
             Some(task_id) => Ok(task_id.into()),
             None => {
                 let cause: String = format!("cannot schedule coroutine (task_name={:?})", &task_name);
@@ -183,7 +227,8 @@ impl SharedDemiRuntime {
         let yielder: Yielder = Yielder::new();
         let yielder_handle: YielderHandle = yielder.get_handle();
         let coroutine: Pin<Box<dyn FusedFuture<Output = (QDesc, OperationResult)>>> = coroutine_factory(yielder);
-        match self.insert_coroutine(task_name, coroutine) {
+        let priority: Priority = self.queue_priorities.get(&qd).copied().unwrap_or_default();
+        match self.insert_coroutine(task_name, coroutine, priority) {
             Ok(qt) => {
                 // This allows to keep track of currently running coroutines.
                 self.pending_ops
@@ -196,6 +241,31 @@ impl SharedDemiRuntime {
         }
     }
 
+    /// Sets the scheduler priority of all coroutines that are inserted with tracking for the queue [qd] from now on.
+    pub fn set_queue_priority(&mut self, qd: QDesc, priority: Priority) {
+        match priority {
+            Priority::Normal => self.queue_priorities.remove(&qd),
+            Priority::High => self.queue_priorities.insert(qd, priority),
+        };
+    }
+
+    /// Checks whether any coroutine inserted with tracking has not been removed from the scheduler yet.
+    pub fn has_pending_ops(&self) -> bool {
+        self.pending_ops.values().any(|ops| !ops.is_empty())
+    }
+
+    /// Limits the number of coroutines that the runtime holds at once to `max_tasks`. Operations and connections that
+    /// need a coroutine while the runtime is at its limit fail with `EAGAIN`, and coroutines already running are not
+    /// affected. The runtime has no limit by default.
+    pub fn set_max_tasks(&mut self, max_tasks: usize) {
+        self.scheduler.set_group_quota(TaskId::from(0), max_tasks);
+    }
+
+    /// Returns the occupancy of the scheduler slabs.
+    pub fn slab_stats(&self) -> SlabStats {
+        self.scheduler.slab_stats()
+    }
+
     /// Removes a coroutine from the underlying scheduler given its associated QToken.
     pub fn remove_coroutine(&mut self, qt: QToken) -> (QDesc, OperationResult) {
         // 1. Remove Task from scheduler.
@@ -208,11 +278,24 @@ impl SharedDemiRuntime {
         let operation_task: OperationTask = OperationTask::from(boxed_task.as_any());
         let (qd, result): (QDesc, OperationResult) = operation_task.get_result().expect("coroutine not finished");
         self.cancel_or_remove_pending_ops_as_needed(&result, qd, qt);
+        self.accept_batches.remove(&qt);
         (qd, result)
     }
 
+    /// Removes the coroutine associated with `qt` from the scheduler before it completes, so that its operation does not
+    /// take place if the coroutine has not run yet.
+    pub fn abort_coroutine(&mut self, qt: QToken) {
+        if self.scheduler.remove_task(qt.into()).is_some() {
+            trace!("Aborting coroutine: {:?}", qt);
+            if let Some(qd) = self.get_pending_op_qd(qt) {
+                self.cancel_pending_op(&qd, &qt);
+            }
+        }
+    }
+
     /// Removes a coroutine from the underlying scheduler given its associated QToken and gets the result immediately.
     pub fn remove_coroutine_and_get_result(&mut self, qt: QToken) -> Result<demi_qresult_t, Fail> {
+        self.ensure_no_accept_batch(&[qt])?;
         let (qd, result): (QDesc, OperationResult) = self.remove_coroutine(qt);
         let result: demi_qresult_t = self.create_result(result, qd, qt);
         Ok(result)
@@ -231,6 +314,14 @@ impl SharedDemiRuntime {
         }
     }
 
+    /// Returns the queue of the pending operation associated with `qt`, if it was inserted with tracking.
+    fn get_pending_op_qd(&self, qt: QToken) -> Option<QDesc> {
+        self.pending_ops
+            .iter()
+            .find(|(_, ops)| ops.contains_key(&qt))
+            .map(|(&qd, _)| qd)
+    }
+
     /// Cancel pending op because the coroutine was removed.
     fn cancel_pending_op(&mut self, qd: &QDesc, qt: &QToken) {
         if let Some(inner_hash_map) = self.pending_ops.get_mut(&qd) {
@@ -299,6 +390,176 @@ impl SharedDemiRuntime {
         self.poll()
     }
 
+    /// Waits for any of the given pending operations to complete, a timeout to expire, or an interrupt. Expired
+    /// timeouts fail with `ETIMEDOUT` and interrupts fail with `EINTR`.
+    pub fn wait_any(&mut self, qts: &[QToken], timeout: Option<Duration>) -> Result<(usize, demi_qresult_t), Fail> {
+        self.ensure_no_accept_batch(qts)?;
+        let i: usize = self.wait_for_completion(qts, timeout)?;
+        Ok((i, self.remove_coroutine_and_get_result(qts[i])?))
+    }
+
+    /// Waits for any of the given pending operations to complete or its own timeout to expire, and returns the offset
+    /// in `qts` of the operation that did so first. An operation whose timeout expired is reported with a failed result
+    /// carrying `ETIMEDOUT`, and keeps running, so that it can be waited for again. A zero timeout gives the operation a
+    /// single poll, like in [Self::wait_any]. Interrupts fail with `EINTR`, and waits on no operation at all fail with
+    /// `EINVAL`.
+    pub fn wait_any_timeout(&mut self, qts: &[QToken], timeouts: &[Duration]) -> Result<(usize, demi_qresult_t), Fail> {
+        if qts.is_empty() {
+            let cause: &str = "no operation to wait for";
+            error!("wait_any_timeout(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, cause));
+        }
+        if qts.len() != timeouts.len() {
+            let cause: String = format!(
+                "every operation needs a timeout (qts={:?}, timeouts={:?})",
+                qts.len(),
+                timeouts.len()
+            );
+            error!("wait_any_timeout(): {}", cause);
+            return Err(Fail::new(libc::EINVAL, &cause));
+        }
+        self.ensure_no_accept_batch(qts)?;
+        let start: Instant = Instant::now();
+
+        loop {
+            // Poll first, so as to give pending operations a chance to complete.
+            self.poll_and_advance_clock();
+
+            for (i, &qt) in qts.iter().enumerate() {
+                if self.has_completed(qt)? {
+                    return Ok((i, self.remove_coroutine_and_get_result(qt)?));
+                }
+            }
+
+            if self.interrupter.take() {
+                return Err(Fail::new(libc::EINTR, "wait was interrupted"));
+            }
+
+            // Of the operations whose timeouts expired, report the one with the earliest deadline. Operations that are no
+            // longer pending were cancelled by a close, so they complete on the next poll instead.
+            let elapsed: Duration = start.elapsed();
+            let expired: Option<(usize, QDesc)> = (0..qts.len())
+                .filter(|&i| timeouts[i] == Duration::from_secs(0) || elapsed > timeouts[i])
+                .filter_map(|i| self.get_pending_op_qd(qts[i]).map(|qd| (i, qd)))
+                .min_by_key(|&(i, _)| timeouts[i]);
+            if let Some((i, qd)) = expired {
+                let e: Fail = Fail::new(libc::ETIMEDOUT, "timer expired");
+                return Ok((i, self.create_result(OperationResult::Failed(e), qd, qts[i])));
+            }
+        }
+    }
+
+    /// Waits for a pending operation like [Self::wait_any], but first spins on the scheduler for up to `spin`, so that
+    /// operations that complete almost immediately are returned without going through the clock. `timeout` counts from
+    /// the start of the spin.
+    pub fn wait_spin(&mut self, qt: QToken, spin: Duration, timeout: Option<Duration>) -> Result<demi_qresult_t, Fail> {
+        self.ensure_no_accept_batch(&[qt])?;
+        let start: Instant = Instant::now();
+        let spin: Duration = timeout.map_or(spin, |timeout| spin.min(timeout));
+
+        loop {
+            self.poll();
+            if self.has_completed(qt)? {
+                return self.remove_coroutine_and_get_result(qt);
+            }
+            if start.elapsed() >= spin {
+                break;
+            }
+        }
+
+        let timeout: Option<Duration> = timeout.map(|timeout| timeout.saturating_sub(start.elapsed()));
+        Ok(self.wait_any(&[qt], timeout)?.1)
+    }
+
+    /// Marks `qt` as a batch accept, so that it is only handed out by [Self::wait_accept_batch]. Other waits fail on it
+    /// with `EINVAL`, and the completion ring leaves it alone.
+    pub fn track_accept_batch(&mut self, qt: QToken) {
+        self.accept_batches.insert(qt);
+    }
+
+    /// Fails with `EINVAL` if any of `qts` is a batch accept, whose connections would not fit in a `demi_qresult_t`.
+    fn ensure_no_accept_batch(&self, qts: &[QToken]) -> Result<(), Fail> {
+        match qts.iter().find(|qt| self.accept_batches.contains(qt)) {
+            Some(qt) => {
+                let cause: String = format!(
+                    "batch accepts must be waited for with wait_accept_batch() (qt={:?})",
+                    qt
+                );
+                error!("ensure_no_accept_batch(): {}", cause);
+                Err(Fail::new(libc::EINVAL, &cause))
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Waits for an accept started with `accept_batch()` like [Self::wait_any], and returns the descriptors and remote
+    /// addresses of all the connections that it accepted.
+    pub fn wait_accept_batch(
+        &mut self,
+        qt: QToken,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(QDesc, SocketAddrV4)>, Fail> {
+        self.wait_for_completion(&[qt], timeout)?;
+        match self.remove_coroutine(qt) {
+            (_, OperationResult::AcceptBatch(accepted)) => Ok(accepted),
+            (_, OperationResult::Failed(e)) => Err(e),
+            (qd, result) => {
+                let cause: String = format!("not a batch accept (qd={:?}, result={:?})", qd, result);
+                error!("wait_accept_batch(): {}", cause);
+                Err(Fail::new(libc::EINVAL, &cause))
+            },
+        }
+    }
+
+    /// Polls until any of the given pending operations completes, and returns its offset in `qts`. Fails like
+    /// [Self::wait_any] if a timeout expires or the wait is interrupted first.
+    fn wait_for_completion(&mut self, qts: &[QToken], timeout: Option<Duration>) -> Result<usize, Fail> {
+        // Get the wait start time, but only if we have a timeout.  We don't care when we started if we wait forever.
+        let start: Option<Instant> = timeout.filter(|&t| t != Duration::from_secs(0)).map(|_| Instant::now());
+
+        loop {
+            // Poll first, so as to give pending operations a chance to complete.
+            self.poll_and_advance_clock();
+
+            // Search for any operation that has completed.
+            for (i, &qt) in qts.iter().enumerate() {
+                if self.has_completed(qt)? {
+                    return Ok(i);
+                }
+            }
+
+            // Completed operations take precedence over interrupts, so that no result is held back.
+            if self.interrupter.take() {
+                return Err(Fail::new(libc::EINTR, "wait was interrupted"));
+            }
+
+            // If we have a timeout, check for expiration.
+            // For performance reasons we check for immediate expiration first.
+            if let Some(timeout) = timeout {
+                if timeout == Duration::from_secs(0)
+                    || Instant::now().duration_since(start.expect("start should be set if timeout is")) > timeout
+                {
+                    return Err(Fail::new(libc::ETIMEDOUT, "timer expired"));
+                }
+            }
+        }
+    }
+
+    /// Waits for a pending operation like [Self::wait_any] and reports why the wait returned.
+    pub fn wait_ex(&mut self, qt: QToken, timeout: Option<Duration>) -> Result<WaitOutcome, Fail> {
+        WaitOutcome::from_wait(self.wait_any(&[qt], timeout).map(|(_, qr)| qr))
+    }
+
+    /// Returns a handle that interrupts waits on this runtime.
+    pub fn get_interrupter(&self) -> WaitInterrupter {
+        self.interrupter.clone()
+    }
+
+    /// Installs a hook that the scheduler runs once at the start of every poll sweep.
+    pub fn set_per_sweep_hook(&mut self, hook: Box<dyn FnMut()>) {
+        self.scheduler.set_per_sweep_hook(hook)
+    }
+
     /// Performs a single pool on the underlying scheduler.
     pub fn poll(&mut self) {
         // Grab the number of polled tasks because this might be useful in the future.
@@ -307,6 +568,70 @@ impl SharedDemiRuntime {
         // FIXME: https://github.com/microsoft/demikernel/issues/1128
         #[allow(unused)]
         let num_ready: usize = self.scheduler.poll_all();
+
+        if self.completion_ring.is_some() {
+            self.fill_completion_ring();
+        }
+    }
+
+    /// Makes poll write completed operations to a ring of `capacity` entries, from which they are retrieved with
+    /// [Self::harvest]. Once enabled, completed operations are no longer available through wait or get_result.
+    pub fn enable_completion_ring(&mut self, capacity: usize) {
+        self.completion_ring = Some(CompletionRing::new(capacity));
+        self.completed_qts = Vec::with_capacity(capacity);
+        self.scheduler.track_completions();
+    }
+
+    /// Copies up to `out.len()` completed operations into `out` and returns how many were copied. This neither polls
+    /// nor allocates.
+    pub fn harvest(&mut self, out: &mut [demi_qresult_t]) -> usize {
+        match self.completion_ring.as_mut() {
+            Some(ring) => ring.harvest(out),
+            None => 0,
+        }
+    }
+
+    /// Returns the number of completed operations waiting to be harvested.
+    pub fn completion_ring_len(&self) -> usize {
+        match self.completion_ring.as_ref() {
+            Some(ring) => ring.len(),
+            None => 0,
+        }
+    }
+
+    /// Moves completed operations into the completion ring, as long as it has free space. Operations that do not fit
+    /// wait for the next poll, in the order in which they completed.
+    fn fill_completion_ring(&mut self) {
+        let mut completed_tasks: Vec<TaskId> = mem::take(&mut self.completed_tasks);
+        self.scheduler.take_completed_tasks(&mut completed_tasks);
+        let mut completed_qts: Vec<QToken> = mem::take(&mut self.completed_qts);
+        for task_id in completed_tasks.drain(..) {
+            let qt: QToken = task_id.into();
+            // Only operations inserted with tracking complete into the ring, and accept batches are waited for instead.
+            if self.get_pending_op_qd(qt).is_some() && !self.accept_batches.contains(&qt) {
+                completed_qts.push(qt);
+            }
+        }
+        self.completed_tasks = completed_tasks;
+
+        let mut free_space: usize = self.completion_ring.as_ref().map_or(0, |ring| ring.free_space());
+        completed_qts.retain(|qt| {
+            if free_space == 0 {
+                return true;
+            }
+            // The operation may have been cancelled while it waited for room.
+            if self.get_pending_op_qd(*qt).is_none() {
+                return false;
+            }
+            let (qd, result): (QDesc, OperationResult) = self.remove_coroutine(*qt);
+            let qr: demi_qresult_t = self.create_result(result, qd, *qt);
+            if let Some(ring) = self.completion_ring.as_mut() {
+                ring.push(qr);
+            }
+            free_space -= 1;
+            false
+        });
+        self.completed_qts = completed_qts;
     }
 
     /// Allocates a queue of type `T` and returns the associated queue descriptor.
@@ -330,9 +655,25 @@ impl SharedDemiRuntime {
     pub fn free_queue<T: IoQueue>(&mut self, qd: &QDesc) -> Result<T, Fail> {
         trace!("Freeing queue: qd={:?}", qd);
         self.cancel_all_pending_ops_for_queue(qd);
+        self.queue_priorities.remove(qd);
+        self.user_data.remove(qd);
         self.qtable.free(qd)
     }
 
+    /// Attaches the opaque value [user_data] to the queue [qd], replacing any value attached before. The value is
+    /// dropped when the queue is freed.
+    pub fn set_user_data(&mut self, qd: QDesc, user_data: u64) -> Result<(), Fail> {
+        // Only allow values on queues that exist.
+        self.qtable.get_type(&qd)?;
+        self.user_data.insert(qd, user_data);
+        Ok(())
+    }
+
+    /// Returns the opaque value attached to the queue [qd], if any.
+    pub fn get_user_data(&self, qd: QDesc) -> Option<u64> {
+        self.user_data.get(&qd).copied()
+    }
+
     /// Gets a reference to a shared queue. It is very important that this function bump the reference count (using
     /// clone) so that we can track how many references to this shared queue that we have handed out.
     /// TODO: This should only return SharedObject types but for now we will also allow other cloneable queue types.
@@ -474,6 +815,25 @@ impl SharedDemiRuntime {
                     qr_value,
                 }
             },
+            // The result only has room for a single connection, so the others are reported through their count. The
+            // runtime never hands batches out this way, only callers that remove the coroutine themselves.
+            OperationResult::AcceptBatch(accepted) => {
+                let (new_qd, addr): (QDesc, SocketAddrV4) = accepted[0];
+                let saddr: SockAddr = socketaddrv4_to_sockaddr(&addr);
+                let qr_value: demi_qr_value_t = demi_qr_value_t {
+                    ares: demi_accept_result_t {
+                        qd: new_qd.into(),
+                        addr: saddr,
+                    },
+                };
+                demi_qresult_t {
+                    qr_opcode: demi_opcode_t::DEMI_OPC_ACCEPT,
+                    qr_qd: qd.into(),
+                    qr_qt: qt.into(),
+                    qr_ret: accepted.len() as i64,
+                    qr_value,
+                }
+            },
             OperationResult::Push => demi_qresult_t {
                 qr_opcode: demi_opcode_t::DEMI_OPC_PUSH,
                 qr_qd: qd.into(),
@@ -542,6 +902,11 @@ impl<T> SharedObject<T> {
     pub fn new(object: T) -> Self {
         Self(Rc::new(object))
     }
+
+    /// Checks whether two shared objects refer to the same object.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(&this.0, &other.0)
+    }
 }
 
 impl<T: ?Sized> SharedBox<T> {
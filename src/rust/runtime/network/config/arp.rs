@@ -21,6 +21,8 @@ use ::std::{
 pub struct ArpConfig {
     /// Time to Live for ARP Cache
     cache_ttl: Duration,
+    /// Time to Live for ARP Cache entries of addresses that failed to resolve. None disables negative caching.
+    negative_cache_ttl: Option<Duration>,
     /// Timeout for ARP Requests
     request_timeout: Duration,
     /// Retry Count for ARP Requests
@@ -40,6 +42,7 @@ impl ArpConfig {
     /// Creates an ARP Configuration Descriptor.
     pub fn new(
         cache_ttl: Option<Duration>,
+        negative_cache_ttl: Option<Duration>,
         request_timeout: Option<Duration>,
         retry_count: Option<usize>,
         initial_values: Option<HashMap<Ipv4Addr, MacAddress>>,
@@ -50,6 +53,9 @@ impl ArpConfig {
         if let Some(cache_ttl) = cache_ttl {
             config.set_cache_ttl(cache_ttl);
         }
+        if let Some(negative_cache_ttl) = negative_cache_ttl {
+            config.set_negative_cache_ttl(negative_cache_ttl);
+        }
         if let Some(request_timeout) = request_timeout {
             config.set_request_timeout(request_timeout);
         }
@@ -71,6 +77,12 @@ impl ArpConfig {
         self.cache_ttl
     }
 
+    /// Gets the time to live for entries of the ARP Cache that record failed resolutions in the target [ArpConfig], if
+    /// failed resolutions are cached at all.
+    pub fn get_negative_cache_ttl(&self) -> Option<Duration> {
+        self.negative_cache_ttl
+    }
+
     /// Gets the request timeout for ARP requests in the target [ArpConfig].
     pub fn get_request_timeout(&self) -> Duration {
         self.request_timeout
@@ -96,6 +108,12 @@ impl ArpConfig {
         self.cache_ttl = cache_ttl
     }
 
+    /// Sets the time to live for entries of the ARP Cache that record failed resolutions in the target [ArpConfig]. A
+    /// zero time to live disables negative caching.
+    fn set_negative_cache_ttl(&mut self, negative_cache_ttl: Duration) {
+        self.negative_cache_ttl = Some(negative_cache_ttl).filter(|ttl| !ttl.is_zero())
+    }
+
     /// Sets the request timeout for ARP requests in the target [ArpConfig].
     fn set_request_timeout(&mut self, request_timeout: Duration) {
         self.request_timeout = request_timeout
@@ -127,6 +145,7 @@ impl Default for ArpConfig {
     fn default() -> Self {
         ArpConfig {
             cache_ttl: Duration::from_secs(15),
+            negative_cache_ttl: Some(Duration::from_secs(5)),
             request_timeout: Duration::from_secs(20),
             retry_count: 5,
             initial_values: HashMap::new(),
@@ -153,6 +172,7 @@ mod tests {
     fn test_arp_config_default() -> Result<()> {
         let config: ArpConfig = ArpConfig::default();
         crate::ensure_eq!(config.get_cache_ttl(), Duration::from_secs(15));
+        crate::ensure_eq!(config.get_negative_cache_ttl(), Some(Duration::from_secs(5)));
         crate::ensure_eq!(config.get_request_timeout(), Duration::from_secs(20));
         crate::ensure_eq!(config.get_retry_count(), 5);
         crate::ensure_eq!(config.get_initial_values(), &HashMap::new());
@@ -160,4 +180,13 @@ mod tests {
 
         Ok(())
     }
+
+    /// Tests that a zero time to live disables negative caching in [ArpConfig].
+    #[test]
+    fn test_arp_config_zero_negative_cache_ttl() -> Result<()> {
+        let config: ArpConfig = ArpConfig::new(None, Some(Duration::ZERO), None, None, None, None);
+        crate::ensure_eq!(config.get_negative_cache_ttl(), None);
+
+        Ok(())
+    }
 }
@@ -6,7 +6,10 @@
 //==============================================================================
 
 use crate::runtime::network::consts::{
+    DEFAULT_DELAYED_ACK_TIMEOUT,
     DEFAULT_MSS,
+    DEFAULT_RATE_WINDOW,
+    MAX_DELAYED_ACK_TIMEOUT,
     MAX_MSS,
     MIN_MSS,
 };
@@ -35,6 +38,22 @@ pub struct TcpConfig {
     rx_checksum_offload: bool,
     /// Offload Checksum to Hardware When Sending?
     tx_checksum_offload: bool,
+    /// Record the TCP Options Received on Each Connection?
+    record_options: bool,
+    /// Merge Adjacent In-Order Segments Before Delivering Them?
+    receive_coalescing: bool,
+    /// Accept Data in the SYN of Connections That Present a Valid TCP Fast Open Cookie?
+    fast_open: bool,
+    /// Window Over Which Send and Receive Rates Are Computed
+    rate_window: Duration,
+    /// Maximum Number of Half-Open Connections per Listening Socket
+    max_half_open: Option<usize>,
+    /// Run the CUBIC Congestion Control Algorithm on Each Connection?
+    congestion_control: bool,
+    /// Pace Outgoing Segments at the Measured Delivery Rate?
+    pacing: bool,
+    /// Reset the Sender of Segments That No Connection Can Take?
+    rst_on_invalid_segment: bool,
 }
 
 //==============================================================================
@@ -124,6 +143,46 @@ impl TcpConfig {
         self.rx_checksum_offload
     }
 
+    /// Gets whether connections record the TCP options they receive in the target [TcpConfig].
+    pub fn get_record_options(&self) -> bool {
+        self.record_options
+    }
+
+    /// Gets whether connections merge adjacent in-order segments before delivering them in the target [TcpConfig].
+    pub fn get_receive_coalescing(&self) -> bool {
+        self.receive_coalescing
+    }
+
+    /// Gets whether listening sockets hand out TCP Fast Open cookies and accept data in SYNs in the target [TcpConfig].
+    pub fn get_fast_open(&self) -> bool {
+        self.fast_open
+    }
+
+    /// Gets the window over which connections compute their send and receive rates in the target [TcpConfig].
+    pub fn get_rate_window(&self) -> Duration {
+        self.rate_window
+    }
+
+    /// Gets the maximum number of half-open connections per listening socket in the target [TcpConfig], if any.
+    pub fn get_max_half_open(&self) -> Option<usize> {
+        self.max_half_open
+    }
+
+    /// Gets whether connections run the CUBIC congestion control algorithm in the target [TcpConfig].
+    pub fn get_congestion_control(&self) -> bool {
+        self.congestion_control
+    }
+
+    /// Gets whether connections pace their segments at their measured delivery rate in the target [TcpConfig].
+    pub fn get_pacing(&self) -> bool {
+        self.pacing
+    }
+
+    /// Gets whether segments that no connection can take are answered with a RST in the target [TcpConfig].
+    pub fn get_rst_on_invalid_segment(&self) -> bool {
+        self.rst_on_invalid_segment
+    }
+
     /// Sets the advertised maximum segment size in the target [TcpConfig].
     fn set_advertised_mss(mut self, value: usize) -> Self {
         assert!(value >= MIN_MSS);
@@ -160,11 +219,60 @@ impl TcpConfig {
     }
 
     /// Sets the acknowledgement delay timeout in the target [TcpConfig].
-    fn set_ack_delay_timeout(mut self, value: Duration) -> Self {
-        assert!(value <= Duration::from_millis(500));
+    pub fn set_ack_delay_timeout(mut self, value: Duration) -> Self {
+        assert!(value <= MAX_DELAYED_ACK_TIMEOUT);
         self.ack_delay_timeout = value;
         self
     }
+
+    /// Sets whether connections record the TCP options they receive in the target [TcpConfig].
+    pub fn set_record_options(mut self, value: bool) -> Self {
+        self.record_options = value;
+        self
+    }
+
+    /// Sets whether connections merge adjacent in-order segments before delivering them in the target [TcpConfig].
+    pub fn set_receive_coalescing(mut self, value: bool) -> Self {
+        self.receive_coalescing = value;
+        self
+    }
+
+    /// Sets whether listening sockets hand out TCP Fast Open cookies and accept data in SYNs in the target [TcpConfig].
+    pub fn set_fast_open(mut self, value: bool) -> Self {
+        self.fast_open = value;
+        self
+    }
+
+    /// Sets the window over which connections compute their send and receive rates in the target [TcpConfig].
+    pub fn set_rate_window(mut self, value: Duration) -> Self {
+        assert!(value > Duration::ZERO);
+        self.rate_window = value;
+        self
+    }
+
+    /// Sets the maximum number of half-open connections per listening socket in the target [TcpConfig].
+    pub fn set_max_half_open(mut self, value: Option<usize>) -> Self {
+        self.max_half_open = value;
+        self
+    }
+
+    /// Sets whether connections run the CUBIC congestion control algorithm in the target [TcpConfig].
+    pub fn set_congestion_control(mut self, value: bool) -> Self {
+        self.congestion_control = value;
+        self
+    }
+
+    /// Sets whether connections pace their segments at their measured delivery rate in the target [TcpConfig].
+    pub fn set_pacing(mut self, value: bool) -> Self {
+        self.pacing = value;
+        self
+    }
+
+    /// Sets whether segments that no connection can take are answered with a RST in the target [TcpConfig].
+    pub fn set_rst_on_invalid_segment(mut self, value: bool) -> Self {
+        self.rst_on_invalid_segment = value;
+        self
+    }
 }
 
 //==============================================================================
@@ -180,10 +288,18 @@ impl Default for TcpConfig {
             handshake_retries: 5,
             handshake_timeout: Duration::from_secs(3),
             receive_window_size: 0xffff,
-            ack_delay_timeout: Duration::from_millis(5),
+            ack_delay_timeout: DEFAULT_DELAYED_ACK_TIMEOUT,
             window_scale: 0,
             rx_checksum_offload: false,
             tx_checksum_offload: false,
+            record_options: false,
+            receive_coalescing: false,
+            fast_open: false,
+            rate_window: DEFAULT_RATE_WINDOW,
+            max_half_open: None,
+            congestion_control: false,
+            pacing: false,
+            rst_on_invalid_segment: false,
         }
     }
 }
@@ -196,7 +312,11 @@ impl Default for TcpConfig {
 mod tests {
     use crate::runtime::network::{
         config::TcpConfig,
-        consts::DEFAULT_MSS,
+        consts::{
+            DEFAULT_DELAYED_ACK_TIMEOUT,
+            DEFAULT_MSS,
+            DEFAULT_RATE_WINDOW,
+        },
     };
     use ::anyhow::Result;
     use ::std::time::Duration;
@@ -210,8 +330,17 @@ mod tests {
         crate::ensure_eq!(config.get_handshake_timeout(), Duration::from_secs(3));
         crate::ensure_eq!(config.get_receive_window_size(), 0xffff);
         crate::ensure_eq!(config.get_window_scale(), 0);
+        crate::ensure_eq!(config.get_ack_delay_timeout(), DEFAULT_DELAYED_ACK_TIMEOUT);
         crate::ensure_eq!(config.get_rx_checksum_offload(), false);
         crate::ensure_eq!(config.get_tx_checksum_offload(), false);
+        crate::ensure_eq!(config.get_record_options(), false);
+        crate::ensure_eq!(config.get_receive_coalescing(), false);
+        crate::ensure_eq!(config.get_fast_open(), false);
+        crate::ensure_eq!(config.get_rate_window(), DEFAULT_RATE_WINDOW);
+        crate::ensure_eq!(config.get_max_half_open(), None);
+        crate::ensure_eq!(config.get_congestion_control(), false);
+        crate::ensure_eq!(config.get_pacing(), false);
+        crate::ensure_eq!(config.get_rst_on_invalid_segment(), false);
 
         Ok(())
     }
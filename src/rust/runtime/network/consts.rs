@@ -1,6 +1,12 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT license.
 
+//==============================================================================
+// Imports
+//==============================================================================
+
+use ::std::time::Duration;
+
 //==============================================================================
 // Constants
 //==============================================================================
@@ -19,6 +25,30 @@ pub const MAX_MSS: usize = u16::max_value() as usize;
 /// TODO: Auto-Discovery MTU Size
 pub const DEFAULT_MSS: usize = 1450;
 
+/// Default Maximum Transmission Unit of a Network Interface
+pub const DEFAULT_MTU: u16 = 1500;
+
+/// Default Idle Time Before the First TCP Keepalive Probe
+pub const DEFAULT_KEEPALIVE_IDLE: Duration = Duration::from_secs(7200);
+
+/// Default Interval Between TCP Keepalive Probes
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(75);
+
+/// Default Number of Unanswered TCP Keepalive Probes Before the Connection is Dropped
+pub const DEFAULT_KEEPALIVE_PROBES: usize = 9;
+
+/// Default Time a TCP Receiver Waits Before Sending a Standalone ACK
+pub const DEFAULT_DELAYED_ACK_TIMEOUT: Duration = Duration::from_millis(40);
+
+/// Maximum Time a TCP Receiver May Wait Before Sending a Standalone ACK
+pub const MAX_DELAYED_ACK_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Default Window Over Which TCP Send and Receive Rates Are Computed
+pub const DEFAULT_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Largest Differentiated Services Code Point (6 bits)
+pub const MAX_DSCP: u8 = 0x3f;
+
 /// Length of a [crate::memory::DemiBuffer] batch.
 ///
 /// TODO: This Should be Generic
@@ -28,7 +28,10 @@ use crate::runtime::{
             TcpConfig,
             UdpConfig,
         },
-        consts::RECEIVE_BATCH_SIZE,
+        consts::{
+            DEFAULT_MTU,
+            RECEIVE_BATCH_SIZE,
+        },
         socket::SocketId,
     },
     Fail,
@@ -137,4 +140,9 @@ pub trait NetworkRuntime: Clone + 'static + MemoryRuntime {
     fn get_tcp_config(&self) -> TcpConfig;
 
     fn get_arp_config(&self) -> ArpConfig;
+
+    /// Gets the MTU of the network interface. Runtimes that do not know it report the default Ethernet MTU.
+    fn get_mtu(&self) -> u16 {
+        DEFAULT_MTU
+    }
 }
@@ -6,6 +6,7 @@
 //======================================================================================================================
 
 pub mod operation;
+pub mod option;
 pub mod state;
 
 //======================================================================================================================
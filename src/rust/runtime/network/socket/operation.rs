@@ -11,6 +11,18 @@ pub enum SocketOp {
     Listen,
     Connect,
     Established,
+    Shutdown,
     Close,
     Closed,
 }
+
+/// Directions of a connection that a shutdown stops.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Shutdown {
+    /// Stop receiving. Data that is already queued may still be popped.
+    Read,
+    /// Stop sending, and signal the end of the stream to the peer.
+    Write,
+    /// Stop both receiving and sending.
+    Both,
+}
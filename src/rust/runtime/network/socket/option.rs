@@ -0,0 +1,178 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::{
+    pal::constants::{
+        IPPROTO_TCP,
+        SOL_SOCKET,
+        SO_RCVBUF,
+        SO_SNDBUF,
+        TCP_NODELAY,
+    },
+    runtime::{
+        fail::Fail,
+        memory::PoolId,
+        scheduler::Priority,
+    },
+};
+use ::std::{
+    mem,
+    time::Duration,
+};
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Options that can be set on a socket.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SocketOption {
+    /// Acknowledge every received TCP segment immediately instead of delaying the ACK.
+    QuickAck(bool),
+    /// Probe idle TCP connections with the stack's default keepalive idle time, interval, and probe count.
+    KeepAliveEnabled(bool),
+    /// Bound the number of unacknowledged and unsent bytes buffered by the TCP sender. Pushes that would exceed it fail
+    /// with EWOULDBLOCK. The send buffer is unbounded unless this is set.
+    SendBufferSize(usize),
+    /// Cap the TCP receive window advertised to the peer at this many bytes, regardless of how much buffer space is
+    /// available. Equivalent to Linux's `TCP_WINDOW_CLAMP`.
+    RecvWindowClamp(u32),
+    /// Mark outgoing IP packets with this Differentiated Services Code Point (the upper 6 bits of the TOS byte). The ECN
+    /// bits remain under the control of the stack.
+    Dscp(u8),
+    /// What to do with data that arrives on a TCP connection after its read side was shut down.
+    HalfClosedReadPolicy(HalfClosedReadPolicy),
+    /// Allow a UDP socket to send datagrams to the broadcast address and to receive datagrams sent to it. Equivalent to
+    /// `SO_BROADCAST`.
+    Broadcast(bool),
+    /// Bound the number of datagrams that a UDP socket holds for popping. When a datagram arrives at a full socket, the
+    /// oldest queued datagram is dropped to make room for it. Must be at least one.
+    MaxDatagramQueue(usize),
+    /// Run the coroutines of the socket's operations at this scheduler priority. In every sweep, high-priority
+    /// coroutines are polled before all others. Only operations issued after the option is set are affected.
+    Priority(Priority),
+    /// Accepted for compatibility with `SO_NOSIGPIPE`. Demikernel never raises signals: pushes on a connection that the
+    /// peer closed fail with EPIPE whether or not this is set.
+    NoSigPipe(bool),
+    /// Draw the buffers of the socket's pops from a small-buffer pool of its own rather than from the one shared by the
+    /// thread, so that the buffers it recycles stay warm for its later pops. Connections accepted on a listening socket
+    /// with this set get pools of their own too.
+    QueueBufferPool(bool),
+    /// Draw the buffers of the socket's pops, and of the scatter-gather arrays allocated for its pushes, from a pool
+    /// that the libOS created on request, e.g. one per NUMA node. The pool may be shared by many sockets, and takes
+    /// fresh buffers from the installed buffer allocator, or from memory on its NUMA node if it was placed on one.
+    /// Connections accepted on a listening socket with this set draw from the same pool. Setting
+    /// [SocketOption::QueueBufferPool] to `false` makes the socket go back to the pool of the thread.
+    BufferPool(PoolId),
+    /// Let several listening TCP sockets bind the same local address, if all of them set this before they bind.
+    /// Equivalent to `SO_REUSEPORT`. Incoming connections are spread among the sockets by a hash of their four-tuple.
+    ReusePort(bool),
+    /// Cap the TCP retransmission timeout, however many times it was backed off, so that a connection over a jittery
+    /// link recovers from a lost segment sooner. The timeout never goes below the minimum that the stack enforces.
+    MaxRto(Duration),
+    /// Close a TCP connection with `ETIMEDOUT` once data that it sent went unacknowledged for longer than this, however
+    /// few times it was retransmitted. Equivalent to `TCP_USER_TIMEOUT`.
+    UserTimeout(Duration),
+    /// Bound the number of bytes that a TCP connection holds for reassembly while a segment before them is missing.
+    /// Out-of-order segments past the bound are dropped, and the peer retransmits them once the hole is filled. Must be
+    /// positive.
+    MaxReassemblyBytes(usize),
+    /// Send data as soon as it is pushed, rather than hold small segments back to coalesce them with Nagle's algorithm.
+    /// Equivalent to `TCP_NODELAY`. TCP connections do not implement Nagle's algorithm, so only `true` is accepted, and
+    /// it reads back as `false` only while [SocketOption::Cork] holds segments back.
+    NoDelay(bool),
+    /// Hold back TCP segments that are not full, and coalesce small pushes into full segments, until the option is
+    /// cleared, which sends the held data right away. Shutting down the write side sends it too. Like `TCP_CORK`, but
+    /// without a ceiling on how long data is held.
+    Cork(bool),
+    /// Lift the cork of a corked TCP connection whenever a pop completes, so that a response pushed after the pop of its
+    /// request goes out right away. Lifting the cork sends the held data. The cork is put back by the next push once
+    /// the data pushed since it was lifted has been sent.
+    AutoFlushAfterPop(bool),
+}
+
+/// Options that can be read back from a socket.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SocketOptionName {
+    /// Read back as [SocketOption::NoDelay].
+    NoDelay,
+    /// Read back as [SocketOption::RecvWindowClamp], with the largest receive window that the socket may advertise.
+    RecvWindowClamp,
+    /// Read back as [SocketOption::SendBufferSize], with the effective send buffer size.
+    SendBufferSize,
+}
+
+/// Handling of data that arrives on a TCP connection after its read side was shut down.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum HalfClosedReadPolicy {
+    /// Acknowledge the data and drop it, so that the peer does not retransmit it.
+    #[default]
+    Discard,
+    /// Reset the connection, to signal the peer to stop sending.
+    Reset,
+}
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl SocketOption {
+    /// Decodes an option that is set with `setsockopt()`, from its `level`, its `name` and its `value`, which holds a C
+    /// `int`. `SO_RCVBUF` maps to [SocketOption::RecvWindowClamp] and `SO_SNDBUF` to [SocketOption::SendBufferSize].
+    pub fn from_raw(level: i32, name: i32, value: &[u8]) -> Result<Self, Fail> {
+        let name: SocketOptionName = SocketOptionName::from_raw(level, name)?;
+        let value: i32 = match value.get(..mem::size_of::<i32>()) {
+            Some(bytes) => i32::from_ne_bytes(bytes.try_into().expect("slice should hold an int")),
+            None => {
+                let cause: String = format!("socket option value is too short (len={:?})", value.len());
+                error!("from_raw(): {}", cause);
+                return Err(Fail::new(libc::EINVAL, &cause));
+            },
+        };
+        match name {
+            SocketOptionName::NoDelay => Ok(SocketOption::NoDelay(value != 0)),
+            SocketOptionName::RecvWindowClamp | SocketOptionName::SendBufferSize if value < 0 => {
+                let cause: String = format!("buffer size must not be negative (value={:?})", value);
+                error!("from_raw(): {}", cause);
+                Err(Fail::new(libc::EINVAL, &cause))
+            },
+            SocketOptionName::RecvWindowClamp => Ok(SocketOption::RecvWindowClamp(value as u32)),
+            SocketOptionName::SendBufferSize => Ok(SocketOption::SendBufferSize(value as usize)),
+        }
+    }
+
+    /// Encodes the value of this option like `getsockopt()` does, as a C `int`. Values that do not fit are saturated.
+    pub fn to_raw(&self) -> Result<Vec<u8>, Fail> {
+        let value: i32 = match *self {
+            SocketOption::NoDelay(no_delay) => no_delay as i32,
+            SocketOption::RecvWindowClamp(clamp) => clamp.min(i32::MAX as u32) as i32,
+            SocketOption::SendBufferSize(size) => size.min(i32::MAX as usize) as i32,
+            _ => {
+                let cause: String = format!("socket option has no raw encoding (option={:?})", self);
+                error!("to_raw(): {}", cause);
+                return Err(Fail::new(libc::ENOPROTOOPT, &cause));
+            },
+        };
+        Ok(value.to_ne_bytes().to_vec())
+    }
+}
+
+impl SocketOptionName {
+    /// Looks up an option by the `level` and `name` that `setsockopt()` and `getsockopt()` take.
+    pub fn from_raw(level: i32, name: i32) -> Result<Self, Fail> {
+        match (level, name) {
+            (IPPROTO_TCP, TCP_NODELAY) => Ok(SocketOptionName::NoDelay),
+            (SOL_SOCKET, SO_RCVBUF) => Ok(SocketOptionName::RecvWindowClamp),
+            (SOL_SOCKET, SO_SNDBUF) => Ok(SocketOptionName::SendBufferSize),
+            _ => {
+                let cause: String = format!("socket option not supported (level={:?}, name={:?})", level, name);
+                warn!("from_raw(): {}", cause);
+                Err(Fail::new(libc::ENOPROTOOPT, &cause))
+            },
+        }
+    }
+}
@@ -5,11 +5,30 @@
 // Imports
 //======================================================================================================================
 
+#[cfg(feature = "state-history")]
+use crate::runtime::timer::SharedTimer;
 use crate::runtime::{
     fail::Fail,
-    network::socket::operation::SocketOp,
+    network::socket::operation::{
+        Shutdown,
+        SocketOp,
+    },
 };
 use ::socket2::Type;
+#[cfg(feature = "state-history")]
+use ::std::{
+    collections::VecDeque,
+    fmt,
+    time::Instant,
+};
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of transitions that the history of a socket holds. Older transitions are forgotten.
+#[cfg(feature = "state-history")]
+pub const STATE_HISTORY_LENGTH: usize = 32;
 
 //======================================================================================================================
 // Structures
@@ -17,7 +36,7 @@ use ::socket2::Type;
 
 /// States of a Socket.
 #[derive(Copy, Clone, Debug, PartialEq)]
-enum SocketState {
+pub enum SocketState {
     /// A socket that is not bound.
     Unbound,
     /// A socket that is bound to a local address.
@@ -28,18 +47,52 @@ enum SocketState {
     ActiveConnecting,
     /// A socket that is connected to a remote address.
     ActiveEstablished,
+    /// A socket that is connected to a remote address and had one or both directions of the connection shut down.
+    HalfClosed,
     /// A socket that is closing.
     Closing,
     /// A socket that is closed.
     Closed,
 }
 
-/// Encodes the state of a socket.
+/// Transition of a socket from one state to another, as recorded in its history.
+#[cfg(feature = "state-history")]
 #[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SocketTransition {
+    /// Operation that caused the transition.
+    pub op: SocketOp,
+    /// State before the transition.
+    pub from: SocketState,
+    /// State after the transition.
+    pub to: SocketState,
+    /// When the transition was committed.
+    pub timestamp: Instant,
+}
+
+/// Ring buffer of the most recent transitions of a socket.
+#[cfg(feature = "state-history")]
+#[derive(Clone)]
+struct SocketStateHistory {
+    /// Clock for the timestamps of the transitions.
+    timer: SharedTimer,
+    /// Recorded transitions, oldest first.
+    transitions: VecDeque<SocketTransition>,
+}
+
+/// Encodes the state of a socket.
+#[derive(Clone, Debug)]
 pub struct SocketStateMachine {
     typ: Type,
     current: SocketState,
     next: Option<SocketState>,
+    /// Whether the sending direction of the connection was shut down.
+    write_shutdown: bool,
+    /// Operation that prepared the next state.
+    #[cfg(feature = "state-history")]
+    next_op: Option<SocketOp>,
+    /// Recent transitions of the socket, if they are being recorded.
+    #[cfg(feature = "state-history")]
+    history: Option<SocketStateHistory>,
 }
 
 //======================================================================================================================
@@ -55,6 +108,11 @@ impl SocketStateMachine {
             typ,
             current: SocketState::Unbound,
             next: None,
+            write_shutdown: false,
+            #[cfg(feature = "state-history")]
+            next_op: None,
+            #[cfg(feature = "state-history")]
+            history: None,
         }
     }
 
@@ -64,9 +122,38 @@ impl SocketStateMachine {
             typ: Type::STREAM,
             current: SocketState::ActiveEstablished,
             next: None,
+            write_shutdown: false,
+            #[cfg(feature = "state-history")]
+            next_op: None,
+            #[cfg(feature = "state-history")]
+            history: None,
         }
     }
 
+    /// Makes the target [SocketStateMachine] record its transitions, timestamped with `timer`.
+    #[cfg(feature = "state-history")]
+    pub fn with_history(mut self, timer: SharedTimer) -> Self {
+        self.history = Some(SocketStateHistory {
+            timer,
+            transitions: VecDeque::with_capacity(STATE_HISTORY_LENGTH),
+        });
+        self
+    }
+
+    /// Returns the recorded transitions of the target [SocketStateMachine], oldest first.
+    #[cfg(feature = "state-history")]
+    pub fn history(&self) -> Vec<SocketTransition> {
+        match self.history {
+            Some(ref history) => history.transitions.iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the committed state of the target [SocketStateMachine].
+    pub fn current(&self) -> SocketState {
+        self.current
+    }
+
     /// Asserts whether the target may continue accepting connections.
     pub fn may_accept(&self) -> Result<(), Fail> {
         self.ensure_not_closing()?;
@@ -86,6 +173,7 @@ impl SocketStateMachine {
     pub fn may_push(&self) -> Result<(), Fail> {
         self.ensure_not_closing()?;
         self.ensure_not_closed()?;
+        self.ensure_not_write_shutdown()?;
 
         if self.typ == Type::STREAM {
             self.ensure_established()?;
@@ -114,13 +202,37 @@ impl SocketStateMachine {
 
     /// Commits to moving into the prepared state
     pub fn commit(&mut self) {
+        #[cfg(feature = "state-history")]
+        if let (Some(next), Some(op), Some(history)) = (self.next, self.next_op.take(), self.history.as_mut()) {
+            if history.transitions.len() == STATE_HISTORY_LENGTH {
+                history.transitions.pop_front();
+            }
+            history.transitions.push_back(SocketTransition {
+                op,
+                from: self.current,
+                to: next,
+                timestamp: history.timer.now(),
+            });
+        }
         self.current = self.next.unwrap_or(self.current);
         self.next = None;
     }
 
+    /// Commits to moving into the half-closed state prepared by a shutdown of the `how` directions.
+    pub fn commit_shutdown(&mut self, how: Shutdown) {
+        if how != Shutdown::Read {
+            self.write_shutdown = true;
+        }
+        self.commit();
+    }
+
     /// Rolls back the prepared state.
     pub fn abort(&mut self) {
         self.next = None;
+        #[cfg(feature = "state-history")]
+        {
+            self.next_op = None;
+        }
     }
 
     /// Prepares to move into the next state.
@@ -135,6 +247,10 @@ impl SocketStateMachine {
         }
 
         self.next = Some(next);
+        #[cfg(feature = "state-history")]
+        {
+            self.next_op = Some(op);
+        }
         Ok(())
     }
 
@@ -146,6 +262,7 @@ impl SocketStateMachine {
             SocketState::PassiveListening => self.listening_state(op),
             SocketState::ActiveConnecting => self.connecting_state(op),
             SocketState::ActiveEstablished => self.established_state(op),
+            SocketState::HalfClosed => self.half_closed_state(op),
             SocketState::Closing => self.closing_state(op),
             SocketState::Closed => self.closed_state(op),
         };
@@ -174,6 +291,7 @@ impl SocketStateMachine {
             SocketOp::Connect => Ok(SocketState::ActiveConnecting),
             // Should this be possible without going through the Connecting state?
             SocketOp::Established => Ok(SocketState::ActiveEstablished),
+            SocketOp::Shutdown => Err(fail(op, &(format!("socket is not connected")), libc::ENOTCONN)),
             SocketOp::Close => Ok(SocketState::Closing),
             SocketOp::Closed => Err(fail(op, &(format!("socket is busy")), libc::EBUSY)),
         }
@@ -186,6 +304,7 @@ impl SocketStateMachine {
             SocketOp::Listen => Ok(SocketState::PassiveListening),
             SocketOp::Connect => Ok(SocketState::ActiveConnecting),
             SocketOp::Established => Ok(SocketState::ActiveConnecting),
+            SocketOp::Shutdown => Err(fail(op, &(format!("socket is not connected")), libc::ENOTCONN)),
             SocketOp::Close => Ok(SocketState::Closing),
             SocketOp::Closed => Err(fail(op, &(format!("socket is busy")), libc::EBUSY)),
         }
@@ -199,6 +318,7 @@ impl SocketStateMachine {
             },
             SocketOp::Listen => Err(fail(op, &(format!("socket is already listening")), libc::EADDRINUSE)),
             SocketOp::Connect => Err(fail(op, &(format!("socket is already listening")), libc::EOPNOTSUPP)),
+            SocketOp::Shutdown => Err(fail(op, &(format!("socket is not connected")), libc::ENOTCONN)),
             SocketOp::Close => Ok(SocketState::Closing),
             SocketOp::Closed => Err(fail(op, &(format!("socket is busy")), libc::EBUSY)),
         }
@@ -215,6 +335,7 @@ impl SocketStateMachine {
                 libc::EINPROGRESS,
             )),
             SocketOp::Established => Ok(SocketState::ActiveEstablished),
+            SocketOp::Shutdown => Err(fail(op, &(format!("socket is not connected yet")), libc::ENOTCONN)),
             SocketOp::Close => Ok(SocketState::Closing),
             // We may enter the closed state from other states because either the state machine was incorrectly rolled
             // back or the close cased another operation to fail.
@@ -229,6 +350,19 @@ impl SocketStateMachine {
             SocketOp::Bind | SocketOp::Listen | SocketOp::Connect | SocketOp::Established => {
                 Err(fail(op, &(format!("socket is already connected")), libc::EISCONN))
             },
+            SocketOp::Shutdown => Ok(SocketState::HalfClosed),
+            SocketOp::Close => Ok(SocketState::Closing),
+            SocketOp::Closed => Ok(SocketState::Closed),
+        }
+    }
+
+    /// Attempts to transition from a half-closed state.
+    fn half_closed_state(&self, op: SocketOp) -> Result<SocketState, Fail> {
+        match op {
+            SocketOp::Bind | SocketOp::Listen | SocketOp::Connect | SocketOp::Established => {
+                Err(fail(op, &(format!("socket is already connected")), libc::EISCONN))
+            },
+            SocketOp::Shutdown => Ok(SocketState::HalfClosed),
             SocketOp::Close => Ok(SocketState::Closing),
             SocketOp::Closed => Ok(SocketState::Closed),
         }
@@ -274,7 +408,7 @@ impl SocketStateMachine {
 
     /// Ensures that the target [SocketState] is connected.
     fn ensure_established(&self) -> Result<(), Fail> {
-        if self.current != SocketState::ActiveEstablished {
+        if self.current != SocketState::ActiveEstablished && self.current != SocketState::HalfClosed {
             let cause: String = format!("socket is not connected");
             error!("ensure_connected(): {}", cause);
             return Err(Fail::new(libc::ENOTCONN, &cause));
@@ -292,6 +426,16 @@ impl SocketStateMachine {
         Ok(())
     }
 
+    /// Ensures that the sending direction of the target [SocketState] was not shut down.
+    fn ensure_not_write_shutdown(&self) -> Result<(), Fail> {
+        if self.write_shutdown {
+            let cause: String = format!("socket was shut down for writing");
+            error!("ensure_not_write_shutdown(): {}", cause);
+            return Err(Fail::new(libc::EPIPE, &cause));
+        }
+        Ok(())
+    }
+
     /// Ensures that the target [SocketState] is not closed.
     fn ensure_not_closed(&self) -> Result<(), Fail> {
         if self.current == SocketState::Closed {
@@ -303,6 +447,17 @@ impl SocketStateMachine {
     }
 }
 
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+#[cfg(feature = "state-history")]
+impl fmt::Debug for SocketStateHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.transitions.iter()).finish()
+    }
+}
+
 //======================================================================================================================
 // Standalone Functions
 //======================================================================================================================
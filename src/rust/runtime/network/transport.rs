@@ -7,12 +7,35 @@
 
 use crate::{
     demi_sgarray_t,
+    inetstack::protocols::{
+        ipv4::IPV4_HEADER_MIN_SIZE,
+        tcp::segment::MIN_TCP_HEADER_SIZE,
+    },
     runtime::{
         fail::Fail,
+        limits,
         memory::{
             DemiBuffer,
             MemoryRuntime,
         },
+        network::{
+            consts::DEFAULT_MTU,
+            socket::{
+                operation::Shutdown,
+                option::{
+                    SocketOption,
+                    SocketOptionName,
+                },
+            },
+            types::{
+                ConnectionSnapshot,
+                DropStats,
+                MacAddress,
+                TcpOption,
+                TcpSeqState,
+                TcpState,
+            },
+        },
         scheduler::Yielder,
         SharedDemiRuntime,
     },
@@ -22,8 +45,13 @@ use ::socket2::{
     Type,
 };
 use ::std::{
+    collections::HashMap,
     fmt::Debug,
-    net::SocketAddr,
+    future::Future,
+    net::{
+        Ipv4Addr,
+        SocketAddr,
+    },
 };
 
 //======================================================================================================================
@@ -55,7 +83,8 @@ pub trait NetworkTransport: Clone + 'static {
         yielder: Yielder,
     ) -> impl std::future::Future<Output = Result<(Self::SocketDescriptor, SocketAddr), Fail>>;
 
-    /// Asynchronously connect this socket to [remote].
+    /// Asynchronously connect this socket to [remote]. The future resolves once the connection is established, and not
+    /// when the connection request is merely sent.
     fn connect(
         &mut self,
         sd: &mut Self::SocketDescriptor,
@@ -63,6 +92,23 @@ pub trait NetworkTransport: Clone + 'static {
         yielder: Yielder,
     ) -> impl std::future::Future<Output = Result<(), Fail>>;
 
+    /// Connect a socket to a remote address and send `initial` data, in the SYN if the transport supports TCP Fast Open
+    /// and already holds a cookie from the remote. Transports that do not support TCP Fast Open connect normally and
+    /// then push the data.
+    fn connect_with_data(
+        &mut self,
+        sd: &mut Self::SocketDescriptor,
+        remote: SocketAddr,
+        initial: DemiBuffer,
+        yielder: Yielder,
+    ) -> impl std::future::Future<Output = Result<(), Fail>> {
+        async move {
+            self.connect(sd, remote, yielder).await?;
+            let mut buf: DemiBuffer = initial;
+            self.push(sd, &mut buf, None, Yielder::new()).await
+        }
+    }
+
     /// Push data to a connected socket.
     fn push(
         &mut self,
@@ -81,6 +127,21 @@ pub trait NetworkTransport: Clone + 'static {
         yielder: Yielder,
     ) -> impl std::future::Future<Output = Result<Option<SocketAddr>, Fail>>;
 
+    /// Pop all buffered data from a connected socket in a single buffer. Transports that cannot do so fall back to a
+    /// regular pop of at most [limits::RECVBUF_SIZE_MAX] bytes.
+    fn pop_all(
+        &mut self,
+        sd: &mut Self::SocketDescriptor,
+        yielder: Yielder,
+    ) -> impl std::future::Future<Output = Result<(Option<SocketAddr>, DemiBuffer), Fail>> {
+        async move {
+            let size: usize = limits::RECVBUF_SIZE_MAX;
+            let mut buf: DemiBuffer = DemiBuffer::new(size as u16);
+            let addr: Option<SocketAddr> = self.pop(sd, &mut buf, size, yielder).await?;
+            Ok((addr, buf))
+        }
+    }
+
     /// Asynchronously close a socket.
     fn close(
         &mut self,
@@ -88,8 +149,227 @@ pub trait NetworkTransport: Clone + 'static {
         yielder: Yielder,
     ) -> impl std::future::Future<Output = Result<(), Fail>>;
 
+    /// Probe whether another host on the link uses [ipv4_addr], before claiming it as our address (RFC 5227). Fails with
+    /// EADDRINUSE if another host claims the address. Transports that do not resolve addresses fail with ENOTSUP.
+    fn probe_address(&mut self, _ipv4_addr: Ipv4Addr, _yielder: Yielder) -> impl Future<Output = Result<(), Fail>> {
+        async move {
+            let cause: &str = "address probes not supported";
+            warn!("probe_address(): {}", cause);
+            Err(Fail::new(libc::ENOTSUP, cause))
+        }
+    }
+
+    /// Stop the background work of the transport, once none of its sockets is open anymore. The transport carries no
+    /// traffic afterwards.
+    fn stop(&mut self) {}
+
     /// Pull the common runtime out of the transport. We only need this because traits do not support members.
     fn get_runtime(&self) -> &SharedDemiRuntime;
+
+    /// Set a socket option. Transports that do not support [option] fail with ENOPROTOOPT.
+    fn set_socket_option(&mut self, _sd: &mut Self::SocketDescriptor, option: SocketOption) -> Result<(), Fail> {
+        let cause: String = format!("socket option not supported (option={:?})", option);
+        warn!("set_socket_option(): {}", cause);
+        Err(Fail::new(libc::ENOPROTOOPT, &cause))
+    }
+
+    /// Read a socket option back. Transports that do not support [name] fail with ENOPROTOOPT.
+    fn get_socket_option(&self, _sd: &Self::SocketDescriptor, name: SocketOptionName) -> Result<SocketOption, Fail> {
+        let cause: String = format!("socket option not supported (name={:?})", name);
+        warn!("get_socket_option(): {}", cause);
+        Err(Fail::new(libc::ENOPROTOOPT, &cause))
+    }
+
+    /// Move an established connection to a new local address without resetting it. Transports that do not support
+    /// connection migration fail with ENOTSUP.
+    fn migrate_local(&mut self, _sd: &mut Self::SocketDescriptor, new_local: SocketAddr) -> Result<(), Fail> {
+        let cause: String = format!("connection migration not supported (new_local={:?})", new_local);
+        warn!("migrate_local(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, &cause))
+    }
+
+    /// Pause or resume the data flow of an established connection. Transports that cannot pause connections fail with
+    /// ENOTSUP.
+    fn set_paused(&mut self, _sd: &mut Self::SocketDescriptor, paused: bool) -> Result<(), Fail> {
+        let cause: String = format!("pausing connections not supported (paused={:?})", paused);
+        warn!("set_paused(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, &cause))
+    }
+
+    /// Shut down the `how` directions of a connection. Shutting down the write side signals the end of the stream to the
+    /// peer, while data can still be received. Transports that do not support half-closed connections fail with ENOTSUP.
+    fn shutdown(&mut self, _sd: &mut Self::SocketDescriptor, how: Shutdown) -> Result<(), Fail> {
+        let cause: String = format!("shutting down connections not supported (how={:?})", how);
+        warn!("shutdown(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, &cause))
+    }
+
+    /// Number of segments retransmitted on this socket. Transports that do not track retransmissions report zero.
+    fn retransmit_count(&self, _sd: &Self::SocketDescriptor) -> u64 {
+        0
+    }
+
+    /// Congestion window of this connection, in bytes. Transports that do not run congestion control report `None`.
+    fn cwnd(&self, _sd: &Self::SocketDescriptor) -> Option<u32> {
+        None
+    }
+
+    /// Slow start threshold of this connection, in bytes. Transports that do not run congestion control report `None`.
+    fn ssthresh(&self, _sd: &Self::SocketDescriptor) -> Option<u32> {
+        None
+    }
+
+    /// Whether data was sent in the SYN of this connection. Transports that do not support TCP Fast Open report false.
+    fn used_fast_open(&self, _sd: &Self::SocketDescriptor) -> bool {
+        false
+    }
+
+    /// Number of connections to this listening socket whose handshake has not completed yet. Transports that do not
+    /// track half-open connections fail with ENOTSUP.
+    fn half_open_count(&self, _sd: &Self::SocketDescriptor) -> Result<usize, Fail> {
+        let cause: &str = "half_open_count is not available on this transport";
+        Err(Fail::new(libc::ENOTSUP, cause))
+    }
+
+    /// Number of connections to this listening socket that an accept would return without waiting. Transports that do
+    /// not queue accepted connections report zero, so that only the first accept of a batch waits.
+    fn ready_connections(&self, _sd: &Self::SocketDescriptor) -> usize {
+        0
+    }
+
+    /// Rate, in bits per second, at which this socket sent payload bytes over the recent past. Transports that do not
+    /// track rates report zero.
+    fn send_rate_bps(&self, _sd: &Self::SocketDescriptor) -> u64 {
+        0
+    }
+
+    /// Rate, in bits per second, at which this socket received payload bytes over the recent past. Transports that do
+    /// not track rates report zero.
+    fn recv_rate_bps(&self, _sd: &Self::SocketDescriptor) -> u64 {
+        0
+    }
+
+    /// Rate, in bits per second, at which the peer of this socket acknowledged payload bytes over the recent past.
+    /// Transports that do not track rates report zero.
+    fn delivery_rate_bps(&self, _sd: &Self::SocketDescriptor) -> u64 {
+        0
+    }
+
+    /// Number of complete datagrams that this socket has received and not yet popped. Transports that do not track queued
+    /// datagrams report zero.
+    fn queued_datagrams(&self, _sd: &Self::SocketDescriptor) -> usize {
+        0
+    }
+
+    /// Whether a pop on this socket would currently have to wait for data to arrive. This checks what the socket holds
+    /// without popping anything.
+    fn would_block_pop(&self, _sd: &Self::SocketDescriptor) -> Result<bool, Fail> {
+        Err(Fail::new(
+            libc::ENOTSUP,
+            "this transport cannot tell whether a pop would block",
+        ))
+    }
+
+    /// Number of buffers that this socket queued in the transport and that were not transmitted yet, e.g. because the
+    /// send ring of the NIC is full. Transports that do not queue buffers on their way out report zero.
+    fn tx_depth(&self, _sd: &Self::SocketDescriptor) -> usize {
+        0
+    }
+
+    /// TCP options received on this connection, if the transport was asked to record them. Transports that do not
+    /// record options report none.
+    fn observed_options(&self, _sd: &Self::SocketDescriptor) -> Vec<TcpOption> {
+        Vec::new()
+    }
+
+    /// Effective send buffer size of this socket, or none if its send buffer is unbounded. Transports that do not bound
+    /// their send buffer fail with ENOTSUP.
+    fn send_buffer_size(&self, _sd: &Self::SocketDescriptor) -> Result<Option<usize>, Fail> {
+        let cause: &str = "send buffer size is not available on this transport";
+        warn!("send_buffer_size(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, cause))
+    }
+
+    /// Largest payload that fits in a single packet on the path of this socket, i.e. the effective MTU minus the
+    /// network and transport headers. Transports that do not track the path report the payload that fits in a packet of
+    /// the default Ethernet MTU.
+    fn path_mtu(&self, _sd: &Self::SocketDescriptor) -> u16 {
+        DEFAULT_MTU - IPV4_HEADER_MIN_SIZE - MIN_TCP_HEADER_SIZE as u16
+    }
+
+    /// Endpoints, sequence space, and buffered data of this TCP connection. Transports that do not expose the state of
+    /// their connections fail with ENOTSUP.
+    fn connection_snapshot(&self, _sd: &Self::SocketDescriptor) -> Result<ConnectionSnapshot, Fail> {
+        let cause: &str = "connection snapshots are not available on this transport";
+        Err(Fail::new(libc::ENOTSUP, cause))
+    }
+
+    /// Restore the TCP connection that `snapshot` was taken of on a fresh socket, which takes over its endpoints,
+    /// sequence space, and buffered data. Transports that cannot restore connections fail with ENOTSUP.
+    fn resume_from(&mut self, _sd: &mut Self::SocketDescriptor, _snapshot: &ConnectionSnapshot) -> Result<(), Fail> {
+        let cause: &str = "connections cannot be restored on this transport";
+        warn!("resume_from(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, cause))
+    }
+
+    /// Sequence numbers and windows of this TCP connection. Transports that do not expose their sequence space, and
+    /// sockets that are not connected, report none.
+    fn tcp_sequence_state(&self, _sd: &Self::SocketDescriptor) -> Option<TcpSeqState> {
+        None
+    }
+
+    /// Initial sequence number that this TCP connection chose for its end. Transports that do not expose their sequence
+    /// space, and sockets that never sent a SYN, report none.
+    fn local_isn(&self, _sd: &Self::SocketDescriptor) -> Option<u32> {
+        None
+    }
+
+    /// RFC 793 state of the connection of this socket. Transports that do not track it report none.
+    fn tcp_state(&self, _sd: &Self::SocketDescriptor) -> Option<TcpState> {
+        None
+    }
+
+    /// DSCP that outgoing packets of this socket are marked with. Transports that do not mark packets fail with ENOTSUP.
+    fn dscp(&self, _sd: &Self::SocketDescriptor) -> Result<u8, Fail> {
+        let cause: &str = "dscp is not available on this transport";
+        warn!("dscp(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, cause))
+    }
+
+    /// Counters of incoming packets dropped by this transport. Transports that do not track drops report zero.
+    fn drop_stats(&self) -> DropStats {
+        DropStats::default()
+    }
+
+    /// Zeroes the counters of dropped packets. Transports that do not track drops do nothing.
+    fn reset_drop_stats(&mut self) {}
+
+    /// Get the link address used to frame outgoing packets. Transports that do not frame packets themselves fail with
+    /// ENOTSUP.
+    fn get_local_link_addr(&self) -> Result<MacAddress, Fail> {
+        let cause: &str = "local link address is not available on this transport";
+        warn!("get_local_link_addr(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, cause))
+    }
+
+    /// Override the link address used to frame outgoing packets. Transports that do not frame packets themselves fail
+    /// with ENOTSUP.
+    fn set_local_link_addr(&mut self, link_addr: MacAddress) -> Result<(), Fail> {
+        let cause: String = format!(
+            "cannot set local link address on this transport (link_addr={:?})",
+            link_addr
+        );
+        warn!("set_local_link_addr(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, &cause))
+    }
+
+    /// Replace the learned entries of the ARP cache with `values`, keeping static ones. Transports that do not resolve
+    /// link addresses themselves fail with ENOTSUP.
+    fn replace_arp_cache(&mut self, _values: &HashMap<Ipv4Addr, MacAddress>) -> Result<(), Fail> {
+        let cause: &str = "ARP cache is not available on this transport";
+        warn!("replace_arp_cache(): {}", cause);
+        Err(Fail::new(libc::ENOTSUP, cause))
+    }
 }
 
 impl<N: NetworkTransport> MemoryRuntime for N {
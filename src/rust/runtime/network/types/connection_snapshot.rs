@@ -0,0 +1,122 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//==============================================================================
+// Imports
+//==============================================================================
+
+use crate::runtime::{
+    fail::Fail,
+    network::types::TcpSeqState,
+};
+use ::std::{
+    convert::TryInto,
+    net::{
+        Ipv4Addr,
+        SocketAddrV4,
+    },
+};
+
+//==============================================================================
+// Constants
+//==============================================================================
+
+/// Size of a serialized snapshot without its buffered data: two endpoints, the sequence state, the window scales, the
+/// MSS, and the lengths of the buffered data.
+const SNAPSHOT_HEADER_SIZE: usize = 6 + 6 + 20 + 1 + 1 + 2 + 4 + 4;
+
+//==============================================================================
+// Structures
+//==============================================================================
+
+/// State of a quiesced TCP connection: its endpoints, its sequence space, and the data that it buffers. The connection
+/// can be restored from it, on the same stack or on another one that took over the local address.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct ConnectionSnapshot {
+    /// Local endpoint of the connection.
+    pub local: SocketAddrV4,
+    /// Remote endpoint of the connection.
+    pub remote: SocketAddrV4,
+    /// Sequence numbers and windows of the connection.
+    pub seq_state: TcpSeqState,
+    /// Scale of the windows that the peer advertises.
+    pub send_window_scale: u8,
+    /// Scale of the windows that we advertise to the peer.
+    pub receive_window_scale: u8,
+    /// Largest segment that we send to the peer.
+    pub mss: u16,
+    /// Data sent but not yet acknowledged by the peer, followed by data not yet sent. Starts at SND.UNA.
+    pub send_buffered: Vec<u8>,
+    /// Data received from the peer but not yet popped by the application. Ends at RCV.NXT.
+    pub recv_buffered: Vec<u8>,
+}
+
+//==============================================================================
+// Associate Functions
+//==============================================================================
+
+impl ConnectionSnapshot {
+    /// Serializes the snapshot, so that it can be handed over to another process. Multi-byte fields are in network
+    /// byte order.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> =
+            Vec::with_capacity(SNAPSHOT_HEADER_SIZE + self.send_buffered.len() + self.recv_buffered.len());
+        for endpoint in [self.local, self.remote] {
+            buf.extend_from_slice(&endpoint.ip().octets());
+            buf.extend_from_slice(&endpoint.port().to_be_bytes());
+        }
+        for value in [
+            self.seq_state.snd_una,
+            self.seq_state.snd_nxt,
+            self.seq_state.rcv_nxt,
+            self.seq_state.snd_wnd,
+            self.seq_state.rcv_wnd,
+        ] {
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+        buf.push(self.send_window_scale);
+        buf.push(self.receive_window_scale);
+        buf.extend_from_slice(&self.mss.to_be_bytes());
+        buf.extend_from_slice(&(self.send_buffered.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&(self.recv_buffered.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&self.send_buffered);
+        buf.extend_from_slice(&self.recv_buffered);
+        buf
+    }
+
+    /// Parses a snapshot serialized with [Self::serialize]. Fails with EBADMSG if `buf` does not hold one.
+    pub fn parse(buf: &[u8]) -> Result<Self, Fail> {
+        if buf.len() < SNAPSHOT_HEADER_SIZE {
+            return Err(Fail::new(libc::EBADMSG, "connection snapshot too short"));
+        }
+        let endpoint = |offset: usize| -> SocketAddrV4 {
+            SocketAddrV4::new(
+                Ipv4Addr::new(buf[offset], buf[offset + 1], buf[offset + 2], buf[offset + 3]),
+                u16::from_be_bytes([buf[offset + 4], buf[offset + 5]]),
+            )
+        };
+        let word = |offset: usize| -> u32 { u32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap()) };
+        let send_len: usize = word(36) as usize;
+        let recv_len: usize = word(40) as usize;
+        if buf.len() != SNAPSHOT_HEADER_SIZE + send_len + recv_len {
+            return Err(Fail::new(libc::EBADMSG, "connection snapshot has a bad length"));
+        }
+        let (send_buffered, recv_buffered): (&[u8], &[u8]) = buf[SNAPSHOT_HEADER_SIZE..].split_at(send_len);
+        Ok(Self {
+            local: endpoint(0),
+            remote: endpoint(6),
+            seq_state: TcpSeqState {
+                snd_una: word(12),
+                snd_nxt: word(16),
+                rcv_nxt: word(20),
+                snd_wnd: word(24),
+                rcv_wnd: word(28),
+            },
+            send_window_scale: buf[32],
+            receive_window_scale: buf[33],
+            mss: u16::from_be_bytes([buf[34], buf[35]]),
+            send_buffered: send_buffered.to_vec(),
+            recv_buffered: recv_buffered.to_vec(),
+        })
+    }
+}
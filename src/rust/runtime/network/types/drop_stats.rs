@@ -0,0 +1,86 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//==============================================================================
+// Imports
+//==============================================================================
+
+use crate::runtime::fail::Fail;
+use ::libc::{
+    EILSEQ,
+    ENOTSUP,
+};
+
+//==============================================================================
+// Structures
+//==============================================================================
+
+/// Reason for dropping an incoming packet.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum DropReason {
+    /// A checksum did not match the contents of the packet.
+    BadChecksum,
+    /// The packet is not addressed to us.
+    NoRoute,
+    /// No socket is bound to the destination of the packet.
+    NoSocket,
+    /// The socket could not take in any more packets.
+    BufferFull,
+    /// A header is truncated or otherwise invalid.
+    MalformedHeader,
+    /// The packet carries a protocol that the stack does not speak.
+    UnsupportedProtocol,
+}
+
+/// Number of incoming packets dropped by the network stack, by reason.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub struct DropStats {
+    pub bad_checksum: u64,
+    pub no_route: u64,
+    pub no_socket: u64,
+    pub buffer_full: u64,
+    pub malformed_header: u64,
+    pub unsupported_protocol: u64,
+}
+
+//==============================================================================
+// Associate Functions
+//==============================================================================
+
+/// Associate Functions for Drop Reasons
+impl DropReason {
+    /// Classifies an error returned while parsing a header. Header parsers report checksum mismatches with `EILSEQ`,
+    /// protocols that we do not speak with `ENOTSUP`, and any other invalid header with `EBADMSG`.
+    pub fn from_parse_error(e: &Fail) -> Self {
+        match e.errno {
+            EILSEQ => DropReason::BadChecksum,
+            ENOTSUP => DropReason::UnsupportedProtocol,
+            _ => DropReason::MalformedHeader,
+        }
+    }
+}
+
+/// Associate Functions for Drop Statistics
+impl DropStats {
+    /// Counts a packet dropped for `reason`.
+    pub fn record(&mut self, reason: DropReason) {
+        match reason {
+            DropReason::BadChecksum => self.bad_checksum += 1,
+            DropReason::NoRoute => self.no_route += 1,
+            DropReason::NoSocket => self.no_socket += 1,
+            DropReason::BufferFull => self.buffer_full += 1,
+            DropReason::MalformedHeader => self.malformed_header += 1,
+            DropReason::UnsupportedProtocol => self.unsupported_protocol += 1,
+        }
+    }
+
+    /// Total number of dropped packets.
+    pub fn total(&self) -> u64 {
+        self.bad_checksum
+            + self.no_route
+            + self.no_socket
+            + self.buffer_full
+            + self.malformed_header
+            + self.unsupported_protocol
+    }
+}
@@ -1,14 +1,29 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT license.
 
+mod connection_snapshot;
+mod drop_stats;
 mod macaddr;
+mod op_counts;
 mod portnum;
+mod tcp_option;
+mod tcp_seq_state;
+mod tcp_state;
 
 //==============================================================================
 // Exports
 //==============================================================================
 
 pub use self::{
+    connection_snapshot::ConnectionSnapshot,
+    drop_stats::{
+        DropReason,
+        DropStats,
+    },
     macaddr::MacAddress,
+    op_counts::OpCounts,
     portnum::Port16,
+    tcp_option::TcpOption,
+    tcp_seq_state::TcpSeqState,
+    tcp_state::TcpState,
 };
@@ -0,0 +1,21 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//==============================================================================
+// Structures
+//==============================================================================
+
+/// Number of operations that completed successfully on a queue, by type.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub struct OpCounts {
+    /// Pushes of data, once the data was handed to the transport.
+    pub pushes: u64,
+    /// Pops of data, once data was received.
+    pub pops: u64,
+    /// Connections accepted on a listening queue.
+    pub accepts: u64,
+    /// Connections established by the queue.
+    pub connects: u64,
+    /// Graceful closes of the queue. This is at most one, and the queue descriptor is released once it completes.
+    pub closes: u64,
+}
@@ -0,0 +1,19 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//==============================================================================
+// Structures
+//==============================================================================
+
+/// TCP option received from the remote end of a connection.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum TcpOption {
+    /// Maximum segment size the remote end is willing to receive.
+    MaximumSegmentSize(u16),
+    /// Shift count the remote end applies to its advertised window.
+    WindowScale(u8),
+    /// The remote end accepts selective acknowledgements.
+    SelectiveAcknowledgementPermitted,
+    /// Most recent timestamp received from the remote end.
+    Timestamp { sender_timestamp: u32, echo_timestamp: u32 },
+}
@@ -0,0 +1,21 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//==============================================================================
+// Structures
+//==============================================================================
+
+/// Snapshot of the sequence space of a TCP connection, in RFC 793 terminology.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct TcpSeqState {
+    /// Oldest unacknowledged sequence number (SND.UNA).
+    pub snd_una: u32,
+    /// Next sequence number to be sent (SND.NXT).
+    pub snd_nxt: u32,
+    /// Next sequence number expected from the peer (RCV.NXT).
+    pub rcv_nxt: u32,
+    /// Send window advertised by the peer, in bytes (SND.WND).
+    pub snd_wnd: u32,
+    /// Receive window that we offer to the peer, in bytes (RCV.WND).
+    pub rcv_wnd: u32,
+}
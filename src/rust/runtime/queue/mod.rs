@@ -165,6 +165,14 @@ impl IoQueueTable {
         Ok(downcast_queue::<T>(self.table.remove(index as usize))?)
     }
 
+    /// Gets the I/O queue descriptors of all registered queues.
+    pub fn get_qds(&self) -> Vec<QDesc> {
+        self.table
+            .iter()
+            .map(|(index, _)| QDesc::from((index as u32) + Self::BASE_QD))
+            .collect()
+    }
+
     /// Gets an iterator over all registered queues.
     pub fn get_values(&self) -> Iter<'_, Box<dyn IoQueue>> {
         self.table.iter()
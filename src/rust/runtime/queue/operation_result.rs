@@ -23,6 +23,8 @@ use ::std::{
 pub enum OperationResult {
     Connect,
     Accept((QDesc, SocketAddrV4)),
+    /// Connections accepted at once, in the order in which they were accepted. Never empty.
+    AcceptBatch(Vec<(QDesc, SocketAddrV4)>),
     Push,
     Pop(Option<SocketAddrV4>, DemiBuffer),
     Close,
@@ -38,6 +40,7 @@ impl fmt::Debug for OperationResult {
         match self {
             OperationResult::Connect => write!(f, "Connect"),
             OperationResult::Accept(..) => write!(f, "Accept"),
+            OperationResult::AcceptBatch(ref accepted) => write!(f, "AcceptBatch({})", accepted.len()),
             OperationResult::Push => write!(f, "Push"),
             OperationResult::Pop(..) => write!(f, "Pop"),
             OperationResult::Close => write!(f, "Close"),
@@ -21,25 +21,48 @@ use crate::{
             WakerPageRef,
             WakerRef,
         },
-        scheduler::InternalId,
+        scheduler::{
+            InternalId,
+            Priority,
+            SlabStats,
+        },
         waker64::{
             WAKER_BIT_LENGTH,
             WAKER_BIT_LENGTH_SHIFT,
         },
         Task,
         TaskId,
+        TaskWithResult,
+        YielderHandle,
     },
 };
 use ::bit_iter::BitIter;
-use ::futures::Future;
+use ::futures::{
+    future,
+    Future,
+    FutureExt,
+};
+use ::rand::rngs::SmallRng;
+#[cfg(feature = "scheduler-latency")]
+use ::std::sync::Mutex;
 use ::std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
     pin::Pin,
     ptr::NonNull,
+    sync::Arc,
     task::{
         Context,
         Poll,
+        Wake,
         Waker,
     },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 //======================================================================================================================
@@ -55,6 +78,41 @@ pub struct TaskGroup {
     tasks: PinSlab<Box<dyn Task>>,
     /// Holds the waker bits for controlling task scheduling.
     waker_page_refs: Vec<WakerPageRef>,
+    /// Maximum number of tasks that the group may hold at once, if any.
+    quota: Option<usize>,
+    /// Offsets of the slots that ids imported with [Self::with_id_map] resolve to. They hold a placeholder task that
+    /// never completes, so that new tasks do not take them over, until the imported id is removed.
+    reserved: HashSet<usize>,
+    /// Offsets of the tasks that run at high priority. All other tasks run at normal priority.
+    high_priority: HashSet<usize>,
+    /// Coroutines that wait for each task to complete, by offset. They are woken and forgotten as soon as the task
+    /// completes or is removed, so they never outlive the task in its slot.
+    joiners: HashMap<usize, Vec<YielderHandle>>,
+    /// When each task was inserted, by offset, for the tasks inserted while insertion times were recorded.
+    inserted_at: HashMap<usize, Instant>,
+    /// Wakers handed to tasks while an external waker is registered, by offset. They are built on the first such poll of
+    /// each task and reused for as long as the same external waker stays registered.
+    forwarding_wakers: HashMap<usize, Arc<ForwardingWaker>>,
+    /// Number of polls of tasks in this group that took longer than the poll watchdog allowed.
+    slow_polls: usize,
+    /// When each notified task was first notified since it was last polled, by offset. Wakers record into it, so it is
+    /// shared with them.
+    #[cfg(feature = "scheduler-latency")]
+    notified_at: Arc<Mutex<HashMap<usize, Instant>>>,
+}
+
+/// Waker handed to tasks while an external waker is registered: notifies the task and then the external waker.
+struct ForwardingWaker {
+    task: Waker,
+    external: Waker,
+}
+
+/// Waker handed to tasks while latencies are recorded: records when the task is notified and then notifies it.
+#[cfg(feature = "scheduler-latency")]
+struct LatencyWaker {
+    task: Waker,
+    pin_slab_index: usize,
+    notified_at: Arc<Mutex<HashMap<usize, Instant>>>,
 }
 
 //======================================================================================================================
@@ -62,6 +120,64 @@ pub struct TaskGroup {
 //======================================================================================================================
 
 impl TaskGroup {
+    /// Creates a group whose task ids resolve according to `mappings`, as exported by [Self::export_id_map]. This lets
+    /// ids handed out before a restart keep resolving to the same internal ids. The tasks themselves are not restored:
+    /// the slots of the imported ids are reserved with placeholders that never complete, until the ids are removed.
+    /// Mappings to an internal id that an earlier mapping already took are dropped.
+    pub fn with_id_map(mappings: Vec<(TaskId, InternalId)>) -> Self {
+        let mut reserved: HashSet<usize> = HashSet::new();
+        let mut unique_mappings: Vec<(TaskId, InternalId)> = Vec::with_capacity(mappings.len());
+        for (task_id, internal_id) in mappings {
+            if reserved.insert(internal_id.into()) {
+                unique_mappings.push((task_id, internal_id));
+            } else {
+                warn!(
+                    "with_id_map(): internal id is already mapped (task_id={:?}, internal_id={:?})",
+                    task_id, internal_id
+                );
+            }
+        }
+        let mut group: Self = Self {
+            ids: IdMap::from_mappings(unique_mappings),
+            ..Default::default()
+        };
+
+        // The slab hands out slots in order, so fill every slot up to the last reserved one and free the others.
+        if let Some(last) = reserved.iter().max().copied() {
+            for pin_slab_index in 0..=last {
+                let placeholder: TaskWithResult<()> =
+                    TaskWithResult::new(String::from("reserved"), Box::pin(future::pending().fuse()));
+                let inserted: Option<usize> = group.tasks.insert(Box::new(placeholder));
+                debug_assert_eq!(inserted, Some(pin_slab_index));
+            }
+            for pin_slab_index in (0..=last).rev().filter(|index| !reserved.contains(index)) {
+                group.tasks.remove_unpin(pin_slab_index);
+            }
+            group.add_new_pages_up_to_pin_slab_index(last);
+        }
+        group.reserved = reserved;
+        group
+    }
+
+    /// Creates an empty group that draws the ids of its tasks from `rng`.
+    pub fn with_rng(rng: SmallRng) -> Self {
+        Self {
+            ids: IdMap::with_rng(rng),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the mappings between the ids of the tasks in this group and their internal ids, in no particular order.
+    pub fn export_id_map(&self) -> Vec<(TaskId, InternalId)> {
+        self.ids.export()
+    }
+
+    #[cfg(test)]
+    /// Returns the internal id that a task id resolves to, if the task id belongs to this group.
+    pub fn get_internal_id(&self, task_id: &TaskId) -> Option<InternalId> {
+        self.ids.get(task_id)
+    }
+
     /// Given a handle to a task, remove it from the scheduler
     pub fn remove(&mut self, task_id: TaskId) -> Option<Box<dyn Task>> {
         // We should not have a scheduler handle that refers to an invalid id, so unwrap and expect are safe here.
@@ -70,11 +186,31 @@ impl TaskGroup {
             .remove(&task_id)
             .expect("Token should be in the token table")
             .into();
+        // An imported id frees its reserved slot, but has no task to hand back.
+        if self.reserved.remove(&pin_slab_index) {
+            if let Some((waker_page_index, waker_page_offset)) = self.get_waker_page_index_and_offset(pin_slab_index) {
+                self.waker_page_refs[waker_page_index].clear(waker_page_offset);
+            }
+            self.wake_joiners(pin_slab_index);
+            self.tasks.remove_unpin(pin_slab_index);
+            return None;
+        }
         let (waker_page_ref, waker_page_offset): (&WakerPageRef, usize) = {
             let (waker_page_index, waker_page_offset) = self.get_waker_page_index_and_offset(pin_slab_index)?;
             (&self.waker_page_refs[waker_page_index], waker_page_offset)
         };
         waker_page_ref.clear(waker_page_offset);
+        // A removed task never runs again, so it counts as completed for whoever joins it.
+        waker_page_ref.mark_completed(waker_page_offset);
+        self.wake_joiners(pin_slab_index);
+        self.high_priority.remove(&pin_slab_index);
+        self.inserted_at.remove(&pin_slab_index);
+        self.forwarding_wakers.remove(&pin_slab_index);
+        #[cfg(feature = "scheduler-latency")]
+        self.notified_at
+            .lock()
+            .expect("notification times should not be poisoned")
+            .remove(&pin_slab_index);
         if let Some(task) = self.tasks.remove_unpin(pin_slab_index) {
             trace!(
                 "remove(): name={:?}, id={:?}, pin_slab_index={:?}",
@@ -92,9 +228,46 @@ impl TaskGroup {
         }
     }
 
-    /// Insert a new task into our scheduler returning a handle corresponding to it.
-    pub fn insert(&mut self, task: Box<dyn Task>) -> Option<TaskId> {
+    /// Limits the number of tasks that the group may hold at once to `quota`. Tasks already in the group are not
+    /// affected, but no new task is inserted until the group drops below its quota.
+    pub fn set_quota(&mut self, quota: usize) {
+        self.quota = Some(quota);
+    }
+
+    /// Sets the priority of a task. Returns `None` if the task does not belong to this group.
+    pub fn set_priority(&mut self, task_id: TaskId, priority: Priority) -> Option<()> {
+        let pin_slab_index: usize = self.ids.get(&task_id)?.into();
+        match priority {
+            Priority::High => self.high_priority.insert(pin_slab_index),
+            Priority::Normal => self.high_priority.remove(&pin_slab_index),
+        };
+        Some(())
+    }
+
+    /// Returns the priority of the task at the given offset.
+    pub fn get_priority(&self, pin_slab_index: usize) -> Priority {
+        if self.high_priority.contains(&pin_slab_index) {
+            Priority::High
+        } else {
+            Priority::Normal
+        }
+    }
+
+    /// Checks whether the group holds any high-priority task.
+    pub fn has_high_priority_tasks(&self) -> bool {
+        !self.high_priority.is_empty()
+    }
+
+    /// Insert a new task into our scheduler returning a handle corresponding to it. If `record_insertion` is set, the
+    /// time of insertion is recorded for [Self::oldest_pending_task].
+    pub fn insert(&mut self, task: Box<dyn Task>, record_insertion: bool) -> Option<TaskId> {
         let task_name: String = task.get_name();
+        if let Some(quota) = self.quota {
+            if self.tasks.occupied_slots() >= quota {
+                warn!("insert(): task group is full (name={:?}, quota={:?})", task_name, quota);
+                return None;
+            }
+        }
         // The pin slab index can be reverse-computed in a page index and an offset within the page.
         let pin_slab_index: usize = self.tasks.insert(task)?;
         let task_id: TaskId = self.ids.insert_with_new_id(pin_slab_index.into());
@@ -107,6 +280,15 @@ impl TaskGroup {
             (&self.waker_page_refs[waker_page_index], waker_page_offset)
         };
         waker_page_ref.initialize(waker_page_offset);
+        if record_insertion {
+            self.inserted_at.insert(pin_slab_index, Instant::now());
+        }
+        // New tasks are notified.
+        #[cfg(feature = "scheduler-latency")]
+        self.notified_at
+            .lock()
+            .expect("notification times should not be poisoned")
+            .insert(pin_slab_index, Instant::now());
 
         trace!(
             "insert(): name={:?}, id={:?}, pin_slab_index={:?}",
@@ -165,6 +347,69 @@ impl TaskGroup {
         Some(waker_page_ref.has_completed(waker_page_offset))
     }
 
+    /// Registers `joiner` to be woken once a task completes or is removed. Returns false, without registering it, if
+    /// the task does not belong to this group or has already completed.
+    pub fn add_joiner(&mut self, task_id: TaskId, joiner: YielderHandle) -> bool {
+        match self.has_completed(task_id) {
+            Some(false) => {
+                let pin_slab_index: usize = self.ids.get(&task_id).expect("task should exist").into();
+                self.joiners.entry(pin_slab_index).or_default().push(joiner);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Wakes the coroutines that wait for the task at the given offset.
+    fn wake_joiners(&mut self, pin_slab_index: usize) {
+        for mut joiner in self.joiners.remove(&pin_slab_index).unwrap_or_default() {
+            joiner.wake_with(Ok(()));
+        }
+    }
+
+    /// Returns the id, name and insertion time of the task of this group that was inserted the longest ago and has not
+    /// completed yet, if any. Tasks inserted without recording their insertion time are not considered.
+    pub fn oldest_pending_task(&self) -> Option<(TaskId, String, Instant)> {
+        let (pin_slab_index, inserted_at): (usize, Instant) = self
+            .inserted_at
+            .iter()
+            .filter(
+                |(pin_slab_index, _)| match self.get_waker_page_index_and_offset(**pin_slab_index) {
+                    Some((waker_page_index, waker_page_offset)) => {
+                        !self.waker_page_refs[waker_page_index].has_completed(waker_page_offset)
+                    },
+                    None => false,
+                },
+            )
+            .min_by_key(|(_, inserted_at)| **inserted_at)
+            .map(|(pin_slab_index, inserted_at)| (*pin_slab_index, *inserted_at))?;
+        let task: &dyn Task = self.tasks.get(pin_slab_index)?.as_ref();
+        Some((task.get_id(), task.get_name(), inserted_at))
+    }
+
+    /// Sets the notified bit of a task, so that it is polled in the next sweep. Returns `None` if the task does not
+    /// belong to this group.
+    pub fn notify(&self, task_id: TaskId) -> Option<()> {
+        let pin_slab_index: usize = self.ids.get(&task_id)?.into();
+        let (waker_page_index, waker_page_offset) = self.get_waker_page_index_and_offset(pin_slab_index)?;
+        self.waker_page_refs[waker_page_index].notify(waker_page_offset);
+        #[cfg(feature = "scheduler-latency")]
+        record_notification(&self.notified_at, pin_slab_index);
+        Some(())
+    }
+
+    /// Returns how long ago the task at the given offset was first notified since it was last polled, and forgets it,
+    /// if the notification was recorded.
+    #[cfg(feature = "scheduler-latency")]
+    pub fn take_notify_latency(&self, pin_slab_index: usize) -> Option<Duration> {
+        let notified_at: Instant = self
+            .notified_at
+            .lock()
+            .expect("notification times should not be poisoned")
+            .remove(&pin_slab_index)?;
+        Some(notified_at.elapsed())
+    }
+
     pub fn get_offsets_for_ready_tasks(&mut self) -> Vec<usize> {
         let mut result: Vec<usize> = vec![];
         for i in 0..self.get_num_waker_pages() {
@@ -179,6 +424,11 @@ impl TaskGroup {
         result
     }
 
+    /// Checks whether a task occupies the given offset.
+    pub fn contains(&self, pin_slab_index: usize) -> bool {
+        self.tasks.contains(pin_slab_index)
+    }
+
     pub fn get_id(&self, pin_slab_index: usize) -> TaskId {
         self.tasks
             .get(pin_slab_index)
@@ -198,19 +448,61 @@ impl TaskGroup {
         unsafe { Waker::from_raw(WakerRef::new(raw_waker).into()) }
     }
 
-    pub fn poll_notified_task(&mut self, pin_slab_index: usize) -> Option<bool> {
+    /// Polls a notified task. If `external_waker` is given, it is woken whenever the task is notified afterwards. If
+    /// `watchdog` is given, polls that take longer than it are logged and counted.
+    pub fn poll_notified_task(
+        &mut self,
+        pin_slab_index: usize,
+        external_waker: Option<&Waker>,
+        watchdog: Option<Duration>,
+    ) -> Option<bool> {
         // Get the waker context.
         let (waker_page_index, waker_page_offset) = self.get_waker_page_index_and_offset(pin_slab_index)?;
-        let waker: Waker = self.get_waker(waker_page_index, waker_page_offset);
+        let waker: Waker = match external_waker {
+            Some(external) => match self.forwarding_wakers.get(&pin_slab_index) {
+                Some(forwarding_waker) if forwarding_waker.external.will_wake(external) => {
+                    Waker::from(forwarding_waker.clone())
+                },
+                _ => {
+                    let forwarding_waker: Arc<ForwardingWaker> = Arc::new(ForwardingWaker {
+                        task: self.get_waker(waker_page_index, waker_page_offset),
+                        external: external.clone(),
+                    });
+                    self.forwarding_wakers.insert(pin_slab_index, forwarding_waker.clone());
+                    Waker::from(forwarding_waker)
+                },
+            },
+            None => self.get_waker(waker_page_index, waker_page_offset),
+        };
+        #[cfg(feature = "scheduler-latency")]
+        let waker: Waker = Waker::from(Arc::new(LatencyWaker {
+            task: waker,
+            pin_slab_index,
+            notified_at: self.notified_at.clone(),
+        }));
         let mut waker_context: Context = Context::from_waker(&waker);
 
         let mut pinned_ptr = self.get_pinned_task_ptr(pin_slab_index);
         let pinned_ref = unsafe { Pin::new_unchecked(&mut *pinned_ptr) };
 
         // Poll future.
+        let start: Option<Instant> = watchdog.map(|_| Instant::now());
         let poll_result: Poll<()> = Future::poll(pinned_ref, &mut waker_context);
+        if let (Some(threshold), Some(start)) = (watchdog, start) {
+            let elapsed: Duration = start.elapsed();
+            if elapsed > threshold {
+                warn!(
+                    "poll_notified_task(): slow poll (name={:?}, elapsed={:?}, threshold={:?})",
+                    self.get_pinned_task_ptr(pin_slab_index).get_name(),
+                    elapsed,
+                    threshold
+                );
+                self.slow_polls += 1;
+            }
+        }
         if let Poll::Ready(()) = poll_result {
             self.waker_page_refs[waker_page_index].mark_completed(waker_page_offset);
+            self.wake_joiners(pin_slab_index);
             Some(true)
         } else {
             Some(false)
@@ -230,4 +522,58 @@ impl TaskGroup {
     pub fn num_tasks(&self) -> usize {
         self.ids.len()
     }
+
+    /// Returns the number of polls of tasks in this group that exceeded the poll watchdog.
+    pub fn get_slow_polls(&self) -> usize {
+        self.slow_polls
+    }
+
+    /// Returns the number of free and occupied entries in the slab that stores the tasks of this group.
+    pub fn slab_stats(&self) -> SlabStats {
+        SlabStats {
+            free_slots: self.tasks.free_slots(),
+            occupied_slots: self.tasks.occupied_slots(),
+        }
+    }
+}
+
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+impl Wake for ForwardingWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.task.wake_by_ref();
+        self.external.wake_by_ref();
+    }
+}
+
+#[cfg(feature = "scheduler-latency")]
+impl Wake for LatencyWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        record_notification(&self.notified_at, self.pin_slab_index);
+        self.task.wake_by_ref();
+    }
+}
+
+//======================================================================================================================
+// Standalone Functions
+//======================================================================================================================
+
+/// Records that the task at the given offset was notified now, unless it was already notified since it was last polled.
+#[cfg(feature = "scheduler-latency")]
+fn record_notification(notified_at: &Mutex<HashMap<usize, Instant>>, pin_slab_index: usize) {
+    notified_at
+        .lock()
+        .expect("notification times should not be poisoned")
+        .entry(pin_slab_index)
+        .or_insert_with(Instant::now);
 }
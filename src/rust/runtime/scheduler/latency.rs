@@ -0,0 +1,146 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use ::std::time::Duration;
+
+//======================================================================================================================
+// Constants
+//======================================================================================================================
+
+/// Number of buckets of the histogram. Bucket `i` counts latencies of `2^i` to `2^(i+1) - 1` nanoseconds, and the first
+/// one also counts latencies of zero.
+const NUM_BUCKETS: usize = 64;
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Percentiles of the time that tasks spent between being notified and being polled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Median latency.
+    pub p50: Duration,
+    /// 99th percentile latency.
+    pub p99: Duration,
+    /// Largest latency.
+    pub max: Duration,
+    /// Number of latencies recorded.
+    pub count: u64,
+}
+
+/// Histogram of notify-to-poll latencies, with buckets that double in width. Percentiles are reported as the upper
+/// bound of the bucket that holds them, so they are accurate within a factor of two.
+pub struct LatencyHistogram {
+    buckets: [u64; NUM_BUCKETS],
+    max: Duration,
+    count: u64,
+}
+
+//======================================================================================================================
+// Associate Functions
+//======================================================================================================================
+
+impl LatencyHistogram {
+    /// Records a latency.
+    pub fn record(&mut self, latency: Duration) {
+        let nanos: u64 = latency.as_nanos().min(u64::MAX as u128) as u64;
+        let bucket: usize = (u64::BITS - nanos.max(1).leading_zeros() - 1) as usize;
+        self.buckets[bucket] += 1;
+        self.max = self.max.max(latency);
+        self.count += 1;
+    }
+
+    /// Returns the percentiles of the recorded latencies.
+    pub fn stats(&self) -> LatencyStats {
+        LatencyStats {
+            p50: self.percentile(50),
+            p99: self.percentile(99),
+            max: self.max,
+            count: self.count,
+        }
+    }
+
+    /// Returns the latency below which `percent` percent of the recorded latencies fall, or zero if there are none.
+    fn percentile(&self, percent: u64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        // Rank of the latency that we look for, counting from one.
+        let rank: u64 = ((self.count * percent + 99) / 100).max(1);
+        let mut seen: u64 = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let upper_bound: u64 = if bucket + 1 < NUM_BUCKETS {
+                    (1 << (bucket + 1)) - 1
+                } else {
+                    u64::MAX
+                };
+                return Duration::from_nanos(upper_bound).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+//======================================================================================================================
+// Trait Implementations
+//======================================================================================================================
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; NUM_BUCKETS],
+            max: Duration::ZERO,
+            count: 0,
+        }
+    }
+}
+
+//======================================================================================================================
+// Unit Tests
+//======================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        LatencyHistogram,
+        LatencyStats,
+    };
+    use ::anyhow::Result;
+    use ::std::time::Duration;
+
+    #[test]
+    fn empty_histogram_reports_zero() -> Result<()> {
+        crate::ensure_eq!(LatencyHistogram::default().stats(), LatencyStats::default());
+        Ok(())
+    }
+
+    #[test]
+    fn percentiles_fall_in_the_right_buckets() -> Result<()> {
+        let mut histogram: LatencyHistogram = LatencyHistogram::default();
+        // 98 short latencies and two long ones.
+        for _ in 0..98 {
+            histogram.record(Duration::from_micros(10));
+        }
+        histogram.record(Duration::from_millis(5));
+        histogram.record(Duration::from_millis(7));
+
+        let stats: LatencyStats = histogram.stats();
+        crate::ensure_eq!(stats.count, 100);
+        crate::ensure_eq!(stats.max, Duration::from_millis(7));
+        crate::ensure_eq!(
+            stats.p50 >= Duration::from_micros(10) && stats.p50 < Duration::from_micros(20),
+            true
+        );
+        crate::ensure_eq!(
+            stats.p99 >= Duration::from_millis(5) && stats.p99 <= Duration::from_millis(7),
+            true
+        );
+        Ok(())
+    }
+}
@@ -31,6 +31,8 @@
 //! the scheduler. The [YielderHandle] identifies a specific blocked coroutine and can be used to wake the coroutine.
 
 mod group;
+#[cfg(feature = "scheduler-latency")]
+mod latency;
 pub mod mutex;
 mod page;
 pub mod scheduler;
@@ -42,9 +44,16 @@ pub mod yielder;
 // Exports
 //==============================================================================
 
+#[cfg(feature = "scheduler-latency")]
+pub use self::latency::LatencyStats;
 pub use self::{
     mutex::Mutex,
-    scheduler::Scheduler,
+    scheduler::{
+        PollOutcome,
+        Priority,
+        Scheduler,
+        SlabStats,
+    },
     task::{
         Task,
         TaskId,
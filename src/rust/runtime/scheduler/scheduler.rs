@@ -11,29 +11,106 @@
 // Imports
 //======================================================================================================================
 
+#[cfg(feature = "scheduler-latency")]
+use crate::runtime::scheduler::latency::{
+    LatencyHistogram,
+    LatencyStats,
+};
 use crate::{
     collections::id_map::IdMap,
     runtime::scheduler::{
         group::TaskGroup,
         Task,
         TaskId,
+        Yielder,
     },
 };
+use ::rand::{
+    rngs::SmallRng,
+    RngCore,
+    SeedableRng,
+};
 use ::slab::Slab;
+use ::std::{
+    collections::VecDeque,
+    future::Future,
+    task::Waker,
+    time::{
+        Duration,
+        Instant,
+    },
+};
 
 //======================================================================================================================
 // Structures
 //======================================================================================================================
 
 /// Internal offset into the slab that holds the task state.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct InternalId(usize);
 
+/// Scheduling priority of a task.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Priority {
+    /// Polled in the order in which the task groups and tasks were created.
+    #[default]
+    Normal,
+    /// Polled before every ready normal-priority task of the same sweep. Normal-priority tasks are still polled in every
+    /// sweep, after the high-priority ones, so they cannot starve.
+    High,
+}
+
+/// Result of a time-bounded poll sweep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PollOutcome {
+    /// Number of tasks polled.
+    pub polled_tasks: usize,
+    /// Did the sweep run out of time before polling every ready task? If so, the next call resumes it.
+    pub truncated: bool,
+}
+
+/// Occupancy of the slabs that store the tasks of the scheduler.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlabStats {
+    /// Number of allocated entries that do not hold a task.
+    pub free_slots: usize,
+    /// Number of entries that hold a task.
+    pub occupied_slots: usize,
+}
+
+/// Where an unfinished time-bounded sweep resumes.
+#[derive(Default)]
+struct SweepCursor {
+    /// Index of the next task group to collect ready tasks from.
+    next_group_index: usize,
+    /// Ready tasks (group index and offset in the group) collected but not yet polled.
+    ready: VecDeque<(usize, usize)>,
+}
+
 /// Task Scheduler
 pub struct Scheduler {
     ids: IdMap<TaskId, InternalId>,
     groups: Slab<TaskGroup>,
     current_task: TaskId,
+    /// User-supplied function to run once at the start of every poll sweep.
+    per_sweep_hook: Option<Box<dyn FnMut()>>,
+    /// Are we currently running the per-sweep hook? Used to prevent the hook from re-entering the poll loop.
+    in_per_sweep_hook: bool,
+    /// Host reactor waker to notify whenever a task becomes ready to run.
+    external_waker: Option<Waker>,
+    /// Resume point of a time-bounded sweep that ran out of time, if any.
+    sweep_cursor: Option<SweepCursor>,
+    /// Longest that a single poll of a task may take before it is logged as slow, if any.
+    poll_watchdog: Option<Duration>,
+    /// Generator that seeds the id generators of new task groups, if ids are drawn from an injected generator.
+    rng: Option<SmallRng>,
+    /// Tasks that completed since they were last taken, if completions are tracked.
+    completed_tasks: Option<Vec<TaskId>>,
+    /// Are insertion times of tasks recorded, for [Self::oldest_pending_task]?
+    track_pending_tasks: bool,
+    /// Time that tasks spent between being notified and being polled.
+    #[cfg(feature = "scheduler-latency")]
+    latency: LatencyHistogram,
 }
 
 //======================================================================================================================
@@ -41,12 +118,86 @@ pub struct Scheduler {
 //======================================================================================================================
 
 impl Scheduler {
+    /// Creates a scheduler whose task ids, and hence queue tokens, are drawn from a generator seeded with `seed`. Two
+    /// schedulers created with the same seed hand out the same sequence of ids, in release builds too, so that a run can
+    /// be replayed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::new_with_rng(SmallRng::seed_from_u64(seed))
+    }
+
+    /// Creates a scheduler whose task ids, and hence queue tokens, are drawn from `rng`.
+    pub fn new_with_rng(rng: SmallRng) -> Self {
+        Self::new(Some(rng))
+    }
+
+    /// Creates a scheduler with only the root task group. Ids are drawn from `rng` if given, and from the default id
+    /// generator otherwise.
+    fn new(mut rng: Option<SmallRng>) -> Self {
+        let (group, mut ids): (TaskGroup, IdMap<TaskId, InternalId>) = match rng {
+            Some(ref mut rng) => (
+                TaskGroup::with_rng(SmallRng::seed_from_u64(rng.next_u64())),
+                IdMap::with_rng(SmallRng::seed_from_u64(rng.next_u64())),
+            ),
+            None => (TaskGroup::default(), IdMap::<TaskId, InternalId>::default()),
+        };
+        let mut groups: Slab<TaskGroup> = Slab::<TaskGroup>::default();
+        let internal_id: InternalId = groups.insert(group).into();
+        // Use 0 as a special task id for the root.
+        let current_task: TaskId = TaskId::from(0);
+        ids.insert(current_task, internal_id);
+        Self {
+            ids,
+            groups,
+            current_task,
+            per_sweep_hook: None,
+            in_per_sweep_hook: false,
+            external_waker: None,
+            sweep_cursor: None,
+            poll_watchdog: None,
+            rng,
+            completed_tasks: None,
+            track_pending_tasks: false,
+            #[cfg(feature = "scheduler-latency")]
+            latency: LatencyHistogram::default(),
+        }
+    }
+
     /// Creates a new task group. Returns an identifier for the group.
     pub fn create_group(&mut self) -> TaskId {
-        let internal_id: InternalId = self.groups.insert(TaskGroup::default()).into();
+        let group: TaskGroup = match self.rng {
+            Some(ref mut rng) => TaskGroup::with_rng(SmallRng::seed_from_u64(rng.next_u64())),
+            None => TaskGroup::default(),
+        };
+        let internal_id: InternalId = self.groups.insert(group).into();
         self.ids.insert_with_new_id(internal_id)
     }
 
+    /// Creates a new task group whose task ids resolve according to `mappings`, as exported by
+    /// [Self::export_group_id_map]. Returns an identifier for the group.
+    pub fn create_group_with_id_map(&mut self, mappings: Vec<(TaskId, InternalId)>) -> TaskId {
+        let internal_id: InternalId = self.groups.insert(TaskGroup::with_id_map(mappings)).into();
+        self.ids.insert_with_new_id(internal_id)
+    }
+
+    /// Exports the mappings between the task ids of the group `group_id` and their internal ids, so that a group
+    /// created from them after a restart resolves the same task ids. Returns `None` if there is no such group.
+    pub fn export_group_id_map(&self, group_id: TaskId) -> Option<Vec<(TaskId, InternalId)>> {
+        Some(self.get_group(&group_id)?.export_id_map())
+    }
+
+    /// Limits the number of tasks that the group `group_id` may hold at once to `quota`, so that inserting into a full
+    /// group fails, which the runtime reports as `EAGAIN`. Groups have no quota by default. Returns false if there is
+    /// no such group.
+    pub fn set_group_quota(&mut self, group_id: TaskId, quota: usize) -> bool {
+        match self.get_mut_group(&group_id) {
+            Some(group) => {
+                group.set_quota(quota);
+                true
+            },
+            None => false,
+        }
+    }
+
     pub fn switch_group(&mut self, group_id: TaskId) -> Option<TaskId> {
         if let Some(internal_id) = self.ids.get(&group_id) {
             if self.groups.contains(internal_id.into()) {
@@ -91,14 +242,25 @@ impl Scheduler {
         // Use that to find the task group for this task.
         let group: &mut TaskGroup = self.groups.get_mut(group_id.into())?;
         // Insert the task into the task group.
-        let new_task_id: TaskId = group.insert(Box::new(task))?;
+        let new_task_id: TaskId = group.insert(Box::new(task), self.track_pending_tasks)?;
         // Add a mapping so we can use this new task id to find the task in the future.
         if let Some(existing) = self.ids.insert(new_task_id, group_id) {
             panic!("should not exist an id: {:?}", existing);
         }
+        // New tasks are ready to run.
+        self.wake_external_waker();
         Some(new_task_id)
     }
 
+    /// Insert a task into the current task group, like [insert_task](Self::insert_task), to run at `priority`.
+    pub fn insert_task_with_priority<T: Task>(&mut self, task: T, priority: Priority) -> Option<TaskId> {
+        let task_id: TaskId = self.insert_task(task)?;
+        if priority != Priority::Normal {
+            self.set_priority(task_id, priority);
+        }
+        Some(task_id)
+    }
+
     /// Insert a task into a task group. The parent id can either be the id of the group or another task in the same
     /// group.
     pub fn insert_task_with_group_id<T: Task>(&mut self, group_id: TaskId, task: T) -> Option<TaskId> {
@@ -107,12 +269,57 @@ impl Scheduler {
         // Use that to find the task group for this task.
         let group: &mut TaskGroup = self.groups.get_mut(group_id.into())?;
         // Insert the task into the task group.
-        let new_task_id: TaskId = group.insert(Box::new(task))?;
+        let new_task_id: TaskId = group.insert(Box::new(task), self.track_pending_tasks)?;
         // Add a mapping so we can use this new task id to find the task in the future.
         self.ids.insert(new_task_id, group_id);
+        // New tasks are ready to run.
+        self.wake_external_waker();
         Some(new_task_id)
     }
 
+    /// Marks a task as ready to run, so that the next sweep polls it even though nothing woke it. This is a shortcut
+    /// for code that knows that a task can make progress but does not hold its [Waker]. Returns false if there is no
+    /// such task.
+    pub fn notify(&mut self, task_id: TaskId) -> bool {
+        let notified: bool = match self.get_group(&task_id) {
+            Some(group) => group.notify(task_id).is_some(),
+            None => false,
+        };
+        if notified {
+            self.wake_external_waker();
+        }
+        notified
+    }
+
+    /// Returns a future that yields until a task completes, so that a coroutine can wait for another one. The task wakes
+    /// the future when it completes or is removed from the scheduler, so the future needs no reference to the
+    /// scheduler, and is not fooled by another task that takes over the slot. It resolves right away if there is no
+    /// such task, or if the task has already completed.
+    pub fn join(&mut self, task_id: TaskId, yielder: Yielder) -> impl Future<Output = ()> {
+        let joined: bool = match self.get_mut_group(&task_id) {
+            Some(group) => group.add_joiner(task_id, yielder.get_handle()),
+            None => false,
+        };
+        async move {
+            if joined {
+                if let Err(e) = yielder.yield_until_wake().await {
+                    warn!(
+                        "join(): stopped waiting for task (task_id={:?}, error={:?})",
+                        task_id, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Sets the priority of a task. Returns false if there is no such task.
+    pub fn set_priority(&mut self, task_id: TaskId, priority: Priority) -> bool {
+        match self.get_mut_group(&task_id) {
+            Some(group) => group.set_priority(task_id, priority).is_some(),
+            None => false,
+        }
+    }
+
     pub fn remove_task(&mut self, task_id: TaskId) -> Option<Box<dyn Task>> {
         // Use that to find the task group for this task.
         let group: &mut TaskGroup = self.get_mut_group(&task_id)?;
@@ -130,29 +337,272 @@ impl Scheduler {
 
         let ready_indices: Vec<usize> = self.groups[group_index].get_offsets_for_ready_tasks();
         for pin_slab_index in ready_indices {
-            // Set the current running task for polling this task. This ensures that all tasks spawned by this task
-            // will share the same task group.
-            let old_task: TaskId = self.current_task;
-            self.current_task = self.groups[group_index].get_id(pin_slab_index);
-            self.groups[group_index].poll_notified_task(pin_slab_index);
-            // Unset the current running task.
-            self.current_task = old_task;
-            polled_tasks += 1;
+            // A task polled earlier in the sweep may have removed this one, e.g. by closing the socket that it served.
+            if self.is_ready_task(group_index, pin_slab_index) {
+                self.poll_task(group_index, pin_slab_index);
+                polled_tasks += 1;
+            }
+        }
+        polled_tasks
+    }
+
+    /// Polls a single notified task of a task group.
+    fn poll_task(&mut self, group_index: usize, pin_slab_index: usize) {
+        // Set the current running task for polling this task. This ensures that all tasks spawned by this task will
+        // share the same task group.
+        let old_task: TaskId = self.current_task;
+        self.current_task = self.groups[group_index].get_id(pin_slab_index);
+        #[cfg(feature = "scheduler-latency")]
+        if let Some(latency) = self.groups[group_index].take_notify_latency(pin_slab_index) {
+            self.latency.record(latency);
+        }
+        let completed: Option<bool> = self.groups[group_index].poll_notified_task(
+            pin_slab_index,
+            self.external_waker.as_ref(),
+            self.poll_watchdog,
+        );
+        if let (Some(true), Some(completed_tasks)) = (completed, self.completed_tasks.as_mut()) {
+            completed_tasks.push(self.current_task);
+        }
+        // Unset the current running task.
+        self.current_task = old_task;
+    }
+
+    /// Polls the ready tasks left over by an unfinished time-bounded sweep, if any.
+    fn finish_bounded_sweep(&mut self) -> usize {
+        let mut polled_tasks: usize = 0;
+        if let Some(cursor) = self.sweep_cursor.take() {
+            for (group_index, pin_slab_index) in cursor.ready {
+                if self.is_ready_task(group_index, pin_slab_index) {
+                    self.poll_task(group_index, pin_slab_index);
+                    polled_tasks += 1;
+                }
+            }
         }
         polled_tasks
     }
 
+    /// Checks whether any task group holds a high-priority task.
+    fn has_high_priority_tasks(&self) -> bool {
+        self.groups.iter().any(|(_, group)| group.has_high_priority_tasks())
+    }
+
+    /// Collects the ready tasks of all task groups, high-priority tasks first.
+    fn collect_ready_tasks_by_priority(&mut self) -> VecDeque<(usize, usize)> {
+        let mut ready: VecDeque<(usize, usize)> = VecDeque::new();
+        let mut normal: Vec<(usize, usize)> = Vec::new();
+        for group_index in 0..self.groups.len() {
+            if let Some(group) = self.groups.get_mut(group_index) {
+                for pin_slab_index in group.get_offsets_for_ready_tasks() {
+                    match group.get_priority(pin_slab_index) {
+                        Priority::High => ready.push_back((group_index, pin_slab_index)),
+                        Priority::Normal => normal.push((group_index, pin_slab_index)),
+                    }
+                }
+            }
+        }
+        ready.extend(normal);
+        ready
+    }
+
+    /// Checks whether a task collected by a sweep still exists. Tasks and groups may be removed by the tasks polled
+    /// earlier in the sweep, or between two calls of a time-bounded sweep.
+    fn is_ready_task(&self, group_index: usize, pin_slab_index: usize) -> bool {
+        match self.groups.get(group_index) {
+            Some(group) => group.contains(pin_slab_index),
+            None => false,
+        }
+    }
+
+    /// Registers a waker of the host reactor, replacing any previously registered one. It is woken whenever a task is
+    /// inserted or notified, so that the host knows when to call [poll_all](Self::poll_all) instead of busy polling.
+    /// Tasks only pick up the waker the next time that they are polled.
+    pub fn set_external_waker(&mut self, waker: Waker) {
+        self.external_waker = Some(waker);
+    }
+
+    /// Removes the external waker, if any.
+    pub fn clear_external_waker(&mut self) {
+        self.external_waker = None;
+    }
+
+    /// Wakes the external waker, if one is registered.
+    fn wake_external_waker(&self) {
+        if let Some(waker) = self.external_waker.as_ref() {
+            waker.wake_by_ref();
+        }
+    }
+
+    /// Logs a warning with the name of the task whenever a single poll of a task takes longer than `threshold`. A task
+    /// that does synchronous work for that long stalls every other task of the scheduler.
+    pub fn set_poll_watchdog(&mut self, threshold: Duration) {
+        self.poll_watchdog = Some(threshold);
+    }
+
+    /// Records the tasks that complete from now on, so that they can be taken with [Self::take_completed_tasks] instead
+    /// of checking every task for completion.
+    pub fn track_completions(&mut self) {
+        self.completed_tasks.get_or_insert_with(Vec::new);
+    }
+
+    /// Records when tasks are inserted from now on, so that [Self::oldest_pending_task] can report them. This is off by
+    /// default, since it reads the clock on every insertion.
+    pub fn track_pending_tasks(&mut self) {
+        self.track_pending_tasks = true;
+    }
+
+    /// Moves the tasks that completed since the last call into `completed`, in the order in which they completed.
+    /// Moves nothing unless completions are tracked.
+    pub fn take_completed_tasks(&mut self, completed: &mut Vec<TaskId>) {
+        if let Some(completed_tasks) = self.completed_tasks.as_mut() {
+            completed.append(completed_tasks);
+        }
+    }
+
+    /// Returns the percentiles of the time that tasks spent between being notified, or inserted, and being polled, across
+    /// all task groups and since the scheduler was created.
+    #[cfg(feature = "scheduler-latency")]
+    pub fn latency_percentiles(&self) -> LatencyStats {
+        self.latency.stats()
+    }
+
+    /// Returns the number of polls that exceeded the poll watchdog, across all task groups.
+    pub fn get_slow_polls(&self) -> usize {
+        self.groups.iter().map(|(_, group)| group.get_slow_polls()).sum()
+    }
+
+    /// Installs a hook that runs once at the start of every call to [poll_all](Self::poll_all), replacing any
+    /// previously installed hook. The hook must not poll the scheduler; if it does, that poll is ignored.
+    pub fn set_per_sweep_hook(&mut self, hook: Box<dyn FnMut()>) {
+        self.per_sweep_hook = Some(hook);
+    }
+
+    /// Removes the per-sweep hook, if any.
+    pub fn clear_per_sweep_hook(&mut self) {
+        self.per_sweep_hook = None;
+    }
+
+    /// Runs the per-sweep hook, if one is installed.
+    fn run_per_sweep_hook(&mut self) {
+        if let Some(mut hook) = self.per_sweep_hook.take() {
+            self.in_per_sweep_hook = true;
+            hook();
+            self.in_per_sweep_hook = false;
+            // Put the hook back, unless it was replaced or cleared while running.
+            if self.per_sweep_hook.is_none() {
+                self.per_sweep_hook = Some(hook);
+            }
+        }
+    }
+
     /// Poll all tasks which are ready to run once. Tasks in our scheduler are notified when
     /// relevant data or events happen. The relevant event have callback function (the waker) which
     /// they can invoke to notify the scheduler that future should be polled again.
     pub fn poll_all(&mut self) -> usize {
-        let mut polled_tasks: usize = 0;
-        for i in 0..self.groups.len() {
-            polled_tasks += self.poll(i);
+        if self.in_per_sweep_hook {
+            warn!("poll_all(): cannot poll the scheduler from the per-sweep hook");
+            return 0;
+        }
+        self.run_per_sweep_hook();
+
+        // Tasks collected by an unfinished time-bounded sweep are no longer flagged as notified, so poll them first.
+        let mut polled_tasks: usize = self.finish_bounded_sweep();
+        if self.has_high_priority_tasks() {
+            // Collect the ready tasks of all groups up front, so that high-priority tasks run first.
+            for (group_index, pin_slab_index) in self.collect_ready_tasks_by_priority() {
+                if self.is_ready_task(group_index, pin_slab_index) {
+                    self.poll_task(group_index, pin_slab_index);
+                    polled_tasks += 1;
+                }
+            }
+        } else {
+            for i in 0..self.groups.len() {
+                polled_tasks += self.poll(i);
+            }
         }
         polled_tasks
     }
 
+    /// Poll ready tasks, like [poll_all](Self::poll_all), but stop once `max` has elapsed. A sweep that runs out of time
+    /// is resumed from where it stopped by the next call, so that every ready task eventually gets polled. At least
+    /// one task is polled per call, if any is ready.
+    pub fn poll_bounded(&mut self, max: Duration) -> PollOutcome {
+        if self.in_per_sweep_hook {
+            warn!("poll_bounded(): cannot poll the scheduler from the per-sweep hook");
+            return PollOutcome {
+                polled_tasks: 0,
+                truncated: false,
+            };
+        }
+        // Only run the hook at the start of a new sweep.
+        if self.sweep_cursor.is_none() {
+            self.run_per_sweep_hook();
+        }
+
+        let start: Instant = Instant::now();
+        let mut cursor: SweepCursor = match self.sweep_cursor.take() {
+            Some(cursor) => cursor,
+            // Collect the ready tasks of all groups up front, so that high-priority tasks run first.
+            None if self.has_high_priority_tasks() => SweepCursor {
+                next_group_index: self.groups.len(),
+                ready: self.collect_ready_tasks_by_priority(),
+            },
+            None => SweepCursor::default(),
+        };
+        let mut polled_tasks: usize = 0;
+        loop {
+            let (group_index, pin_slab_index): (usize, usize) = match cursor.ready.pop_front() {
+                Some(task) => task,
+                None if cursor.next_group_index < self.groups.len() => {
+                    // Collect the ready tasks of the next group.
+                    let group_index: usize = cursor.next_group_index;
+                    cursor.next_group_index += 1;
+                    if let Some(group) = self.groups.get_mut(group_index) {
+                        for pin_slab_index in group.get_offsets_for_ready_tasks() {
+                            cursor.ready.push_back((group_index, pin_slab_index));
+                        }
+                    }
+                    continue;
+                },
+                None => {
+                    return PollOutcome {
+                        polled_tasks,
+                        truncated: false,
+                    }
+                },
+            };
+
+            if self.is_ready_task(group_index, pin_slab_index) {
+                self.poll_task(group_index, pin_slab_index);
+                polled_tasks += 1;
+            }
+
+            let has_more: bool = !cursor.ready.is_empty() || cursor.next_group_index < self.groups.len();
+            if has_more && start.elapsed() > max {
+                self.sweep_cursor = Some(cursor);
+                return PollOutcome {
+                    polled_tasks,
+                    truncated: true,
+                };
+            }
+        }
+    }
+
+    /// Sweeps the scheduler with [poll_all](Self::poll_all) until `cond` holds or `max_iters` sweeps have run, and
+    /// returns whether `cond` holds. Stops early once a sweep finds no task to poll, since nothing can make progress
+    /// until some task is notified from outside the scheduler.
+    pub fn poll_until<F: Fn() -> bool>(&mut self, cond: F, max_iters: usize) -> bool {
+        for _ in 0..max_iters {
+            if cond() {
+                return true;
+            }
+            if self.poll_all() == 0 {
+                break;
+            }
+        }
+        cond()
+    }
+
     /// Poll all tasks in this group that are ready to run.
     pub fn poll_group(&mut self, group_id: TaskId) -> Option<usize> {
         Some(self.poll(self.ids.get(&group_id)?.into()))
@@ -164,6 +614,28 @@ impl Scheduler {
         group.has_completed(task_id)
     }
 
+    /// Returns the occupancy of the task slabs across all task groups. Slabs never shrink, so occupied slots that keep
+    /// growing while the number of free ones stays low usually mean that coroutines are leaking.
+    pub fn slab_stats(&self) -> SlabStats {
+        let mut stats: SlabStats = SlabStats::default();
+        for (_, group) in self.groups.iter() {
+            let group_stats: SlabStats = group.slab_stats();
+            stats.free_slots += group_stats.free_slots;
+            stats.occupied_slots += group_stats.occupied_slots;
+        }
+        stats
+    }
+
+    /// Returns the id, name and insertion time of the task that was inserted the longest ago and has not completed yet,
+    /// across all task groups. A task that stays at the top for long is a good candidate for a stuck coroutine. Only
+    /// tasks inserted while [Self::track_pending_tasks] is on are reported.
+    pub fn oldest_pending_task(&self) -> Option<(TaskId, String, Instant)> {
+        self.groups
+            .iter()
+            .filter_map(|(_, group)| group.oldest_pending_task())
+            .min_by_key(|(_, _, inserted_at)| *inserted_at)
+    }
+
     #[cfg(test)]
     pub fn is_valid_task(&self, task_id: &TaskId) -> bool {
         if let Some(group) = self.get_group(task_id) {
@@ -189,18 +661,7 @@ impl Scheduler {
 
 impl Default for Scheduler {
     fn default() -> Self {
-        let group: TaskGroup = TaskGroup::default();
-        let mut ids: IdMap<TaskId, InternalId> = IdMap::<TaskId, InternalId>::default();
-        let mut groups: Slab<TaskGroup> = Slab::<TaskGroup>::default();
-        let internal_id: InternalId = groups.insert(group).into();
-        // Use 0 as a special task id for the root.
-        let current_task: TaskId = TaskId::from(0);
-        ids.insert(current_task, internal_id);
-        Self {
-            ids,
-            groups,
-            current_task,
-        }
+        Self::new(None)
     }
 }
 
@@ -234,23 +695,58 @@ impl From<InternalId> for u64 {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "scheduler-latency")]
+    use crate::runtime::scheduler::LatencyStats;
     use crate::runtime::scheduler::{
         scheduler::{
+            InternalId,
+            PollOutcome,
+            Priority,
             Scheduler,
+            SlabStats,
             TaskId,
         },
         task::TaskWithResult,
+        Yielder,
     };
     use ::anyhow::Result;
-    use ::futures::FutureExt;
+    use ::futures::{
+        channel::oneshot,
+        FutureExt,
+    };
+    use ::rand::{
+        rngs::SmallRng,
+        SeedableRng,
+    };
     use ::std::{
-        future::Future,
+        cell::{
+            Cell,
+            RefCell,
+        },
+        future::{
+            self,
+            Future,
+        },
         pin::Pin,
+        rc::Rc,
+        sync::{
+            atomic::{
+                AtomicUsize,
+                Ordering,
+            },
+            Arc,
+        },
         task::{
             Context,
             Poll,
+            Wake,
             Waker,
         },
+        thread,
+        time::{
+            Duration,
+            Instant,
+        },
     };
     use ::test::{
         black_box,
@@ -286,6 +782,36 @@ mod tests {
 
     type DummyTask = TaskWithResult<()>;
 
+    /// Coroutine that never completes and hands its waker out, so that it can be woken after it is removed.
+    struct WakerLeakingCoroutine {
+        waker: Rc<Cell<Option<Waker>>>,
+    }
+
+    impl Future for WakerLeakingCoroutine {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+            self.waker.set(Some(ctx.waker().clone()));
+            Poll::Pending
+        }
+    }
+
+    /// Waker that counts how many times it has been woken.
+    #[derive(Default)]
+    struct CountingWaker {
+        count: AtomicUsize,
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
     /// Tests if when inserting multiple tasks into the scheduler at once each, of them gets a unique identifier.
     #[test]
     fn insert_creates_unique_tasks_ids() -> Result<()> {
@@ -399,6 +925,38 @@ mod tests {
         Ok(())
     }
 
+    /// Tests if schedulers that draw their ids from generators seeded alike hand out the same task ids, in every group.
+    #[test]
+    fn seeded_schedulers_hand_out_same_task_ids() -> Result<()> {
+        fn task_ids(mut scheduler: Scheduler) -> Result<Vec<TaskId>> {
+            let mut task_ids: Vec<TaskId> = Vec::new();
+            for _ in 0..2 {
+                for _ in 0..8 {
+                    let task: DummyTask =
+                        DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(0).fuse()));
+                    let Some(task_id) = scheduler.insert_task(task) else {
+                        anyhow::bail!("insert() failed")
+                    };
+                    task_ids.push(task_id);
+                }
+                let group_id: TaskId = scheduler.create_group();
+                task_ids.push(group_id);
+                scheduler.switch_group(group_id);
+            }
+            Ok(task_ids)
+        }
+
+        let task_ids_7: Vec<TaskId> = task_ids(Scheduler::with_seed(7))?;
+        crate::ensure_eq!(task_ids(Scheduler::with_seed(7))?, task_ids_7);
+        crate::ensure_eq!(
+            task_ids(Scheduler::new_with_rng(SmallRng::seed_from_u64(7)))?,
+            task_ids_7
+        );
+        crate::ensure_neq!(task_ids(Scheduler::with_seed(8))?, task_ids_7);
+
+        Ok(())
+    }
+
     #[test]
     fn remove_removes_task_id() -> Result<()> {
         let mut scheduler: Scheduler = Scheduler::default();
@@ -438,6 +996,760 @@ mod tests {
         Ok(())
     }
 
+    /// Tests that waking a task after it was removed does not make the scheduler poll its empty slot, since removing a
+    /// task marks its slot as completed.
+    #[test]
+    fn stale_waker_of_removed_task_is_ignored() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        let waker: Rc<Cell<Option<Waker>>> = Rc::new(Cell::new(None));
+        let coroutine: WakerLeakingCoroutine = WakerLeakingCoroutine { waker: waker.clone() };
+        let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(coroutine.fuse()));
+        let Some(task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        scheduler.poll_all();
+
+        // Remove the task, then wake it through the waker that it handed out.
+        scheduler.remove_task(task_id);
+        let Some(stale_waker) = waker.take() else {
+            anyhow::bail!("task should have been polled")
+        };
+        stale_waker.wake();
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.num_tasks(), 0);
+
+        Ok(())
+    }
+
+    /// Tests if the per-sweep hook runs exactly once for each call to poll.
+    #[test]
+    fn per_sweep_hook_runs_once_per_poll() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        let count: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+        let hook_count: Rc<Cell<usize>> = count.clone();
+        scheduler.set_per_sweep_hook(Box::new(move || hook_count.set(hook_count.get() + 1)));
+
+        // Insert a long-running task so that some sweeps have work to do and others do not.
+        let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(1).fuse()));
+        let Some(task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+
+        const NUM_SWEEPS: usize = 5;
+        for i in 1..=NUM_SWEEPS {
+            scheduler.poll_all();
+            crate::ensure_eq!(count.get(), i);
+        }
+        crate::ensure_eq!(
+            scheduler
+                .has_completed(task_id)
+                .expect("should find task completion status"),
+            true
+        );
+
+        // Once cleared, the hook should no longer run.
+        scheduler.clear_per_sweep_hook();
+        scheduler.poll_all();
+        crate::ensure_eq!(count.get(), NUM_SWEEPS);
+
+        Ok(())
+    }
+
+    /// Tests if the external waker is woken when a task is notified by an event from outside the scheduler.
+    #[test]
+    fn external_waker_woken_on_notification() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        let external: Arc<CountingWaker> = Arc::new(CountingWaker::default());
+        scheduler.set_external_waker(Waker::from(external.clone()));
+
+        // Insert a task that waits on an event that we trigger from outside the scheduler.
+        let (tx, rx): (oneshot::Sender<()>, oneshot::Receiver<()>) = oneshot::channel();
+        let task: DummyTask = DummyTask::new(
+            String::from("testing"),
+            Box::pin(
+                async move {
+                    let _ = rx.await;
+                }
+                .fuse(),
+            ),
+        );
+        let Some(task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        crate::ensure_eq!(external.count.load(Ordering::Relaxed), 1);
+
+        // The task blocks on the event, so nothing should wake the external waker.
+        scheduler.poll_all();
+        crate::ensure_eq!(external.count.load(Ordering::Relaxed), 1);
+        crate::ensure_eq!(
+            scheduler
+                .has_completed(task_id)
+                .expect("should find task completion status"),
+            false
+        );
+
+        // Triggering the event should notify the task and wake the external waker.
+        tx.send(()).expect("receiver should be alive");
+        crate::ensure_eq!(external.count.load(Ordering::Relaxed), 2);
+        scheduler.poll_all();
+        crate::ensure_eq!(
+            scheduler
+                .has_completed(task_id)
+                .expect("should find task completion status"),
+            true
+        );
+
+        Ok(())
+    }
+
+    /// Tests if a task gets the same waker on every poll while the same external waker stays registered, and a new one
+    /// once another external waker is registered.
+    #[test]
+    fn external_waker_reused_across_polls() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        scheduler.set_external_waker(Waker::from(Arc::new(CountingWaker::default())));
+
+        // Insert a task that records the waker of every poll and never completes.
+        let wakers: Rc<RefCell<Vec<Waker>>> = Rc::new(RefCell::new(Vec::new()));
+        let task_wakers: Rc<RefCell<Vec<Waker>>> = wakers.clone();
+        let task: DummyTask = DummyTask::new(
+            String::from("testing"),
+            Box::pin(
+                future::poll_fn(move |ctx: &mut Context| -> Poll<()> {
+                    task_wakers.borrow_mut().push(ctx.waker().clone());
+                    Poll::Pending
+                })
+                .fuse(),
+            ),
+        );
+        let Some(task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        scheduler.poll_all();
+        scheduler.notify(task_id);
+        scheduler.poll_all();
+        scheduler.set_external_waker(Waker::from(Arc::new(CountingWaker::default())));
+        scheduler.notify(task_id);
+        scheduler.poll_all();
+
+        let wakers: Vec<Waker> = wakers.take();
+        crate::ensure_eq!(wakers.len(), 3);
+        crate::ensure_eq!(wakers[0].will_wake(&wakers[1]), true);
+        crate::ensure_eq!(wakers[1].will_wake(&wakers[2]), false);
+
+        Ok(())
+    }
+
+    /// Tests if a time-bounded poll truncates a large sweep and resumes it until every task has been polled.
+    #[test]
+    fn poll_bounded_resumes_truncated_sweep() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+
+        // Insert many tasks that complete the first time that they are polled.
+        const NUM_TASKS: usize = 256;
+        let mut task_ids: Vec<TaskId> = Vec::<TaskId>::with_capacity(NUM_TASKS);
+        for val in 0..NUM_TASKS {
+            let task: DummyTask =
+                DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(val * 2).fuse()));
+            let Some(task_id) = scheduler.insert_task(task) else {
+                anyhow::bail!("insert() failed")
+            };
+            task_ids.push(task_id);
+        }
+
+        // With no time budget, every call stops after the first task.
+        let outcome: PollOutcome = scheduler.poll_bounded(Duration::ZERO);
+        crate::ensure_eq!(outcome.polled_tasks, 1);
+        crate::ensure_eq!(outcome.truncated, true);
+
+        // Keep polling until the sweep finishes.
+        let mut polled_tasks: usize = outcome.polled_tasks;
+        loop {
+            let outcome: PollOutcome = scheduler.poll_bounded(Duration::ZERO);
+            polled_tasks += outcome.polled_tasks;
+            if !outcome.truncated {
+                break;
+            }
+        }
+        crate::ensure_eq!(polled_tasks, NUM_TASKS);
+        for task_id in task_ids {
+            crate::ensure_eq!(
+                scheduler
+                    .has_completed(task_id)
+                    .expect("should find task completion status"),
+                true
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Tests if high-priority tasks are polled before normal-priority ones, so that they complete in fewer sweeps.
+    #[test]
+    fn high_priority_tasks_run_first() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+
+        // Insert bulk tasks first, and then a high-priority task. All of them complete the first time that they are
+        // polled.
+        const NUM_BULK_TASKS: usize = 8;
+        let mut bulk_task_ids: Vec<TaskId> = Vec::<TaskId>::with_capacity(NUM_BULK_TASKS);
+        for _ in 0..NUM_BULK_TASKS {
+            let task: DummyTask = DummyTask::new(String::from("bulk"), Box::pin(DummyCoroutine::new(0).fuse()));
+            let Some(task_id) = scheduler.insert_task(task) else {
+                anyhow::bail!("insert() failed")
+            };
+            bulk_task_ids.push(task_id);
+        }
+        let task: DummyTask = DummyTask::new(String::from("urgent"), Box::pin(DummyCoroutine::new(0).fuse()));
+        let Some(urgent_task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        crate::ensure_eq!(scheduler.set_priority(urgent_task_id, Priority::High), true);
+
+        // With no time budget, every call polls a single task. The high-priority task goes first.
+        scheduler.poll_bounded(Duration::ZERO);
+        crate::ensure_eq!(scheduler.has_completed(urgent_task_id), Some(true));
+        for task_id in bulk_task_ids.iter() {
+            crate::ensure_eq!(scheduler.has_completed(*task_id), Some(false));
+        }
+
+        // The bulk tasks follow in the remaining sweeps.
+        for _ in 0..NUM_BULK_TASKS {
+            scheduler.poll_bounded(Duration::ZERO);
+        }
+        for task_id in bulk_task_ids.iter() {
+            crate::ensure_eq!(scheduler.has_completed(*task_id), Some(true));
+        }
+
+        Ok(())
+    }
+
+    /// Tests if normal-priority tasks are still polled in every sweep while a high-priority task keeps waking itself up.
+    #[test]
+    fn normal_priority_tasks_do_not_starve() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+
+        let busy_coroutine = future::poll_fn(|ctx: &mut Context| {
+            ctx.waker().wake_by_ref();
+            Poll::<()>::Pending
+        });
+        let task: DummyTask = DummyTask::new(String::from("busy"), Box::pin(busy_coroutine.fuse()));
+        let Some(busy_task_id) = scheduler.insert_task_with_priority(task, Priority::High) else {
+            anyhow::bail!("insert() failed")
+        };
+
+        // A full sweep polls the normal-priority task after the high-priority one.
+        let task: DummyTask = DummyTask::new(String::from("bulk"), Box::pin(DummyCoroutine::new(0).fuse()));
+        let Some(bulk_task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.has_completed(bulk_task_id), Some(true));
+        crate::ensure_eq!(scheduler.has_completed(busy_task_id), Some(false));
+
+        // A sweep that runs out of time after the high-priority task resumes with the normal-priority task, although the
+        // high-priority task is ready again.
+        let task: DummyTask = DummyTask::new(String::from("bulk"), Box::pin(DummyCoroutine::new(0).fuse()));
+        let Some(bulk_task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        scheduler.poll_bounded(Duration::ZERO);
+        crate::ensure_eq!(scheduler.has_completed(bulk_task_id), Some(false));
+        scheduler.poll_bounded(Duration::ZERO);
+        crate::ensure_eq!(scheduler.has_completed(bulk_task_id), Some(true));
+
+        Ok(())
+    }
+
+    /// Tests if the scheduler records how long tasks wait between being inserted and being polled.
+    #[cfg(feature = "scheduler-latency")]
+    #[test]
+    fn latency_percentiles_record_notify_to_poll_delay() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        crate::ensure_eq!(scheduler.latency_percentiles(), LatencyStats::default());
+
+        const NUM_TASKS: u64 = 16;
+        const DELAY: Duration = Duration::from_millis(2);
+        for _ in 0..NUM_TASKS {
+            let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(0).fuse()));
+            if scheduler.insert_task(task).is_none() {
+                anyhow::bail!("insert() failed")
+            }
+        }
+        thread::sleep(DELAY);
+        scheduler.poll_all();
+
+        // Every task waited at least as long as the sleep before its first poll, and not unreasonably long.
+        let stats: LatencyStats = scheduler.latency_percentiles();
+        crate::ensure_eq!(stats.count, NUM_TASKS);
+        crate::ensure_eq!(stats.p50 >= DELAY, true);
+        crate::ensure_eq!(stats.p50 <= stats.p99 && stats.p99 <= stats.max, true);
+        crate::ensure_eq!(stats.max < Duration::from_secs(5), true);
+
+        Ok(())
+    }
+
+    /// Tests if poll_until() sweeps until a condition that a task sets holds, and no longer.
+    #[test]
+    fn poll_until_stops_once_condition_holds() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        let sweeps: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+        let hook_sweeps: Rc<Cell<usize>> = sweeps.clone();
+        scheduler.set_per_sweep_hook(Box::new(move || hook_sweeps.set(hook_sweeps.get() + 1)));
+
+        // The task wakes itself up until its third poll, when it sets the flag and completes.
+        const NUM_POLLS: usize = 3;
+        let done: Rc<Cell<bool>> = Rc::new(Cell::new(false));
+        let task_done: Rc<Cell<bool>> = done.clone();
+        let mut polls: usize = 0;
+        let coroutine = future::poll_fn(move |ctx: &mut Context| {
+            polls += 1;
+            if polls == NUM_POLLS {
+                task_done.set(true);
+                return Poll::Ready(());
+            }
+            ctx.waker().wake_by_ref();
+            Poll::Pending
+        });
+        let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(coroutine.fuse()));
+        if scheduler.insert_task(task).is_none() {
+            anyhow::bail!("insert() failed")
+        }
+
+        crate::ensure_eq!(scheduler.poll_until(|| done.get(), 100), true);
+        crate::ensure_eq!(sweeps.get(), NUM_POLLS);
+
+        // A condition that already holds needs no sweep.
+        crate::ensure_eq!(scheduler.poll_until(|| done.get(), 100), true);
+        crate::ensure_eq!(sweeps.get(), NUM_POLLS);
+
+        Ok(())
+    }
+
+    /// Tests if poll_until() gives up once no task is notified, and after the given number of sweeps otherwise.
+    #[test]
+    fn poll_until_gives_up() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        let sweeps: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+        let hook_sweeps: Rc<Cell<usize>> = sweeps.clone();
+        scheduler.set_per_sweep_hook(Box::new(move || hook_sweeps.set(hook_sweeps.get() + 1)));
+
+        // A task that is never woken up again is polled once, and the next sweep finds nothing to poll.
+        let task: DummyTask = DummyTask::new(String::from("idle"), Box::pin(future::pending::<()>().fuse()));
+        let Some(idle_task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        crate::ensure_eq!(scheduler.poll_until(|| false, 100), false);
+        crate::ensure_eq!(sweeps.get(), 2);
+        scheduler.remove_task(idle_task_id);
+
+        // A task that keeps waking itself up keeps every sweep busy, so the number of sweeps bounds the wait.
+        sweeps.set(0);
+        let busy_coroutine = future::poll_fn(|ctx: &mut Context| {
+            ctx.waker().wake_by_ref();
+            Poll::<()>::Pending
+        });
+        let task: DummyTask = DummyTask::new(String::from("busy"), Box::pin(busy_coroutine.fuse()));
+        if scheduler.insert_task(task).is_none() {
+            anyhow::bail!("insert() failed")
+        }
+        const MAX_ITERS: usize = 10;
+        crate::ensure_eq!(scheduler.poll_until(|| false, MAX_ITERS), false);
+        crate::ensure_eq!(sweeps.get(), MAX_ITERS);
+
+        Ok(())
+    }
+
+    /// Tests if the slab statistics track tasks as they are inserted and removed.
+    #[test]
+    fn slab_stats_track_inserts_and_removes() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        crate::ensure_eq!(scheduler.slab_stats(), SlabStats::default());
+
+        // Insert enough tasks to spill over into a second slot of the slab.
+        const NUM_TASKS: usize = 20;
+        let mut task_ids: Vec<TaskId> = Vec::<TaskId>::with_capacity(NUM_TASKS);
+        for val in 0..NUM_TASKS {
+            let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(val).fuse()));
+            let Some(task_id) = scheduler.insert_task(task) else {
+                anyhow::bail!("insert() failed")
+            };
+            task_ids.push(task_id);
+        }
+        let capacity: usize = scheduler.slab_stats().free_slots + NUM_TASKS;
+        crate::ensure_eq!(scheduler.slab_stats().occupied_slots, NUM_TASKS);
+
+        // Remove every other task.
+        for task_id in task_ids.iter().step_by(2) {
+            crate::ensure_eq!(scheduler.remove_task(*task_id).is_some(), true);
+        }
+        let stats: SlabStats = scheduler.slab_stats();
+        crate::ensure_eq!(stats.occupied_slots, NUM_TASKS / 2);
+        crate::ensure_eq!(stats.free_slots, capacity - NUM_TASKS / 2);
+
+        // New tasks reuse the freed entries instead of growing the slab.
+        for val in 0..(NUM_TASKS / 2) {
+            let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(val).fuse()));
+            crate::ensure_eq!(scheduler.insert_task(task).is_some(), true);
+        }
+        let stats: SlabStats = scheduler.slab_stats();
+        crate::ensure_eq!(stats.occupied_slots, NUM_TASKS);
+        crate::ensure_eq!(stats.free_slots + stats.occupied_slots, capacity);
+
+        Ok(())
+    }
+
+    /// Tests if notifying a task that does not wake itself gets it polled again in the next sweep.
+    #[test]
+    fn notify_polls_task_in_next_sweep() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+
+        // Insert a task that never completes and never wakes itself.
+        let polls: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+        let polls_clone: Rc<Cell<usize>> = polls.clone();
+        let task: DummyTask = DummyTask::new(
+            String::from("testing"),
+            Box::pin(
+                future::poll_fn(move |_: &mut Context| -> Poll<()> {
+                    polls_clone.set(polls_clone.get() + 1);
+                    Poll::Pending
+                })
+                .fuse(),
+            ),
+        );
+        let Some(task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+
+        // New tasks run once, and then not again until they are notified.
+        scheduler.poll_all();
+        scheduler.poll_all();
+        crate::ensure_eq!(polls.get(), 1);
+
+        crate::ensure_eq!(scheduler.notify(task_id), true);
+        scheduler.poll_all();
+        crate::ensure_eq!(polls.get(), 2);
+        scheduler.poll_all();
+        crate::ensure_eq!(polls.get(), 2);
+
+        // Unknown tasks cannot be notified.
+        crate::ensure_eq!(scheduler.remove_task(task_id).is_some(), true);
+        crate::ensure_eq!(scheduler.notify(task_id), false);
+
+        Ok(())
+    }
+
+    /// Tests if the poll watchdog records a task whose poll takes too long, but not a task that polls quickly.
+    #[test]
+    fn poll_watchdog_records_slow_task() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        scheduler.set_poll_watchdog(Duration::from_millis(50));
+
+        let task: DummyTask = DummyTask::new(String::from("fast"), Box::pin(DummyCoroutine::new(0).fuse()));
+        if scheduler.insert_task(task).is_none() {
+            anyhow::bail!("insert() failed")
+        }
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.get_slow_polls(), 0);
+
+        // Insert a task that does synchronous work for longer than the watchdog allows.
+        let task: DummyTask = DummyTask::new(
+            String::from("slow"),
+            Box::pin(
+                future::poll_fn(|_: &mut Context| -> Poll<()> {
+                    thread::sleep(Duration::from_millis(100));
+                    Poll::Ready(())
+                })
+                .fuse(),
+            ),
+        );
+        if scheduler.insert_task(task).is_none() {
+            anyhow::bail!("insert() failed")
+        }
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.get_slow_polls(), 1);
+
+        Ok(())
+    }
+
+    /// Tests if inserting into a task group that has reached its quota fails, while the default group is unaffected.
+    #[test]
+    fn insert_into_full_group_fails() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        let group_id: TaskId = scheduler.create_group();
+        crate::ensure_eq!(scheduler.set_group_quota(group_id, 2), true);
+
+        for val in 0..2 {
+            let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(val).fuse()));
+            crate::ensure_eq!(scheduler.insert_task_with_group_id(group_id, task).is_some(), true);
+        }
+        let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(2).fuse()));
+        crate::ensure_eq!(scheduler.insert_task_with_group_id(group_id, task).is_none(), true);
+
+        // The default group has no quota.
+        let default_group_id: TaskId = TaskId::from(0);
+        for val in 0..3 {
+            let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(val).fuse()));
+            crate::ensure_eq!(
+                scheduler.insert_task_with_group_id(default_group_id, task).is_some(),
+                true
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Tests if a group created from the exported id map of another group resolves the same task ids to the same
+    /// internal ids.
+    #[test]
+    fn export_and_import_id_map() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        let group_id: TaskId = scheduler.create_group();
+
+        const NUM_TASKS: usize = 4;
+        let mut task_ids: Vec<TaskId> = Vec::<TaskId>::with_capacity(NUM_TASKS);
+        for val in 0..NUM_TASKS {
+            let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(val).fuse()));
+            let Some(task_id) = scheduler.insert_task_with_group_id(group_id, task) else {
+                anyhow::bail!("insert() failed")
+            };
+            task_ids.push(task_id);
+        }
+
+        let Some(mappings) = scheduler.export_group_id_map(group_id) else {
+            anyhow::bail!("export_group_id_map() failed")
+        };
+        crate::ensure_eq!(mappings.len(), NUM_TASKS);
+        let restored_group_id: TaskId = scheduler.create_group_with_id_map(mappings);
+
+        let Some(group) = scheduler.get_group(&group_id) else {
+            anyhow::bail!("group should exist")
+        };
+        let Some(restored_group) = scheduler.get_group(&restored_group_id) else {
+            anyhow::bail!("restored group should exist")
+        };
+        for task_id in task_ids.iter() {
+            let Some(internal_id) = group.get_internal_id(task_id) else {
+                anyhow::bail!("task id should resolve in the original group")
+            };
+            crate::ensure_eq!(restored_group.get_internal_id(task_id), Some(internal_id));
+        }
+
+        let imported_internal_ids: Vec<InternalId> = task_ids
+            .iter()
+            .filter_map(|task_id| restored_group.get_internal_id(task_id))
+            .collect();
+
+        // New tasks in the restored group reuse neither the imported task ids nor the slots that they resolve to.
+        let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(DummyCoroutine::new(0).fuse()));
+        let Some(new_task_id) = scheduler.insert_task_with_group_id(restored_group_id, task) else {
+            anyhow::bail!("insert() failed")
+        };
+        crate::ensure_eq!(task_ids.contains(&new_task_id), false);
+        let Some(restored_group) = scheduler.get_mut_group(&restored_group_id) else {
+            anyhow::bail!("restored group should exist")
+        };
+        let Some(new_internal_id) = restored_group.get_internal_id(&new_task_id) else {
+            anyhow::bail!("new task id should resolve")
+        };
+        crate::ensure_eq!(imported_internal_ids.contains(&new_internal_id), false);
+
+        // Removing an imported id frees its slot without handing back a task, and leaves the new task alone.
+        crate::ensure_eq!(restored_group.remove(task_ids[0]).is_none(), true);
+        crate::ensure_eq!(restored_group.get_internal_id(&new_task_id), Some(new_internal_id));
+        let Some(task) = restored_group.remove(new_task_id) else {
+            anyhow::bail!("new task should be removed")
+        };
+        crate::ensure_eq!(task.get_id(), new_task_id);
+        for task_id in task_ids[1..].iter() {
+            crate::ensure_eq!(restored_group.remove(*task_id).is_none(), true);
+        }
+
+        Ok(())
+    }
+
+    /// Tests if a task that joins another one completes only after the other one does.
+    #[test]
+    fn join_waits_for_task_completion() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+
+        // Insert a task that waits on an event that we trigger from outside the scheduler.
+        let (tx, rx): (oneshot::Sender<()>, oneshot::Receiver<()>) = oneshot::channel();
+        let first: DummyTask = DummyTask::new(
+            String::from("first"),
+            Box::pin(
+                async move {
+                    let _ = rx.await;
+                }
+                .fuse(),
+            ),
+        );
+        let Some(first_id) = scheduler.insert_task(first) else {
+            anyhow::bail!("insert() failed")
+        };
+
+        // Insert a task that joins the first one.
+        let join = scheduler.join(first_id, Yielder::new());
+        let second: DummyTask = DummyTask::new(String::from("second"), Box::pin(join.fuse()));
+        let Some(second_id) = scheduler.insert_task(second) else {
+            anyhow::bail!("insert() failed")
+        };
+
+        // Neither task completes while the first one waits on the event.
+        for _ in 0..4 {
+            scheduler.poll_all();
+            crate::ensure_eq!(scheduler.has_completed(first_id), Some(false));
+            crate::ensure_eq!(scheduler.has_completed(second_id), Some(false));
+        }
+
+        // Once the first task completes, so does the second one.
+        tx.send(()).expect("receiver should be alive");
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.has_completed(first_id), Some(true));
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.has_completed(second_id), Some(true));
+
+        Ok(())
+    }
+
+    /// Tests if a task that joins another one completes even if the other one is removed, and its slot taken over by a
+    /// new task that never completes, before the joining task gets polled again.
+    #[test]
+    fn join_survives_slot_reuse() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+
+        let (tx, rx): (oneshot::Sender<()>, oneshot::Receiver<()>) = oneshot::channel();
+        let first: DummyTask = DummyTask::new(
+            String::from("first"),
+            Box::pin(
+                async move {
+                    let _ = rx.await;
+                }
+                .fuse(),
+            ),
+        );
+        let Some(first_id) = scheduler.insert_task(first) else {
+            anyhow::bail!("insert() failed")
+        };
+        let join = scheduler.join(first_id, Yielder::new());
+        let second: DummyTask = DummyTask::new(String::from("second"), Box::pin(join.fuse()));
+        let Some(second_id) = scheduler.insert_task(second) else {
+            anyhow::bail!("insert() failed")
+        };
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.has_completed(second_id), Some(false));
+
+        // Remove the first task before it completes, and reuse its slot.
+        let internal_id = |scheduler: &Scheduler, task_id: TaskId| -> Option<InternalId> {
+            scheduler
+                .export_group_id_map(TaskId::from(0))?
+                .into_iter()
+                .find(|(id, _)| *id == task_id)
+                .map(|(_, internal_id)| internal_id)
+        };
+        let first_internal_id: Option<InternalId> = internal_id(&scheduler, first_id);
+        crate::ensure_eq!(scheduler.remove_task(first_id).is_some(), true);
+        drop(tx);
+        let third: DummyTask = DummyTask::new(String::from("third"), Box::pin(future::pending().fuse()));
+        let Some(third_id) = scheduler.insert_task(third) else {
+            anyhow::bail!("insert() failed")
+        };
+        crate::ensure_eq!(internal_id(&scheduler, third_id), first_internal_id);
+
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.has_completed(second_id), Some(true));
+        crate::ensure_eq!(scheduler.has_completed(third_id), Some(false));
+
+        Ok(())
+    }
+
+    /// Tests if the oldest pending task is the earliest inserted task that did not complete yet.
+    #[test]
+    fn oldest_pending_task_skips_completed_tasks() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+        scheduler.track_pending_tasks();
+        crate::ensure_eq!(scheduler.oldest_pending_task(), None);
+
+        // Insert tasks a few milliseconds apart, some of which complete right away and some of which wait on an event
+        // that we trigger from outside the scheduler.
+        let mut senders: Vec<oneshot::Sender<()>> = Vec::new();
+        let mut inserted: Vec<(TaskId, Instant, Instant)> = Vec::new();
+        for i in 0..4 {
+            let name: String = format!("task-{}", i);
+            let task: DummyTask = if i % 2 == 0 {
+                DummyTask::new(name, Box::pin(DummyCoroutine::new(0).fuse()))
+            } else {
+                let (tx, rx): (oneshot::Sender<()>, oneshot::Receiver<()>) = oneshot::channel();
+                senders.push(tx);
+                DummyTask::new(
+                    name,
+                    Box::pin(
+                        async move {
+                            let _ = rx.await;
+                        }
+                        .fuse(),
+                    ),
+                )
+            };
+            let before: Instant = Instant::now();
+            let Some(task_id) = scheduler.insert_task(task) else {
+                anyhow::bail!("insert() failed")
+            };
+            inserted.push((task_id, before, Instant::now()));
+            thread::sleep(Duration::from_millis(2));
+        }
+
+        // Before any poll, the first task is the oldest pending one.
+        let Some((task_id, name, inserted_at)) = scheduler.oldest_pending_task() else {
+            anyhow::bail!("there should be a pending task")
+        };
+        crate::ensure_eq!(task_id, inserted[0].0);
+        crate::ensure_eq!(name, String::from("task-0"));
+        crate::ensure_eq!(inserted_at >= inserted[0].1 && inserted_at <= inserted[0].2, true);
+
+        // Once the even tasks complete, the second task is the oldest pending one, even though the first one was not
+        // removed from the scheduler.
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.has_completed(inserted[0].0), Some(true));
+        let Some((task_id, name, inserted_at)) = scheduler.oldest_pending_task() else {
+            anyhow::bail!("there should be a pending task")
+        };
+        crate::ensure_eq!(task_id, inserted[1].0);
+        crate::ensure_eq!(name, String::from("task-1"));
+        crate::ensure_eq!(inserted_at >= inserted[1].1 && inserted_at <= inserted[1].2, true);
+
+        // Once the second task completes, the fourth one is left.
+        senders.remove(0).send(()).expect("receiver should be alive");
+        scheduler.poll_all();
+        crate::ensure_eq!(
+            scheduler.oldest_pending_task().map(|(task_id, _, _)| task_id),
+            Some(inserted[3].0)
+        );
+
+        // Once every task completes, none is pending.
+        senders.remove(0).send(()).expect("receiver should be alive");
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.oldest_pending_task(), None);
+
+        Ok(())
+    }
+
+    /// Tests if joining a task that does not exist completes right away.
+    #[test]
+    fn join_missing_task_completes_immediately() -> Result<()> {
+        let mut scheduler: Scheduler = Scheduler::default();
+
+        let join = scheduler.join(TaskId(u64::MAX), Yielder::new());
+        let task: DummyTask = DummyTask::new(String::from("testing"), Box::pin(join.fuse()));
+        let Some(task_id) = scheduler.insert_task(task) else {
+            anyhow::bail!("insert() failed")
+        };
+        scheduler.poll_all();
+        crate::ensure_eq!(scheduler.has_completed(task_id), Some(true));
+
+        Ok(())
+    }
+
     #[bench]
     fn benchmark_insert(b: &mut Bencher) {
         let mut scheduler: Scheduler = Scheduler::default();
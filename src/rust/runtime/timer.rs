@@ -37,8 +37,12 @@ use ::std::{
 //==============================================================================
 
 struct TimerQueueEntry {
+    /// Identifies the entry, so that it can be removed before it expires.
+    id: u64,
     expiry: Instant,
     yielder: YielderHandle,
+    /// What the coroutine is woken with when the entry expires.
+    result: Result<(), Fail>,
 }
 
 /// Timer that holds one or more events for future wake up.
@@ -46,11 +50,21 @@ pub struct Timer {
     now: Instant,
     // Use a reverse to get a min heap.
     heap: BinaryHeap<Reverse<TimerQueueEntry>>,
+    /// Identifier of the next entry.
+    next_id: u64,
 }
 
 #[derive(Clone)]
 pub struct SharedTimer(SharedObject<Timer>);
 
+/// Deadline set by [SharedTimer::expire_at]. Dropping it removes the deadline from the timer, so that the coroutine is
+/// not woken once it no longer waits for the deadline.
+#[must_use]
+pub struct TimerExpiry {
+    timer: SharedTimer,
+    id: u64,
+}
+
 //==============================================================================
 // Associate Functions
 //==============================================================================
@@ -60,6 +74,7 @@ impl SharedTimer {
         Self(SharedObject::<Timer>::new(Timer {
             now,
             heap: BinaryHeap::new(),
+            next_id: 0,
         }))
     }
 
@@ -75,7 +90,7 @@ impl SharedTimer {
                 .pop()
                 .expect("should have an entry because we were able to peek")
                 .0;
-            entry.yielder.wake_with(Ok(()));
+            entry.yielder.wake_with(entry.result);
         }
         self.now = now;
     }
@@ -91,12 +106,47 @@ impl SharedTimer {
 
     pub async fn wait_until(mut self, expiry: Instant, yielder: &Yielder) -> Result<(), Fail> {
         let entry = TimerQueueEntry {
+            id: self.alloc_entry_id(),
             expiry,
             yielder: yielder.get_handle(),
+            result: Ok(()),
         };
         self.heap.push(Reverse(entry));
         yielder.yield_until_wake().await
     }
+
+    /// Makes the coroutine that waits on `yielder_handle` fail with `ETIMEDOUT` once the clock reaches `deadline`, or
+    /// right away if it already has. Like a cancellation, this only takes effect where the coroutine waits. The
+    /// deadline holds until the returned [TimerExpiry] is dropped, which the coroutine should do once it completes.
+    pub fn expire_at(&mut self, deadline: Instant, mut yielder_handle: YielderHandle) -> TimerExpiry {
+        let id: u64 = self.alloc_entry_id();
+        let result: Result<(), Fail> = Err(Fail::new(libc::ETIMEDOUT, "deadline expired"));
+        if deadline <= self.now {
+            yielder_handle.wake_with(result);
+        } else {
+            self.heap.push(Reverse(TimerQueueEntry {
+                id,
+                expiry: deadline,
+                yielder: yielder_handle,
+                result,
+            }));
+        }
+        TimerExpiry {
+            timer: self.clone(),
+            id,
+        }
+    }
+
+    /// Removes the entry identified by `id`, if it has not expired yet.
+    fn remove_entry(&mut self, id: u64) {
+        self.heap.retain(|Reverse(entry)| entry.id != id);
+    }
+
+    fn alloc_entry_id(&mut self) -> u64 {
+        let id: u64 = self.next_id;
+        self.next_id += 1;
+        id
+    }
 }
 
 //======================================================================================================================
@@ -129,11 +179,18 @@ impl<F: ?Sized> UtilityMethods for F where F: Future + Unpin + FusedFuture {}
 // Trait Implementations
 //==============================================================================
 
+impl Drop for TimerExpiry {
+    fn drop(&mut self) {
+        self.timer.remove_entry(self.id);
+    }
+}
+
 impl Default for SharedTimer {
     fn default() -> Self {
         Self(SharedObject::<Timer>::new(Timer {
             now: Instant::now(),
             heap: BinaryHeap::new(),
+            next_id: 0,
         }))
     }
 }
@@ -240,4 +297,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_expire_at_removed_on_drop() -> Result<()> {
+        let now = Instant::now();
+        let mut timer: SharedTimer = SharedTimer::new(now);
+        let yielder: Yielder = Yielder::new();
+
+        let expiry = timer.expire_at(now + Duration::from_secs(1), yielder.get_handle());
+        crate::ensure_eq!(timer.heap.len(), 1);
+
+        // The coroutine completed before its deadline, so the timer no longer holds the deadline.
+        drop(expiry);
+        crate::ensure_eq!(timer.heap.len(), 0);
+
+        Ok(())
+    }
 }
@@ -36,7 +36,9 @@ pub enum demi_opcode_t {
 #[repr(C, packed)]
 #[derive(Copy, Clone)]
 pub struct demi_accept_result_t {
+    /// Queue descriptor of the accepted connection.
     pub qd: i32,
+    /// Remote address of the accepted connection.
     pub addr: SockAddr,
 }
 
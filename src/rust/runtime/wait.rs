@@ -0,0 +1,73 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//======================================================================================================================
+// Imports
+//======================================================================================================================
+
+use crate::runtime::{
+    fail::Fail,
+    types::{
+        demi_opcode_t,
+        demi_qresult_t,
+    },
+};
+use ::std::sync::{
+    atomic::{
+        AtomicBool,
+        Ordering,
+    },
+    Arc,
+};
+
+//======================================================================================================================
+// Structures
+//======================================================================================================================
+
+/// Why a wait returned.
+pub enum WaitOutcome {
+    /// The operation completed, successfully or with an error other than a cancellation.
+    Completed(demi_qresult_t),
+    /// The timeout expired before the operation completed.
+    TimedOut,
+    /// The wait was interrupted through a [WaitInterrupter] before the operation completed.
+    Interrupted,
+    /// The operation was cancelled.
+    Cancelled,
+}
+
+/// Handle that interrupts an ongoing or upcoming wait. Interrupts may be raised from any thread, and each interrupt
+/// stops a single wait.
+#[derive(Clone, Default)]
+pub struct WaitInterrupter(Arc<AtomicBool>);
+
+//======================================================================================================================
+// Associated Functions
+//======================================================================================================================
+
+impl WaitOutcome {
+    /// Classifies the result of a wait. Failures other than an expired timeout or an interrupt are returned as is.
+    pub fn from_wait(result: Result<demi_qresult_t, Fail>) -> Result<Self, Fail> {
+        match result {
+            Ok(qr) if qr.qr_opcode == demi_opcode_t::DEMI_OPC_FAILED && qr.qr_ret == libc::ECANCELED as i64 => {
+                Ok(WaitOutcome::Cancelled)
+            },
+            Ok(qr) => Ok(WaitOutcome::Completed(qr)),
+            Err(e) if e.errno == libc::ETIMEDOUT => Ok(WaitOutcome::TimedOut),
+            Err(e) if e.errno == libc::EINTR => Ok(WaitOutcome::Interrupted),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl WaitInterrupter {
+    /// Interrupts the ongoing wait, or the next one if no wait is ongoing.
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Consumes a pending interrupt, if any, and returns whether there was one.
+    pub fn take(&self) -> bool {
+        self.0.swap(false, Ordering::AcqRel)
+    }
+}
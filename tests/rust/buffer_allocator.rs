@@ -0,0 +1,137 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//==============================================================================
+// Imports
+//==============================================================================
+
+use ::anyhow::Result;
+use ::demikernel::runtime::memory::{
+    install_buffer_allocator,
+    BufferAllocator,
+    DemiBuffer,
+    HeapBufferAllocator,
+};
+use ::std::{
+    alloc::Layout,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+    },
+};
+
+//==============================================================================
+// Structures
+//==============================================================================
+
+/// Allocator that records the size of every allocation and release while it is tracking, and takes memory from the
+/// heap.
+#[derive(Clone, Default)]
+struct TrackingAllocator {
+    tracking: Arc<AtomicBool>,
+    allocations: Arc<Mutex<Vec<usize>>>,
+    releases: Arc<Mutex<Vec<usize>>>,
+}
+
+/// Installed [TrackingAllocator]. An allocator cannot be uninstalled, so dropping the guard stops the tracking instead,
+/// which leaves a plain heap allocator behind even if the test fails.
+struct TrackingGuard(TrackingAllocator);
+
+//==============================================================================
+// Associated Functions
+//==============================================================================
+
+impl TrackingAllocator {
+    /// Installs a tracking allocator as the buffer allocator of the process.
+    fn install() -> Result<TrackingGuard> {
+        let tracker: TrackingAllocator = TrackingAllocator::default();
+        tracker.tracking.store(true, Ordering::SeqCst);
+        if let Err(e) = install_buffer_allocator(Box::new(tracker.clone())) {
+            anyhow::bail!("failed to install buffer allocator: {:?}", e);
+        }
+        Ok(TrackingGuard(tracker))
+    }
+
+    fn is_tracking(&self) -> bool {
+        self.tracking.load(Ordering::SeqCst)
+    }
+}
+
+//==============================================================================
+// Trait Implementations
+//==============================================================================
+
+impl BufferAllocator for TrackingAllocator {
+    unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+        if self.is_tracking() {
+            self.allocations.lock().unwrap().push(layout.size());
+        }
+        HeapBufferAllocator.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, allocation: *mut u8, layout: Layout) {
+        if self.is_tracking() {
+            self.releases.lock().unwrap().push(layout.size());
+        }
+        HeapBufferAllocator.deallocate(allocation, layout)
+    }
+}
+
+impl Drop for TrackingGuard {
+    fn drop(&mut self) {
+        self.0.tracking.store(false, Ordering::SeqCst);
+    }
+}
+
+//==============================================================================
+// test_buffer_allocator()
+//==============================================================================
+
+/// Installs a tracking allocator and checks that buffers of several sizes are allocated from, and released to, it.
+/// This runs in its own process because the allocator can only be installed before the first buffer is allocated.
+#[test]
+fn test_buffer_allocator() -> Result<()> {
+    let guard: TrackingGuard = TrackingAllocator::install()?;
+    let tracker: &TrackingAllocator = &guard.0;
+
+    let sizes: [usize; 3] = [100, 50, 1500];
+    let buffers: Vec<DemiBuffer> = vec![
+        DemiBuffer::new(sizes[0] as u16),
+        DemiBuffer::from_slice(&[0x5a; 50][..])?,
+        DemiBuffer::new(sizes[2] as u16),
+    ];
+
+    // Every allocation holds the requested data behind a header of the same size.
+    let allocations: Vec<usize> = tracker.allocations.lock().unwrap().clone();
+    if allocations.len() != sizes.len() {
+        anyhow::bail!("expected {} allocations, got {:?}", sizes.len(), allocations);
+    }
+    let header_size: usize = allocations[0] - sizes[0];
+    for (allocation, size) in allocations.iter().zip(sizes.iter()) {
+        if *allocation != header_size + *size {
+            anyhow::bail!("allocation of {} bytes for a {}-byte buffer", allocation, size);
+        }
+    }
+
+    // Buffers go back to the allocator that they came from.
+    drop(buffers);
+    let mut releases: Vec<usize> = tracker.releases.lock().unwrap().clone();
+    let mut expected: Vec<usize> = allocations;
+    releases.sort();
+    expected.sort();
+    if releases != expected {
+        anyhow::bail!("expected releases of {:?}, got {:?}", expected, releases);
+    }
+
+    // The allocator cannot be replaced once in use.
+    match install_buffer_allocator(Box::new(HeapBufferAllocator)) {
+        Err(e) if e.errno == libc::EBUSY => {},
+        _ => anyhow::bail!("installing a second buffer allocator should fail with EBUSY"),
+    };
+
+    Ok(())
+}
@@ -70,6 +70,7 @@ impl DummyLibOS {
         let runtime: SharedDemiRuntime = SharedDemiRuntime::default();
         let arp_config: ArpConfig = ArpConfig::new(
             Some(Duration::from_secs(600)),
+            None,
             Some(Duration::from_secs(1)),
             Some(2),
             Some(arp.clone()),
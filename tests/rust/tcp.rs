@@ -515,6 +515,34 @@ fn tcp_bad_bind() -> Result<()> {
     Ok(())
 }
 
+/// Tests if `bind()` enforces the privileged port floor.
+#[test]
+fn tcp_bind_privileged_port_floor() -> Result<()> {
+    let (tx, rx): (Sender<DemiBuffer>, Receiver<DemiBuffer>) = crossbeam_channel::unbounded();
+    let mut libos: DummyLibOS = match DummyLibOS::new(ALICE_MAC, ALICE_IPV4, tx, rx, arp()) {
+        Ok(libos) => libos,
+        Err(e) => anyhow::bail!("Could not create inetstack: {:?}", e),
+    };
+    libos.set_privileged_port_floor(1024);
+
+    // Can't bind below the floor.
+    let sockqd: QDesc = safe_socket(&mut libos)?;
+    match libos.bind(sockqd, SocketAddr::new(ALICE_IP, 80)) {
+        Err(e) if e.errno == libc::EACCES => (),
+        _ => anyhow::bail!("bind() below the privileged port floor should fail with EACCES"),
+    };
+
+    // Can bind above the floor.
+    safe_bind(&mut libos, sockqd, SocketAddr::new(ALICE_IP, 8080))?;
+
+    // Can bind below the floor if the socket is allowed to.
+    let sockqd2: QDesc = safe_socket(&mut libos)?;
+    libos.set_allow_privileged_bind(sockqd2, true)?;
+    safe_bind(&mut libos, sockqd2, SocketAddr::new(ALICE_IP, 80))?;
+
+    Ok(())
+}
+
 //======================================================================================================================
 // Bad Listen
 //======================================================================================================================
@@ -619,6 +647,53 @@ fn tcp_bad_accept() -> Result<()> {
     Ok(())
 }
 
+/// Tests `accept()` on sockets that are not listening.
+#[test]
+fn tcp_accept_not_listening() -> Result<()> {
+    let (tx, rx): (Sender<DemiBuffer>, Receiver<DemiBuffer>) = crossbeam_channel::unbounded();
+    let mut libos: DummyLibOS = match DummyLibOS::new(ALICE_MAC, ALICE_IPV4, tx, rx, arp()) {
+        Ok(libos) => libos,
+        Err(e) => anyhow::bail!("Could not create inetstack: {:?}", e),
+    };
+
+    // Unbound socket.
+    let sockqd: QDesc = safe_socket(&mut libos)?;
+    match libos.accept(sockqd) {
+        Err(e) if e.errno == libc::EINVAL => (),
+        _ => anyhow::bail!("accept() on an unbound socket should fail with EINVAL"),
+    };
+
+    // Bound socket that is not listening.
+    let local: SocketAddr = SocketAddr::new(ALICE_IP, PORT_BASE);
+    safe_bind(&mut libos, sockqd, local)?;
+    match libos.accept(sockqd) {
+        Err(e) if e.errno == libc::EINVAL => (),
+        _ => anyhow::bail!("accept() on a socket that is not listening should fail with EINVAL"),
+    };
+
+    // Closed socket.
+    safe_close_active(&mut libos, sockqd)?;
+    match libos.accept(sockqd) {
+        Err(e) if e.errno == libc::EBADF => (),
+        _ => anyhow::bail!("accept() on a closed socket should fail with EBADF"),
+    };
+
+    // Connecting socket.
+    let remote: SocketAddr = SocketAddr::new(BOB_IP, PORT_BASE);
+    let sockqd: QDesc = safe_socket(&mut libos)?;
+    let qt: QToken = safe_connect(&mut libos, sockqd, remote)?;
+    match libos.accept(sockqd) {
+        Err(e) if e.errno == libc::EINVAL => (),
+        _ => anyhow::bail!("accept() on a connecting socket should fail with EINVAL"),
+    };
+    match libos.wait(qt, Some(Duration::from_millis(10))) {
+        Err(e) if e.errno == libc::ETIMEDOUT => (),
+        _ => anyhow::bail!("connect() should have timed out"),
+    }
+
+    Ok(())
+}
+
 //======================================================================================================================
 // Bad Connect
 //======================================================================================================================
@@ -1162,10 +1237,8 @@ fn safe_wait(libos: &mut DummyLibOS, qt: QToken) -> Result<(QDesc, OperationResu
 fn safe_close_passive(libos: &mut DummyLibOS, sockqd: QDesc) -> Result<()> {
     let qt = libos.async_close(sockqd)?;
     match safe_wait(libos, qt)? {
-        (_, OperationResult::Failed(_)) => Ok(()),
-        _ => {
-            anyhow::bail!("close() on listening socket should have failed (this is a known bug)")
-        },
+        (_, OperationResult::Close) => Ok(()),
+        _ => anyhow::bail!("close() on listening socket has failed"),
     }
 }
 